@@ -0,0 +1,212 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+use dnp3::app::measurement::*;
+use dnp3::custom::*;
+use dnp3::decode::DecodeDirection;
+use dnp3::link::*;
+use dnp3::outstation::database::*;
+use dnp3::outstation::*;
+use dnp3::replay::Decoder;
+
+const OUTSTATION_ADDRESS: u16 = 1024;
+const MASTER_ADDRESS: u16 = 1;
+// maximum amount of user data in a link frame, including the transport header
+const MAX_USER_DATA: usize = 250;
+const NUM_ANALOGS: u16 = 1000;
+
+// the CRC is calculated bit by bit so that the benchmarks don't depend on the implementation
+// being measured
+fn crc(data: &[u8]) -> [u8; 2] {
+    let mut crc: u16 = 0;
+    for byte in data {
+        crc ^= *byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xA6BC
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    (!crc).to_le_bytes()
+}
+
+/// unconfirmed user data frame from the master to the outstation
+fn frame(user_data: &[u8]) -> Vec<u8> {
+    assert!(user_data.len() <= MAX_USER_DATA);
+    let dest = OUTSTATION_ADDRESS.to_le_bytes();
+    let src = MASTER_ADDRESS.to_le_bytes();
+    let header = [
+        0x05,
+        0x64,
+        (user_data.len() + 5) as u8,
+        0xC4,
+        dest[0],
+        dest[1],
+        src[0],
+        src[1],
+    ];
+
+    let mut frame = header.to_vec();
+    frame.extend_from_slice(&crc(&header));
+    for block in user_data.chunks(16) {
+        frame.extend_from_slice(block);
+        frame.extend_from_slice(&crc(block));
+    }
+    frame
+}
+
+/// split an application fragment into transport segments, each in its own link frame
+fn frames(fragment: &[u8]) -> Vec<u8> {
+    let chunks: Vec<&[u8]> = fragment.chunks(MAX_USER_DATA - 1).collect();
+    let mut stream = Vec::new();
+    for (seq, chunk) in chunks.iter().enumerate() {
+        let fir = if seq == 0 { 0x40 } else { 0 };
+        let fin = if seq == chunks.len() - 1 { 0x80 } else { 0 };
+        let mut user_data = vec![fir | fin | (seq as u8 & 0x3F)];
+        user_data.extend_from_slice(chunk);
+        stream.extend(frame(&user_data));
+    }
+    stream
+}
+
+/// READ class 0 request with the specified application sequence number
+fn integrity_poll(seq: u8) -> Vec<u8> {
+    frames(&[0xC0 | (seq & 0x0F), 0x01, 60, 1, 0x06])
+}
+
+fn bench_link_parsing(c: &mut Criterion) {
+    // many small single-frame fragments, e.g. polls and confirms
+    let stream: Vec<u8> = (0..1000u16).flat_map(|x| integrity_poll(x as u8)).collect();
+
+    let mut group = c.benchmark_group("link parsing");
+    group.throughput(Throughput::Bytes(stream.len() as u64));
+    group.bench_function("1000 small frames", |b| {
+        b.iter_batched_ref(
+            || Decoder::new(DecodeDirection::Rx),
+            |decoder| assert_eq!(decoder.decode(&stream).len(), 1000),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_transport_reassembly(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transport reassembly");
+    for size in [2048usize, 65535].iter() {
+        let fragment: Vec<u8> = (0..*size).map(|x| x as u8).collect();
+        let stream = frames(&fragment);
+        group.throughput(Throughput::Bytes(stream.len() as u64));
+        group.bench_function(format!("{} byte fragment", size), |b| {
+            b.iter_batched_ref(
+                || Decoder::new(DecodeDirection::Rx),
+                |decoder| assert_eq!(decoder.decode(&stream).len(), 1),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+struct Outstation {
+    runtime: tokio::runtime::Runtime,
+    stream: DuplexStream,
+    // keeps the outstation alive
+    _handle: OutstationHandle,
+    seq: u8,
+    buffer: Vec<u8>,
+}
+
+impl Outstation {
+    fn create() -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let (stream, phys) = tokio::io::duplex(64 * 1024);
+
+        let mut config = OutstationConfig::new(
+            EndpointAddress::from(OUTSTATION_ADDRESS).unwrap(),
+            EndpointAddress::from(MASTER_ADDRESS).unwrap(),
+        );
+        // the whole class 0 response fits in a single fragment
+        config.solicited_buffer_size = BufferSize::new(BufferSize::MAX).unwrap();
+        config.features.unsolicited = Feature::Disabled;
+        config.keep_alive_timeout = None;
+
+        let handle = runtime.block_on(async {
+            spawn_outstation_custom(
+                LinkErrorMode::Close,
+                Box::new(phys),
+                config,
+                EventBufferConfig::all_types(100),
+                DefaultOutstationApplication::create(),
+                DefaultOutstationInformation::create(),
+                DefaultControlHandler::create(),
+            )
+        });
+
+        handle.transaction(|db| {
+            for i in 0..NUM_ANALOGS {
+                db.add(
+                    i,
+                    Some(EventClass::Class1),
+                    AnalogConfig::new(
+                        StaticAnalogVariation::Group30Var5,
+                        EventAnalogVariation::Group32Var7,
+                        0.0,
+                    ),
+                );
+                db.update(
+                    i,
+                    &Analog::new(i as f64, Flags::ONLINE, Time::synchronized(0)),
+                    UpdateOptions::initialize(),
+                );
+            }
+        });
+
+        Self {
+            runtime,
+            stream,
+            _handle: handle,
+            seq: 0,
+            buffer: vec![0; 64 * 1024],
+        }
+    }
+
+    fn poll(&mut self) -> usize {
+        let request = integrity_poll(self.seq);
+        self.seq = self.seq.wrapping_add(1);
+
+        let stream = &mut self.stream;
+        let buffer = &mut self.buffer;
+        self.runtime.block_on(async {
+            stream.write_all(&request).await.unwrap();
+            let mut decoder = Decoder::new(DecodeDirection::Rx);
+            loop {
+                let count = stream.read(buffer).await.unwrap();
+                assert_ne!(count, 0);
+                if let Some(fragment) = decoder.decode(&buffer[..count]).pop() {
+                    return fragment.len();
+                }
+            }
+        })
+    }
+}
+
+fn bench_response_writing(c: &mut Criterion) {
+    let mut outstation = Outstation::create();
+    c.bench_function("integrity response with 1000 analogs", |b| {
+        b.iter(|| outstation.poll())
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_link_parsing,
+    bench_transport_reassembly,
+    bench_response_writing
+);
+criterion_main!(benches);
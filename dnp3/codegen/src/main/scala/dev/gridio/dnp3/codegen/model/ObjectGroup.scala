@@ -31,6 +31,7 @@ object ObjectGroup {
     Group60,
     //Group70,
     Group80,
+    Group90,
     Group110,
     Group111,
     /*
@@ -83,6 +84,7 @@ object GroupType {
   object ClassData extends GroupType
   object FileControl extends GroupType
   object InternalIndications extends GroupType
+  object ApplicationIdentifier extends GroupType
   object VirtualTerminalOutput extends GroupType
 }
 
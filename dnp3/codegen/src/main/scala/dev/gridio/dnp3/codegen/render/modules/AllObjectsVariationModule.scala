@@ -1,7 +1,7 @@
 package dev.gridio.dnp3.codegen.render.modules
 
 import dev.gridio.dnp3.codegen.model._
-import dev.gridio.dnp3.codegen.model.groups.{Group110, Group111}
+import dev.gridio.dnp3.codegen.model.groups.{Group110, Group111, Group90}
 import dev.gridio.dnp3.codegen.render._
 
 object AllObjectsVariationModule extends Module {
@@ -47,7 +47,7 @@ object AllObjectsVariationModule extends Module {
       case v : ClassData => v
       case v : AnyVariation if v.parent.groupType != GroupType.Command => v
       case v : FixedSize if v.parent.groupType.isStatic || v.parent.groupType.isEvent => v
-      case v if v.parent == Group110 || v.parent == Group111 => v
+      case v if v.parent == Group90 || v.parent == Group110 || v.parent == Group111 => v
     }
   }
 
@@ -45,7 +45,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         SerialSettings::default(),
         get_outstation_config(),
         // event buffer space for 100 analog events
-        EventBufferConfig::new(0, 0, 0, 0, 0, 100, 0, 0),
+        EventBufferConfig::new(0, 0, 0, 0, 0, 100, 0, 0, 0),
         // customizable trait that controls outstation behavior
         DefaultOutstationApplication::create(),
         // customizable trait to receive events about what the outstation is doing
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::app::parse::parser::ParseAnomalies;
+use crate::app::Clock;
+
+/// Type of deviation from the specification observed in data received from the peer
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AnomalyKind {
+    /// padding bytes after the last object header, ignored in [ParseMode::Lenient](crate::app::ParseMode::Lenient)
+    TrailingPadding,
+    /// range whose stop index is less than its start index, treated as empty in
+    /// [ParseMode::Lenient](crate::app::ParseMode::Lenient)
+    InvertedRange,
+    /// zero-length octet string (g110v0/g111v0) outside of a READ request, reported as empty in
+    /// [ParseMode::Lenient](crate::app::ParseMode::Lenient)
+    ZeroLengthOctetString,
+    /// response or confirmation whose sequence number doesn't match the expected sequence number
+    UnexpectedSequence,
+    /// solicited response that repeats the sequence number of the last one processed, e.g. a
+    /// response retransmitted by the outstation
+    DuplicateResponse,
+    /// solicited response whose FIR, FIN, and CON bits are inconsistent with the responses
+    /// received so far, failing the task
+    UnexpectedFirFin,
+    /// solicited response or confirmation received when none was expected
+    UnexpectedFragment,
+    /// reserved bits of IIN2 set in a response
+    ReservedIinBits,
+}
+
+/// Number of times an [AnomalyKind] was observed on a channel, along with its last occurrence
+#[derive(Clone, Debug, PartialEq)]
+pub struct Anomaly {
+    /// type of the anomaly
+    pub kind: AnomalyKind,
+    /// number of occurrences since the channel was created or its anomalies were cleared
+    pub count: u64,
+    /// time of the last occurrence
+    pub last_seen: SystemTime,
+    /// description of the last occurrence
+    pub last_detail: String,
+}
+
+/// anomalies observed on a channel, shared between its sessions and its handle
+#[derive(Debug, Default)]
+pub(crate) struct AnomalyLog {
+    anomalies: Mutex<BTreeMap<AnomalyKind, Anomaly>>,
+    // time source of the occurrences
+    clock: Clock,
+}
+
+impl AnomalyLog {
+    pub(crate) fn new(clock: Clock) -> Self {
+        Self {
+            anomalies: Mutex::default(),
+            clock,
+        }
+    }
+
+    /// log the anomaly as a warning and count it
+    pub(crate) fn record(&self, kind: AnomalyKind, detail: std::fmt::Arguments) {
+        tracing::warn!("{}", detail);
+        self.add(kind, 1, detail);
+    }
+
+    /// count the anomalies tolerated by the parser, which are already logged by the parser
+    pub(crate) fn record_parse(&self, anomalies: ParseAnomalies) {
+        if anomalies.trailing_padding > 0 {
+            self.add(
+                AnomalyKind::TrailingPadding,
+                1,
+                format_args!(
+                    "ignored {} trailing padding byte(s)",
+                    anomalies.trailing_padding
+                ),
+            );
+        }
+        if anomalies.inverted_ranges > 0 {
+            self.add(
+                AnomalyKind::InvertedRange,
+                anomalies.inverted_ranges as u64,
+                format_args!("treated {} range(s) as empty", anomalies.inverted_ranges),
+            );
+        }
+        if anomalies.zero_length_octet_strings > 0 {
+            self.add(
+                AnomalyKind::ZeroLengthOctetString,
+                anomalies.zero_length_octet_strings as u64,
+                format_args!(
+                    "reported {} zero-length octet string(s) as empty",
+                    anomalies.zero_length_octet_strings
+                ),
+            );
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<Anomaly> {
+        self.anomalies.lock().unwrap().values().cloned().collect()
+    }
+
+    pub(crate) fn clear(&self) {
+        self.anomalies.lock().unwrap().clear();
+    }
+
+    fn add(&self, kind: AnomalyKind, count: u64, detail: std::fmt::Arguments) {
+        let now = self.clock.system_time();
+        let mut anomalies = self.anomalies.lock().unwrap();
+        match anomalies.get_mut(&kind) {
+            Some(x) => {
+                x.count = x.count.saturating_add(count);
+                x.last_seen = now;
+                // reuse the allocation of the previous detail since a misbehaving peer
+                // usually repeats the same anomaly in every fragment
+                x.last_detail.clear();
+                let _ = x.last_detail.write_fmt(detail);
+            }
+            None => {
+                anomalies.insert(
+                    kind,
+                    Anomaly {
+                        kind,
+                        count,
+                        last_seen: now,
+                        last_detail: detail.to_string(),
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn counts_anomalies_per_kind() {
+        let log = AnomalyLog::default();
+        log.record(AnomalyKind::UnexpectedSequence, format_args!("seq: {}", 3));
+        log.record(AnomalyKind::UnexpectedSequence, format_args!("seq: {}", 4));
+        log.record_parse(ParseAnomalies {
+            trailing_padding: 2,
+            inverted_ranges: 0,
+            zero_length_octet_strings: 5,
+        });
+
+        let anomalies = log.snapshot();
+        assert_eq!(anomalies.len(), 3);
+        assert_eq!(anomalies[0].kind, AnomalyKind::TrailingPadding);
+        assert_eq!(anomalies[0].count, 1);
+        assert_eq!(anomalies[1].kind, AnomalyKind::ZeroLengthOctetString);
+        assert_eq!(anomalies[1].count, 5);
+        assert_eq!(anomalies[2].kind, AnomalyKind::UnexpectedSequence);
+        assert_eq!(anomalies[2].count, 2);
+        assert_eq!(anomalies[2].last_detail, "seq: 4");
+
+        log.clear();
+        assert!(log.snapshot().is_empty());
+    }
+}
@@ -14,6 +14,7 @@ use crate::util::cursor::{WriteCursor, WriteError};
 
 /// Application object header types
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum QualifierCode {
     ///  8-bit start stop (value == 0x00)
     Range8,
@@ -71,6 +72,7 @@ impl QualifierCode {
 
 /// Application layer function code enumeration
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FunctionCode {
     ///  Master sends this to an outstation to confirm the receipt of an Application Layer fragment (value == 0)
     Confirm,
@@ -134,10 +136,16 @@ pub enum FunctionCode {
     AuthenticateFile,
     ///  Outstation shall abort a file transfer operation (value == 30)
     AbortFile,
+    ///  Master or outstation shall process the secure authentication objects in the request (value == 32)
+    AuthRequest,
+    ///  Same as AuthRequest but the receiver shall not send a response (value == 33)
+    AuthRequestNoAck,
     ///  Master shall interpret this fragment as an Application Layer response to an ApplicationLayer request (value == 129)
     Response,
     ///  Master shall interpret this fragment as an unsolicited response that was not prompted by an explicit request (value == 130)
     UnsolicitedResponse,
+    ///  Master shall interpret this fragment as a secure authentication response (value == 131)
+    AuthResponse,
 }
 
 impl FunctionCode {
@@ -176,8 +184,11 @@ impl FunctionCode {
             28 => Some(FunctionCode::GetFileInfo),
             29 => Some(FunctionCode::AuthenticateFile),
             30 => Some(FunctionCode::AbortFile),
+            32 => Some(FunctionCode::AuthRequest),
+            33 => Some(FunctionCode::AuthRequestNoAck),
             129 => Some(FunctionCode::Response),
             130 => Some(FunctionCode::UnsolicitedResponse),
+            131 => Some(FunctionCode::AuthResponse),
             _ => None,
         }
     }
@@ -216,8 +227,11 @@ impl FunctionCode {
             FunctionCode::GetFileInfo => 28,
             FunctionCode::AuthenticateFile => 29,
             FunctionCode::AbortFile => 30,
+            FunctionCode::AuthRequest => 32,
+            FunctionCode::AuthRequestNoAck => 33,
             FunctionCode::Response => 129,
             FunctionCode::UnsolicitedResponse => 130,
+            FunctionCode::AuthResponse => 131,
         }
     }
     
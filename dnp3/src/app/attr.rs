@@ -0,0 +1,351 @@
+use crate::app::format::write::HeaderWriter;
+use crate::app::variations::Variation;
+use crate::app::{Bytes, ObjectParseError, Timestamp};
+use crate::util::cursor::{ReadCursor, WriteCursor, WriteError};
+
+/// Data type of a device attribute (g0) value as encoded on the wire
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AttrDataType {
+    /// visible string (VSTR)
+    VisibleString,
+    /// unsigned integer of 1 to 4 bytes (UINT)
+    UnsignedInt,
+    /// signed integer of 1 to 4 bytes (INT)
+    SignedInt,
+    /// single or double-precision floating point (FLT)
+    FloatingPoint,
+    /// octet string (OSTR)
+    OctetString,
+    /// bit string (BSTR)
+    BitString,
+    /// 48-bit DNP3 time (DNP3TIME)
+    Time,
+    /// list of attribute variations and their properties (U8BS8LIST)
+    AttrList,
+}
+
+/// Errors that occur when parsing a device attribute
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AttrParseError {
+    /// data type code that is unknown or not supported by the library
+    UnknownDataType(u8),
+    /// length that is not allowed for the data type
+    BadLength(AttrDataType, u8),
+    /// visible string that is not valid UTF-8
+    BadVisibleString,
+    /// header that doesn't contain exactly one attribute set
+    BadCount(usize),
+}
+
+/// Properties of an attribute in a list of attribute variations (g0v255)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AttrProp {
+    /// the attribute may be written by the master
+    pub is_writable: bool,
+}
+
+/// Entry in a list of attribute variations (g0v255)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AttrItem {
+    /// variation of the attribute
+    pub variation: u8,
+    /// properties of the attribute
+    pub properties: AttrProp,
+}
+
+/// List of the attribute variations supported in a set (g0v255)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AttrList<'a> {
+    data: &'a [u8],
+}
+
+/// Iterator over the entries of an [AttrList]
+#[derive(Copy, Clone, Debug)]
+pub struct AttrListIter<'a> {
+    cursor: ReadCursor<'a>,
+}
+
+/// Value of a device attribute
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AttrValue<'a> {
+    /// visible string (VSTR)
+    VisibleString(&'a str),
+    /// unsigned integer (UINT)
+    UnsignedInt(u32),
+    /// signed integer (INT)
+    SignedInt(i32),
+    /// floating point value (FLT), widened to double precision
+    FloatingPoint(f64),
+    /// octet string (OSTR)
+    OctetString(&'a [u8]),
+    /// bit string (BSTR)
+    BitString(&'a [u8]),
+    /// DNP3 time (DNP3TIME)
+    Time(Timestamp),
+    /// list of attribute variations (U8BS8LIST), only used with variation 255
+    AttrList(AttrList<'a>),
+}
+
+/// Device attribute (g0) along with the set and variation that identify it
+///
+/// Attributes are encoded the same way in responses from the outstation and in write requests
+/// from the master.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Attribute<'a> {
+    /// attribute set, 0 for the standard attributes defined in the specification
+    pub set: u16,
+    /// variation of the attribute within the set
+    pub variation: u8,
+    /// value of the attribute
+    pub value: AttrValue<'a>,
+}
+
+impl AttrDataType {
+    pub(crate) fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(AttrDataType::VisibleString),
+            2 => Some(AttrDataType::UnsignedInt),
+            3 => Some(AttrDataType::SignedInt),
+            4 => Some(AttrDataType::FloatingPoint),
+            5 => Some(AttrDataType::OctetString),
+            6 => Some(AttrDataType::BitString),
+            7 => Some(AttrDataType::Time),
+            254 => Some(AttrDataType::AttrList),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_u8(self) -> u8 {
+        match self {
+            AttrDataType::VisibleString => 1,
+            AttrDataType::UnsignedInt => 2,
+            AttrDataType::SignedInt => 3,
+            AttrDataType::FloatingPoint => 4,
+            AttrDataType::OctetString => 5,
+            AttrDataType::BitString => 6,
+            AttrDataType::Time => 7,
+            AttrDataType::AttrList => 254,
+        }
+    }
+}
+
+impl AttrProp {
+    const WRITABLE: u8 = 0x01;
+
+    fn from_u8(value: u8) -> Self {
+        Self {
+            is_writable: value & Self::WRITABLE != 0,
+        }
+    }
+}
+
+impl<'a> AttrList<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Iterate over the entries of the list
+    pub fn iter(&self) -> AttrListIter<'a> {
+        AttrListIter {
+            cursor: ReadCursor::new(self.data),
+        }
+    }
+}
+
+impl Iterator for AttrListIter<'_> {
+    type Item = AttrItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let variation = self.cursor.read_u8().ok()?;
+        let properties = AttrProp::from_u8(self.cursor.read_u8().ok()?);
+        Some(AttrItem {
+            variation,
+            properties,
+        })
+    }
+}
+
+impl<'a> AttrValue<'a> {
+    /// Data type used to encode the value
+    pub fn data_type(&self) -> AttrDataType {
+        match self {
+            AttrValue::VisibleString(_) => AttrDataType::VisibleString,
+            AttrValue::UnsignedInt(_) => AttrDataType::UnsignedInt,
+            AttrValue::SignedInt(_) => AttrDataType::SignedInt,
+            AttrValue::FloatingPoint(_) => AttrDataType::FloatingPoint,
+            AttrValue::OctetString(_) => AttrDataType::OctetString,
+            AttrValue::BitString(_) => AttrDataType::BitString,
+            AttrValue::Time(_) => AttrDataType::Time,
+            AttrValue::AttrList(_) => AttrDataType::AttrList,
+        }
+    }
+
+    pub(crate) fn parse(cursor: &mut ReadCursor<'a>) -> Result<Self, ObjectParseError> {
+        let code = cursor.read_u8()?;
+        let data_type = AttrDataType::from_u8(code).ok_or(AttrParseError::UnknownDataType(code))?;
+        let length = cursor.read_u8()?;
+        let bad_length = AttrParseError::BadLength(data_type, length);
+        let data = cursor.read_bytes(length as usize)?;
+
+        let value = match data_type {
+            AttrDataType::VisibleString => AttrValue::VisibleString(
+                std::str::from_utf8(data).map_err(|_| AttrParseError::BadVisibleString)?,
+            ),
+            AttrDataType::UnsignedInt => match data.len() {
+                1..=4 => AttrValue::UnsignedInt(
+                    data.iter().rev().fold(0, |acc, x| (acc << 8) | *x as u32),
+                ),
+                _ => return Err(bad_length.into()),
+            },
+            AttrDataType::SignedInt => match data.len() {
+                1..=4 => {
+                    // sign extend from the most significant byte
+                    let shift = 32 - 8 * data.len() as u32;
+                    let raw = data.iter().rev().fold(0, |acc, x| (acc << 8) | *x as u32);
+                    AttrValue::SignedInt(((raw << shift) as i32) >> shift)
+                }
+                _ => return Err(bad_length.into()),
+            },
+            AttrDataType::FloatingPoint => {
+                let mut data = ReadCursor::new(data);
+                match length {
+                    4 => AttrValue::FloatingPoint(data.read_f32_le()? as f64),
+                    8 => AttrValue::FloatingPoint(data.read_f64_le()?),
+                    _ => return Err(bad_length.into()),
+                }
+            }
+            AttrDataType::OctetString => AttrValue::OctetString(data),
+            AttrDataType::BitString => AttrValue::BitString(data),
+            AttrDataType::Time => match length {
+                6 => AttrValue::Time(Timestamp::new(ReadCursor::new(data).read_u48_le()?)),
+                _ => return Err(bad_length.into()),
+            },
+            AttrDataType::AttrList => {
+                if length % 2 != 0 {
+                    return Err(bad_length.into());
+                }
+                AttrValue::AttrList(AttrList { data })
+            }
+        };
+
+        Ok(value)
+    }
+
+    /// write the data type, length, and value
+    ///
+    /// integers are always written with 4 bytes and floating point values with 8 bytes
+    pub(crate) fn write(&self, cursor: &mut WriteCursor) -> Result<(), WriteError> {
+        cursor.write_u8(self.data_type().as_u8())?;
+        match self {
+            AttrValue::VisibleString(x) => Self::write_bytes(cursor, x.as_bytes()),
+            AttrValue::UnsignedInt(x) => {
+                cursor.write_u8(4)?;
+                cursor.write_u32_le(*x)
+            }
+            AttrValue::SignedInt(x) => {
+                cursor.write_u8(4)?;
+                cursor.write_i32_le(*x)
+            }
+            AttrValue::FloatingPoint(x) => {
+                cursor.write_u8(8)?;
+                cursor.write_f64_le(*x)
+            }
+            AttrValue::OctetString(x) => Self::write_bytes(cursor, x),
+            AttrValue::BitString(x) => Self::write_bytes(cursor, x),
+            AttrValue::Time(x) => {
+                cursor.write_u8(6)?;
+                cursor.write_u48_le(x.raw_value())
+            }
+            AttrValue::AttrList(x) => Self::write_bytes(cursor, x.data),
+        }
+    }
+
+    fn write_bytes(cursor: &mut WriteCursor, bytes: &[u8]) -> Result<(), WriteError> {
+        // the length is a single byte, values that are too long can't be encoded
+        if bytes.len() > u8::MAX as usize {
+            return Err(WriteError);
+        }
+        cursor.write_u8(bytes.len() as u8)?;
+        cursor.write(bytes)
+    }
+}
+
+impl<'a> Attribute<'a> {
+    /// Construct an attribute from its fields
+    pub fn new(set: u16, variation: u8, value: AttrValue<'a>) -> Self {
+        Self {
+            set,
+            variation,
+            value,
+        }
+    }
+
+    /// write the attribute as an object header with a start-stop range containing only its set
+    ///
+    /// nothing is written if the attribute doesn't fit
+    pub(crate) fn write(&self, cursor: &mut WriteCursor) -> Result<(), WriteError> {
+        let variation = match self.variation {
+            254 => Variation::Group0Var254,
+            255 => Variation::Group0Var255,
+            x => Variation::Group0(x),
+        };
+        cursor.transaction(|cursor| {
+            HeaderWriter::new(cursor).write_range_only(variation, self.set, self.set)?;
+            self.value.write(cursor)
+        })
+    }
+}
+
+impl std::fmt::Display for AttrValue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AttrValue::VisibleString(x) => write!(f, "vstr: {:?}", x),
+            AttrValue::UnsignedInt(x) => write!(f, "uint: {}", x),
+            AttrValue::SignedInt(x) => write!(f, "int: {}", x),
+            AttrValue::FloatingPoint(x) => write!(f, "float: {}", x),
+            AttrValue::OctetString(x) => write!(f, "ostr: {}", Bytes::new(x)),
+            AttrValue::BitString(x) => write!(f, "bstr: {}", Bytes::new(x)),
+            AttrValue::Time(x) => write!(f, "time: {}", x),
+            AttrValue::AttrList(x) => {
+                f.write_str("list:")?;
+                for item in x.iter() {
+                    write!(
+                        f,
+                        " (var: {} writable: {})",
+                        item.variation, item.properties.is_writable
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Attribute<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "set: {} var: {} {}",
+            self.set, self.variation, self.value
+        )
+    }
+}
+
+impl std::fmt::Display for AttrParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AttrParseError::UnknownDataType(x) => write!(f, "unknown attribute data type: {}", x),
+            AttrParseError::BadLength(t, len) => {
+                write!(f, "attribute data type {:?} may not have length {}", t, len)
+            }
+            AttrParseError::BadVisibleString => f.write_str("attribute string is not valid UTF-8"),
+            AttrParseError::BadCount(count) => write!(
+                f,
+                "attribute headers must contain exactly one set, but count is {}",
+                count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AttrParseError {}
@@ -0,0 +1,415 @@
+use crate::app::{Bytes, Timestamp};
+use crate::util::cursor::{ReadCursor, ReadError};
+
+/// Authentication - Challenge (g120v1)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Challenge<'a> {
+    /// challenge sequence number (CSQ)
+    pub(crate) challenge_seq: u32,
+    /// user number (USR)
+    pub(crate) user: u16,
+    /// MAC algorithm (MAL)
+    pub(crate) mac_algorithm: u8,
+    /// reason for the challenge (RSN)
+    pub(crate) reason: u8,
+    /// pseudo-random challenge data
+    pub(crate) data: &'a [u8],
+}
+
+/// Authentication - Reply (g120v2)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Reply<'a> {
+    /// challenge sequence number (CSQ)
+    pub(crate) challenge_seq: u32,
+    /// user number (USR)
+    pub(crate) user: u16,
+    /// MAC value
+    pub(crate) mac: &'a [u8],
+}
+
+/// Authentication - Session Key Status (g120v5)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct SessionKeyStatus<'a> {
+    /// key change sequence number (KSQ)
+    pub(crate) key_change_seq: u32,
+    /// user number (USR)
+    pub(crate) user: u16,
+    /// key wrap algorithm (KWA)
+    pub(crate) key_wrap_algorithm: u8,
+    /// key status (KST)
+    pub(crate) key_status: u8,
+    /// MAC algorithm (MAL)
+    pub(crate) mac_algorithm: u8,
+    /// pseudo-random challenge data
+    pub(crate) data: &'a [u8],
+    /// MAC value, empty unless the keys are valid
+    pub(crate) mac: &'a [u8],
+}
+
+/// Authentication - Session Key Change (g120v6)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct SessionKeyChange<'a> {
+    /// key change sequence number (KSQ)
+    pub(crate) key_change_seq: u32,
+    /// user number (USR)
+    pub(crate) user: u16,
+    /// session keys wrapped with the update key
+    pub(crate) wrapped_keys: &'a [u8],
+}
+
+/// Authentication - Error (g120v7)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct AuthError<'a> {
+    /// challenge or key change sequence number
+    pub(crate) seq: u32,
+    /// user number (USR)
+    pub(crate) user: u16,
+    /// association identifier (AID)
+    pub(crate) association_id: u16,
+    /// error code (ERR)
+    pub(crate) code: u8,
+    /// time at which the error occurred
+    pub(crate) time: Timestamp,
+    /// optional vendor-specific error text
+    pub(crate) text: &'a [u8],
+}
+
+/// Authentication - User Certificate (g120v8)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct UserCertificate<'a> {
+    /// key change method (KCM)
+    pub(crate) key_change_method: u8,
+    /// certificate type (CTY)
+    pub(crate) certificate_type: u8,
+    /// encoded certificate
+    pub(crate) certificate: &'a [u8],
+}
+
+/// Authentication - User Status Change (g120v10)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct UserStatusChange<'a> {
+    /// key change method (KCM)
+    pub(crate) key_change_method: u8,
+    /// operation (OPR)
+    pub(crate) operation: u8,
+    /// status change sequence number (SCS)
+    pub(crate) status_change_seq: u32,
+    /// user role
+    pub(crate) user_role: u16,
+    /// user role expiry interval in days
+    pub(crate) user_role_expiry: u16,
+    /// globally unique user name
+    pub(crate) user_name: &'a [u8],
+    /// public key of the user
+    pub(crate) user_public_key: &'a [u8],
+    /// certification data from the authority
+    pub(crate) certification_data: &'a [u8],
+}
+
+/// Authentication - Update Key Change Request (g120v11)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct UpdateKeyChangeRequest<'a> {
+    /// key change method (KCM)
+    pub(crate) key_change_method: u8,
+    /// globally unique user name
+    pub(crate) user_name: &'a [u8],
+    /// pseudo-random challenge data of the master
+    pub(crate) data: &'a [u8],
+}
+
+/// Authentication - Update Key Change Reply (g120v12)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct UpdateKeyChangeReply<'a> {
+    /// key change sequence number (KSQ)
+    pub(crate) key_change_seq: u32,
+    /// user number (USR) assigned by the outstation
+    pub(crate) user: u16,
+    /// pseudo-random challenge data of the outstation
+    pub(crate) data: &'a [u8],
+}
+
+/// Authentication - Update Key Change (g120v13)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct UpdateKeyChange<'a> {
+    /// key change sequence number (KSQ)
+    pub(crate) key_change_seq: u32,
+    /// user number (USR)
+    pub(crate) user: u16,
+    /// encrypted update key data
+    pub(crate) encrypted_data: &'a [u8],
+}
+
+fn read_sized<'a>(cursor: &mut ReadCursor<'a>) -> Result<&'a [u8], ReadError> {
+    let length = cursor.read_u16_le()?;
+    cursor.read_bytes(length as usize)
+}
+
+impl<'a> Challenge<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, ReadError> {
+        let mut cursor = ReadCursor::new(data);
+        Ok(Self {
+            challenge_seq: cursor.read_u32_le()?,
+            user: cursor.read_u16_le()?,
+            mac_algorithm: cursor.read_u8()?,
+            reason: cursor.read_u8()?,
+            data: cursor.read_all(),
+        })
+    }
+}
+
+impl<'a> Reply<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, ReadError> {
+        let mut cursor = ReadCursor::new(data);
+        Ok(Self {
+            challenge_seq: cursor.read_u32_le()?,
+            user: cursor.read_u16_le()?,
+            mac: cursor.read_all(),
+        })
+    }
+}
+
+impl<'a> SessionKeyStatus<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, ReadError> {
+        let mut cursor = ReadCursor::new(data);
+        Ok(Self {
+            key_change_seq: cursor.read_u32_le()?,
+            user: cursor.read_u16_le()?,
+            key_wrap_algorithm: cursor.read_u8()?,
+            key_status: cursor.read_u8()?,
+            mac_algorithm: cursor.read_u8()?,
+            data: read_sized(&mut cursor)?,
+            mac: cursor.read_all(),
+        })
+    }
+}
+
+impl<'a> SessionKeyChange<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, ReadError> {
+        let mut cursor = ReadCursor::new(data);
+        Ok(Self {
+            key_change_seq: cursor.read_u32_le()?,
+            user: cursor.read_u16_le()?,
+            wrapped_keys: cursor.read_all(),
+        })
+    }
+}
+
+impl<'a> AuthError<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, ReadError> {
+        let mut cursor = ReadCursor::new(data);
+        Ok(Self {
+            seq: cursor.read_u32_le()?,
+            user: cursor.read_u16_le()?,
+            association_id: cursor.read_u16_le()?,
+            code: cursor.read_u8()?,
+            time: Timestamp::new(cursor.read_u48_le()?),
+            text: cursor.read_all(),
+        })
+    }
+}
+
+impl<'a> UserCertificate<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, ReadError> {
+        let mut cursor = ReadCursor::new(data);
+        Ok(Self {
+            key_change_method: cursor.read_u8()?,
+            certificate_type: cursor.read_u8()?,
+            certificate: cursor.read_all(),
+        })
+    }
+}
+
+impl<'a> UserStatusChange<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, ReadError> {
+        let mut cursor = ReadCursor::new(data);
+        let key_change_method = cursor.read_u8()?;
+        let operation = cursor.read_u8()?;
+        let status_change_seq = cursor.read_u32_le()?;
+        let user_role = cursor.read_u16_le()?;
+        let user_role_expiry = cursor.read_u16_le()?;
+        // the lengths of all three fields precede their values
+        let user_name_length = cursor.read_u16_le()?;
+        let user_public_key_length = cursor.read_u16_le()?;
+        let certification_data_length = cursor.read_u16_le()?;
+        Ok(Self {
+            key_change_method,
+            operation,
+            status_change_seq,
+            user_role,
+            user_role_expiry,
+            user_name: cursor.read_bytes(user_name_length as usize)?,
+            user_public_key: cursor.read_bytes(user_public_key_length as usize)?,
+            certification_data: cursor.read_bytes(certification_data_length as usize)?,
+        })
+    }
+}
+
+impl<'a> UpdateKeyChangeRequest<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, ReadError> {
+        let mut cursor = ReadCursor::new(data);
+        let key_change_method = cursor.read_u8()?;
+        let user_name_length = cursor.read_u16_le()?;
+        let data_length = cursor.read_u16_le()?;
+        Ok(Self {
+            key_change_method,
+            user_name: cursor.read_bytes(user_name_length as usize)?,
+            data: cursor.read_bytes(data_length as usize)?,
+        })
+    }
+}
+
+impl<'a> UpdateKeyChangeReply<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, ReadError> {
+        let mut cursor = ReadCursor::new(data);
+        Ok(Self {
+            key_change_seq: cursor.read_u32_le()?,
+            user: cursor.read_u16_le()?,
+            data: read_sized(&mut cursor)?,
+        })
+    }
+}
+
+impl<'a> UpdateKeyChange<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, ReadError> {
+        let mut cursor = ReadCursor::new(data);
+        Ok(Self {
+            key_change_seq: cursor.read_u32_le()?,
+            user: cursor.read_u16_le()?,
+            encrypted_data: read_sized(&mut cursor)?,
+        })
+    }
+}
+
+impl std::fmt::Display for Challenge<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "csq: {} user: {} mal: {} reason: {} data: {}",
+            self.challenge_seq,
+            self.user,
+            self.mac_algorithm,
+            self.reason,
+            Bytes::new(self.data)
+        )
+    }
+}
+
+impl std::fmt::Display for Reply<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "csq: {} user: {} mac: {}",
+            self.challenge_seq,
+            self.user,
+            Bytes::new(self.mac)
+        )
+    }
+}
+
+impl std::fmt::Display for SessionKeyStatus<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "ksq: {} user: {} kwa: {} status: {} mal: {} data: {} mac: {}",
+            self.key_change_seq,
+            self.user,
+            self.key_wrap_algorithm,
+            self.key_status,
+            self.mac_algorithm,
+            Bytes::new(self.data),
+            Bytes::new(self.mac)
+        )
+    }
+}
+
+impl std::fmt::Display for SessionKeyChange<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "ksq: {} user: {} wrapped keys: {}",
+            self.key_change_seq,
+            self.user,
+            Bytes::new(self.wrapped_keys)
+        )
+    }
+}
+
+impl std::fmt::Display for AuthError<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "seq: {} user: {} aid: {} error: {} time: {} text: {}",
+            self.seq,
+            self.user,
+            self.association_id,
+            self.code,
+            self.time,
+            Bytes::new(self.text)
+        )
+    }
+}
+
+impl std::fmt::Display for UserCertificate<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "kcm: {} type: {} certificate: {}",
+            self.key_change_method,
+            self.certificate_type,
+            Bytes::new(self.certificate)
+        )
+    }
+}
+
+impl std::fmt::Display for UserStatusChange<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "kcm: {} operation: {} scs: {} role: {} expiry: {} user name: {} public key: {} certification data: {}",
+            self.key_change_method,
+            self.operation,
+            self.status_change_seq,
+            self.user_role,
+            self.user_role_expiry,
+            Bytes::new(self.user_name),
+            Bytes::new(self.user_public_key),
+            Bytes::new(self.certification_data)
+        )
+    }
+}
+
+impl std::fmt::Display for UpdateKeyChangeRequest<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "kcm: {} user name: {} data: {}",
+            self.key_change_method,
+            Bytes::new(self.user_name),
+            Bytes::new(self.data)
+        )
+    }
+}
+
+impl std::fmt::Display for UpdateKeyChangeReply<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "ksq: {} user: {} data: {}",
+            self.key_change_seq,
+            self.user,
+            Bytes::new(self.data)
+        )
+    }
+}
+
+impl std::fmt::Display for UpdateKeyChange<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "ksq: {} user: {} encrypted data: {}",
+            self.key_change_seq,
+            self.user,
+            Bytes::new(self.encrypted_data)
+        )
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Snapshot of the usage of a [BufferPool]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BufferPoolStatistics {
+    /// number of buffers currently held by sessions
+    pub in_use: usize,
+    /// number of released buffers kept for reuse
+    pub idle: usize,
+    /// total size of the released buffers kept for reuse
+    pub idle_bytes: usize,
+    /// number of buffers allocated because no released buffer of the requested size was available
+    pub allocations: u64,
+}
+
+/// Buffers shared between the sessions of many channels or outstations
+///
+/// Without a pool, every outstation session and master channel allocates its transmit and
+/// receive buffers when it is created and keeps them for as long as it runs. With a pool, the
+/// buffers are obtained when there is something to send or receive and released when the
+/// session goes idle, so a server hosting many mostly idle outstations only pins the buffers
+/// of the sessions that are busy.
+///
+/// Released buffers are kept for reuse up to a limit on their total size, beyond which they are
+/// freed. The configurations share the pool through an `Arc`, and the sessions keep it alive for
+/// as long as they run.
+pub struct BufferPool {
+    max_idle_bytes: usize,
+    state: Mutex<PoolState>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    idle: HashMap<usize, Vec<Box<[u8]>>>,
+    stats: BufferPoolStatistics,
+}
+
+impl BufferPool {
+    /// Create a pool that keeps released buffers for reuse as long as their total size doesn't
+    /// exceed `max_idle_bytes`
+    pub fn new(max_idle_bytes: usize) -> Self {
+        Self {
+            max_idle_bytes,
+            state: Mutex::new(PoolState::default()),
+        }
+    }
+
+    /// Get a snapshot of the usage of the pool
+    pub fn statistics(&self) -> BufferPoolStatistics {
+        self.state.lock().unwrap().stats
+    }
+
+    /// Free every released buffer kept for reuse
+    pub fn trim(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.idle.clear();
+        state.stats.idle = 0;
+        state.stats.idle_bytes = 0;
+    }
+
+    /// reuse a released buffer of the requested size or allocate a new one
+    pub(crate) fn acquire(&self, size: usize) -> Box<[u8]> {
+        let mut state = self.state.lock().unwrap();
+        state.stats.in_use += 1;
+        match state.idle.get_mut(&size).and_then(|x| x.pop()) {
+            Some(buffer) => {
+                state.stats.idle -= 1;
+                state.stats.idle_bytes -= size;
+                buffer
+            }
+            None => {
+                state.stats.allocations += 1;
+                // allocate outside of the lock
+                drop(state);
+                vec![0; size].into_boxed_slice()
+            }
+        }
+    }
+
+    /// return a buffer obtained with [BufferPool::acquire]
+    pub(crate) fn release(&self, buffer: Box<[u8]>) {
+        let mut state = self.state.lock().unwrap();
+        state.stats.in_use = state.stats.in_use.saturating_sub(1);
+        let size = buffer.len();
+        if state.stats.idle_bytes + size > self.max_idle_bytes {
+            return;
+        }
+        state.stats.idle += 1;
+        state.stats.idle_bytes += size;
+        state.idle.entry(size).or_default().push(buffer);
+    }
+}
+
+impl std::fmt::Debug for BufferPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("max_idle_bytes", &self.max_idle_bytes)
+            .field("statistics", &self.statistics())
+            .finish()
+    }
+}
+
+// pools are compared by identity so that configurations referring to the same pool are equal
+impl PartialEq for BufferPool {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reuses_released_buffers_of_the_same_size() {
+        let pool = BufferPool::new(4096);
+        let first = pool.acquire(2048);
+        pool.release(first);
+        let second = pool.acquire(2048);
+        let other = pool.acquire(1024);
+
+        assert_eq!(
+            pool.statistics(),
+            BufferPoolStatistics {
+                in_use: 2,
+                idle: 0,
+                idle_bytes: 0,
+                allocations: 2,
+            }
+        );
+
+        pool.release(second);
+        pool.release(other);
+        assert_eq!(pool.statistics().idle_bytes, 3072);
+        pool.trim();
+        assert_eq!(pool.statistics().idle, 0);
+    }
+
+    #[test]
+    fn frees_buffers_beyond_the_idle_limit() {
+        let pool = BufferPool::new(2048);
+        let first = pool.acquire(2048);
+        let second = pool.acquire(2048);
+        pool.release(first);
+        pool.release(second);
+
+        let stats = pool.statistics();
+        assert_eq!(stats.in_use, 0);
+        assert_eq!(stats.idle, 1);
+        assert_eq!(stats.idle_bytes, 2048);
+    }
+}
@@ -1,5 +1,9 @@
+use crate::app::QualifierCode;
+use crate::decode::PayloadKind;
+
 /// Wrapper around an underlying u8 slice
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bytes<'a> {
     /// underlying slice
     pub value: &'a [u8],
@@ -13,6 +17,10 @@ impl<'a> Bytes<'a> {
 
 impl std::fmt::Display for Bytes<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if crate::decode::is_redacted(PayloadKind::Object, self.value) {
+            return write!(f, "length = {}, <redacted>", self.value.len());
+        }
+
         if self.value.len() <= 3 {
             return write!(f, "{:02X?}", self.value);
         }
@@ -24,6 +32,37 @@ impl std::fmt::Display for Bytes<'_> {
     }
 }
 
+/// Object header with a group and variation unknown to the parser, along with its objects
+///
+/// Unknown headers can only be captured when the size of their objects can be determined from
+/// the qualifier: all objects (0x06), a count of zero, or free-format (0x5B).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct UnknownHeader<'a> {
+    /// group of the header
+    pub group: u8,
+    /// variation of the header
+    pub variation: u8,
+    /// qualifier of the header
+    pub qualifier: QualifierCode,
+    /// raw bytes of the header, including the group, variation, qualifier, and range or count
+    pub header: &'a [u8],
+    /// raw bytes of the objects following the header
+    pub objects: &'a [u8],
+}
+
+impl std::fmt::Display for UnknownHeader<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "g{}v{} : Unknown - {} - {}",
+            self.group,
+            self.variation,
+            self.qualifier.description(),
+            Bytes::new(self.objects)
+        )
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
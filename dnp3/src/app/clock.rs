@@ -0,0 +1,582 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::ops::{Add, AddAssign, Sub};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, SystemTime};
+
+/// Point in the monotonic time of a [Clock]
+///
+/// Instants are measured from an origin that belongs to each clock, so only the instants of the
+/// same clock, or of its clones, can be compared.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant {
+    since_origin: Duration,
+}
+
+impl Instant {
+    const fn new(since_origin: Duration) -> Self {
+        Self { since_origin }
+    }
+
+    /// Amount of time elapsed from another instant to this one, or zero if the other instant is
+    /// later than this one
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.saturating_duration_since(earlier)
+    }
+
+    /// Amount of time elapsed from another instant to this one, or `None` if the other instant
+    /// is later than this one
+    pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+        self.since_origin.checked_sub(earlier.since_origin)
+    }
+
+    /// Amount of time elapsed from another instant to this one, or zero if the other instant is
+    /// later than this one
+    pub fn saturating_duration_since(&self, earlier: Instant) -> Duration {
+        self.checked_duration_since(earlier).unwrap_or_default()
+    }
+
+    /// Instant that is a duration later than this one, or `None` if it can't be represented
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        self.since_origin.checked_add(duration).map(Self::new)
+    }
+
+    /// Instant that is a duration earlier than this one, or `None` if it precedes the origin of
+    /// the clock
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        self.since_origin.checked_sub(duration).map(Self::new)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, rhs: Duration) -> Instant {
+        self.checked_add(rhs)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, rhs: Duration) -> Instant {
+        self.checked_sub(rhs)
+            .expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, rhs: Instant) -> Duration {
+        self.saturating_duration_since(rhs)
+    }
+}
+
+/// Source of the monotonic and wall-clock time used for the timeouts, retry delays, polls,
+/// keep-alives, and timestamps of a channel
+///
+/// The default clock is Tokio's clock, which requires a Tokio runtime with its timer enabled and
+/// a system clock. A [ManualClock] only moves forward when it is advanced,
+/// which makes simulations independent of real time: they may run much faster than real time
+/// and produce the same results on every run. A [Timer] provides the time and the delays of
+/// another executor, e.g. that of `async-std` or `smol`, or those of the browser on
+/// `wasm32-unknown-unknown`, which has no system clock.
+#[derive(Clone, Default)]
+pub struct Clock {
+    source: Source,
+}
+
+#[derive(Clone)]
+enum Source {
+    Tokio(TokioOrigin),
+    Manual(Arc<ManualClock>),
+    Custom(Arc<Timer>),
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Self::Tokio(TokioOrigin::default())
+    }
+}
+
+// read from Tokio's clock when the clock is first used, so that configurations may hold the
+// default clock on targets without a system clock
+#[derive(Clone, Default)]
+struct TokioOrigin {
+    instant: Arc<Mutex<Option<crate::tokio::time::Instant>>>,
+}
+
+impl TokioOrigin {
+    fn get(&self) -> crate::tokio::time::Instant {
+        *self
+            .instant
+            .lock()
+            .unwrap()
+            .get_or_insert_with(crate::tokio::time::Instant::now)
+    }
+}
+
+impl Clock {
+    /// Clock that follows Tokio's clock
+    pub fn tokio() -> Self {
+        Self {
+            source: Source::default(),
+        }
+    }
+
+    /// Clock that only moves forward when the [ManualClock] is advanced
+    pub fn manual(clock: Arc<ManualClock>) -> Self {
+        Self {
+            source: Source::Manual(clock),
+        }
+    }
+
+    /// Clock that reads the time from the [Timer] and waits using it
+    pub fn custom(timer: Arc<Timer>) -> Self {
+        Self {
+            source: Source::Custom(timer),
+        }
+    }
+
+    pub(crate) fn now(&self) -> Instant {
+        match &self.source {
+            Source::Tokio(origin) => {
+                let origin = origin.get();
+                Instant::new(crate::tokio::time::Instant::now().saturating_duration_since(origin))
+            }
+            Source::Manual(clock) => clock.now(),
+            Source::Custom(timer) => Instant::new((timer.now)()),
+        }
+    }
+
+    /// wall-clock time that moves forward together with [Clock::now]
+    pub(crate) fn system_time(&self) -> SystemTime {
+        match &self.source {
+            Source::Tokio(_) => SystemTime::now(),
+            Source::Manual(clock) => clock.system_time(),
+            Source::Custom(timer) => (timer.system_time)(),
+        }
+    }
+
+    /// the returned future doesn't borrow the clock
+    pub(crate) fn sleep_until(&self, deadline: Instant) -> impl Future<Output = ()> {
+        let source = self.source.clone();
+        async move {
+            match source {
+                Source::Tokio(origin) => {
+                    crate::tokio::time::sleep_until(origin.get() + deadline.since_origin).await
+                }
+                Source::Manual(clock) => ManualSleep::new(clock, deadline).await,
+                Source::Custom(timer) => {
+                    let now = Instant::new((timer.now)());
+                    if deadline > now {
+                        (timer.sleep)(deadline - now).await
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Clock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.source {
+            Source::Tokio(_) => f.write_str("Clock::Tokio"),
+            Source::Manual(clock) => write!(f, "Clock::Manual({:?})", clock.elapsed()),
+            Source::Custom(_) => f.write_str("Clock::Custom"),
+        }
+    }
+}
+
+/// Tokio clocks are all equal, although their instants are measured from different origins
+impl PartialEq for Clock {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.source, &other.source) {
+            (Source::Tokio(_), Source::Tokio(_)) => true,
+            (Source::Manual(x), Source::Manual(y)) => Arc::ptr_eq(x, y),
+            (Source::Custom(x), Source::Custom(y)) => Arc::ptr_eq(x, y),
+            _ => false,
+        }
+    }
+}
+
+/// Delay returned by a [Timer]
+pub type TimerFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Reads the time and waits on behalf of the channels and outstations using an executor other
+/// than Tokio
+///
+/// Together with a [Spawner](crate::app::Spawner) and a custom physical layer, a timer lets the
+/// master and outstation sessions run on executors such as `async-std` or `smol` without a Tokio
+/// runtime, and the crate builds without the runtime when the default `tokio-runtime` feature is
+/// disabled. The built-in TCP, UDP, and serial physical layers still require Tokio.
+///
+/// The channels never read the system's clocks through a timer, which makes it usable on
+/// `wasm32-unknown-unknown`, where the time comes from the browser, e.g. `performance.now()`
+/// and `Date.now()`.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::time::SystemTime;
+/// use dnp3::app::{Clock, Timer};
+///
+/// # fn executor_sleep(_: std::time::Duration) -> impl std::future::Future<Output = ()> + Send { async {} }
+/// let start = std::time::Instant::now();
+/// let timer = Arc::new(Timer::new(
+///     |duration| Box::pin(executor_sleep(duration)),
+///     move || start.elapsed(),
+///     SystemTime::now,
+/// ));
+/// let clock = Clock::custom(timer);
+/// ```
+///
+/// The clocks of all the channels on an executor may share the same timer.
+pub struct Timer {
+    sleep: Box<dyn Fn(Duration) -> TimerFuture + Send + Sync>,
+    now: Box<dyn Fn() -> Duration + Send + Sync>,
+    system_time: Box<dyn Fn() -> SystemTime + Send + Sync>,
+}
+
+impl Timer {
+    /// Create a timer from a function that returns a future completing after a duration, a
+    /// function that returns the monotonic time elapsed since an arbitrary origin, and a
+    /// function that returns the wall-clock time
+    pub fn new<S, N, T>(sleep: S, now: N, system_time: T) -> Self
+    where
+        S: Fn(Duration) -> TimerFuture + Send + Sync + 'static,
+        N: Fn() -> Duration + Send + Sync + 'static,
+        T: Fn() -> SystemTime + Send + Sync + 'static,
+    {
+        Self {
+            sleep: Box::new(sleep),
+            now: Box::new(now),
+            system_time: Box::new(system_time),
+        }
+    }
+}
+
+impl std::fmt::Debug for Timer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Timer").finish()
+    }
+}
+
+/// Clock whose time only moves forward when it is advanced
+///
+/// Channels share the clock with the simulation that advances it.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use dnp3::app::{Clock, ManualClock};
+///
+/// let manual = Arc::new(ManualClock::new());
+/// let clock = Clock::manual(manual.clone());
+/// // ... spawn channels configured with the clock ...
+///
+/// // run the simulation as fast as possible by jumping to each pending deadline
+/// while let Some(delay) = manual.next_wake() {
+///     manual.advance(delay);
+///     // ... let the channels process the expired timers ...
+/// #   break;
+/// }
+/// ```
+pub struct ManualClock {
+    system_start: SystemTime,
+    state: Mutex<ManualState>,
+}
+
+struct ManualState {
+    elapsed: Duration,
+    next_id: u64,
+    sleepers: BTreeMap<u64, (Instant, Waker)>,
+}
+
+impl ManualClock {
+    /// Create a clock that starts at the current system time and stays there until it is
+    /// advanced
+    ///
+    /// On targets without a system clock, e.g. `wasm32-unknown-unknown`, use
+    /// [ManualClock::starting_at] instead.
+    pub fn new() -> Self {
+        Self::starting_at(SystemTime::now())
+    }
+
+    /// Create a clock that starts at the specified wall-clock time and stays there until it is
+    /// advanced, e.g. to make the timestamps of a simulation the same on every run
+    pub fn starting_at(system_time: SystemTime) -> Self {
+        Self {
+            system_start: system_time,
+            state: Mutex::new(ManualState {
+                elapsed: Duration::from_secs(0),
+                next_id: 0,
+                sleepers: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// Move the clock forward, waking every timer whose deadline is reached
+    pub fn advance(&self, duration: Duration) {
+        let woken: Vec<Waker> = {
+            let mut state = self.state.lock().unwrap();
+            state.elapsed += duration;
+            let now = Instant::new(state.elapsed);
+            let expired: Vec<u64> = state
+                .sleepers
+                .iter()
+                .filter(|(_, (deadline, _))| *deadline <= now)
+                .map(|(id, _)| *id)
+                .collect();
+            expired
+                .into_iter()
+                .filter_map(|id| state.sleepers.remove(&id))
+                .map(|(_, waker)| waker)
+                .collect()
+        };
+
+        for waker in woken {
+            waker.wake();
+        }
+    }
+
+    /// Amount of time by which the clock was advanced since it was created
+    pub fn elapsed(&self) -> Duration {
+        self.state.lock().unwrap().elapsed
+    }
+
+    /// Amount of time until the earliest pending timer expires, if any timer is pending
+    pub fn next_wake(&self) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        let now = Instant::new(state.elapsed);
+        state
+            .sleepers
+            .values()
+            .map(|(deadline, _)| deadline.saturating_duration_since(now))
+            .min()
+    }
+
+    fn now(&self) -> Instant {
+        Instant::new(self.elapsed())
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.system_start + self.elapsed()
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ManualClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManualClock")
+            .field("elapsed", &self.elapsed())
+            .finish()
+    }
+}
+
+struct ManualSleep {
+    clock: Arc<ManualClock>,
+    deadline: Instant,
+    id: Option<u64>,
+}
+
+impl ManualSleep {
+    fn new(clock: Arc<ManualClock>, deadline: Instant) -> Self {
+        Self {
+            clock,
+            deadline,
+            id: None,
+        }
+    }
+}
+
+impl Future for ManualSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.clock.state.lock().unwrap();
+
+        if Instant::new(state.elapsed) >= self.deadline {
+            if let Some(id) = self.id {
+                state.sleepers.remove(&id);
+            }
+            drop(state);
+            self.id = None;
+            return Poll::Ready(());
+        }
+
+        let id = match self.id {
+            Some(id) => id,
+            None => {
+                let id = state.next_id;
+                state.next_id += 1;
+                id
+            }
+        };
+        state
+            .sleepers
+            .insert(id, (self.deadline, cx.waker().clone()));
+        drop(state);
+        self.id = Some(id);
+        Poll::Pending
+    }
+}
+
+impl Drop for ManualSleep {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.clock.state.lock().unwrap().sleepers.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tokio::test::*;
+
+    #[test]
+    fn manual_clock_only_moves_when_advanced() {
+        let manual = Arc::new(ManualClock::new());
+        let clock = Clock::manual(manual.clone());
+        let start = clock.now();
+
+        crate::tokio::time::advance(Duration::from_secs(10));
+        assert_eq!(clock.now(), start);
+
+        manual.advance(Duration::from_secs(3));
+        assert_eq!(clock.now(), start + Duration::from_secs(3));
+        assert_eq!(manual.elapsed(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn reception_timestamps_follow_the_manual_clock() {
+        let manual = Arc::new(ManualClock::new());
+        let clock = Clock::manual(manual.clone());
+        let first = crate::link::RxTimestamp::now(&clock);
+
+        manual.advance(Duration::from_secs(2));
+        let second = crate::link::RxTimestamp::now(&clock);
+
+        assert_eq!(first.monotonic, clock.now() - Duration::from_secs(2));
+        assert_eq!(second.monotonic, clock.now());
+        assert_eq!(
+            second.system.duration_since(first.system).unwrap(),
+            Duration::from_secs(2)
+        );
+    }
+
+    #[test]
+    fn sleep_completes_when_deadline_is_reached() {
+        let manual = Arc::new(ManualClock::new());
+        let clock = Clock::manual(manual.clone());
+        let mut sleep = spawn(clock.sleep_until(clock.now() + Duration::from_secs(5)));
+
+        assert_pending!(sleep.poll());
+        assert_eq!(manual.next_wake(), Some(Duration::from_secs(5)));
+
+        manual.advance(Duration::from_secs(4));
+        assert_pending!(sleep.poll());
+        assert_eq!(manual.next_wake(), Some(Duration::from_secs(1)));
+
+        manual.advance(Duration::from_secs(1));
+        assert_ready!(sleep.poll());
+        assert_eq!(manual.next_wake(), None);
+    }
+
+    #[test]
+    fn dropped_sleep_is_not_pending() {
+        let manual = Arc::new(ManualClock::new());
+        let clock = Clock::manual(manual.clone());
+        let mut sleep = spawn(clock.sleep_until(clock.now() + Duration::from_secs(1)));
+        assert_pending!(sleep.poll());
+        drop(sleep);
+        assert_eq!(manual.next_wake(), None);
+    }
+
+    fn custom_clock(durations: Arc<Mutex<Vec<Duration>>>, now: Arc<Mutex<Duration>>) -> Clock {
+        let system_now = now.clone();
+        Clock::custom(Arc::new(Timer::new(
+            move |duration| {
+                durations.lock().unwrap().push(duration);
+                Box::pin(async {})
+            },
+            move || *now.lock().unwrap(),
+            move || SystemTime::UNIX_EPOCH + *system_now.lock().unwrap(),
+        )))
+    }
+
+    #[test]
+    fn custom_clock_sleeps_with_the_timer() {
+        let durations = Arc::new(Mutex::new(Vec::new()));
+        let now = Arc::new(Mutex::new(Duration::from_secs(7)));
+        let clock = custom_clock(durations.clone(), now);
+
+        let mut sleep = spawn(clock.sleep_until(clock.now() + Duration::from_secs(5)));
+        assert_ready!(sleep.poll());
+        drop(sleep);
+        // deadlines that have already passed complete without calling the timer
+        let mut sleep = spawn(clock.sleep_until(clock.now()));
+        assert_ready!(sleep.poll());
+        drop(sleep);
+
+        assert_eq!(*durations.lock().unwrap(), vec![Duration::from_secs(5)]);
+    }
+
+    #[test]
+    fn custom_clock_reads_the_time_from_the_timer() {
+        let now = Arc::new(Mutex::new(Duration::from_secs(7)));
+        let clock = custom_clock(Arc::new(Mutex::new(Vec::new())), now.clone());
+        let start = clock.now();
+
+        *now.lock().unwrap() += Duration::from_secs(2);
+        assert_eq!(clock.now(), start + Duration::from_secs(2));
+        assert_eq!(
+            clock.system_time(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(9)
+        );
+    }
+
+    #[test]
+    fn manual_clock_starts_at_the_specified_time() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let manual = Arc::new(ManualClock::starting_at(start));
+        let clock = Clock::manual(manual.clone());
+
+        manual.advance(Duration::from_secs(5));
+        assert_eq!(clock.system_time(), start + Duration::from_secs(5));
+    }
+
+    #[test]
+    fn clocks_compare_by_identity() {
+        let manual = Arc::new(ManualClock::new());
+        let timer = Arc::new(Timer::new(
+            |_| Box::pin(async {}),
+            || Duration::from_secs(0),
+            || SystemTime::UNIX_EPOCH,
+        ));
+        assert_eq!(Clock::default(), Clock::tokio());
+        assert_eq!(Clock::manual(manual.clone()), Clock::manual(manual.clone()));
+        assert_ne!(Clock::manual(manual.clone()), Clock::tokio());
+        assert_ne!(
+            Clock::manual(manual),
+            Clock::manual(Arc::new(ManualClock::new()))
+        );
+        assert_eq!(Clock::custom(timer.clone()), Clock::custom(timer.clone()));
+        assert_ne!(Clock::custom(timer), Clock::tokio());
+    }
+}
@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use crate::app::{RangeError, RetryStrategy};
+use crate::link::EndpointAddress;
+
+/// Error returned when a configuration builder is given settings that are inconsistent with
+/// each other and would misbehave at runtime
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ConfigError {
+    /// the outstation and master addresses are the same
+    AddressCollision(EndpointAddress),
+    /// a duration is outside the range allowed for timeouts
+    InvalidTimeout {
+        /// name of the configuration field
+        field: &'static str,
+        /// reason the duration is invalid
+        error: RangeError,
+    },
+    /// a limit is zero, so the associated function could never make progress
+    ZeroLimit {
+        /// name of the configuration field
+        field: &'static str,
+    },
+    /// a new unsolicited series would start before the master has had as much time to confirm
+    /// the previous one as the outstation waits for a confirmation
+    UnsolicitedRetryDelayTooShort {
+        /// configured delay between unsolicited series
+        retry_delay: Duration,
+        /// configured confirmation timeout
+        confirm_timeout: Duration,
+    },
+    /// the receive buffer is larger than the fragments that can be reassembled from the
+    /// maximum number of transport segments
+    RxBufferExceedsSegments {
+        /// configured receive buffer size
+        rx_buffer_size: usize,
+        /// largest fragment that can be reassembled from the maximum number of segments
+        max_fragment_size: usize,
+    },
+    /// the minimum delay of a retry strategy is larger than its maximum delay
+    InvalidRetryStrategy {
+        /// name of the configuration field
+        field: &'static str,
+        /// configured minimum delay
+        min_delay: Duration,
+        /// configured maximum delay
+        max_delay: Duration,
+    },
+}
+
+impl ConfigError {
+    pub(crate) fn check_timeout(field: &'static str, value: Duration) -> Result<(), Self> {
+        match crate::app::Timeout::from_duration(value) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(ConfigError::InvalidTimeout { field, error }),
+        }
+    }
+
+    pub(crate) fn check_limit<T>(field: &'static str, value: Option<T>) -> Result<(), Self>
+    where
+        T: Default + PartialEq,
+    {
+        match value {
+            Some(x) if x == T::default() => Err(ConfigError::ZeroLimit { field }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_retry_strategy(
+        field: &'static str,
+        strategy: &RetryStrategy,
+    ) -> Result<(), Self> {
+        if strategy.min_delay > strategy.max_delay {
+            return Err(ConfigError::InvalidRetryStrategy {
+                field,
+                min_delay: strategy.min_delay,
+                max_delay: strategy.max_delay,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConfigError::AddressCollision(address) => write!(
+                f,
+                "outstation and master have the same address ({})",
+                address
+            ),
+            ConfigError::InvalidTimeout { field, error } => write!(f, "{}: {}", field, error),
+            ConfigError::ZeroLimit { field } => write!(f, "{} may not be zero", field),
+            ConfigError::UnsolicitedRetryDelayTooShort {
+                retry_delay,
+                confirm_timeout,
+            } => write!(
+                f,
+                "unsolicited retry delay ({} ms) is shorter than the confirm timeout ({} ms)",
+                retry_delay.as_millis(),
+                confirm_timeout.as_millis()
+            ),
+            ConfigError::RxBufferExceedsSegments {
+                rx_buffer_size,
+                max_fragment_size,
+            } => write!(
+                f,
+                "rx buffer size ({}) is larger than the largest fragment that can be reassembled ({})",
+                rx_buffer_size, max_fragment_size
+            ),
+            ConfigError::InvalidRetryStrategy {
+                field,
+                min_delay,
+                max_delay,
+            } => write!(
+                f,
+                "{}: minimum delay ({} ms) is larger than the maximum delay ({} ms)",
+                field,
+                min_delay.as_millis(),
+                max_delay.as_millis()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
@@ -0,0 +1,86 @@
+use crate::app::Listener;
+
+/// Receiving end of a bounded channel of events produced by a master channel or outstation
+///
+/// Notifications are delivered without blocking the session, so events are discarded with a
+/// warning if the channel is full. Size the channel for the bursts the application expects to
+/// fall behind by.
+#[derive(Debug)]
+pub struct EventReceiver<T> {
+    inner: crate::tokio::sync::mpsc::Receiver<T>,
+}
+
+impl<T> EventReceiver<T> {
+    /// Wait for the next event
+    ///
+    /// Returns `None` once the session that produces the events has been dropped and every event
+    /// has been received
+    pub async fn next(&mut self) -> Option<T> {
+        self.inner.recv().await
+    }
+
+    /// Receive the next event if one is available without waiting
+    pub fn try_next(&mut self) -> Option<T> {
+        self.inner.try_recv().ok()
+    }
+}
+
+/// sending end of an event channel, used by the implementations of the callback traits
+#[derive(Debug)]
+pub(crate) struct EventSender<T> {
+    inner: crate::tokio::sync::mpsc::Sender<T>,
+}
+
+impl<T> EventSender<T> {
+    pub(crate) fn send(&self, event: T) {
+        use crate::tokio::sync::mpsc::error::TrySendError;
+
+        if let Err(TrySendError::Full(_)) = self.inner.try_send(event) {
+            tracing::warn!("event channel is full, discarding event");
+        }
+        // a closed channel means that the application is no longer interested
+    }
+}
+
+/// create a bounded event channel, `capacity` being at least one
+pub(crate) fn event_channel<T>(capacity: usize) -> (EventSender<T>, EventReceiver<T>) {
+    let (tx, rx) = crate::tokio::sync::mpsc::channel(capacity.max(1));
+    (EventSender { inner: tx }, EventReceiver { inner: rx })
+}
+
+impl<T> Listener<T> for EventSender<T>
+where
+    T: Send,
+{
+    fn update(&mut self, value: T) {
+        self.send(value);
+    }
+}
+
+/// Create a listener that forwards each value to a bounded channel instead of invoking a callback,
+/// e.g. to receive the state of a channel in an async `select!` loop
+///
+/// The listener never blocks, so values are discarded if more than `capacity` are waiting.
+pub fn listener_channel<T>(capacity: usize) -> (Box<dyn Listener<T>>, EventReceiver<T>)
+where
+    T: Send + 'static,
+{
+    let (tx, rx) = event_channel(capacity);
+    (Box::new(tx), rx)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn events_are_discarded_when_the_channel_is_full() {
+        let (mut listener, mut rx) = listener_channel(2);
+        listener.update(1);
+        listener.update(2);
+        listener.update(3);
+        assert_eq!(rx.try_next(), Some(1));
+        assert_eq!(rx.try_next(), Some(2));
+        assert_eq!(rx.try_next(), None);
+    }
+}
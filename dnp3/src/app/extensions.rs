@@ -245,6 +245,8 @@ impl FunctionCode {
             FunctionCode::GetFileInfo => FunctionInfo::request_with_objects(),
             FunctionCode::AuthenticateFile => FunctionInfo::request_with_objects(),
             FunctionCode::AbortFile => FunctionInfo::request_with_objects(),
+            FunctionCode::AuthRequest => FunctionInfo::request_with_objects(),
+            FunctionCode::AuthRequestNoAck => FunctionInfo::request_with_objects(),
             // requests that never have object headers
             FunctionCode::ColdRestart => FunctionInfo::request_by_function_only(),
             FunctionCode::WarmRestart => FunctionInfo::request_by_function_only(),
@@ -255,6 +257,7 @@ impl FunctionCode {
             // responses
             FunctionCode::Response => FunctionInfo::response(),
             FunctionCode::UnsolicitedResponse => FunctionInfo::response(),
+            FunctionCode::AuthResponse => FunctionInfo::response(),
         }
     }
 }
@@ -14,6 +14,9 @@ use crate::app::variations::Variation;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub(crate) enum AllObjectsVariation {
+    Group0(u8),
+    Group0Var254,
+    Group0Var255,
     Group1Var0,
     Group1Var1,
     Group1Var2,
@@ -91,6 +94,9 @@ pub(crate) enum AllObjectsVariation {
     Group60Var3,
     Group60Var4,
     Group80Var1,
+    Group90Var1,
+    Group102Var0,
+    Group102Var1,
     Group110Var0,
     Group111Var0,
 }
@@ -98,6 +104,9 @@ pub(crate) enum AllObjectsVariation {
 impl AllObjectsVariation {
     pub(crate) fn get(v: Variation) -> Option<AllObjectsVariation> {
         match v {
+            Variation::Group0(x) => Some(AllObjectsVariation::Group0(x)),
+            Variation::Group0Var254 => Some(AllObjectsVariation::Group0Var254),
+            Variation::Group0Var255 => Some(AllObjectsVariation::Group0Var255),
             Variation::Group1Var0 => Some(AllObjectsVariation::Group1Var0),
             Variation::Group1Var1 => Some(AllObjectsVariation::Group1Var1),
             Variation::Group1Var2 => Some(AllObjectsVariation::Group1Var2),
@@ -175,6 +184,9 @@ impl AllObjectsVariation {
             Variation::Group60Var3 => Some(AllObjectsVariation::Group60Var3),
             Variation::Group60Var4 => Some(AllObjectsVariation::Group60Var4),
             Variation::Group80Var1 => Some(AllObjectsVariation::Group80Var1),
+            Variation::Group90Var1 => Some(AllObjectsVariation::Group90Var1),
+            Variation::Group102Var0 => Some(AllObjectsVariation::Group102Var0),
+            Variation::Group102Var1 => Some(AllObjectsVariation::Group102Var1),
             Variation::Group110(0) => Some(AllObjectsVariation::Group110Var0),
             Variation::Group111(0) => Some(AllObjectsVariation::Group111Var0),
             _ => None,
@@ -120,6 +120,10 @@ pub(crate) enum CountVariation<'a> {
     /// Octet String Event - Sized by variation
     Group111Var0,
     Group111VarX(u8),
+    /// Authentication - Aggressive Mode Request
+    Group120Var3(CountSequence<'a, Group120Var3>),
+    /// Authentication - Session Key Status Request
+    Group120Var4(CountSequence<'a, Group120Var4>),
 }
 
 impl<'a> CountVariation<'a> {
@@ -176,6 +180,8 @@ impl<'a> CountVariation<'a> {
             Variation::Group60Var4 => Ok(CountVariation::Group60Var4),
             Variation::Group111(0) => Ok(CountVariation::Group111Var0),
             Variation::Group111(x) => Ok(CountVariation::Group111VarX(x)),
+            Variation::Group120Var3 => Ok(CountVariation::Group120Var3(CountSequence::parse(count, cursor)?)),
+            Variation::Group120Var4 => Ok(CountVariation::Group120Var4(CountSequence::parse(count, cursor)?)),
             _ => Err(ObjectParseError::InvalidQualifierForVariation(v, qualifier)),
         }
     }
@@ -233,6 +239,8 @@ impl<'a> CountVariation<'a> {
             CountVariation::Group60Var4 => Ok(()),
             CountVariation::Group111Var0 => Ok(()),
             CountVariation::Group111VarX(_) => Ok(()),
+            CountVariation::Group120Var3(seq) => format_count_of_items(f, seq.iter()),
+            CountVariation::Group120Var4(seq) => format_count_of_items(f, seq.iter()),
         }
     }
 }
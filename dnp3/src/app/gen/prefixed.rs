@@ -97,6 +97,10 @@ pub(crate) enum PrefixedVariation<'a, I> where I : FixedSize + Index + std::fmt:
     Group42Var7(CountSequence<'a, Prefix<I, Group42Var7>>),
     /// Analog Output Event - Double-precision With Flag and Time
     Group42Var8(CountSequence<'a, Prefix<I, Group42Var8>>),
+    /// Time and Date - Indexed absolute time and long interval
+    Group50Var4(CountSequence<'a, Prefix<I, Group50Var4>>),
+    /// Unsigned Integer - 8-bit
+    Group102Var1(CountSequence<'a, Prefix<I, Group102Var1>>),
     /// Octet String Event - Sized by variation
     Group111VarX(u8, PrefixedBytesSequence<'a, I>),
 }
@@ -141,6 +145,8 @@ impl<'a, I> PrefixedVariation<'a, I> where I : FixedSize + Index + std::fmt::Dis
             Variation::Group42Var6 => Ok(PrefixedVariation::Group42Var6(CountSequence::parse(count, cursor)?)),
             Variation::Group42Var7 => Ok(PrefixedVariation::Group42Var7(CountSequence::parse(count, cursor)?)),
             Variation::Group42Var8 => Ok(PrefixedVariation::Group42Var8(CountSequence::parse(count, cursor)?)),
+            Variation::Group50Var4 => Ok(PrefixedVariation::Group50Var4(CountSequence::parse(count, cursor)?)),
+            Variation::Group102Var1 => Ok(PrefixedVariation::Group102Var1(CountSequence::parse(count, cursor)?)),
             Variation::Group111(0) => Err(ObjectParseError::ZeroLengthOctetData),
             Variation::Group111(x) => Ok(PrefixedVariation::Group111VarX(x, PrefixedBytesSequence::parse(x, count, cursor)?)),
             _ => Err(ObjectParseError::InvalidQualifierForVariation(v, I::COUNT_AND_PREFIX_QUALIFIER)),
@@ -186,6 +192,8 @@ impl<'a, I> PrefixedVariation<'a, I> where I : FixedSize + Index + std::fmt::Dis
             PrefixedVariation::Group42Var6(seq) => format_prefixed_items(f, seq.iter()),
             PrefixedVariation::Group42Var7(seq) => format_prefixed_items(f, seq.iter()),
             PrefixedVariation::Group42Var8(seq) => format_prefixed_items(f, seq.iter()),
+            PrefixedVariation::Group50Var4(seq) => format_prefixed_items(f, seq.iter()),
+            PrefixedVariation::Group102Var1(seq) => format_prefixed_items(f, seq.iter()),
             PrefixedVariation::Group111VarX(_,seq) =>  format_indexed_items(f, seq.iter()),
         }
     }
@@ -431,6 +439,20 @@ impl<'a, I> PrefixedVariation<'a, I> where I : FixedSize + Index + std::fmt::Dis
                 );
                 true
             }
+            PrefixedVariation::Group50Var4(seq) => {
+                handler.handle_indexed_time(
+                    self.get_header_info(),
+                    &mut seq.iter().map(|x| (x.value.into(), x.index.widen_to_u16()))
+                );
+                true
+            }
+            PrefixedVariation::Group102Var1(seq) => {
+                handler.handle_unsigned_integer(
+                    self.get_header_info(),
+                    &mut seq.iter().map(|x| (x.value.into(), x.index.widen_to_u16()))
+                );
+                true
+            }
             PrefixedVariation::Group111VarX(_, seq) => {
                 handler.handle_octet_string(
                     self.get_header_info(),
@@ -480,6 +502,8 @@ impl<'a, I> PrefixedVariation<'a, I> where I : FixedSize + Index + std::fmt::Dis
             PrefixedVariation::Group42Var6(_) => HeaderInfo::new(Variation::Group42Var6, I::COUNT_AND_PREFIX_QUALIFIER),
             PrefixedVariation::Group42Var7(_) => HeaderInfo::new(Variation::Group42Var7, I::COUNT_AND_PREFIX_QUALIFIER),
             PrefixedVariation::Group42Var8(_) => HeaderInfo::new(Variation::Group42Var8, I::COUNT_AND_PREFIX_QUALIFIER),
+            PrefixedVariation::Group50Var4(_) => HeaderInfo::new(Variation::Group50Var4, I::COUNT_AND_PREFIX_QUALIFIER),
+            PrefixedVariation::Group102Var1(_) => HeaderInfo::new(Variation::Group102Var1, I::COUNT_AND_PREFIX_QUALIFIER),
             PrefixedVariation::Group111VarX(x, _) =>  HeaderInfo::new(Variation::Group111(*x), I::COUNT_AND_PREFIX_QUALIFIER),
         }
     }
@@ -18,10 +18,17 @@ use crate::app::parse::parser::*;
 use crate::app::parse::bytes::RangedBytesSequence;
 use crate::app::parse::bit::{BitSequence, DoubleBitSequence};
 use crate::master::{ReadHandler, HeaderInfo};
-use crate::app::ObjectParseError;
+use crate::app::{AttrParseError, AttrValue, Attribute, ObjectParseError};
 
 #[derive(Debug, PartialEq)]
 pub(crate) enum RangedVariation<'a> {
+    /// Device Attributes - Specific attribute
+    Group0(u8),
+    /// Device Attributes - Non-specific all attributes request
+    Group0Var254,
+    /// Device Attributes - List of attribute variations
+    Group0Var255,
+    Group0VarX(Attribute<'a>),
     /// Binary Input - Any Variation
     Group1Var0,
     /// Binary Input - Packed Format
@@ -88,8 +95,14 @@ pub(crate) enum RangedVariation<'a> {
     Group40Var3(RangedSequence<'a, Group40Var3>),
     /// Analog Output Status - Double-precision With Flag
     Group40Var4(RangedSequence<'a, Group40Var4>),
+    /// Time and Date - Indexed absolute time and long interval
+    Group50Var4(RangedSequence<'a, Group50Var4>),
     /// Internal Indications - Packed Format
     Group80Var1(BitSequence<'a>),
+    /// Unsigned Integer - Any Variation
+    Group102Var0,
+    /// Unsigned Integer - 8-bit
+    Group102Var1(RangedSequence<'a, Group102Var1>),
     /// Octet String - Sized by variation
     Group110Var0,
     Group110VarX(u8, RangedBytesSequence<'a>),
@@ -98,6 +111,8 @@ pub(crate) enum RangedVariation<'a> {
 impl<'a> RangedVariation<'a> {
     pub(crate) fn parse_non_read(v: Variation, qualifier: QualifierCode, range: Range, cursor: &mut ReadCursor<'a>) -> Result<RangedVariation<'a>, ObjectParseError> {
         match v {
+            Variation::Group0(x) => Ok(RangedVariation::Group0VarX(Self::parse_attribute(x, range, cursor)?)),
+            Variation::Group0Var255 => Ok(RangedVariation::Group0VarX(Self::parse_attribute(255, range, cursor)?)),
             Variation::Group1Var0 => Ok(RangedVariation::Group1Var0),
             Variation::Group1Var1 => Ok(RangedVariation::Group1Var1(BitSequence::parse(range, cursor)?)),
             Variation::Group1Var2 => Ok(RangedVariation::Group1Var2(RangedSequence::parse(range, cursor)?)),
@@ -131,7 +146,10 @@ impl<'a> RangedVariation<'a> {
             Variation::Group40Var2 => Ok(RangedVariation::Group40Var2(RangedSequence::parse(range, cursor)?)),
             Variation::Group40Var3 => Ok(RangedVariation::Group40Var3(RangedSequence::parse(range, cursor)?)),
             Variation::Group40Var4 => Ok(RangedVariation::Group40Var4(RangedSequence::parse(range, cursor)?)),
+            Variation::Group50Var4 => Ok(RangedVariation::Group50Var4(RangedSequence::parse(range, cursor)?)),
             Variation::Group80Var1 => Ok(RangedVariation::Group80Var1(BitSequence::parse(range, cursor)?)),
+            Variation::Group102Var0 => Ok(RangedVariation::Group102Var0),
+            Variation::Group102Var1 => Ok(RangedVariation::Group102Var1(RangedSequence::parse(range, cursor)?)),
             Variation::Group110(0) => Err(ObjectParseError::ZeroLengthOctetData),
             Variation::Group110(x) => {
                 Ok(RangedVariation::Group110VarX(x, RangedBytesSequence::parse(x, range.get_start(), range.get_count(), cursor)?))
@@ -140,8 +158,19 @@ impl<'a> RangedVariation<'a> {
         }
     }
     
+    fn parse_attribute(variation: u8, range: Range, cursor: &mut ReadCursor<'a>) -> Result<Attribute<'a>, ObjectParseError> {
+        // each attribute is sent in its own header where start == stop == set
+        if range.get_count() != 1 {
+            return Err(AttrParseError::BadCount(range.get_count()).into());
+        }
+        Ok(Attribute::new(range.get_start(), variation, AttrValue::parse(cursor)?))
+    }
+    
     pub(crate) fn parse_read(v: Variation, qualifier: QualifierCode) -> Result<RangedVariation<'a>, ObjectParseError> {
         match v {
+            Variation::Group0(x) => Ok(RangedVariation::Group0(x)),
+            Variation::Group0Var254 => Ok(RangedVariation::Group0Var254),
+            Variation::Group0Var255 => Ok(RangedVariation::Group0Var255),
             Variation::Group1Var0 => Ok(RangedVariation::Group1Var0),
             Variation::Group1Var1 => Ok(RangedVariation::Group1Var1(BitSequence::empty())),
             Variation::Group1Var2 => Ok(RangedVariation::Group1Var2(RangedSequence::empty())),
@@ -175,7 +204,10 @@ impl<'a> RangedVariation<'a> {
             Variation::Group40Var2 => Ok(RangedVariation::Group40Var2(RangedSequence::empty())),
             Variation::Group40Var3 => Ok(RangedVariation::Group40Var3(RangedSequence::empty())),
             Variation::Group40Var4 => Ok(RangedVariation::Group40Var4(RangedSequence::empty())),
+            Variation::Group50Var4 => Ok(RangedVariation::Group50Var4(RangedSequence::empty())),
             Variation::Group80Var1 => Ok(RangedVariation::Group80Var1(BitSequence::empty())),
+            Variation::Group102Var0 => Ok(RangedVariation::Group102Var0),
+            Variation::Group102Var1 => Ok(RangedVariation::Group102Var1(RangedSequence::empty())),
             Variation::Group110(0) => Ok(RangedVariation::Group110Var0),
             _ => Err(ObjectParseError::InvalidQualifierForVariation(v, qualifier)),
         }
@@ -183,6 +215,10 @@ impl<'a> RangedVariation<'a> {
     
     pub(crate) fn format_objects(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
+            RangedVariation::Group0(_) => Ok(()),
+            RangedVariation::Group0Var254 => Ok(()),
+            RangedVariation::Group0Var255 => Ok(()),
+            RangedVariation::Group0VarX(attr) => write!(f, "\n{}", attr),
             RangedVariation::Group1Var0 => Ok(()),
             RangedVariation::Group1Var1(seq) => format_indexed_items(f, seq.iter()),
             RangedVariation::Group1Var2(seq) => format_indexed_items(f, seq.iter()),
@@ -216,7 +252,10 @@ impl<'a> RangedVariation<'a> {
             RangedVariation::Group40Var2(seq) => format_indexed_items(f, seq.iter()),
             RangedVariation::Group40Var3(seq) => format_indexed_items(f, seq.iter()),
             RangedVariation::Group40Var4(seq) => format_indexed_items(f, seq.iter()),
+            RangedVariation::Group50Var4(seq) => format_indexed_items(f, seq.iter()),
             RangedVariation::Group80Var1(seq) => format_indexed_items(f, seq.iter()),
+            RangedVariation::Group102Var0 => Ok(()),
+            RangedVariation::Group102Var1(seq) => format_indexed_items(f, seq.iter()),
             RangedVariation::Group110Var0 => Ok(()),
             RangedVariation::Group110VarX(_,seq) =>  format_indexed_items(f, seq.iter()),
         }
@@ -224,6 +263,22 @@ impl<'a> RangedVariation<'a> {
     
     pub(crate) fn extract_measurements_to(&self, qualifier: QualifierCode, handler: &mut dyn ReadHandler) -> bool {
         match self {
+            RangedVariation::Group0(_) => {
+                false
+            }
+            RangedVariation::Group0Var254 => {
+                false
+            }
+            RangedVariation::Group0Var255 => {
+                false
+            }
+            RangedVariation::Group0VarX(attr) => {
+                handler.handle_device_attribute(
+                    HeaderInfo::new(self.variation(), qualifier),
+                    *attr
+                );
+                true
+            }
             RangedVariation::Group1Var0 => {
                 false // qualifier 0x06
             }
@@ -427,9 +482,26 @@ impl<'a> RangedVariation<'a> {
                 );
                 true
             }
+            RangedVariation::Group50Var4(seq) => {
+                handler.handle_indexed_time(
+                    HeaderInfo::new(self.variation(), qualifier),
+                    &mut seq.iter().map(|(v,i)| (v.into(), i))
+                );
+                true
+            }
             RangedVariation::Group80Var1(_) => {
                 false // internal indications
             }
+            RangedVariation::Group102Var0 => {
+                false // qualifier 0x06
+            }
+            RangedVariation::Group102Var1(seq) => {
+                handler.handle_unsigned_integer(
+                    HeaderInfo::new(self.variation(), qualifier),
+                    &mut seq.iter().map(|(v,i)| (v.into(), i))
+                );
+                true
+            }
             RangedVariation::Group110Var0 => {
                 false
             }
@@ -445,6 +517,13 @@ impl<'a> RangedVariation<'a> {
     
     pub(crate) fn variation(&self) -> Variation {
         match self {
+            RangedVariation::Group0(x) => Variation::Group0(*x),
+            RangedVariation::Group0Var254 => Variation::Group0Var254,
+            RangedVariation::Group0Var255 => Variation::Group0Var255,
+            RangedVariation::Group0VarX(attr) => match attr.variation {
+                255 => Variation::Group0Var255,
+                x => Variation::Group0(x),
+            },
             RangedVariation::Group1Var0 => Variation::Group1Var0,
             RangedVariation::Group1Var1(_) => Variation::Group1Var1,
             RangedVariation::Group1Var2(_) => Variation::Group1Var2,
@@ -478,7 +557,10 @@ impl<'a> RangedVariation<'a> {
             RangedVariation::Group40Var2(_) => Variation::Group40Var2,
             RangedVariation::Group40Var3(_) => Variation::Group40Var3,
             RangedVariation::Group40Var4(_) => Variation::Group40Var4,
+            RangedVariation::Group50Var4(_) => Variation::Group50Var4,
             RangedVariation::Group80Var1(_) => Variation::Group80Var1,
+            RangedVariation::Group102Var0 => Variation::Group102Var0,
+            RangedVariation::Group102Var1(_) => Variation::Group102Var1,
             RangedVariation::Group110Var0 => Variation::Group110(0),
             RangedVariation::Group110VarX(x, _) => Variation::Group110(*x),
         }
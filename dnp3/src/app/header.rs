@@ -10,6 +10,7 @@ use crate::util::cursor::{ReadCursor, ReadError, WriteCursor, WriteError};
 
 /// Control field in the application-layer header
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ControlField {
     /// FIR bit - set if the first fragment in a multi-fragmented response
     pub fir: bool,
@@ -136,6 +137,7 @@ impl ControlField {
 
 /// Internal Indications Byte #1
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Iin1 {
     /// underlying value for IIN1
     pub value: u8,
@@ -143,6 +145,7 @@ pub struct Iin1 {
 
 /// Internal Indications Byte #2
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Iin2 {
     /// underlying value for IIN2
     pub value: u8,
@@ -150,6 +153,7 @@ pub struct Iin2 {
 
 /// Internal Indications (2 bytes)
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Iin {
     /// IIN byte #1
     pub iin1: Iin1,
@@ -502,6 +506,7 @@ impl Iin {
 
 /// application-layer header for requests
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RequestHeader {
     /// control field
     pub control: ControlField,
@@ -511,6 +516,7 @@ pub struct RequestHeader {
 
 /// Only 2 function codes allowed in responses
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ResponseFunction {
     /// (solicited) response (0x81)
     Response,
@@ -520,6 +526,7 @@ pub enum ResponseFunction {
 
 /// application-layer header for responses
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ResponseHeader {
     /// control field
     pub control: ControlField,
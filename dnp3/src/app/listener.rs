@@ -18,3 +18,35 @@ impl NullListener {
 impl<T> Listener<T> for NullListener {
     fn update(&mut self, _value: T) {}
 }
+
+/// Reason a connection or port was closed, reported to state listeners
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum DisconnectReason {
+    /// an I/O error occurred, including the remote end closing the connection
+    Io(std::io::ErrorKind),
+    /// a malformed link-layer frame was received on a channel that closes on link errors
+    MalformedFrame,
+    /// an internal error occurred in the library
+    Internal,
+    /// communication was disabled
+    Disabled,
+    /// the connection was replaced by a newer connection
+    Replaced,
+    /// the connection to a fail-over endpoint was closed to try the main endpoint again
+    ReturnToMain,
+    /// a dial-up connection was closed after the idle timeout elapsed without tasks
+    IdleTimeout,
+    /// the channel was shut down
+    Shutdown,
+}
+
+impl From<crate::link::error::LinkError> for DisconnectReason {
+    fn from(err: crate::link::error::LinkError) -> Self {
+        match err {
+            crate::link::error::LinkError::Stdio(kind) => DisconnectReason::Io(kind),
+            crate::link::error::LinkError::BadFrame(_) => DisconnectReason::MalformedFrame,
+            crate::link::error::LinkError::BadLogic(_) => DisconnectReason::Internal,
+        }
+    }
+}
@@ -1,12 +1,14 @@
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use crate::app::types::Timestamp;
+use crate::app::variations::Group102Var1;
 use crate::util::bit::bits;
 use crate::util::bit::BitMask;
 use crate::util::bit::Bitfield;
 
 /// Enumeration modeling two stables states and an in-transit state
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DoubleBit {
     /// Transitioning between end conditions
     Intermediate,
@@ -20,6 +22,7 @@ pub enum DoubleBit {
 
 /// A DNP3 time value that may be Synchronized or NotSynchronized
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Time {
     /// The timestamp is UTC synchronized at the remote device
     Synchronized(Timestamp),
@@ -50,6 +53,7 @@ impl Time {
 /// should refer to the standard to determine what flag values
 /// correspond to each type.
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Flags {
     /// underlying bitmask
     pub value: u8,
@@ -125,10 +129,26 @@ impl Time {
             Time::NotSynchronized(ts) => *ts,
         }
     }
+
+    /// convert the `Time` to a `SystemTime` discarding synchronization information
+    pub fn to_system_time(&self) -> SystemTime {
+        self.timestamp().to_system_time()
+    }
+}
+
+/// formatted as ISO-8601 in UTC, followed by a marker if the time is not synchronized
+impl std::fmt::Display for Time {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Time::Synchronized(ts) => write!(f, "{}", ts),
+            Time::NotSynchronized(ts) => write!(f, "{} (unsynchronized)", ts),
+        }
+    }
 }
 
 /// Measurement type corresponding to groups 1 and 2
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Binary {
     /// value of the type
     pub value: bool,
@@ -151,6 +171,7 @@ impl Binary {
 
 /// Measurement type corresponding to groups 3 and 4
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DoubleBitBinary {
     /// value of the type
     pub value: DoubleBit,
@@ -173,6 +194,7 @@ impl DoubleBitBinary {
 
 /// Measurement type corresponding to groups 10 and 11
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BinaryOutputStatus {
     /// value of the type
     pub value: bool,
@@ -195,6 +217,7 @@ impl BinaryOutputStatus {
 
 /// Measurement type corresponding to groups 20 and 22
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Counter {
     /// value of the type
     pub value: u32,
@@ -217,6 +240,7 @@ impl Counter {
 
 /// Measurement type corresponding to groups 21 and 23
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrozenCounter {
     /// value of the type
     pub value: u32,
@@ -239,6 +263,7 @@ impl FrozenCounter {
 
 /// Measurement type corresponding to groups 30 and 32
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Analog {
     /// value of the type
     pub value: f64,
@@ -541,6 +566,7 @@ pub(crate) trait AnalogConversions {
 
 /// Measurement type corresponding to groups 40 and 42
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnalogOutputStatus {
     /// value of the type
     pub value: f64,
@@ -561,6 +587,36 @@ impl AnalogOutputStatus {
     }
 }
 
+/// Measurement type corresponding to group 102
+///
+/// Unsigned integers are reported without flags or time. The outstation reports static values
+/// using g102v1 and events using g102v1 with an index prefix.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnsignedInteger {
+    /// value of the type
+    pub value: u8,
+}
+
+impl UnsignedInteger {
+    /// construct an `UnsignedInteger` from its value
+    pub fn new(value: u8) -> Self {
+        Self { value }
+    }
+}
+
+impl From<Group102Var1> for UnsignedInteger {
+    fn from(v: Group102Var1) -> Self {
+        Self::new(v.value)
+    }
+}
+
+impl ToVariation<Group102Var1> for UnsignedInteger {
+    fn to_variation(&self) -> Group102Var1 {
+        Group102Var1 { value: self.value }
+    }
+}
+
 /// Octet string point type corresponding to groups 110 and 111
 ///
 /// Octet strings can only hold from 1 to 255 octets. Zero-length
@@ -628,6 +684,68 @@ pub enum OctetStringError {
     MoreThan255Octets,
 }
 
+impl std::fmt::Display for OctetStringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OctetStringError::ZeroLength => f.write_str("octet strings may not be zero length"),
+            OctetStringError::MoreThan255Octets => {
+                f.write_str("octet strings may not contain more than 255 octets")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OctetStringError {}
+
+// serialized as the bytes of the value since the backing array is larger than serde supports
+#[cfg(feature = "serde")]
+impl serde::Serialize for OctetString {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_bytes(self.value())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for OctetString {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct OctetStringVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for OctetStringVisitor {
+            type Value = OctetString;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("between 1 and 255 octets")
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                OctetString::new(value).map_err(E::custom)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut value = Vec::new();
+                while let Some(x) = seq.next_element::<u8>()? {
+                    value.push(x);
+                }
+                self.visit_bytes(&value)
+            }
+        }
+
+        deserializer.deserialize_bytes(OctetStringVisitor)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -658,6 +776,43 @@ mod tests {
         assert_eq!(&[0x00], OctetString::default().value());
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn octet_string_serde_round_trip() {
+        let octet_string = OctetString::new(&[0, 1, 2]).unwrap();
+        let json = serde_json::to_string(&octet_string).unwrap();
+        assert_eq!(json, "[0,1,2]");
+        assert_eq!(
+            serde_json::from_str::<OctetString>(&json).unwrap(),
+            octet_string
+        );
+        assert!(serde_json::from_str::<OctetString>("[]").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn analog_serde_round_trip() {
+        let analog = Analog::new(4.5, Flags::ONLINE, Time::synchronized(1000));
+        let json = serde_json::to_string(&analog).unwrap();
+        assert_eq!(
+            json,
+            r#"{"value":4.5,"flags":{"value":1},"time":{"Synchronized":1000}}"#
+        );
+        assert_eq!(serde_json::from_str::<Analog>(&json).unwrap(), analog);
+    }
+
+    #[test]
+    fn time_display_indicates_synchronization() {
+        assert_eq!(
+            format!("{}", Time::synchronized(1500)),
+            "1970-01-01T00:00:01.500Z"
+        );
+        assert_eq!(
+            format!("{}", Time::not_synchronized(0)),
+            "1970-01-01T00:00:00.000Z (unsynchronized)"
+        );
+    }
+
     #[test]
     fn flag_bit_or_works() {
         let flags = Flags::ONLINE | Flags::LOCAL_FORCED;
@@ -1,14 +1,23 @@
+pub use anomaly::*;
 pub use app_enums::*;
+pub use attr::*;
+pub use buffer_pool::*;
 pub use bytes::*;
+pub use clock::*;
+pub use config_error::*;
+pub use events::*;
 pub use header::*;
 pub use listener::*;
 pub use parse_error::*;
+pub use parse_mode::*;
 pub use retry::*;
 pub use sequence::*;
 pub use shutdown::*;
+pub use spawner::*;
 pub use timeout::*;
 pub use types::*;
 pub use variations::Variation;
+pub use vendor::*;
 
 /// Types used for making binary and analog output control requests
 pub mod control {
@@ -30,24 +39,34 @@ impl EndpointType {
     }
 }
 
+mod anomaly;
+mod attr;
+/// secure authentication objects (g120), currently only parsed
+pub(crate) mod auth;
+mod buffer_pool;
 mod bytes;
+mod clock;
+mod config_error;
 
 mod control_types;
 #[rustfmt::skip]
 mod app_enums;
 mod control_enums;
+mod events;
 mod extensions;
 mod header;
 mod listener;
 /// measurement types, e.g. Binary, Analog, Counter, etc
 pub mod measurement;
-/// application layer parser
-pub(crate) mod parse;
+/// application layer parser, usable outside of a master or outstation to decode captured fragments
+pub mod parse;
 mod retry;
 mod sequence;
 mod shutdown;
+mod spawner;
 mod timeout;
 mod types;
+mod vendor;
 
 #[rustfmt::skip]
 pub(crate) mod variations;
@@ -55,6 +74,7 @@ pub(crate) mod variations;
 pub(crate) mod format;
 /// errors associated with parsing the application layer
 mod parse_error;
+mod parse_mode;
 
 #[rustfmt::skip]
 pub(crate) mod gen {
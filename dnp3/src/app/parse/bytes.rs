@@ -59,6 +59,16 @@ impl<'a> RangedBytesSequence<'a> {
         })
     }
 
+    /// zero-length octet strings (variation 0) occupy no bytes and are reported as empty values
+    pub(crate) fn zero_length(index: u16, count: usize) -> Self {
+        RangedBytesSequence {
+            bytes: &[],
+            index,
+            size: 0,
+            count,
+        }
+    }
+
     pub(crate) fn iter(&self) -> RangedBytesIterator<'a> {
         RangedBytesIterator {
             cursor: ReadCursor::new(self.bytes),
@@ -92,6 +102,19 @@ where
         })
     }
 
+    /// zero-length octet strings (variation 0) consist of only the index prefixes
+    pub(crate) fn parse_zero_length(
+        count: u16,
+        cursor: &mut ReadCursor<'a>,
+    ) -> Result<Self, ObjectParseError> {
+        Ok(PrefixedBytesSequence {
+            bytes: cursor.read_bytes(T::SIZE as usize * count as usize)?,
+            size: 0,
+            count: count as usize,
+            phantom: std::marker::PhantomData {},
+        })
+    }
+
     pub(crate) fn iter(&self) -> PrefixedBytesIterator<'a, T> {
         PrefixedBytesIterator {
             cursor: ReadCursor::new(self.bytes),
@@ -106,6 +129,9 @@ impl<'a> Iterator for RangedBytesIterator<'a> {
     type Item = (Bytes<'a>, u16);
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
         self.cursor.read_bytes(self.size).ok().map(|b| {
             let index = self.index;
             self.index = self.index.saturating_add(1);
@@ -0,0 +1,241 @@
+use std::fmt::Formatter;
+
+use crate::app::parse::parser::{
+    HeaderCollection, ParsedFragment, ParsedHeader, ParsedHeaderIterator, UnknownObjects,
+};
+use crate::app::*;
+use crate::decode::AppDecodeLevel;
+use crate::master::extract::extract_measurements;
+use crate::master::{ReadHandler, ReadType};
+
+/// An application-layer fragment parsed outside of a master or outstation session
+///
+/// This is the parser used internally by the library, exposed so that analyzers, test tools,
+/// and replay utilities can decode captured fragments.
+#[derive(Copy, Clone)]
+pub struct Fragment<'a> {
+    inner: ParsedFragment<'a>,
+}
+
+/// Iterator over the object headers of a [Fragment]
+#[derive(Copy, Clone)]
+pub struct Headers<'a> {
+    inner: ParsedHeaderIterator<'a>,
+}
+
+/// An object header of a [Fragment] along with its objects
+pub struct Header<'a> {
+    inner: ParsedHeader<'a>,
+}
+
+/// Errors that occur when extracting measurements from a [Fragment]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExtractError {
+    /// the fragment is not a valid response
+    BadResponse(ResponseValidationError),
+    /// the object headers of the response are malformed
+    BadObjects(ObjectParseError),
+}
+
+impl<'a> Fragment<'a> {
+    /// Parse a fragment in [ParseMode::Strict]
+    ///
+    /// Only the application-layer header must be valid for the fragment to be returned. Errors in
+    /// the object headers are reported by [Fragment::headers].
+    pub fn parse(fragment: &'a [u8]) -> Result<Self, HeaderParseError> {
+        Ok(Self {
+            inner: ParsedFragment::parse(fragment)?,
+        })
+    }
+
+    /// Parse a fragment, handling deviations from the specification in the object headers as
+    /// specified by the mode
+    pub fn parse_with_mode(fragment: &'a [u8], mode: ParseMode) -> Result<Self, HeaderParseError> {
+        let mut inner = ParsedFragment::parse(fragment)?;
+        if inner.objects.is_err() && mode.is_lenient() {
+            inner.objects = HeaderCollection::parse_with_mode(
+                inner.function,
+                inner.raw_objects,
+                UnknownObjects::REJECT,
+                mode,
+            );
+        }
+        Ok(Self { inner })
+    }
+
+    /// Control field of the fragment
+    pub fn control(&self) -> ControlField {
+        self.inner.control
+    }
+
+    /// Function code of the fragment
+    pub fn function(&self) -> FunctionCode {
+        self.inner.function
+    }
+
+    /// Internal indications of the fragment if it is a response
+    pub fn iin(&self) -> Option<Iin> {
+        self.inner.iin
+    }
+
+    /// Raw bytes of the entire fragment
+    pub fn raw(&self) -> &'a [u8] {
+        self.inner.raw_fragment
+    }
+
+    /// Raw bytes of the object headers following the application-layer header
+    pub fn raw_objects(&self) -> &'a [u8] {
+        self.inner.raw_objects
+    }
+
+    /// Iterator over the object headers or the error that prevented them from being parsed
+    pub fn headers(&self) -> Result<Headers<'a>, ObjectParseError> {
+        self.inner.objects.map(|objects| Headers {
+            inner: objects.iter_all(),
+        })
+    }
+
+    /// Pass the measurements of a response to a [ReadHandler] exactly as a master would
+    ///
+    /// Unsolicited responses are reported as [ReadType::Unsolicited] and solicited responses as
+    /// [ReadType::SinglePoll].
+    pub fn extract_measurements(&self, handler: &mut dyn ReadHandler) -> Result<(), ExtractError> {
+        let response = self.inner.to_response()?;
+        let objects = response.objects?;
+        let read_type = if response.header.function.is_unsolicited() {
+            ReadType::Unsolicited
+        } else {
+            ReadType::SinglePoll
+        };
+        extract_measurements(read_type, response.header, objects, None, None, handler);
+        Ok(())
+    }
+
+    /// Format the fragment in the same way as the library's protocol decoding
+    pub fn display(&self, level: AppDecodeLevel) -> impl std::fmt::Display + 'a {
+        self.inner.display(level)
+    }
+}
+
+impl<'a> Iterator for Headers<'a> {
+    type Item = Header<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|inner| Header { inner })
+    }
+}
+
+impl<'a> Header<'a> {
+    /// Group and variation of the header
+    pub fn group_and_variation(&self) -> (u8, u8) {
+        match &self.inner {
+            ParsedHeader::Known(header) => header.variation.to_group_and_var(),
+            ParsedHeader::Unknown(header) => (header.group, header.variation),
+            ParsedHeader::Vendor(header) => (header.group, header.variation),
+        }
+    }
+
+    /// Variation of the header if it is defined by the standard
+    pub fn variation(&self) -> Option<Variation> {
+        match &self.inner {
+            ParsedHeader::Known(header) => Some(header.variation),
+            ParsedHeader::Unknown(_) | ParsedHeader::Vendor(_) => None,
+        }
+    }
+
+    /// Qualifier code of the header
+    pub fn qualifier(&self) -> QualifierCode {
+        match &self.inner {
+            ParsedHeader::Known(header) => header.details.qualifier(),
+            ParsedHeader::Unknown(header) => header.qualifier,
+            ParsedHeader::Vendor(header) => header.qualifier,
+        }
+    }
+
+    /// Objects of a vendor-specific header
+    pub fn vendor(&self) -> Option<&VendorHeader<'a>> {
+        match &self.inner {
+            ParsedHeader::Vendor(header) => Some(header),
+            _ => None,
+        }
+    }
+
+    /// Objects of a header with an unknown group and variation
+    pub fn unknown(&self) -> Option<&UnknownHeader<'a>> {
+        match &self.inner {
+            ParsedHeader::Unknown(header) => Some(header),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Header<'_> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        self.inner.format(true, f)
+    }
+}
+
+impl From<ResponseValidationError> for ExtractError {
+    fn from(err: ResponseValidationError) -> Self {
+        ExtractError::BadResponse(err)
+    }
+}
+
+impl From<ObjectParseError> for ExtractError {
+    fn from(err: ObjectParseError) -> Self {
+        ExtractError::BadObjects(err)
+    }
+}
+
+impl std::fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ExtractError::BadResponse(err) => write!(f, "bad response: {}", err),
+            ExtractError::BadObjects(err) => write!(f, "bad objects: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+#[cfg(test)]
+mod test {
+    use crate::master::NullReadHandler;
+
+    use super::*;
+
+    // response with g1v2 for indices 1 and 2
+    const RESPONSE: &[u8] = &[
+        0xC0, 0x81, 0x00, 0x00, 0x01, 0x02, 0x00, 0x01, 0x02, 0x81, 0x01,
+    ];
+
+    #[test]
+    fn iterates_headers_of_response() {
+        let fragment = Fragment::parse(RESPONSE).unwrap();
+
+        assert_eq!(fragment.function(), FunctionCode::Response);
+        assert_eq!(fragment.iin(), Some(Iin::default()));
+
+        let headers: Vec<Header> = fragment.headers().unwrap().collect();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].variation(), Some(Variation::Group1Var2));
+        assert_eq!(headers[0].group_and_variation(), (1, 2));
+        assert_eq!(headers[0].qualifier(), QualifierCode::Range8);
+    }
+
+    #[test]
+    fn only_responses_contain_measurements() {
+        let mut handler = NullReadHandler;
+
+        let response = Fragment::parse(RESPONSE).unwrap();
+        assert_eq!(response.extract_measurements(&mut handler), Ok(()));
+
+        let request = Fragment::parse(&[0xC0, 0x01, 0x3C, 0x02, 0x06]).unwrap();
+        assert_eq!(
+            request.extract_measurements(&mut handler),
+            Err(ExtractError::BadResponse(
+                ResponseValidationError::UnexpectedFunction(FunctionCode::Read)
+            ))
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use crate::app::auth::*;
+use crate::app::parse_error::ObjectParseError;
+use crate::app::variations::Variation;
+use crate::app::{Bytes, QualifierCode};
+use crate::util::cursor::ReadCursor;
+
+/// variations that may be used with the free-format qualifier (0x5B)
+#[derive(Debug, PartialEq)]
+pub(crate) enum FreeFormatVariation<'a> {
+    Group90Var1(Bytes<'a>),
+    Group120Var1(Challenge<'a>),
+    Group120Var2(Reply<'a>),
+    Group120Var5(SessionKeyStatus<'a>),
+    Group120Var6(SessionKeyChange<'a>),
+    Group120Var7(AuthError<'a>),
+    Group120Var8(UserCertificate<'a>),
+    Group120Var9(Bytes<'a>),
+    Group120Var10(UserStatusChange<'a>),
+    Group120Var11(UpdateKeyChangeRequest<'a>),
+    Group120Var12(UpdateKeyChangeReply<'a>),
+    Group120Var13(UpdateKeyChange<'a>),
+    Group120Var14(Bytes<'a>),
+    Group120Var15(Bytes<'a>),
+}
+
+impl<'a> FreeFormatVariation<'a> {
+    pub(crate) fn parse(
+        v: Variation,
+        size: u16,
+        cursor: &mut ReadCursor<'a>,
+    ) -> Result<FreeFormatVariation<'a>, ObjectParseError> {
+        let mut object = || cursor.read_bytes(size as usize);
+        let variation = match v {
+            Variation::Group90Var1 => FreeFormatVariation::Group90Var1(Bytes::new(object()?)),
+            Variation::Group120Var1 => {
+                FreeFormatVariation::Group120Var1(Challenge::parse(object()?)?)
+            }
+            Variation::Group120Var2 => FreeFormatVariation::Group120Var2(Reply::parse(object()?)?),
+            Variation::Group120Var5 => {
+                FreeFormatVariation::Group120Var5(SessionKeyStatus::parse(object()?)?)
+            }
+            Variation::Group120Var6 => {
+                FreeFormatVariation::Group120Var6(SessionKeyChange::parse(object()?)?)
+            }
+            Variation::Group120Var7 => {
+                FreeFormatVariation::Group120Var7(AuthError::parse(object()?)?)
+            }
+            Variation::Group120Var8 => {
+                FreeFormatVariation::Group120Var8(UserCertificate::parse(object()?)?)
+            }
+            Variation::Group120Var9 => FreeFormatVariation::Group120Var9(Bytes::new(object()?)),
+            Variation::Group120Var10 => {
+                FreeFormatVariation::Group120Var10(UserStatusChange::parse(object()?)?)
+            }
+            Variation::Group120Var11 => {
+                FreeFormatVariation::Group120Var11(UpdateKeyChangeRequest::parse(object()?)?)
+            }
+            Variation::Group120Var12 => {
+                FreeFormatVariation::Group120Var12(UpdateKeyChangeReply::parse(object()?)?)
+            }
+            Variation::Group120Var13 => {
+                FreeFormatVariation::Group120Var13(UpdateKeyChange::parse(object()?)?)
+            }
+            Variation::Group120Var14 => FreeFormatVariation::Group120Var14(Bytes::new(object()?)),
+            Variation::Group120Var15 => FreeFormatVariation::Group120Var15(Bytes::new(object()?)),
+            _ => {
+                return Err(ObjectParseError::InvalidQualifierForVariation(
+                    v,
+                    QualifierCode::FreeFormat16,
+                ))
+            }
+        };
+
+        Ok(variation)
+    }
+
+    pub(crate) fn format_objects(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FreeFormatVariation::Group90Var1(bytes) => write!(f, "\n{}", bytes),
+            FreeFormatVariation::Group120Var1(x) => write!(f, "\n{}", x),
+            FreeFormatVariation::Group120Var2(x) => write!(f, "\n{}", x),
+            FreeFormatVariation::Group120Var5(x) => write!(f, "\n{}", x),
+            FreeFormatVariation::Group120Var6(x) => write!(f, "\n{}", x),
+            FreeFormatVariation::Group120Var7(x) => write!(f, "\n{}", x),
+            FreeFormatVariation::Group120Var8(x) => write!(f, "\n{}", x),
+            FreeFormatVariation::Group120Var9(mac) => write!(f, "\nmac: {}", mac),
+            FreeFormatVariation::Group120Var10(x) => write!(f, "\n{}", x),
+            FreeFormatVariation::Group120Var11(x) => write!(f, "\n{}", x),
+            FreeFormatVariation::Group120Var12(x) => write!(f, "\n{}", x),
+            FreeFormatVariation::Group120Var13(x) => write!(f, "\n{}", x),
+            FreeFormatVariation::Group120Var14(signature) => {
+                write!(f, "\nsignature: {}", signature)
+            }
+            FreeFormatVariation::Group120Var15(mac) => write!(f, "\nmac: {}", mac),
+        }
+    }
+}
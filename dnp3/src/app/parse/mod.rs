@@ -1,6 +1,10 @@
+pub use fragment::*;
+
 pub(crate) mod bit;
 pub(crate) mod bytes;
 pub(crate) mod count;
+mod fragment;
+pub(crate) mod free_format;
 pub(crate) mod parser;
 pub(crate) mod prefix;
 pub(crate) mod range;
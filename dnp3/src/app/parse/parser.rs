@@ -7,12 +7,16 @@ use crate::app::gen::count::CountVariation;
 use crate::app::gen::prefixed::PrefixedVariation;
 use crate::app::gen::ranged::RangedVariation;
 use crate::app::header::{ControlField, Iin, RequestHeader, ResponseFunction, ResponseHeader};
+use crate::app::parse::bytes::{PrefixedBytesSequence, RangedBytesSequence};
+use crate::app::parse::free_format::FreeFormatVariation;
 use crate::app::parse::prefix::Prefix;
 use crate::app::parse::range::Range;
-use crate::app::parse::traits::{FixedSizeVariation, Index};
+use crate::app::parse::traits::{FixedSize, FixedSizeVariation, Index};
 use crate::app::parse_error::*;
 use crate::app::variations::Variation;
-use crate::app::{FunctionCode, QualifierCode};
+use crate::app::{
+    FunctionCode, ParseMode, QualifierCode, UnknownHeader, VendorHeader, VendorObjects,
+};
 use crate::decode::AppDecodeLevel;
 use crate::util::cursor::ReadCursor;
 
@@ -62,7 +66,7 @@ pub(crate) struct ParsedFragment<'a> {
 }
 
 impl<'a> ParsedFragment<'a> {
-    pub(crate) fn display(&'a self, level: AppDecodeLevel) -> FragmentDisplay<'a> {
+    pub(crate) fn display(&self, level: AppDecodeLevel) -> FragmentDisplay<'a> {
         FragmentDisplay {
             level,
             fragment: *self,
@@ -151,6 +155,7 @@ impl<'a> ParsedFragment<'a> {
         let iin = match function {
             FunctionCode::Response => Some(Iin::parse(&mut cursor)?),
             FunctionCode::UnsolicitedResponse => Some(Iin::parse(&mut cursor)?),
+            FunctionCode::AuthResponse => Some(Iin::parse(&mut cursor)?),
             _ => None,
         };
 
@@ -172,6 +177,45 @@ impl<'a> ParsedFragment<'a> {
     }
 }
 
+/// header yielded by the parser, which may be unknown when capturing them is enabled
+#[derive(Debug, PartialEq)]
+pub(crate) enum ParsedHeader<'a> {
+    Known(ObjectHeader<'a>),
+    Unknown(UnknownHeader<'a>),
+    Vendor(VendorHeader<'a>),
+}
+
+/// controls how headers with a group and variation unknown to the parser are handled
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct UnknownObjects<'a> {
+    /// capture unknown headers whose size can be determined from the qualifier
+    capture: bool,
+    /// vendor-specific objects registered by the user
+    vendor: Option<&'a VendorObjects>,
+}
+
+impl<'a> UnknownObjects<'a> {
+    /// fail the parse when an unknown header is encountered
+    pub(crate) const REJECT: UnknownObjects<'static> = UnknownObjects {
+        capture: false,
+        vendor: None,
+    };
+
+    pub(crate) fn new(capture: bool, vendor: Option<&'a VendorObjects>) -> Self {
+        Self { capture, vendor }
+    }
+}
+
+impl ParsedHeader<'_> {
+    pub(crate) fn format(&self, format_values: bool, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ParsedHeader::Known(header) => header.format(format_values, f),
+            ParsedHeader::Unknown(header) => write!(f, "{}", header),
+            ParsedHeader::Vendor(header) => write!(f, "{}", header),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub(crate) struct ObjectHeader<'a> {
     pub(crate) variation: Variation,
@@ -278,6 +322,20 @@ impl<'a> ObjectHeader<'a> {
                 }
                 Ok(())
             }
+            HeaderDetails::TwoByteFreeFormat(c, obj) => {
+                write!(
+                    f,
+                    "{} : {} - {} - [{}]",
+                    self.variation,
+                    self.variation.description(),
+                    self.details.qualifier().description(),
+                    c
+                )?;
+                if format_values {
+                    obj.format_objects(f)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -296,16 +354,20 @@ impl std::fmt::Display for FragmentDisplay<'_> {
                 if !self.level.object_headers() {
                     return Ok(());
                 }
-                for header in headers.iter() {
+                for header in headers.iter_all() {
                     f.write_str("\n")?;
                     header.format(self.level.object_values(), f)?
                 }
             }
             Err(err) => {
                 // if an error occurred, we re-parse the object headers so we can log any headers before the error
-                for header in
-                    ObjectParser::one_pass(self.fragment.function, self.fragment.raw_objects)
-                        .flatten()
+                for header in ObjectParser::one_pass(
+                    self.fragment.function,
+                    self.fragment.raw_objects,
+                    UnknownObjects::REJECT,
+                    ParseMode::Strict,
+                )
+                .flatten()
                 {
                     f.write_str("\n")?;
                     header.format(self.level.object_values(), f)?;
@@ -328,6 +390,7 @@ pub(crate) enum HeaderDetails<'a> {
     TwoByteCount(u16, CountVariation<'a>),
     OneByteCountAndPrefix(u8, PrefixedVariation<'a, u8>),
     TwoByteCountAndPrefix(u16, PrefixedVariation<'a, u16>),
+    TwoByteFreeFormat(u8, FreeFormatVariation<'a>),
 }
 
 impl HeaderDetails<'_> {
@@ -340,6 +403,7 @@ impl HeaderDetails<'_> {
             HeaderDetails::TwoByteCount(_, _) => QualifierCode::Count16,
             HeaderDetails::OneByteCountAndPrefix(_, _) => QualifierCode::CountAndPrefix8,
             HeaderDetails::TwoByteCountAndPrefix(_, _) => QualifierCode::CountAndPrefix16,
+            HeaderDetails::TwoByteFreeFormat(_, _) => QualifierCode::FreeFormat16,
         }
     }
 
@@ -372,6 +436,20 @@ struct ObjectParser<'a> {
     errored: bool,
     function: FunctionCode,
     cursor: ReadCursor<'a>,
+    unknown: UnknownObjects<'a>,
+    mode: ParseMode,
+    /// anomalies are only logged during the validation pass
+    log_anomalies: bool,
+    anomalies: ParseAnomalies,
+}
+
+/// counts of the deviations from the specification tolerated while parsing in
+/// [ParseMode::Lenient]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) struct ParseAnomalies {
+    pub(crate) trailing_padding: usize,
+    pub(crate) inverted_ranges: usize,
+    pub(crate) zero_length_octet_strings: usize,
 }
 
 /// An abstract collection of pre-validated object headers
@@ -380,22 +458,60 @@ struct ObjectParser<'a> {
 pub(crate) struct HeaderCollection<'a> {
     function: FunctionCode,
     data: &'a [u8],
+    unknown: UnknownObjects<'a>,
+    mode: ParseMode,
+    anomalies: ParseAnomalies,
 }
 
 impl<'a> HeaderCollection<'a> {
     /// parse the the raw header data in accordance with the provided function code
     pub(crate) fn parse(function: FunctionCode, data: &'a [u8]) -> Result<Self, ObjectParseError> {
-        ObjectParser::parse(function, data)
+        ObjectParser::parse(function, data, UnknownObjects::REJECT, ParseMode::Strict)
+    }
+
+    /// parse the raw header data, handling headers with an unknown group and variation
+    /// as specified instead of failing
+    pub(crate) fn parse_with_unknown(
+        function: FunctionCode,
+        data: &'a [u8],
+        unknown: UnknownObjects<'a>,
+    ) -> Result<Self, ObjectParseError> {
+        ObjectParser::parse(function, data, unknown, ParseMode::Strict)
+    }
+
+    /// parse the raw header data, handling unknown headers and recoverable deviations from
+    /// the specification as specified
+    pub(crate) fn parse_with_mode(
+        function: FunctionCode,
+        data: &'a [u8],
+        unknown: UnknownObjects<'a>,
+        mode: ParseMode,
+    ) -> Result<Self, ObjectParseError> {
+        ObjectParser::parse(function, data, unknown, mode)
     }
 
     pub(crate) fn is_empty(&self) -> bool {
         self.data.is_empty()
     }
 
+    /// deviations from the specification tolerated while validating the headers
+    pub(crate) fn anomalies(&self) -> ParseAnomalies {
+        self.anomalies
+    }
+
     /// return and iterator of the headers that lazily parses them
+    ///
+    /// captured unknown and vendor-specific headers are skipped
     pub(crate) fn iter(&self) -> HeaderIterator<'a> {
         HeaderIterator {
-            parser: ObjectParser::one_pass(self.function, self.data),
+            parser: self.iter_all(),
+        }
+    }
+
+    /// return an iterator of the headers including any captured unknown and vendor-specific headers
+    pub(crate) fn iter_all(&self) -> ParsedHeaderIterator<'a> {
+        ParsedHeaderIterator {
+            parser: ObjectParser::one_pass(self.function, self.data, self.unknown, self.mode),
         }
     }
 
@@ -417,12 +533,30 @@ impl<'a> HeaderCollection<'a> {
 
 #[derive(Copy, Clone)]
 pub(crate) struct HeaderIterator<'a> {
-    parser: ObjectParser<'a>,
+    parser: ParsedHeaderIterator<'a>,
 }
 
 impl<'a> Iterator for HeaderIterator<'a> {
     type Item = ObjectHeader<'a>;
 
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.parser.next()? {
+                ParsedHeader::Known(x) => return Some(x),
+                ParsedHeader::Unknown(_) | ParsedHeader::Vendor(_) => continue,
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub(crate) struct ParsedHeaderIterator<'a> {
+    parser: ObjectParser<'a>,
+}
+
+impl<'a> Iterator for ParsedHeaderIterator<'a> {
+    type Item = ParsedHeader<'a>;
+
     fn next(&mut self) -> Option<Self::Item> {
         match self.parser.next() {
             None => None,
@@ -437,9 +571,15 @@ impl<'a> ObjectParser<'a> {
     pub(crate) fn parse(
         function: FunctionCode,
         data: &'a [u8],
+        unknown: UnknownObjects<'a>,
+        mode: ParseMode,
     ) -> Result<HeaderCollection<'a>, ObjectParseError> {
         // we first do a single pass to ensure the ASDU is well-formed, returning an error if it occurs
-        for result in ObjectParser::one_pass(function, data) {
+        let mut first_pass = ObjectParser {
+            log_anomalies: true,
+            ..ObjectParser::one_pass(function, data, unknown, mode)
+        };
+        for result in &mut first_pass {
             if let Err(err) = result {
                 return Err(err);
             }
@@ -447,22 +587,53 @@ impl<'a> ObjectParser<'a> {
 
         // now we know that we know the headers are well-formed, our 2nd pass
         // can use the HeaderCollection iterator implementation to read them
-        Ok(HeaderCollection { function, data })
+        Ok(HeaderCollection {
+            function,
+            data,
+            unknown,
+            mode,
+            anomalies: first_pass.anomalies,
+        })
     }
 
-    fn one_pass(function: FunctionCode, data: &'a [u8]) -> Self {
+    fn one_pass(
+        function: FunctionCode,
+        data: &'a [u8],
+        unknown: UnknownObjects<'a>,
+        mode: ParseMode,
+    ) -> Self {
         ObjectParser {
             cursor: ReadCursor::new(data),
             function,
             errored: false,
+            unknown,
+            mode,
+            log_anomalies: false,
+            anomalies: ParseAnomalies::default(),
+        }
+    }
+
+    fn anomaly(&self, args: std::fmt::Arguments) {
+        if self.log_anomalies {
+            tracing::warn!("parse anomaly: {}", args);
         }
     }
 
-    fn parse_one(&mut self) -> Option<Result<ObjectHeader<'a>, ObjectParseError>> {
+    fn parse_one(&mut self) -> Option<Result<ParsedHeader<'a>, ObjectParseError>> {
         if self.errored || self.cursor.is_empty() {
             return None;
         }
 
+        if self.mode.is_lenient() && self.cursor.peek_all().iter().all(|x| *x == 0) {
+            self.anomalies.trailing_padding += self.cursor.remaining();
+            self.anomaly(format_args!(
+                "ignoring {} trailing padding byte(s)",
+                self.cursor.remaining()
+            ));
+            self.cursor.read_all();
+            return None;
+        }
+
         let result = self.parse_one_inner();
 
         if result.is_err() {
@@ -472,10 +643,24 @@ impl<'a> ObjectParser<'a> {
         Some(result)
     }
 
-    fn parse_one_inner(&mut self) -> Result<ObjectHeader<'a>, ObjectParseError> {
-        let gv = Variation::parse(&mut self.cursor)?;
+    fn parse_one_inner(&mut self) -> Result<ParsedHeader<'a>, ObjectParseError> {
+        let start = self.cursor;
+        let group = self.cursor.read_u8()?;
+        let var = self.cursor.read_u8()?;
+        let gv = match Variation::lookup(group, var) {
+            Some(gv) => gv,
+            None => {
+                if let Some(size) = self.unknown.vendor.and_then(|x| x.size(group, var)) {
+                    return self.parse_vendor(group, var, size);
+                }
+                if self.unknown.capture {
+                    return self.parse_unknown(start, group, var);
+                }
+                return Err(ObjectParseError::UnknownGroupVariation(group, var));
+            }
+        };
         let qualifier = QualifierCode::parse(&mut self.cursor)?;
-        match qualifier {
+        let header = match qualifier {
             QualifierCode::AllObjects => self.parse_all_objects(gv),
             QualifierCode::Range8 => self.parse_start_stop_u8(gv),
             QualifierCode::Range16 => self.parse_start_stop_u16(gv),
@@ -483,8 +668,88 @@ impl<'a> ObjectParser<'a> {
             QualifierCode::Count16 => self.parse_count_u16(gv),
             QualifierCode::CountAndPrefix8 => self.parse_count_and_prefix_u8(gv),
             QualifierCode::CountAndPrefix16 => self.parse_count_and_prefix_u16(gv),
-            _ => Err(ObjectParseError::UnsupportedQualifierCode(qualifier)),
-        }
+            QualifierCode::FreeFormat16 => self.parse_free_format_u16(gv),
+        }?;
+        Ok(ParsedHeader::Known(header))
+    }
+
+    fn parse_unknown(
+        &mut self,
+        start: ReadCursor<'a>,
+        group: u8,
+        variation: u8,
+    ) -> Result<ParsedHeader<'a>, ObjectParseError> {
+        let unknown = ObjectParseError::UnknownGroupVariation(group, variation);
+        let qualifier = QualifierCode::parse(&mut self.cursor)?;
+        // only an empty count can be captured without knowing the size of each object
+        let size = match qualifier {
+            QualifierCode::AllObjects => 0,
+            QualifierCode::Count8 => match self.cursor.read_u8()? {
+                0 => 0,
+                _ => return Err(unknown),
+            },
+            QualifierCode::Count16 => match self.cursor.read_u16_le()? {
+                0 => 0,
+                _ => return Err(unknown),
+            },
+            QualifierCode::FreeFormat16 => {
+                let count = self.cursor.read_u8()?;
+                if count != 1 {
+                    return Err(ObjectParseError::UnsupportedFreeFormatCount(count));
+                }
+                self.cursor.read_u16_le()? as usize
+            }
+            // the size of the objects can't be determined without knowing the variation
+            _ => return Err(unknown),
+        };
+
+        let header_length = start.remaining() - self.cursor.remaining();
+        let header = &start.peek_all()[..header_length];
+        let objects = self.cursor.read_bytes(size)?;
+        Ok(ParsedHeader::Unknown(UnknownHeader {
+            group,
+            variation,
+            qualifier,
+            header,
+            objects,
+        }))
+    }
+
+    fn parse_vendor(
+        &mut self,
+        group: u8,
+        variation: u8,
+        size: usize,
+    ) -> Result<ParsedHeader<'a>, ObjectParseError> {
+        let qualifier = QualifierCode::parse(&mut self.cursor)?;
+        let (start, count, prefix) = match qualifier {
+            QualifierCode::AllObjects => (0, 0, 0),
+            QualifierCode::Range8 => {
+                let start = self.cursor.read_u8()?;
+                let stop = self.cursor.read_u8()?;
+                let range = self.range(start as u16, stop as u16)?;
+                (range.get_start(), range.get_count(), 0)
+            }
+            QualifierCode::Range16 => {
+                let start = self.cursor.read_u16_le()?;
+                let stop = self.cursor.read_u16_le()?;
+                let range = self.range(start, stop)?;
+                (range.get_start(), range.get_count(), 0)
+            }
+            QualifierCode::Count8 => (0, self.cursor.read_u8()? as usize, 0),
+            QualifierCode::Count16 => (0, self.cursor.read_u16_le()? as usize, 0),
+            QualifierCode::CountAndPrefix8 => (0, self.cursor.read_u8()? as usize, 1),
+            QualifierCode::CountAndPrefix16 => (0, self.cursor.read_u16_le()? as usize, 2),
+            // registered objects have a fixed size
+            QualifierCode::FreeFormat16 => {
+                return Err(ObjectParseError::UnsupportedQualifierCode(qualifier))
+            }
+        };
+
+        let objects = self.cursor.read_bytes(count * (prefix + size))?;
+        Ok(ParsedHeader::Vendor(VendorHeader::new(
+            group, variation, qualifier, size, start, objects,
+        )))
     }
 
     fn parse_all_objects(&mut self, v: Variation) -> Result<ObjectHeader<'a>, ObjectParseError> {
@@ -518,14 +783,8 @@ impl<'a> ObjectParser<'a> {
     fn parse_start_stop_u8(&mut self, v: Variation) -> Result<ObjectHeader<'a>, ObjectParseError> {
         let start = self.cursor.read_u8()?;
         let stop = self.cursor.read_u8()?;
-        let range = Range::from(start as u16, stop as u16)?;
-        let data = RangedVariation::parse(
-            self.function,
-            QualifierCode::Range8,
-            v,
-            range,
-            &mut self.cursor,
-        )?;
+        let range = self.range(start as u16, stop as u16)?;
+        let data = self.parse_ranged(QualifierCode::Range8, v, range)?;
         Ok(ObjectHeader::new(
             v,
             HeaderDetails::OneByteStartStop(start, stop, data),
@@ -535,14 +794,8 @@ impl<'a> ObjectParser<'a> {
     fn parse_start_stop_u16(&mut self, v: Variation) -> Result<ObjectHeader<'a>, ObjectParseError> {
         let start = self.cursor.read_u16_le()?;
         let stop = self.cursor.read_u16_le()?;
-        let range = Range::from(start, stop)?;
-        let data = RangedVariation::parse(
-            self.function,
-            QualifierCode::Range16,
-            v,
-            range,
-            &mut self.cursor,
-        )?;
+        let range = self.range(start, stop)?;
+        let data = self.parse_ranged(QualifierCode::Range16, v, range)?;
         Ok(ObjectHeader::new(
             v,
             HeaderDetails::TwoByteStartStop(start, stop, data),
@@ -554,7 +807,7 @@ impl<'a> ObjectParser<'a> {
         v: Variation,
     ) -> Result<ObjectHeader<'a>, ObjectParseError> {
         let count = self.cursor.read_u8()?;
-        let data = PrefixedVariation::<u8>::parse(v, count as u16, &mut self.cursor)?;
+        let data = self.parse_prefixed::<u8>(v, count as u16)?;
         Ok(ObjectHeader::new(
             v,
             HeaderDetails::OneByteCountAndPrefix(count, data),
@@ -566,33 +819,99 @@ impl<'a> ObjectParser<'a> {
         v: Variation,
     ) -> Result<ObjectHeader<'a>, ObjectParseError> {
         let count = self.cursor.read_u16_le()?;
-        let data = PrefixedVariation::<u16>::parse(v, count, &mut self.cursor)?;
+        let data = self.parse_prefixed::<u16>(v, count)?;
         Ok(ObjectHeader::new(
             v,
             HeaderDetails::TwoByteCountAndPrefix(count, data),
         ))
     }
+
+    fn range(&mut self, start: u16, stop: u16) -> Result<Range, ObjectParseError> {
+        match Range::from(start, stop) {
+            Ok(range) => Ok(range),
+            Err(_) if self.mode.is_lenient() => {
+                self.anomalies.inverted_ranges += 1;
+                self.anomaly(format_args!(
+                    "treating range with start: {} stop: {} as empty",
+                    start, stop
+                ));
+                Ok(Range::empty())
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn parse_ranged(
+        &mut self,
+        qualifier: QualifierCode,
+        v: Variation,
+        range: Range,
+    ) -> Result<RangedVariation<'a>, ObjectParseError> {
+        if self.mode.is_lenient()
+            && self.function != FunctionCode::Read
+            && v == Variation::Group110(0)
+        {
+            self.anomalies.zero_length_octet_strings += range.get_count();
+            self.anomaly(format_args!(
+                "reporting {} zero-length octet string(s) as empty",
+                range.get_count()
+            ));
+            return Ok(RangedVariation::Group110VarX(
+                0,
+                RangedBytesSequence::zero_length(range.get_start(), range.get_count()),
+            ));
+        }
+        RangedVariation::parse(self.function, qualifier, v, range, &mut self.cursor)
+    }
+
+    fn parse_prefixed<I>(
+        &mut self,
+        v: Variation,
+        count: u16,
+    ) -> Result<PrefixedVariation<'a, I>, ObjectParseError>
+    where
+        I: FixedSize + Index + std::fmt::Display,
+    {
+        if self.mode.is_lenient() && v == Variation::Group111(0) {
+            self.anomalies.zero_length_octet_strings += count as usize;
+            self.anomaly(format_args!(
+                "reporting {} zero-length octet string(s) as empty",
+                count
+            ));
+            return Ok(PrefixedVariation::Group111VarX(
+                0,
+                PrefixedBytesSequence::parse_zero_length(count, &mut self.cursor)?,
+            ));
+        }
+        PrefixedVariation::<I>::parse(v, count, &mut self.cursor)
+    }
+
+    fn parse_free_format_u16(
+        &mut self,
+        v: Variation,
+    ) -> Result<ObjectHeader<'a>, ObjectParseError> {
+        let count = self.cursor.read_u8()?;
+        // the specification only defines free-format headers with a single object
+        if count != 1 {
+            return Err(ObjectParseError::UnsupportedFreeFormatCount(count));
+        }
+        let size = self.cursor.read_u16_le()?;
+        let data = FreeFormatVariation::parse(v, size, &mut self.cursor)?;
+        Ok(ObjectHeader::new(
+            v,
+            HeaderDetails::TwoByteFreeFormat(count, data),
+        ))
+    }
 }
 
 impl<'a> Iterator for ObjectParser<'a> {
-    type Item = Result<ObjectHeader<'a>, ObjectParseError>;
+    type Item = Result<ParsedHeader<'a>, ObjectParseError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.parse_one()
     }
 }
 
-impl Variation {
-    pub(crate) fn parse(cursor: &mut ReadCursor) -> Result<Variation, ObjectParseError> {
-        let group = cursor.read_u8()?;
-        let var = cursor.read_u8()?;
-        match Self::lookup(group, var) {
-            Some(gv) => Ok(gv),
-            None => Err(ObjectParseError::UnknownGroupVariation(group, var)),
-        }
-    }
-}
-
 impl<'a> RangedVariation<'a> {
     pub(crate) fn parse(
         function: FunctionCode,
@@ -620,6 +939,7 @@ impl QualifierCode {
 
 #[cfg(test)]
 mod test {
+    use crate::app::auth::Challenge;
     use crate::app::control::CommandStatus;
     use crate::app::header::{ControlField, Iin, Iin1, Iin2};
     use crate::app::measurement::DoubleBit;
@@ -628,12 +948,12 @@ mod test {
     use crate::app::sequence::Sequence;
     use crate::app::types::Timestamp;
     use crate::app::variations::*;
-    use crate::app::Bytes;
+    use crate::app::{AttrItem, AttrParseError, AttrProp, AttrValue, Attribute, Bytes};
 
     use super::*;
 
     fn test_parse_error(input: &[u8], func: FunctionCode, err: ObjectParseError) {
-        assert_eq!(ObjectParser::parse(func, input).err().unwrap(), err);
+        assert_eq!(HeaderCollection::parse(func, input).err().unwrap(), err);
     }
 
     fn test_request_validation_error(input: &[u8], err: RequestValidationError) {
@@ -781,7 +1101,7 @@ mod test {
 
     #[test]
     fn parses_integrity_scan() {
-        let vec: Vec<HeaderDetails> = ObjectParser::parse(
+        let vec: Vec<HeaderDetails> = HeaderCollection::parse(
             FunctionCode::Read,
             &[
                 0x3C, 0x02, 0x06, 0x3C, 0x03, 0x06, 0x3C, 0x04, 0x06, 0x3C, 0x01, 0x06,
@@ -806,7 +1126,7 @@ mod test {
     #[test]
     fn parses_analog_output() {
         let header = &[0x29, 0x01, 0x17, 0x01, 0xFF, 0x01, 0x02, 0x03, 0x04, 0x00];
-        let mut headers = ObjectParser::parse(FunctionCode::Operate, header)
+        let mut headers = HeaderCollection::parse(FunctionCode::Operate, header)
             .unwrap()
             .iter();
 
@@ -831,7 +1151,7 @@ mod test {
     #[test]
     fn parses_range_of_g3v1() {
         let header = &[0x03, 0x01, 0x00, 0x01, 0x04, 0b11_10_01_00];
-        let mut headers = ObjectParser::parse(FunctionCode::Response, header)
+        let mut headers = HeaderCollection::parse(FunctionCode::Response, header)
             .unwrap()
             .iter();
 
@@ -954,6 +1274,178 @@ mod test {
         assert_eq!(headers.next(), None);
     }
 
+    #[test]
+    fn parses_g90v1_with_free_format_qualifier() {
+        let input = [0x5A, 0x01, 0x5B, 0x01, 0x03, 0x00, b'a', b'b', b'c'];
+        let mut headers = HeaderCollection::parse(FunctionCode::StartApplication, &input)
+            .unwrap()
+            .iter();
+
+        let name: &[u8] = assert_matches!(
+            headers.next().unwrap().details,
+            HeaderDetails::TwoByteFreeFormat(1, FreeFormatVariation::Group90Var1(bytes)) => bytes.value
+        );
+
+        assert_eq!(name, b"abc");
+        assert_eq!(headers.next(), None);
+    }
+
+    #[test]
+    fn parses_g120v1_challenge() {
+        let input = [
+            0x78, 0x01, 0x5B, 0x01, 0x0C, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x04, 0x01,
+            0xAA, 0xBB, 0xCC, 0xDD,
+        ];
+        let mut headers = HeaderCollection::parse(FunctionCode::AuthResponse, &input)
+            .unwrap()
+            .iter();
+
+        let challenge = assert_matches!(
+            headers.next().unwrap().details,
+            HeaderDetails::TwoByteFreeFormat(1, FreeFormatVariation::Group120Var1(x)) => x
+        );
+
+        assert_eq!(
+            challenge,
+            Challenge {
+                challenge_seq: 1,
+                user: 2,
+                mac_algorithm: 4,
+                reason: 1,
+                data: &[0xAA, 0xBB, 0xCC, 0xDD],
+            }
+        );
+        assert_eq!(headers.next(), None);
+    }
+
+    #[test]
+    fn parses_count_of_g120v4() {
+        let input = [0x78, 0x04, 0x07, 0x01, 0x01, 0x00];
+        let mut headers = HeaderCollection::parse(FunctionCode::AuthRequest, &input)
+            .unwrap()
+            .iter();
+
+        let items: Vec<Group120Var4> = assert_matches!(
+            headers.next().unwrap().details,
+            HeaderDetails::OneByteCount(1, CountVariation::Group120Var4(seq)) => seq.iter().collect()
+        );
+
+        assert_eq!(items, vec![Group120Var4 { user: 1 }]);
+        assert_eq!(headers.next(), None);
+    }
+
+    #[test]
+    fn parses_read_of_all_attributes() {
+        let input = [0x00, 0xFE, 0x06, 0x00, 0xFE, 0x00, 0x01, 0x01];
+        let mut headers = HeaderCollection::parse(FunctionCode::Read, &input)
+            .unwrap()
+            .iter();
+
+        assert_eq!(
+            headers.next().unwrap().details,
+            HeaderDetails::AllObjects(AllObjectsVariation::Group0Var254)
+        );
+        assert_eq!(
+            headers.next().unwrap().details,
+            HeaderDetails::OneByteStartStop(1, 1, RangedVariation::Group0Var254)
+        );
+        assert_eq!(headers.next(), None);
+    }
+
+    #[test]
+    fn parses_visible_string_attribute() {
+        let input = [0x00, 0xFC, 0x00, 0x00, 0x00, 0x01, 0x03, b'a', b'b', b'c'];
+        let mut headers = HeaderCollection::parse(FunctionCode::Response, &input)
+            .unwrap()
+            .iter();
+
+        let header = headers.next().unwrap();
+        assert_eq!(header.variation, Variation::Group0(252));
+        assert_eq!(
+            header.details,
+            HeaderDetails::OneByteStartStop(
+                0,
+                0,
+                RangedVariation::Group0VarX(Attribute::new(
+                    0,
+                    252,
+                    AttrValue::VisibleString("abc")
+                ))
+            )
+        );
+        assert_eq!(headers.next(), None);
+    }
+
+    #[test]
+    fn parses_list_of_attribute_variations() {
+        let input = [
+            0x00, 0xFF, 0x00, 0x00, 0x00, 0xFE, 0x04, 0xFC, 0x00, 0xF0, 0x01,
+        ];
+        let mut headers = HeaderCollection::parse(FunctionCode::Response, &input)
+            .unwrap()
+            .iter();
+
+        let items: Vec<AttrItem> = assert_matches!(
+            headers.next().unwrap().details,
+            HeaderDetails::OneByteStartStop(0, 0, RangedVariation::Group0VarX(Attribute {
+                set: 0,
+                variation: 255,
+                value: AttrValue::AttrList(list)
+            })) => list.iter().collect()
+        );
+
+        assert_eq!(
+            items,
+            vec![
+                AttrItem {
+                    variation: 252,
+                    properties: AttrProp { is_writable: false }
+                },
+                AttrItem {
+                    variation: 240,
+                    properties: AttrProp { is_writable: true }
+                },
+            ]
+        );
+        assert_eq!(headers.next(), None);
+    }
+
+    #[test]
+    fn parses_sign_extended_integer_attribute() {
+        let input = [0x00, 0xF6, 0x00, 0x00, 0x00, 0x03, 0x02, 0xFE, 0xFF];
+        let header = HeaderCollection::parse(FunctionCode::Response, &input)
+            .unwrap()
+            .get_only_header()
+            .unwrap();
+
+        assert_eq!(
+            header.details,
+            HeaderDetails::OneByteStartStop(
+                0,
+                0,
+                RangedVariation::Group0VarX(Attribute::new(0, 246, AttrValue::SignedInt(-2)))
+            )
+        );
+    }
+
+    #[test]
+    fn attribute_headers_must_contain_a_single_set() {
+        test_parse_error(
+            &[0x00, 0xFC, 0x00, 0x00, 0x01, 0x01, 0x01, b'a'],
+            FunctionCode::Response,
+            ObjectParseError::BadAttribute(AttrParseError::BadCount(2)),
+        );
+    }
+
+    #[test]
+    fn free_format_qualifier_requires_a_count_of_one() {
+        test_parse_error(
+            &[0x5A, 0x01, 0x5B, 0x02, 0x01, 0x00, b'a', 0x01, 0x00, b'b'],
+            FunctionCode::StartApplication,
+            ObjectParseError::UnsupportedFreeFormatCount(2),
+        );
+    }
+
     #[test]
     fn g110_variations_other_than_0_cannot_be_used_in_read() {
         test_parse_error(
@@ -969,7 +1461,7 @@ mod test {
     #[test]
     fn parses_group110var1_as_non_read() {
         let input = [0x6E, 0x01, 0x00, 0x01, 0x02, 0xAA, 0xBB];
-        let mut headers = ObjectParser::parse(FunctionCode::Response, &input)
+        let mut headers = HeaderCollection::parse(FunctionCode::Response, &input)
             .unwrap()
             .iter();
 
@@ -987,12 +1479,128 @@ mod test {
         assert_eq!(headers.next(), None);
     }
 
+    #[test]
+    fn parses_group102var1_with_range_and_prefix() {
+        let input = [
+            0x66, 0x01, 0x00, 0x01, 0x02, 0xAA, 0xBB, // range
+            0x66, 0x01, 0x28, 0x01, 0x00, 0x07, 0x00, 0xCC, // count and prefix
+        ];
+        let mut headers = HeaderCollection::parse(FunctionCode::Response, &input)
+            .unwrap()
+            .iter();
+
+        let values: Vec<(u8, u16)> = assert_matches!(
+            headers.next().unwrap().details,
+            HeaderDetails::OneByteStartStop(01, 02, RangedVariation::Group102Var1(seq)) => {
+                seq.iter().map(|(v, i)| (v.value, i)).collect()
+            }
+        );
+        assert_eq!(values, vec![(0xAA, 1), (0xBB, 2)]);
+
+        let values: Vec<(u8, u16)> = assert_matches!(
+            headers.next().unwrap().details,
+            HeaderDetails::TwoByteCountAndPrefix(0x01, PrefixedVariation::Group102Var1(seq)) => {
+                seq.iter().map(|x| (x.value.value, x.index)).collect()
+            }
+        );
+        assert_eq!(values, vec![(0xCC, 7)]);
+        assert_eq!(headers.next(), None);
+    }
+
+    fn parse_lenient(function: FunctionCode, input: &[u8]) -> HeaderCollection<'_> {
+        HeaderCollection::parse_with_mode(
+            function,
+            input,
+            UnknownObjects::REJECT,
+            ParseMode::Lenient,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn lenient_mode_reports_zero_length_octet_strings_as_empty() {
+        let input = [
+            0x6E, 0x00, 0x00, 0x01, 0x02, // range
+            0x6F, 0x00, 0x17, 0x01, 0x05, // count and prefix
+        ];
+        test_parse_error(
+            &input,
+            FunctionCode::Response,
+            ObjectParseError::ZeroLengthOctetData,
+        );
+
+        let headers = parse_lenient(FunctionCode::Response, &input);
+        assert_eq!(headers.anomalies().zero_length_octet_strings, 3);
+        let mut headers = headers.iter();
+
+        let bytes: Vec<(Bytes, u16)> = assert_matches!(
+            headers.next().unwrap().details,
+            HeaderDetails::OneByteStartStop(01, 02, RangedVariation::Group110VarX(0x00, seq)) => {
+                seq.iter().collect()
+            }
+        );
+        assert_eq!(
+            bytes,
+            vec![(Bytes { value: &[] }, 1), (Bytes { value: &[] }, 2)]
+        );
+
+        let bytes: Vec<(Bytes, u8)> = assert_matches!(
+            headers.next().unwrap().details,
+            HeaderDetails::OneByteCountAndPrefix(01, PrefixedVariation::Group111VarX(0x00, seq)) => {
+                seq.iter().collect()
+            }
+        );
+        assert_eq!(bytes, vec![(Bytes { value: &[] }, 5)]);
+        assert_eq!(headers.next(), None);
+    }
+
+    #[test]
+    fn lenient_mode_ignores_trailing_padding() {
+        let input = [0x3C, 0x01, 0x06, 0x00, 0x00];
+        test_parse_error(
+            &input,
+            FunctionCode::Read,
+            ObjectParseError::UnknownGroupVariation(0, 0),
+        );
+
+        let headers = parse_lenient(FunctionCode::Read, &input);
+        assert_eq!(headers.anomalies().trailing_padding, 2);
+        let mut headers = headers.iter();
+        assert_eq!(
+            headers.next().unwrap().details,
+            HeaderDetails::AllObjects(AllObjectsVariation::Group60Var1)
+        );
+        assert_eq!(headers.next(), None);
+    }
+
+    #[test]
+    fn lenient_mode_treats_inverted_range_as_empty() {
+        let input = [0x01, 0x02, 0x00, 0x01, 0x00, 0x1E, 0x01, 0x06];
+        test_parse_error(
+            &input,
+            FunctionCode::Response,
+            ObjectParseError::InvalidRange(1, 0),
+        );
+
+        let headers = parse_lenient(FunctionCode::Response, &input);
+        assert_eq!(headers.anomalies().inverted_ranges, 1);
+        let mut headers = headers.iter();
+        assert_matches!(
+            headers.next().unwrap().details,
+            HeaderDetails::OneByteStartStop(01, 00, RangedVariation::Group1Var2(seq)) => {
+                assert_eq!(seq.iter().count(), 0);
+            }
+        );
+        assert_eq!(headers.next().unwrap().variation, Variation::Group30Var1);
+        assert_eq!(headers.next(), None);
+    }
+
     #[test]
     fn parses_group111var1_as_non_read() {
         let input = [
             0x6F, 0x01, 0x28, 0x02, 0x00, 0x01, 0x00, 0xAA, 0x02, 0x00, 0xBB,
         ];
-        let mut headers = ObjectParser::parse(FunctionCode::Response, &input)
+        let mut headers = HeaderCollection::parse(FunctionCode::Response, &input)
             .unwrap()
             .iter();
 
@@ -1018,4 +1626,108 @@ mod test {
             ObjectParseError::ZeroLengthOctetData,
         );
     }
+
+    #[test]
+    fn captures_unknown_headers_when_size_is_known() {
+        let input = [
+            // g200v1 free-format with 2 bytes
+            0xC8, 0x01, 0x5B, 0x01, 0x02, 0x00, 0xAA, 0xBB, // g1v2 range 1..1
+            0x01, 0x02, 0x00, 0x01, 0x01, 0x81, // g200v2 all objects
+            0xC8, 0x02, 0x06,
+        ];
+
+        assert_eq!(
+            HeaderCollection::parse(FunctionCode::Response, &input).err(),
+            Some(ObjectParseError::UnknownGroupVariation(200, 1))
+        );
+
+        let headers = HeaderCollection::parse_with_unknown(
+            FunctionCode::Response,
+            &input,
+            UnknownObjects::new(true, None),
+        )
+        .unwrap();
+        let mut iter = headers.iter_all();
+        assert_eq!(
+            iter.next(),
+            Some(ParsedHeader::Unknown(UnknownHeader {
+                group: 200,
+                variation: 1,
+                qualifier: QualifierCode::FreeFormat16,
+                header: &input[0..6],
+                objects: &[0xAA, 0xBB],
+            }))
+        );
+        assert_matches!(
+            iter.next(),
+            Some(ParsedHeader::Known(ObjectHeader {
+                details: HeaderDetails::OneByteStartStop(1, 1, RangedVariation::Group1Var2(_)),
+                ..
+            }))
+        );
+        assert_matches!(
+            iter.next(),
+            Some(ParsedHeader::Unknown(UnknownHeader {
+                group: 200,
+                variation: 2,
+                qualifier: QualifierCode::AllObjects,
+                ..
+            }))
+        );
+        assert_eq!(iter.next(), None);
+
+        // the regular iterator skips the unknown headers
+        assert_eq!(headers.iter().count(), 1);
+    }
+
+    #[test]
+    fn cannot_capture_unknown_headers_without_known_size() {
+        assert_eq!(
+            HeaderCollection::parse_with_unknown(
+                FunctionCode::Response,
+                &[0xC8, 0x01, 0x00, 0x01, 0x02, 0xAA, 0xBB],
+                UnknownObjects::new(true, None),
+            )
+            .err(),
+            Some(ObjectParseError::UnknownGroupVariation(200, 1))
+        );
+    }
+
+    #[test]
+    fn parses_registered_vendor_objects_with_any_fixed_size_qualifier() {
+        let mut vendor = VendorObjects::new();
+        vendor.register(200, 1, 2).unwrap();
+
+        let input = [
+            // g200v1 range 3..4
+            0xC8, 0x01, 0x00, 0x03, 0x04, 0xAA, 0xBB, 0xCC, 0xDD,
+        ];
+
+        // registered objects are parsed even if other unknown headers aren't captured
+        let headers = HeaderCollection::parse_with_unknown(
+            FunctionCode::Response,
+            &input,
+            UnknownObjects::new(false, Some(&vendor)),
+        )
+        .unwrap();
+        let header = match headers.iter_all().next() {
+            Some(ParsedHeader::Vendor(x)) => x,
+            x => panic!("unexpected header: {:?}", x),
+        };
+        assert_eq!(header.group, 200);
+        assert_eq!(header.variation, 1);
+        assert_eq!(header.qualifier, QualifierCode::Range8);
+        let objects: Vec<(u16, &[u8])> = header.iter().collect();
+        assert_eq!(objects, vec![(3, &input[5..7]), (4, &input[7..9])]);
+
+        assert_eq!(
+            HeaderCollection::parse_with_unknown(
+                FunctionCode::Response,
+                &[0xC8, 0x02, 0x06],
+                UnknownObjects::new(false, Some(&vendor)),
+            )
+            .err(),
+            Some(ObjectParseError::UnknownGroupVariation(200, 2))
+        );
+    }
 }
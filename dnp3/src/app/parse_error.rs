@@ -3,7 +3,7 @@ use std::fmt::Formatter;
 use crate::app::parse::range::InvalidRange;
 use crate::app::sequence::Sequence;
 use crate::app::variations::Variation;
-use crate::app::{FunctionCode, QualifierCode};
+use crate::app::{AttrParseError, FunctionCode, QualifierCode};
 use crate::util::cursor::ReadError;
 
 /// errors that occur when parsing an application layer header
@@ -15,6 +15,15 @@ pub enum HeaderParseError {
     InsufficientBytes,
 }
 
+/// errors that prevent the header of a received response from being used
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ResponseHeaderError {
+    /// the header could not be parsed
+    Parse(HeaderParseError),
+    /// the header was parsed but is not valid for a response
+    Validation(ResponseValidationError),
+}
+
 /// errors that occur when parsing object headers
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ObjectParseError {
@@ -32,6 +41,10 @@ pub enum ObjectParseError {
     UnsupportedQualifierCode(QualifierCode),
     /// response containing zero-length octet data disallowed by the specification
     ZeroLengthOctetData,
+    /// free-format qualifier with a count other than 1
+    UnsupportedFreeFormatCount(u8),
+    /// malformed device attribute (g0)
+    BadAttribute(AttrParseError),
 }
 
 /// errors that occur when interpreting a header as a request header
@@ -69,6 +82,15 @@ impl std::fmt::Display for HeaderParseError {
     }
 }
 
+impl std::fmt::Display for ResponseHeaderError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ResponseHeaderError::Parse(err) => write!(f, "malformed response header: {}", err),
+            ResponseHeaderError::Validation(err) => write!(f, "invalid response header: {}", err),
+        }
+    }
+}
+
 impl std::fmt::Display for ObjectParseError {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match self {
@@ -98,6 +120,12 @@ impl std::fmt::Display for ObjectParseError {
             ObjectParseError::ZeroLengthOctetData => {
                 f.write_str("octet-data may not be zero length")
             }
+            ObjectParseError::UnsupportedFreeFormatCount(count) => write!(
+                f,
+                "free-format qualifier only supports a count of 1, but count is {}",
+                count
+            ),
+            ObjectParseError::BadAttribute(err) => write!(f, "bad attribute: {}", err),
         }
     }
 }
@@ -137,6 +165,30 @@ impl std::fmt::Display for ResponseValidationError {
     }
 }
 
+impl std::error::Error for HeaderParseError {}
+
+impl std::error::Error for ResponseHeaderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ResponseHeaderError::Parse(err) => Some(err),
+            ResponseHeaderError::Validation(err) => Some(err),
+        }
+    }
+}
+
+impl std::error::Error for ObjectParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObjectParseError::BadAttribute(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for RequestValidationError {}
+
+impl std::error::Error for ResponseValidationError {}
+
 impl From<ReadError> for ObjectParseError {
     fn from(_: ReadError) -> Self {
         ObjectParseError::InsufficientBytes
@@ -149,6 +201,24 @@ impl From<ReadError> for HeaderParseError {
     }
 }
 
+impl From<HeaderParseError> for ResponseHeaderError {
+    fn from(err: HeaderParseError) -> Self {
+        ResponseHeaderError::Parse(err)
+    }
+}
+
+impl From<ResponseValidationError> for ResponseHeaderError {
+    fn from(err: ResponseValidationError) -> Self {
+        ResponseHeaderError::Validation(err)
+    }
+}
+
+impl From<AttrParseError> for ObjectParseError {
+    fn from(err: AttrParseError) -> Self {
+        ObjectParseError::BadAttribute(err)
+    }
+}
+
 impl From<InvalidRange> for ObjectParseError {
     fn from(r: InvalidRange) -> Self {
         ObjectParseError::InvalidRange(r.start, r.stop)
@@ -0,0 +1,27 @@
+/// Controls how the parser treats small deviations from the specification in received objects
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseMode {
+    /// Any deviation from the specification fails the entire fragment
+    Strict,
+    /// Recoverable deviations are logged as anomalies and parsing continues
+    ///
+    /// The following deviations are tolerated:
+    ///
+    /// * zero-length octet strings (g110v0/g111v0) which are reported as empty values
+    /// * trailing padding bytes (0x00) after the last object header which are ignored
+    /// * ranges where stop < start which are treated as empty
+    Lenient,
+}
+
+impl Default for ParseMode {
+    fn default() -> Self {
+        Self::Strict
+    }
+}
+
+impl ParseMode {
+    pub(crate) fn is_lenient(self) -> bool {
+        self == ParseMode::Lenient
+    }
+}
@@ -1,15 +1,27 @@
 use std::time::Duration;
 
-/// Parameterizes the minimum and maximum delays between retries
-/// for a retry strategy based on exponential backoff
+use crate::util::random::XorShift;
+
+/// Parameterizes the delays between retries for a retry strategy based on exponential backoff
+///
+/// Each consecutive failure multiplies the delay by `multiplier`, up to `max_delay`. A random
+/// `jitter` spreads the delays of many devices retrying at the same time, e.g. after a
+/// network outage, so that they do not retry in lock-step.
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "UncheckedRetryStrategy"))]
 pub struct RetryStrategy {
     pub(crate) min_delay: Duration,
     pub(crate) max_delay: Duration,
+    pub(crate) multiplier: f64,
+    pub(crate) jitter: f64,
+    // only read when reconnecting TCP clients
+    #[cfg_attr(not(any(test, feature = "tcp")), allow(dead_code))]
+    pub(crate) reset_after_success: Duration,
 }
 
 impl RetryStrategy {
-    /// construct a `RetryStrategy`
+    /// construct a `RetryStrategy` that doubles the delay after each failure without jitter
     ///
     /// `min_delay` - the minimum amount of time for the retry
     /// `max_delay` - the maximum amount of time for the retry
@@ -17,21 +29,64 @@ impl RetryStrategy {
         Self {
             min_delay,
             max_delay,
+            multiplier: 2.0,
+            jitter: 0.0,
+            reset_after_success: Duration::from_secs(0),
+        }
+    }
+
+    /// factor applied to the delay after each consecutive failure, values less than 1.0 are treated as 1.0
+    ///
+    /// Non-finite values are rejected and leave the multiplier unchanged.
+    pub fn with_multiplier(self, multiplier: f64) -> Self {
+        if !multiplier.is_finite() {
+            return self;
+        }
+        Self {
+            multiplier: multiplier.max(1.0),
+            ..self
+        }
+    }
+
+    /// fraction of each delay, in the range [0.0, 1.0], by which it is randomly shortened or lengthened
+    ///
+    /// Non-finite values are rejected and leave the jitter unchanged.
+    pub fn with_jitter(self, jitter: f64) -> Self {
+        if !jitter.is_finite() {
+            return self;
+        }
+        Self {
+            jitter: jitter.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// how long a connection must stay up before the delay is reset to the minimum
+    ///
+    /// Connections that drop sooner count as a failure, so flapping links keep backing off.
+    pub fn with_reset_after_success(self, reset_after_success: Duration) -> Self {
+        Self {
+            reset_after_success,
+            ..self
         }
     }
 }
 
 /// Parameterizes connection attempts
+#[cfg(feature = "tcp")]
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConnectStrategy {
-    /// Minimum delay between two connection attempts, doubles up to the maximum delay
-    pub(crate) min_connect_delay: Duration,
-    /// Maximum delay between two connection attempts
-    pub(crate) max_connect_delay: Duration,
+    /// Delays between failed connection attempts
+    pub(crate) retry: RetryStrategy,
     /// Delay before attempting a connection after a disconnect
     pub(crate) reconnect_delay: Duration,
+    /// Connect only when tasks are pending and disconnect after this time without tasks
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) dial_up_idle_timeout: Option<Duration>,
 }
 
+#[cfg(feature = "tcp")]
 impl ConnectStrategy {
     /// construct a `ConnectStrategy`
     ///
@@ -43,14 +98,44 @@ impl ConnectStrategy {
         max_connect_delay: Duration,
         reconnect_delay: Duration,
     ) -> Self {
+        Self::with_retry_strategy(
+            RetryStrategy::new(min_connect_delay, max_connect_delay),
+            reconnect_delay,
+        )
+    }
+
+    /// construct a `ConnectStrategy` from a `RetryStrategy`
+    ///
+    /// `retry` - Delays between failed connection attempts
+    /// `reconnect_delay` - Delay before attempting a connection after a disconnect, subject to
+    /// the same jitter as the retry delays
+    pub fn with_retry_strategy(retry: RetryStrategy, reconnect_delay: Duration) -> Self {
         ConnectStrategy {
-            min_connect_delay,
-            max_connect_delay,
+            retry,
             reconnect_delay,
+            dial_up_idle_timeout: None,
+        }
+    }
+
+    /// connect only when a task is pending, e.g. a user request, an automatic task, or a periodic
+    /// poll, and disconnect once no task has run for `idle_timeout`
+    ///
+    /// This suits metered links, e.g. cellular modems, where an idle connection has a cost.
+    /// Requests made while disconnected are queued and establish the connection. The associations
+    /// keep their startup state across these disconnections, so the startup integrity poll is not
+    /// repeated and unsolicited responses buffered by the outstation are accepted on the next
+    /// connection. Keep-alives are only sent while connected and never establish a connection.
+    ///
+    /// Only used by master TCP clients
+    pub fn with_dial_up(self, idle_timeout: Duration) -> Self {
+        Self {
+            dial_up_idle_timeout: Some(idle_timeout),
+            ..self
         }
     }
 }
 
+#[cfg(feature = "tcp")]
 impl Default for ConnectStrategy {
     fn default() -> Self {
         Self::new(
@@ -67,10 +152,42 @@ impl Default for RetryStrategy {
     }
 }
 
+/// fields of a deserialized `RetryStrategy`, which are limited by the same methods used in code
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedRetryStrategy {
+    min_delay: Duration,
+    max_delay: Duration,
+    #[serde(default = "UncheckedRetryStrategy::default_multiplier")]
+    multiplier: f64,
+    #[serde(default)]
+    jitter: f64,
+    #[serde(default)]
+    reset_after_success: Duration,
+}
+
+#[cfg(feature = "serde")]
+impl UncheckedRetryStrategy {
+    fn default_multiplier() -> f64 {
+        2.0
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<UncheckedRetryStrategy> for RetryStrategy {
+    fn from(value: UncheckedRetryStrategy) -> Self {
+        RetryStrategy::new(value.min_delay, value.max_delay)
+            .with_multiplier(value.multiplier)
+            .with_jitter(value.jitter)
+            .with_reset_after_success(value.reset_after_success)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct ExponentialBackOff {
     strategy: RetryStrategy,
     last: Option<Duration>,
+    rng: XorShift,
 }
 
 impl ExponentialBackOff {
@@ -78,27 +195,122 @@ impl ExponentialBackOff {
         Self {
             strategy,
             last: None,
+            rng: XorShift::new(),
         }
     }
 
+    #[cfg(any(test, feature = "tcp"))]
     pub(crate) fn on_success(&mut self) {
         self.last = None;
     }
 
     pub(crate) fn on_failure(&mut self) -> Duration {
-        match self.last {
+        let next = match self.last {
             Some(x) => {
-                let next = x
-                    .checked_mul(2)
-                    .unwrap_or(self.strategy.max_delay)
-                    .min(self.strategy.max_delay);
-                self.last = Some(next);
-                next
-            }
-            None => {
-                self.last = Some(self.strategy.min_delay);
-                self.strategy.min_delay
+                let max = self.strategy.max_delay.as_secs_f64();
+                Duration::from_secs_f64((x.as_secs_f64() * self.strategy.multiplier).min(max))
             }
+            None => self.strategy.min_delay,
+        };
+        self.last = Some(next);
+        self.jitter(next)
+    }
+
+    /// returns the delay before reconnecting after a connection that stayed up for `uptime`
+    #[cfg(any(test, feature = "tcp"))]
+    pub(crate) fn on_disconnect(
+        &mut self,
+        uptime: Duration,
+        reconnect_delay: Duration,
+    ) -> Duration {
+        if uptime < self.strategy.reset_after_success {
+            return self.on_failure().max(reconnect_delay);
+        }
+        self.on_success();
+        self.jitter(reconnect_delay)
+    }
+
+    fn jitter(&mut self, delay: Duration) -> Duration {
+        if self.strategy.jitter == 0.0 {
+            return delay;
         }
+        // uniformly distributed in the range [-jitter, jitter]
+        let factor = 1.0 + self.strategy.jitter * (2.0 * self.rng.next_f64() - 1.0);
+        delay.mul_f64(factor).min(self.strategy.max_delay)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn delay_grows_by_multiplier_up_to_maximum() {
+        let strategy = RetryStrategy::new(Duration::from_secs(1), Duration::from_secs(10))
+            .with_multiplier(3.0);
+        let mut backoff = ExponentialBackOff::new(strategy);
+        assert_eq!(backoff.on_failure(), Duration::from_secs(1));
+        assert_eq!(backoff.on_failure(), Duration::from_secs(3));
+        assert_eq!(backoff.on_failure(), Duration::from_secs(9));
+        assert_eq!(backoff.on_failure(), Duration::from_secs(10));
+        backoff.on_success();
+        assert_eq!(backoff.on_failure(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let strategy = RetryStrategy::new(Duration::from_secs(4), Duration::from_secs(100))
+            .with_multiplier(1.0)
+            .with_jitter(0.5);
+        let mut backoff = ExponentialBackOff::new(strategy);
+        for _ in 0..100 {
+            let delay = backoff.on_failure();
+            assert!(delay >= Duration::from_secs(2));
+            assert!(delay <= Duration::from_secs(6));
+        }
+    }
+
+    #[test]
+    fn non_finite_values_are_rejected() {
+        let strategy = RetryStrategy::default()
+            .with_multiplier(3.0)
+            .with_jitter(0.25)
+            .with_multiplier(f64::INFINITY)
+            .with_jitter(f64::NAN);
+        assert_eq!(strategy.multiplier, 3.0);
+        assert_eq!(strategy.jitter, 0.25);
+
+        let mut backoff = ExponentialBackOff::new(RetryStrategy::default().with_jitter(f64::NAN));
+        assert_eq!(backoff.on_failure(), Duration::from_secs(1));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialized_strategy_is_limited_like_the_builder() {
+        let json = r#"{"min_delay":{"secs":1,"nanos":0},"max_delay":{"secs":10,"nanos":0},"multiplier":0.5,"jitter":4.0}"#;
+        let strategy: RetryStrategy = serde_json::from_str(json).unwrap();
+        assert_eq!(strategy.min_delay, Duration::from_secs(1));
+        assert_eq!(strategy.max_delay, Duration::from_secs(10));
+        assert_eq!(strategy.multiplier, 1.0);
+        assert_eq!(strategy.jitter, 1.0);
+        assert_eq!(strategy.reset_after_success, Duration::from_secs(0));
+    }
+
+    #[test]
+    fn short_connections_keep_backing_off() {
+        let strategy = RetryStrategy::new(Duration::from_secs(1), Duration::from_secs(10))
+            .with_reset_after_success(Duration::from_secs(60));
+        let mut backoff = ExponentialBackOff::new(strategy);
+        let reconnect = Duration::from_millis(500);
+        assert_eq!(backoff.on_failure(), Duration::from_secs(1));
+        assert_eq!(
+            backoff.on_disconnect(Duration::from_secs(5), reconnect),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            backoff.on_disconnect(Duration::from_secs(60), reconnect),
+            reconnect
+        );
+        assert_eq!(backoff.on_failure(), Duration::from_secs(1));
     }
 }
@@ -1,5 +1,6 @@
 /// Application-layer sequence number
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sequence {
     value: u8,
 }
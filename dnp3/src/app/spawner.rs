@@ -0,0 +1,107 @@
+use std::future::Future;
+use std::pin::Pin;
+
+/// Task of a master channel or outstation passed to a [Spawner]
+pub type SpawnedTask = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Places the tasks of master channels and outstations onto a particular runtime
+///
+/// Without a spawner, the `spawn_*` functions spawn tasks onto the Tokio runtime from which they
+/// are called, which requires the `tokio-runtime` feature. A spawner lets an application run latency-critical channels, e.g. serial channels,
+/// on a dedicated runtime whose threads may be pinned to particular cores, away from bulk TCP
+/// traffic. Tasks spawned by a running server, e.g. for each accepted connection, run on the same
+/// runtime as the server.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use dnp3::app::Spawner;
+///
+/// let runtime = tokio::runtime::Runtime::new().unwrap();
+/// let handle = runtime.handle().clone();
+/// let spawner = Arc::new(Spawner::new(move |task| {
+///     handle.spawn(task);
+/// }));
+/// ```
+///
+/// The configurations of all the channels and outstations on a runtime may share the same
+/// spawner.
+pub struct Spawner {
+    spawn: Box<dyn Fn(SpawnedTask) + Send + Sync>,
+}
+
+impl Spawner {
+    /// Create a spawner from a function that spawns a task, e.g. onto a `tokio::runtime::Handle`
+    pub fn new<F>(spawn: F) -> Self
+    where
+        F: Fn(SpawnedTask) + Send + Sync + 'static,
+    {
+        Self {
+            spawn: Box::new(spawn),
+        }
+    }
+
+    /// spawn a task onto the spawner, if any, or onto the current runtime
+    pub(crate) fn spawn<F>(spawner: Option<&Spawner>, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        match spawner {
+            Some(spawner) => (spawner.spawn)(Box::pin(future)),
+            None => Self::spawn_default(future),
+        }
+    }
+
+    #[cfg(any(test, feature = "tokio-runtime"))]
+    fn spawn_default<F>(future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        crate::tokio::spawn(future);
+    }
+
+    // without a runtime to fall back on, the task is dropped and its handle reports the shutdown
+    #[cfg(not(any(test, feature = "tokio-runtime")))]
+    fn spawn_default<F>(future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        drop(future);
+        tracing::error!(
+            "task not spawned: no spawner is configured and the tokio-runtime feature is disabled"
+        );
+    }
+}
+
+impl std::fmt::Debug for Spawner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Spawner").finish()
+    }
+}
+
+// spawners are compared by identity so that configurations referring to the same spawner are equal
+impl PartialEq for Spawner {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn tasks_are_passed_to_the_spawner() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let spawned = count.clone();
+        let spawner = Spawner::new(move |_| {
+            spawned.fetch_add(1, Ordering::Relaxed);
+        });
+
+        Spawner::spawn(Some(&spawner), async {});
+        Spawner::spawn(Some(&spawner), async {});
+        assert_eq!(count.load(Ordering::Relaxed), 2);
+    }
+}
@@ -1,8 +1,12 @@
 use std::time::Duration;
 
+use crate::app::Clock;
+
 /// A wrapper around a std::time::Duration
 /// that ensures values are in the range `[1ms .. 1hour]`
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Duration", into = "Duration"))]
 pub struct Timeout {
     value: Duration,
 }
@@ -59,10 +63,24 @@ impl Timeout {
         Ok(Self { value })
     }
 
-    pub(crate) fn deadline_from_now(self) -> crate::tokio::time::Instant {
+    pub(crate) fn deadline_from_now(self, clock: &Clock) -> crate::app::Instant {
         // if this panics due to overflow we have bigger problems than the panic
         // it means the tim value being returned by now() is WAAAY too big
-        crate::tokio::time::Instant::now() + self.value
+        clock.now() + self.value
+    }
+}
+
+impl std::convert::TryFrom<Duration> for Timeout {
+    type Error = RangeError;
+
+    fn try_from(value: Duration) -> Result<Self, Self::Error> {
+        Self::from_duration(value)
+    }
+}
+
+impl From<Timeout> for Duration {
+    fn from(value: Timeout) -> Self {
+        value.value
     }
 }
 
@@ -1,15 +1,17 @@
 use std::convert::TryFrom;
 use std::time::{Duration, SystemTime};
 
-use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use chrono::{DateTime, LocalResult, NaiveDateTime, SecondsFormat, TimeZone, Utc};
 
 use crate::app::measurement::DoubleBit;
-use crate::app::variations::Variation;
+use crate::app::variations::{Group50Var4, Variation};
 use crate::app::QualifierCode;
 use crate::util::cursor::{WriteCursor, WriteError};
 
 /// Wrapper around a u64 count of milliseconds since Unix epoch UTC
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Timestamp {
     value: u64,
 }
@@ -37,16 +39,10 @@ impl Timestamp {
     }
 
     /// Attempt to create a Timestamp from a SystemTime
+    ///
+    /// Returns `None` if the time is before the epoch or after [Timestamp::max].
     pub fn try_from_system_time(system_time: SystemTime) -> Option<Timestamp> {
-        Some(Timestamp::new(
-            u64::try_from(
-                system_time
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .ok()?
-                    .as_millis(),
-            )
-            .ok()?,
-        ))
+        Self::try_from(system_time).ok()
     }
 
     /// Attempt to create a DateTime<Utc> from a Timestamp
@@ -54,6 +50,49 @@ impl Timestamp {
         Utc.timestamp_millis_opt(self.value as i64).single()
     }
 
+    /// Convert the timestamp to a SystemTime, which can represent any valid timestamp
+    pub fn to_system_time(self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(self.value)
+    }
+
+    /// Attempt to create a Timestamp from a chrono `DateTime` in any time zone
+    ///
+    /// Sub-millisecond precision is truncated.
+    pub fn try_from_datetime<Tz: TimeZone>(
+        datetime: &DateTime<Tz>,
+    ) -> Result<Timestamp, TimestampError> {
+        Self::from_millis(datetime.timestamp_millis())
+    }
+
+    /// Attempt to create a Timestamp from a civil (wall clock) date and time in a time zone
+    ///
+    /// Local times skipped by a daylight saving transition are rejected, and local times that
+    /// occur twice are reported along with both candidates so that the caller can decide which
+    /// one is meant instead of silently picking one.
+    pub fn try_from_local<Tz: TimeZone>(
+        tz: &Tz,
+        local: &NaiveDateTime,
+    ) -> Result<Timestamp, TimestampError> {
+        match tz.from_local_datetime(local) {
+            LocalResult::Single(x) => Self::try_from_datetime(&x),
+            LocalResult::Ambiguous(earliest, latest) => Err(TimestampError::AmbiguousLocalTime(
+                Self::try_from_datetime(&earliest)?,
+                Self::try_from_datetime(&latest)?,
+            )),
+            LocalResult::None => Err(TimestampError::NonExistentLocalTime),
+        }
+    }
+
+    fn from_millis(millis: i64) -> Result<Timestamp, TimestampError> {
+        if millis < 0 {
+            return Err(TimestampError::BeforeEpoch);
+        }
+        if millis as u64 > Self::MAX_VALUE {
+            return Err(TimestampError::OutOfRange);
+        }
+        Ok(Timestamp::new(millis as u64))
+    }
+
     /// Retrieve the raw u64 value
     pub fn raw_value(&self) -> u64 {
         self.value
@@ -74,6 +113,96 @@ impl Timestamp {
     }
 }
 
+/// Errors that occur when converting a time to a [Timestamp]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TimestampError {
+    /// the time is before the Unix epoch
+    BeforeEpoch,
+    /// the time is after [Timestamp::max]
+    OutOfRange,
+    /// the local time doesn't exist because it was skipped by a daylight saving transition
+    NonExistentLocalTime,
+    /// the local time occurs twice because of a daylight saving transition
+    ///
+    /// The earliest and latest candidates are provided.
+    AmbiguousLocalTime(Timestamp, Timestamp),
+}
+
+impl TryFrom<SystemTime> for Timestamp {
+    type Error = TimestampError;
+
+    fn try_from(value: SystemTime) -> Result<Self, Self::Error> {
+        let millis = value
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|_| TimestampError::BeforeEpoch)?
+            .as_millis();
+        if millis > Self::MAX_VALUE as u128 {
+            return Err(TimestampError::OutOfRange);
+        }
+        Ok(Timestamp::new(millis as u64))
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(value: Timestamp) -> Self {
+        value.to_system_time()
+    }
+}
+
+impl<Tz: TimeZone> TryFrom<DateTime<Tz>> for Timestamp {
+    type Error = TimestampError;
+
+    fn try_from(value: DateTime<Tz>) -> Result<Self, Self::Error> {
+        Self::try_from_datetime(&value)
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::OffsetDateTime> for Timestamp {
+    type Error = TimestampError;
+
+    fn try_from(value: time::OffsetDateTime) -> Result<Self, Self::Error> {
+        let millis = value.unix_timestamp_nanos() / 1_000_000;
+        if millis < 0 {
+            return Err(TimestampError::BeforeEpoch);
+        }
+        if millis > Self::MAX_VALUE as i128 {
+            return Err(TimestampError::OutOfRange);
+        }
+        Ok(Timestamp::new(millis as u64))
+    }
+}
+
+// timestamps beyond the year 9999 can't be represented without the large-dates feature of `time`
+#[cfg(feature = "time")]
+impl TryFrom<Timestamp> for time::OffsetDateTime {
+    type Error = TimestampError;
+
+    fn try_from(value: Timestamp) -> Result<Self, Self::Error> {
+        time::OffsetDateTime::from_unix_timestamp_nanos(value.value as i128 * 1_000_000)
+            .map_err(|_| TimestampError::OutOfRange)
+    }
+}
+
+impl std::fmt::Display for TimestampError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TimestampError::BeforeEpoch => f.write_str("time is before the Unix epoch"),
+            TimestampError::OutOfRange => f.write_str("time exceeds the maximum DNP3 timestamp"),
+            TimestampError::NonExistentLocalTime => {
+                f.write_str("local time was skipped by a daylight saving transition")
+            }
+            TimestampError::AmbiguousLocalTime(earliest, latest) => write!(
+                f,
+                "local time is ambiguous, it may be {} or {}",
+                earliest, latest
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TimestampError {}
+
 impl std::fmt::Display for Timestamp {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self.to_datetime_utc() {
@@ -83,6 +212,49 @@ impl std::fmt::Display for Timestamp {
     }
 }
 
+/// Absolute time and interval associated with an index (g50v4)
+///
+/// Outstations use these values as per-index parameters, e.g. the schedule of a freeze. The
+/// meaning of each index is defined by the device.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexedTime {
+    /// absolute time of the first occurrence
+    pub time: Timestamp,
+    /// number of `units` between occurrences
+    pub interval: u32,
+    /// units of the interval as defined in the standard, e.g. 0 = no repetition, 1 = milliseconds,
+    /// 2 = seconds, 3 = minutes, 4 = hours, 5 = days
+    pub units: u8,
+}
+
+impl IndexedTime {
+    /// construct an `IndexedTime` from its fields
+    pub fn new(time: Timestamp, interval: u32, units: u8) -> Self {
+        Self {
+            time,
+            interval,
+            units,
+        }
+    }
+}
+
+impl From<Group50Var4> for IndexedTime {
+    fn from(x: Group50Var4) -> Self {
+        Self::new(x.time, x.interval, x.units)
+    }
+}
+
+impl From<IndexedTime> for Group50Var4 {
+    fn from(x: IndexedTime) -> Self {
+        Self {
+            time: x.time,
+            interval: x.interval,
+            units: x.units,
+        }
+    }
+}
+
 pub(crate) struct BitPair {
     pub(crate) high: bool,
     pub(crate) low: bool,
@@ -161,6 +333,112 @@ mod test {
         timestamp.to_datetime_utc();
     }
 
+    #[test]
+    fn converts_timestamp_to_and_from_system_time() {
+        let timestamp = Timestamp::new(1_600_000_000_123);
+        let system_time = timestamp.to_system_time();
+        assert_eq!(Timestamp::try_from(system_time), Ok(timestamp));
+        assert_eq!(
+            SystemTime::from(Timestamp::max()),
+            Timestamp::max().to_system_time()
+        );
+    }
+
+    #[test]
+    fn rejects_system_time_out_of_range() {
+        let before = SystemTime::UNIX_EPOCH - Duration::from_millis(1);
+        assert_eq!(
+            Timestamp::try_from(before),
+            Err(TimestampError::BeforeEpoch)
+        );
+
+        let after = Timestamp::max().to_system_time() + Duration::from_millis(1);
+        assert_eq!(Timestamp::try_from(after), Err(TimestampError::OutOfRange));
+        assert_eq!(Timestamp::try_from_system_time(after), None);
+    }
+
+    fn naive(day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(1970, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    }
+
+    fn offset(hours: i32) -> chrono::FixedOffset {
+        chrono::FixedOffset::east_opt(hours * 3600).unwrap()
+    }
+
+    /// zone that skips local 02:00-03:00 on day 2 and repeats local 01:00-02:00 on day 3
+    #[derive(Copy, Clone, Debug)]
+    struct DstZone;
+
+    impl TimeZone for DstZone {
+        type Offset = chrono::FixedOffset;
+
+        fn from_offset(_: &Self::Offset) -> Self {
+            DstZone
+        }
+
+        fn offset_from_local_date(&self, _: &chrono::NaiveDate) -> LocalResult<Self::Offset> {
+            LocalResult::Single(offset(0))
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<Self::Offset> {
+            if *local >= naive(2, 2, 0) && *local < naive(2, 3, 0) {
+                return LocalResult::None;
+            }
+            if *local >= naive(3, 1, 0) && *local < naive(3, 2, 0) {
+                return LocalResult::Ambiguous(offset(1), offset(0));
+            }
+            if *local >= naive(2, 3, 0) && *local < naive(3, 1, 0) {
+                return LocalResult::Single(offset(1));
+            }
+            LocalResult::Single(offset(0))
+        }
+
+        fn offset_from_utc_date(&self, _: &chrono::NaiveDate) -> Self::Offset {
+            offset(0)
+        }
+
+        fn offset_from_utc_datetime(&self, _: &NaiveDateTime) -> Self::Offset {
+            offset(0)
+        }
+    }
+
+    #[test]
+    fn converts_datetime_in_any_time_zone() {
+        let datetime = offset(1)
+            .from_local_datetime(&naive(1, 1, 0))
+            .single()
+            .unwrap();
+        assert_eq!(Timestamp::try_from(datetime), Ok(Timestamp::new(0)));
+        assert_eq!(
+            Timestamp::try_from_datetime(&offset(2).from_local_datetime(&naive(1, 1, 0)).unwrap()),
+            Err(TimestampError::BeforeEpoch)
+        );
+    }
+
+    #[test]
+    fn local_times_are_resolved_without_guessing() {
+        const HOUR: u64 = 3600 * 1000;
+
+        assert_eq!(
+            Timestamp::try_from_local(&DstZone, &naive(2, 4, 0)),
+            Ok(Timestamp::new(27 * HOUR))
+        );
+        assert_eq!(
+            Timestamp::try_from_local(&DstZone, &naive(2, 2, 30)),
+            Err(TimestampError::NonExistentLocalTime)
+        );
+        assert_eq!(
+            Timestamp::try_from_local(&DstZone, &naive(3, 1, 0)),
+            Err(TimestampError::AmbiguousLocalTime(
+                Timestamp::new(48 * HOUR),
+                Timestamp::new(49 * HOUR)
+            ))
+        );
+    }
+
     #[test]
     fn timestamp_display_formatting_works_as_expected() {
         assert_eq!(format!("{}", Timestamp::min()), "1970-01-01T00:00:00.000Z");
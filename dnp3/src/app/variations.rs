@@ -18,7 +18,14 @@ use crate::app::measurement::*;
 
 /// All variations supported by the library
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Variation {
+    /// Device Attributes - Specific attribute
+    Group0(u8),
+    /// Device Attributes - Non-specific all attributes request
+    Group0Var254,
+    /// Device Attributes - List of attribute variations
+    Group0Var255,
     /// Binary Input - Any Variation
     Group1Var0,
     /// Binary Input - Packed Format
@@ -201,15 +208,57 @@ pub enum Variation {
     Group60Var4,
     /// Internal Indications - Packed Format
     Group80Var1,
+    /// Application - Identifier
+    Group90Var1,
+    /// Unsigned Integer - Any Variation
+    Group102Var0,
+    /// Unsigned Integer - 8-bit
+    Group102Var1,
     /// Octet String - Sized by variation
     Group110(u8),
     /// Octet String Event - Sized by variation
     Group111(u8),
+    /// Authentication - Challenge
+    Group120Var1,
+    /// Authentication - Reply
+    Group120Var2,
+    /// Authentication - Aggressive Mode Request
+    Group120Var3,
+    /// Authentication - Session Key Status Request
+    Group120Var4,
+    /// Authentication - Session Key Status
+    Group120Var5,
+    /// Authentication - Session Key Change
+    Group120Var6,
+    /// Authentication - Error
+    Group120Var7,
+    /// Authentication - User Certificate
+    Group120Var8,
+    /// Authentication - Message Authentication Code
+    Group120Var9,
+    /// Authentication - User Status Change
+    Group120Var10,
+    /// Authentication - Update Key Change Request
+    Group120Var11,
+    /// Authentication - Update Key Change Reply
+    Group120Var12,
+    /// Authentication - Update Key Change
+    Group120Var13,
+    /// Authentication - Update Key Change Signature
+    Group120Var14,
+    /// Authentication - Update Key Change Confirmation
+    Group120Var15,
 }
 
 impl Variation {
     pub(crate) fn lookup(group: u8, var: u8) -> Option<Variation> {
         match group {
+            0 => match var {
+                0 => None,
+                254 => Some(Variation::Group0Var254),
+                255 => Some(Variation::Group0Var255),
+                _ => Some(Variation::Group0(var)),
+            },
             1 => match var {
                 0 => Some(Variation::Group1Var0),
                 1 => Some(Variation::Group1Var1),
@@ -364,14 +413,44 @@ impl Variation {
                 1 => Some(Variation::Group80Var1),
                 _ => None,
             },
+            90 => match var {
+                1 => Some(Variation::Group90Var1),
+                _ => None,
+            },
+            102 => match var {
+                0 => Some(Variation::Group102Var0),
+                1 => Some(Variation::Group102Var1),
+                _ => None,
+            },
             110 => Some(Variation::Group110(var)),
             111 => Some(Variation::Group111(var)),
+            120 => match var {
+                1 => Some(Variation::Group120Var1),
+                2 => Some(Variation::Group120Var2),
+                3 => Some(Variation::Group120Var3),
+                4 => Some(Variation::Group120Var4),
+                5 => Some(Variation::Group120Var5),
+                6 => Some(Variation::Group120Var6),
+                7 => Some(Variation::Group120Var7),
+                8 => Some(Variation::Group120Var8),
+                9 => Some(Variation::Group120Var9),
+                10 => Some(Variation::Group120Var10),
+                11 => Some(Variation::Group120Var11),
+                12 => Some(Variation::Group120Var12),
+                13 => Some(Variation::Group120Var13),
+                14 => Some(Variation::Group120Var14),
+                15 => Some(Variation::Group120Var15),
+                _ => None,
+            },
             _ => None,
         }
     }
     
     pub(crate) fn to_group_and_var(self) -> (u8, u8) {
         match self {
+            Variation::Group0(x) => (0, x),
+            Variation::Group0Var254 => (0, 254),
+            Variation::Group0Var255 => (0, 255),
             Variation::Group1Var0 => (1, 0),
             Variation::Group1Var1 => (1, 1),
             Variation::Group1Var2 => (1, 2),
@@ -463,13 +542,34 @@ impl Variation {
             Variation::Group60Var3 => (60, 3),
             Variation::Group60Var4 => (60, 4),
             Variation::Group80Var1 => (80, 1),
+            Variation::Group90Var1 => (90, 1),
+            Variation::Group102Var0 => (102, 0),
+            Variation::Group102Var1 => (102, 1),
             Variation::Group110(x) => (110, x),
             Variation::Group111(x) => (111, x),
+            Variation::Group120Var1 => (120, 1),
+            Variation::Group120Var2 => (120, 2),
+            Variation::Group120Var3 => (120, 3),
+            Variation::Group120Var4 => (120, 4),
+            Variation::Group120Var5 => (120, 5),
+            Variation::Group120Var6 => (120, 6),
+            Variation::Group120Var7 => (120, 7),
+            Variation::Group120Var8 => (120, 8),
+            Variation::Group120Var9 => (120, 9),
+            Variation::Group120Var10 => (120, 10),
+            Variation::Group120Var11 => (120, 11),
+            Variation::Group120Var12 => (120, 12),
+            Variation::Group120Var13 => (120, 13),
+            Variation::Group120Var14 => (120, 14),
+            Variation::Group120Var15 => (120, 15),
         }
     }
     
     pub(crate) fn description(self) -> &'static str {
         match self {
+            Variation::Group0(_) => "Device Attributes - Specific attribute",
+            Variation::Group0Var254 => "Device Attributes - Non-specific all attributes request",
+            Variation::Group0Var255 => "Device Attributes - List of attribute variations",
             Variation::Group1Var0 => "Binary Input - Any Variation",
             Variation::Group1Var1 => "Binary Input - Packed Format",
             Variation::Group1Var2 => "Binary Input - With Flags",
@@ -561,12 +661,53 @@ impl Variation {
             Variation::Group60Var3 => "Class Data - Class 2",
             Variation::Group60Var4 => "Class Data - Class 3",
             Variation::Group80Var1 => "Internal Indications - Packed Format",
+            Variation::Group90Var1 => "Application - Identifier",
+            Variation::Group102Var0 => "Unsigned Integer - Any Variation",
+            Variation::Group102Var1 => "Unsigned Integer - 8-bit",
             Variation::Group110(_) => "Octet String - Sized by variation",
             Variation::Group111(_) => "Octet String Event - Sized by variation",
+            Variation::Group120Var1 => "Authentication - Challenge",
+            Variation::Group120Var2 => "Authentication - Reply",
+            Variation::Group120Var3 => "Authentication - Aggressive Mode Request",
+            Variation::Group120Var4 => "Authentication - Session Key Status Request",
+            Variation::Group120Var5 => "Authentication - Session Key Status",
+            Variation::Group120Var6 => "Authentication - Session Key Change",
+            Variation::Group120Var7 => "Authentication - Error",
+            Variation::Group120Var8 => "Authentication - User Certificate",
+            Variation::Group120Var9 => "Authentication - Message Authentication Code",
+            Variation::Group120Var10 => "Authentication - User Status Change",
+            Variation::Group120Var11 => "Authentication - Update Key Change Request",
+            Variation::Group120Var12 => "Authentication - Update Key Change Reply",
+            Variation::Group120Var13 => "Authentication - Update Key Change",
+            Variation::Group120Var14 => "Authentication - Update Key Change Signature",
+            Variation::Group120Var15 => "Authentication - Update Key Change Confirmation",
         }
     }
 }
 
+/// Authentication - Session Key Status Request
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Group120Var4 {
+    /// user field of the variation
+    pub(crate) user: u16,
+}
+
+/// Authentication - Aggressive Mode Request
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Group120Var3 {
+    /// challenge_seq field of the variation
+    pub(crate) challenge_seq: u32,
+    /// user field of the variation
+    pub(crate) user: u16,
+}
+
+/// Unsigned Integer - 8-bit
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Group102Var1 {
+    /// value field of the variation
+    pub(crate) value: u8,
+}
+
 /// Time Delay - Fine
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub(crate) struct Group52Var2 {
@@ -1171,6 +1312,53 @@ pub(crate) struct Group1Var2 {
 }
 
 
+impl FixedSize for Group120Var4 {
+    const SIZE: u8 = 2;
+    fn read(cursor: &mut ReadCursor) -> Result<Self, ReadError> {
+        Ok(
+            Group120Var4 {
+                user: cursor.read_u16_le()?,
+            }
+        )
+    }
+    fn write(&self, cursor: &mut WriteCursor) -> Result<(), WriteError> {
+        cursor.write_u16_le(self.user)?;
+        Ok(())
+    }
+}
+
+impl FixedSize for Group120Var3 {
+    const SIZE: u8 = 6;
+    fn read(cursor: &mut ReadCursor) -> Result<Self, ReadError> {
+        Ok(
+            Group120Var3 {
+                challenge_seq: cursor.read_u32_le()?,
+                user: cursor.read_u16_le()?,
+            }
+        )
+    }
+    fn write(&self, cursor: &mut WriteCursor) -> Result<(), WriteError> {
+        cursor.write_u32_le(self.challenge_seq)?;
+        cursor.write_u16_le(self.user)?;
+        Ok(())
+    }
+}
+
+impl FixedSize for Group102Var1 {
+    const SIZE: u8 = 1;
+    fn read(cursor: &mut ReadCursor) -> Result<Self, ReadError> {
+        Ok(
+            Group102Var1 {
+                value: cursor.read_u8()?,
+            }
+        )
+    }
+    fn write(&self, cursor: &mut WriteCursor) -> Result<(), WriteError> {
+        cursor.write_u8(self.value)?;
+        Ok(())
+    }
+}
+
 impl FixedSize for Group52Var2 {
     const SIZE: u8 = 2;
     fn read(cursor: &mut ReadCursor) -> Result<Self, ReadError> {
@@ -2311,6 +2499,24 @@ impl FixedSize for Group1Var2 {
 }
 
 
+impl std::fmt::Display for Group120Var4 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "user: {}", self.user)
+    }
+}
+
+impl std::fmt::Display for Group120Var3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "challenge_seq: {} user: {}", self.challenge_seq, self.user)
+    }
+}
+
+impl std::fmt::Display for Group102Var1 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "value: {}", self.value)
+    }
+}
+
 impl std::fmt::Display for Group52Var2 {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "time: {}", self.time)
@@ -2714,6 +2920,18 @@ impl std::fmt::Display for Group1Var2 {
 }
 
 
+impl FixedSizeVariation for Group120Var4 {
+    const VARIATION : Variation = Variation::Group120Var4;
+}
+
+impl FixedSizeVariation for Group120Var3 {
+    const VARIATION : Variation = Variation::Group120Var3;
+}
+
+impl FixedSizeVariation for Group102Var1 {
+    const VARIATION : Variation = Variation::Group102Var1;
+}
+
 impl FixedSizeVariation for Group52Var2 {
     const VARIATION : Variation = Variation::Group52Var2;
 }
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::app::variations::Variation;
+use crate::app::QualifierCode;
+use crate::util::cursor::ReadCursor;
+
+/// Vendor-specific objects that the parser reads in addition to the standard objects
+///
+/// Each object is registered with the size of its encoding so that headers containing it can be
+/// parsed with any qualifier other than free-format. The objects are then passed to the
+/// application as raw bytes for it to decode. Cloning the registry is cheap.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VendorObjects {
+    sizes: Arc<BTreeMap<(u8, u8), usize>>,
+}
+
+/// Errors that occur when registering a vendor-specific object
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum VendorObjectError {
+    /// the group and variation are already defined by the standard
+    StandardObject(Variation),
+    /// objects must have a size of at least one byte
+    ZeroSize,
+}
+
+impl VendorObjects {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a vendor-specific group and variation whose objects are `size` bytes long
+    ///
+    /// Registering the same group and variation again replaces its size.
+    pub fn register(
+        &mut self,
+        group: u8,
+        variation: u8,
+        size: usize,
+    ) -> Result<(), VendorObjectError> {
+        if let Some(v) = Variation::lookup(group, variation) {
+            return Err(VendorObjectError::StandardObject(v));
+        }
+        if size == 0 {
+            return Err(VendorObjectError::ZeroSize);
+        }
+        Arc::make_mut(&mut self.sizes).insert((group, variation), size);
+        Ok(())
+    }
+
+    /// true if no objects are registered
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    pub(crate) fn size(&self, group: u8, variation: u8) -> Option<usize> {
+        self.sizes.get(&(group, variation)).copied()
+    }
+}
+
+impl std::fmt::Display for VendorObjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            VendorObjectError::StandardObject(v) => {
+                write!(f, "{} is defined by the standard", v)
+            }
+            VendorObjectError::ZeroSize => f.write_str("vendor objects may not have a zero size"),
+        }
+    }
+}
+
+impl std::error::Error for VendorObjectError {}
+
+/// Object header containing a vendor-specific object registered in [VendorObjects]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct VendorHeader<'a> {
+    /// group of the header
+    pub group: u8,
+    /// variation of the header
+    pub variation: u8,
+    /// qualifier of the header
+    pub qualifier: QualifierCode,
+    /// registered size of each object
+    pub size: usize,
+    /// first index of a range
+    start: u16,
+    /// objects following the header, including any index prefixes
+    objects: &'a [u8],
+}
+
+impl<'a> VendorHeader<'a> {
+    pub(crate) fn new(
+        group: u8,
+        variation: u8,
+        qualifier: QualifierCode,
+        size: usize,
+        start: u16,
+        objects: &'a [u8],
+    ) -> Self {
+        Self {
+            group,
+            variation,
+            qualifier,
+            size,
+            start,
+            objects,
+        }
+    }
+
+    /// Iterate over the raw bytes of each object along with its index
+    ///
+    /// Objects of a header with a count qualifier don't have an index and are numbered from zero.
+    pub fn iter(&self) -> VendorObjectIterator<'a> {
+        VendorObjectIterator {
+            qualifier: self.qualifier,
+            size: self.size,
+            position: 0,
+            start: self.start,
+            cursor: ReadCursor::new(self.objects),
+        }
+    }
+}
+
+impl std::fmt::Display for VendorHeader<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "g{}v{} : Vendor - {}",
+            self.group,
+            self.variation,
+            self.qualifier.description(),
+        )?;
+        for (index, object) in self.iter() {
+            write!(f, "\nindex: {} {:02X?}", index, object)?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over the objects of a [VendorHeader]
+#[derive(Copy, Clone, Debug)]
+pub struct VendorObjectIterator<'a> {
+    qualifier: QualifierCode,
+    size: usize,
+    position: u16,
+    start: u16,
+    cursor: ReadCursor<'a>,
+}
+
+impl<'a> Iterator for VendorObjectIterator<'a> {
+    type Item = (u16, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cursor.is_empty() {
+            return None;
+        }
+
+        let index = match self.qualifier {
+            QualifierCode::CountAndPrefix8 => self.cursor.read_u8().ok()? as u16,
+            QualifierCode::CountAndPrefix16 => self.cursor.read_u16_le().ok()?,
+            QualifierCode::Range8 | QualifierCode::Range16 => {
+                self.start.wrapping_add(self.position)
+            }
+            _ => self.position,
+        };
+        let object = self.cursor.read_bytes(self.size).ok()?;
+        self.position = self.position.wrapping_add(1);
+        Some((index, object))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rejects_standard_objects_and_zero_size() {
+        let mut objects = VendorObjects::new();
+        assert_eq!(
+            objects.register(1, 2, 1),
+            Err(VendorObjectError::StandardObject(Variation::Group1Var2))
+        );
+        assert_eq!(
+            objects.register(210, 1, 0),
+            Err(VendorObjectError::ZeroSize)
+        );
+        assert!(objects.is_empty());
+        assert_eq!(objects.register(210, 1, 4), Ok(()));
+        assert_eq!(objects.size(210, 1), Some(4));
+    }
+
+    #[test]
+    fn iterates_over_prefixed_objects() {
+        let header = VendorHeader::new(
+            210,
+            1,
+            QualifierCode::CountAndPrefix16,
+            2,
+            0,
+            &[0x07, 0x00, 0xAA, 0xBB, 0x09, 0x00, 0xCC, 0xDD],
+        );
+        let objects: Vec<(u16, &[u8])> = header.iter().collect();
+        assert_eq!(
+            objects,
+            vec![(7, &[0xAA, 0xBB][..]), (9, &[0xCC, 0xDD][..])]
+        );
+    }
+}
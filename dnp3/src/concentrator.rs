@@ -0,0 +1,436 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::ops::RangeInclusive;
+
+use crate::app::measurement::*;
+use crate::app::{Bytes, ResponseHeader};
+use crate::master::{HeaderInfo, ReadHandler, ReadType};
+use crate::outstation::database::*;
+use crate::outstation::OutstationHandle;
+
+/// Type of a measurement bridged by a [ConcentratorMap]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PointType {
+    /// `Binary` input
+    Binary,
+    /// `DoubleBitBinary` input
+    DoubleBitBinary,
+    /// `BinaryOutputStatus`
+    BinaryOutputStatus,
+    /// `Counter`
+    Counter,
+    /// `FrozenCounter`
+    FrozenCounter,
+    /// `Analog` input
+    Analog,
+    /// `AnalogOutputStatus`
+    AnalogOutputStatus,
+}
+
+/// Bits set and cleared in the flags of every value bridged for a point, e.g. to report that the
+/// values are remote forced
+///
+/// The bits in `clear` are cleared before the bits in `set` are set
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FlagTranslation {
+    /// bits that are set
+    pub set: Flags,
+    /// bits that are cleared
+    pub clear: Flags,
+}
+
+impl FlagTranslation {
+    /// Translation that reports the flags of the upstream outstation unchanged
+    pub const NONE: FlagTranslation = FlagTranslation {
+        set: Flags::new(0),
+        clear: Flags::new(0),
+    };
+
+    /// Create a translation that sets and clears the specified bits
+    pub fn new(set: Flags, clear: Flags) -> Self {
+        Self { set, clear }
+    }
+
+    /// Apply the translation to a set of flags
+    pub fn apply(&self, flags: Flags) -> Flags {
+        Flags::new((flags.value & !self.clear.value) | self.set.value)
+    }
+}
+
+impl Default for FlagTranslation {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Point of the local outstation to which a point of the upstream outstation is bridged
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PointMapping {
+    /// index of the point in the database of the local outstation
+    pub index: u16,
+    /// event class of the point in the local outstation, `None` for a static-only point
+    pub class: Option<EventClass>,
+    /// translation of the flags of the values
+    pub flags: FlagTranslation,
+}
+
+impl PointMapping {
+    /// Map to the specified index and class without translating the flags
+    pub fn new(index: u16, class: Option<EventClass>) -> Self {
+        Self {
+            index,
+            class,
+            flags: FlagTranslation::NONE,
+        }
+    }
+
+    /// Translate the flags of the values of the point
+    pub fn with_flags(self, flags: FlagTranslation) -> Self {
+        Self { flags, ..self }
+    }
+}
+
+/// Rules that map the points of an upstream outstation, read by a master association, onto the
+/// database of a local outstation, producing a DNP3-to-DNP3 data concentrator
+///
+/// ```no_run
+/// use dnp3::app::measurement::Flags;
+/// use dnp3::concentrator::*;
+/// use dnp3::outstation::database::EventClass;
+/// # fn wire(outstation: dnp3::outstation::OutstationHandle) {
+///
+/// let map = ConcentratorMap::new()
+///     // upstream binaries 0 to 9 are reported as binaries 100 to 109 in class 1
+///     .map_range(PointType::Binary, 0..=9, 100, Some(EventClass::Class1))
+///     // upstream analog 3 is reported as analog 0 in class 2, flagged as remote forced
+///     .map(
+///         PointType::Analog,
+///         3,
+///         PointMapping::new(0, Some(EventClass::Class2))
+///             .with_flags(FlagTranslation::new(Flags::REMOTE_FORCED, Flags::new(0))),
+///     );
+///
+/// // create the points in the local outstation and pass the read handler to the association
+/// outstation.transaction(|db| map.add_points(db));
+/// let read_handler = map.create_read_handler(outstation);
+/// # }
+/// ```
+///
+/// Values of unmapped points are discarded. Events received from the upstream outstation always
+/// produce an event in the local outstation, while static values only produce one if they changed.
+#[derive(Clone, Debug, Default)]
+pub struct ConcentratorMap {
+    points: HashMap<(PointType, u16), PointMapping>,
+}
+
+impl ConcentratorMap {
+    /// Create a map without any points
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bridge a point of the upstream outstation, replacing any existing mapping of the point
+    pub fn map(mut self, point_type: PointType, upstream: u16, mapping: PointMapping) -> Self {
+        self.points.insert((point_type, upstream), mapping);
+        self
+    }
+
+    /// Bridge a range of points of the upstream outstation to consecutive indices of the local
+    /// outstation starting at `first_index`, all in the same class
+    ///
+    /// Indices that would be past the maximum index of the local outstation are not mapped
+    pub fn map_range(
+        mut self,
+        point_type: PointType,
+        upstream: RangeInclusive<u16>,
+        first_index: u16,
+        class: Option<EventClass>,
+    ) -> Self {
+        for (offset, upstream) in upstream.enumerate() {
+            let index = match u16::try_from(offset)
+                .ok()
+                .and_then(|x| first_index.checked_add(x))
+            {
+                Some(x) => x,
+                None => break,
+            };
+            self.points
+                .insert((point_type, upstream), PointMapping::new(index, class));
+        }
+        self
+    }
+
+    /// Mapping of a point of the upstream outstation, if any
+    pub fn get(&self, point_type: PointType, upstream: u16) -> Option<PointMapping> {
+        self.points.get(&(point_type, upstream)).copied()
+    }
+
+    /// Add every mapped point to the database of the local outstation with its class and the
+    /// default configuration of its type
+    ///
+    /// Points that already exist are left unchanged
+    pub fn add_points(&self, database: &mut Database) {
+        for ((point_type, _), mapping) in self.points.iter() {
+            let index = mapping.index;
+            let class = mapping.class;
+            match point_type {
+                PointType::Binary => database.add(index, class, BinaryConfig::default()),
+                PointType::DoubleBitBinary => {
+                    database.add(index, class, DoubleBitBinaryConfig::default())
+                }
+                PointType::BinaryOutputStatus => {
+                    database.add(index, class, BinaryOutputStatusConfig::default())
+                }
+                PointType::Counter => database.add(index, class, CounterConfig::default()),
+                PointType::FrozenCounter => {
+                    database.add(index, class, FrozenCounterConfig::default())
+                }
+                PointType::Analog => database.add(index, class, AnalogConfig::default()),
+                PointType::AnalogOutputStatus => {
+                    database.add(index, class, AnalogOutputStatusConfig::default())
+                }
+            };
+        }
+    }
+
+    /// Create the read handler of the master association that updates the local outstation
+    pub fn create_read_handler(&self, outstation: OutstationHandle) -> Box<dyn ReadHandler> {
+        Box::new(ConcentratorReadHandler {
+            map: self.clone(),
+            outstation,
+        })
+    }
+}
+
+struct ConcentratorReadHandler {
+    map: ConcentratorMap,
+    outstation: OutstationHandle,
+}
+
+/// measurement types whose flags can be translated
+trait WithFlags {
+    fn flags_mut(&mut self) -> &mut Flags;
+}
+
+impl ConcentratorReadHandler {
+    fn bridge<T>(
+        &mut self,
+        point_type: PointType,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (T, u16)>,
+    ) where
+        T: WithFlags,
+        Database: Update<T>,
+    {
+        let options = if is_event(info) {
+            UpdateOptions::new(true, EventMode::Force)
+        } else {
+            UpdateOptions::default()
+        };
+
+        let map = &self.map;
+        let mut iter = iter.filter_map(|(mut value, upstream)| {
+            let mapping = map.get(point_type, upstream)?;
+            let flags = value.flags_mut();
+            *flags = mapping.flags.apply(*flags);
+            Some((value, mapping.index))
+        });
+
+        self.outstation.transaction(|db| {
+            for (value, index) in &mut iter {
+                db.update(index, &value, options);
+            }
+        });
+    }
+}
+
+fn is_event(info: HeaderInfo) -> bool {
+    let (group, _) = info.variation.to_group_and_var();
+    matches!(group, 2 | 4 | 11 | 22 | 23 | 32 | 42)
+}
+
+impl ReadHandler for ConcentratorReadHandler {
+    fn begin_fragment(&mut self, _read_type: ReadType, _header: ResponseHeader) {}
+
+    fn end_fragment(&mut self, _read_type: ReadType, _header: ResponseHeader) {}
+
+    fn handle_binary(&mut self, info: HeaderInfo, iter: &mut dyn Iterator<Item = (Binary, u16)>) {
+        self.bridge(PointType::Binary, info, iter);
+    }
+
+    fn handle_double_bit_binary(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (DoubleBitBinary, u16)>,
+    ) {
+        self.bridge(PointType::DoubleBitBinary, info, iter);
+    }
+
+    fn handle_binary_output_status(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (BinaryOutputStatus, u16)>,
+    ) {
+        self.bridge(PointType::BinaryOutputStatus, info, iter);
+    }
+
+    fn handle_counter(&mut self, info: HeaderInfo, iter: &mut dyn Iterator<Item = (Counter, u16)>) {
+        self.bridge(PointType::Counter, info, iter);
+    }
+
+    fn handle_frozen_counter(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (FrozenCounter, u16)>,
+    ) {
+        self.bridge(PointType::FrozenCounter, info, iter);
+    }
+
+    fn handle_analog(&mut self, info: HeaderInfo, iter: &mut dyn Iterator<Item = (Analog, u16)>) {
+        self.bridge(PointType::Analog, info, iter);
+    }
+
+    fn handle_analog_output_status(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (AnalogOutputStatus, u16)>,
+    ) {
+        self.bridge(PointType::AnalogOutputStatus, info, iter);
+    }
+
+    fn handle_octet_string<'a>(
+        &mut self,
+        _info: HeaderInfo,
+        _iter: &mut dyn Iterator<Item = (Bytes<'a>, u16)>,
+    ) {
+    }
+}
+
+impl WithFlags for Binary {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+}
+
+impl WithFlags for DoubleBitBinary {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+}
+
+impl WithFlags for BinaryOutputStatus {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+}
+
+impl WithFlags for Counter {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+}
+
+impl WithFlags for FrozenCounter {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+}
+
+impl WithFlags for Analog {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+}
+
+impl WithFlags for AnalogOutputStatus {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::app::{QualifierCode, Variation};
+    use crate::link::{EndpointAddress, LinkErrorMode};
+    use crate::outstation::task::OutstationTask;
+    use crate::outstation::*;
+
+    use super::*;
+
+    fn outstation() -> OutstationHandle {
+        let (_, handle) = OutstationTask::create(
+            LinkErrorMode::Close,
+            OutstationConfig::new(
+                EndpointAddress::from(10).unwrap(),
+                EndpointAddress::from(1).unwrap(),
+            ),
+            EventBufferConfig::all_types(10),
+            DefaultOutstationApplication::create(),
+            DefaultOutstationInformation::create(),
+            DefaultControlHandler::create(),
+        );
+        handle
+    }
+
+    fn analog(value: f64) -> Analog {
+        Analog::new(value, Flags::ONLINE, Time::synchronized(0))
+    }
+
+    #[test]
+    fn ranges_are_mapped_to_consecutive_indices() {
+        let map = ConcentratorMap::new().map_range(
+            PointType::Binary,
+            2..=4,
+            u16::MAX - 1,
+            Some(EventClass::Class1),
+        );
+
+        assert_eq!(
+            map.get(PointType::Binary, 3),
+            Some(PointMapping::new(u16::MAX, Some(EventClass::Class1)))
+        );
+        // past the maximum index
+        assert_eq!(map.get(PointType::Binary, 4), None);
+        assert_eq!(map.get(PointType::Analog, 2), None);
+    }
+
+    #[test]
+    fn values_are_bridged_with_translated_flags() {
+        let outstation = outstation();
+        let map = ConcentratorMap::new().map(
+            PointType::Analog,
+            7,
+            PointMapping::new(1, Some(EventClass::Class2))
+                .with_flags(FlagTranslation::new(Flags::REMOTE_FORCED, Flags::ONLINE)),
+        );
+        outstation.transaction(|db| map.add_points(db));
+
+        let mut handler = map.create_read_handler(outstation.clone());
+        handler.handle_analog(
+            HeaderInfo::new(Variation::Group30Var5, QualifierCode::Range8),
+            &mut vec![(analog(1.0), 6), (analog(2.0), 7)].into_iter(),
+        );
+
+        let value: Option<Analog> = outstation.transaction(|db| db.get(1));
+        assert_eq!(
+            value,
+            Some(Analog::new(
+                2.0,
+                Flags::REMOTE_FORCED,
+                Time::synchronized(0)
+            ))
+        );
+        let unmapped: Option<Analog> = outstation.transaction(|db| db.get(0));
+        assert_eq!(unmapped, None);
+    }
+
+    #[test]
+    fn flag_translation_clears_before_setting() {
+        let translation = FlagTranslation::new(Flags::ONLINE, Flags::new(0xFF));
+        assert_eq!(translation.apply(Flags::COMM_LOST), Flags::ONLINE);
+        assert_eq!(
+            FlagTranslation::NONE.apply(Flags::COMM_LOST),
+            Flags::COMM_LOST
+        );
+    }
+}
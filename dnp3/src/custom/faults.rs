@@ -0,0 +1,575 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use crate::app::Clock;
+use crate::custom::PhysicalLayer;
+use crate::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use crate::util::random::XorShift;
+
+/// Action applied by a [FaultyLayer] to a single write (one link frame) or a single read
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Fault {
+    /// pass the data through unmodified
+    Pass,
+    /// silently discard the data
+    Drop,
+    /// deliver the data twice
+    Duplicate,
+    /// flip bits of one byte of the data
+    Corrupt {
+        /// offset of the corrupted byte, modulo the length of the data
+        offset: usize,
+        /// bits of the byte that are flipped
+        mask: u8,
+    },
+    /// deliver at most this many bytes
+    Truncate(usize),
+    /// deliver the data after a delay
+    Delay(Duration),
+}
+
+/// Decides which fault, if any, a [FaultyLayer] applies to the data passing through it
+pub trait FaultPolicy: Send {
+    /// called for every write, i.e. every transmitted link frame
+    fn on_write(&mut self, data: &[u8]) -> Fault;
+    /// called for every chunk of bytes read from the wrapped layer
+    fn on_read(&mut self, data: &[u8]) -> Fault;
+}
+
+/// Applies a fixed sequence of faults, then passes everything through once it is exhausted
+///
+/// ```
+/// use dnp3::custom::{Fault, ScriptedFaults};
+///
+/// // drop the first transmitted frame, then corrupt the first received chunk
+/// let faults = ScriptedFaults::new()
+///     .write(Fault::Drop)
+///     .read(Fault::Corrupt { offset: 4, mask: 0x01 });
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedFaults {
+    writes: VecDeque<Fault>,
+    reads: VecDeque<Fault>,
+}
+
+impl ScriptedFaults {
+    /// Create a script that doesn't apply any fault
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a fault applied to the next write that isn't already scripted
+    pub fn write(mut self, fault: Fault) -> Self {
+        self.writes.push_back(fault);
+        self
+    }
+
+    /// Append a fault applied to the next read that isn't already scripted
+    pub fn read(mut self, fault: Fault) -> Self {
+        self.reads.push_back(fault);
+        self
+    }
+}
+
+impl FaultPolicy for ScriptedFaults {
+    fn on_write(&mut self, _: &[u8]) -> Fault {
+        self.writes.pop_front().unwrap_or(Fault::Pass)
+    }
+
+    fn on_read(&mut self, _: &[u8]) -> Fault {
+        self.reads.pop_front().unwrap_or(Fault::Pass)
+    }
+}
+
+/// Probability of each fault, in the range [0.0, 1.0]
+///
+/// The faults are considered in the order of the fields, and at most one of them is applied
+/// to the same data.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FaultProbabilities {
+    /// probability that the data is discarded
+    pub drop: f64,
+    /// probability that the data is delivered twice
+    pub duplicate: f64,
+    /// probability that a random byte of the data is corrupted
+    pub corrupt: f64,
+    /// probability that the data is truncated to a random length
+    pub truncate: f64,
+    /// probability that the data is delayed, and the delay
+    pub delay: Option<(f64, Duration)>,
+}
+
+/// Applies faults randomly using a seeded generator, so that the same seed always produces the
+/// same faults
+#[derive(Debug)]
+pub struct RandomFaults {
+    rng: XorShift,
+    write: FaultProbabilities,
+    read: FaultProbabilities,
+}
+
+impl RandomFaults {
+    /// Create a policy with the probabilities of the faults applied to the writes and reads
+    pub fn new(seed: u64, write: FaultProbabilities, read: FaultProbabilities) -> Self {
+        Self {
+            rng: XorShift::seeded(seed),
+            write,
+            read,
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        self.rng.next_f64()
+    }
+
+    fn select(&mut self, probabilities: FaultProbabilities, data: &[u8]) -> Fault {
+        if self.next_f64() < probabilities.drop {
+            return Fault::Drop;
+        }
+        if self.next_f64() < probabilities.duplicate {
+            return Fault::Duplicate;
+        }
+        if self.next_f64() < probabilities.corrupt {
+            let offset = self.next_u64() as usize;
+            // never a zero mask, otherwise nothing is corrupted
+            let mask = (self.next_u64() as u8) | 0x01;
+            return Fault::Corrupt { offset, mask };
+        }
+        if !data.is_empty() && self.next_f64() < probabilities.truncate {
+            return Fault::Truncate(self.next_u64() as usize % data.len());
+        }
+        if let Some((probability, delay)) = probabilities.delay {
+            if self.next_f64() < probability {
+                return Fault::Delay(delay);
+            }
+        }
+        Fault::Pass
+    }
+}
+
+impl FaultPolicy for RandomFaults {
+    fn on_write(&mut self, data: &[u8]) -> Fault {
+        let probabilities = self.write;
+        self.select(probabilities, data)
+    }
+
+    fn on_read(&mut self, data: &[u8]) -> Fault {
+        let probabilities = self.read;
+        self.select(probabilities, data)
+    }
+}
+
+/// [PhysicalLayer] that wraps another physical layer and drops, duplicates, corrupts,
+/// truncates, or delays the data read from and written to it according to a [FaultPolicy]
+///
+/// The sessions write every link frame separately, so the faults applied to the writes
+/// affect whole frames. Wrapping one end of an [impaired_pair](crate::custom::impaired_pair)
+/// or of `tokio::io::duplex` exercises the retry, confirm, and reassembly logic of a master
+/// and an outstation in integration tests.
+///
+/// ```
+/// use dnp3::app::Clock;
+/// use dnp3::custom::{Fault, FaultyLayer, ScriptedFaults};
+///
+/// let (master, outstation) = tokio::io::duplex(4096);
+/// // lose the first frame transmitted by the master
+/// let master = FaultyLayer::new(
+///     Box::new(master),
+///     Box::new(ScriptedFaults::new().write(Fault::Drop)),
+///     Clock::default(),
+/// );
+/// // ... spawn the master with `master` and the outstation with `outstation` ...
+/// # drop((master, outstation));
+/// ```
+///
+/// Only available with the `fault-injection` feature.
+pub struct FaultyLayer {
+    inner: Box<dyn PhysicalLayer>,
+    policy: Box<dyn FaultPolicy>,
+    clock: Clock,
+    // bytes that still need to be returned from reads, e.g. the copy of a duplicated read
+    pending: VecDeque<u8>,
+    // bytes received while a delay fault holds them back
+    delayed: Option<(Vec<u8>, Pin<Box<dyn Future<Output = ()> + Send>>)>,
+    // the write in progress, completed across calls when the wrapped layer isn't ready
+    outgoing: Option<Outgoing>,
+}
+
+struct Outgoing {
+    copies: VecDeque<Vec<u8>>,
+    written: usize,
+    delay: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+    length: usize,
+}
+
+impl FaultyLayer {
+    /// Wrap a physical layer, applying the policy's faults and measuring delays with the clock
+    pub fn new(inner: Box<dyn PhysicalLayer>, policy: Box<dyn FaultPolicy>, clock: Clock) -> Self {
+        Self {
+            inner,
+            policy,
+            clock,
+            pending: VecDeque::new(),
+            delayed: None,
+            outgoing: None,
+        }
+    }
+
+    fn sleep(&self, delay: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let clock = self.clock.clone();
+        Box::pin(clock.sleep_until(clock.now() + delay))
+    }
+
+    fn start_write(&mut self, data: &[u8]) -> Outgoing {
+        let mut copies = VecDeque::new();
+        let mut delay = None;
+        match self.policy.on_write(data) {
+            Fault::Pass => copies.push_back(data.to_vec()),
+            Fault::Drop => {
+                tracing::warn!(
+                    "fault injection - dropping {} transmitted bytes",
+                    data.len()
+                );
+            }
+            Fault::Duplicate => {
+                tracing::warn!(
+                    "fault injection - duplicating {} transmitted bytes",
+                    data.len()
+                );
+                copies.push_back(data.to_vec());
+                copies.push_back(data.to_vec());
+            }
+            Fault::Corrupt { offset, mask } => {
+                tracing::warn!("fault injection - corrupting transmitted bytes");
+                let mut copy = data.to_vec();
+                if let Some(x) = copy.get_mut(offset % data.len().max(1)) {
+                    *x ^= mask;
+                }
+                copies.push_back(copy);
+            }
+            Fault::Truncate(length) => {
+                let length = length.min(data.len());
+                tracing::warn!(
+                    "fault injection - truncating {} transmitted bytes to {}",
+                    data.len(),
+                    length
+                );
+                copies.push_back(data[..length].to_vec());
+            }
+            Fault::Delay(duration) => {
+                tracing::warn!(
+                    "fault injection - delaying transmitted bytes by {} ms",
+                    duration.as_millis()
+                );
+                copies.push_back(data.to_vec());
+                delay = Some(self.sleep(duration));
+            }
+        }
+
+        copies.retain(|x| !x.is_empty());
+        Outgoing {
+            copies,
+            written: 0,
+            delay,
+            length: data.len(),
+        }
+    }
+
+    fn drain_pending(&mut self, buf: &mut ReadBuf<'_>) {
+        let count = buf.remaining().min(self.pending.len());
+        let data: Vec<u8> = self.pending.drain(..count).collect();
+        buf.put_slice(&data);
+    }
+}
+
+impl AsyncRead for FaultyLayer {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if let Some((data, timer)) = &mut this.delayed {
+                if timer.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                this.pending.extend(data.iter());
+                this.delayed = None;
+            }
+
+            if !this.pending.is_empty() {
+                this.drain_pending(buf);
+                return Poll::Ready(Ok(()));
+            }
+
+            let start = buf.filled().len();
+            match Pin::new(&mut this.inner).poll_read(cx, buf) {
+                Poll::Ready(Ok(())) => {}
+                other => return other,
+            }
+            let count = buf.filled().len() - start;
+            if count == 0 {
+                // end of the stream
+                return Poll::Ready(Ok(()));
+            }
+
+            let data = &mut buf.filled_mut()[start..];
+            match this.policy.on_read(data) {
+                Fault::Pass => return Poll::Ready(Ok(())),
+                Fault::Drop => {
+                    tracing::warn!("fault injection - dropping {} received bytes", count);
+                    buf.set_filled(start);
+                }
+                Fault::Duplicate => {
+                    tracing::warn!("fault injection - duplicating {} received bytes", count);
+                    this.pending.extend(data.iter());
+                    return Poll::Ready(Ok(()));
+                }
+                Fault::Corrupt { offset, mask } => {
+                    tracing::warn!("fault injection - corrupting received bytes");
+                    data[offset % count] ^= mask;
+                    return Poll::Ready(Ok(()));
+                }
+                Fault::Truncate(length) => {
+                    let length = length.min(count);
+                    tracing::warn!(
+                        "fault injection - truncating {} received bytes to {}",
+                        count,
+                        length
+                    );
+                    buf.set_filled(start + length);
+                    if length > 0 {
+                        return Poll::Ready(Ok(()));
+                    }
+                }
+                Fault::Delay(duration) => {
+                    tracing::warn!(
+                        "fault injection - delaying received bytes by {} ms",
+                        duration.as_millis()
+                    );
+                    let data = data.to_vec();
+                    buf.set_filled(start);
+                    this.delayed = Some((data, this.sleep(duration)));
+                }
+            }
+        }
+    }
+}
+
+impl AsyncWrite for FaultyLayer {
+    // a write that isn't completed immediately must be retried with the same data
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        let mut outgoing = match this.outgoing.take() {
+            Some(x) => x,
+            None => this.start_write(buf),
+        };
+
+        if let Some(delay) = &mut outgoing.delay {
+            if delay.as_mut().poll(cx).is_pending() {
+                this.outgoing = Some(outgoing);
+                return Poll::Pending;
+            }
+            outgoing.delay = None;
+        }
+
+        while let Some(copy) = outgoing.copies.front() {
+            match Pin::new(&mut this.inner).poll_write(cx, &copy[outgoing.written..]) {
+                Poll::Ready(Ok(0)) => {
+                    return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()))
+                }
+                Poll::Ready(Ok(count)) => {
+                    outgoing.written += count;
+                    if outgoing.written == copy.len() {
+                        outgoing.copies.pop_front();
+                        outgoing.written = 0;
+                    }
+                }
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => {
+                    this.outgoing = Some(outgoing);
+                    return Poll::Pending;
+                }
+            }
+        }
+
+        Poll::Ready(Ok(outgoing.length))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+impl std::fmt::Debug for FaultyLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FaultyLayer")
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::decode::DecodeLevel;
+    use crate::link::error::LinkError;
+    use crate::link::reader::Reader;
+    use crate::link::statistics::LinkCounters;
+    use crate::link::test_data::*;
+    use crate::link::LinkErrorMode;
+    use crate::tokio::test::*;
+    use crate::util::phys::PhysLayer;
+
+    fn faulty(policy: ScriptedFaults) -> (PhysLayer, io::Handle) {
+        let (io, handle) = io::mock();
+        let layer = FaultyLayer::new(Box::new(io), Box::new(policy), Clock::default());
+        (PhysLayer::Custom(Box::new(layer)), handle)
+    }
+
+    fn read_frame(
+        reader: &mut Reader,
+        io: &mut PhysLayer,
+    ) -> Result<crate::link::header::Header, LinkError> {
+        let mut task = spawn(reader.read(io, DecodeLevel::nothing()));
+        assert_ready!(task.poll()).map(|(header, _)| header)
+    }
+
+    #[test]
+    fn write_faults_are_applied_in_order() {
+        let policy = ScriptedFaults::new()
+            .write(Fault::Drop)
+            .write(Fault::Duplicate)
+            .write(Fault::Truncate(3))
+            .write(Fault::Corrupt {
+                offset: 0,
+                mask: 0xFF,
+            });
+        let (mut io, mut handle) = faulty(policy);
+
+        handle.write(&[0x01, 0x02]);
+        handle.write(&[0x01, 0x02]);
+        handle.write(&[0x01, 0x02, 0x03]);
+        handle.write(&[0xFE, 0x02]);
+        handle.write(&[0x01, 0x02]);
+
+        for data in [
+            [0x01, 0x02].as_ref(),
+            &[0x01, 0x02],
+            &[0x01, 0x02, 0x03, 0x04],
+            &[0x01, 0x02],
+            &[0x01, 0x02],
+        ]
+        .iter()
+        {
+            let mut task = spawn(io.write(data));
+            assert_ready!(task.poll()).unwrap();
+        }
+        assert!(handle.all_written());
+    }
+
+    #[test]
+    fn delayed_write_waits_for_the_delay() {
+        let policy = ScriptedFaults::new().write(Fault::Delay(Duration::from_secs(1)));
+        let (mut io, mut handle) = faulty(policy);
+        handle.write(&[0x01]);
+
+        let mut task = spawn(io.write(&[0x01]));
+        assert_pending!(task.poll());
+        crate::tokio::time::advance(Duration::from_secs(1));
+        assert_ready!(task.poll()).unwrap();
+        drop(task);
+        assert!(handle.all_written());
+    }
+
+    #[test]
+    fn delayed_read_waits_for_the_delay() {
+        let policy = ScriptedFaults::new().read(Fault::Delay(Duration::from_secs(1)));
+        let (mut io, mut handle) = faulty(policy);
+        handle.read(&[0x01, 0x02]);
+
+        let mut buffer = [0; 8];
+        let mut task = spawn(io.read(&mut buffer));
+        assert_pending!(task.poll());
+        crate::tokio::time::advance(Duration::from_secs(1));
+        assert_eq!(assert_ready!(task.poll()).unwrap(), 2);
+        drop(task);
+        assert_eq!(&buffer[..2], &[0x01, 0x02]);
+    }
+
+    #[test]
+    fn reader_skips_dropped_and_duplicated_frames() {
+        let policy = ScriptedFaults::new()
+            .read(Fault::Drop)
+            .read(Fault::Duplicate);
+        let (mut io, mut handle) = faulty(policy);
+        let counters = Arc::new(LinkCounters::default());
+        let mut reader = Reader::new(LinkErrorMode::Close, None, counters.clone());
+
+        handle.read(ACK.bytes);
+        handle.read(RESET_LINK.bytes);
+
+        assert_eq!(read_frame(&mut reader, &mut io).unwrap(), RESET_LINK.header);
+        assert_eq!(read_frame(&mut reader, &mut io).unwrap(), RESET_LINK.header);
+        assert_eq!(counters.snapshot().frames_rx, 2);
+    }
+
+    #[test]
+    fn reader_discards_corrupted_frame() {
+        let policy = ScriptedFaults::new().read(Fault::Corrupt {
+            offset: 4,
+            mask: 0x01,
+        });
+        let (mut io, mut handle) = faulty(policy);
+        let counters = Arc::new(LinkCounters::default());
+        let mut reader = Reader::new(LinkErrorMode::Discard, None, counters.clone());
+
+        handle.read(RESET_LINK.bytes);
+        handle.read(ACK.bytes);
+
+        assert_eq!(read_frame(&mut reader, &mut io).unwrap(), ACK.header);
+        assert_eq!(counters.snapshot().frames_rx, 1);
+    }
+
+    #[test]
+    fn random_faults_are_reproducible() {
+        let probabilities = FaultProbabilities {
+            drop: 0.2,
+            duplicate: 0.2,
+            corrupt: 0.2,
+            truncate: 0.2,
+            delay: Some((0.2, Duration::from_millis(100))),
+        };
+        let mut first = RandomFaults::new(42, probabilities, probabilities);
+        let mut second = RandomFaults::new(42, probabilities, probabilities);
+        let data = [0u8; 16];
+
+        let mut faults = Vec::new();
+        for _ in 0..100 {
+            let fault = first.on_write(&data);
+            assert_eq!(fault, second.on_write(&data));
+            faults.push(fault);
+        }
+        assert!(faults.contains(&Fault::Drop));
+        assert!(faults.contains(&Fault::Duplicate));
+        assert!(faults.contains(&Fault::Pass));
+    }
+}
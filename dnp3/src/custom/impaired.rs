@@ -0,0 +1,425 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use crate::app::Clock;
+use crate::app::Instant;
+use crate::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use crate::util::random::XorShift;
+
+/// Impairments applied to the data sent in one direction of an [impaired_pair]
+///
+/// Each write is one packet, and the sessions write every link frame separately. The defaults
+/// deliver every packet immediately and in order.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Impairment {
+    /// fixed delay between the end of the transmission of a packet and its delivery
+    pub latency: Duration,
+    /// maximum random delay added to the latency of each packet
+    ///
+    /// Jitter changes the spacing between packets but never their order.
+    pub jitter: Duration,
+    /// probability, in the range [0.0, 1.0], that a packet is lost
+    pub loss: f64,
+    /// probability, in the range [0.0, 1.0], that a packet skips the latency and jitter and
+    /// overtakes the packets still in flight
+    pub reorder: f64,
+    /// maximum number of bytes carried per second
+    ///
+    /// Packets are queued behind each other while the channel is busy. A value of `None`
+    /// doesn't limit the rate.
+    pub bytes_per_second: Option<u32>,
+}
+
+/// Configuration of an [impaired_pair]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImpairedPairConfig {
+    /// impairments of the data written to the first end and read from the second end
+    pub forward: Impairment,
+    /// impairments of the data written to the second end and read from the first end
+    pub reverse: Impairment,
+    /// seed of the random decisions, the same seed always produces the same impairments
+    pub seed: u64,
+    /// clock that determines when packets are delivered, usually the clock of the sessions
+    pub clock: Clock,
+}
+
+/// Create an in-memory pair of physical layers whose data is delayed, lost, reordered, and
+/// rate limited according to the configuration
+///
+/// Each end implements [PhysicalLayer](crate::custom::PhysicalLayer), so a master and an
+/// outstation can be connected in the same process with
+/// [spawn_master_custom](crate::custom::spawn_master_custom) and
+/// [spawn_outstation_custom](crate::custom::spawn_outstation_custom) to test retry and
+/// unsolicited settings without hardware. Closing or dropping one end is seen as the end of
+/// the stream by the other end once the packets in flight have been delivered.
+pub fn impaired_pair(config: ImpairedPairConfig) -> (ImpairedChannel, ImpairedChannel) {
+    let forward = Arc::new(Mutex::new(Direction::new(
+        config.forward,
+        XorShift::seeded(config.seed),
+    )));
+    let reverse = Arc::new(Mutex::new(Direction::new(
+        config.reverse,
+        // a different sequence for each direction
+        XorShift::seeded(!config.seed),
+    )));
+
+    (
+        ImpairedChannel::new(forward.clone(), reverse.clone(), config.clock.clone()),
+        ImpairedChannel::new(reverse, forward, config.clock),
+    )
+}
+
+/// One end of an [impaired_pair]
+pub struct ImpairedChannel {
+    tx: Arc<Mutex<Direction>>,
+    rx: Arc<Mutex<Direction>>,
+    clock: Clock,
+    // expires when the earliest packet in flight towards this end is delivered
+    timer: Option<(Instant, Pin<Box<dyn Future<Output = ()> + Send>>)>,
+}
+
+struct Direction {
+    impairment: Impairment,
+    random: XorShift,
+    // ordered by delivery time, then by the order in which the packets were written
+    in_flight: BTreeMap<(Instant, u64), Vec<u8>>,
+    next_id: u64,
+    // delivery time of the last packet that wasn't reordered
+    last_delivery: Option<Instant>,
+    // time at which the channel finishes transmitting the packets already written
+    busy_until: Option<Instant>,
+    delivered: VecDeque<u8>,
+    reader: Option<Waker>,
+    writer_closed: bool,
+    reader_closed: bool,
+}
+
+impl ImpairedChannel {
+    fn new(tx: Arc<Mutex<Direction>>, rx: Arc<Mutex<Direction>>, clock: Clock) -> Self {
+        Self {
+            tx,
+            rx,
+            clock,
+            timer: None,
+        }
+    }
+}
+
+impl Direction {
+    fn new(impairment: Impairment, random: XorShift) -> Self {
+        Self {
+            impairment,
+            random,
+            in_flight: BTreeMap::new(),
+            next_id: 0,
+            last_delivery: None,
+            busy_until: None,
+            delivered: VecDeque::new(),
+            reader: None,
+            writer_closed: false,
+            reader_closed: false,
+        }
+    }
+
+    fn send(&mut self, now: Instant, data: &[u8]) {
+        // a lost packet still occupies the channel while it is transmitted
+        let start = match self.busy_until {
+            Some(x) if x > now => x,
+            _ => now,
+        };
+        let sent = start + self.transmission_time(data.len());
+        self.busy_until = Some(sent);
+
+        if chance(&mut self.random, self.impairment.loss) {
+            return;
+        }
+
+        let delivery = if chance(&mut self.random, self.impairment.reorder) {
+            sent
+        } else {
+            let delivery =
+                sent + self.impairment.latency + up_to(&mut self.random, self.impairment.jitter);
+            let delivery = match self.last_delivery {
+                Some(x) if x > delivery => x,
+                _ => delivery,
+            };
+            self.last_delivery = Some(delivery);
+            delivery
+        };
+
+        self.in_flight
+            .insert((delivery, self.next_id), data.to_vec());
+        self.next_id += 1;
+
+        if let Some(reader) = self.reader.take() {
+            reader.wake();
+        }
+    }
+
+    fn deliver(&mut self, now: Instant) {
+        while let Some(&key) = self.in_flight.keys().next() {
+            if key.0 > now {
+                break;
+            }
+            if let Some(data) = self.in_flight.remove(&key) {
+                self.delivered.extend(data);
+            }
+        }
+    }
+
+    fn next_delivery(&self) -> Option<Instant> {
+        self.in_flight.keys().next().map(|(time, _)| *time)
+    }
+
+    fn transmission_time(&self, length: usize) -> Duration {
+        match self.impairment.bytes_per_second {
+            Some(0) | None => Duration::from_secs(0),
+            Some(rate) => Duration::from_nanos(length as u64 * 1_000_000_000 / rate as u64),
+        }
+    }
+
+    fn close_writer(&mut self) {
+        self.writer_closed = true;
+        if let Some(reader) = self.reader.take() {
+            reader.wake();
+        }
+    }
+}
+
+fn chance(random: &mut XorShift, probability: f64) -> bool {
+    probability > 0.0 && random.next_f64() < probability
+}
+
+fn up_to(random: &mut XorShift, max: Duration) -> Duration {
+    let max = max.as_nanos() as u64;
+    if max == 0 {
+        return Duration::from_secs(0);
+    }
+    Duration::from_nanos(random.next_u64() % (max + 1))
+}
+
+impl AsyncRead for ImpairedChannel {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            let next = {
+                let mut rx = this.rx.lock().unwrap();
+                rx.deliver(this.clock.now());
+
+                if !rx.delivered.is_empty() {
+                    let count = buf.remaining().min(rx.delivered.len());
+                    let data: Vec<u8> = rx.delivered.drain(..count).collect();
+                    buf.put_slice(&data);
+                    return Poll::Ready(Ok(()));
+                }
+
+                match rx.next_delivery() {
+                    Some(next) => {
+                        rx.reader = Some(cx.waker().clone());
+                        next
+                    }
+                    None => {
+                        if rx.writer_closed {
+                            // end of the stream
+                            return Poll::Ready(Ok(()));
+                        }
+                        rx.reader = Some(cx.waker().clone());
+                        this.timer = None;
+                        return Poll::Pending;
+                    }
+                }
+            };
+
+            if !matches!(&this.timer, Some((deadline, _)) if *deadline == next) {
+                this.timer = Some((next, Box::pin(this.clock.sleep_until(next))));
+            }
+
+            if let Some((_, timer)) = &mut this.timer {
+                if timer.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+            }
+            this.timer = None;
+        }
+    }
+}
+
+impl AsyncWrite for ImpairedChannel {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let mut tx = self.tx.lock().unwrap();
+        if tx.writer_closed || tx.reader_closed {
+            return Poll::Ready(Err(std::io::ErrorKind::BrokenPipe.into()));
+        }
+        tx.send(self.clock.now(), buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.tx.lock().unwrap().close_writer();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for ImpairedChannel {
+    fn drop(&mut self) {
+        self.tx.lock().unwrap().close_writer();
+        self.rx.lock().unwrap().reader_closed = true;
+    }
+}
+
+impl std::fmt::Debug for ImpairedChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImpairedChannel")
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tokio::test::*;
+    use crate::tokio::time;
+    use crate::util::phys::PhysLayer;
+
+    fn pair(forward: Impairment) -> (PhysLayer, PhysLayer) {
+        let (a, b) = impaired_pair(ImpairedPairConfig {
+            forward,
+            ..Default::default()
+        });
+        (
+            PhysLayer::Custom(Box::new(a)),
+            PhysLayer::Custom(Box::new(b)),
+        )
+    }
+
+    fn write(phys: &mut PhysLayer, data: &[u8]) {
+        let mut write = spawn(phys.write(data));
+        assert_ready!(write.poll()).unwrap();
+    }
+
+    #[test]
+    fn delivers_after_latency() {
+        let (mut a, mut b) = pair(Impairment {
+            latency: Duration::from_millis(100),
+            ..Default::default()
+        });
+
+        write(&mut a, &[0x01, 0x02]);
+
+        let mut buffer = [0; 8];
+        let mut read = spawn(b.read(&mut buffer));
+        assert_pending!(read.poll());
+        time::advance(Duration::from_millis(99));
+        assert_pending!(read.poll());
+        time::advance(Duration::from_millis(1));
+        assert_eq!(assert_ready!(read.poll()).unwrap(), 2);
+    }
+
+    #[test]
+    fn queues_packets_behind_each_other_at_the_rate() {
+        let (mut a, mut b) = pair(Impairment {
+            bytes_per_second: Some(1000),
+            ..Default::default()
+        });
+
+        write(&mut a, &[0xAA; 100]);
+        write(&mut a, &[0xBB; 100]);
+
+        let mut buffer = [0; 256];
+        let mut read = spawn(b.read(&mut buffer));
+        assert_pending!(read.poll());
+        time::advance(Duration::from_millis(100));
+        assert_eq!(assert_ready!(read.poll()).unwrap(), 100);
+        drop(read);
+
+        let mut read = spawn(b.read(&mut buffer));
+        assert_pending!(read.poll());
+        time::advance(Duration::from_millis(100));
+        assert_eq!(assert_ready!(read.poll()).unwrap(), 100);
+        drop(read);
+        assert_eq!(buffer[0], 0xBB);
+    }
+
+    #[test]
+    fn lost_packets_are_never_delivered() {
+        let (mut a, mut b) = pair(Impairment {
+            loss: 1.0,
+            ..Default::default()
+        });
+
+        write(&mut a, &[0x01]);
+
+        let mut buffer = [0; 8];
+        let mut read = spawn(b.read(&mut buffer));
+        time::advance(Duration::from_secs(60));
+        assert_pending!(read.poll());
+    }
+
+    #[test]
+    fn reordered_packets_overtake_packets_in_flight() {
+        let (a, b) = impaired_pair(ImpairedPairConfig {
+            forward: Impairment {
+                latency: Duration::from_millis(100),
+                reorder: 0.5,
+                ..Default::default()
+            },
+            seed: 42,
+            ..Default::default()
+        });
+        let mut a = PhysLayer::Custom(Box::new(a));
+        let mut b = PhysLayer::Custom(Box::new(b));
+
+        for x in 0..32u8 {
+            write(&mut a, &[x]);
+        }
+
+        time::advance(Duration::from_millis(100));
+        let mut buffer = [0; 64];
+        let mut read = spawn(b.read(&mut buffer));
+        assert_eq!(assert_ready!(read.poll()).unwrap(), 32);
+        drop(read);
+
+        let mut sorted = buffer[0..32].to_vec();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..32u8).collect::<Vec<u8>>());
+        assert_ne!(&buffer[0..32], sorted.as_slice());
+    }
+
+    #[test]
+    fn closing_one_end_ends_the_stream_of_the_other() {
+        let (mut a, mut b) = pair(Impairment {
+            latency: Duration::from_millis(10),
+            ..Default::default()
+        });
+
+        write(&mut a, &[0x01]);
+        let mut close = spawn(a.close());
+        assert_ready!(close.poll());
+        drop(close);
+
+        let mut buffer = [0; 8];
+        time::advance(Duration::from_millis(10));
+        let mut read = spawn(b.read(&mut buffer));
+        assert_eq!(assert_ready!(read.poll()).unwrap(), 1);
+        drop(read);
+        let mut read = spawn(b.read(&mut buffer));
+        assert_eq!(assert_ready!(read.poll()).unwrap(), 0);
+    }
+}
@@ -0,0 +1,134 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use tracing::Instrument;
+
+use crate::app::{Shutdown, Spawner};
+use crate::custom::PhysicalLayer;
+use crate::link::statistics::LinkCounters;
+use crate::link::LinkErrorMode;
+use crate::master::session::{MasterSession, RunError, StateChange};
+use crate::master::{MasterChannel, MasterChannelConfig};
+use crate::transport::TransportReader;
+use crate::transport::TransportWriter;
+use crate::util::phys::PhysLayer;
+
+/// Spawn a master task that communicates over a user-provided physical layer onto the `Tokio`
+/// runtime. The task runs until the returned handle, and any `AssociationHandle` created from it,
+/// are dropped, or until an error occurs on the physical layer.
+///
+/// **Note**: This function may only be called from within the runtime itself, and panics otherwise.
+/// It is preferable to use this method instead of `create(..)` when using `[tokio::main]`.
+///
+/// The task is spawned by the `spawner` of the configuration instead, if any, which is required
+/// without the `tokio-runtime` feature.
+pub fn spawn_master_custom(
+    link_error_mode: LinkErrorMode,
+    config: MasterChannelConfig,
+    phys: Box<dyn PhysicalLayer>,
+) -> MasterChannel {
+    let spawner = config.spawner.clone();
+    let (future, handle) = create_master_custom(link_error_mode, config, phys);
+    Spawner::spawn(spawner.as_deref(), future);
+    handle
+}
+
+/// Create a master future that communicates over a user-provided physical layer, which can be
+/// spawned onto a runtime, along with a controlling handle.
+///
+/// Once spawned or otherwise executed using the `run` method, the task runs until the handle
+/// and any `AssociationHandle` created from it are dropped, or until an error occurs on the
+/// physical layer.
+///
+/// **Note**: This function is required instead of `spawn` when using a runtime to directly spawn
+/// tasks instead of within the context of a runtime, e.g. in applications that cannot use
+/// `[tokio::main]` such as C language bindings.
+pub fn create_master_custom(
+    link_error_mode: LinkErrorMode,
+    config: MasterChannelConfig,
+    phys: Box<dyn PhysicalLayer>,
+) -> (impl Future<Output = ()> + 'static, MasterChannel) {
+    let tracing_config = config.tracing;
+    let (mut task, handle) = MasterTask::new(link_error_mode, config, phys);
+    let future = async move {
+        let span = tracing_config.channel_span(tracing::info_span!("DNP3-Master-Custom"));
+        task.run().instrument(span).await;
+    };
+    (future, handle)
+}
+
+struct MasterTask {
+    io: PhysLayer,
+    session: MasterSession,
+    reader: TransportReader,
+    writer: TransportWriter,
+}
+
+impl MasterTask {
+    fn new(
+        link_error_mode: LinkErrorMode,
+        config: MasterChannelConfig,
+        phys: Box<dyn PhysicalLayer>,
+    ) -> (Self, MasterChannel) {
+        let (tx, rx) = crate::util::channel::request_channel();
+        let link_counters = Arc::new(LinkCounters::new(
+            config.clock,
+            config.tracing,
+            config.buffer_pool,
+        ));
+        let session = MasterSession::new(
+            false,
+            config.decode_level,
+            config.response_timeout,
+            config.scheduling,
+            config.link,
+            link_counters.clone(),
+            config.tracing,
+            config.tx_buffer_size,
+            config.rx_buffer_size,
+            rx,
+        );
+        let (reader, writer) = crate::transport::create_master_transport_layer(
+            link_error_mode,
+            config.master_address,
+            config.rx_buffer_size,
+            config.link,
+            config.response_timeout.into(),
+            config.transport,
+            config.unknown_destination,
+            link_counters.clone(),
+        );
+        let task = Self {
+            io: PhysLayer::Custom(phys),
+            session,
+            reader,
+            writer,
+        };
+        (task, MasterChannel::new(tx, link_counters))
+    }
+
+    async fn run(&mut self) {
+        let _ = self.run_impl().await;
+        self.io.close().await;
+        self.session.shutdown().await;
+    }
+
+    async fn run_impl(&mut self) -> Result<(), Shutdown> {
+        loop {
+            self.session.wait_for_enabled().await?;
+            match self
+                .session
+                .run(&mut self.io, &mut self.writer, &mut self.reader)
+                .await
+            {
+                // the physical layer is kept open while disabled
+                RunError::State(StateChange::Disable) => {}
+                RunError::State(_) => return Err(Shutdown),
+                RunError::Link(err) => {
+                    tracing::warn!("physical layer error: {}", err);
+                    return Err(Shutdown);
+                }
+            }
+        }
+    }
+}
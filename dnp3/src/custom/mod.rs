@@ -0,0 +1,57 @@
+use crate::tokio::io::{AsyncRead, AsyncWrite};
+
+#[cfg(any(test, feature = "fault-injection"))]
+pub use faults::*;
+pub use impaired::*;
+pub use master::*;
+pub use outstation::*;
+#[cfg(feature = "websocket")]
+pub use websocket::*;
+
+#[cfg(any(test, feature = "fault-injection"))]
+mod faults;
+mod impaired;
+mod master;
+mod outstation;
+#[cfg(feature = "websocket")]
+mod websocket;
+
+/// A user-provided physical layer, e.g. a WebSocket, a QUIC stream, or one end of an in-memory
+/// pair such as `tokio::io::duplex` in tests
+///
+/// Reading, writing, and closing are provided by Tokio's `AsyncRead` and `AsyncWrite` traits.
+/// The layer is shut down when the session that uses it ends. Any type implementing both traits
+/// implements this trait.
+pub trait PhysicalLayer: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T> PhysicalLayer for T where T: AsyncRead + AsyncWrite + Send + Unpin {}
+
+#[cfg(test)]
+mod test {
+    use crate::tokio::test::*;
+    use crate::util::phys::PhysLayer;
+
+    #[test]
+    fn in_memory_pair_carries_data_both_ways() {
+        let (a, b) = crate::tokio::io::duplex(64);
+        let mut a = PhysLayer::Custom(Box::new(a));
+        let mut b = PhysLayer::Custom(Box::new(b));
+
+        let mut write = spawn(a.write(&[0x05, 0x64]));
+        assert_ready!(write.poll()).unwrap();
+        drop(write);
+
+        let mut buffer = [0; 8];
+        let mut read = spawn(b.read(&mut buffer));
+        assert_eq!(assert_ready!(read.poll()).unwrap(), 2);
+        drop(read);
+        assert_eq!(&buffer[0..2], &[0x05, 0x64]);
+
+        // closing one end is seen as the end of the stream by the other
+        let mut close = spawn(b.close());
+        assert_ready!(close.poll());
+        drop(close);
+        let mut read = spawn(a.read(&mut buffer));
+        assert_eq!(assert_ready!(read.poll()).unwrap(), 0);
+    }
+}
@@ -0,0 +1,84 @@
+use std::future::Future;
+
+use tracing::Instrument;
+
+use crate::app::Spawner;
+use crate::custom::PhysicalLayer;
+use crate::link::LinkErrorMode;
+use crate::outstation::database::EventBufferConfig;
+use crate::outstation::task::OutstationTask;
+use crate::outstation::{
+    ControlHandler, OutstationApplication, OutstationConfig, OutstationHandle,
+    OutstationInformation,
+};
+use crate::util::phys::PhysLayer;
+
+/// Spawn an outstation task that communicates over a user-provided physical layer onto the `Tokio`
+/// runtime. The task runs until the returned handle is dropped or an error occurs on the physical layer.
+///
+/// **Note**: This function may only be called from within the runtime itself, and panics otherwise.
+/// It is preferable to use this method instead of `create_outstation_custom(..)` when using `[tokio::main]`.
+///
+/// The task is spawned by the `spawner` of the configuration instead, if any, which is required
+/// without the `tokio-runtime` feature.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_outstation_custom(
+    link_error_mode: LinkErrorMode,
+    phys: Box<dyn PhysicalLayer>,
+    config: OutstationConfig,
+    event_config: EventBufferConfig,
+    application: Box<dyn OutstationApplication>,
+    information: Box<dyn OutstationInformation>,
+    control_handler: Box<dyn ControlHandler>,
+) -> OutstationHandle {
+    let spawner = config.spawner.clone();
+    let (future, handle) = create_outstation_custom(
+        link_error_mode,
+        phys,
+        config,
+        event_config,
+        application,
+        information,
+        control_handler,
+    );
+    Spawner::spawn(spawner.as_deref(), future);
+    handle
+}
+
+/// Create an outstation future that communicates over a user-provided physical layer, which can
+/// be spawned onto a runtime, along with a controlling handle.
+///
+/// Once spawned or otherwise executed using the `run` method, the task runs until the handle
+/// is dropped or an error occurs on the physical layer.
+///
+/// **Note**: This function is required instead of `spawn` when using a runtime to directly spawn
+/// tasks instead of within the context of a runtime, e.g. in applications that cannot use
+/// `[tokio::main]` such as C language bindings.
+#[allow(clippy::too_many_arguments)]
+pub fn create_outstation_custom(
+    link_error_mode: LinkErrorMode,
+    phys: Box<dyn PhysicalLayer>,
+    config: OutstationConfig,
+    event_config: EventBufferConfig,
+    application: Box<dyn OutstationApplication>,
+    information: Box<dyn OutstationInformation>,
+    control_handler: Box<dyn ControlHandler>,
+) -> (impl Future<Output = ()> + 'static, OutstationHandle) {
+    let tracing_config = config.tracing;
+    let (mut task, handle) = OutstationTask::create(
+        link_error_mode,
+        config,
+        event_config,
+        application,
+        information,
+        control_handler,
+    );
+
+    let future = async move {
+        let span = tracing_config.channel_span(tracing::info_span!("DNP3-Outstation-Custom"));
+        let mut io = PhysLayer::Custom(phys);
+        let _ = task.run(&mut io).instrument(span).await;
+        io.close().await;
+    };
+    (future, handle)
+}
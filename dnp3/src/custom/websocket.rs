@@ -0,0 +1,281 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{BinaryType, MessageEvent, WebSocket};
+
+use crate::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// [PhysicalLayer](crate::custom::PhysicalLayer) over a browser WebSocket, e.g. to a gateway
+/// that forwards the binary messages to a TCP connection with a DNP3 device
+///
+/// Every write is sent as one binary message, and the sessions write every link frame
+/// separately. The messages received are read as a stream of bytes, so the gateway may
+/// split or combine frames.
+///
+/// ```no_run
+/// use dnp3::custom::WebSocketLayer;
+///
+/// # async fn connect() -> std::io::Result<()> {
+/// let layer = WebSocketLayer::connect("wss://gateway.example.com/dnp3").await?;
+/// // ... run a master with `spawn_master_custom(.., Box::new(layer), ..)` ...
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Only available with the `websocket` feature, on `wasm32-unknown-unknown`. The socket is
+/// driven by a task spawned with `wasm_bindgen_futures::spawn_local`, and is closed when the
+/// layer is shut down or dropped. Without a Tokio runtime in the browser, the master is spawned
+/// by a [Spawner](crate::app::Spawner) that calls `spawn_local`.
+pub struct WebSocketLayer {
+    shared: Arc<Mutex<Shared>>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Status {
+    Connecting,
+    Open,
+    Closed,
+}
+
+// state shared between the layer, which must be Send, and the JavaScript objects, which aren't
+struct Shared {
+    status: Status,
+    // set once the layer is shut down or dropped
+    closing: bool,
+    received: VecDeque<u8>,
+    outgoing: VecDeque<Vec<u8>>,
+    // wakes the reads and the connection of the layer
+    reader: Option<Waker>,
+    // wakes the task that owns the socket
+    pump: Option<Waker>,
+}
+
+impl Shared {
+    fn new() -> Self {
+        Self {
+            status: Status::Connecting,
+            closing: false,
+            received: VecDeque::new(),
+            outgoing: VecDeque::new(),
+            reader: None,
+            pump: None,
+        }
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.reader.take() {
+            waker.wake();
+        }
+        if let Some(waker) = self.pump.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl WebSocketLayer {
+    /// Open a WebSocket to the URL, completing once the connection is established
+    pub async fn connect(url: &str) -> std::io::Result<Self> {
+        let socket =
+            WebSocket::new(url).map_err(|err| error(std::io::ErrorKind::InvalidInput, err))?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        let shared = Arc::new(Mutex::new(Shared::new()));
+        let pump = Pump::new(socket, shared.clone());
+        wasm_bindgen_futures::spawn_local(pump);
+
+        // created before the connection completes so that dropping the future closes the socket
+        let layer = Self { shared };
+        Opened {
+            shared: layer.shared.clone(),
+        }
+        .await?;
+        Ok(layer)
+    }
+}
+
+impl Drop for WebSocketLayer {
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.closing = true;
+        shared.wake();
+    }
+}
+
+impl std::fmt::Debug for WebSocketLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketLayer")
+            .field("status", &self.shared.lock().unwrap().status)
+            .finish()
+    }
+}
+
+impl AsyncRead for WebSocketLayer {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let mut shared = self.shared.lock().unwrap();
+        if !shared.received.is_empty() {
+            let count = buf.remaining().min(shared.received.len());
+            let data: Vec<u8> = shared.received.drain(..count).collect();
+            buf.put_slice(&data);
+            return Poll::Ready(Ok(()));
+        }
+
+        if shared.status == Status::Closed || shared.closing {
+            // end of the stream
+            return Poll::Ready(Ok(()));
+        }
+
+        shared.reader = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl AsyncWrite for WebSocketLayer {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let mut shared = self.shared.lock().unwrap();
+        if shared.status != Status::Open || shared.closing {
+            return Poll::Ready(Err(std::io::ErrorKind::BrokenPipe.into()));
+        }
+        shared.outgoing.push_back(buf.to_vec());
+        if let Some(waker) = shared.pump.take() {
+            waker.wake();
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let mut shared = self.shared.lock().unwrap();
+        shared.closing = true;
+        shared.wake();
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn error(kind: std::io::ErrorKind, value: JsValue) -> std::io::Error {
+    std::io::Error::new(kind, format!("{:?}", value))
+}
+
+// completes once the socket is open, or fails if it closes first
+struct Opened {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Future for Opened {
+    type Output = std::io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap();
+        match shared.status {
+            Status::Connecting => {
+                shared.reader = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            Status::Open => Poll::Ready(Ok(())),
+            Status::Closed => Poll::Ready(Err(std::io::ErrorKind::ConnectionRefused.into())),
+        }
+    }
+}
+
+// owns the socket and its callbacks, which can't leave the browser's thread, and sends the
+// messages queued by the layer
+struct Pump {
+    socket: WebSocket,
+    shared: Arc<Mutex<Shared>>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_status: Vec<Closure<dyn FnMut(JsValue)>>,
+}
+
+impl Pump {
+    fn new(socket: WebSocket, shared: Arc<Mutex<Shared>>) -> Self {
+        let received = shared.clone();
+        let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+            if let Ok(data) = event.data().dyn_into::<js_sys::ArrayBuffer>() {
+                let mut shared = received.lock().unwrap();
+                shared
+                    .received
+                    .extend(js_sys::Uint8Array::new(&data).to_vec());
+                shared.wake();
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        let on_status = |status: Status| {
+            let shared = shared.clone();
+            Closure::wrap(Box::new(move |_: JsValue| {
+                let mut shared = shared.lock().unwrap();
+                // an error is always followed by a close, but the socket can't be used after it
+                if shared.status != Status::Closed {
+                    shared.status = status;
+                }
+                shared.wake();
+            }) as Box<dyn FnMut(JsValue)>)
+        };
+        let on_open = on_status(Status::Open);
+        let on_error = on_status(Status::Closed);
+        let on_close = on_status(Status::Closed);
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        Self {
+            socket,
+            shared,
+            _on_message: on_message,
+            _on_status: vec![on_open, on_error, on_close],
+        }
+    }
+
+    fn close(&self) {
+        self.socket.set_onmessage(None);
+        self.socket.set_onopen(None);
+        self.socket.set_onerror(None);
+        self.socket.set_onclose(None);
+        let _ = self.socket.close();
+    }
+}
+
+impl Future for Pump {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut shared = this.shared.lock().unwrap();
+        if shared.status == Status::Open {
+            while let Some(data) = shared.outgoing.pop_front() {
+                if let Err(err) = this.socket.send_with_u8_array(&data) {
+                    tracing::warn!("unable to send WebSocket message: {:?}", err);
+                    shared.status = Status::Closed;
+                    shared.wake();
+                    break;
+                }
+            }
+        }
+
+        if shared.status == Status::Closed || shared.closing {
+            shared.status = Status::Closed;
+            shared.wake();
+            drop(shared);
+            this.close();
+            return Poll::Ready(());
+        }
+
+        shared.pump = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
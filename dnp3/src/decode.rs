@@ -1,5 +1,15 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::app::parse::parser::{HeaderDetails, ParsedFragment, ParsedHeader};
+use crate::app::{Clock, ControlField, FunctionCode, Iin, QualifierCode};
+
 /// Controls the decoding of transmitted and received data at the application, transport, and link layer
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DecodeLevel {
     /// Controls application layer decoding
     pub application: AppDecodeLevel,
@@ -13,6 +23,7 @@ pub struct DecodeLevel {
 
 /// Controls how transmitted and received application-layer fragments are decoded at the INFO log level
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AppDecodeLevel {
     /// Decode nothing
     Nothing,
@@ -26,6 +37,7 @@ pub enum AppDecodeLevel {
 
 /// Controls how transmitted and received transport segments are decoded at the INFO log level
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransportDecodeLevel {
     /// Decode nothing
     Nothing,
@@ -37,6 +49,7 @@ pub enum TransportDecodeLevel {
 
 /// Controls how transmitted and received link frames are decoded at the INFO log level
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LinkDecodeLevel {
     /// Decode nothing
     Nothing,
@@ -48,6 +61,7 @@ pub enum LinkDecodeLevel {
 
 /// Controls how data transmitted at the physical layer (TCP, serial, etc) is logged
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PhysDecodeLevel {
     /// Log nothing
     Nothing,
@@ -57,6 +71,631 @@ pub enum PhysDecodeLevel {
     Data,
 }
 
+/// Destination for the decoded link, transport, and application layer output of a channel
+///
+/// By default, decoded output is logged at the INFO level using `tracing`. A sink registered
+/// on a channel receives that channel's output instead, one entry per frame, segment, or
+/// fragment, each terminated by a newline. Physical layer logging always uses `tracing`.
+///
+/// If writing to the sink fails, it is removed and output reverts to `tracing`.
+pub struct DecodeSink {
+    inner: Box<dyn LineWriter>,
+}
+
+trait LineWriter: Send {
+    fn write_line(&mut self, line: std::fmt::Arguments) -> std::io::Result<()>;
+}
+
+struct FmtWriter<W>(W);
+
+struct IoWriter<W>(W);
+
+impl<W> LineWriter for FmtWriter<W>
+where
+    W: std::fmt::Write + Send,
+{
+    fn write_line(&mut self, line: std::fmt::Arguments) -> std::io::Result<()> {
+        writeln!(self.0, "{}", line)
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::Other, "formatter error"))
+    }
+}
+
+impl<W> LineWriter for IoWriter<W>
+where
+    W: std::io::Write + Send,
+{
+    fn write_line(&mut self, line: std::fmt::Arguments) -> std::io::Result<()> {
+        writeln!(self.0, "{}", line)?;
+        self.0.flush()
+    }
+}
+
+impl DecodeSink {
+    /// construct a sink from a type that implements `std::fmt::Write`, e.g. a `String`
+    pub fn from_fmt<W>(writer: W) -> Self
+    where
+        W: std::fmt::Write + Send + 'static,
+    {
+        Self {
+            inner: Box::new(FmtWriter(writer)),
+        }
+    }
+
+    /// construct a sink from a type that implements `std::io::Write`, e.g. a `File` or `Stdout`
+    ///
+    /// the writer is flushed after each entry
+    pub fn from_io<W>(writer: W) -> Self
+    where
+        W: std::io::Write + Send + 'static,
+    {
+        Self {
+            inner: Box::new(IoWriter(writer)),
+        }
+    }
+}
+
+impl std::fmt::Debug for DecodeSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("DecodeSink")
+    }
+}
+
+/// Direction of decoded data relative to the channel
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DecodeDirection {
+    /// received from the remote device
+    Rx,
+    /// transmitted to the remote device
+    Tx,
+}
+
+/// Link-layer frame sent or received by a channel
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkFrameEvent {
+    /// direction of the frame
+    pub direction: DecodeDirection,
+    /// raw control byte containing the DIR, PRM, FCB, FCV/DFC bits and the function code
+    pub control: u8,
+    /// destination address
+    pub destination: u16,
+    /// source address
+    pub source: u16,
+    /// number of user data bytes in the frame, including the transport header
+    pub length: usize,
+}
+
+/// Transport-layer segment sent or received by a channel
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransportSegmentEvent {
+    /// direction of the segment
+    pub direction: DecodeDirection,
+    /// first segment of a fragment
+    pub fir: bool,
+    /// final segment of a fragment
+    pub fin: bool,
+    /// sequence number of the segment
+    pub seq: u8,
+    /// number of application-layer bytes in the segment
+    pub length: usize,
+}
+
+/// Application-layer fragment sent or received by a channel
+///
+/// Followed by an [ObjectHeaderEvent] for each of its object headers
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FragmentEvent {
+    /// direction of the fragment
+    pub direction: DecodeDirection,
+    /// application-layer control field
+    pub control: ControlField,
+    /// function code
+    pub function: FunctionCode,
+    /// internal indications if the fragment is a response
+    pub iin: Option<Iin>,
+    /// number of bytes following the application-layer header
+    pub length: usize,
+    /// description of the error if the object headers could not be parsed
+    pub object_error: Option<String>,
+}
+
+/// Object header of a fragment sent or received by a channel
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ObjectHeaderEvent {
+    /// direction of the fragment containing the header
+    pub direction: DecodeDirection,
+    /// group of the header
+    pub group: u8,
+    /// variation of the header
+    pub variation: u8,
+    /// qualifier code of the header
+    pub qualifier: QualifierCode,
+    /// start and stop indices for range qualifiers
+    pub range: Option<(u16, u16)>,
+    /// number of objects for count and free-format qualifiers
+    pub count: Option<u16>,
+}
+
+/// Structured description of data sent or received by a channel
+///
+/// Events are produced from the library's own parse results, independently of the
+/// [DecodeLevel], whenever a [DecodeEventHandler] is registered on the channel.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DecodeEvent {
+    /// link-layer frame
+    LinkFrame(LinkFrameEvent),
+    /// transport-layer segment
+    TransportSegment(TransportSegmentEvent),
+    /// application-layer fragment
+    Fragment(FragmentEvent),
+    /// object header of the preceding fragment
+    ObjectHeader(ObjectHeaderEvent),
+}
+
+/// Receives the [DecodeEvent]s of a channel
+///
+/// Called from the task running the channel, so implementations should not block
+pub trait DecodeEventHandler: Send {
+    /// called for every frame, segment, fragment, and object header in the order they are decoded
+    fn on_event(&mut self, event: DecodeEvent);
+}
+
+impl<F> DecodeEventHandler for F
+where
+    F: FnMut(DecodeEvent) + Send,
+{
+    fn on_event(&mut self, event: DecodeEvent) {
+        self(event)
+    }
+}
+
+/// Protocol layer at which a [WireEvent] is captured
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WireLayer {
+    /// bytes as they were read from or written to the physical layer, in arbitrary chunks
+    Physical,
+    /// complete link-layer frame, including its header and CRCs
+    Link,
+    /// transport segment, i.e. the transport header followed by its part of the fragment
+    Transport,
+    /// complete application-layer fragment
+    Application,
+}
+
+/// Bytes sent or received by a channel at one protocol layer
+///
+/// Comparing the events of adjacent layers shows exactly where data was corrupted or truncated,
+/// e.g. a segment that is received but missing from the reassembled fragment.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WireEvent<'a> {
+    /// layer at which the bytes were captured
+    pub layer: WireLayer,
+    /// direction of the bytes
+    pub direction: DecodeDirection,
+    /// time at which the bytes were captured
+    pub timestamp: SystemTime,
+    /// captured bytes, only valid for the duration of the callback
+    pub data: &'a [u8],
+}
+
+/// Receives the [WireEvent]s of a channel
+///
+/// Called from the task running the channel, so implementations should not block. Received
+/// bytes are reported from the physical layer up and transmitted bytes from the application layer
+/// down. Received link frames are re-encoded because the parser doesn't retain their header bytes.
+///
+/// The [RedactionFilter] of the channel doesn't apply to these bytes.
+pub trait WireTap: Send {
+    /// called for every chunk of bytes, frame, segment, and fragment in the order they are processed
+    fn on_data(&mut self, event: WireEvent<'_>);
+}
+
+impl<F> WireTap for F
+where
+    F: FnMut(WireEvent<'_>) + Send,
+{
+    fn on_data(&mut self, event: WireEvent<'_>) {
+        self(event)
+    }
+}
+
+/// Fragment recently sent or received by a channel
+///
+/// Channels keep a bounded history of these fragments once it is enabled on their handle, so
+/// that an application can include the recent protocol context in a bug report when an error
+/// occurs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecentFragment {
+    /// time at which the fragment was sent or received
+    pub timestamp: SystemTime,
+    /// direction of the fragment
+    pub direction: DecodeDirection,
+    /// size of the fragment in bytes
+    pub length: usize,
+    /// header and object headers of the fragment, as logged with [AppDecodeLevel::ObjectHeaders]
+    pub summary: String,
+}
+
+#[derive(Default)]
+struct RecentFragments {
+    capacity: usize,
+    fragments: VecDeque<RecentFragment>,
+}
+
+/// Kind of payload bytes written to the decoded output of a channel
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PayloadKind {
+    /// data read from or written to the physical layer
+    Physical,
+    /// user data of a link-layer frame
+    Link,
+    /// payload of a transport-layer segment
+    Transport,
+    /// raw value of an object, e.g. an octet string
+    Object,
+}
+
+/// Hides payload bytes from the decoded output of a channel
+///
+/// The filter is applied before the output reaches `tracing` or a [DecodeSink]. Redacted bytes
+/// are replaced by their length. It does not apply to [DecodeEvent]s, which never contain
+/// payload bytes.
+pub trait RedactionFilter: Send + Sync {
+    /// return true if the bytes must not be written to the output
+    fn redact(&self, kind: PayloadKind, data: &[u8]) -> bool;
+}
+
+impl<F> RedactionFilter for F
+where
+    F: Fn(PayloadKind, &[u8]) -> bool + Send + Sync,
+{
+    fn redact(&self, kind: PayloadKind, data: &[u8]) -> bool {
+        self(kind, data)
+    }
+}
+
+/// Creates the `tracing` spans of a channel in place of the spans of the library
+///
+/// Each method receives the span the library would have used, e.g. `DNP3-Master-TCP` with the
+/// endpoint for the channel, so that implementations may return it unchanged, use it as the parent
+/// of their own span, or replace it with a span having a different name and fields.
+pub trait SpanFactory: Send + Sync {
+    /// span in which the channel runs
+    fn channel(&self, default: tracing::Span) -> tracing::Span;
+
+    /// span of a master task or of an outstation waiting for a confirmation
+    ///
+    /// `correlation_id` is unique within the process, unless supplied by the user with
+    /// [`AssociationHandle::with_correlation_id`](crate::master::AssociationHandle::with_correlation_id),
+    /// and is also recorded in the default span
+    fn task(&self, default: tracing::Span, correlation_id: u64) -> tracing::Span;
+}
+
+/// Customizes the `tracing` output of a channel
+///
+/// The factory and the filter are shared with the channel for as long as it runs, so they
+/// must have a `'static` lifetime, e.g. by being leaked with `Box::leak` once per application.
+#[derive(Copy, Clone, Default)]
+pub struct TracingConfig {
+    /// creates the spans of the channel, the spans of the library are used if `None`
+    pub spans: Option<&'static dyn SpanFactory>,
+    /// hides payload bytes from the decoded output, nothing is hidden if `None`
+    pub redaction: Option<&'static dyn RedactionFilter>,
+}
+
+impl TracingConfig {
+    pub(crate) fn channel_span(&self, default: tracing::Span) -> tracing::Span {
+        match self.spans {
+            Some(spans) => spans.channel(default),
+            None => default,
+        }
+    }
+
+    pub(crate) fn task_span(&self, default: tracing::Span, correlation_id: u64) -> tracing::Span {
+        match self.spans {
+            Some(spans) => spans.task(default, correlation_id),
+            None => default,
+        }
+    }
+}
+
+impl std::fmt::Debug for TracingConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("TracingConfig")
+            .field("spans", &self.spans.is_some())
+            .field("redaction", &self.redaction.is_some())
+            .finish()
+    }
+}
+
+impl PartialEq for TracingConfig {
+    fn eq(&self, other: &Self) -> bool {
+        // compared by identity, as for the clock
+        fn address<T: ?Sized>(x: Option<&T>) -> Option<*const u8> {
+            x.map(|x| x as *const T as *const u8)
+        }
+
+        address(self.spans) == address(other.spans)
+            && address(self.redaction) == address(other.redaction)
+    }
+}
+
+/// allocate the correlation ID of a task, unique within the process
+pub(crate) fn next_correlation_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+thread_local! {
+    // filter of the channel whose output is being formatted on this thread
+    static REDACTION: Cell<Option<&'static dyn RedactionFilter>> = Cell::new(None);
+}
+
+/// true if payload bytes formatted on this thread may be redacted
+pub(crate) fn redaction_enabled() -> bool {
+    REDACTION.with(|x| x.get().is_some())
+}
+
+/// true if the payload bytes formatted on this thread must be hidden
+pub(crate) fn is_redacted(kind: PayloadKind, data: &[u8]) -> bool {
+    REDACTION.with(|x| x.get().map_or(false, |filter| filter.redact(kind, data)))
+}
+
+/// decode output shared between the layers of a channel and its handle
+#[derive(Default)]
+pub(crate) struct DecodeOutput {
+    redaction: Option<&'static dyn RedactionFilter>,
+    sink: Mutex<Option<DecodeSink>>,
+    // avoids taking the lock for every frame when no handler is registered
+    has_handler: AtomicBool,
+    handler: Mutex<Option<Box<dyn DecodeEventHandler>>>,
+    has_recent: AtomicBool,
+    recent: Mutex<RecentFragments>,
+    has_tap: AtomicBool,
+    tap: Mutex<Option<Box<dyn WireTap>>>,
+    // time source of the timestamps
+    clock: Clock,
+}
+
+impl DecodeOutput {
+    pub(crate) fn new(redaction: Option<&'static dyn RedactionFilter>, clock: Clock) -> Self {
+        Self {
+            redaction,
+            clock,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn set_sink(&self, sink: Option<DecodeSink>) {
+        *self.sink.lock().unwrap() = sink;
+    }
+
+    pub(crate) fn set_event_handler(&self, handler: Option<Box<dyn DecodeEventHandler>>) {
+        let mut guard = self.handler.lock().unwrap();
+        self.has_handler.store(handler.is_some(), Ordering::Relaxed);
+        *guard = handler;
+    }
+
+    pub(crate) fn events_enabled(&self) -> bool {
+        self.has_handler.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_wire_tap(&self, tap: Option<Box<dyn WireTap>>) {
+        let mut guard = self.tap.lock().unwrap();
+        self.has_tap.store(tap.is_some(), Ordering::Relaxed);
+        *guard = tap;
+    }
+
+    /// true if the bytes of each layer must be made available even when they aren't contiguous
+    pub(crate) fn tap_enabled(&self) -> bool {
+        self.has_tap.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn emit_wire(&self, layer: WireLayer, direction: DecodeDirection, data: &[u8]) {
+        if !self.tap_enabled() {
+            return;
+        }
+
+        if let Some(tap) = self.tap.lock().unwrap().as_mut() {
+            tap.on_data(WireEvent {
+                layer,
+                direction,
+                timestamp: self.clock.system_time(),
+                data,
+            });
+        }
+    }
+
+    pub(crate) fn emit_event(&self, event: DecodeEvent) {
+        if !self.events_enabled() {
+            return;
+        }
+
+        if let Some(handler) = self.handler.lock().unwrap().as_mut() {
+            handler.on_event(event);
+        }
+    }
+
+    pub(crate) fn set_recent_capacity(&self, capacity: usize) {
+        let mut recent = self.recent.lock().unwrap();
+        recent.capacity = capacity;
+        while recent.fragments.len() > capacity {
+            recent.fragments.pop_front();
+        }
+        self.has_recent.store(capacity > 0, Ordering::Relaxed);
+    }
+
+    pub(crate) fn recent_fragments(&self) -> Vec<RecentFragment> {
+        self.recent
+            .lock()
+            .unwrap()
+            .fragments
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// true if sent and received fragments must be parsed even when they aren't logged
+    pub(crate) fn fragments_enabled(&self) -> bool {
+        self.events_enabled() || self.has_recent.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn emit_fragment(&self, direction: DecodeDirection, fragment: &ParsedFragment) {
+        self.record_fragment(direction, fragment);
+
+        if !self.events_enabled() {
+            return;
+        }
+
+        let mut guard = self.handler.lock().unwrap();
+        let handler = match guard.as_mut() {
+            Some(handler) => handler,
+            None => return,
+        };
+
+        handler.on_event(DecodeEvent::Fragment(FragmentEvent {
+            direction,
+            control: fragment.control,
+            function: fragment.function,
+            iin: fragment.iin,
+            length: fragment.raw_objects.len(),
+            object_error: fragment.objects.err().map(|err| err.to_string()),
+        }));
+
+        if let Ok(objects) = fragment.objects {
+            for header in objects.iter_all() {
+                handler.on_event(DecodeEvent::ObjectHeader(ObjectHeaderEvent::new(
+                    direction, &header,
+                )));
+            }
+        }
+    }
+
+    fn record_fragment(&self, direction: DecodeDirection, fragment: &ParsedFragment) {
+        if !self.has_recent.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let summary =
+            self.redacting(|| fragment.display(AppDecodeLevel::ObjectHeaders).to_string());
+        let mut recent = self.recent.lock().unwrap();
+        if recent.capacity == 0 {
+            return;
+        }
+        if recent.fragments.len() == recent.capacity {
+            recent.fragments.pop_front();
+        }
+        recent.fragments.push_back(RecentFragment {
+            timestamp: self.clock.system_time(),
+            direction,
+            length: fragment.raw_fragment.len(),
+            summary,
+        });
+    }
+
+    pub(crate) fn emit(&self, line: std::fmt::Arguments) {
+        let mut guard = self.sink.lock().unwrap();
+        let result = match guard.as_mut() {
+            Some(sink) => self.redacting(|| sink.inner.write_line(line)),
+            None => {
+                self.redacting(|| tracing::info!("{}", line));
+                Ok(())
+            }
+        };
+        if let Err(err) = result {
+            tracing::warn!("removing decode sink after write error: {}", err);
+            *guard = None;
+        }
+    }
+
+    /// log data read from or written to the physical layer, always using `tracing`
+    pub(crate) fn emit_phys(
+        &self,
+        direction: DecodeDirection,
+        level: PhysDecodeLevel,
+        data: &[u8],
+    ) {
+        self.emit_wire(WireLayer::Physical, direction, data);
+
+        if !level.enabled() {
+            return;
+        }
+
+        let data = crate::util::phys::PhysDisplay::new(level, data);
+        self.redacting(|| match direction {
+            DecodeDirection::Rx => tracing::info!("PHYS RX - {}", data),
+            DecodeDirection::Tx => tracing::info!("PHYS TX - {}", data),
+        });
+    }
+
+    // payload bytes are formatted lazily by the Display implementations, so the filter of the
+    // channel is made available to them for the duration of the formatting
+    fn redacting<R>(&self, format: impl FnOnce() -> R) -> R {
+        let previous = REDACTION.with(|x| x.replace(self.redaction));
+        let result = format();
+        REDACTION.with(|x| x.set(previous));
+        result
+    }
+}
+
+impl std::fmt::Debug for DecodeOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("DecodeOutput")
+            .field("sink", &self.sink)
+            .field("has_handler", &self.has_handler)
+            .field("has_recent", &self.has_recent)
+            .field("has_tap", &self.has_tap)
+            .field("redaction", &self.redaction.is_some())
+            .finish()
+    }
+}
+
+impl ObjectHeaderEvent {
+    fn new(direction: DecodeDirection, header: &ParsedHeader) -> Self {
+        let (group, variation, qualifier, range, count) = match header {
+            ParsedHeader::Known(header) => {
+                let (group, variation) = header.variation.to_group_and_var();
+                let (range, count) = match &header.details {
+                    HeaderDetails::AllObjects(_) => (None, None),
+                    HeaderDetails::OneByteStartStop(start, stop, _) => {
+                        (Some((*start as u16, *stop as u16)), None)
+                    }
+                    HeaderDetails::TwoByteStartStop(start, stop, _) => {
+                        (Some((*start, *stop)), None)
+                    }
+                    HeaderDetails::OneByteCount(count, _) => (None, Some(*count as u16)),
+                    HeaderDetails::TwoByteCount(count, _) => (None, Some(*count)),
+                    HeaderDetails::OneByteCountAndPrefix(count, _) => (None, Some(*count as u16)),
+                    HeaderDetails::TwoByteCountAndPrefix(count, _) => (None, Some(*count)),
+                    HeaderDetails::TwoByteFreeFormat(count, _) => (None, Some(*count as u16)),
+                };
+                (group, variation, header.details.qualifier(), range, count)
+            }
+            ParsedHeader::Unknown(header) => {
+                (header.group, header.variation, header.qualifier, None, None)
+            }
+            ParsedHeader::Vendor(header) => {
+                (header.group, header.variation, header.qualifier, None, None)
+            }
+        };
+
+        Self {
+            direction,
+            group,
+            variation,
+            qualifier,
+            range,
+            count,
+        }
+    }
+}
+
 impl DecodeLevel {
     /// construct a `DecodeLevel` with nothing enabled
     pub fn nothing() -> Self {
@@ -199,3 +838,227 @@ impl PhysDecodeLevel {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct SharedString(Arc<Mutex<String>>);
+
+    impl std::fmt::Write for SharedString {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            self.0.lock().unwrap().push_str(s);
+            Ok(())
+        }
+    }
+
+    struct FailingWriter;
+
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn wire_tap_receives_tagged_events_until_removed() {
+        let output = DecodeOutput::default();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let captured = events.clone();
+        assert!(!output.tap_enabled());
+        output.set_wire_tap(Some(Box::new(move |event: WireEvent| {
+            captured
+                .lock()
+                .unwrap()
+                .push((event.layer, event.direction, event.data.to_vec()));
+        })));
+        assert!(output.tap_enabled());
+
+        output.emit_wire(WireLayer::Application, DecodeDirection::Tx, &[0xC0, 0x01]);
+        output.emit_phys(DecodeDirection::Rx, PhysDecodeLevel::Nothing, &[0x05, 0x64]);
+        output.set_wire_tap(None);
+        output.emit_wire(WireLayer::Link, DecodeDirection::Rx, &[0x05]);
+
+        assert_eq!(
+            events.lock().unwrap().as_slice(),
+            &[
+                (
+                    WireLayer::Application,
+                    DecodeDirection::Tx,
+                    vec![0xC0, 0x01]
+                ),
+                (WireLayer::Physical, DecodeDirection::Rx, vec![0x05, 0x64]),
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_lines_to_registered_sink() {
+        let output = DecodeOutput::default();
+        let text = SharedString::default();
+        output.set_sink(Some(DecodeSink::from_fmt(text.clone())));
+        output.emit(format_args!("APP TX - {}", 42));
+        output.emit(format_args!("LINK RX - {}", "frame"));
+        assert_eq!(
+            text.0.lock().unwrap().as_str(),
+            "APP TX - 42\nLINK RX - frame\n"
+        );
+
+        output.set_sink(None);
+        output.emit(format_args!("APP RX"));
+        assert_eq!(text.0.lock().unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn emits_fragment_and_object_header_events() {
+        let output = DecodeOutput::default();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        output.set_event_handler(Some(Box::new(move |event: DecodeEvent| {
+            sink.lock().unwrap().push(event)
+        })));
+
+        // read g60v2 and g60v3 with all objects, then g1v2 for indices 1 to 2
+        let request = [
+            0xC0, 0x01, 0x3C, 0x02, 0x06, 0x3C, 0x03, 0x06, 0x01, 0x02, 0x00, 0x01, 0x02,
+        ];
+        let fragment = ParsedFragment::parse(&request).unwrap();
+        output.emit_fragment(DecodeDirection::Tx, &fragment);
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 4);
+        match &events[0] {
+            DecodeEvent::Fragment(x) => {
+                assert_eq!(x.direction, DecodeDirection::Tx);
+                assert_eq!(x.function, FunctionCode::Read);
+                assert_eq!(x.iin, None);
+                assert_eq!(x.length, 11);
+                assert_eq!(x.object_error, None);
+            }
+            x => panic!("unexpected event: {:?}", x),
+        }
+        assert_eq!(
+            events[3],
+            DecodeEvent::ObjectHeader(ObjectHeaderEvent {
+                direction: DecodeDirection::Tx,
+                group: 1,
+                variation: 2,
+                qualifier: QualifierCode::Range8,
+                range: Some((1, 2)),
+                count: None,
+            })
+        );
+    }
+
+    #[test]
+    fn events_are_not_emitted_without_handler() {
+        let output = DecodeOutput::default();
+        assert!(!output.events_enabled());
+        output.set_event_handler(Some(Box::new(|_: DecodeEvent| {})));
+        assert!(output.events_enabled());
+        output.set_event_handler(None);
+        assert!(!output.events_enabled());
+    }
+
+    struct Payload<'a>(PayloadKind, &'a [u8]);
+
+    impl std::fmt::Display for Payload<'_> {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            crate::util::decode::format_bytes(f, self.0, self.1)
+        }
+    }
+
+    #[test]
+    fn redacts_payload_bytes_selected_by_filter() {
+        let filter: &'static dyn RedactionFilter =
+            Box::leak(Box::new(|kind: PayloadKind, data: &[u8]| {
+                kind == PayloadKind::Object || data.len() > 2
+            }));
+        let output = DecodeOutput::new(Some(filter), Clock::default());
+        let text = SharedString::default();
+        output.set_sink(Some(DecodeSink::from_fmt(text.clone())));
+
+        let octets = crate::app::Bytes::new(&[0x01, 0x02, 0x03, 0x04]);
+        output.emit(format_args!("{}", octets));
+        output.emit(format_args!(
+            "{}",
+            Payload(PayloadKind::Link, &[0xAA, 0xBB])
+        ));
+        output.emit(format_args!(
+            "{}",
+            Payload(PayloadKind::Link, &[0xAA, 0xBB, 0xCC])
+        ));
+        assert_eq!(
+            text.0.lock().unwrap().as_str(),
+            "length = 4, <redacted>\n\nAA BB\n\n<3 bytes redacted>\n"
+        );
+
+        // the filter only applies to the output of the channel
+        assert_eq!(format!("{}", octets), "length = 4, [01, 02, 03] ...");
+    }
+
+    #[test]
+    fn tracing_configs_compare_by_identity() {
+        let filter: &'static dyn RedactionFilter =
+            Box::leak(Box::new(|_: PayloadKind, _: &[u8]| true));
+        let config = TracingConfig {
+            spans: None,
+            redaction: Some(filter),
+        };
+        assert_eq!(config, config);
+        assert_ne!(config, TracingConfig::default());
+    }
+
+    #[test]
+    fn correlation_ids_are_unique() {
+        let first = next_correlation_id();
+        let second = next_correlation_id();
+        assert!(second > first);
+    }
+
+    #[test]
+    fn keeps_the_most_recent_fragments() {
+        let output = DecodeOutput::default();
+        // read class 1 events
+        let request = [0xC0, 0x01, 0x3C, 0x02, 0x06];
+        let fragment = ParsedFragment::parse(&request).unwrap();
+
+        output.emit_fragment(DecodeDirection::Tx, &fragment);
+        assert!(!output.fragments_enabled());
+        assert!(output.recent_fragments().is_empty());
+
+        output.set_recent_capacity(2);
+        assert!(output.fragments_enabled());
+        output.emit_fragment(DecodeDirection::Tx, &fragment);
+        output.emit_fragment(DecodeDirection::Rx, &fragment);
+        output.emit_fragment(DecodeDirection::Tx, &fragment);
+
+        let recent = output.recent_fragments();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].direction, DecodeDirection::Rx);
+        assert_eq!(recent[1].direction, DecodeDirection::Tx);
+        assert_eq!(recent[1].length, 5);
+        assert!(recent[1].summary.contains("func: Read"));
+        assert!(recent[1].summary.contains("g60v2"));
+        assert!(recent[0].timestamp <= recent[1].timestamp);
+
+        output.set_recent_capacity(0);
+        assert!(!output.fragments_enabled());
+        assert!(output.recent_fragments().is_empty());
+    }
+
+    #[test]
+    fn removes_sink_after_write_error() {
+        let output = DecodeOutput::default();
+        output.set_sink(Some(DecodeSink::from_io(FailingWriter)));
+        output.emit(format_args!("APP TX"));
+        assert!(output.sink.lock().unwrap().is_none());
+    }
+}
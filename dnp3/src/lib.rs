@@ -5,7 +5,61 @@
 //! * Panic-free, zero-copy, zero-allocation parsing
 //! * Fully automated level 2 outstation conformance tests
 //! * Automatic TCP connection management with configurable reconnect strategy
-//! * Scalable performance using Tokio's multi-threaded executor
+//! * TCP, UDP, and serial physical layers behind the `tcp`, `udp`, and `serial` features (enabled
+//!   by default). Without them, the protocol builds for `wasm32-unknown-unknown` and runs over a
+//!   user-provided physical layer, e.g. the browser WebSocket of the optional `websocket` feature,
+//!   with the time of the browser given by a custom clock
+//! * Optional `serde` feature that derives `Serialize` and `Deserialize` for measurements, IIN,
+//!   application-layer headers, and the master and outstation configurations, so that they can
+//!   be loaded from TOML, YAML, or JSON files
+//! * Configuration builders that reject inconsistent outstation and association settings, e.g.
+//!   colliding addresses, with a descriptive `ConfigError`
+//! * Conversions between `Timestamp` and `SystemTime` or `chrono` types, plus `time` types behind
+//!   the optional `time` feature
+//! * Optional `pcap` feature that records link-layer frames to a pcapng file for analysis in
+//!   Wireshark, started and stopped at runtime from the channel handle
+//! * Optional `replay` feature that feeds captured traffic through the parsers and through
+//!   simulated sessions to reproduce field issues deterministically in tests
+//! * Injectable clock for timeouts, retries, polls, and keep-alives, so that simulations run
+//!   faster than real time and tests are deterministic
+//! * In-memory channel pair with configurable latency, jitter, loss, reordering, and bandwidth
+//!   for testing retry and unsolicited settings without hardware
+//! * Optional `fault-injection` feature that wraps a custom physical layer to drop, duplicate,
+//!   corrupt, truncate, or delay frames according to a script or seeded probabilities
+//! * Custom `tracing` spans, per-task correlation IDs that callers can supply with their requests,
+//!   and redaction of payload bytes in the decoded output
+//! * Optional history of the most recent fragments of each channel, retrievable from its handle
+//!   for bug reports
+//! * Per-channel counts of the deviations from the specification observed from the peer
+//! * Wire taps that report the bytes of each layer (physical, link, transport, and application)
+//!   with their direction and time
+//! * Optional buffer pool shared between sessions, so that idle outstations and channels don't
+//!   pin their transmit and receive buffers, and a compact outstation configuration with a
+//!   documented per-session memory cost for servers hosting tens of thousands of outstations
+//! * Optional `sim` feature that drives an outstation from a declarative scenario of point
+//!   waveforms, event bursts, delayed responses, and forced IIN bits
+//! * Per-type choice of ordered or flat storage of the outstation database, so that reading
+//!   large contiguous point maps is a linear scan
+//! * Scalable performance using Tokio's multi-threaded executor, with optional placement of each
+//!   channel and outstation onto a dedicated runtime
+//! * Optional `concentrator` feature that maps the points read by a master association onto the
+//!   database of an outstation with index, class, and flag translation rules
+//! * Notifications of outstations and controls, and channel states, delivered as typed events on
+//!   bounded channels for applications that prefer async `select!` loops over callback traits
+//! * Graceful shutdown of master channels and outstations that completes the work in progress,
+//!   e.g. queued commands or a pending response, before the task exits
+//! * Sessions over a custom physical layer can run on other executors, e.g. `async-std` or
+//!   `smol`, given a spawner and a timer from that executor, and build without the Tokio runtime
+//!   when the default `tokio-runtime` feature is disabled
+//! * Class 0 responses restricted by point type and by individual point, e.g. to omit event-only
+//!   points from integrity polls, and master integrity polls restricted to selected static headers
+//! * Dial-up master TCP clients that connect only when a request, automatic task, or poll is due
+//!   and disconnect once idle, for metered cellular links, and outstation TCP clients that connect
+//!   to report events or on a heartbeat, as battery-powered RTUs do
+//! * Optional reporting of the last known values of the points with `COMM_LOST` when a master
+//!   loses communication with an outstation, cleared once it answers again
+//! * Operator commands run ahead of queued READ requests and polls, and can optionally interrupt a
+//!   multi-fragment READ response in progress
 //!
 //! # License
 //!
@@ -71,6 +125,11 @@ pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// application layer types shared by both the master and outstation APIs
 pub mod app;
+/// Bridging of the values read by a master association into the database of an outstation
+#[cfg(any(test, feature = "concentrator"))]
+pub mod concentrator;
+/// Entry points and types for user-provided physical layers
+pub mod custom;
 /// types used to control decoding in the log
 pub mod decode;
 /// Types specific to the link-layer
@@ -79,10 +138,24 @@ pub mod link;
 pub mod master;
 /// Types and traits specific to outstations
 pub mod outstation;
+/// Entry points and types for masters that fail over between two physical channels
+#[cfg(any(feature = "tcp", feature = "serial"))]
+pub mod redundant;
+/// Replay of captured traffic through the parsers and through master or outstation sessions
+#[cfg(any(test, feature = "replay"))]
+pub mod replay;
 /// Entry points and types for serial
+#[cfg(feature = "serial")]
 pub mod serial;
+/// Scripted outstation scenarios for load and regression testing of masters
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
 /// Entry points and types for TCP
+#[cfg(feature = "tcp")]
 pub mod tcp;
+/// Entry points and types for UDP
+#[cfg(feature = "udp")]
+pub mod udp;
 
 pub(crate) mod tokio;
 pub(crate) mod transport;
@@ -0,0 +1,331 @@
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::app::Clock;
+use crate::link::format::with_received_frame;
+use crate::link::header::Header;
+use crate::link::parser::FramePayload;
+
+/// Link type written to the interface description block of a capture
+///
+/// There is no link type assigned to raw DNP3 link frames, so captures normally use one of the
+/// link types reserved for private use. Wireshark decodes them as DNP3 once the link type is
+/// mapped to the `dnp3.udp` dissector in the DLT_USER preferences.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CaptureLinkType {
+    value: u16,
+}
+
+impl CaptureLinkType {
+    /// LINKTYPE_USER0, the first of the 16 link types reserved for private use
+    pub const USER0: Self = Self { value: 147 };
+
+    /// One of the 16 link types reserved for private use, LINKTYPE_USER0 to LINKTYPE_USER15
+    ///
+    /// Returns `None` if `index` is greater than 15
+    pub fn user(index: u8) -> Option<Self> {
+        if index > 15 {
+            return None;
+        }
+        Some(Self {
+            value: Self::USER0.value + index as u16,
+        })
+    }
+
+    /// Any link type by value
+    pub fn new(value: u16) -> Self {
+        Self { value }
+    }
+
+    /// Value written to the capture
+    pub fn value(&self) -> u16 {
+        self.value
+    }
+}
+
+impl Default for CaptureLinkType {
+    fn default() -> Self {
+        Self::USER0
+    }
+}
+
+/// Records link-layer frames to a pcapng file or another `Write` sink
+///
+/// Each frame is written as an enhanced packet block with a microsecond timestamp and the
+/// direction (inbound or outbound) of the frame. The sink is flushed after every frame so that
+/// the capture may be opened while the channel is running.
+pub struct FrameCapture {
+    writer: Box<dyn Write + Send>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum CaptureDirection {
+    Inbound,
+    Outbound,
+}
+
+impl FrameCapture {
+    const SECTION_HEADER: u32 = 0x0A0D_0D0A;
+    const INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+    const ENHANCED_PACKET: u32 = 0x0000_0006;
+    const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+    const EPB_FLAGS: u16 = 2;
+
+    /// Begin a capture on an arbitrary sink, writing the section header and interface
+    /// description blocks immediately
+    pub fn new<W>(writer: W, link_type: CaptureLinkType) -> std::io::Result<Self>
+    where
+        W: Write + Send + 'static,
+    {
+        let mut capture = Self {
+            writer: Box::new(writer),
+        };
+        capture.write_headers(link_type)?;
+        Ok(capture)
+    }
+
+    /// Begin a capture in a new pcapng file, truncating the file if it already exists
+    pub fn create<P: AsRef<Path>>(path: P, link_type: CaptureLinkType) -> std::io::Result<Self> {
+        let file = std::fs::File::create(path)?;
+        Self::new(std::io::BufWriter::new(file), link_type)
+    }
+
+    pub(crate) fn write_frame(
+        &mut self,
+        direction: CaptureDirection,
+        time: SystemTime,
+        frame: &[u8],
+    ) -> std::io::Result<()> {
+        let micros = time
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_micros() as u64)
+            .unwrap_or(0);
+        let padding = (4 - frame.len() % 4) % 4;
+        let flags: u32 = match direction {
+            CaptureDirection::Inbound => 0b01,
+            CaptureDirection::Outbound => 0b10,
+        };
+
+        let mut body = Vec::with_capacity(frame.len() + padding + 32);
+        // interface id
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(micros as u32).to_le_bytes());
+        // captured and original length
+        body.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        body.extend_from_slice(frame);
+        body.extend(std::iter::repeat(0).take(padding));
+        // epb_flags option with the direction followed by opt_endofopt
+        body.extend_from_slice(&Self::EPB_FLAGS.to_le_bytes());
+        body.extend_from_slice(&4u16.to_le_bytes());
+        body.extend_from_slice(&flags.to_le_bytes());
+        body.extend_from_slice(&0u32.to_le_bytes());
+
+        self.write_block(Self::ENHANCED_PACKET, &body)?;
+        self.writer.flush()
+    }
+
+    pub(crate) fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn write_headers(&mut self, link_type: CaptureLinkType) -> std::io::Result<()> {
+        let mut shb = Vec::with_capacity(16);
+        shb.extend_from_slice(&Self::BYTE_ORDER_MAGIC.to_le_bytes());
+        // version 1.0
+        shb.extend_from_slice(&1u16.to_le_bytes());
+        shb.extend_from_slice(&0u16.to_le_bytes());
+        // section length is not specified
+        shb.extend_from_slice(&(-1i64).to_le_bytes());
+        self.write_block(Self::SECTION_HEADER, &shb)?;
+
+        let mut idb = Vec::with_capacity(8);
+        idb.extend_from_slice(&link_type.value.to_le_bytes());
+        // reserved
+        idb.extend_from_slice(&0u16.to_le_bytes());
+        // no limit on the snapshot length
+        idb.extend_from_slice(&0u32.to_le_bytes());
+        self.write_block(Self::INTERFACE_DESCRIPTION, &idb)?;
+
+        self.writer.flush()
+    }
+
+    fn write_block(&mut self, block_type: u32, body: &[u8]) -> std::io::Result<()> {
+        // block type and the total length before and after the body
+        let total_length = (body.len() + 12) as u32;
+        self.writer.write_all(&block_type.to_le_bytes())?;
+        self.writer.write_all(&total_length.to_le_bytes())?;
+        self.writer.write_all(body)?;
+        self.writer.write_all(&total_length.to_le_bytes())
+    }
+}
+
+impl std::fmt::Debug for FrameCapture {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("FrameCapture")
+    }
+}
+
+/// capture shared between the link and transport layers and the channel handle
+#[derive(Debug, Default)]
+pub(crate) struct CaptureSlot {
+    inner: Mutex<Option<FrameCapture>>,
+    // time source of the timestamps
+    clock: Clock,
+}
+
+impl CaptureSlot {
+    pub(crate) fn new(clock: Clock) -> Self {
+        Self {
+            inner: Mutex::default(),
+            clock,
+        }
+    }
+
+    pub(crate) fn start(&self, capture: FrameCapture) {
+        *self.inner.lock().unwrap() = Some(capture);
+    }
+
+    pub(crate) fn stop(&self) -> std::io::Result<()> {
+        match self.inner.lock().unwrap().take() {
+            Some(mut capture) => capture.flush(),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.inner.lock().unwrap().is_some()
+    }
+
+    pub(crate) fn on_tx(&self, frame: &[u8]) {
+        self.write(CaptureDirection::Outbound, frame);
+    }
+
+    /// the received frame is re-encoded because the parser doesn't retain the header bytes
+    pub(crate) fn on_rx(&self, header: Header, payload: FramePayload) {
+        if !self.is_active() {
+            return;
+        }
+
+        with_received_frame(header, payload, |frame| {
+            self.write(CaptureDirection::Inbound, frame)
+        });
+    }
+
+    fn write(&self, direction: CaptureDirection, frame: &[u8]) {
+        let mut guard = self.inner.lock().unwrap();
+        if let Some(capture) = guard.as_mut() {
+            if let Err(err) = capture.write_frame(direction, self.clock.system_time(), frame) {
+                tracing::warn!("stopping frame capture: {}", err);
+                *guard = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::super::test_data::*;
+    use super::*;
+    use crate::link::parser::Parser;
+    use crate::link::statistics::LinkCounters;
+    use crate::link::LinkErrorMode;
+    use crate::util::cursor::ReadCursor;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn bytes(&self) -> Vec<u8> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    fn read_u32(data: &[u8], pos: usize) -> u32 {
+        u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+    }
+
+    // section header (28 bytes) followed by the interface description (20 bytes)
+    const HEADERS_LENGTH: usize = 48;
+
+    #[test]
+    fn writes_section_header_and_interface_description() {
+        let buffer = SharedBuffer::default();
+        FrameCapture::new(buffer.clone(), CaptureLinkType::user(2).unwrap()).unwrap();
+        let data = buffer.bytes();
+
+        assert_eq!(data.len(), HEADERS_LENGTH);
+        assert_eq!(read_u32(&data, 0), 0x0A0D_0D0A);
+        assert_eq!(read_u32(&data, 4), 28);
+        assert_eq!(read_u32(&data, 8), 0x1A2B_3C4D);
+        assert_eq!(read_u32(&data, 24), 28);
+        assert_eq!(read_u32(&data, 28), 1);
+        assert_eq!(read_u32(&data, 32), 20);
+        assert_eq!(&data[36..38], &149u16.to_le_bytes());
+        assert_eq!(read_u32(&data, 44), 20);
+    }
+
+    #[test]
+    fn writes_enhanced_packet_with_timestamp_and_direction() {
+        let buffer = SharedBuffer::default();
+        let mut capture = FrameCapture::new(buffer.clone(), CaptureLinkType::USER0).unwrap();
+        let time = UNIX_EPOCH + Duration::from_micros(0x0000_0001_0000_0002);
+        capture
+            .write_frame(CaptureDirection::Outbound, time, RESET_LINK.bytes)
+            .unwrap();
+        let data = buffer.bytes();
+        let epb = &data[HEADERS_LENGTH..];
+
+        // 10 byte frame padded to 12
+        assert_eq!(epb.len(), 56);
+        assert_eq!(read_u32(epb, 0), 6);
+        assert_eq!(read_u32(epb, 4), 56);
+        assert_eq!(read_u32(epb, 12), 1);
+        assert_eq!(read_u32(epb, 16), 2);
+        assert_eq!(read_u32(epb, 20), 10);
+        assert_eq!(read_u32(epb, 24), 10);
+        assert_eq!(&epb[28..38], RESET_LINK.bytes);
+        assert_eq!(&epb[40..44], &[0x02, 0x00, 0x04, 0x00]);
+        assert_eq!(read_u32(epb, 44), 0b10);
+        assert_eq!(read_u32(epb, 52), 56);
+    }
+
+    #[test]
+    fn re_encodes_received_frames_exactly() {
+        let buffer = SharedBuffer::default();
+        let counters = Arc::new(LinkCounters::default());
+        let slot = CaptureSlot::default();
+        slot.start(FrameCapture::new(buffer.clone(), CaptureLinkType::USER0).unwrap());
+
+        let mut parser = Parser::new(LinkErrorMode::Close, counters);
+        let mut cursor = ReadCursor::new(CONFIRM_USER_DATA.bytes);
+        let mut payload = FramePayload::empty();
+        let header = parser.parse(&mut cursor, &mut payload).unwrap().unwrap();
+        slot.on_rx(header, payload);
+        slot.stop().unwrap();
+        assert!(!slot.is_active());
+
+        let data = buffer.bytes();
+        let epb = &data[HEADERS_LENGTH..];
+        let length = CONFIRM_USER_DATA.bytes.len();
+        assert_eq!(read_u32(epb, 20) as usize, length);
+        assert_eq!(&epb[28..28 + length], CONFIRM_USER_DATA.bytes);
+        assert_eq!(read_u32(epb, 28 + length + 1 + 4), 0b01);
+    }
+}
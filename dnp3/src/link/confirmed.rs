@@ -0,0 +1,389 @@
+use std::io::ErrorKind;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::decode::{DecodeDirection, DecodeLevel};
+use crate::link::constant::{LINK_HEADER_LENGTH, MAX_LINK_FRAME_LENGTH};
+use crate::link::crc::calc_crc;
+use crate::link::error::{LinkError, LogicError};
+use crate::link::format::format_header_fixed_size;
+use crate::link::function::Function;
+use crate::link::header::{ControlField, Header};
+use crate::link::parser::{FramePayload, Parser};
+use crate::link::statistics::LinkCounters;
+use crate::link::LinkErrorMode;
+use crate::util::cursor::ReadCursor;
+use crate::util::phys::PhysLayer;
+
+/// Primary station side of the confirmed user data service
+///
+/// Each frame is retransmitted until the secondary station acknowledges it or the retries are
+/// exhausted. The secondary station is reset with RESET_LINK_STATES before the first frame and
+/// after it rejects a frame with a NACK.
+///
+/// The acknowledgements are read from the physical layer while the link reader is idle. Any
+/// other bytes received in the meantime are handed back to the reader through the counters.
+pub(crate) struct ConfirmedService {
+    retries: usize,
+    ack_timeout: Duration,
+    counters: Arc<LinkCounters>,
+    // true once the secondary station acknowledged RESET_LINK_STATES
+    reset: bool,
+    // frame count bit of the next frame
+    fcb: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Reply {
+    Ack,
+    Nack,
+}
+
+impl ConfirmedService {
+    pub(crate) fn new(retries: usize, ack_timeout: Duration, counters: Arc<LinkCounters>) -> Self {
+        Self {
+            retries,
+            ack_timeout,
+            counters,
+            reset: false,
+            fcb: true,
+        }
+    }
+
+    /// reset the secondary station again before the next frame, e.g. after reconnecting
+    pub(crate) fn reset(&mut self) {
+        self.reset = false;
+    }
+
+    /// transmit a frame of confirmed user data, returning false if the secondary station
+    /// didn't acknowledge it
+    ///
+    /// The frame count bit of `frame` is replaced. The first transmission of the frame isn't
+    /// counted since the writer counts frames as it formats them.
+    pub(crate) async fn transmit(
+        &mut self,
+        io: &mut PhysLayer,
+        level: DecodeLevel,
+        header: Header,
+        frame: &[u8],
+    ) -> Result<bool, LinkError> {
+        let mut buffer = [0; MAX_LINK_FRAME_LENGTH];
+        let data = match buffer.get_mut(0..frame.len()) {
+            Some(data) if frame.len() >= LINK_HEADER_LENGTH => data,
+            _ => return Err(LinkError::BadLogic(LogicError::BadSize)),
+        };
+        data.copy_from_slice(frame);
+
+        let mut transmitted = false;
+        for _ in 0..=self.retries {
+            if !self.reset {
+                let mut request = [0; LINK_HEADER_LENGTH];
+                format_header_fixed_size(
+                    Header::new(
+                        ControlField::new(header.control.master, Function::PriResetLinkStates),
+                        header.destination,
+                        header.source,
+                    ),
+                    &mut request,
+                );
+                self.send(io, level, &request, true).await?;
+                match self.wait_for_reply(io, level, header).await? {
+                    Some(Reply::Ack) => {
+                        self.reset = true;
+                        self.fcb = true;
+                    }
+                    _ => {
+                        tracing::warn!(
+                            "RESET_LINK_STATES not acknowledged by {}",
+                            header.destination
+                        );
+                        continue;
+                    }
+                }
+            }
+
+            Self::set_fcb(data, self.fcb);
+            self.send(io, level, data, transmitted).await?;
+            transmitted = true;
+
+            match self.wait_for_reply(io, level, header).await? {
+                Some(Reply::Ack) => {
+                    self.fcb = !self.fcb;
+                    return Ok(true);
+                }
+                Some(Reply::Nack) => {
+                    tracing::warn!(
+                        "confirmed user data rejected with NACK by {}",
+                        header.destination
+                    );
+                    self.reset = false;
+                }
+                None => {
+                    tracing::warn!(
+                        "confirmed user data not acknowledged by {} within {} ms",
+                        header.destination,
+                        self.ack_timeout.as_millis()
+                    );
+                }
+            }
+        }
+
+        tracing::warn!(
+            "giving up on confirmed user data after {} retries",
+            self.retries
+        );
+        self.reset = false;
+        Ok(false)
+    }
+
+    fn set_fcb(frame: &mut [u8], fcb: bool) {
+        let mut control = ControlField::from(frame[3]);
+        control.fcb = fcb;
+        frame[3] = control.to_u8();
+        let crc = calc_crc(&frame[0..8]);
+        frame[8] = (crc & 0xFF) as u8;
+        frame[9] = (crc >> 8) as u8;
+    }
+
+    async fn send(
+        &mut self,
+        io: &mut PhysLayer,
+        level: DecodeLevel,
+        frame: &[u8],
+        count: bool,
+    ) -> Result<(), LinkError> {
+        self.counters
+            .decode()
+            .emit_phys(DecodeDirection::Tx, level.physical, frame);
+        io.write(frame).await?;
+        if count {
+            self.counters.on_frame_tx();
+            #[cfg(feature = "pcap")]
+            self.counters.capture().on_tx(frame);
+        }
+        Ok(())
+    }
+
+    /// read until the secondary station replies to `request` or the ACK timeout elapses
+    async fn wait_for_reply(
+        &mut self,
+        io: &mut PhysLayer,
+        level: DecodeLevel,
+        request: Header,
+    ) -> Result<Option<Reply>, LinkError> {
+        let clock = self.counters.clock();
+        let deadline = clock.now() + self.ack_timeout;
+        // the bytes are parsed separately so that everything but the reply can be handed back
+        let mut parser = Parser::new(LinkErrorMode::Discard, Arc::default());
+        let mut received: Vec<u8> = Vec::new();
+        let mut parsed = 0;
+        let mut buffer = [0; MAX_LINK_FRAME_LENGTH];
+
+        let result = loop {
+            let mut cursor = ReadCursor::new(&received[parsed..]);
+            let start = cursor.remaining();
+            let mut payload = FramePayload::empty();
+            let frame = parser.parse(&mut cursor, &mut payload);
+            parsed += start - cursor.remaining();
+
+            if let Ok(Some(frame)) = frame {
+                if let Some(reply) = Self::reply(request, frame) {
+                    // replies are header-only frames
+                    received.drain(parsed - LINK_HEADER_LENGTH..parsed);
+                    break Ok(Some(reply));
+                }
+                // keep the frame for the reader and look at the next one
+                continue;
+            }
+
+            let count = crate::tokio::select! {
+                count = io.read(&mut buffer) => Some(count),
+                _ = clock.sleep_until(deadline) => None,
+            };
+
+            match count {
+                None => break Ok(None),
+                Some(Ok(0)) => break Err(LinkError::Stdio(ErrorKind::UnexpectedEof)),
+                Some(Ok(count)) => {
+                    let data = &buffer[0..count];
+                    self.counters
+                        .decode()
+                        .emit_phys(DecodeDirection::Rx, level.physical, data);
+                    received.extend_from_slice(data);
+                }
+                Some(Err(err)) => break Err(err.into()),
+            }
+        };
+
+        self.counters.push_unread(&received);
+        result
+    }
+
+    fn reply(request: Header, frame: Header) -> Option<Reply> {
+        if frame.control.master == request.control.master
+            || frame.source != request.destination
+            || frame.destination != request.source
+        {
+            return None;
+        }
+
+        match frame.control.func {
+            Function::SecAck => Some(Reply::Ack),
+            Function::SecNack => Some(Reply::Nack),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test_data::*;
+    use super::*;
+    use crate::tokio::test::*;
+
+    // CONFIRM_USER_DATA with the FCB cleared
+    const CONFIRM_USER_DATA_FCB_UNSET: &[u8] = &[
+        0x05, 0x64, 0x14, 0xD3, 0x01, 0x00, 0x00, 0x04, 0x57, 0x23, 0xC0, 0xC3, 0x01, 0x3C, 0x02,
+        0x06, 0x3C, 0x03, 0x06, 0x3C, 0x04, 0x06, 0x3C, 0x01, 0x06, 0x9A, 0x12,
+    ];
+    // NACK from outstation 1 to master 1024
+    const NACK: &[u8] = &[0x05, 0x64, 0x05, 0x01, 0x00, 0x04, 0x01, 0x00, 0x1F, 0x85];
+
+    fn service(retries: usize, counters: Arc<LinkCounters>) -> ConfirmedService {
+        ConfirmedService::new(retries, Duration::from_secs(1), counters)
+    }
+
+    #[test]
+    fn resets_secondary_station_and_alternates_fcb() {
+        let (io, mut io_handle) = io::mock();
+        let mut io = PhysLayer::Mock(io);
+        let mut service = service(0, Arc::default());
+
+        {
+            let mut task = spawn(service.transmit(
+                &mut io,
+                DecodeLevel::nothing(),
+                CONFIRM_USER_DATA.header,
+                CONFIRM_USER_DATA_FCB_UNSET,
+            ));
+
+            io_handle.write(RESET_LINK.bytes);
+            assert_pending!(task.poll());
+            io_handle.read(ACK.bytes);
+            io_handle.write(CONFIRM_USER_DATA.bytes);
+            assert_pending!(task.poll());
+            io_handle.read(ACK.bytes);
+            assert_eq!(assert_ready!(task.poll()), Ok(true));
+        }
+
+        // the secondary station stays reset and the next frame has the opposite FCB
+        let mut task = spawn(service.transmit(
+            &mut io,
+            DecodeLevel::nothing(),
+            CONFIRM_USER_DATA.header,
+            CONFIRM_USER_DATA.bytes,
+        ));
+        io_handle.write(CONFIRM_USER_DATA_FCB_UNSET);
+        assert_pending!(task.poll());
+        io_handle.read(ACK.bytes);
+        assert_eq!(assert_ready!(task.poll()), Ok(true));
+    }
+
+    #[test]
+    fn retransmits_frame_after_ack_timeout() {
+        let (io, mut io_handle) = io::mock();
+        let mut io = PhysLayer::Mock(io);
+        let counters = Arc::new(LinkCounters::default());
+        let mut service = service(1, counters.clone());
+
+        let mut task = spawn(service.transmit(
+            &mut io,
+            DecodeLevel::nothing(),
+            CONFIRM_USER_DATA.header,
+            CONFIRM_USER_DATA.bytes,
+        ));
+
+        io_handle.write(RESET_LINK.bytes);
+        assert_pending!(task.poll());
+        io_handle.read(ACK.bytes);
+        io_handle.write(CONFIRM_USER_DATA.bytes);
+        assert_pending!(task.poll());
+
+        crate::tokio::time::advance(Duration::from_millis(1001));
+        io_handle.write(CONFIRM_USER_DATA.bytes);
+        assert_pending!(task.poll());
+        io_handle.read(ACK.bytes);
+        assert_eq!(assert_ready!(task.poll()), Ok(true));
+        drop(task);
+
+        // the reset and the retransmission, but not the first transmission
+        assert_eq!(counters.snapshot().frames_tx, 2);
+    }
+
+    #[test]
+    fn gives_up_after_retries_and_resets_again() {
+        let (io, mut io_handle) = io::mock();
+        let mut io = PhysLayer::Mock(io);
+        let mut service = service(1, Arc::default());
+
+        {
+            let mut task = spawn(service.transmit(
+                &mut io,
+                DecodeLevel::nothing(),
+                CONFIRM_USER_DATA.header,
+                CONFIRM_USER_DATA.bytes,
+            ));
+
+            io_handle.write(RESET_LINK.bytes);
+            assert_pending!(task.poll());
+            io_handle.read(ACK.bytes);
+            io_handle.write(CONFIRM_USER_DATA.bytes);
+            assert_pending!(task.poll());
+
+            // a NACK uses up an attempt and requires another reset
+            io_handle.read(NACK);
+            io_handle.write(RESET_LINK.bytes);
+            assert_pending!(task.poll());
+
+            crate::tokio::time::advance(Duration::from_millis(1001));
+            assert_eq!(assert_ready!(task.poll()), Ok(false));
+        }
+
+        assert!(!service.reset);
+    }
+
+    #[test]
+    fn hands_other_frames_back_to_the_reader() {
+        let (io, mut io_handle) = io::mock();
+        let mut io = PhysLayer::Mock(io);
+        let counters = Arc::new(LinkCounters::default());
+        let mut service = service(0, counters.clone());
+
+        let mut task = spawn(service.transmit(
+            &mut io,
+            DecodeLevel::nothing(),
+            CONFIRM_USER_DATA.header,
+            CONFIRM_USER_DATA.bytes,
+        ));
+
+        io_handle.write(RESET_LINK.bytes);
+        assert_pending!(task.poll());
+        io_handle.read(ACK.bytes);
+        io_handle.write(CONFIRM_USER_DATA.bytes);
+        assert_pending!(task.poll());
+
+        let mut data = UNCONFIRMED_USER_DATA.bytes.to_vec();
+        data.extend_from_slice(ACK.bytes);
+        data.extend_from_slice(&RESET_LINK.bytes[0..4]);
+        io_handle.read(&data);
+        assert_eq!(assert_ready!(task.poll()), Ok(true));
+        drop(task);
+
+        let mut expected = UNCONFIRMED_USER_DATA.bytes.to_vec();
+        expected.extend_from_slice(&RESET_LINK.bytes[0..4]);
+        let mut buffer = [0; MAX_LINK_FRAME_LENGTH];
+        let count = counters.take_unread(&mut buffer);
+        assert_eq!(&buffer[0..count], expected.as_slice());
+        assert_eq!(counters.take_unread(&mut buffer), 0);
+    }
+}
@@ -23,6 +23,31 @@ const CRC_TABLE: [u16; 256] = [
     0x48D7, 0x7E89, 0x246B, 0x1235,
 ];
 
+/// number of bytes processed by each step of [crc_increment], i.e. the length of the data
+/// blocks of a link frame
+const SLICE_LENGTH: usize = 16;
+
+/// `SLICED_TABLES[k][i]` is the CRC of byte `i` followed by `k` zero bytes
+///
+/// This lets the CRC of a block be computed from independent lookups of each of its bytes
+/// rather than from a chain of lookups that each depend on the previous one.
+static SLICED_TABLES: [[u16; 256]; SLICE_LENGTH] = sliced_tables();
+
+const fn sliced_tables() -> [[u16; 256]; SLICE_LENGTH] {
+    let mut tables = [CRC_TABLE; SLICE_LENGTH];
+    let mut k = 1;
+    while k < SLICE_LENGTH {
+        let mut i = 0;
+        while i < 256 {
+            let prev = tables[k - 1][i];
+            tables[k][i] = (prev >> 8) ^ CRC_TABLE[(prev & 0xFF) as usize];
+            i += 1;
+        }
+        k += 1;
+    }
+    tables
+}
+
 const CRC_OF_0564: u16 = 0x3F0D;
 
 pub(crate) fn calc_crc(slice: &[u8]) -> u16 {
@@ -34,6 +59,28 @@ pub(crate) fn calc_crc_with_0564(slice: &[u8]) -> u16 {
 }
 
 pub(crate) fn crc_increment(mut acc: u16, slice: &[u8]) -> u16 {
+    let mut blocks = slice.chunks_exact(SLICE_LENGTH);
+
+    for block in &mut blocks {
+        // the accumulator only affects the first two bytes of the block
+        let first = block[0] ^ (acc as u8);
+        let second = block[1] ^ ((acc >> 8) as u8);
+        acc = SLICED_TABLES[SLICE_LENGTH - 1][first as usize]
+            ^ SLICED_TABLES[SLICE_LENGTH - 2][second as usize];
+        for (table, byte) in SLICED_TABLES.iter().rev().skip(2).zip(block[2..].iter()) {
+            acc ^= table[*byte as usize];
+        }
+    }
+
+    for byte in blocks.remainder() {
+        let index = ((acc as u8) ^ *byte) as usize;
+        acc = CRC_TABLE[index] ^ (acc >> 8)
+    }
+    acc
+}
+
+#[cfg(test)]
+fn crc_increment_bytewise(mut acc: u16, slice: &[u8]) -> u16 {
     for byte in slice {
         let index = ((acc as u8) ^ *byte) as usize;
         acc = CRC_TABLE[index] ^ (acc >> 8)
@@ -55,4 +102,17 @@ mod test {
         let bytes: [u8; 8] = [0x05, 0x64, 0x05, 0xC0, 0x01, 0x00, 0x00, 0x04];
         assert_eq!(calc_crc(&bytes), 0x21E9);
     }
+
+    #[test]
+    fn sliced_crc_matches_bytewise_crc() {
+        let bytes: Vec<u8> = (0..100u8).map(|x| x.wrapping_mul(37) ^ 0x5A).collect();
+        for len in 0..bytes.len() {
+            for acc in [0, CRC_OF_0564, 0xFFFF].iter() {
+                assert_eq!(
+                    crc_increment(*acc, &bytes[..len]),
+                    crc_increment_bytewise(*acc, &bytes[..len])
+                );
+            }
+        }
+    }
 }
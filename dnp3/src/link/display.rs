@@ -1,14 +1,15 @@
-use crate::decode::LinkDecodeLevel;
+use crate::decode::{LinkDecodeLevel, PayloadKind};
 use crate::link::header::Header;
+use crate::link::parser::FramePayload;
 
 pub(crate) struct LinkDisplay<'a> {
     header: Header,
-    payload: &'a [u8],
+    payload: FramePayload<'a>,
     level: LinkDecodeLevel,
 }
 
 impl<'a> LinkDisplay<'a> {
-    pub(crate) fn new(header: Header, payload: &'a [u8], level: LinkDecodeLevel) -> Self {
+    pub(crate) fn new(header: Header, payload: FramePayload<'a>, level: LinkDecodeLevel) -> Self {
         LinkDisplay {
             header,
             payload,
@@ -30,7 +31,7 @@ impl<'a> std::fmt::Display for LinkDisplay<'a> {
             )?;
         }
         if self.level.payload_enabled() {
-            crate::util::decode::format_bytes(f, self.payload)?;
+            crate::util::decode::format_byte_iter(f, PayloadKind::Link, self.payload.bytes())?;
         }
         Ok(())
     }
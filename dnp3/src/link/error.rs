@@ -5,8 +5,11 @@ use crate::util::cursor::WriteError;
 /// a bug in the library itself
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum LogicError {
+    /// a read was out-of-bounds
     BadRead,
+    /// a write was out-of-bounds
     BadWrite,
+    /// a size was out-of-bounds
     BadSize,
 }
 
@@ -15,10 +18,15 @@ pub enum LogicError {
 /// the communication session. On serial, they are just discarded.
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum FrameError {
+    /// first start byte is not 0x05
     UnexpectedStart1(u8),
+    /// second start byte is not 0x64
     UnexpectedStart2(u8),
+    /// length is less than the minimum of 5
     BadLength(u8),
+    /// CRC of the header doesn't match its contents
     BadHeaderCrc,
+    /// CRC of a block of the payload doesn't match its contents
     BadBodyCrc,
 }
 
@@ -28,19 +36,23 @@ pub enum ParseError {
     BadLogic(LogicError),
 }
 
+/// Errors that end a communication session at the link layer
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum LinkError {
+    /// reading from or writing to the physical layer failed
     Stdio(std::io::ErrorKind),
+    /// a malformed frame was received
     BadFrame(FrameError),
+    /// a bug in the library
     BadLogic(LogicError),
 }
 
 impl std::fmt::Display for LinkError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            LinkError::Stdio(kind) => write!(f, "{}", std::io::Error::from(*kind)),
-            LinkError::BadFrame(err) => write!(f, "{}", err),
-            LinkError::BadLogic(err) => write!(f, "{}", err),
+            LinkError::Stdio(kind) => write!(f, "I/O error: {}", std::io::Error::from(*kind)),
+            LinkError::BadFrame(err) => write!(f, "bad frame: {}", err),
+            LinkError::BadLogic(err) => write!(f, "internal error: {}", err),
         }
     }
 }
@@ -52,7 +64,7 @@ impl std::fmt::Display for FrameError {
             FrameError::BadLength(x) => write!(f, "bad frame length: {}", x),
             FrameError::BadHeaderCrc => f.write_str("bad CRC value in frame header"),
             FrameError::UnexpectedStart1(x) => write!(f, "bad frame start1: {} != 0x05", x),
-            FrameError::UnexpectedStart2(x) => write!(f, "bad frame start1: {} != 0x64", x),
+            FrameError::UnexpectedStart2(x) => write!(f, "bad frame start2: {} != 0x64", x),
         }
     }
 }
@@ -67,6 +79,20 @@ impl std::fmt::Display for LogicError {
     }
 }
 
+impl std::error::Error for LinkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LinkError::Stdio(_) => None,
+            LinkError::BadFrame(err) => Some(err),
+            LinkError::BadLogic(err) => Some(err),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+impl std::error::Error for LogicError {}
+
 impl From<ParseError> for LinkError {
     fn from(err: ParseError) -> Self {
         match err {
@@ -4,6 +4,7 @@ use crate::link::crc::{calc_crc, calc_crc_with_0564};
 use crate::link::display::LinkDisplay;
 use crate::link::error::LogicError;
 use crate::link::header::Header;
+use crate::link::parser::FramePayload;
 use crate::util::cursor::{WriteCursor, WriteError};
 use crate::util::slice_ext::SliceExtNoPanic;
 
@@ -37,7 +38,35 @@ pub(crate) struct FrameData<'a> {
 
 impl<'a> FrameData<'a> {
     pub(crate) fn to_link_display(&self, level: LinkDecodeLevel) -> LinkDisplay {
-        LinkDisplay::new(self.header, self.payload_only, level)
+        LinkDisplay::new(
+            self.header,
+            FramePayload::contiguous(self.payload_only),
+            level,
+        )
+    }
+}
+
+/// re-encode a received frame for consumers of its raw bytes, because the parser doesn't retain
+/// the header bytes
+pub(crate) fn with_received_frame<F>(header: Header, payload: FramePayload, consume: F)
+where
+    F: FnOnce(&[u8]),
+{
+    let mut user_data = [0; constant::MAX_FRAME_PAYLOAD_LENGTH];
+    let mut buffer = [0; constant::MAX_LINK_FRAME_LENGTH];
+    let mut cursor = WriteCursor::new(&mut buffer);
+    let frame = match payload.split_first() {
+        None => format_header_only(header, &mut cursor),
+        Some((transport, remainder)) => format_data_frame(
+            header,
+            Payload::new(transport, remainder.copy_to(&mut user_data)),
+            &mut cursor,
+        ),
+    };
+
+    match frame {
+        Ok(frame) => consume(frame.frame),
+        Err(err) => tracing::warn!("unable to re-encode received frame: {:?}", err),
     }
 }
 
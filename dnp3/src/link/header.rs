@@ -1,3 +1,4 @@
+use crate::decode::{DecodeDirection, DecodeEvent, LinkFrameEvent};
 use crate::link::EndpointAddress;
 
 use super::function::Function;
@@ -16,15 +17,21 @@ pub(crate) mod constants {
     pub(crate) const RESERVED_START: u16 = 0xFFF0;
 }
 
+/// Broadcast address on which a request was received, which determines
+/// whether the outstation must request confirmation of its next response
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub(crate) enum BroadcastConfirmMode {
+pub enum BroadcastConfirmMode {
+    /// sent to 0xFFFF - confirmation of the next response is optional
     Optional,
+    /// sent to 0xFFFE - the outstation must request confirmation of its next response
     Mandatory,
+    /// sent to 0xFFFD - the outstation does not request confirmation
     NotRequired,
 }
 
 impl BroadcastConfirmMode {
-    pub(crate) fn address(&self) -> u16 {
+    /// link-layer destination address associated with the mode
+    pub fn address(&self) -> u16 {
         match self {
             BroadcastConfirmMode::Optional => constants::BROADCAST_CONFIRM_OPTIONAL,
             BroadcastConfirmMode::Mandatory => constants::BROADCAST_CONFIRM_MANDATORY,
@@ -178,6 +185,17 @@ impl Header {
         )
     }
 
+    /// the FCB is set by the confirmed service before the frame is transmitted
+    pub(crate) fn confirmed_user_data(
+        is_master: bool,
+        destination: AnyAddress,
+        source: AnyAddress,
+    ) -> Self {
+        let mut control = ControlField::new(is_master, Function::PriConfirmedUserData);
+        control.fcv = true;
+        Self::new(control, destination, source)
+    }
+
     pub(crate) fn request_link_status(
         is_master: bool,
         destination: AnyAddress,
@@ -189,4 +207,14 @@ impl Header {
             source,
         )
     }
+
+    pub(crate) fn to_event(self, direction: DecodeDirection, length: usize) -> DecodeEvent {
+        DecodeEvent::LinkFrame(LinkFrameEvent {
+            direction,
+            control: self.control.to_u8(),
+            destination: self.destination.value(),
+            source: self.source.value(),
+            length,
+        })
+    }
 }
@@ -1,5 +1,7 @@
+use std::sync::Arc;
+
 use crate::app::EndpointType;
-use crate::decode::DecodeLevel;
+use crate::decode::{DecodeDirection, DecodeLevel, WireLayer};
 use crate::link::display::LinkDisplay;
 use crate::link::error::LinkError;
 use crate::link::format::format_header_fixed_size;
@@ -8,7 +10,10 @@ use crate::link::header::{
     AnyAddress, BroadcastConfirmMode, ControlField, FrameInfo, FrameType, Header,
 };
 use crate::link::parser::FramePayload;
-use crate::link::{EndpointAddress, LinkErrorMode};
+use crate::link::statistics::{ActivityRecorder, LinkCounters};
+use crate::link::{
+    EndpointAddress, LinkErrorMode, MisaddressedFrame, RxTimestamp, UnknownDestinationPolicy,
+};
 use crate::outstation::Feature;
 use crate::util::phys::PhysLayer;
 
@@ -21,8 +26,11 @@ pub(crate) struct Layer {
     endpoint_type: EndpointType,
     self_address: Feature,
     local_address: EndpointAddress,
+    unknown_destination: UnknownDestinationPolicy,
     secondary_state: SecondaryState,
     reader: super::reader::Reader,
+    activity: ActivityRecorder,
+    counters: Arc<LinkCounters>,
     tx_buffer: [u8; super::constant::LINK_HEADER_LENGTH],
 }
 
@@ -43,13 +51,19 @@ impl Layer {
         endpoint_type: EndpointType,
         self_address: Feature,
         local_address: EndpointAddress,
+        inter_frame_timeout: Option<std::time::Duration>,
+        unknown_destination: UnknownDestinationPolicy,
+        counters: Arc<LinkCounters>,
     ) -> Self {
         Self {
             endpoint_type,
             self_address,
             local_address,
+            unknown_destination,
             secondary_state: SecondaryState::NotReset,
-            reader: super::reader::Reader::new(error_mode),
+            reader: super::reader::Reader::new(error_mode, inter_frame_timeout, counters.clone()),
+            activity: ActivityRecorder::default(),
+            counters,
             tx_buffer: [0; super::constant::LINK_HEADER_LENGTH],
         }
     }
@@ -63,15 +77,19 @@ impl Layer {
         &mut self,
         io: &mut PhysLayer,
         level: DecodeLevel,
-        payload: &mut FramePayload,
-    ) -> Result<FrameInfo, LinkError> {
+    ) -> Result<(FrameInfo, RxTimestamp), LinkError> {
         loop {
-            if let Some(address) = self.read_one(io, level, payload).await? {
-                return Ok(address);
+            if let Some(frame) = self.read_one(io, level).await? {
+                return Ok(frame);
             }
         }
     }
 
+    /// payload of the last frame returned by `read`
+    pub(crate) fn payload(&self) -> FramePayload {
+        self.reader.payload()
+    }
+
     fn get_header(&self, reply: Reply) -> Header {
         Header::new(
             ControlField::new(self.endpoint_type.dir_bit(), reply.function),
@@ -80,27 +98,55 @@ impl Layer {
         )
     }
 
-    fn format_reply(&mut self, header: Header) -> &[u8] {
+    fn format_reply(&mut self, header: Header) {
         format_header_fixed_size(header, &mut self.tx_buffer);
-        &self.tx_buffer
     }
 
     async fn read_one(
         &mut self,
         io: &mut PhysLayer,
         level: DecodeLevel,
-        payload: &mut FramePayload,
-    ) -> Result<Option<FrameInfo>, LinkError> {
-        let header = self.reader.read(io, payload, level).await?;
+    ) -> Result<Option<(FrameInfo, RxTimestamp)>, LinkError> {
+        let (header, received) = self.reader.read(io, level).await?;
         let (info, reply) = self.process_header(&header);
         if let Some(reply) = reply {
             let header = self.get_header(reply);
             if level.link.enabled() {
-                tracing::info!("LINK TX - {}", LinkDisplay::new(header, &[], level.link));
+                self.counters.decode().emit(format_args!(
+                    "LINK TX - {}",
+                    LinkDisplay::new(header, FramePayload::empty(), level.link)
+                ));
+            }
+            self.counters
+                .decode()
+                .emit_event(header.to_event(DecodeDirection::Tx, 0));
+            self.format_reply(header);
+            self.counters
+                .decode()
+                .emit_wire(WireLayer::Link, DecodeDirection::Tx, &self.tx_buffer);
+            self.counters
+                .decode()
+                .emit_phys(DecodeDirection::Tx, level.physical, &self.tx_buffer);
+            io.write(&self.tx_buffer).await?;
+            self.counters.on_frame_tx();
+            #[cfg(feature = "pcap")]
+            self.counters.capture().on_tx(&self.tx_buffer);
+        }
+        Ok(info.map(|info| (info, received)))
+    }
+
+    fn on_unknown_destination(&mut self, source: EndpointAddress, destination: AnyAddress) {
+        self.counters.on_bad_address();
+        match self.unknown_destination {
+            UnknownDestinationPolicy::Discard => {}
+            UnknownDestinationPolicy::Log => {
+                tracing::warn!("ignoring frame from {} sent to {}", source, destination);
             }
-            io.write(self.format_reply(header), level.physical).await?
+            UnknownDestinationPolicy::Notify(listener) => listener.notify(MisaddressedFrame {
+                source: source.raw_value(),
+                destination: destination.value(),
+            }),
         }
-        Ok(info)
     }
 
     fn process_header(&mut self, header: &Header) -> (Option<FrameInfo>, Option<Reply>) {
@@ -118,6 +164,7 @@ impl Layer {
                     "ignoring frame from disallowed source address: {}",
                     header.source
                 );
+                self.counters.on_bad_address();
                 return (None, None);
             }
         };
@@ -128,7 +175,7 @@ impl Layer {
                 if x == self.local_address {
                     None
                 } else {
-                    tracing::warn!("ignoring frame sent to address: {}", x);
+                    self.on_unknown_destination(source, header.destination);
                     return (None, None);
                 }
             }
@@ -137,17 +184,18 @@ impl Layer {
                     // just pretend like it was sent to us
                     None
                 } else {
-                    tracing::warn!("ignoring frame sent to self address");
+                    self.on_unknown_destination(source, header.destination);
                     return (None, None);
                 }
             }
-            AnyAddress::Reserved(x) => {
-                tracing::warn!("ignoring frame sent to reserved address: {}", x);
+            AnyAddress::Reserved(_) => {
+                self.on_unknown_destination(source, header.destination);
                 return (None, None);
             }
             AnyAddress::Broadcast(mode) => match self.endpoint_type {
                 EndpointType::Master => {
                     tracing::warn!("ignoring broadcast frame sent to master");
+                    self.counters.on_bad_address();
                     return (None, None);
                 }
                 EndpointType::Outstation => Some(mode),
@@ -168,6 +216,12 @@ impl Layer {
             return (None, None);
         }
 
+        self.activity.record(
+            &self.counters,
+            source,
+            header.control.func == Function::SecLinkStatus,
+        );
+
         match header.control.func {
             Function::PriUnconfirmedUserData => {
                 if header.control.fcv {
@@ -253,3 +307,117 @@ impl Layer {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::app::{Clock, Listener, ManualClock};
+    use crate::decode::TracingConfig;
+    use crate::link::MisaddressedFrameListener;
+
+    struct MockListener {
+        frames: Arc<Mutex<Vec<MisaddressedFrame>>>,
+    }
+
+    impl Listener<MisaddressedFrame> for MockListener {
+        fn update(&mut self, value: MisaddressedFrame) {
+            self.frames.lock().unwrap().push(value);
+        }
+    }
+
+    #[test]
+    fn notifies_listener_of_frames_sent_to_other_addresses() {
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        let mut layer = Layer::new(
+            LinkErrorMode::Close,
+            EndpointType::Outstation,
+            Feature::Disabled,
+            EndpointAddress::from(1).unwrap(),
+            None,
+            UnknownDestinationPolicy::Notify(MisaddressedFrameListener::leak(Box::new(
+                MockListener {
+                    frames: frames.clone(),
+                },
+            ))),
+            Arc::default(),
+        );
+
+        let header = Header::new(
+            ControlField::new(true, Function::PriUnconfirmedUserData),
+            AnyAddress::from(2),
+            AnyAddress::from(1024),
+        );
+
+        let (info, reply) = layer.process_header(&header);
+        assert!(info.is_none());
+        assert!(reply.is_none());
+        assert_eq!(
+            frames.lock().unwrap().as_slice(),
+            &[MisaddressedFrame {
+                source: 1024,
+                destination: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn tracks_link_activity_per_source_address() {
+        let clock = Arc::new(ManualClock::new());
+        let counters = Arc::new(LinkCounters::new(
+            Clock::manual(clock.clone()),
+            TracingConfig::default(),
+            None,
+        ));
+        let mut layer = Layer::new(
+            LinkErrorMode::Close,
+            EndpointType::Master,
+            Feature::Disabled,
+            EndpointAddress::from(1).unwrap(),
+            None,
+            UnknownDestinationPolicy::default(),
+            counters.clone(),
+        );
+        let outstation = EndpointAddress::from(1024).unwrap();
+
+        assert!(counters.activity(outstation).is_none());
+
+        let header = Header::new(
+            ControlField::new(false, Function::SecLinkStatus),
+            AnyAddress::from(1),
+            AnyAddress::from(1024),
+        );
+        layer.process_header(&header);
+
+        let activity = counters.activity(outstation).unwrap();
+        assert_eq!(activity.since_last_frame, Duration::from_secs(0));
+        assert_eq!(
+            activity.since_last_link_status,
+            Some(Duration::from_secs(0))
+        );
+
+        // other frames don't update the time of the last LINK_STATUS
+        clock.advance(Duration::from_secs(5));
+        let header = Header::new(
+            ControlField::new(false, Function::SecAck),
+            AnyAddress::from(1),
+            AnyAddress::from(1024),
+        );
+        layer.process_header(&header);
+        clock.advance(Duration::from_secs(2));
+
+        let activity = counters.activity(outstation).unwrap();
+        assert_eq!(activity.since_last_frame, Duration::from_secs(2));
+        assert_eq!(
+            activity.since_last_link_status,
+            Some(Duration::from_secs(7))
+        );
+        assert!(activity.is_alive(Duration::from_secs(2)));
+        assert!(!activity.is_alive(Duration::from_secs(1)));
+        assert!(counters
+            .activity(EndpointAddress::from(1025).unwrap())
+            .is_none());
+    }
+}
@@ -1,14 +1,30 @@
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::app::{Listener, Timeout};
 use crate::link::header::AnyAddress;
 
-mod crc;
+#[cfg(feature = "pcap")]
+pub use capture::{CaptureLinkType, FrameCapture};
+pub use error::{FrameError, LinkError, LogicError};
+pub use header::BroadcastConfirmMode;
+pub use statistics::{LinkActivity, LinkStatistics, TransportStatistics};
+
+#[cfg(feature = "pcap")]
+pub(crate) mod capture;
+pub(crate) mod confirmed;
+pub(crate) mod crc;
 pub(crate) mod display;
 pub(crate) mod error;
 pub(crate) mod format;
 mod function;
 pub(crate) mod header;
 pub(crate) mod layer;
+pub(crate) mod pacing;
 pub(crate) mod parser;
 pub(crate) mod reader;
+pub(crate) mod statistics;
 
 pub(crate) mod constant {
     pub(crate) const START1: u8 = 0x05;
@@ -36,10 +52,246 @@ pub enum LinkStatusResult {
     UnexpectedResponse,
 }
 
+/// Link-layer retry and timeout settings
+///
+/// The defaults match a reliable physical layer: unconfirmed user data, no retries, and no
+/// inter-frame timeout. Slow or lossy links like radios usually require retries and an
+/// inter-frame timeout.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkConfig {
+    /// number of times a link status request or a frame of confirmed user data is
+    /// retransmitted when no reply is received within `ack_timeout`
+    pub retries: usize,
+    /// amount of time to wait for the reply to a link status request or confirmed user data
+    ///
+    /// A value of `None` uses the response timeout of the master or the confirm timeout of
+    /// the outstation.
+    pub ack_timeout: Option<Timeout>,
+    /// send user data with CONFIRMED_USER_DATA instead of UNCONFIRMED_USER_DATA
+    ///
+    /// Each frame must be acknowledged by the remote device before the next one is sent. This is
+    /// intended for half-duplex links where the application layer retries are too slow to
+    /// recover lost frames. Broadcast frames are always sent unconfirmed.
+    pub confirmed_user_data: bool,
+    /// maximum amount of time between bytes of a partially received frame
+    ///
+    /// When it elapses, the partial frame is discarded and the parser searches for the
+    /// start of a new frame. A value of `None` waits indefinitely.
+    pub inter_frame_timeout: Option<Duration>,
+    /// limits on how quickly frames are transmitted
+    pub pacing: PacingConfig,
+}
+
+impl LinkConfig {
+    /// ACK timeout, or `default` if none is configured
+    pub(crate) fn ack_timeout_or(&self, default: Duration) -> Duration {
+        self.ack_timeout.map_or(default, Duration::from)
+    }
+}
+
+/// Limits on the rate at which link frames are transmitted
+///
+/// Store-and-forward radio systems may drop frames that are sent back-to-back or faster than
+/// the radio link can carry them. Pacing applies to every frame sent by the transport layer,
+/// including multi-segment solicited and unsolicited responses.
+///
+/// The defaults transmit frames as quickly as the physical layer accepts them.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PacingConfig {
+    /// minimum amount of time between the end of one frame and the start of the next
+    pub min_frame_gap: Duration,
+    /// maximum average number of bytes transmitted per second
+    ///
+    /// After each frame, transmission is delayed until the frame would have been sent at
+    /// this rate. A value of `None` doesn't limit the rate.
+    pub max_bytes_per_second: Option<u32>,
+}
+
+/// Validated maximum number of application bytes carried in each transport segment
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "usize", into = "usize"))]
+pub struct SegmentSize {
+    size: usize,
+}
+
+/// Error type returned for invalid segment sizes
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SegmentSizeError {
+    /// a segment must carry at least one byte
+    Zero,
+    /// provided size exceeds the payload of a link frame
+    TooLarge(usize),
+}
+
+impl SegmentSize {
+    /// maximum and default segment size, i.e. the payload of a link frame minus the transport header
+    pub const MAX: usize = constant::MAX_APP_BYTES_PER_FRAME;
+
+    /// get the underlying value
+    pub fn value(&self) -> usize {
+        self.size
+    }
+
+    /// attempt to construct a `SegmentSize`
+    pub fn new(size: usize) -> Result<Self, SegmentSizeError> {
+        if size == 0 {
+            return Err(SegmentSizeError::Zero);
+        }
+        if size > Self::MAX {
+            return Err(SegmentSizeError::TooLarge(size));
+        }
+        Ok(Self { size })
+    }
+}
+
+impl Default for SegmentSize {
+    fn default() -> Self {
+        Self { size: Self::MAX }
+    }
+}
+
+impl std::convert::TryFrom<usize> for SegmentSize {
+    type Error = SegmentSizeError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<SegmentSize> for usize {
+    fn from(value: SegmentSize) -> Self {
+        value.size
+    }
+}
+
+impl std::fmt::Display for SegmentSizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Zero => f.write_str("segment size must be greater than zero"),
+            Self::TooLarge(size) => write!(
+                f,
+                "provided size {} is greater than the maximum allowed size of {}",
+                size,
+                SegmentSize::MAX
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SegmentSizeError {}
+
+/// Settings for the transport function that splits application fragments into link frames
+///
+/// The defaults use full link frames and accept any number of segments that fit in the
+/// receive buffer. Constrained radios may require smaller segments.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransportConfig {
+    /// maximum number of application bytes sent in each segment
+    pub segment_size: SegmentSize,
+    /// maximum number of segments accepted while reassembling a received fragment
+    ///
+    /// Fragments that require more segments are discarded. A value of `None` means
+    /// the number of segments is only limited by the receive buffer size.
+    pub max_rx_segments: Option<NonZeroUsize>,
+    /// maximum amount of time between segments of a partially reassembled fragment
+    ///
+    /// When it elapses, the partial fragment is discarded and the next fragment must
+    /// begin with a FIR segment. A value of `None` waits indefinitely.
+    pub reassembly_timeout: Option<Duration>,
+}
+
+/// Time at which a frame was received, captured in the link layer as soon as the frame is parsed
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RxTimestamp {
+    /// monotonic time of the channel's [Clock](crate::app::Clock), suitable for measuring latency
+    pub monotonic: crate::app::Instant,
+    /// wall-clock time of the channel's [Clock](crate::app::Clock), suitable for correcting the
+    /// time of events
+    pub system: std::time::SystemTime,
+}
+
+impl RxTimestamp {
+    pub(crate) fn now(clock: &crate::app::Clock) -> Self {
+        Self {
+            monotonic: clock.now(),
+            system: clock.system_time(),
+        }
+    }
+}
+
+/// Addresses of a received frame whose destination doesn't match the local endpoint
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MisaddressedFrame {
+    /// source address of the frame
+    pub source: u16,
+    /// destination address of the frame
+    pub destination: u16,
+}
+
+/// Listener informed of misaddressed frames by [UnknownDestinationPolicy::Notify]
+pub struct MisaddressedFrameListener {
+    listener: Mutex<Box<dyn Listener<MisaddressedFrame>>>,
+}
+
+impl MisaddressedFrameListener {
+    /// create a listener that forwards misaddressed frames to `listener`
+    pub fn new(listener: Box<dyn Listener<MisaddressedFrame>>) -> Self {
+        Self {
+            listener: Mutex::new(listener),
+        }
+    }
+
+    /// create a listener that is never freed so that it can be referred to by configurations
+    pub fn leak(listener: Box<dyn Listener<MisaddressedFrame>>) -> &'static Self {
+        Box::leak(Box::new(Self::new(listener)))
+    }
+
+    pub(crate) fn notify(&self, frame: MisaddressedFrame) {
+        self.listener.lock().unwrap().update(frame)
+    }
+}
+
+impl std::fmt::Debug for MisaddressedFrameListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MisaddressedFrameListener").finish()
+    }
+}
+
+// listeners are compared by identity so that configurations referring to the same listener are equal
+impl PartialEq for MisaddressedFrameListener {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+/// Controls how frames sent to a destination other than the local endpoint are handled.
+/// These frames are always discarded, but visibility into them can help diagnose
+/// wiring or addressing errors on multidrop networks.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UnknownDestinationPolicy {
+    /// Discard the frame silently
+    Discard,
+    /// Discard the frame and log a warning
+    Log,
+    /// Discard the frame and inform the listener of its addresses
+    Notify(&'static MisaddressedFrameListener),
+}
+
+impl Default for UnknownDestinationPolicy {
+    fn default() -> Self {
+        Self::Log
+    }
+}
+
 /// Controls how errors in parsed link-layer frames are handled. This behavior
 /// is configurable for physical layers with built-in error correction like TCP
 /// as the connection might be through a terminal server.
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LinkErrorMode {
     /// Framing errors are discarded. The link-layer parser is reset on any error, and the
     /// parser begins scanning for 0x0564. This is always the behavior for serial ports.
@@ -53,6 +305,8 @@ pub enum LinkErrorMode {
 /// Certain special addresses are not allowed by the standard to be used
 /// as endpoint addresses.
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u16", into = "u16"))]
 pub struct EndpointAddress {
     address: u16,
 }
@@ -108,6 +362,12 @@ impl std::convert::TryFrom<u16> for EndpointAddress {
     }
 }
 
+impl From<EndpointAddress> for u16 {
+    fn from(value: EndpointAddress) -> Self {
+        value.address
+    }
+}
+
 impl std::fmt::Display for EndpointAddress {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", self.address)
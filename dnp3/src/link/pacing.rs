@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use crate::app::Clock;
+use crate::app::Instant;
+use crate::link::PacingConfig;
+
+/// Delays transmitted frames according to a `PacingConfig`
+pub(crate) struct Pacer {
+    config: PacingConfig,
+    clock: Clock,
+    next_tx: Option<Instant>,
+}
+
+impl Pacer {
+    pub(crate) fn new(config: PacingConfig, clock: Clock) -> Self {
+        Self {
+            config,
+            clock,
+            next_tx: None,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.next_tx = None;
+    }
+
+    /// true if frames may be delayed, in which case each frame must be written separately
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.config.min_frame_gap != Duration::from_secs(0)
+            || self.config.max_bytes_per_second.unwrap_or(0) != 0
+    }
+
+    /// wait until the next frame may be transmitted
+    pub(crate) async fn wait(&mut self) {
+        if let Some(next) = self.next_tx {
+            if self.clock.now() < next {
+                self.clock.sleep_until(next).await;
+            }
+        }
+    }
+
+    /// time until which the previously transmitted frames occupy the link, if still in the future
+    pub(crate) fn busy_until(&self) -> Option<Instant> {
+        self.next_tx.filter(|next| self.clock.now() < *next)
+    }
+
+    /// record that a frame of the specified length was just transmitted
+    pub(crate) fn on_transmit(&mut self, length: usize) {
+        let delay = self.delay_after(length);
+        self.next_tx = if delay == Duration::from_secs(0) {
+            None
+        } else {
+            Some(self.clock.now() + delay)
+        };
+    }
+
+    fn delay_after(&self, length: usize) -> Duration {
+        let airtime = match self.config.max_bytes_per_second {
+            Some(0) | None => Duration::from_secs(0),
+            Some(rate) => Duration::from_nanos(length as u64 * 1_000_000_000 / rate as u64),
+        };
+        airtime + self.config.min_frame_gap
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tokio::test::*;
+    use crate::tokio::time;
+
+    #[test]
+    fn delay_combines_rate_and_gap() {
+        let pacer = Pacer::new(
+            PacingConfig {
+                min_frame_gap: Duration::from_millis(50),
+                max_bytes_per_second: Some(1000),
+            },
+            Clock::default(),
+        );
+        assert_eq!(pacer.delay_after(292), Duration::from_millis(342));
+        assert_eq!(
+            Pacer::new(PacingConfig::default(), Clock::default()).delay_after(292),
+            Duration::from_secs(0)
+        );
+    }
+
+    #[test]
+    fn pacing_is_enabled_by_gap_or_rate() {
+        let pacer = |min_frame_gap, max_bytes_per_second| {
+            Pacer::new(
+                PacingConfig {
+                    min_frame_gap,
+                    max_bytes_per_second,
+                },
+                Clock::default(),
+            )
+        };
+        assert!(!pacer(Duration::from_secs(0), None).is_enabled());
+        assert!(!pacer(Duration::from_secs(0), Some(0)).is_enabled());
+        assert!(pacer(Duration::from_millis(1), None).is_enabled());
+        assert!(pacer(Duration::from_secs(0), Some(1000)).is_enabled());
+    }
+
+    #[test]
+    fn waits_until_frame_may_be_transmitted() {
+        let mut pacer = Pacer::new(
+            PacingConfig {
+                min_frame_gap: Duration::from_millis(100),
+                max_bytes_per_second: None,
+            },
+            Clock::default(),
+        );
+
+        // the first frame is never delayed
+        assert_ready!(spawn(pacer.wait()).poll());
+        assert_eq!(pacer.busy_until(), None);
+        pacer.on_transmit(10);
+        assert!(pacer.busy_until().is_some());
+
+        {
+            let mut wait = spawn(pacer.wait());
+            assert_pending!(wait.poll());
+            time::advance(Duration::from_millis(99));
+            assert_pending!(wait.poll());
+            time::advance(Duration::from_millis(1));
+            assert_ready!(wait.poll());
+        }
+        assert_eq!(pacer.busy_until(), None);
+
+        pacer.on_transmit(10);
+        pacer.reset();
+        assert_ready!(spawn(pacer.wait()).poll());
+    }
+}
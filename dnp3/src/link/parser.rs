@@ -1,6 +1,9 @@
+use std::sync::Arc;
+
 use crate::link::constant;
 use crate::link::error::*;
 use crate::link::header::{AnyAddress, ControlField, Header};
+use crate::link::statistics::LinkCounters;
 use crate::link::LinkErrorMode;
 use crate::util::cursor::{ReadCursor, ReadError};
 use crate::util::slice_ext::*;
@@ -13,45 +16,101 @@ enum ParseState {
     ReadBody(Header, usize), // the header + calculated trailer length
 }
 
-pub(crate) struct FramePayload {
-    length: usize,
-    buffer: [u8; super::constant::MAX_FRAME_PAYLOAD_LENGTH],
+/// A view of a frame payload borrowed from the buffer it was read from
+///
+/// Received payloads are not copied out of the read buffer. Instead, the view
+/// retains the CRC-validated body and skips the CRCs when iterating over the blocks.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct FramePayload<'a> {
+    body: &'a [u8],
+    block_size: usize,
+    crc_length: usize,
+    // number of data bytes skipped at the start of the first block
+    skip: usize,
 }
 
-impl FramePayload {
-    pub(crate) fn new() -> Self {
+impl<'a> FramePayload<'a> {
+    pub(crate) fn empty() -> Self {
+        Self::contiguous(&[])
+    }
+
+    /// a payload that isn't interleaved with CRCs
+    pub(crate) fn contiguous(data: &'a [u8]) -> Self {
         Self {
-            length: 0,
-            buffer: [0; super::constant::MAX_FRAME_PAYLOAD_LENGTH],
+            body: data,
+            block_size: constant::MAX_BLOCK_SIZE,
+            crc_length: 0,
+            skip: 0,
         }
     }
 
-    pub(crate) fn clear(&mut self) {
-        self.length = 0;
+    /// a frame body consisting of data blocks that are each followed by a CRC
+    pub(crate) fn from_blocks(body: &'a [u8]) -> Self {
+        Self {
+            body,
+            block_size: constant::MAX_BLOCK_SIZE_WITH_CRC,
+            crc_length: constant::CRC_LENGTH,
+            skip: 0,
+        }
     }
 
-    pub(crate) fn get(&self) -> &[u8] {
-        &self.buffer[0..self.length]
+    /// length of the body including any CRCs
+    pub(crate) fn encoded_len(&self) -> usize {
+        self.body.len()
     }
 
-    pub(crate) fn push(&mut self, data: &[u8]) -> Result<(), LogicError> {
-        let mut buff = self.buffer.as_mut();
-        let dest = buff.np_get_mut(self.length..self.length + data.len())?;
-        dest.copy_from_slice(data);
-        self.length += data.len();
-        Ok(())
+    /// the data portion of each block
+    pub(crate) fn blocks(&self) -> impl Iterator<Item = &'a [u8]> {
+        let crc_length = self.crc_length;
+        let skip = self.skip;
+        self.body
+            .chunks(self.block_size)
+            .enumerate()
+            .map(move |(index, block)| {
+                let data = &block[..block.len().saturating_sub(crc_length)];
+                if index == 0 {
+                    data.get(skip..).unwrap_or(&[])
+                } else {
+                    data
+                }
+            })
     }
-}
 
-impl Default for FramePayload {
-    fn default() -> Self {
-        Self::new()
+    pub(crate) fn bytes(&self) -> impl Iterator<Item = u8> + 'a {
+        self.blocks().flat_map(|block| block.iter().copied())
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.blocks().map(|block| block.len()).sum()
+    }
+
+    /// copy the data bytes into a contiguous buffer, returning the copied portion
+    pub(crate) fn copy_to<'b>(&self, buffer: &'b mut [u8]) -> &'b [u8] {
+        let mut length = 0;
+        for (dest, src) in buffer.iter_mut().zip(self.bytes()) {
+            *dest = src;
+            length += 1;
+        }
+        &buffer[..length]
+    }
+
+    /// split off the first byte of the payload, e.g. the transport header
+    pub(crate) fn split_first(&self) -> Option<(u8, Self)> {
+        let first = *self.blocks().next()?.first()?;
+        Some((
+            first,
+            Self {
+                skip: self.skip + 1,
+                ..*self
+            },
+        ))
     }
 }
 
 pub(crate) struct Parser {
     mode: LinkErrorMode,
     state: ParseState,
+    counters: Arc<LinkCounters>,
 }
 
 impl From<ReadError> for ParseError {
@@ -73,33 +132,55 @@ impl From<LogicError> for ParseError {
 }
 
 impl Parser {
-    pub(crate) fn new(mode: LinkErrorMode) -> Parser {
+    pub(crate) fn new(mode: LinkErrorMode, counters: Arc<LinkCounters>) -> Parser {
         Parser {
             mode,
             state: ParseState::FindSync1,
+            counters,
         }
     }
 
+    /// true if the parser has consumed part of a frame
+    pub(crate) fn in_frame(&self) -> bool {
+        !matches!(self.state, ParseState::FindSync1)
+    }
+
     pub(crate) fn reset(&mut self) {
         self.state = ParseState::FindSync1;
     }
 
-    pub(crate) fn parse(
+    pub(crate) fn parse<'a>(
         &mut self,
-        cursor: &mut ReadCursor,
-        payload: &mut FramePayload,
+        cursor: &mut ReadCursor<'a>,
+        payload: &mut FramePayload<'a>,
     ) -> Result<Option<Header>, ParseError> {
         loop {
             if self.mode == LinkErrorMode::Close {
-                return self.parse_impl(cursor, payload);
+                let res = self.parse_impl(cursor, payload);
+                if let Err(err) = res {
+                    self.count_error(err);
+                }
+                return res;
+            }
+
+            // between frames, skip anything that can't be the start of a frame
+            if !self.in_frame() {
+                self.counters.on_garbage(Self::hunt(cursor));
             }
 
+            let started_in_frame = self.in_frame();
             let res = cursor.transaction(|cur| self.parse_impl(cur, payload));
 
             match res {
                 Ok(x) => return Ok(x),
-                Err(_) => {
-                    let _ = cursor.read_u8(); // advance one byte
+                Err(err) => {
+                    self.count_error(err);
+                    self.counters.on_resync();
+                    // if the bad frame started in this buffer, advance past its start. Otherwise,
+                    // it started in a previous read and the hunt resumes at the current position.
+                    if !started_in_frame && cursor.read_u8().is_ok() {
+                        self.counters.on_garbage(1);
+                    }
                     self.reset();
                     // goto next iteration
                 }
@@ -107,10 +188,38 @@ impl Parser {
         }
     }
 
-    fn parse_impl(
+    /// skip bytes until the next 0x0564 sync pattern, returning the number of bytes skipped
+    ///
+    /// a trailing 0x05 is not skipped since it may be completed by the next read
+    fn hunt(cursor: &mut ReadCursor) -> usize {
+        let data = cursor.peek_all();
+        let count = match data
+            .windows(2)
+            .position(|x| x == [constant::START1, constant::START2])
+        {
+            Some(pos) => pos,
+            None => match data.last() {
+                Some(&constant::START1) => data.len() - 1,
+                _ => data.len(),
+            },
+        };
+        let _ = cursor.read_bytes(count);
+        count
+    }
+
+    fn count_error(&self, err: ParseError) {
+        match err {
+            ParseError::BadFrame(FrameError::BadHeaderCrc)
+            | ParseError::BadFrame(FrameError::BadBodyCrc) => self.counters.on_bad_crc(),
+            ParseError::BadFrame(FrameError::BadLength(_)) => self.counters.on_bad_length(),
+            _ => {}
+        }
+    }
+
+    fn parse_impl<'a>(
         &mut self,
-        cursor: &mut ReadCursor,
-        payload: &mut FramePayload,
+        cursor: &mut ReadCursor<'a>,
+        payload: &mut FramePayload<'a>,
     ) -> Result<Option<Header>, ParseError> {
         loop {
             let start = cursor.remaining();
@@ -208,18 +317,16 @@ impl Parser {
         Ok(())
     }
 
-    fn parse_body(
+    fn parse_body<'a>(
         &mut self,
         trailer_length: usize,
-        cursor: &mut ReadCursor,
-        payload: &mut FramePayload,
+        cursor: &mut ReadCursor<'a>,
+        payload: &mut FramePayload<'a>,
     ) -> Result<Option<()>, ParseError> {
         if cursor.remaining() < trailer_length {
             return Ok(None);
         }
 
-        payload.clear();
-
         let body = cursor.read_bytes(trailer_length)?;
 
         for block in body.chunks(18) {
@@ -237,11 +344,9 @@ impl Parser {
             if crc_value != calc_crc {
                 return Err(FrameError::BadBodyCrc.into());
             }
-
-            // copy the data and advance the position
-            payload.push(data)?;
         }
 
+        *payload = FramePayload::from_blocks(body);
         self.state = ParseState::FindSync1;
         Ok(Some(()))
     }
@@ -254,18 +359,18 @@ mod test {
 
     fn test_frame_parsing(parser: &mut Parser, frame: &TestFrame) {
         let mut cursor = ReadCursor::new(frame.bytes);
-        let mut payload = FramePayload::new();
+        let mut payload = FramePayload::empty();
         let header: Header = parser.parse(&mut cursor, &mut payload).unwrap().unwrap();
         assert_eq!(cursor.remaining(), 0);
         assert_eq!(header, frame.header);
-        assert_eq!(payload.get(), frame.payload)
+        assert_eq!(payload.bytes().collect::<Vec<u8>>(), frame.payload)
     }
 
     #[test]
     fn catches_bad_start1() {
-        let mut parser = Parser::new(LinkErrorMode::Close);
+        let mut parser = Parser::new(LinkErrorMode::Close, Arc::default());
         let mut cursor = ReadCursor::new(&[0x06]);
-        let mut payload = FramePayload::new();
+        let mut payload = FramePayload::empty();
 
         assert_eq!(
             parser.parse(&mut cursor, &mut payload),
@@ -277,9 +382,9 @@ mod test {
 
     #[test]
     fn catches_bad_start2() {
-        let mut parser = Parser::new(LinkErrorMode::Close);
+        let mut parser = Parser::new(LinkErrorMode::Close, Arc::default());
         let mut cursor = ReadCursor::new(&[0x05, 0x65]);
-        let mut payload = FramePayload::new();
+        let mut payload = FramePayload::empty();
 
         assert_eq!(
             parser.parse(&mut cursor, &mut payload),
@@ -291,10 +396,10 @@ mod test {
 
     #[test]
     fn catches_bad_length() {
-        let mut parser = Parser::new(LinkErrorMode::Close);
+        let mut parser = Parser::new(LinkErrorMode::Close, Arc::default());
         let mut cursor =
             ReadCursor::new(&[0x05, 0x64, 0x04, 0xC0, 0x01, 0x00, 0x00, 0x04, 0xE9, 0x21]);
-        let mut payload = FramePayload::new();
+        let mut payload = FramePayload::empty();
 
         assert_eq!(
             parser.parse(&mut cursor, &mut payload),
@@ -305,10 +410,10 @@ mod test {
 
     #[test]
     fn header_parse_catches_bad_crc() {
-        let mut parser = Parser::new(LinkErrorMode::Close);
+        let mut parser = Parser::new(LinkErrorMode::Close, Arc::default());
         let mut cursor =
             ReadCursor::new(&[0x05, 0x64, 0x05, 0xC0, 0x01, 0x00, 0x00, 0x04, 0xE9, 0x20]);
-        let mut payload = FramePayload::new();
+        let mut payload = FramePayload::empty();
 
         assert_eq!(
             parser.parse(&mut cursor, &mut payload),
@@ -326,9 +431,9 @@ mod test {
             0x06, 0x9A, 0xFF,
         ];
 
-        let mut parser = Parser::new(LinkErrorMode::Close);
+        let mut parser = Parser::new(LinkErrorMode::Close, Arc::default());
         let mut cursor = ReadCursor::new(&data);
-        let mut payload = FramePayload::new();
+        let mut payload = FramePayload::empty();
 
         assert_eq!(
             parser.parse(&mut cursor, &mut payload),
@@ -339,7 +444,7 @@ mod test {
 
     #[test]
     fn can_parse_multiple_different_frames_sequentially() {
-        let mut parser = Parser::new(LinkErrorMode::Close);
+        let mut parser = Parser::new(LinkErrorMode::Close, Arc::default());
         test_frame_parsing(&mut parser, &RESET_LINK);
         test_frame_parsing(&mut parser, &ACK);
         test_frame_parsing(&mut parser, &CONFIRM_USER_DATA);
@@ -347,14 +452,14 @@ mod test {
 
     #[test]
     fn can_consume_leading_garbage_in_discard_mode() {
-        let mut parser = Parser::new(LinkErrorMode::Discard);
+        let mut parser = Parser::new(LinkErrorMode::Discard, Arc::default());
         // -- ------------ leading garbage ------------- valid frame -----------------------------------------------------
         let data = [
             0x06, 0x05, 0x07, 0x05, 0x64, 0x05, 0x05, 0x64, 0x05, 0xC0, 0x01, 0x00, 0x00, 0x04,
             0xE9, 0x21,
         ];
         let mut cursor = ReadCursor::new(&data);
-        let mut payload = FramePayload::new();
+        let mut payload = FramePayload::empty();
 
         // consume leading garbage until we get to the valid frame
         assert_eq!(
@@ -362,4 +467,86 @@ mod test {
             Ok(Some(RESET_LINK.header)),
         );
     }
+
+    #[test]
+    fn counts_errors_and_resyncs_in_discard_mode() {
+        let counters = Arc::new(LinkCounters::default());
+        let mut parser = Parser::new(LinkErrorMode::Discard, counters.clone());
+        // -- frame with a bad header CRC followed by a valid frame
+        let data = [
+            0x05, 0x64, 0x05, 0xC0, 0x01, 0x00, 0x00, 0x04, 0xE9, 0x20, 0x05, 0x64, 0x05, 0xC0,
+            0x01, 0x00, 0x00, 0x04, 0xE9, 0x21,
+        ];
+        let mut cursor = ReadCursor::new(&data);
+        let mut payload = FramePayload::empty();
+
+        assert_eq!(
+            parser.parse(&mut cursor, &mut payload),
+            Ok(Some(RESET_LINK.header)),
+        );
+
+        let stats = counters.snapshot();
+        assert_eq!(stats.bad_crc, 1);
+        assert_eq!(stats.resyncs, 1);
+        // all of the bad frame
+        assert_eq!(stats.garbage_bytes, 10);
+    }
+
+    #[test]
+    fn hunts_for_sync_across_reads_in_discard_mode() {
+        let counters = Arc::new(LinkCounters::default());
+        let mut parser = Parser::new(LinkErrorMode::Discard, counters.clone());
+        let mut payload = FramePayload::empty();
+
+        // noise followed by the first byte of a frame
+        let mut cursor = ReadCursor::new(&[0xFF, 0x64, 0x00, 0x05]);
+        assert_eq!(parser.parse(&mut cursor, &mut payload), Ok(None));
+
+        // remainder of the frame
+        let mut cursor = ReadCursor::new(&RESET_LINK.bytes[1..]);
+        assert_eq!(
+            parser.parse(&mut cursor, &mut payload),
+            Ok(Some(RESET_LINK.header)),
+        );
+
+        let stats = counters.snapshot();
+        assert_eq!(stats.garbage_bytes, 3);
+        assert_eq!(stats.resyncs, 0);
+    }
+
+    #[test]
+    fn does_not_lose_frame_after_false_start_at_end_of_read() {
+        let counters = Arc::new(LinkCounters::default());
+        let mut parser = Parser::new(LinkErrorMode::Discard, counters.clone());
+        let mut payload = FramePayload::empty();
+
+        // noise ending in 0x05
+        let mut cursor = ReadCursor::new(&[0xFF, 0x05]);
+        assert_eq!(parser.parse(&mut cursor, &mut payload), Ok(None));
+
+        // complete frame
+        let mut cursor = ReadCursor::new(RESET_LINK.bytes);
+        assert_eq!(
+            parser.parse(&mut cursor, &mut payload),
+            Ok(Some(RESET_LINK.header)),
+        );
+
+        assert_eq!(counters.snapshot().resyncs, 1);
+    }
+
+    #[test]
+    fn payload_view_skips_crcs_between_blocks() {
+        let mut body: Vec<u8> = (0..16).collect();
+        body.extend_from_slice(&[0xAA, 0xAA, 16, 17, 0xBB, 0xBB]);
+        let payload = FramePayload::from_blocks(&body);
+
+        assert_eq!(payload.len(), 18);
+        let (first, rest) = payload.split_first().unwrap();
+        assert_eq!(first, 0);
+        assert_eq!(rest.len(), 17);
+        assert_eq!(
+            rest.bytes().collect::<Vec<u8>>(),
+            (1..18).collect::<Vec<u8>>()
+        );
+    }
 }
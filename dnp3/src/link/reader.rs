@@ -1,25 +1,39 @@
 use std::io::ErrorKind;
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::decode::DecodeLevel;
+use crate::decode::{DecodeDirection, DecodeLevel, WireLayer};
 use crate::link::display::LinkDisplay;
 use crate::link::error::LinkError;
 use crate::link::header::Header;
 use crate::link::parser::{FramePayload, Parser};
-use crate::link::LinkErrorMode;
+use crate::link::statistics::LinkCounters;
+use crate::link::{LinkErrorMode, RxTimestamp};
 use crate::util::cursor::ReadCursor;
 use crate::util::phys::PhysLayer;
 
 pub(crate) struct Reader {
     parser: Parser,
+    counters: Arc<LinkCounters>,
+    inter_frame_timeout: Option<Duration>,
+    // encoded length of the payload of the last frame, which ends at `begin`
+    payload_length: usize,
     begin: usize,
     end: usize,
     buffer: [u8; super::constant::MAX_LINK_FRAME_LENGTH],
 }
 
 impl Reader {
-    pub(crate) fn new(mode: LinkErrorMode) -> Self {
+    pub(crate) fn new(
+        mode: LinkErrorMode,
+        inter_frame_timeout: Option<Duration>,
+        counters: Arc<LinkCounters>,
+    ) -> Self {
         Self {
-            parser: Parser::new(mode),
+            parser: Parser::new(mode, counters.clone()),
+            counters,
+            inter_frame_timeout,
+            payload_length: 0,
             begin: 0,
             end: 0,
             buffer: [0; super::constant::MAX_LINK_FRAME_LENGTH],
@@ -27,21 +41,30 @@ impl Reader {
     }
 
     pub(crate) fn reset(&mut self) {
+        self.payload_length = 0;
         self.begin = 0;
         self.end = 0;
         self.parser.reset();
     }
 
+    /// payload of the last frame returned by `read`, borrowed from the read buffer
+    pub(crate) fn payload(&self) -> FramePayload {
+        let start = self.begin.saturating_sub(self.payload_length);
+        FramePayload::from_blocks(self.buffer.get(start..self.begin).unwrap_or(&[]))
+    }
+
     /**
     Returns a future that keeps reading until a frame is received or an error is returned
     This future can be dropped without losing any state.
+
+    The payload of the frame is available from `payload()` until the next call to `read`.
     */
     pub(crate) async fn read(
         &mut self,
         io: &mut PhysLayer,
-        payload: &mut FramePayload,
         level: DecodeLevel,
-    ) -> Result<Header, LinkError> {
+    ) -> Result<(Header, RxTimestamp), LinkError> {
+        self.payload_length = 0;
         loop {
             // if all bytes are consumed, ensure these are set back to zero
             if self.begin == self.end {
@@ -52,18 +75,36 @@ impl Reader {
             // the readable portion of the buffer
             let mut cursor = ReadCursor::new(&self.buffer[self.begin..self.end]);
             let start = cursor.remaining();
-            let result = self.parser.parse(&mut cursor, payload)?;
+            let mut payload = FramePayload::empty();
+            let result = self.parser.parse(&mut cursor, &mut payload)?;
             self.begin += start - cursor.remaining();
             match result {
                 // complete frame
                 Some(header) => {
+                    let received = RxTimestamp::now(&self.counters.clock());
+                    self.counters.on_frame_rx();
+                    self.counters
+                        .decode()
+                        .emit_event(header.to_event(DecodeDirection::Rx, payload.len()));
+                    if self.counters.decode().tap_enabled() {
+                        crate::link::format::with_received_frame(header, payload, |frame| {
+                            self.counters.decode().emit_wire(
+                                WireLayer::Link,
+                                DecodeDirection::Rx,
+                                frame,
+                            )
+                        });
+                    }
+                    #[cfg(feature = "pcap")]
+                    self.counters.capture().on_rx(header, payload);
                     if level.link.enabled() {
-                        tracing::info!(
+                        self.counters.decode().emit(format_args!(
                             "LINK RX - {}",
-                            LinkDisplay::new(header, payload.get(), level.link)
-                        );
+                            LinkDisplay::new(header, payload, level.link)
+                        ));
                     }
-                    return Ok(header);
+                    self.payload_length = payload.encoded_len();
+                    return Ok((header, received));
                 }
                 // parser can't make progress without more bytes
                 None => {
@@ -75,9 +116,15 @@ impl Reader {
                     }
 
                     // now we can read more data
-                    let count = io
-                        .read(&mut self.buffer[self.end..], level.physical)
-                        .await?;
+                    let count = match self.read_more(io, level).await? {
+                        Some(count) => count,
+                        None => {
+                            tracing::warn!("discarding partial frame after inter-frame timeout");
+                            self.counters.on_resync();
+                            self.reset();
+                            continue;
+                        }
+                    };
                     if count == 0 {
                         return Err(LinkError::Stdio(ErrorKind::UnexpectedEof));
                     }
@@ -87,4 +134,75 @@ impl Reader {
             }
         }
     }
+
+    /// read more bytes, returning `None` if the inter-frame timeout elapses in the middle of a frame
+    async fn read_more(
+        &mut self,
+        io: &mut PhysLayer,
+        level: DecodeLevel,
+    ) -> Result<Option<usize>, LinkError> {
+        // bytes that the writer read while waiting for an ACK were already decoded
+        let count = self.counters.take_unread(&mut self.buffer[self.end..]);
+        if count > 0 {
+            return Ok(Some(count));
+        }
+
+        let count = match self.inter_frame_timeout {
+            Some(timeout) if self.parser.in_frame() => {
+                let clock = self.counters.clock();
+                let deadline = clock.now() + timeout;
+                crate::tokio::select! {
+                    count = io.read(&mut self.buffer[self.end..]) => {
+                        count?
+                    }
+                    _ = clock.sleep_until(deadline) => {
+                        return Ok(None);
+                    }
+                }
+            }
+            _ => io.read(&mut self.buffer[self.end..]).await?,
+        };
+
+        if let Some(data) = self.buffer.get(self.end..self.end + count) {
+            self.counters
+                .decode()
+                .emit_phys(DecodeDirection::Rx, level.physical, data);
+        }
+        Ok(Some(count))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test_data::*;
+    use super::*;
+    use crate::tokio::test::*;
+
+    #[test]
+    fn discards_partial_frame_after_inter_frame_timeout() {
+        let (io, mut io_handle) = io::mock();
+        let mut io = PhysLayer::Mock(io);
+        let counters = Arc::new(LinkCounters::default());
+        let mut reader = Reader::new(
+            LinkErrorMode::Close,
+            Some(Duration::from_secs(1)),
+            counters.clone(),
+        );
+        let mut task = spawn(async { reader.read(&mut io, DecodeLevel::nothing()).await });
+
+        io_handle.read(&RESET_LINK.bytes[0..5]);
+        assert_pending!(task.poll());
+
+        crate::tokio::time::advance(Duration::from_millis(1001));
+        assert_pending!(task.poll());
+
+        io_handle.read(RESET_LINK.bytes);
+        let (header, _) = assert_ready!(task.poll()).unwrap();
+        assert_eq!(header, RESET_LINK.header);
+        drop(task);
+
+        let stats = counters.snapshot();
+        assert_eq!(stats.resyncs, 1);
+        assert_eq!(stats.frames_rx, 1);
+    }
 }
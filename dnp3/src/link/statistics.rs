@@ -0,0 +1,319 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::app::{AnomalyLog, BufferPool, Clock, Instant};
+use crate::decode::{DecodeOutput, TracingConfig};
+#[cfg(feature = "pcap")]
+use crate::link::capture::CaptureSlot;
+use crate::link::EndpointAddress;
+
+/// Snapshot of the link-layer counters of a channel
+///
+/// Counters start at zero when the channel is created and are never reset
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct LinkStatistics {
+    /// number of valid frames received
+    pub frames_rx: u64,
+    /// number of frames transmitted
+    pub frames_tx: u64,
+    /// number of frames with a bad CRC in the header or the body
+    pub bad_crc: u64,
+    /// number of frames with an invalid length field
+    pub bad_length: u64,
+    /// number of frames discarded because of their source or destination address
+    pub bad_address: u64,
+    /// number of times the parser discarded a partial frame and searched for the start of a new one
+    pub resyncs: u64,
+    /// number of bytes discarded while searching for the start of a frame
+    pub garbage_bytes: u64,
+}
+
+/// Snapshot of the transport-layer counters of a channel
+///
+/// Counters start at zero when the channel is created and are never reset
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TransportStatistics {
+    /// number of segments received
+    pub segments_rx: u64,
+    /// number of partial fragments discarded because a segment arrived out of sequence
+    pub out_of_sequence: u64,
+    /// number of partial fragments discarded because a FIR segment arrived while assembling
+    pub fir_resets: u64,
+    /// number of fragments fully reassembled
+    pub fragments_rx: u64,
+}
+
+/// Most recent link-layer activity from a remote device
+///
+/// The ages are measured with the [Clock] of the channel when the snapshot is taken
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LinkActivity {
+    /// time elapsed since the last valid frame was received from the remote device
+    pub since_last_frame: Duration,
+    /// time elapsed since the last LINK_STATUS response was received from the remote device
+    pub since_last_link_status: Option<Duration>,
+}
+
+impl LinkActivity {
+    /// true if a frame was received from the remote device within `max_age`
+    pub fn is_alive(&self, max_age: Duration) -> bool {
+        self.since_last_frame <= max_age
+    }
+}
+
+/// times of the most recent activity from a remote device, stored as the number of nanoseconds
+/// since the origin of the counters plus one, or zero if there was no such activity
+#[derive(Debug, Default)]
+pub(crate) struct ActivityCell {
+    last_frame: AtomicU64,
+    last_link_status: AtomicU64,
+}
+
+/// Records the activity of remote devices in the counters of a link layer
+///
+/// The counters are only locked the first time a frame is received from a remote device.
+/// After that, the activity is recorded with atomic stores.
+#[derive(Default)]
+pub(crate) struct ActivityRecorder {
+    cells: BTreeMap<EndpointAddress, Arc<ActivityCell>>,
+}
+
+impl ActivityRecorder {
+    /// record a valid frame from a remote address
+    pub(crate) fn record(
+        &mut self,
+        counters: &LinkCounters,
+        source: EndpointAddress,
+        link_status: bool,
+    ) {
+        let now = counters.ticks(counters.clock.now());
+        let cell = self
+            .cells
+            .entry(source)
+            .or_insert_with(|| counters.activity_cell(source));
+        cell.last_frame.store(now, Ordering::Relaxed);
+        if link_status {
+            cell.last_link_status.store(now, Ordering::Relaxed);
+        }
+    }
+}
+
+/// counters, activity, and outputs shared between the link and transport layers and the channel
+/// handle
+#[derive(Debug)]
+pub(crate) struct LinkCounters {
+    frames_rx: AtomicU64,
+    frames_tx: AtomicU64,
+    bad_crc: AtomicU64,
+    bad_length: AtomicU64,
+    bad_address: AtomicU64,
+    resyncs: AtomicU64,
+    garbage_bytes: AtomicU64,
+    segments_rx: AtomicU64,
+    out_of_sequence: AtomicU64,
+    fir_resets: AtomicU64,
+    fragments_rx: AtomicU64,
+    // activity is measured from the time the counters are created
+    origin: Instant,
+    activity: Mutex<BTreeMap<EndpointAddress, Arc<ActivityCell>>>,
+    // bytes read by the writer while waiting for an ACK that belong to the reader
+    unread: Mutex<Vec<u8>>,
+    has_unread: AtomicBool,
+    decode: DecodeOutput,
+    anomalies: AnomalyLog,
+    #[cfg(feature = "pcap")]
+    capture: CaptureSlot,
+    clock: Clock,
+    buffer_pool: Option<Arc<BufferPool>>,
+}
+
+impl LinkCounters {
+    pub(crate) fn new(
+        clock: Clock,
+        tracing: TracingConfig,
+        buffer_pool: Option<Arc<BufferPool>>,
+    ) -> Self {
+        Self {
+            frames_rx: AtomicU64::default(),
+            frames_tx: AtomicU64::default(),
+            bad_crc: AtomicU64::default(),
+            bad_length: AtomicU64::default(),
+            bad_address: AtomicU64::default(),
+            resyncs: AtomicU64::default(),
+            garbage_bytes: AtomicU64::default(),
+            segments_rx: AtomicU64::default(),
+            out_of_sequence: AtomicU64::default(),
+            fir_resets: AtomicU64::default(),
+            fragments_rx: AtomicU64::default(),
+            origin: clock.now(),
+            activity: Mutex::default(),
+            unread: Mutex::default(),
+            has_unread: AtomicBool::default(),
+            decode: DecodeOutput::new(tracing.redaction, clock.clone()),
+            anomalies: AnomalyLog::new(clock.clone()),
+            #[cfg(feature = "pcap")]
+            capture: CaptureSlot::new(clock.clone()),
+            clock,
+            buffer_pool,
+        }
+    }
+
+    pub(crate) fn on_frame_rx(&self) {
+        Self::increment(&self.frames_rx);
+    }
+
+    pub(crate) fn on_frame_tx(&self) {
+        Self::increment(&self.frames_tx);
+    }
+
+    pub(crate) fn on_bad_crc(&self) {
+        Self::increment(&self.bad_crc);
+    }
+
+    pub(crate) fn on_bad_length(&self) {
+        Self::increment(&self.bad_length);
+    }
+
+    pub(crate) fn on_bad_address(&self) {
+        Self::increment(&self.bad_address);
+    }
+
+    pub(crate) fn on_resync(&self) {
+        Self::increment(&self.resyncs);
+    }
+
+    pub(crate) fn on_garbage(&self, count: usize) {
+        if count > 0 {
+            self.garbage_bytes
+                .fetch_add(count as u64, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn on_segment_rx(&self) {
+        Self::increment(&self.segments_rx);
+    }
+
+    pub(crate) fn on_out_of_sequence(&self) {
+        Self::increment(&self.out_of_sequence);
+    }
+
+    pub(crate) fn on_fir_reset(&self) {
+        Self::increment(&self.fir_resets);
+    }
+
+    pub(crate) fn on_fragment_rx(&self) {
+        Self::increment(&self.fragments_rx);
+    }
+
+    fn activity_cell(&self, source: EndpointAddress) -> Arc<ActivityCell> {
+        self.activity
+            .lock()
+            .unwrap()
+            .entry(source)
+            .or_default()
+            .clone()
+    }
+
+    pub(crate) fn activity(&self, source: EndpointAddress) -> Option<LinkActivity> {
+        let cell = self.activity.lock().unwrap().get(&source)?.clone();
+        let now = self.ticks(self.clock.now());
+        let age = |ticks: u64| match ticks {
+            0 => None,
+            ticks => Some(Duration::from_nanos(now.saturating_sub(ticks))),
+        };
+        Some(LinkActivity {
+            since_last_frame: age(cell.last_frame.load(Ordering::Relaxed))?,
+            since_last_link_status: age(cell.last_link_status.load(Ordering::Relaxed)),
+        })
+    }
+
+    fn ticks(&self, time: Instant) -> u64 {
+        time.saturating_duration_since(self.origin).as_nanos() as u64 + 1
+    }
+
+    /// hand bytes read outside of the link reader back to it
+    pub(crate) fn push_unread(&self, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        self.unread.lock().unwrap().extend_from_slice(data);
+        self.has_unread.store(true, Ordering::Release);
+    }
+
+    /// discard any bytes handed back to the reader and hand it the bytes of a new connection
+    #[cfg(feature = "tcp")]
+    pub(crate) fn replace_unread(&self, data: &[u8]) {
+        let mut unread = self.unread.lock().unwrap();
+        unread.clear();
+        unread.extend_from_slice(data);
+        self.has_unread.store(!unread.is_empty(), Ordering::Release);
+    }
+
+    /// move as many of the bytes handed back to the reader as fit into `dest`
+    pub(crate) fn take_unread(&self, dest: &mut [u8]) -> usize {
+        if !self.has_unread.load(Ordering::Acquire) {
+            return 0;
+        }
+        let mut unread = self.unread.lock().unwrap();
+        let count = unread.len().min(dest.len());
+        dest[0..count].copy_from_slice(&unread[0..count]);
+        unread.drain(0..count);
+        self.has_unread.store(!unread.is_empty(), Ordering::Release);
+        count
+    }
+
+    pub(crate) fn decode(&self) -> &DecodeOutput {
+        &self.decode
+    }
+
+    pub(crate) fn anomalies(&self) -> &AnomalyLog {
+        &self.anomalies
+    }
+
+    #[cfg(feature = "pcap")]
+    pub(crate) fn capture(&self) -> &CaptureSlot {
+        &self.capture
+    }
+
+    pub(crate) fn clock(&self) -> Clock {
+        self.clock.clone()
+    }
+
+    /// pool from which the sessions and the transport layer of the channel obtain their buffers
+    pub(crate) fn buffer_pool(&self) -> Option<Arc<BufferPool>> {
+        self.buffer_pool.clone()
+    }
+
+    pub(crate) fn snapshot(&self) -> LinkStatistics {
+        LinkStatistics {
+            frames_rx: self.frames_rx.load(Ordering::Relaxed),
+            frames_tx: self.frames_tx.load(Ordering::Relaxed),
+            bad_crc: self.bad_crc.load(Ordering::Relaxed),
+            bad_length: self.bad_length.load(Ordering::Relaxed),
+            bad_address: self.bad_address.load(Ordering::Relaxed),
+            resyncs: self.resyncs.load(Ordering::Relaxed),
+            garbage_bytes: self.garbage_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn transport_snapshot(&self) -> TransportStatistics {
+        TransportStatistics {
+            segments_rx: self.segments_rx.load(Ordering::Relaxed),
+            out_of_sequence: self.out_of_sequence.load(Ordering::Relaxed),
+            fir_resets: self.fir_resets.load(Ordering::Relaxed),
+            fragments_rx: self.fragments_rx.load(Ordering::Relaxed),
+        }
+    }
+
+    fn increment(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Default for LinkCounters {
+    fn default() -> Self {
+        Self::new(Clock::default(), TracingConfig::default(), None)
+    }
+}
@@ -3,29 +3,68 @@ use std::time::Duration;
 
 use xxhash_rust::xxh64::xxh64;
 
-use crate::app::parse::parser::{HeaderCollection, Response};
+use crate::app::parse::parser::{HeaderCollection, Response, UnknownObjects};
+use crate::app::variations::Variation;
+use crate::app::AnomalyLog;
+use crate::app::Clock;
+use crate::app::ConfigError;
+use crate::app::Instant;
+use crate::app::ObjectParseError;
+use crate::app::ParseMode;
 use crate::app::Sequence;
+use crate::app::Timeout;
 use crate::app::Timestamp;
+use crate::app::VendorObjects;
 use crate::app::{ExponentialBackOff, RetryStrategy};
 use crate::app::{Iin, ResponseHeader};
-use crate::link::EndpointAddress;
+use crate::link::{EndpointAddress, RxTimestamp};
+use crate::master::comm_lost::PointCache;
 use crate::master::error::{AssociationError, TaskError, TimeSyncError};
 use crate::master::extract::extract_measurements;
-use crate::master::handle::{AssociationHandler, Promise};
+use crate::master::handle::{AssociationHandler, Promise, TaskCompletion};
 use crate::master::messages::AssociationMsgType;
 use crate::master::poll::{PollHandle, PollMap, PollMsg};
-use crate::master::request::{Classes, EventClasses, TimeSyncProcedure};
-use crate::master::session::RunError;
+use crate::master::request::{
+    ClassZeroRequest, Classes, EventClasses, TimeSyncProcedure, VariationFallback,
+};
+use crate::master::session::{RunError, StateChange};
 use crate::master::tasks::auto::AutoTask;
 use crate::master::tasks::time::TimeSyncTask;
 use crate::master::tasks::NonReadTask::TimeSync;
-use crate::master::tasks::{AssociationTask, ReadTask, Task};
+use crate::master::tasks::{AssociationTask, ReadTask, Task, UserTask};
 use crate::master::{ReadHandler, ReadType};
-use crate::tokio::time::Instant;
 use crate::util::Smallest;
 
+/// How an association handles a user request when its queue already holds
+/// [AssociationConfig::max_queued_user_requests]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QueueOverflowPolicy {
+    /// fail the new request with [TaskError::TooManyRequests]
+    RejectNew,
+    /// remove the oldest queued READ, failing it with [TaskError::Dropped], and queue the new
+    /// request in its place
+    ///
+    /// The new request is rejected if no READ is queued.
+    DropOldestRead,
+    /// complete a new READ along with an identical READ that is already queued
+    ///
+    /// Other requests are rejected. The READ is sent once, under the correlation ID of the queued
+    /// request, and the ID of the new request is logged when it is merged. Each request is
+    /// reported with its own ID to
+    /// [AssociationHandler::task_completed](crate::master::AssociationHandler::task_completed).
+    CoalesceDuplicateReads,
+}
+
+impl Default for QueueOverflowPolicy {
+    fn default() -> Self {
+        Self::RejectNew
+    }
+}
+
 /// Configuration for a master association
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AssociationConfig {
     /// The event classes to disable on startup
     pub disable_unsol_classes: EventClasses,
@@ -35,6 +74,12 @@ pub struct AssociationConfig {
     ///
     /// For conformance, this should be Class 1230.
     pub startup_integrity_classes: Classes,
+    /// Headers that request the class 0 data of the startup integrity poll
+    ///
+    /// Defaults to [ClassZeroRequest::Class0]. Headers are not part of a serialized
+    /// configuration.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub class_zero_request: ClassZeroRequest,
     /// automatic time synchronization based on NEED_TIME IIN bit
     pub auto_time_sync: Option<TimeSyncProcedure>,
     /// automatic tasks retry strategy
@@ -51,6 +96,54 @@ pub struct AssociationConfig {
     /// The maximum number of user requests (e.g. commands, adhoc reads, etc) that will be queued
     /// before back-pressure is applied by failing requests with TaskError::TooManyRequests
     pub max_queued_user_requests: usize,
+    /// How a user request is handled when the queue is full
+    ///
+    /// Defaults to [QueueOverflowPolicy::RejectNew].
+    pub queue_overflow_policy: QueueOverflowPolicy,
+    /// Scheduling priority relative to the other associations on the channel
+    ///
+    /// When several associations have a task ready, the one with the highest priority runs first.
+    /// Associations with the same priority take turns.
+    pub priority: u8,
+    /// Maximum time a request to this association may remain outstanding
+    ///
+    /// Overrides the response timeout of the channel when set. A shorter value for an unreliable
+    /// outstation limits how long it can hold a channel shared with other associations.
+    pub response_timeout: Option<Timeout>,
+    /// Capture object headers with an unknown group and variation in responses
+    ///
+    /// By default, a response containing an unknown header is discarded. When enabled, unknown
+    /// headers whose size can be determined from their qualifier are passed to
+    /// [ReadHandler::handle_unknown_header](crate::master::ReadHandler::handle_unknown_header)
+    /// and the other headers of the response are processed normally.
+    pub capture_unknown_objects: bool,
+    /// Vendor-specific objects to parse in responses
+    ///
+    /// Headers containing these objects are passed to
+    /// [ReadHandler::handle_vendor_header](crate::master::ReadHandler::handle_vendor_header).
+    ///
+    /// Vendor objects are registered in code and are not part of a serialized configuration.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub vendor_objects: VendorObjects,
+    /// How recoverable deviations from the specification in responses are handled
+    ///
+    /// Defaults to [ParseMode::Strict] which discards the entire response.
+    pub parse_mode: ParseMode,
+    /// How READ requests answered with the OBJECT_UNKNOWN IIN bit are retried
+    ///
+    /// The variations that succeed are remembered and requested in place of the original ones
+    /// for the lifetime of the association. Defaults to [VariationFallback::Disabled].
+    pub variation_fallback: VariationFallback,
+    /// Number of consecutive response timeouts after which communication with the outstation is
+    /// considered lost
+    ///
+    /// When set, the association remembers the last value of each point passed to the
+    /// [ReadHandler](crate::master::ReadHandler). If the channel is closed or disabled, or after
+    /// this many consecutive timeouts, these values are reported again with the `COMM_LOST` flag
+    /// set in a fragment of type [ReadType::CommLost](crate::master::ReadType::CommLost). Once the
+    /// outstation answers and the startup integrity poll completes, the points that were not
+    /// updated in the meantime are reported again with their last flags. Defaults to `None`.
+    pub comm_lost_timeouts: Option<usize>,
 }
 
 impl AssociationConfig {
@@ -69,12 +162,21 @@ impl AssociationConfig {
             disable_unsol_classes,
             enable_unsol_classes,
             startup_integrity_classes,
+            class_zero_request: ClassZeroRequest::Class0,
             auto_time_sync: None,
             auto_tasks_retry_strategy: RetryStrategy::default(),
             keep_alive_timeout: None,
             auto_integrity_scan_on_buffer_overflow: false,
             event_scan_on_events_available,
             max_queued_user_requests: Self::DEFAULT_MAX_QUEUED_USER_REQUESTS,
+            queue_overflow_policy: QueueOverflowPolicy::RejectNew,
+            priority: 0,
+            response_timeout: None,
+            capture_unknown_objects: false,
+            vendor_objects: VendorObjects::new(),
+            parse_mode: ParseMode::Strict,
+            variation_fallback: VariationFallback::Disabled,
+            comm_lost_timeouts: None,
         }
     }
 
@@ -85,12 +187,21 @@ impl AssociationConfig {
             disable_unsol_classes: EventClasses::none(),
             enable_unsol_classes: EventClasses::none(),
             startup_integrity_classes: Classes::none(),
+            class_zero_request: ClassZeroRequest::Class0,
             auto_time_sync: None,
             auto_tasks_retry_strategy: RetryStrategy::default(),
             keep_alive_timeout: None,
             auto_integrity_scan_on_buffer_overflow: false,
             event_scan_on_events_available: EventClasses::none(),
             max_queued_user_requests: Self::DEFAULT_MAX_QUEUED_USER_REQUESTS,
+            queue_overflow_policy: QueueOverflowPolicy::RejectNew,
+            priority: 0,
+            response_timeout: None,
+            capture_unknown_objects: false,
+            vendor_objects: VendorObjects::new(),
+            parse_mode: ParseMode::Strict,
+            variation_fallback: VariationFallback::Disabled,
+            comm_lost_timeouts: None,
         }
     }
 }
@@ -101,16 +212,240 @@ impl Default for AssociationConfig {
             disable_unsol_classes: EventClasses::all(),
             enable_unsol_classes: EventClasses::all(),
             startup_integrity_classes: Classes::all(),
+            class_zero_request: ClassZeroRequest::Class0,
             auto_time_sync: None,
             auto_tasks_retry_strategy: RetryStrategy::default(),
             keep_alive_timeout: None,
             auto_integrity_scan_on_buffer_overflow: true,
             event_scan_on_events_available: EventClasses::none(),
             max_queued_user_requests: Self::DEFAULT_MAX_QUEUED_USER_REQUESTS,
+            queue_overflow_policy: QueueOverflowPolicy::RejectNew,
+            priority: 0,
+            response_timeout: None,
+            capture_unknown_objects: false,
+            vendor_objects: VendorObjects::new(),
+            parse_mode: ParseMode::Strict,
+            variation_fallback: VariationFallback::Disabled,
+            comm_lost_timeouts: None,
         }
     }
 }
 
+impl AssociationConfig {
+    /// check that the settings are consistent with each other
+    ///
+    /// This is performed by [AssociationConfigBuilder::build] and is useful for configurations
+    /// that are constructed directly or loaded from a file.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        ConfigError::check_retry_strategy(
+            "auto_tasks_retry_strategy",
+            &self.auto_tasks_retry_strategy,
+        )?;
+        if let Some(timeout) = self.keep_alive_timeout {
+            ConfigError::check_timeout("keep_alive_timeout", timeout)?;
+        }
+        ConfigError::check_limit(
+            "max_queued_user_requests",
+            Some(self.max_queued_user_requests),
+        )?;
+        ConfigError::check_limit("comm_lost_timeouts", self.comm_lost_timeouts)?;
+        Ok(())
+    }
+}
+
+/// Builds an [AssociationConfig] whose settings are checked for consistency
+///
+/// Settings that are not specified have the values of [AssociationConfig::default].
+#[derive(Debug, Clone, Default)]
+pub struct AssociationConfigBuilder {
+    config: AssociationConfig,
+}
+
+impl AssociationConfigBuilder {
+    /// create a builder with the default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// set the event classes to disable on startup
+    pub fn with_disable_unsol_classes(mut self, disable_unsol_classes: EventClasses) -> Self {
+        self.config.disable_unsol_classes = disable_unsol_classes;
+        self
+    }
+
+    /// set the event classes to enable on startup
+    pub fn with_enable_unsol_classes(mut self, enable_unsol_classes: EventClasses) -> Self {
+        self.config.enable_unsol_classes = enable_unsol_classes;
+        self
+    }
+
+    /// set the classes of the startup integrity poll
+    pub fn with_startup_integrity_classes(mut self, startup_integrity_classes: Classes) -> Self {
+        self.config.startup_integrity_classes = startup_integrity_classes;
+        self
+    }
+
+    /// set the headers that request the class 0 data of the startup integrity poll
+    pub fn with_class_zero_request(mut self, class_zero_request: ClassZeroRequest) -> Self {
+        self.config.class_zero_request = class_zero_request;
+        self
+    }
+
+    /// set the automatic time synchronization procedure
+    pub fn with_auto_time_sync(mut self, auto_time_sync: Option<TimeSyncProcedure>) -> Self {
+        self.config.auto_time_sync = auto_time_sync;
+        self
+    }
+
+    /// set the retry strategy of the automatic tasks
+    pub fn with_auto_tasks_retry_strategy(
+        mut self,
+        auto_tasks_retry_strategy: RetryStrategy,
+    ) -> Self {
+        self.config.auto_tasks_retry_strategy = auto_tasks_retry_strategy;
+        self
+    }
+
+    /// set the time without received bytes before a `REQUEST_LINK_STATUS`
+    pub fn with_keep_alive_timeout(mut self, keep_alive_timeout: Option<Duration>) -> Self {
+        self.config.keep_alive_timeout = keep_alive_timeout;
+        self
+    }
+
+    /// set the automatic integrity scan on `EVENT_BUFFER_OVERFLOW`
+    pub fn with_auto_integrity_scan_on_buffer_overflow(
+        mut self,
+        auto_integrity_scan_on_buffer_overflow: bool,
+    ) -> Self {
+        self.config.auto_integrity_scan_on_buffer_overflow = auto_integrity_scan_on_buffer_overflow;
+        self
+    }
+
+    /// set the classes scanned when their IIN bit is set
+    pub fn with_event_scan_on_events_available(
+        mut self,
+        event_scan_on_events_available: EventClasses,
+    ) -> Self {
+        self.config.event_scan_on_events_available = event_scan_on_events_available;
+        self
+    }
+
+    /// set the maximum number of queued user requests
+    pub fn with_max_queued_user_requests(mut self, max_queued_user_requests: usize) -> Self {
+        self.config.max_queued_user_requests = max_queued_user_requests;
+        self
+    }
+
+    /// set how a user request is handled when the queue is full
+    pub fn with_queue_overflow_policy(
+        mut self,
+        queue_overflow_policy: QueueOverflowPolicy,
+    ) -> Self {
+        self.config.queue_overflow_policy = queue_overflow_policy;
+        self
+    }
+
+    /// set the scheduling priority relative to the other associations on the channel
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.config.priority = priority;
+        self
+    }
+
+    /// set the response timeout overriding that of the channel
+    pub fn with_response_timeout(mut self, response_timeout: Option<Timeout>) -> Self {
+        self.config.response_timeout = response_timeout;
+        self
+    }
+
+    /// set the capture of object headers with an unknown group and variation
+    pub fn with_capture_unknown_objects(mut self, capture_unknown_objects: bool) -> Self {
+        self.config.capture_unknown_objects = capture_unknown_objects;
+        self
+    }
+
+    /// set the vendor-specific objects to parse in responses
+    pub fn with_vendor_objects(mut self, vendor_objects: VendorObjects) -> Self {
+        self.config.vendor_objects = vendor_objects;
+        self
+    }
+
+    /// set the handling of recoverable deviations from the specification
+    pub fn with_parse_mode(mut self, parse_mode: ParseMode) -> Self {
+        self.config.parse_mode = parse_mode;
+        self
+    }
+
+    /// set how READ requests answered with OBJECT_UNKNOWN are retried
+    pub fn with_variation_fallback(mut self, variation_fallback: VariationFallback) -> Self {
+        self.config.variation_fallback = variation_fallback;
+        self
+    }
+
+    /// set the number of consecutive response timeouts after which the points are reported with
+    /// `COMM_LOST`
+    pub fn with_comm_lost_timeouts(mut self, comm_lost_timeouts: Option<usize>) -> Self {
+        self.config.comm_lost_timeouts = comm_lost_timeouts;
+        self
+    }
+
+    /// check the settings and return the configuration
+    pub fn build(self) -> Result<AssociationConfig, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+impl From<AssociationConfig> for AssociationConfigBuilder {
+    fn from(config: AssociationConfig) -> Self {
+        Self { config }
+    }
+}
+
+/// Controls how the associations sharing a channel are given access to it
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SchedulingConfig {
+    /// Defer the automatic tasks and polls of an association whose requests time out
+    ///
+    /// Each consecutive response timeout from the same association grows the delay according to
+    /// the strategy, so that an offline outstation does not hold the channel for a full response
+    /// timeout every time one of its polls comes due. The delay is cleared as soon as the
+    /// association answers a request. User requests are never deferred.
+    ///
+    /// `None` disables the hold-off.
+    pub timeout_holdoff: Option<RetryStrategy>,
+    /// What happens to a multi-fragment READ response in progress when a command is queued
+    ///
+    /// Queued commands always run before queued READ requests and polls, but by default a READ
+    /// that has started receives its entire response first, which can take many seconds for a
+    /// large integrity poll.
+    pub read_preemption: ReadPreemption,
+}
+
+/// Action taken on a multi-fragment READ response in progress when a command is queued
+///
+/// The READ is interrupted by not confirming the fragment just received, so that the outstation
+/// abandons the rest of the response when it receives the command. The values in the fragments
+/// received so far are reported, and the events that weren't confirmed are reported again.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReadPreemption {
+    /// the READ receives its entire response before the command runs
+    Disabled,
+    /// the READ fails with [TaskError::Preempted]
+    ///
+    /// Automatic tasks and polls are retried as if they had failed for another reason.
+    Cancel,
+    /// the READ runs again once the command completes
+    Postpone,
+}
+
+impl Default for ReadPreemption {
+    fn default() -> Self {
+        Self::Disabled
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) enum AutoTaskState {
     /// The task doesn't need to be scheduled (because it was completed or not required)
@@ -132,12 +467,12 @@ impl AutoTaskState {
     }
 
     /// Create a `Next<Task>`
-    fn create_next_task(&self, builder: impl FnOnce() -> Task) -> Next<Task> {
+    fn create_next_task(&self, now: Instant, builder: impl FnOnce() -> Task) -> Next<Task> {
         match self {
             Self::Idle => Next::None,
             Self::Pending => Next::Now(builder()),
             Self::Failed(_, next) => {
-                if Instant::now() >= *next {
+                if now >= *next {
                     Next::Now(builder())
                 } else {
                     Next::NotBefore(*next)
@@ -159,16 +494,16 @@ impl AutoTaskState {
     }
 
     /// The task failed and needs rescheduling
-    fn failure(&mut self, config: &AssociationConfig) {
+    fn failure(&mut self, config: &AssociationConfig, now: Instant) {
         *self = match self {
             Self::Failed(backoff, _) => {
                 let delay = backoff.on_failure();
-                Self::Failed(backoff.clone(), Instant::now() + delay)
+                Self::Failed(backoff.clone(), now + delay)
             }
             _ => {
                 let mut backoff = ExponentialBackOff::new(config.auto_tasks_retry_strategy);
                 let delay = backoff.on_failure();
-                Self::Failed(backoff, Instant::now() + delay)
+                Self::Failed(backoff, now + delay)
             }
         }
     }
@@ -206,35 +541,43 @@ impl TaskStates {
         self.enabled_unsolicited.demand();
     }
 
-    fn next(&self, config: &AssociationConfig, association: &Association) -> Next<Task> {
+    fn next(
+        &self,
+        config: &AssociationConfig,
+        association: &Association,
+        now: Instant,
+    ) -> Next<Task> {
         if self.clear_restart_iin.is_pending() {
             return self
                 .clear_restart_iin
-                .create_next_task(|| AutoTask::ClearRestartBit.wrap());
+                .create_next_task(now, || AutoTask::ClearRestartBit.wrap());
         }
 
         if config.disable_unsol_classes.any() && self.disable_unsolicited.is_pending() {
-            return self.disable_unsolicited.create_next_task(|| {
+            return self.disable_unsolicited.create_next_task(now, || {
                 AutoTask::DisableUnsolicited(config.disable_unsol_classes).wrap()
             });
         }
 
         if config.startup_integrity_classes.any() && self.integrity_scan.is_pending() {
-            return self.integrity_scan.create_next_task(|| {
-                Task::Read(ReadTask::StartupIntegrity(config.startup_integrity_classes))
+            return self.integrity_scan.create_next_task(now, || {
+                Task::Read(ReadTask::StartupIntegrity(
+                    config.startup_integrity_classes,
+                    config.class_zero_request.clone(),
+                ))
             });
         }
 
         if self.time_sync.is_pending() {
             if let Some(procedure) = config.auto_time_sync {
-                return self.time_sync.create_next_task(|| {
+                return self.time_sync.create_next_task(now, || {
                     TimeSync(TimeSyncTask::get_procedure(procedure, Promise::None)).wrap()
                 });
             }
         }
 
         if config.enable_unsol_classes.any() && self.enabled_unsolicited.is_pending() {
-            return self.enabled_unsolicited.create_next_task(|| {
+            return self.enabled_unsolicited.create_next_task(now, || {
                 AutoTask::EnableUnsolicited(config.enable_unsol_classes).wrap()
             });
         }
@@ -243,7 +586,7 @@ impl TaskStates {
         if events_to_scan.any() {
             return self
                 .event_scan
-                .create_next_task(|| ReadTask::EventScan(events_to_scan).wrap());
+                .create_next_task(now, || ReadTask::EventScan(events_to_scan).wrap());
         }
 
         Next::None
@@ -272,7 +615,9 @@ pub(crate) struct Association {
     address: EndpointAddress,
     seq: Sequence,
     last_unsol_frag: Option<LastUnsolFragment>,
-    request_queue: VecDeque<Task>,
+    // sequence number of the last solicited response that was processed
+    last_solicited_seq: Option<Sequence>,
+    request_queue: VecDeque<UserTask>,
     max_request_queue_size: usize,
     auto_tasks: TaskStates,
     read_handler: Box<dyn ReadHandler>,
@@ -280,8 +625,18 @@ pub(crate) struct Association {
     config: AssociationConfig,
     polls: PollMap,
     next_link_status: Option<Instant>,
+    // reception time of the fragment being processed
+    fragment_received: Option<RxTimestamp>,
     startup_integrity_done: bool,
     events_available: EventClasses,
+    // automatic tasks and polls are deferred until the instant after response timeouts
+    holdoff: Option<(ExponentialBackOff, Instant)>,
+    // variations supported by the outstation in place of requested ones, keyed by group/variation
+    variation_fallbacks: BTreeMap<(u8, u8), Variation>,
+    // last values of the points, present if COMM_LOST is reported
+    point_cache: Option<PointCache>,
+    consecutive_timeouts: usize,
+    clock: Clock,
 }
 
 impl Association {
@@ -290,45 +645,144 @@ impl Association {
         config: AssociationConfig,
         read_handler: Box<dyn ReadHandler>,
         assoc_handler: Box<dyn AssociationHandler>,
+        clock: Clock,
     ) -> Self {
         Self {
             address,
             seq: Sequence::default(),
             last_unsol_frag: None,
+            last_solicited_seq: None,
             request_queue: VecDeque::new(),
             max_request_queue_size: config.max_queued_user_requests,
             auto_tasks: TaskStates::new(),
             read_handler,
             assoc_handler,
+            point_cache: config.comm_lost_timeouts.map(|_| PointCache::new()),
+            consecutive_timeouts: 0,
+            polls: PollMap::new(clock.clone()),
+            next_link_status: config.keep_alive_timeout.map(|delay| clock.now() + delay),
             config,
-            polls: PollMap::new(),
-            next_link_status: config
-                .keep_alive_timeout
-                .map(|delay| Instant::now() + delay),
+            fragment_received: None,
             startup_integrity_done: false,
             events_available: EventClasses::none(),
+            holdoff: None,
+            variation_fallbacks: BTreeMap::new(),
+            clock,
         }
     }
 
-    pub(crate) fn process_message(&mut self, msg: AssociationMsgType, is_connected: bool) {
+    /// process a message from a handle, failing queued tasks with `rejection` if present
+    pub(crate) fn process_message(
+        &mut self,
+        msg: AssociationMsgType,
+        rejection: Option<TaskError>,
+    ) {
         match msg {
-            AssociationMsgType::QueueTask(task) => {
-                if is_connected {
-                    if self.request_queue.len() < self.max_request_queue_size {
-                        self.request_queue.push_back(task);
-                    } else {
-                        task.on_task_error(Some(self), TaskError::TooManyRequests);
+            AssociationMsgType::QueueTask(task) => match rejection {
+                None => self.queue_task(task),
+                Some(err) => self.fail_user_task(task, err),
+            },
+            AssociationMsgType::Poll(msg) => {
+                self.process_poll_message(msg);
+            }
+        }
+    }
+
+    fn queue_task(&mut self, task: UserTask) {
+        if self.request_queue.len() < self.max_request_queue_size {
+            self.enqueue(task);
+            return;
+        }
+
+        let task = match self.config.queue_overflow_policy {
+            QueueOverflowPolicy::RejectNew => task,
+            QueueOverflowPolicy::DropOldestRead => {
+                let oldest = self
+                    .request_queue
+                    .iter()
+                    .position(|queued| queued.task.is_single_read())
+                    .and_then(|pos| self.request_queue.remove(pos));
+                match oldest {
+                    Some(oldest) => {
+                        self.fail_user_task(oldest, TaskError::Dropped);
+                        self.enqueue(task);
+                        return;
                     }
-                } else {
-                    task.on_task_error(Some(self), TaskError::NoConnection);
+                    None => task,
                 }
             }
-            AssociationMsgType::Poll(msg) => {
-                self.process_poll_message(msg);
+            QueueOverflowPolicy::CoalesceDuplicateReads => {
+                let mut task = task;
+                for queued in self.request_queue.iter_mut() {
+                    task.task = match queued.task.coalesce(task.task, task.correlation_id) {
+                        Some(task) => task,
+                        // the task completes along with the queued read
+                        None => return,
+                    };
+                }
+                task
             }
+        };
+
+        self.fail_user_task(task, TaskError::TooManyRequests);
+    }
+
+    /// fail a request that didn't run, reporting its completion to the handler
+    fn fail_user_task(&mut self, task: UserTask, err: TaskError) {
+        let id = task
+            .correlation_id
+            .unwrap_or_else(crate::decode::next_correlation_id);
+        let merged = task.task.coalesced_ids();
+        task.task.on_task_error(Some(self), err);
+        self.on_task_complete(id, &merged, Err(err));
+    }
+
+    /// report the completion of a task, and of the requests merged into it, to the handler
+    pub(crate) fn on_task_complete(
+        &mut self,
+        id: u64,
+        merged: &[u64],
+        result: Result<(), TaskError>,
+    ) {
+        for id in std::iter::once(id).chain(merged.iter().copied()) {
+            self.assoc_handler
+                .task_completed(TaskCompletion::new(id, result));
         }
     }
 
+    /// queue a task in order, except that commands are placed ahead of the queued READ requests
+    fn enqueue(&mut self, task: UserTask) {
+        if !task.task.is_command() {
+            self.request_queue.push_back(task);
+            return;
+        }
+
+        let position = self
+            .request_queue
+            .iter()
+            .rposition(|x| !x.task.is_read())
+            .map_or(0, |x| x + 1);
+        self.request_queue.insert(position, task);
+    }
+
+    /// run a READ that was interrupted by a command again once the queued commands complete
+    pub(crate) fn postpone_read(&mut self, task: ReadTask, correlation_id: Option<u64>) {
+        // automatic tasks and polls remain due until they complete
+        if let ReadTask::SingleRead(_) = task {
+            let position = self
+                .request_queue
+                .iter()
+                .position(|x| x.task.is_read())
+                .unwrap_or(self.request_queue.len());
+            self.request_queue
+                .insert(position, UserTask::new(task.wrap(), correlation_id));
+        }
+    }
+
+    fn has_queued_command(&self) -> bool {
+        self.request_queue.iter().any(|x| x.task.is_command())
+    }
+
     fn process_poll_message(&mut self, msg: PollMsg) {
         match msg {
             PollMsg::AddPoll(association, request, period, callback) => {
@@ -347,26 +801,88 @@ impl Association {
 
     fn reset(&mut self, err: RunError) {
         // Fail any pending requests
-        while let Some(task) = self.request_queue.pop_front() {
-            task.on_task_error(Some(self), err.into());
+        while let Some(queued) = self.request_queue.pop_front() {
+            self.fail_user_task(queued, err.into());
         }
 
         // Reset the auto tasks
         self.auto_tasks.reset();
         self.startup_integrity_done = false;
+        self.holdoff = None;
 
         // Clear last unsolicited fragment
         self.last_unsol_frag = None;
+        self.last_solicited_seq = None;
+        self.fragment_received = None;
+
+        self.consecutive_timeouts = 0;
+        if err != RunError::State(StateChange::Shutdown) {
+            self.on_comm_lost();
+        }
+    }
+
+    fn on_comm_lost(&mut self) {
+        if let Some(cache) = &mut self.point_cache {
+            cache.on_comm_lost(self.read_handler.as_mut());
+        }
+    }
+
+    fn on_comm_restored(&mut self) {
+        if let Some(cache) = &mut self.point_cache {
+            cache.on_comm_restored(self.read_handler.as_mut());
+        }
+    }
+
+    /// pass the measurements of a response to the read handler, recording them in the point cache
+    fn extract(
+        &mut self,
+        read_type: ReadType,
+        header: ResponseHeader,
+        objects: HeaderCollection,
+        correlation_id: Option<u64>,
+    ) {
+        match &mut self.point_cache {
+            Some(cache) => extract_measurements(
+                read_type,
+                header,
+                objects,
+                self.fragment_received,
+                correlation_id,
+                &mut cache.handler(self.read_handler.as_mut()),
+            ),
+            None => extract_measurements(
+                read_type,
+                header,
+                objects,
+                self.fragment_received,
+                correlation_id,
+                self.read_handler.as_mut(),
+            ),
+        }
     }
 
     pub(crate) fn get_system_time(&self) -> Option<Timestamp> {
         self.assoc_handler.get_system_time()
     }
 
+    /// current time of the clock of the channel
+    pub(crate) fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
     pub(crate) fn complete_poll(&mut self, id: u64) {
         self.polls.complete(id)
     }
 
+    pub(crate) fn on_solicited_response(&mut self, seq: Sequence) {
+        self.last_solicited_seq = Some(seq);
+    }
+
+    /// test if a solicited response repeats the last one that was processed
+    pub(crate) fn is_duplicate_response(&self, seq: Sequence) -> bool {
+        self.last_solicited_seq == Some(seq)
+    }
+
     pub(crate) fn increment_seq(&mut self) -> Sequence {
         self.seq.increment()
     }
@@ -420,7 +936,9 @@ impl Association {
 
     pub(crate) fn on_integrity_scan_failure(&mut self) {
         tracing::warn!("startup integrity scan failed");
-        self.auto_tasks.integrity_scan.failure(&self.config);
+        self.auto_tasks
+            .integrity_scan
+            .failure(&self.config, self.clock.now());
     }
 
     pub(crate) fn on_event_scan_complete(&mut self) {
@@ -429,13 +947,17 @@ impl Association {
 
     pub(crate) fn on_event_scan_failure(&mut self) {
         tracing::warn!("automatic event scan failed");
-        self.auto_tasks.event_scan.failure(&self.config);
+        self.auto_tasks
+            .event_scan
+            .failure(&self.config, self.clock.now());
     }
 
     pub(crate) fn on_clear_restart_iin_response(&mut self, iin: Iin) {
         if iin.iin1.get_device_restart() {
             tracing::warn!("device failed to clear restart IIN bit");
-            self.auto_tasks.clear_restart_iin.failure(&self.config);
+            self.auto_tasks
+                .clear_restart_iin
+                .failure(&self.config, self.clock.now());
         } else {
             self.auto_tasks.clear_restart_iin.done();
         }
@@ -443,7 +965,9 @@ impl Association {
 
     pub(crate) fn on_clear_restart_iin_failure(&mut self) {
         tracing::warn!("device failed to clear restart IIN bit");
-        self.auto_tasks.clear_restart_iin.failure(&self.config);
+        self.auto_tasks
+            .clear_restart_iin
+            .failure(&self.config, self.clock.now());
     }
 
     pub(crate) fn on_time_sync_success(&mut self) {
@@ -452,7 +976,9 @@ impl Association {
 
     pub(crate) fn on_time_sync_failure(&mut self, err: TimeSyncError) {
         tracing::warn!("auto time sync failed: {}", err);
-        self.auto_tasks.time_sync.failure(&self.config);
+        self.auto_tasks
+            .time_sync
+            .failure(&self.config, self.clock.now());
     }
 
     pub(crate) fn on_enable_unsolicited_response(&mut self, _iin: Iin) {
@@ -461,7 +987,9 @@ impl Association {
 
     pub(crate) fn on_enable_unsolicited_failure(&mut self) {
         tracing::warn!("device failed to enable unsolicited responses");
-        self.auto_tasks.enabled_unsolicited.failure(&self.config);
+        self.auto_tasks
+            .enabled_unsolicited
+            .failure(&self.config, self.clock.now());
     }
 
     pub(crate) fn on_disable_unsolicited_response(&mut self, _iin: Iin) {
@@ -470,17 +998,101 @@ impl Association {
 
     pub(crate) fn on_disable_unsolicited_failure(&mut self) {
         tracing::warn!("device failed to disable unsolicited responses");
-        self.auto_tasks.disable_unsolicited.failure(&self.config);
+        self.auto_tasks
+            .disable_unsolicited
+            .failure(&self.config, self.clock.now());
     }
 
     pub(crate) fn on_link_activity(&mut self) {
         self.next_link_status = self
             .config
             .keep_alive_timeout
-            .map(|timeout| Instant::now() + timeout)
+            .map(|timeout| self.clock.now() + timeout)
+    }
+
+    pub(crate) fn on_fragment_received(&mut self, received: RxTimestamp) {
+        self.fragment_received = Some(received);
+    }
+
+    /// request the variations that replaced the original ones after an OBJECT_UNKNOWN
+    pub(crate) fn substitute_variations(&self, task: &mut ReadTask) {
+        for variation in task.variations_mut() {
+            if let Some(x) = self.variation_fallbacks.get(&variation.to_group_and_var()) {
+                *variation = *x;
+            }
+        }
+    }
+
+    /// change the variations of the task to their fallbacks, returning false if none have one
+    pub(crate) fn fall_back_variations(&self, task: &mut ReadTask) -> bool {
+        let mut changed = false;
+        for variation in task.variations_mut() {
+            if let Some(x) = self.config.variation_fallback.next(*variation) {
+                *variation = x;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// remember the variations of the task that succeeded in place of the requested ones
+    pub(crate) fn remember_variations(&mut self, requested: &[Variation], task: &mut ReadTask) {
+        for (requested, working) in requested.iter().zip(task.variations_mut()) {
+            if *requested != *working {
+                tracing::info!(
+                    "outstation {} supports {:?} in place of {:?}",
+                    self.address,
+                    working,
+                    requested
+                );
+                self.variation_fallbacks
+                    .insert(requested.to_group_and_var(), *working);
+            }
+        }
+    }
+
+    /// objects of a response, re-parsed to capture unknown headers if configured to do so
+    pub(crate) fn vendor_objects(&self) -> VendorObjects {
+        self.config.vendor_objects.clone()
+    }
+
+    /// the objects of the response, parsed again if they contain unknown headers that the
+    /// association captures, vendor-specific objects, or deviations tolerated by the parse mode
+    pub(crate) fn get_objects<'a>(
+        &self,
+        response: &Response<'a>,
+        vendor_objects: &'a VendorObjects,
+        anomalies: &AnomalyLog,
+    ) -> Result<HeaderCollection<'a>, ObjectParseError> {
+        let reparse = match response.objects {
+            Err(ObjectParseError::UnknownGroupVariation(_, _)) => {
+                self.config.capture_unknown_objects
+                    || !vendor_objects.is_empty()
+                    || self.config.parse_mode.is_lenient()
+            }
+            Err(_) => self.config.parse_mode.is_lenient(),
+            Ok(_) => false,
+        };
+
+        if !reparse {
+            return response.objects;
+        }
+
+        let objects = HeaderCollection::parse_with_mode(
+            response.header.function.function(),
+            response.raw_objects,
+            UnknownObjects::new(self.config.capture_unknown_objects, Some(vendor_objects)),
+            self.config.parse_mode,
+        )?;
+        anomalies.record_parse(objects.anomalies());
+        Ok(objects)
     }
 
-    pub(crate) fn handle_unsolicited_response(&mut self, response: &Response) -> bool {
+    pub(crate) fn handle_unsolicited_response(
+        &mut self,
+        response: &Response,
+        anomalies: &AnomalyLog,
+    ) -> bool {
         // Accept the fragment only if the startup sequence was completed or if it's a null response.
         //
         // Now here's the deal. According to TB2015-002a, we should also ignore null responses without
@@ -502,13 +1114,9 @@ impl Association {
                 return true; // still want to send confirmation if requested
             }
 
-            if let Ok(objects) = response.objects {
-                extract_measurements(
-                    ReadType::Unsolicited,
-                    response.header,
-                    objects,
-                    self.read_handler.as_mut(),
-                );
+            let vendor_objects = self.vendor_objects();
+            if let Ok(objects) = self.get_objects(response, &vendor_objects, anomalies) {
+                self.extract(ReadType::Unsolicited, response.header, objects, None);
             }
 
             true
@@ -524,71 +1132,109 @@ impl Association {
         &mut self,
         header: ResponseHeader,
         objects: HeaderCollection,
+        correlation_id: Option<u64>,
     ) {
-        extract_measurements(
-            ReadType::StartupIntegrity,
-            header,
-            objects,
-            self.read_handler.as_mut(),
-        );
+        self.extract(ReadType::StartupIntegrity, header, objects, correlation_id);
     }
 
     pub(crate) fn handle_poll_response(
         &mut self,
         header: ResponseHeader,
         objects: HeaderCollection,
+        correlation_id: Option<u64>,
     ) {
-        extract_measurements(
-            ReadType::PeriodicPoll,
-            header,
-            objects,
-            self.read_handler.as_mut(),
-        );
+        self.extract(ReadType::PeriodicPoll, header, objects, correlation_id);
     }
 
     pub(crate) fn handle_event_scan_response(
         &mut self,
         header: ResponseHeader,
         objects: HeaderCollection,
+        correlation_id: Option<u64>,
     ) {
-        extract_measurements(
-            ReadType::PeriodicPoll,
-            header,
-            objects,
-            self.read_handler.as_mut(),
-        );
+        self.extract(ReadType::PeriodicPoll, header, objects, correlation_id);
     }
 
     pub(crate) fn handle_read_response(
         &mut self,
         header: ResponseHeader,
         objects: HeaderCollection,
+        correlation_id: Option<u64>,
     ) {
-        extract_measurements(
-            ReadType::SinglePoll,
-            header,
-            objects,
-            self.read_handler.as_mut(),
-        );
+        self.extract(ReadType::SinglePoll, header, objects, correlation_id);
     }
 
-    pub(crate) fn priority_task(&mut self) -> Option<Task> {
-        while let Some(task) = self.request_queue.pop_front() {
-            if let Some(task) = task.start(self) {
-                return Some(task);
+    pub(crate) fn priority_task(&mut self) -> Option<UserTask> {
+        while let Some(queued) = self.request_queue.pop_front() {
+            if let Some(task) = queued.task.start(self) {
+                self.fragment_received = None;
+                return Some(UserTask::new(task, queued.correlation_id));
             }
         }
 
         None
     }
 
+    fn on_task_result(&mut self, result: Result<(), TaskError>, strategy: Option<RetryStrategy>) {
+        match result {
+            Err(TaskError::ResponseTimeout(_)) => {
+                self.consecutive_timeouts = self.consecutive_timeouts.saturating_add(1);
+                if Some(self.consecutive_timeouts) == self.config.comm_lost_timeouts {
+                    tracing::warn!(
+                        "communication lost with {} after {} consecutive timeouts",
+                        self.address,
+                        self.consecutive_timeouts
+                    );
+                    self.on_comm_lost();
+                }
+                if let Some(strategy) = strategy {
+                    let mut backoff = match self.holdoff.take() {
+                        Some((backoff, _)) => backoff,
+                        None => ExponentialBackOff::new(strategy),
+                    };
+                    let delay = backoff.on_failure();
+                    tracing::warn!(
+                        "deferring automatic tasks and polls of {} for {} ms",
+                        self.address,
+                        delay.as_millis()
+                    );
+                    self.holdoff = Some((backoff, self.clock.now() + delay));
+                }
+            }
+            // the outstation answered the request
+            Ok(())
+            | Err(TaskError::MalformedResponse(_))
+            | Err(TaskError::UnexpectedResponseHeaders)
+            | Err(TaskError::NonFinWithoutCon(_))
+            | Err(TaskError::NeverReceivedFir(_))
+            | Err(TaskError::UnexpectedFir(_))
+            | Err(TaskError::MultiFragmentResponse(_)) => {
+                self.holdoff = None;
+                self.consecutive_timeouts = 0;
+                // points that aren't part of the integrity poll keep COMM_LOST until it completes
+                if self.is_integrity_complete() {
+                    self.on_comm_restored();
+                }
+            }
+            Err(_) => {}
+        }
+    }
+
     fn next_task(&mut self, now: Instant) -> Next<Task> {
+        if let Some((_, until)) = &self.holdoff {
+            if now < *until {
+                return Next::NotBefore(*until);
+            }
+        }
+
         loop {
             let next_task = self.get_next_task(now);
 
             if let Next::Now(task) = next_task {
                 // Check if execution can still happen
                 if let Some(task) = task.start(self) {
+                    // the fragments of an earlier task must not be attributed to this one
+                    self.fragment_received = None;
                     return Next::Now(task);
                 }
             } else {
@@ -597,9 +1243,35 @@ impl Association {
         }
     }
 
+    /// time at which a task other than a keep-alive is due, used to establish dial-up connections
+    #[cfg(any(test, feature = "tcp"))]
+    fn next_work(&self, now: Instant) -> Next<()> {
+        if !self.request_queue.is_empty() {
+            return Next::Now(());
+        }
+
+        if let Some((_, until)) = &self.holdoff {
+            if now < *until {
+                return Next::NotBefore(*until);
+            }
+        }
+
+        match self.auto_tasks.next(&self.config, self, now) {
+            Next::Now(_) => return Next::Now(()),
+            Next::NotBefore(x) => return Next::NotBefore(x),
+            Next::None => {}
+        }
+
+        match self.polls.next(now) {
+            Next::Now(_) => Next::Now(()),
+            Next::NotBefore(x) => Next::NotBefore(x),
+            Next::None => Next::None,
+        }
+    }
+
     fn get_next_task(&self, now: Instant) -> Next<Task> {
         // Check for automatic tasks
-        let next = self.auto_tasks.next(&self.config, self);
+        let next = self.auto_tasks.next(&self.config, self, now);
 
         // Startup task have greater priority
         if !matches!(next, Next::None) {
@@ -617,7 +1289,6 @@ impl Association {
             },
             Next::None => match self.next_link_status {
                 Some(next) => {
-                    let now = Instant::now();
                     if now < next {
                         Next::NotBefore(next)
                     } else {
@@ -637,13 +1308,16 @@ pub(crate) enum Next<T> {
 }
 
 pub(crate) struct AssociationMap {
+    scheduling: SchedulingConfig,
     map: BTreeMap<EndpointAddress, Association>,
+    // ordered by descending association priority, round-robin within each priority
     priority: VecDeque<EndpointAddress>,
+    clock: Clock,
 }
 
 impl Default for AssociationMap {
     fn default() -> Self {
-        Self::new()
+        Self::new(SchedulingConfig::default(), Clock::default())
     }
 }
 
@@ -653,10 +1327,12 @@ pub(crate) struct NoAssociation {
 }
 
 impl AssociationMap {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(scheduling: SchedulingConfig, clock: Clock) -> Self {
         Self {
+            scheduling,
             map: BTreeMap::new(),
             priority: VecDeque::new(),
+            clock,
         }
     }
 
@@ -671,11 +1347,36 @@ impl AssociationMap {
             return Err(AssociationError::DuplicateAddress(session.address));
         }
 
-        self.priority.push_back(session.address);
-        self.map.insert(session.address, session);
+        let address = session.address;
+        self.map.insert(address, session);
+        self.enqueue(address);
         Ok(())
     }
 
+    /// insert an address after every association of the same or higher priority
+    fn enqueue(&mut self, address: EndpointAddress) {
+        let priority = self.priority_of(address);
+        let position = self
+            .priority
+            .iter()
+            .position(|x| self.priority_of(*x) < priority)
+            .unwrap_or(self.priority.len());
+        self.priority.insert(position, address);
+    }
+
+    fn requeue(&mut self, index: usize) {
+        if let Some(address) = self.priority.remove(index) {
+            self.enqueue(address);
+        }
+    }
+
+    fn priority_of(&self, address: EndpointAddress) -> u8 {
+        self.map
+            .get(&address)
+            .map(|x| x.config.priority)
+            .unwrap_or(0)
+    }
+
     pub(crate) fn remove(&mut self, address: EndpointAddress) {
         self.map.remove(&address);
         self.priority.retain(|x| *x != address);
@@ -691,37 +1392,128 @@ impl AssociationMap {
         }
     }
 
-    pub(crate) fn next_task(&mut self) -> Next<AssociationTask> {
-        // Check for priority task
+    pub(crate) fn response_timeout(&self, address: EndpointAddress) -> Option<Timeout> {
+        self.map
+            .get(&address)
+            .and_then(|x| x.config.response_timeout)
+    }
+
+    pub(crate) fn on_task_result(
+        &mut self,
+        address: EndpointAddress,
+        result: Result<(), TaskError>,
+    ) {
+        let strategy = self.scheduling.timeout_holdoff;
+        if let Some(association) = self.map.get_mut(&address) {
+            association.on_task_result(result, strategy);
+        }
+    }
+
+    /// report the completion of a task that ran, and of the requests merged into it
+    pub(crate) fn on_task_complete(
+        &mut self,
+        address: EndpointAddress,
+        id: u64,
+        merged: &[u64],
+        result: Result<(), TaskError>,
+    ) {
+        if let Some(association) = self.map.get_mut(&address) {
+            association.on_task_complete(id, merged, result);
+        }
+    }
+
+    /// restart the keep-alive timers when a dial-up connection is established
+    pub(crate) fn on_connect(&mut self) {
+        for association in self.map.values_mut() {
+            association.on_link_activity();
+        }
+    }
+
+    /// test if any user request is queued
+    #[cfg(feature = "tcp")]
+    pub(crate) fn has_user_tasks(&self) -> bool {
+        self.map.values().any(|x| !x.request_queue.is_empty())
+    }
+
+    /// test if a READ in progress should be interrupted to run a queued command
+    pub(crate) fn preempts_read(&self) -> bool {
+        self.scheduling.read_preemption != ReadPreemption::Disabled
+            && self.map.values().any(|x| x.has_queued_command())
+    }
+
+    pub(crate) fn read_preemption(&self) -> ReadPreemption {
+        self.scheduling.read_preemption
+    }
+
+    /// time at which any association has a task to run, ignoring keep-alives
+    #[cfg(any(test, feature = "tcp"))]
+    pub(crate) fn next_work(&self) -> Next<()> {
+        let now = self.clock.now();
+        let mut earliest = Smallest::<Instant>::new();
+
+        for association in self.map.values() {
+            match association.next_work(now) {
+                Next::Now(()) => return Next::Now(()),
+                Next::NotBefore(x) => earliest.observe(x),
+                Next::None => {}
+            }
+        }
+
+        match earliest.value() {
+            Some(x) => Next::NotBefore(x),
+            None => Next::None,
+        }
+    }
+
+    /// next task requested by a user, ignoring the automatic tasks and polls
+    ///
+    /// Commands run first, regardless of the READ requests queued by other associations
+    pub(crate) fn next_user_task(&mut self) -> Option<AssociationTask> {
+        self.next_user_task_impl(true)
+            .or_else(|| self.next_user_task_impl(false))
+    }
+
+    fn next_user_task_impl(&mut self, commands_only: bool) -> Option<AssociationTask> {
         for (index, address) in self.priority.iter().enumerate() {
             if let Some(association) = self.map.get_mut(address) {
+                if commands_only
+                    && !matches!(association.request_queue.front(), Some(x) if x.task.is_command())
+                {
+                    continue;
+                }
                 // Check for priority task
-                if let Some(task) = association.priority_task() {
-                    // just before returning, move this session to last priority
-                    if let Some(x) = self.priority.remove(index) {
-                        self.priority.push_back(x);
-                    }
-
-                    let task = AssociationTask::new(association.address, task);
-                    return Next::Now(task);
+                if let Some(queued) = association.priority_task() {
+                    let task = AssociationTask::new(
+                        association.address,
+                        queued.task,
+                        queued.correlation_id,
+                    );
+                    // just before returning, move this session to last position within its priority
+                    self.requeue(index);
+                    return Some(task);
                 }
             }
         }
+        None
+    }
+
+    pub(crate) fn next_task(&mut self) -> Next<AssociationTask> {
+        // Check for priority task
+        if let Some(task) = self.next_user_task() {
+            return Next::Now(task);
+        }
 
         // Check for non-priority tasks
-        let now = Instant::now();
+        let now = self.clock.now();
         let mut earliest = Smallest::<Instant>::new();
 
         for (index, address) in self.priority.iter().enumerate() {
             if let Some(association) = self.map.get_mut(address) {
                 match association.next_task(now) {
                     Next::Now(task) => {
-                        // just before returning, move this session to last priority
-                        if let Some(x) = self.priority.remove(index) {
-                            self.priority.push_back(x);
-                        }
-
-                        let task = AssociationTask::new(association.address, task);
+                        let task = AssociationTask::new(association.address, task, None);
+                        // just before returning, move this session to last position within its priority
+                        self.requeue(index);
                         return Next::Now(task);
                     }
                     Next::NotBefore(x) => earliest.observe(x),
@@ -739,3 +1531,71 @@ impl AssociationMap {
         Next::None
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::link::error::LinkError;
+    use crate::master::{DefaultAssociationHandler, NullReadHandler};
+
+    fn association() -> Association {
+        Association::new(
+            EndpointAddress::from(1024).unwrap(),
+            AssociationConfig::default(),
+            NullReadHandler::boxed(),
+            DefaultAssociationHandler::boxed(),
+            Clock::default(),
+        )
+    }
+
+    #[test]
+    fn forgets_reception_time_when_a_task_starts() {
+        let mut association = association();
+        association.on_fragment_received(RxTimestamp::now(&Clock::default()));
+
+        assert!(matches!(
+            association.next_task(Clock::default().now()),
+            Next::Now(_)
+        ));
+        assert_eq!(association.fragment_received, None);
+    }
+
+    #[test]
+    fn forgets_reception_time_when_reset() {
+        let mut association = association();
+        association.on_fragment_received(RxTimestamp::now(&Clock::default()));
+
+        association.reset(RunError::Link(LinkError::Stdio(
+            std::io::ErrorKind::ConnectionReset,
+        )));
+        assert_eq!(association.fragment_received, None);
+    }
+
+    #[test]
+    fn builder_rejects_inconsistent_settings() {
+        assert!(AssociationConfigBuilder::new().build().is_ok());
+        assert_eq!(
+            AssociationConfigBuilder::new()
+                .with_auto_tasks_retry_strategy(RetryStrategy::new(
+                    Duration::from_secs(10),
+                    Duration::from_secs(1)
+                ))
+                .build()
+                .unwrap_err(),
+            ConfigError::InvalidRetryStrategy {
+                field: "auto_tasks_retry_strategy",
+                min_delay: Duration::from_secs(10),
+                max_delay: Duration::from_secs(1),
+            }
+        );
+        assert_eq!(
+            AssociationConfigBuilder::new()
+                .with_max_queued_user_requests(0)
+                .build()
+                .unwrap_err(),
+            ConfigError::ZeroLimit {
+                field: "max_queued_user_requests"
+            }
+        );
+    }
+}
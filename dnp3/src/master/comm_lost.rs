@@ -0,0 +1,514 @@
+use std::collections::BTreeMap;
+
+use crate::app::measurement::*;
+use crate::app::variations::Variation;
+use crate::app::*;
+use crate::link::RxTimestamp;
+use crate::master::{HeaderInfo, ReadHandler, ReadType};
+
+/// types whose last value is cached so that it can be reported again with COMM_LOST
+trait Cached: Copy {
+    fn with_flags(self, flags: Flags) -> Self;
+    fn flags(&self) -> Flags;
+}
+
+macro_rules! impl_cached {
+    ($($name:ident),*) => {
+        $(
+            impl Cached for $name {
+                fn with_flags(self, flags: Flags) -> Self {
+                    Self { flags, ..self }
+                }
+
+                fn flags(&self) -> Flags {
+                    self.flags
+                }
+            }
+        )*
+    };
+}
+
+impl_cached!(
+    Binary,
+    DoubleBitBinary,
+    BinaryOutputStatus,
+    Counter,
+    FrozenCounter,
+    Analog,
+    AnalogOutputStatus
+);
+
+#[derive(Copy, Clone)]
+struct Entry<T> {
+    value: T,
+    // not updated since communication was lost
+    stale: bool,
+}
+
+struct Points<T> {
+    variation: Variation,
+    map: BTreeMap<u16, Entry<T>>,
+}
+
+impl<T> Points<T>
+where
+    T: Cached,
+{
+    fn new(variation: Variation) -> Self {
+        Self {
+            variation,
+            map: BTreeMap::new(),
+        }
+    }
+
+    fn update(&mut self, value: T, index: u16) {
+        self.map.insert(
+            index,
+            Entry {
+                value,
+                stale: false,
+            },
+        );
+    }
+
+    fn any_stale(&self) -> bool {
+        self.map.values().any(|x| x.stale)
+    }
+
+    fn mark_stale(&mut self) {
+        for entry in self.map.values_mut() {
+            entry.stale = true;
+        }
+    }
+
+    /// report the stale values with COMM_LOST set or with the flags reported by the outstation
+    fn report(
+        &mut self,
+        comm_lost: bool,
+        handle: impl FnOnce(HeaderInfo, &mut dyn Iterator<Item = (T, u16)>),
+    ) {
+        if !self.any_stale() {
+            return;
+        }
+
+        let mut iter = self.map.iter().filter(|(_, x)| x.stale).map(|(index, x)| {
+            let value = if comm_lost {
+                x.value.with_flags(x.value.flags() | Flags::COMM_LOST)
+            } else {
+                x.value
+            };
+            (value, *index)
+        });
+        handle(
+            HeaderInfo::new(self.variation, QualifierCode::Range16),
+            &mut iter,
+        );
+
+        if !comm_lost {
+            for entry in self.map.values_mut() {
+                entry.stale = false;
+            }
+        }
+    }
+}
+
+/// last values of the points read by an association, reported again with COMM_LOST when
+/// communication with the outstation is lost
+pub(crate) struct PointCache {
+    comm_lost: bool,
+    binary: Points<Binary>,
+    double_bit_binary: Points<DoubleBitBinary>,
+    binary_output_status: Points<BinaryOutputStatus>,
+    counter: Points<Counter>,
+    frozen_counter: Points<FrozenCounter>,
+    analog: Points<Analog>,
+    analog_output_status: Points<AnalogOutputStatus>,
+}
+
+impl PointCache {
+    pub(crate) fn new() -> Self {
+        Self {
+            comm_lost: false,
+            binary: Points::new(Variation::Group1Var2),
+            double_bit_binary: Points::new(Variation::Group3Var2),
+            binary_output_status: Points::new(Variation::Group10Var2),
+            counter: Points::new(Variation::Group20Var1),
+            frozen_counter: Points::new(Variation::Group21Var1),
+            analog: Points::new(Variation::Group30Var6),
+            analog_output_status: Points::new(Variation::Group40Var4),
+        }
+    }
+
+    /// wrap a handler so that the values passed to it are also recorded in the cache
+    pub(crate) fn handler<'a>(&'a mut self, inner: &'a mut dyn ReadHandler) -> CachingHandler<'a> {
+        CachingHandler { cache: self, inner }
+    }
+
+    /// report every cached value with COMM_LOST set, unless it has already been reported
+    pub(crate) fn on_comm_lost(&mut self, handler: &mut dyn ReadHandler) {
+        if self.comm_lost {
+            return;
+        }
+        self.comm_lost = true;
+        self.binary.mark_stale();
+        self.double_bit_binary.mark_stale();
+        self.binary_output_status.mark_stale();
+        self.counter.mark_stale();
+        self.frozen_counter.mark_stale();
+        self.analog.mark_stale();
+        self.analog_output_status.mark_stale();
+        self.report(handler);
+    }
+
+    /// report the values that haven't been updated since communication was lost without COMM_LOST
+    pub(crate) fn on_comm_restored(&mut self, handler: &mut dyn ReadHandler) {
+        if !self.comm_lost {
+            return;
+        }
+        self.comm_lost = false;
+        self.report(handler);
+    }
+
+    fn any_stale(&self) -> bool {
+        self.binary.any_stale()
+            || self.double_bit_binary.any_stale()
+            || self.binary_output_status.any_stale()
+            || self.counter.any_stale()
+            || self.frozen_counter.any_stale()
+            || self.analog.any_stale()
+            || self.analog_output_status.any_stale()
+    }
+
+    fn report(&mut self, handler: &mut dyn ReadHandler) {
+        if !self.any_stale() {
+            return;
+        }
+
+        let comm_lost = self.comm_lost;
+        let header = ResponseHeader::new(
+            ControlField::single_response(Sequence::default()),
+            ResponseFunction::Response,
+            Iin::default(),
+        );
+        handler.begin_fragment(ReadType::CommLost, header);
+        self.binary
+            .report(comm_lost, |info, iter| handler.handle_binary(info, iter));
+        self.double_bit_binary.report(comm_lost, |info, iter| {
+            handler.handle_double_bit_binary(info, iter)
+        });
+        self.binary_output_status.report(comm_lost, |info, iter| {
+            handler.handle_binary_output_status(info, iter)
+        });
+        self.counter
+            .report(comm_lost, |info, iter| handler.handle_counter(info, iter));
+        self.frozen_counter.report(comm_lost, |info, iter| {
+            handler.handle_frozen_counter(info, iter)
+        });
+        self.analog
+            .report(comm_lost, |info, iter| handler.handle_analog(info, iter));
+        self.analog_output_status.report(comm_lost, |info, iter| {
+            handler.handle_analog_output_status(info, iter)
+        });
+        handler.end_fragment(ReadType::CommLost, header);
+    }
+}
+
+/// records the values of every header in the cache before passing them to the inner handler
+pub(crate) struct CachingHandler<'a> {
+    cache: &'a mut PointCache,
+    inner: &'a mut dyn ReadHandler,
+}
+
+fn forward<T>(
+    points: &mut Points<T>,
+    info: HeaderInfo,
+    iter: &mut dyn Iterator<Item = (T, u16)>,
+    handle: impl FnOnce(HeaderInfo, &mut dyn Iterator<Item = (T, u16)>),
+) where
+    T: Cached,
+{
+    let mut iter = iter.inspect(|(value, index)| points.update(*value, *index));
+    handle(info, &mut iter);
+    // record the values that the handler didn't iterate over
+    for _ in iter {}
+}
+
+impl<'a> ReadHandler for CachingHandler<'a> {
+    fn begin_fragment(&mut self, read_type: ReadType, header: ResponseHeader) {
+        self.inner.begin_fragment(read_type, header);
+    }
+
+    fn fragment_received(&mut self, timestamp: RxTimestamp) {
+        self.inner.fragment_received(timestamp);
+    }
+
+    fn fragment_correlation_id(&mut self, id: u64) {
+        self.inner.fragment_correlation_id(id);
+    }
+
+    fn end_fragment(&mut self, read_type: ReadType, header: ResponseHeader) {
+        self.inner.end_fragment(read_type, header);
+    }
+
+    fn handle_binary(&mut self, info: HeaderInfo, iter: &mut dyn Iterator<Item = (Binary, u16)>) {
+        let inner = &mut self.inner;
+        forward(&mut self.cache.binary, info, iter, |info, iter| {
+            inner.handle_binary(info, iter)
+        });
+    }
+
+    fn handle_double_bit_binary(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (DoubleBitBinary, u16)>,
+    ) {
+        let inner = &mut self.inner;
+        forward(
+            &mut self.cache.double_bit_binary,
+            info,
+            iter,
+            |info, iter| inner.handle_double_bit_binary(info, iter),
+        );
+    }
+
+    fn handle_binary_output_status(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (BinaryOutputStatus, u16)>,
+    ) {
+        let inner = &mut self.inner;
+        forward(
+            &mut self.cache.binary_output_status,
+            info,
+            iter,
+            |info, iter| inner.handle_binary_output_status(info, iter),
+        );
+    }
+
+    fn handle_counter(&mut self, info: HeaderInfo, iter: &mut dyn Iterator<Item = (Counter, u16)>) {
+        let inner = &mut self.inner;
+        forward(&mut self.cache.counter, info, iter, |info, iter| {
+            inner.handle_counter(info, iter)
+        });
+    }
+
+    fn handle_frozen_counter(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (FrozenCounter, u16)>,
+    ) {
+        let inner = &mut self.inner;
+        forward(&mut self.cache.frozen_counter, info, iter, |info, iter| {
+            inner.handle_frozen_counter(info, iter)
+        });
+    }
+
+    fn handle_analog(&mut self, info: HeaderInfo, iter: &mut dyn Iterator<Item = (Analog, u16)>) {
+        let inner = &mut self.inner;
+        forward(&mut self.cache.analog, info, iter, |info, iter| {
+            inner.handle_analog(info, iter)
+        });
+    }
+
+    fn handle_analog_output_status(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (AnalogOutputStatus, u16)>,
+    ) {
+        let inner = &mut self.inner;
+        forward(
+            &mut self.cache.analog_output_status,
+            info,
+            iter,
+            |info, iter| inner.handle_analog_output_status(info, iter),
+        );
+    }
+
+    fn handle_octet_string<'b>(
+        &mut self,
+        info: HeaderInfo,
+        iter: &'b mut dyn Iterator<Item = (Bytes<'b>, u16)>,
+    ) {
+        self.inner.handle_octet_string(info, iter);
+    }
+
+    fn handle_unknown_header(&mut self, header: UnknownHeader) {
+        self.inner.handle_unknown_header(header);
+    }
+
+    fn handle_vendor_header(&mut self, header: VendorHeader) {
+        self.inner.handle_vendor_header(header);
+    }
+
+    fn handle_indexed_time(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (IndexedTime, u16)>,
+    ) {
+        self.inner.handle_indexed_time(info, iter);
+    }
+
+    fn handle_unsigned_integer(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (UnsignedInteger, u16)>,
+    ) {
+        self.inner.handle_unsigned_integer(info, iter);
+    }
+
+    fn handle_device_attribute(&mut self, info: HeaderInfo, attr: Attribute) {
+        self.inner.handle_device_attribute(info, attr);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct AnalogRecorder {
+        fragments: Vec<ReadType>,
+        values: Vec<(Analog, u16)>,
+    }
+
+    impl ReadHandler for AnalogRecorder {
+        fn begin_fragment(&mut self, read_type: ReadType, _header: ResponseHeader) {
+            self.fragments.push(read_type);
+        }
+
+        fn end_fragment(&mut self, _read_type: ReadType, _header: ResponseHeader) {}
+
+        fn handle_binary(
+            &mut self,
+            _info: HeaderInfo,
+            _iter: &mut dyn Iterator<Item = (Binary, u16)>,
+        ) {
+        }
+
+        fn handle_double_bit_binary(
+            &mut self,
+            _info: HeaderInfo,
+            _iter: &mut dyn Iterator<Item = (DoubleBitBinary, u16)>,
+        ) {
+        }
+
+        fn handle_binary_output_status(
+            &mut self,
+            _info: HeaderInfo,
+            _iter: &mut dyn Iterator<Item = (BinaryOutputStatus, u16)>,
+        ) {
+        }
+
+        fn handle_counter(
+            &mut self,
+            _info: HeaderInfo,
+            _iter: &mut dyn Iterator<Item = (Counter, u16)>,
+        ) {
+        }
+
+        fn handle_frozen_counter(
+            &mut self,
+            _info: HeaderInfo,
+            _iter: &mut dyn Iterator<Item = (FrozenCounter, u16)>,
+        ) {
+        }
+
+        fn handle_analog(
+            &mut self,
+            _info: HeaderInfo,
+            iter: &mut dyn Iterator<Item = (Analog, u16)>,
+        ) {
+            self.values.extend(iter);
+        }
+
+        fn handle_analog_output_status(
+            &mut self,
+            _info: HeaderInfo,
+            _iter: &mut dyn Iterator<Item = (AnalogOutputStatus, u16)>,
+        ) {
+        }
+
+        fn handle_octet_string<'a>(
+            &mut self,
+            _info: HeaderInfo,
+            _iter: &'a mut dyn Iterator<Item = (Bytes<'a>, u16)>,
+        ) {
+        }
+    }
+
+    fn analog(value: f64) -> Analog {
+        Analog {
+            value,
+            flags: Flags::ONLINE,
+            time: None,
+        }
+    }
+
+    fn update(cache: &mut PointCache, values: &[(Analog, u16)]) {
+        let mut inner = AnalogRecorder::default();
+        cache.handler(&mut inner).handle_analog(
+            HeaderInfo::new(Variation::Group30Var6, QualifierCode::Range16),
+            &mut values.iter().copied(),
+        );
+        assert_eq!(inner.values, values);
+    }
+
+    #[test]
+    fn reports_cached_values_with_comm_lost() {
+        let mut cache = PointCache::new();
+        update(&mut cache, &[(analog(1.0), 0), (analog(2.0), 1)]);
+
+        let mut handler = AnalogRecorder::default();
+        cache.on_comm_lost(&mut handler);
+        // reported once
+        cache.on_comm_lost(&mut handler);
+
+        let lost = Flags::ONLINE | Flags::COMM_LOST;
+        assert_eq!(handler.fragments, [ReadType::CommLost]);
+        assert_eq!(
+            handler.values,
+            [
+                (
+                    Analog {
+                        flags: lost,
+                        ..analog(1.0)
+                    },
+                    0
+                ),
+                (
+                    Analog {
+                        flags: lost,
+                        ..analog(2.0)
+                    },
+                    1
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn restores_values_that_were_not_updated() {
+        let mut cache = PointCache::new();
+        update(&mut cache, &[(analog(1.0), 0), (analog(2.0), 1)]);
+        cache.on_comm_lost(&mut AnalogRecorder::default());
+        update(&mut cache, &[(analog(3.0), 0)]);
+
+        let mut handler = AnalogRecorder::default();
+        cache.on_comm_restored(&mut handler);
+        // reported once
+        cache.on_comm_restored(&mut handler);
+
+        assert_eq!(handler.fragments, [ReadType::CommLost]);
+        assert_eq!(handler.values, [(analog(2.0), 1)]);
+    }
+
+    #[test]
+    fn nothing_is_reported_without_values() {
+        let mut handler = AnalogRecorder::default();
+        let mut cache = PointCache::new();
+        cache.on_comm_lost(&mut handler);
+        cache.on_comm_restored(&mut handler);
+
+        assert!(handler.fragments.is_empty());
+    }
+}
@@ -1,15 +1,14 @@
 use std::error::Error;
 
 use crate::app::control::CommandStatus;
+use crate::app::{FunctionCode, ObjectParseError, ResponseHeaderError, Sequence, Shutdown};
 use crate::app::{Iin, Iin2};
-use crate::app::{ObjectParseError, Shutdown};
 use crate::link::error::LinkError;
 use crate::link::EndpointAddress;
 use crate::master::association::NoAssociation;
 use crate::master::session::{RunError, StateChange};
 use crate::tokio::sync::mpsc::error::SendError;
 use crate::tokio::sync::oneshot::error::RecvError;
-use crate::transport::TransportResponseError;
 use crate::util::cursor::WriteError;
 
 /// Errors that can occur when adding an association
@@ -21,29 +20,60 @@ pub enum AssociationError {
     DuplicateAddress(EndpointAddress),
 }
 
+/// Outstation and request to which a task error applies
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RequestContext {
+    /// Address of the outstation
+    pub address: EndpointAddress,
+    /// Function code of the request, `None` for link-layer requests
+    pub function: Option<FunctionCode>,
+    /// Sequence number of the request, `None` for link-layer requests
+    pub seq: Option<Sequence>,
+}
+
+impl RequestContext {
+    pub(crate) fn request(address: EndpointAddress, function: FunctionCode, seq: Sequence) -> Self {
+        Self {
+            address,
+            function: Some(function),
+            seq: Some(seq),
+        }
+    }
+
+    pub(crate) fn link(address: EndpointAddress) -> Self {
+        Self {
+            address,
+            function: None,
+            seq: None,
+        }
+    }
+}
+
 /// Errors that can occur while executing a master task
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum TaskError {
     /// There are too many user requests queued
     TooManyRequests,
+    /// The request was removed from a full queue to make room for a newer request
+    Dropped,
     /// An error occurred at the link level
     Link(LinkError),
-    /// An error occurred at the transport/app level
-    Transport,
+    /// The header of a response could not be parsed or was invalid
+    Transport(ResponseHeaderError),
     /// A response to the task's request was malformed
     MalformedResponse(ObjectParseError),
     /// The response contains headers that don't match the request
     UnexpectedResponseHeaders,
     /// Non-final response not requesting confirmation
-    NonFinWithoutCon,
+    NonFinWithoutCon(RequestContext),
     /// Received a non-FIR response when expecting the FIR bit
-    NeverReceivedFir,
+    NeverReceivedFir(RequestContext),
     /// Received FIR bit after already receiving FIR
-    UnexpectedFir,
+    UnexpectedFir(RequestContext),
     /// Received a multi-fragmented response when expecting FIR/FIN
-    MultiFragmentResponse,
+    MultiFragmentResponse(RequestContext),
     /// The response timed-out
-    ResponseTimeout,
+    ResponseTimeout(RequestContext),
     /// Insufficient buffer space to serialize the request
     WriteError,
     /// The requested association does not exist (not configured)
@@ -54,6 +84,10 @@ pub enum TaskError {
     Shutdown,
     /// The master was disabled
     Disabled,
+    /// The READ was interrupted to run a queued command before its response was complete
+    ///
+    /// This only occurs with [ReadPreemption::Cancel](crate::master::ReadPreemption::Cancel)
+    Preempted,
 }
 
 /// Errors that can occur when adding/modifying polls
@@ -103,6 +137,20 @@ pub enum TimeSyncError {
     IinError(Iin2),
 }
 
+impl TaskError {
+    /// Outstation and request to which the error applies, if known
+    pub fn context(&self) -> Option<RequestContext> {
+        match self {
+            TaskError::NonFinWithoutCon(x)
+            | TaskError::NeverReceivedFir(x)
+            | TaskError::UnexpectedFir(x)
+            | TaskError::MultiFragmentResponse(x)
+            | TaskError::ResponseTimeout(x) => Some(*x),
+            _ => None,
+        }
+    }
+}
+
 impl TimeSyncError {
     pub(crate) fn from_iin(iin: Iin) -> Result<(), TimeSyncError> {
         if iin.iin1.get_need_time() {
@@ -115,6 +163,15 @@ impl TimeSyncError {
     }
 }
 
+/// Errors that can occur when writing objects to an outstation
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WriteRequestError {
+    /// Error occurred during task execution
+    Task(TaskError),
+    /// Outstation returned an IIN.2 error
+    IinError(Iin2),
+}
+
 /// Parent error type for command tasks
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum CommandError {
@@ -124,6 +181,20 @@ pub enum CommandError {
     Response(CommandResponseError),
 }
 
+impl std::fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "outstation: {}", self.address)?;
+        match self.function {
+            Some(function) => write!(f, ", function: {:?}", function)?,
+            None => f.write_str(", link status request")?,
+        }
+        if let Some(seq) = self.seq {
+            write!(f, ", seq: {}", seq.value())?;
+        }
+        Ok(())
+    }
+}
+
 impl std::fmt::Display for AssociationError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -190,30 +261,42 @@ impl std::fmt::Display for TaskError {
             TaskError::TooManyRequests => {
                 f.write_str("the number of queued user requests has reached the configured limit")
             }
-            TaskError::Link(_) => f.write_str("link-layer or I/O error"),
-            TaskError::Transport => f.write_str("malformed response"),
+            TaskError::Dropped => f.write_str(
+                "the request was removed from the full queue to make room for a newer request",
+            ),
+            TaskError::Link(err) => write!(f, "link-layer or I/O error: {}", err),
+            TaskError::Transport(err) => write!(f, "malformed response: {}", err),
             TaskError::MalformedResponse(err) => write!(f, "malformed response: {}", err),
             TaskError::UnexpectedResponseHeaders => {
                 f.write_str("response contains headers that don't match the request")
             }
-            TaskError::NonFinWithoutCon => {
-                f.write_str("outstation responses with FIN == 0 must request confirmation")
-            }
-            TaskError::NeverReceivedFir => {
-                f.write_str("received non-FIR response before receiving FIR")
+            TaskError::NonFinWithoutCon(x) => write!(
+                f,
+                "outstation responses with FIN == 0 must request confirmation ({})",
+                x
+            ),
+            TaskError::NeverReceivedFir(x) => {
+                write!(f, "received non-FIR response before receiving FIR ({})", x)
             }
-            TaskError::UnexpectedFir => {
-                f.write_str("received FIR bit after already receiving FIR bit")
+            TaskError::UnexpectedFir(x) => write!(
+                f,
+                "received FIR bit after already receiving FIR bit ({})",
+                x
+            ),
+            TaskError::MultiFragmentResponse(x) => {
+                write!(f, "received unexpected multi-fragment response ({})", x)
             }
-            TaskError::MultiFragmentResponse => {
-                f.write_str("received unexpected multi-fragment response")
+            TaskError::ResponseTimeout(x) => {
+                write!(f, "no response received within timeout ({})", x)
             }
-            TaskError::ResponseTimeout => f.write_str("no response received within timeout"),
             TaskError::WriteError => {
                 f.write_str("unable to serialize the task's request (insufficient buffer space)")
             }
             TaskError::Shutdown => f.write_str("the master was shutdown while executing the task"),
             TaskError::Disabled => f.write_str("the master was disabled while executing the task"),
+            TaskError::Preempted => f.write_str(
+                "the READ was interrupted to run a command before its response was complete",
+            ),
             TaskError::NoConnection => f.write_str("no connection"),
             TaskError::NoSuchAssociation(x) => write!(f, "no association with address: {}", x),
         }
@@ -241,6 +324,17 @@ impl std::fmt::Display for TimeSyncError {
     }
 }
 
+impl std::fmt::Display for WriteRequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            WriteRequestError::Task(err) => write!(f, "{}", err),
+            WriteRequestError::IinError(iin2) => {
+                write!(f, "outstation indicated an error: {}", iin2)
+            }
+        }
+    }
+}
+
 impl From<WriteError> for TaskError {
     fn from(_: WriteError) -> Self {
         TaskError::WriteError
@@ -253,9 +347,9 @@ impl From<LinkError> for TaskError {
     }
 }
 
-impl From<TransportResponseError> for TaskError {
-    fn from(_: TransportResponseError) -> Self {
-        TaskError::Transport
+impl From<ResponseHeaderError> for TaskError {
+    fn from(err: ResponseHeaderError) -> Self {
+        TaskError::Transport(err)
     }
 }
 
@@ -270,6 +364,10 @@ impl From<RunError> for TaskError {
         match x {
             RunError::State(StateChange::Shutdown) => TaskError::Shutdown,
             RunError::State(StateChange::Disable) => TaskError::Disabled,
+            RunError::State(StateChange::Disconnect) => TaskError::NoConnection,
+            RunError::State(StateChange::IdleTimeout) => TaskError::NoConnection,
+            #[cfg(feature = "tcp")]
+            RunError::State(StateChange::ReplaceIo) => TaskError::NoConnection,
             RunError::Link(x) => TaskError::Link(x),
         }
     }
@@ -311,6 +409,12 @@ impl From<TaskError> for TimeSyncError {
     }
 }
 
+impl From<TaskError> for WriteRequestError {
+    fn from(err: TaskError) -> Self {
+        WriteRequestError::Task(err)
+    }
+}
+
 impl From<RecvError> for AssociationError {
     fn from(_: RecvError) -> Self {
         AssociationError::Shutdown
@@ -321,6 +425,10 @@ impl From<StateChange> for TaskError {
     fn from(x: StateChange) -> Self {
         match x {
             StateChange::Disable => TaskError::Disabled,
+            StateChange::Disconnect => TaskError::NoConnection,
+            StateChange::IdleTimeout => TaskError::NoConnection,
+            #[cfg(feature = "tcp")]
+            StateChange::ReplaceIo => TaskError::NoConnection,
             StateChange::Shutdown => TaskError::Shutdown,
         }
     }
@@ -344,6 +452,12 @@ impl From<RecvError> for TimeSyncError {
     }
 }
 
+impl From<RecvError> for WriteRequestError {
+    fn from(_: RecvError) -> Self {
+        WriteRequestError::Task(TaskError::Shutdown)
+    }
+}
+
 impl<T> From<SendError<T>> for Shutdown {
     fn from(_: SendError<T>) -> Self {
         Shutdown
@@ -374,6 +488,12 @@ impl From<Shutdown> for TimeSyncError {
     }
 }
 
+impl From<Shutdown> for WriteRequestError {
+    fn from(_: Shutdown) -> Self {
+        WriteRequestError::Task(TaskError::Shutdown)
+    }
+}
+
 impl From<Shutdown> for PollError {
     fn from(_: Shutdown) -> Self {
         PollError::Shutdown
@@ -381,8 +501,90 @@ impl From<Shutdown> for PollError {
 }
 
 impl Error for AssociationError {}
-impl Error for TaskError {}
 impl Error for PollError {}
-impl Error for CommandError {}
-impl Error for CommandResponseError {}
-impl Error for TimeSyncError {}
+
+impl Error for TaskError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TaskError::Link(err) => Some(err),
+            TaskError::Transport(err) => Some(err),
+            TaskError::MalformedResponse(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// errors that wrap a task error display it as is, so they also share its source
+impl Error for CommandError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CommandError::Task(err) => err.source(),
+            CommandError::Response(err) => err.source(),
+        }
+    }
+}
+
+impl Error for CommandResponseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CommandResponseError::Request(err) => err.source(),
+            _ => None,
+        }
+    }
+}
+
+impl Error for TimeSyncError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TimeSyncError::Task(err) => err.source(),
+            _ => None,
+        }
+    }
+}
+
+impl Error for WriteRequestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WriteRequestError::Task(err) => err.source(),
+            WriteRequestError::IinError(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::link::error::FrameError;
+
+    use super::*;
+
+    #[test]
+    fn timeout_reports_outstation_and_request() {
+        let context = RequestContext::request(
+            EndpointAddress::from(1024).unwrap(),
+            FunctionCode::Read,
+            Sequence::new(3),
+        );
+        let err = TaskError::ResponseTimeout(context);
+        assert_eq!(err.context(), Some(context));
+        assert_eq!(
+            err.to_string(),
+            "no response received within timeout (outstation: 1024, function: Read, seq: 3)"
+        );
+    }
+
+    #[test]
+    fn wrapped_errors_expose_the_underlying_cause() {
+        let err = CommandError::Task(TaskError::Link(LinkError::BadFrame(
+            FrameError::BadHeaderCrc,
+        )));
+        let source = err.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<LinkError>(),
+            Some(&LinkError::BadFrame(FrameError::BadHeaderCrc))
+        );
+        assert_eq!(
+            source.source().unwrap().downcast_ref::<FrameError>(),
+            Some(&FrameError::BadHeaderCrc)
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use crate::app::gen::count::CountVariation;
 use crate::app::measurement::*;
-use crate::app::parse::parser::{HeaderCollection, HeaderDetails, ObjectHeader};
+use crate::app::parse::parser::{HeaderCollection, HeaderDetails, ObjectHeader, ParsedHeader};
 use crate::app::variations::*;
 use crate::app::ResponseHeader;
+use crate::link::RxTimestamp;
 use crate::master::handle::ReadHandler;
 use crate::master::ReadType;
 
@@ -12,6 +13,8 @@ pub(crate) fn extract_measurements(
     read_type: ReadType,
     header: ResponseHeader,
     objects: HeaderCollection,
+    received: Option<RxTimestamp>,
+    correlation_id: Option<u64>,
     handler: &mut dyn ReadHandler,
 ) {
     fn extract_cto_g51v1(prev: Option<Time>, item: Option<Group51Var1>) -> Option<Time> {
@@ -69,10 +72,24 @@ pub(crate) fn extract_measurements(
         cto
     }
 
+    if let Some(received) = received {
+        handler.fragment_received(received);
+    }
+    if let Some(id) = correlation_id {
+        handler.fragment_correlation_id(id);
+    }
     handler.begin_fragment(read_type, header);
-    objects
-        .iter()
-        .fold(None, |cto, header| handle(cto, header, handler));
+    objects.iter_all().fold(None, |cto, header| match header {
+        ParsedHeader::Known(header) => handle(cto, header, handler),
+        ParsedHeader::Unknown(header) => {
+            handler.handle_unknown_header(header);
+            cto
+        }
+        ParsedHeader::Vendor(header) => {
+            handler.handle_vendor_header(header);
+            cto
+        }
+    });
     handler.end_fragment(read_type, header);
 }
 
@@ -99,11 +116,17 @@ mod test {
 
     struct MockHandler {
         expected: Vec<Header>,
+        timestamps: Vec<RxTimestamp>,
+        correlation_ids: Vec<u64>,
     }
 
     impl MockHandler {
         fn new() -> Self {
-            Self { expected: vec![] }
+            Self {
+                expected: vec![],
+                timestamps: vec![],
+                correlation_ids: vec![],
+            }
         }
 
         fn is_empty(&self) -> bool {
@@ -116,6 +139,14 @@ mod test {
     }
 
     impl ReadHandler for MockHandler {
+        fn fragment_received(&mut self, timestamp: RxTimestamp) {
+            self.timestamps.push(timestamp);
+        }
+
+        fn fragment_correlation_id(&mut self, id: u64) {
+            self.correlation_ids.push(id);
+        }
+
         fn begin_fragment(&mut self, _read_type: ReadType, _header: ResponseHeader) {}
         fn end_fragment(&mut self, _read_type: ReadType, _header: ResponseHeader) {}
 
@@ -192,6 +223,40 @@ mod test {
         }
     }
 
+    #[test]
+    fn passes_reception_time_to_handler() {
+        let mut handler = MockHandler::new();
+        let objects = HeaderCollection::parse(FunctionCode::Response, &[]).unwrap();
+        let received = RxTimestamp::now(&Clock::default());
+
+        extract_measurements(
+            ReadType::Unsolicited,
+            header(),
+            objects,
+            Some(received),
+            None,
+            &mut handler,
+        );
+        assert_eq!(handler.timestamps, vec![received]);
+        assert!(handler.correlation_ids.is_empty());
+    }
+
+    #[test]
+    fn passes_correlation_id_to_handler() {
+        let mut handler = MockHandler::new();
+        let objects = HeaderCollection::parse(FunctionCode::Response, &[]).unwrap();
+
+        extract_measurements(
+            ReadType::SinglePoll,
+            header(),
+            objects,
+            None,
+            Some(42),
+            &mut handler,
+        );
+        assert_eq!(handler.correlation_ids, vec![42]);
+    }
+
     #[test]
     fn g2v3_without_cto_yields_invalid_time() {
         let mut handler = MockHandler::new();
@@ -211,7 +276,14 @@ mod test {
         );
 
         handler.expect(Header::Binary(vec![expected]));
-        extract_measurements(ReadType::PeriodicPoll, header(), objects, &mut handler);
+        extract_measurements(
+            ReadType::PeriodicPoll,
+            header(),
+            objects,
+            None,
+            None,
+            &mut handler,
+        );
         assert!(handler.is_empty());
     }
 
@@ -238,7 +310,14 @@ mod test {
         );
 
         handler.expect(Header::Binary(vec![expected]));
-        extract_measurements(ReadType::PeriodicPoll, header(), objects, &mut handler);
+        extract_measurements(
+            ReadType::PeriodicPoll,
+            header(),
+            objects,
+            None,
+            None,
+            &mut handler,
+        );
         assert!(handler.is_empty());
     }
 
@@ -265,7 +344,14 @@ mod test {
         );
 
         handler.expect(Header::Binary(vec![expected]));
-        extract_measurements(ReadType::PeriodicPoll, header(), objects, &mut handler);
+        extract_measurements(
+            ReadType::PeriodicPoll,
+            header(),
+            objects,
+            None,
+            None,
+            &mut handler,
+        );
         assert!(handler.is_empty());
     }
 
@@ -292,7 +378,14 @@ mod test {
         );
 
         handler.expect(Header::Binary(vec![expected]));
-        extract_measurements(ReadType::PeriodicPoll, header(), objects, &mut handler);
+        extract_measurements(
+            ReadType::PeriodicPoll,
+            header(),
+            objects,
+            None,
+            None,
+            &mut handler,
+        );
         assert!(handler.is_empty());
     }
 
@@ -319,7 +412,14 @@ mod test {
         );
 
         handler.expect(Header::Binary(vec![expected]));
-        extract_measurements(ReadType::PeriodicPoll, header(), objects, &mut handler);
+        extract_measurements(
+            ReadType::PeriodicPoll,
+            header(),
+            objects,
+            None,
+            None,
+            &mut handler,
+        );
         assert!(handler.is_empty());
     }
 }
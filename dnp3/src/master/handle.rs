@@ -1,12 +1,21 @@
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 
 use crate::app::measurement::*;
 use crate::app::variations::Variation;
 use crate::app::*;
-use crate::decode::DecodeLevel;
-use crate::link::{EndpointAddress, LinkStatusResult};
-use crate::master::association::AssociationConfig;
-use crate::master::error::{AssociationError, CommandError, PollError, TaskError, TimeSyncError};
+use crate::decode::{
+    DecodeEventHandler, DecodeLevel, DecodeSink, RecentFragment, TracingConfig, WireTap,
+};
+use crate::link::statistics::LinkCounters;
+use crate::link::{
+    EndpointAddress, LinkActivity, LinkConfig, LinkStatistics, LinkStatusResult, RxTimestamp,
+    TransportConfig, TransportStatistics, UnknownDestinationPolicy,
+};
+use crate::master::association::{AssociationConfig, SchedulingConfig};
+use crate::master::error::{
+    AssociationError, CommandError, PollError, TaskError, TimeSyncError, WriteRequestError,
+};
 use crate::master::messages::{AssociationMsg, AssociationMsgType, MasterMsg, Message};
 use crate::master::poll::{PollHandle, PollMsg};
 use crate::master::request::{CommandHeaders, CommandMode, ReadRequest, TimeSyncProcedure};
@@ -15,7 +24,8 @@ use crate::master::tasks::command::CommandTask;
 use crate::master::tasks::read::SingleReadTask;
 use crate::master::tasks::restart::{RestartTask, RestartType};
 use crate::master::tasks::time::TimeSyncTask;
-use crate::master::tasks::Task;
+use crate::master::tasks::write::WriteTask;
+use crate::master::tasks::{Task, UserTask};
 use crate::util::channel::Sender;
 
 /// Handle to a master communication channel. This handle controls
@@ -26,6 +36,7 @@ use crate::util::channel::Sender;
 #[derive(Debug, Clone)]
 pub struct MasterChannel {
     sender: Sender<Message>,
+    link_counters: Arc<LinkCounters>,
 }
 
 /// Handle used to make requests against a particular outstation associated with the master channel
@@ -33,10 +44,12 @@ pub struct MasterChannel {
 pub struct AssociationHandle {
     address: EndpointAddress,
     master: MasterChannel,
+    correlation_id: Option<u64>,
 }
 
 /// Configuration for a MasterChannel
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MasterChannelConfig {
     /// Local DNP3 master address
     pub master_address: EndpointAddress,
@@ -44,14 +57,42 @@ pub struct MasterChannelConfig {
     pub decode_level: DecodeLevel,
     /// Response timeout
     pub response_timeout: Timeout,
+    /// How associations sharing the channel are scheduled
+    pub scheduling: SchedulingConfig,
+    /// Link-layer retries and timeouts
+    pub link: LinkConfig,
+    /// Transport segment size and reassembly limits
+    pub transport: TransportConfig,
+    /// How frames sent to other destinations are handled
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub unknown_destination: UnknownDestinationPolicy,
     /// TX buffer size
     ///
-    /// Must be at least 249.
+    /// Must be between 249 and 65535. Requests larger than 2048 bytes may be rejected by
+    /// outstations that don't support larger fragments.
     pub tx_buffer_size: usize,
     /// RX buffer size
     ///
-    /// Must be at least 2048.
+    /// Must be between 2048 and 65535. Outstations that support larger fragments report their
+    /// maximum transmit fragment size in device attribute g0v240.
     pub rx_buffer_size: usize,
+    /// Clock used for timeouts, retry delays, polls, and keep-alives
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub clock: Clock,
+    /// Custom spans and redaction of the decoded output
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub tracing: TracingConfig,
+    /// Pool from which the TX and RX buffers are obtained when needed and to which they are
+    /// released when the channel is idle
+    ///
+    /// A value of `None` allocates the buffers when the channel is created and keeps them
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub buffer_pool: Option<Arc<BufferPool>>,
+    /// Runtime onto which the `spawn_*` functions and servers spawn the channel
+    ///
+    /// A value of `None` spawns the channel onto the current runtime
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub spawner: Option<Arc<Spawner>>,
 }
 
 impl MasterChannelConfig {
@@ -61,15 +102,112 @@ impl MasterChannelConfig {
             master_address,
             decode_level: DecodeLevel::nothing(),
             response_timeout: Timeout::default(),
+            scheduling: SchedulingConfig::default(),
+            link: LinkConfig::default(),
+            transport: TransportConfig::default(),
+            unknown_destination: UnknownDestinationPolicy::default(),
             tx_buffer_size: MasterSession::DEFAULT_TX_BUFFER_SIZE,
             rx_buffer_size: MasterSession::DEFAULT_RX_BUFFER_SIZE,
+            clock: Clock::default(),
+            tracing: TracingConfig::default(),
+            buffer_pool: None,
+            spawner: None,
         }
     }
 }
 
 impl MasterChannel {
-    pub(crate) fn new(sender: Sender<Message>) -> Self {
-        Self { sender }
+    pub(crate) fn new(sender: Sender<Message>, link_counters: Arc<LinkCounters>) -> Self {
+        Self {
+            sender,
+            link_counters,
+        }
+    }
+
+    /// get a snapshot of the link-layer counters of the channel
+    pub fn get_link_statistics(&self) -> LinkStatistics {
+        self.link_counters.snapshot()
+    }
+
+    /// get a snapshot of the transport-layer counters of the channel
+    pub fn get_transport_statistics(&self) -> TransportStatistics {
+        self.link_counters.transport_snapshot()
+    }
+
+    /// get the age of the most recent link-layer activity from an outstation
+    ///
+    /// returns `None` if no valid frame has been received from the address
+    pub fn get_link_activity(&self, address: EndpointAddress) -> Option<LinkActivity> {
+        self.link_counters.activity(address)
+    }
+
+    /// send the decoded link, transport, and application layer output of the channel to a sink
+    ///
+    /// `None` reverts to logging the output with `tracing`
+    pub fn set_decode_sink(&self, sink: Option<DecodeSink>) {
+        self.link_counters.decode().set_sink(sink);
+    }
+
+    /// receive structured events for every frame, segment, fragment, and object header sent or
+    /// received on the channel
+    ///
+    /// `None` removes the handler
+    pub fn set_decode_event_handler(&self, handler: Option<Box<dyn DecodeEventHandler>>) {
+        self.link_counters.decode().set_event_handler(handler);
+    }
+
+    /// keep the last `capacity` fragments sent or received on the channel, retrievable with
+    /// [MasterChannel::get_recent_fragments]
+    ///
+    /// a capacity of zero stops recording and discards the fragments already kept
+    pub fn set_recent_fragment_capacity(&self, capacity: usize) {
+        self.link_counters.decode().set_recent_capacity(capacity);
+    }
+
+    /// get the fragments recently sent or received on the channel, oldest first
+    pub fn get_recent_fragments(&self) -> Vec<RecentFragment> {
+        self.link_counters.decode().recent_fragments()
+    }
+
+    /// receive the raw bytes, link frames, transport segments, and application fragments sent or
+    /// received on the channel, tagged with their layer, direction, and time
+    ///
+    /// `None` removes the tap
+    pub fn set_wire_tap(&self, tap: Option<Box<dyn WireTap>>) {
+        self.link_counters.decode().set_wire_tap(tap);
+    }
+
+    /// get the deviations from the specification observed from the outstations on the channel,
+    /// with a count per type of anomaly
+    pub fn get_anomalies(&self) -> Vec<Anomaly> {
+        self.link_counters.anomalies().snapshot()
+    }
+
+    /// reset the counts of the anomalies observed on the channel
+    pub fn clear_anomalies(&self) {
+        self.link_counters.anomalies().clear()
+    }
+
+    /// start recording every link-layer frame sent or received on the channel
+    ///
+    /// replaces any capture already in progress. The capture stops automatically if writing to
+    /// it fails.
+    #[cfg(feature = "pcap")]
+    pub fn start_capture(&self, capture: crate::link::FrameCapture) {
+        self.link_counters.capture().start(capture);
+    }
+
+    /// stop recording frames, flushing the capture if one is in progress
+    #[cfg(feature = "pcap")]
+    pub fn stop_capture(&self) -> std::io::Result<()> {
+        self.link_counters.capture().stop()
+    }
+
+    /// true if a valid frame was received from an outstation within `max_age`
+    pub fn is_alive(&self, address: EndpointAddress, max_age: Duration) -> bool {
+        self.get_link_activity(address)
+            .map(|activity| activity.is_alive(max_age))
+            .unwrap_or(false)
     }
 
     /// enable communications
@@ -86,6 +224,34 @@ impl MasterChannel {
         Ok(())
     }
 
+    /// shut down the channel once the requests already queued have completed
+    ///
+    /// The task that is running, if any, and the requests queued before this call are completed
+    /// normally, e.g. so that the results of queued commands are not lost. Requests made after
+    /// this call fail with [TaskError::Shutdown] and the automatic tasks and polls are no longer
+    /// started. Queued requests that can't run because the channel is disabled or disconnected
+    /// fail with [TaskError::Shutdown].
+    ///
+    /// The returned future completes once the connection is closed and the channel task has
+    /// exited, or immediately if the task has already exited.
+    pub async fn shutdown(&mut self) {
+        let (tx, rx) = crate::tokio::sync::oneshot::channel::<()>();
+        if self
+            .send_master_message(MasterMsg::Shutdown(Promise::OneShot(tx)))
+            .await
+            .is_ok()
+        {
+            // the promise is also dropped if the task exits before processing the request
+            let _ = rx.await;
+        }
+    }
+
+    /// request that the current connection is closed because a newer connection replaces it
+    #[cfg(feature = "tcp")]
+    pub(crate) async fn replace_io(&mut self) -> Result<(), Shutdown> {
+        self.send_master_message(MasterMsg::ReplaceIo).await
+    }
+
     /// Set the decoding level used by this master
     pub async fn set_decode_level(&mut self, decode_level: DecodeLevel) -> Result<(), Shutdown> {
         self.send_master_message(MasterMsg::SetDecodeLevel(decode_level))
@@ -101,6 +267,19 @@ impl MasterChannel {
         rx.await?
     }
 
+    /// deliver the unsolicited responses of outstations whose address doesn't match any
+    /// association of the channel to a catch-all handler, e.g. during commissioning
+    ///
+    /// By default, these responses are ignored. `None` removes the handler.
+    pub async fn set_catch_all_handler(
+        &mut self,
+        handler: Option<Box<dyn CatchAllHandler>>,
+    ) -> Result<(), Shutdown> {
+        self.send_master_message(MasterMsg::SetCatchAllHandler(handler))
+            .await?;
+        Ok(())
+    }
+
     /// Create a new association:
     /// * `address` is the DNP3 link-layer address of the outstation
     /// * `config` controls the behavior of the master for this outstation
@@ -163,7 +342,11 @@ impl AssociationHandle {
     }
 
     pub(crate) fn new(address: EndpointAddress, master: MasterChannel) -> Self {
-        Self { address, master }
+        Self {
+            address,
+            master,
+            correlation_id: None,
+        }
     }
 
     /// retrieve the outstation address of the association
@@ -171,6 +354,22 @@ impl AssociationHandle {
         self.address
     }
 
+    /// create a copy of the handle whose requests are tagged with `id`
+    ///
+    /// The ID replaces the one allocated by the library in the `cid` field of the task span and is
+    /// passed to [`SpanFactory::task`](crate::decode::SpanFactory::task). The log records of the
+    /// request, the [`ReadHandler`] callbacks, and the completion of the request all occur within
+    /// that span, so they can be correlated with the operation of the caller. The ID is also passed
+    /// to [`ReadHandler::fragment_correlation_id`] and reported in the [`TaskCompletion`] of the
+    /// request. The library doesn't check that the IDs are unique. Polls and automatic tasks are
+    /// never tagged.
+    pub fn with_correlation_id(&self, id: u64) -> Self {
+        Self {
+            correlation_id: Some(id),
+            ..self.clone()
+        }
+    }
+
     /// Add a poll to the association
     /// * `request` defines what data is being requested
     /// * `period` defines how often the READ operation is performed
@@ -253,6 +452,19 @@ impl AssociationHandle {
         rx.await?
     }
 
+    /// Write indexed absolute times and intervals (g50v4), e.g. to schedule freezes
+    ///
+    /// All of the values are written in a single header with 2-byte count and prefix.
+    pub async fn write_indexed_time(
+        &mut self,
+        values: Vec<(IndexedTime, u16)>,
+    ) -> Result<(), WriteRequestError> {
+        let (tx, rx) = crate::tokio::sync::oneshot::channel::<Result<(), WriteRequestError>>();
+        let task = WriteTask::indexed_time(values, Promise::OneShot(tx));
+        self.send_task(task.wrap().wrap()).await?;
+        rx.await?
+    }
+
     /// Trigger the master to issue a REQUEST_LINK_STATUS function in advance of the link status timeout
     ///
     /// This function is provided for testing purposes. Using the configured link status timeout
@@ -266,6 +478,7 @@ impl AssociationHandle {
     }
 
     async fn send_task(&mut self, task: Task) -> Result<(), Shutdown> {
+        let task = UserTask::new(task, self.correlation_id);
         self.master
             .send_association_message(self.address, AssociationMsgType::QueueTask(task))
             .await
@@ -305,13 +518,46 @@ impl<T> Promise<T> {
 /// callbacks associated with a single master to outstation association
 pub trait AssociationHandler: Send {
     /// Retrieve the system time used for time synchronization
+    ///
+    /// The default implementation reads the system's clock, which doesn't exist on
+    /// `wasm32-unknown-unknown`, where the associations that synchronize the time of the
+    /// outstation must override it, e.g. with `Date.now()`.
     fn get_system_time(&self) -> Option<Timestamp> {
         Timestamp::try_from_system_time(SystemTime::now())
     }
+
+    /// Called once for each task of the association when it completes, including the requests
+    /// that fail before they run, e.g. with [TaskError::TooManyRequests]
+    ///
+    /// A READ coalesced with an identical queued READ (see
+    /// [QueueOverflowPolicy::CoalesceDuplicateReads](crate::master::QueueOverflowPolicy::CoalesceDuplicateReads))
+    /// is sent once under the ID of the queued READ, but each of the merged requests is reported
+    /// with its own ID. The default implementation ignores the completions.
+    fn task_completed(&mut self, _completion: TaskCompletion) {}
+}
+
+/// Completion of a task reported to [AssociationHandler::task_completed]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TaskCompletion {
+    /// ID attached to the request with [AssociationHandle::with_correlation_id], or the one
+    /// allocated by the library otherwise
+    pub correlation_id: u64,
+    /// result of the task, as returned to the caller of the request
+    pub result: Result<(), TaskError>,
+}
+
+impl TaskCompletion {
+    pub(crate) fn new(correlation_id: u64, result: Result<(), TaskError>) -> Self {
+        Self {
+            correlation_id,
+            result,
+        }
+    }
 }
 
 /// Information about the object header from which the measurement values were mapped
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeaderInfo {
     /// underlying variation
     pub variation: Variation,
@@ -330,6 +576,7 @@ impl HeaderInfo {
 
 /// Describes the source of a read event
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReadType {
     /// Startup integrity poll
     StartupIntegrity,
@@ -339,9 +586,18 @@ pub enum ReadType {
     SinglePoll,
     /// Periodic poll configured by the user
     PeriodicPoll,
+    /// Last values of the points reported by the master when communication with the outstation
+    /// is lost or restored
+    ///
+    /// See [AssociationConfig::comm_lost_timeouts](crate::master::AssociationConfig::comm_lost_timeouts).
+    CommLost,
 }
 
 /// Trait used to process measurement data received from an outstation
+///
+/// Measurements are delivered through iterators that decode them lazily from the receive
+/// buffer, so no allocation takes place between the reception of a fragment and its delivery.
+/// The iterators are only valid for the duration of each call.
 pub trait ReadHandler: Send {
     /// Called as the first action before any of the type-specific handle methods are invoked
     ///
@@ -349,6 +605,22 @@ pub trait ReadHandler: Send {
     /// `header` provides the full response header
     fn begin_fragment(&mut self, read_type: ReadType, header: ResponseHeader);
 
+    /// Called before `begin_fragment` with the time at which the first link-layer frame of the
+    /// fragment was received
+    ///
+    /// The timestamp is captured by the link layer, so it isn't delayed when fragments are queued.
+    /// It can be used to measure latency or to correct the time of events. The default
+    /// implementation ignores it.
+    fn fragment_received(&mut self, _timestamp: RxTimestamp) {}
+
+    /// Called before `begin_fragment` with the correlation ID of the task that requested the
+    /// fragment
+    ///
+    /// The ID is the one attached with [AssociationHandle::with_correlation_id], or the one
+    /// allocated by the library and recorded in the `cid` field of the task span otherwise. It
+    /// isn't called for unsolicited responses. The default implementation ignores it.
+    fn fragment_correlation_id(&mut self, _id: u64) {}
+
     /// Called as the last action after all of the type-specific handle methods have been invoked
     ///
     /// `read_type` provides information about what triggered the call, e.g. response vs unsolicited
@@ -398,6 +670,59 @@ pub trait ReadHandler: Send {
         info: HeaderInfo,
         iter: &'a mut dyn Iterator<Item = (Bytes<'a>, u16)>,
     );
+
+    /// Process an object header with a group and variation unknown to the parser
+    ///
+    /// This is only called when the association is configured to capture unknown objects. The
+    /// default implementation ignores them.
+    fn handle_unknown_header(&mut self, _header: UnknownHeader) {}
+
+    /// Process an object header containing a vendor-specific object
+    ///
+    /// This is only called for the objects registered in the
+    /// [vendor_objects](crate::master::AssociationConfig::vendor_objects) of the association.
+    /// The default implementation ignores them.
+    fn handle_vendor_header(&mut self, _header: VendorHeader) {}
+
+    /// Process an object header of indexed absolute time values (g50v4)
+    ///
+    /// The default implementation ignores them.
+    fn handle_indexed_time(
+        &mut self,
+        _info: HeaderInfo,
+        _iter: &mut dyn Iterator<Item = (IndexedTime, u16)>,
+    ) {
+    }
+
+    /// Process an object header of `UnsignedInteger` values (g102)
+    ///
+    /// The default implementation ignores them.
+    fn handle_unsigned_integer(
+        &mut self,
+        _info: HeaderInfo,
+        _iter: &mut dyn Iterator<Item = (UnsignedInteger, u16)>,
+    ) {
+    }
+
+    /// Process an object header containing a device attribute (g0)
+    ///
+    /// Each attribute, or list of attribute variations (g0v255), is reported in its own call.
+    /// The default implementation ignores them.
+    fn handle_device_attribute(&mut self, _info: HeaderInfo, _attr: Attribute) {}
+}
+
+/// Receives the unsolicited responses of outstations whose address doesn't match any association
+/// of the channel
+///
+/// This is useful during commissioning, when the addresses of the outstations are unknown or
+/// misconfigured. Responses requesting a confirmation are confirmed so that the outstation stops
+/// repeating them, but no other request is sent to these outstations.
+pub trait CatchAllHandler: Send {
+    /// Retrieve the handler that processes the measurements of an unsolicited response received
+    /// from `source`
+    ///
+    /// The fragment is reported as [ReadType::Unsolicited].
+    fn get_read_handler(&mut self, source: EndpointAddress) -> &mut dyn ReadHandler;
 }
 
 /// no-op default association handler type
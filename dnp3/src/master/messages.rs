@@ -5,8 +5,8 @@ use crate::master::error::PollError;
 use crate::master::error::{AssociationError, TaskError};
 use crate::master::handle::Promise;
 use crate::master::poll::PollMsg;
-use crate::master::tasks::Task;
-use crate::master::{AssociationConfig, AssociationHandler, ReadHandler};
+use crate::master::tasks::UserTask;
+use crate::master::{AssociationConfig, AssociationHandler, CatchAllHandler, ReadHandler};
 
 /// Messages sent from the handles to the master task via an mpsc.
 pub(crate) enum Message {
@@ -33,6 +33,13 @@ pub(crate) enum MasterMsg {
     SetDecodeLevel(DecodeLevel),
     /// Get the decoding level
     GetDecodeLevel(Promise<Result<DecodeLevel, Shutdown>>),
+    /// Set or remove the handler of unsolicited responses from unknown addresses
+    SetCatchAllHandler(Option<Box<dyn CatchAllHandler>>),
+    /// Close the current connection in favor of a newer one once the current task is interrupted
+    #[cfg(feature = "tcp")]
+    ReplaceIo,
+    /// Shut down once the queued requests complete, completing the promise when the task exits
+    Shutdown(Promise<()>),
 }
 
 pub(crate) struct AssociationMsg {
@@ -42,7 +49,7 @@ pub(crate) struct AssociationMsg {
 
 pub(crate) enum AssociationMsgType {
     /// Queue an I/O task for execution later
-    QueueTask(Task),
+    QueueTask(UserTask),
     /// Modify polls
     Poll(PollMsg),
 }
@@ -56,8 +63,10 @@ impl AssociationMsg {
 impl AssociationMsgType {
     pub(crate) fn on_association_failure(self, address: EndpointAddress) {
         match self {
-            AssociationMsgType::QueueTask(task) => {
-                task.on_task_error(None, TaskError::NoSuchAssociation(address));
+            AssociationMsgType::QueueTask(queued) => {
+                queued
+                    .task
+                    .on_task_error(None, TaskError::NoSuchAssociation(address));
             }
             AssociationMsgType::Poll(msg) => {
                 msg.on_error(PollError::NoSuchAssociation(address));
@@ -9,6 +9,7 @@ mod error;
 mod handle;
 mod request;
 
+pub(crate) mod comm_lost;
 pub(crate) mod convert;
 pub(crate) mod extract;
 pub(crate) mod messages;
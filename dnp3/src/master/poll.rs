@@ -2,12 +2,14 @@ use std::collections::BTreeMap;
 use std::time::Duration;
 
 use crate::app::format::write::HeaderWriter;
+use crate::app::variations::Variation;
+use crate::app::Clock;
+use crate::app::Instant;
 use crate::app::Shutdown;
 use crate::master::association::Next;
 use crate::master::error::PollError;
 use crate::master::handle::{AssociationHandle, Promise};
 use crate::master::request::ReadRequest;
-use crate::tokio::time::Instant;
 use crate::util::cursor::WriteError;
 use crate::util::Smallest;
 
@@ -28,20 +30,23 @@ pub(crate) struct Poll {
 pub(crate) struct PollMap {
     id: u64,
     polls: BTreeMap<u64, Poll>,
+    clock: Clock,
 }
 
 impl PollMap {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(clock: Clock) -> Self {
         Self {
             id: 0,
             polls: BTreeMap::new(),
+            clock,
         }
     }
 
     pub(crate) fn add(&mut self, request: ReadRequest, period: Duration) -> u64 {
         let id = self.id;
         self.id += 1;
-        self.polls.insert(id, Poll::new(id, request, period));
+        self.polls
+            .insert(id, Poll::new(id, request, period, self.clock.now()));
         id
     }
 
@@ -51,7 +56,7 @@ impl PollMap {
 
     pub(crate) fn demand(&mut self, id: u64) -> bool {
         if let Some(poll) = self.polls.get_mut(&id) {
-            poll.demand();
+            poll.demand(self.clock.now());
             true
         } else {
             false
@@ -60,7 +65,7 @@ impl PollMap {
 
     pub(crate) fn complete(&mut self, id: u64) {
         if let Some(x) = self.polls.get_mut(&id) {
-            x.reset_next()
+            x.reset_next(self.clock.now())
         }
     }
 
@@ -86,12 +91,12 @@ impl PollMap {
 }
 
 impl Poll {
-    pub(crate) fn new(id: u64, request: ReadRequest, period: Duration) -> Self {
+    pub(crate) fn new(id: u64, request: ReadRequest, period: Duration, now: Instant) -> Self {
         Self {
             id,
             request,
             period,
-            next: Instant::now().checked_add(period),
+            next: now.checked_add(period),
         }
     }
 
@@ -99,12 +104,16 @@ impl Poll {
         self.request.format(writer)
     }
 
-    pub(crate) fn demand(&mut self) {
-        self.next = Some(Instant::now());
+    pub(crate) fn variations_mut(&mut self) -> Vec<&mut Variation> {
+        self.request.variations_mut()
     }
 
-    pub(crate) fn reset_next(&mut self) {
-        self.next = Instant::now().checked_add(self.period)
+    pub(crate) fn demand(&mut self, now: Instant) {
+        self.next = Some(now);
+    }
+
+    pub(crate) fn reset_next(&mut self, now: Instant) {
+        self.next = now.checked_add(self.period)
     }
 
     pub(crate) fn is_ready(&self, now: Instant) -> bool {
@@ -22,6 +22,7 @@ pub enum CommandMode {
 
 /// Controls which time synchronization procedure is used
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TimeSyncProcedure {
     /// Master will use the LAN procedure: RECORD_CURRENT_TIME followed by WRITE g50v3
     Lan,
@@ -31,6 +32,7 @@ pub enum TimeSyncProcedure {
 
 /// struct recording which event classes are enabled
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EventClasses {
     /// enable Class 1
     pub class1: bool,
@@ -42,6 +44,7 @@ pub struct EventClasses {
 
 /// struct recording which event classes and class 0 are enabled
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Classes {
     /// enable class zero
     pub class0: bool,
@@ -169,9 +172,18 @@ impl Classes {
     }
 
     pub(crate) fn write(self, writer: &mut HeaderWriter) -> Result<(), WriteError> {
+        self.write_with(&ClassZeroRequest::Class0, writer)
+    }
+
+    /// write the event classes followed by the headers that request the class 0 data
+    pub(crate) fn write_with(
+        self,
+        class0: &ClassZeroRequest,
+        writer: &mut HeaderWriter,
+    ) -> Result<(), WriteError> {
         self.events.write(writer)?;
         if self.class0 {
-            writer.write_all_objects_header(Variation::Group60Var1)?;
+            class0.write(writer)?;
         }
         Ok(())
     }
@@ -245,6 +257,14 @@ impl ReadHeader {
         ReadHeader::AllObjects(AllObjectsScan::new(variation))
     }
 
+    pub(crate) fn variation_mut(&mut self) -> &mut Variation {
+        match self {
+            ReadHeader::Range8(scan) => &mut scan.variation,
+            ReadHeader::Range16(scan) => &mut scan.variation,
+            ReadHeader::AllObjects(scan) => &mut scan.variation,
+        }
+    }
+
     pub(crate) fn format(self, writer: &mut HeaderWriter) -> Result<(), WriteError> {
         match self {
             ReadHeader::Range8(scan) => scan.write(writer),
@@ -255,7 +275,7 @@ impl ReadHeader {
 }
 
 /// Enum representing all of the READ request types available from the master API
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ReadRequest {
     /// Read a single header
     SingleHeader(ReadHeader),
@@ -291,6 +311,17 @@ impl ReadRequest {
         Self::MultipleHeader(headers.to_vec())
     }
 
+    /// variations of the headers, excluding class data
+    pub(crate) fn variations_mut(&mut self) -> Vec<&mut Variation> {
+        match self {
+            ReadRequest::SingleHeader(req) => vec![req.variation_mut()],
+            ReadRequest::ClassScan(_) => Vec::new(),
+            ReadRequest::MultipleHeader(reqs) => {
+                reqs.iter_mut().map(|req| req.variation_mut()).collect()
+            }
+        }
+    }
+
     pub(crate) fn format(&self, writer: &mut HeaderWriter) -> Result<(), WriteError> {
         match self {
             ReadRequest::SingleHeader(req) => req.format(writer),
@@ -305,6 +336,97 @@ impl ReadRequest {
     }
 }
 
+/// Static data requested by the class 0 portion of the startup integrity poll
+///
+/// Outstations may report large amounts of static data in class 0, e.g. octet strings or frozen
+/// counters. Requesting only the groups of interest reduces the traffic of the integrity poll on
+/// slow links.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClassZeroRequest {
+    /// request all of the static data with a class 0 header (g60v1)
+    Class0,
+    /// request the static data with these headers instead, e.g. `g1v0` and `g30v0` to omit the
+    /// octet strings and frozen counters of the outstation
+    Headers(Vec<ReadHeader>),
+}
+
+impl ClassZeroRequest {
+    /// construct a `ClassZeroRequest` which reads each of these headers instead of class 0
+    pub fn headers(headers: &[ReadHeader]) -> Self {
+        Self::Headers(headers.to_vec())
+    }
+
+    pub(crate) fn variations_mut(&mut self) -> Vec<&mut Variation> {
+        match self {
+            ClassZeroRequest::Class0 => Vec::new(),
+            ClassZeroRequest::Headers(headers) => headers
+                .iter_mut()
+                .map(|header| header.variation_mut())
+                .collect(),
+        }
+    }
+
+    pub(crate) fn write(&self, writer: &mut HeaderWriter) -> Result<(), WriteError> {
+        match self {
+            ClassZeroRequest::Class0 => writer.write_all_objects_header(Variation::Group60Var1),
+            ClassZeroRequest::Headers(headers) => {
+                for header in headers {
+                    header.format(writer)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Default for ClassZeroRequest {
+    fn default() -> Self {
+        Self::Class0
+    }
+}
+
+/// Variation requested in place of a specific variation when an outstation answers a READ with
+/// the OBJECT_UNKNOWN IIN bit
+///
+/// The IIN bit doesn't identify the header that the outstation doesn't support, so every header
+/// of the request that has a fallback is changed when the READ is retried.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VariationFallback {
+    /// READ requests are not retried
+    Disabled,
+    /// retry with variation 0 of the same group, i.e. the default variation of the outstation
+    DefaultVariation,
+    /// retry with the variation that follows the requested one in this list, provided that it
+    /// belongs to the same group, e.g. `[g30v5, g30v1, g30v0]`
+    Listed(Vec<Variation>),
+}
+
+impl VariationFallback {
+    /// variation to request if the outstation doesn't support `variation`
+    pub(crate) fn next(&self, variation: Variation) -> Option<Variation> {
+        let (group, var) = variation.to_group_and_var();
+        match self {
+            VariationFallback::Disabled => None,
+            // device attributes are always requested by attribute
+            VariationFallback::DefaultVariation if group == 0 || var == 0 => None,
+            VariationFallback::DefaultVariation => Variation::lookup(group, 0),
+            VariationFallback::Listed(list) => {
+                let pos = list.iter().position(|x| *x == variation)?;
+                list.get(pos + 1)
+                    .copied()
+                    .filter(|next| next.to_group_and_var().0 == group)
+            }
+        }
+    }
+}
+
+impl Default for VariationFallback {
+    fn default() -> Self {
+        Self::Disabled
+    }
+}
+
 #[derive(Clone)]
 pub(crate) enum CommandHeader {
     G12V1U8(Vec<(Group12Var1, u8)>),
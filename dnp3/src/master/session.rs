@@ -1,25 +1,33 @@
-use std::ops::Add;
+use std::sync::Arc;
 use std::time::Duration;
 
 use tracing::Instrument;
 
 use crate::app::format::write;
 use crate::app::format::write::start_request;
-use crate::app::parse::parser::Response;
+use crate::app::gen::ranged::RangedVariation;
+use crate::app::parse::parser::{HeaderCollection, HeaderDetails, Response};
+use crate::app::AnomalyKind;
+use crate::app::AttrValue;
+use crate::app::Clock;
 use crate::app::ControlField;
+use crate::app::Iin2;
+use crate::app::Instant;
 use crate::app::Sequence;
 use crate::app::Shutdown;
 use crate::app::Timeout;
-use crate::decode::DecodeLevel;
+use crate::decode::{DecodeLevel, TracingConfig};
 use crate::link::error::LinkError;
+use crate::link::statistics::LinkCounters;
 use crate::link::EndpointAddress;
-use crate::link::LinkStatusResult;
-use crate::master::association::{AssociationMap, Next};
-use crate::master::error::TaskError;
+use crate::link::{LinkConfig, LinkStatusResult, RxTimestamp};
+use crate::master::association::{AssociationMap, Next, ReadPreemption, SchedulingConfig};
+use crate::master::error::{AssociationError, RequestContext, TaskError};
+use crate::master::extract::extract_measurements;
+use crate::master::handle::Promise;
 use crate::master::messages::{MasterMsg, Message};
 use crate::master::tasks::{AssociationTask, NonReadTask, ReadTask, RequestWriter, Task};
-use crate::master::Association;
-use crate::tokio::time::Instant;
+use crate::master::{Association, CatchAllHandler, ReadType};
 use crate::transport::{TransportReader, TransportResponse, TransportWriter};
 use crate::util::buffer::Buffer;
 use crate::util::channel::Receiver;
@@ -29,20 +37,58 @@ pub(crate) struct MasterSession {
     enabled: bool,
     decode_level: DecodeLevel,
     timeout: Timeout,
+    link: LinkConfig,
     associations: AssociationMap,
     messages: Receiver<Message>,
     tx_buffer: Buffer,
+    tx_buffer_size: usize,
+    rx_buffer_size: usize,
+    clock: Clock,
+    counters: Arc<LinkCounters>,
+    tracing: TracingConfig,
+    // promises of the requested shutdowns, completed once the session has shut down
+    shutdown: Vec<Promise<()>>,
+    // dial-up connections are closed after this time without tasks
+    idle_timeout: Option<Duration>,
+    // receives unsolicited responses from addresses without an association
+    catch_all: Option<Box<dyn CatchAllHandler>>,
+    // reception time of the last fragment, passed to the catch-all handler
+    fragment_received: Option<RxTimestamp>,
+    // correlation ID of the task in progress, passed to the read handler
+    correlation_id: Option<u64>,
 }
 
 enum ReadResponseAction {
     Ignore,
     ReadNext,
     Complete,
+    // a command is queued, the READ ends without confirming the fragment
+    Preempt,
+}
+
+// how a READ task that didn't fail ended
+enum ReadOutcome {
+    Completed,
+    // interrupted by a command and queued to run again
+    Postponed,
+}
+
+// fragment size reported in the device attributes of an outstation that doesn't fit a buffer
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum FragmentSizeMismatch {
+    // the outstation may send fragments larger than the RX buffer
+    Tx(usize),
+    // the outstation only accepts fragments smaller than the TX buffer
+    Rx(usize),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum StateChange {
     Disable,
+    Disconnect,
+    IdleTimeout,
+    #[cfg(feature = "tcp")]
+    ReplaceIo,
     Shutdown,
 }
 
@@ -79,37 +125,160 @@ impl From<Shutdown> for RunError {
 impl MasterSession {
     pub(crate) const DEFAULT_TX_BUFFER_SIZE: usize = 2048;
     pub(crate) const MIN_TX_BUFFER_SIZE: usize = 249;
+    pub(crate) const MAX_TX_BUFFER_SIZE: usize = 65535;
 
     pub(crate) const DEFAULT_RX_BUFFER_SIZE: usize = 2048;
     pub(crate) const MIN_RX_BUFFER_SIZE: usize = 2048;
+    pub(crate) const MAX_RX_BUFFER_SIZE: usize = 65535;
+
+    // standard device attributes (set 0) describing the fragment sizes of the outstation
+    const ATTR_MAX_TX_FRAGMENT_SIZE: u8 = 240;
+    const ATTR_MAX_RX_FRAGMENT_SIZE: u8 = 241;
+
+    // bounds the retries of a READ when the configured fallbacks form a cycle
+    const MAX_VARIATION_FALLBACKS: usize = 4;
 
+    /// size of the RX buffer used for a configured size, which may be out of range
+    pub(crate) fn effective_rx_buffer_size(size: usize) -> usize {
+        if size < Self::MIN_RX_BUFFER_SIZE {
+            Self::MIN_RX_BUFFER_SIZE
+        } else if size > Self::MAX_RX_BUFFER_SIZE {
+            Self::MAX_RX_BUFFER_SIZE
+        } else {
+            size
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         enabled: bool,
         decode_level: DecodeLevel,
         response_timeout: Timeout,
+        scheduling: SchedulingConfig,
+        link: LinkConfig,
+        counters: Arc<LinkCounters>,
+        tracing: TracingConfig,
         tx_buffer_size: usize,
+        rx_buffer_size: usize,
         messages: Receiver<Message>,
     ) -> Self {
         let tx_buffer_size = if tx_buffer_size < Self::MIN_TX_BUFFER_SIZE {
             tracing::warn!("Minimum TX buffer size is {}. Defaulting to this value because the provided value ({}) is too low.", Self::MIN_TX_BUFFER_SIZE, tx_buffer_size);
             Self::MIN_TX_BUFFER_SIZE
+        } else if tx_buffer_size > Self::MAX_TX_BUFFER_SIZE {
+            tracing::warn!("Maximum TX buffer size is {}. Defaulting to this value because the provided value ({}) is too high.", Self::MAX_TX_BUFFER_SIZE, tx_buffer_size);
+            Self::MAX_TX_BUFFER_SIZE
         } else {
             tx_buffer_size
         };
+        if tx_buffer_size > Self::DEFAULT_TX_BUFFER_SIZE {
+            tracing::warn!(
+                "requests may be up to {} bytes, which outstations that only accept {} byte fragments will reject",
+                tx_buffer_size,
+                Self::DEFAULT_TX_BUFFER_SIZE
+            );
+        }
+        let clock = counters.clock();
 
         Self {
             enabled,
             decode_level,
             timeout: response_timeout,
-            associations: AssociationMap::new(),
+            link,
+            associations: AssociationMap::new(scheduling, clock.clone()),
             messages,
-            tx_buffer: Buffer::new(tx_buffer_size),
+            tx_buffer: Buffer::pooled(tx_buffer_size, counters.buffer_pool()),
+            tx_buffer_size,
+            rx_buffer_size: Self::effective_rx_buffer_size(rx_buffer_size),
+            clock,
+            counters,
+            tracing,
+            shutdown: Vec::new(),
+            idle_timeout: None,
+            catch_all: None,
+            fragment_received: None,
+            correlation_id: None,
+        }
+    }
+
+    /// close connections once no task has run for `idle_timeout`, keeping the state of the
+    /// associations, and queue the requests made while disconnected
+    #[cfg(any(test, feature = "tcp"))]
+    pub(crate) fn set_dial_up(&mut self, idle_timeout: Duration) {
+        self.idle_timeout = Some(idle_timeout);
+    }
+
+    /// Clock used for the timeouts and scheduling of the session
+    pub(crate) fn clock(&self) -> Clock {
+        self.clock.clone()
+    }
+
+    fn anomaly(&self, kind: AnomalyKind, detail: std::fmt::Arguments) {
+        self.counters.anomalies().record(kind, detail);
+    }
+
+    /// true once a shutdown has been requested, after which only the queued requests are run
+    /// record a solicited response whose sequence number doesn't match the request in progress,
+    /// if any, distinguishing repetitions of the last response that was processed
+    fn unexpected_sequence(
+        &mut self,
+        source: EndpointAddress,
+        seq: Sequence,
+        expected: Option<Sequence>,
+    ) {
+        let is_duplicate = self
+            .associations
+            .get_mut(source)
+            .map(|association| association.is_duplicate_response(seq))
+            .unwrap_or(false);
+
+        if is_duplicate {
+            self.anomaly(
+                AnomalyKind::DuplicateResponse,
+                format_args!(
+                    "duplicate response from {} with seq: {}",
+                    source,
+                    seq.value()
+                ),
+            );
+            return;
+        }
+
+        match expected {
+            Some(expected) => self.anomaly(
+                AnomalyKind::UnexpectedSequence,
+                format_args!(
+                    "response with seq: {} doesn't match expected seq: {}",
+                    seq.value(),
+                    expected.value()
+                ),
+            ),
+            None => self.anomaly(
+                AnomalyKind::UnexpectedFragment,
+                format_args!("unexpected response with sequence: {}", seq.value()),
+            ),
         }
     }
 
+    /// record a response with an invalid combination of FIR/FIN/CON bits, returning the error
+    fn unexpected_fir_fin(&self, err: TaskError) -> TaskError {
+        self.anomaly(AnomalyKind::UnexpectedFirFin, format_args!("{}", err));
+        err
+    }
+
+    fn is_draining(&self) -> bool {
+        !self.shutdown.is_empty()
+    }
+
     /// Wait for the defined duration, processing messages that are received in the meantime.
+    #[cfg(any(test, feature = "tcp", feature = "udp", feature = "serial"))]
     pub(crate) async fn wait_for_retry(&mut self, duration: Duration) -> Result<(), StateChange> {
-        let deadline = Instant::now().add(duration);
+        if self.is_draining() {
+            return Err(StateChange::Shutdown);
+        }
+
+        let clock = self.clock.clone();
+        let deadline = clock.now() + duration;
 
         loop {
             crate::tokio::select! {
@@ -119,16 +288,103 @@ impl MasterSession {
                        return Err(StateChange::Disable)
                    }
                 }
-                _ = crate::tokio::time::sleep_until(deadline) => {
+                _ = clock.sleep_until(deadline) => {
                    return Ok(());
                 }
             }
         }
     }
 
+    /// Wait for a future, e.g. a connection attempt, processing messages that are received in the
+    /// meantime. The future is dropped if the session is disabled or shut down.
+    #[cfg(feature = "tcp")]
+    pub(crate) async fn wait_for<F>(&mut self, future: F) -> Result<F::Output, StateChange>
+    where
+        F: std::future::Future,
+    {
+        if self.is_draining() {
+            return Err(StateChange::Shutdown);
+        }
+
+        let mut future = Box::pin(future);
+
+        loop {
+            crate::tokio::select! {
+                result = self.process_message(false) => {
+                   result?;
+                   if !self.enabled {
+                       return Err(StateChange::Disable)
+                   }
+                }
+                output = &mut future => {
+                   return Ok(output);
+                }
+            }
+        }
+    }
+
+    /// process messages while there is no connection until the session is disabled or shut down
+    pub(crate) async fn process_messages(&mut self) -> StateChange {
+        loop {
+            if self.is_draining() {
+                return StateChange::Shutdown;
+            }
+            if let Err(err) = self.process_message(false).await {
+                return err;
+            }
+            if !self.enabled {
+                return StateChange::Disable;
+            }
+        }
+    }
+
+    /// process messages while there is no dial-up connection until a task is due
+    ///
+    /// Requests are queued instead of failing with `NoConnection`.
+    #[cfg(feature = "tcp")]
+    pub(crate) async fn wait_for_work(&mut self) -> Result<(), StateChange> {
+        loop {
+            if self.is_draining() {
+                // connect to complete the requests queued before the shutdown
+                if self.associations.has_user_tasks() {
+                    return Ok(());
+                }
+                return Err(StateChange::Shutdown);
+            }
+
+            let result = match self.associations.next_work() {
+                Next::Now(()) => return Ok(()),
+                Next::NotBefore(time) => {
+                    let clock = self.clock.clone();
+                    crate::tokio::select! {
+                        result = self.process_message(true) => result,
+                        _ = clock.sleep_until(time) => Ok(()),
+                    }
+                }
+                Next::None => self.process_message(true).await,
+            };
+
+            match result {
+                // there is no connection to close
+                Ok(())
+                | Err(StateChange::Disconnect)
+                | Err(StateChange::IdleTimeout)
+                | Err(StateChange::ReplaceIo) => {}
+                Err(err) => {
+                    self.reset(RunError::State(err));
+                    return Err(err);
+                }
+            }
+        }
+    }
+
     /// wait until the session has been enabled
     pub(crate) async fn wait_for_enabled(&mut self) -> Result<(), Shutdown> {
         loop {
+            if self.is_draining() {
+                return Err(Shutdown);
+            }
+
             if self.enabled {
                 return Ok(());
             }
@@ -146,21 +402,88 @@ impl MasterSession {
         writer: &mut TransportWriter,
         reader: &mut TransportReader,
     ) -> RunError {
+        self.run_impl(io, writer, reader, None).await
+    }
+
+    /// Run the master until an error or shutdown occurs, or until the deadline is reached.
+    ///
+    /// Reaching the deadline is reported as `StateChange::Disconnect` once any running task completes.
+    #[cfg(any(feature = "tcp", feature = "serial"))]
+    pub(crate) async fn run_until(
+        &mut self,
+        io: &mut PhysLayer,
+        writer: &mut TransportWriter,
+        reader: &mut TransportReader,
+        deadline: Instant,
+    ) -> RunError {
+        self.run_impl(io, writer, reader, Some(deadline)).await
+    }
+
+    async fn run_impl(
+        &mut self,
+        io: &mut PhysLayer,
+        writer: &mut TransportWriter,
+        reader: &mut TransportReader,
+        deadline: Option<Instant>,
+    ) -> RunError {
+        // time at which the last task other than a keep-alive completed
+        let mut last_work = self.clock.now();
+        if self.idle_timeout.is_some() {
+            self.associations.on_connect();
+        }
+
         loop {
-            let result = match self.get_next_task() {
-                Next::Now(task) => {
-                    let id = task.details.get_id();
-                    let address = task.address.raw_value();
-                    self.run_task(io, task, writer, reader)
-                        .instrument(tracing::info_span!("Task", "type" = ?id, "dest" = address))
-                        .await
-                }
-                Next::NotBefore(time) => self.idle_until(time, io, writer, reader).await,
-                Next::None => self.idle_forever(io, writer, reader).await,
+            let idle_deadline = self.idle_timeout.map(|x| last_work + x);
+            let result = if matches!(deadline, Some(x) if self.clock.now() >= x) {
+                Err(RunError::State(StateChange::Disconnect))
+            } else {
+                match self.get_next_task() {
+                    Next::Now(task) => {
+                        let is_work = !matches!(task.details, Task::LinkStatus(_));
+                        let id = task.details.get_id();
+                        let address = task.address.raw_value();
+                        let cid = task
+                            .correlation_id
+                            .unwrap_or_else(crate::decode::next_correlation_id);
+                        let span = self.tracing.task_span(
+                            tracing::info_span!("Task", "type" = ?id, "dest" = address, "cid" = cid),
+                            cid,
+                        );
+                        let result = self
+                            .run_task(io, task, cid, writer, reader)
+                            .instrument(span)
+                            .await;
+                        if is_work {
+                            last_work = self.clock.now();
+                        }
+                        result
+                    }
+                    // the queued requests have completed
+                    Next::None if self.is_draining() => Err(RunError::State(StateChange::Shutdown)),
+                    _ if matches!(idle_deadline, Some(x) if self.clock.now() >= x) => {
+                        Err(RunError::State(StateChange::IdleTimeout))
+                    }
+                    Next::NotBefore(time) => {
+                        self.tx_buffer.release(0);
+                        let time = deadline.map_or(time, |x| Instant::min(x, time));
+                        let time = idle_deadline.map_or(time, |x| Instant::min(x, time));
+                        self.idle_until(time, io, writer, reader).await
+                    }
+                    Next::None => {
+                        self.tx_buffer.release(0);
+                        match deadline.into_iter().chain(idle_deadline).min() {
+                            Some(x) => self.idle_until(x, io, writer, reader).await,
+                            None => self.idle_forever(io, writer, reader).await,
+                        }
+                    }
+                }
             };
 
             if let Err(err) = result {
-                self.reset(err);
+                // the associations resume where they left off on the next dial-up connection
+                if err != RunError::State(StateChange::IdleTimeout) {
+                    self.reset(err);
+                }
                 writer.reset();
                 reader.reset();
                 return err;
@@ -171,8 +494,13 @@ impl MasterSession {
     pub(crate) async fn shutdown(&mut self) {
         // close the receiver to new messages
         self.messages.close();
-        // process any existing messages
-        while let Ok(()) = self.process_message(false).await {}
+        // process any existing messages, failing the requests they contain
+        while let Ok(message) = self.messages.receive().await {
+            let _ = self.handle_message(message, false);
+        }
+        for promise in self.shutdown.drain(..) {
+            promise.complete(());
+        }
     }
 
     /// Wait until a message is received or a response is received.
@@ -194,8 +522,8 @@ impl MasterSession {
                 result = reader.read(io, decode_level) => {
                    result?;
                    match reader.pop_response() {
-                        Some(TransportResponse::Response(source, response)) => {
-                            self.notify_link_activity(source);
+                        Some(TransportResponse::Response(source, received, response)) => {
+                            self.notify_fragment_received(source, received);
                             return self.handle_fragment_while_idle(io, writer, source, response).await
                         }
                         Some(TransportResponse::LinkLayerMessage(msg)) => self.notify_link_activity(msg.source),
@@ -219,6 +547,7 @@ impl MasterSession {
     ) -> Result<(), RunError> {
         loop {
             let decode_level = self.decode_level;
+            let clock = self.clock.clone();
             crate::tokio::select! {
                 result = self.process_message(true) => {
                    // we need to recheck the tasks
@@ -227,8 +556,8 @@ impl MasterSession {
                 result = reader.read(io, decode_level) => {
                    result?;
                    match reader.pop_response() {
-                        Some(TransportResponse::Response(source, response)) => {
-                            self.notify_link_activity(source);
+                        Some(TransportResponse::Response(source, received, response)) => {
+                            self.notify_fragment_received(source, received);
                             return self.handle_fragment_while_idle(io, writer, source, response).await
                         }
                         Some(TransportResponse::LinkLayerMessage(msg)) => self.notify_link_activity(msg.source),
@@ -236,7 +565,7 @@ impl MasterSession {
                         None => return Ok(()),
                    }
                 }
-                _ = crate::tokio::time::sleep_until(instant) => {
+                _ = clock.sleep_until(instant) => {
                    return Ok(());
                 }
             }
@@ -245,7 +574,26 @@ impl MasterSession {
 
     async fn process_message(&mut self, is_connected: bool) -> Result<(), StateChange> {
         let message = self.messages.receive().await?;
+        self.handle_message(message, is_connected)
+    }
+
+    fn handle_message(&mut self, message: Message, is_connected: bool) -> Result<(), StateChange> {
         match message {
+            Message::Master(MasterMsg::Shutdown(promise)) => {
+                if !self.is_draining() {
+                    tracing::info!("shutting down once the queued requests complete");
+                }
+                self.shutdown.push(promise);
+                if !is_connected {
+                    return Err(StateChange::Shutdown);
+                }
+            }
+            #[cfg(feature = "tcp")]
+            Message::Master(MasterMsg::ReplaceIo) => {
+                if is_connected {
+                    return Err(StateChange::ReplaceIo);
+                }
+            }
             Message::Master(msg) => {
                 self.process_master_message(msg);
                 if is_connected && !self.enabled {
@@ -253,8 +601,15 @@ impl MasterSession {
                 }
             }
             Message::Association(msg) => {
+                let rejection = if self.is_draining() {
+                    Some(TaskError::Shutdown)
+                } else if !is_connected {
+                    Some(TaskError::NoConnection)
+                } else {
+                    None
+                };
                 if let Ok(association) = self.associations.get_mut(msg.address) {
-                    association.process_message(msg.details, is_connected);
+                    association.process_message(msg.details, rejection);
                 } else {
                     msg.on_association_failure();
                 }
@@ -274,11 +629,16 @@ impl MasterSession {
                 self.enabled = enable;
             }
             MasterMsg::AddAssociation(address, config, read_handler, assoc_handler, callback) => {
+                if self.is_draining() {
+                    callback.complete(Err(AssociationError::Shutdown));
+                    return;
+                }
                 callback.complete(self.associations.register(Association::new(
                     address,
                     config,
                     read_handler,
                     assoc_handler,
+                    self.clock.clone(),
                 )));
             }
             MasterMsg::RemoveAssociation(address) => {
@@ -290,10 +650,17 @@ impl MasterSession {
             MasterMsg::GetDecodeLevel(promise) => {
                 promise.complete(Ok(self.decode_level));
             }
+            MasterMsg::SetCatchAllHandler(handler) => {
+                self.catch_all = handler;
+            }
+            #[cfg(feature = "tcp")]
+            MasterMsg::ReplaceIo => {}
+            MasterMsg::Shutdown(_) => {}
         }
     }
 
     fn reset(&mut self, err: RunError) {
+        self.fragment_received = None;
         self.associations.reset(err);
     }
 }
@@ -307,13 +674,28 @@ impl MasterSession {
         &mut self,
         io: &mut PhysLayer,
         task: AssociationTask,
+        cid: u64,
         writer: &mut TransportWriter,
         reader: &mut TransportReader,
     ) -> Result<(), RunError> {
+        self.fragment_received = None;
+        self.correlation_id = Some(cid);
+        let address = task.address;
+        let merged = task.details.coalesced_ids();
+        let mut completed = true;
         let result = match task.details {
             Task::Read(t) => {
-                self.run_read_task(io, task.address, t, writer, reader)
+                match self
+                    .run_read_task(io, task.address, t, cid, writer, reader)
                     .await
+                {
+                    Ok(ReadOutcome::Completed) => Ok(()),
+                    Ok(ReadOutcome::Postponed) => {
+                        completed = false;
+                        Ok(())
+                    }
+                    Err(err) => Err(err),
+                }
             }
             Task::NonRead(t) => {
                 self.run_non_read_task(io, task.address, t, writer, reader)
@@ -336,12 +718,21 @@ impl MasterSession {
             }
         };
 
+        self.correlation_id = None;
+        self.associations.on_task_result(address, result);
+        // a postponed READ completes once it runs again
+        if completed {
+            self.associations
+                .on_task_complete(address, cid, &merged, result);
+        }
+
         // if a task error occurs, if might be a run error
         match result {
             Ok(()) => Ok(()),
             Err(err) => match err {
                 TaskError::Shutdown => Err(RunError::State(StateChange::Shutdown)),
                 TaskError::Disabled => Err(RunError::State(StateChange::Disable)),
+                TaskError::NoConnection => Err(RunError::State(StateChange::Disconnect)),
                 TaskError::Link(err) => Err(RunError::Link(err)),
                 _ => Ok(()),
             },
@@ -365,14 +756,16 @@ impl MasterSession {
                 }
             };
 
-            let deadline = self.timeout.deadline_from_now();
+            let timeout = self.response_timeout(destination);
+            let deadline = timeout.deadline_from_now(&self.clock);
 
             loop {
                 crate::tokio::select! {
-                    _ = crate::tokio::time::sleep_until(deadline) => {
-                        tracing::warn!("no response within timeout: {}", self.timeout);
-                        task.on_task_error(self.associations.get_mut(destination).ok(), TaskError::ResponseTimeout);
-                        return Err(TaskError::ResponseTimeout);
+                    _ = self.clock.sleep_until(deadline) => {
+                        tracing::warn!("no response within timeout: {}", timeout);
+                        let err = TaskError::ResponseTimeout(RequestContext::request(destination, task.function(), seq));
+                        task.on_task_error(self.associations.get_mut(destination).ok(), err);
+                        return Err(err);
                     }
                     x = reader.read(io, self.decode_level) => {
                         if let Err(err) = x {
@@ -381,11 +774,11 @@ impl MasterSession {
                         }
 
                         match reader.pop_response() {
-                            Some(TransportResponse::Response(source, response)) => {
-                                self.notify_link_activity(source);
+                            Some(TransportResponse::Response(source, received, response)) => {
+                                self.notify_fragment_received(source, received);
 
                                 let result = self
-                                    .validate_non_read_response(destination, seq, io, writer, source, response)
+                                    .validate_non_read_response(RequestContext::request(destination, task.function(), seq), io, writer, source, response)
                                     .await;
 
                                 match result {
@@ -398,6 +791,7 @@ impl MasterSession {
                                                 return Err(x.into());
                                             }
                                             Ok(association) => {
+                                                association.on_solicited_response(response.header.control.seq);
                                                 association.process_iin(response.header.iin);
                                                 match task.handle(association, response) {
                                                     None => return Ok(()),
@@ -441,8 +835,7 @@ impl MasterSession {
     #[allow(clippy::needless_lifetimes)]
     async fn validate_non_read_response<'a>(
         &mut self,
-        destination: EndpointAddress,
-        seq: Sequence,
+        context: RequestContext,
         io: &mut PhysLayer,
         writer: &mut TransportWriter,
         source: EndpointAddress,
@@ -454,25 +847,22 @@ impl MasterSession {
             return Ok(None);
         }
 
-        if source != destination {
+        if source != context.address {
             tracing::warn!(
                 "Received response from {} while expecting response from {}",
                 source,
-                destination
+                context.address
             );
             return Ok(None);
         }
 
-        if response.header.control.seq != seq {
-            tracing::warn!(
-                "unexpected sequence number is response: {}",
-                response.header.control.seq.value()
-            );
+        if Some(response.header.control.seq) != context.seq {
+            self.unexpected_sequence(source, response.header.control.seq, context.seq);
             return Ok(None);
         }
 
         if !response.header.control.is_fir_and_fin() {
-            return Err(TaskError::MultiFragmentResponse);
+            return Err(self.unexpected_fir_fin(TaskError::MultiFragmentResponse(context)));
         }
 
         Ok(Some(response))
@@ -482,17 +872,62 @@ impl MasterSession {
         &mut self,
         io: &mut PhysLayer,
         destination: EndpointAddress,
-        task: ReadTask,
+        mut task: ReadTask,
+        correlation_id: u64,
         writer: &mut TransportWriter,
         reader: &mut TransportReader,
-    ) -> Result<(), TaskError> {
-        let result = self
-            .execute_read_task(io, destination, &task, writer, reader)
-            .await;
+    ) -> Result<ReadOutcome, TaskError> {
+        let requested: Vec<_> = task.variations_mut().into_iter().map(|x| *x).collect();
+        if let Ok(association) = self.associations.get_mut(destination) {
+            association.substitute_variations(&mut task);
+        }
+
+        let mut fallbacks = 0;
+        let result = loop {
+            let iin2 = match self
+                .execute_read_task(io, destination, &task, writer, reader)
+                .await
+            {
+                Ok(iin2) => iin2,
+                Err(err) => break Err(err),
+            };
+
+            let association = match self.associations.get_mut(destination) {
+                Ok(association) => association,
+                Err(_) => break Ok(()),
+            };
+
+            if !iin2.get_object_unknown() {
+                if fallbacks > 0 {
+                    association.remember_variations(&requested, &mut task);
+                }
+                break Ok(());
+            }
 
+            if fallbacks == Self::MAX_VARIATION_FALLBACKS
+                || !association.fall_back_variations(&mut task)
+            {
+                break Ok(());
+            }
+
+            fallbacks += 1;
+            tracing::warn!(
+                "outstation {} reported OBJECT_UNKNOWN, retrying READ with fallback variations",
+                destination
+            );
+        };
+
+        let preemption = self.associations.read_preemption();
         let association = self.associations.get_mut(destination).ok();
 
         match result {
+            Err(TaskError::Preempted) if preemption == ReadPreemption::Postpone => {
+                if let Some(association) = association {
+                    // the READ keeps its ID when it runs again
+                    association.postpone_read(task, Some(correlation_id));
+                }
+                return Ok(ReadOutcome::Postponed);
+            }
             Ok(_) => {
                 if let Some(association) = association {
                     task.complete(association);
@@ -503,7 +938,13 @@ impl MasterSession {
             Err(err) => task.on_task_error(association, err),
         }
 
-        result
+        result.map(|_| ReadOutcome::Completed)
+    }
+
+    fn response_timeout(&self, destination: EndpointAddress) -> Timeout {
+        self.associations
+            .response_timeout(destination)
+            .unwrap_or(self.timeout)
     }
 
     async fn execute_read_task(
@@ -513,33 +954,40 @@ impl MasterSession {
         task: &ReadTask,
         writer: &mut TransportWriter,
         reader: &mut TransportReader,
-    ) -> Result<(), TaskError> {
+    ) -> Result<Iin2, TaskError> {
         let mut seq = self.send_request(io, destination, task, writer).await?;
         let mut is_first = true;
+        // IIN2 bits of all the responses
+        let mut iin2 = Iin2::default();
 
         // read responses until we get a FIN or an error occurs
         loop {
-            let deadline = self.timeout.deadline_from_now();
+            let timeout = self.response_timeout(destination);
+            let deadline = timeout.deadline_from_now(&self.clock);
 
             loop {
                 crate::tokio::select! {
-                    _ = crate::tokio::time::sleep_until(deadline) => {
-                            tracing::warn!("no response within timeout: {}", self.timeout);
-                            return Err(TaskError::ResponseTimeout);
+                    _ = self.clock.sleep_until(deadline) => {
+                            tracing::warn!("no response within timeout: {}", timeout);
+                            return Err(TaskError::ResponseTimeout(RequestContext::request(destination, task.function(), seq)));
                     }
                     x = reader.read(io, self.decode_level) => {
                         x?;
                         match reader.pop_response() {
-                            Some(TransportResponse::Response(source, response)) => {
-                                self.notify_link_activity(source);
+                            Some(TransportResponse::Response(source, received, response)) => {
+                                self.notify_fragment_received(source, received);
+                                let response_iin2 = response.header.iin.iin2;
                                 let action = self.process_read_response(destination, is_first, seq, &task, io, writer, source, response).await?;
                                 match action {
                                     // continue reading responses on the inner loop
                                     ReadResponseAction::Ignore => continue,
                                     // read task complete
-                                    ReadResponseAction::Complete => return Ok(()),
+                                    ReadResponseAction::Complete => return Ok(iin2 | response_iin2),
+                                    // a queued command runs instead of the rest of the response
+                                    ReadResponseAction::Preempt => return Err(TaskError::Preempted),
                                     // break to the outer loop and read another response
                                     ReadResponseAction::ReadNext => {
+                                        iin2 |= response_iin2;
                                         is_first = false;
                                         seq = self.associations.get_mut(destination)?.increment_seq();
                                         break;
@@ -587,31 +1035,47 @@ impl MasterSession {
         }
 
         if response.header.control.seq != seq {
-            tracing::warn!(
-                "response with seq: {} doesn't match expected seq: {}",
-                response.header.control.seq.value(),
-                seq.value()
-            );
+            self.unexpected_sequence(source, response.header.control.seq, Some(seq));
             return Ok(ReadResponseAction::Ignore);
         }
 
         // now do validations
+        let context = RequestContext::request(destination, task.function(), seq);
 
         if response.header.control.fir && !is_first {
-            return Err(TaskError::UnexpectedFir);
+            return Err(self.unexpected_fir_fin(TaskError::UnexpectedFir(context)));
         }
 
         if !response.header.control.fir && is_first {
-            return Err(TaskError::NeverReceivedFir);
+            return Err(self.unexpected_fir_fin(TaskError::NeverReceivedFir(context)));
         }
 
         if !response.header.control.fin && !response.header.control.con {
-            return Err(TaskError::NonFinWithoutCon);
+            return Err(self.unexpected_fir_fin(TaskError::NonFinWithoutCon(context)));
         }
 
         let association = self.associations.get_mut(destination)?;
+        association.on_solicited_response(seq);
         association.process_iin(response.header.iin);
-        task.process_response(association, response.header, response.objects?);
+        // the registry is cloned because the objects borrow it while the association is mutated
+        let vendor_objects = association.vendor_objects();
+        let objects =
+            association.get_objects(&response, &vendor_objects, self.counters.anomalies())?;
+        Self::check_fragment_sizes(
+            destination,
+            objects,
+            self.tx_buffer_size,
+            self.rx_buffer_size,
+        );
+        task.process_response(association, response.header, objects, self.correlation_id);
+
+        if !response.header.control.fin && self.associations.preempts_read() {
+            tracing::info!(
+                "interrupting READ of {} to run a queued command",
+                destination
+            );
+            return Ok(ReadResponseAction::Preempt);
+        }
 
         if response.header.control.con {
             self.confirm_solicited(io, destination, seq, writer).await?;
@@ -624,7 +1088,69 @@ impl MasterSession {
         }
     }
 
+    /// warn if the fragment sizes reported by the outstation in its device attributes don't
+    /// match the buffer sizes of the master
+    fn check_fragment_sizes(
+        source: EndpointAddress,
+        objects: HeaderCollection,
+        tx_buffer_size: usize,
+        rx_buffer_size: usize,
+    ) {
+        for mismatch in Self::fragment_size_mismatches(objects, tx_buffer_size, rx_buffer_size) {
+            match mismatch {
+                FragmentSizeMismatch::Tx(size) => tracing::warn!(
+                    "outstation {} may send fragments of up to {} bytes, but the RX buffer size is {}",
+                    source,
+                    size,
+                    rx_buffer_size
+                ),
+                FragmentSizeMismatch::Rx(size) => tracing::warn!(
+                    "outstation {} only accepts fragments of up to {} bytes, but requests may be up to {} bytes",
+                    source,
+                    size,
+                    tx_buffer_size
+                ),
+            }
+        }
+    }
+
+    fn fragment_size_mismatches(
+        objects: HeaderCollection,
+        tx_buffer_size: usize,
+        rx_buffer_size: usize,
+    ) -> Vec<FragmentSizeMismatch> {
+        let mut mismatches = Vec::new();
+        for header in objects.iter() {
+            let attr = match header.details {
+                HeaderDetails::OneByteStartStop(_, _, RangedVariation::Group0VarX(attr)) => attr,
+                HeaderDetails::TwoByteStartStop(_, _, RangedVariation::Group0VarX(attr)) => attr,
+                _ => continue,
+            };
+            let size = match (attr.set, attr.value) {
+                (0, AttrValue::UnsignedInt(size)) => size as usize,
+                _ => continue,
+            };
+            match attr.variation {
+                Self::ATTR_MAX_TX_FRAGMENT_SIZE if size > rx_buffer_size => {
+                    mismatches.push(FragmentSizeMismatch::Tx(size))
+                }
+                Self::ATTR_MAX_RX_FRAGMENT_SIZE if size < tx_buffer_size => {
+                    mismatches.push(FragmentSizeMismatch::Rx(size))
+                }
+                _ => {}
+            }
+        }
+        mismatches
+    }
+
     fn get_next_task(&mut self) -> Next<AssociationTask> {
+        // only the requests queued before a shutdown are run while draining
+        if self.is_draining() {
+            return self
+                .associations
+                .next_user_task()
+                .map_or(Next::None, Next::Now);
+        }
         self.associations.next_task()
     }
 }
@@ -642,10 +1168,7 @@ impl MasterSession {
             self.handle_unsolicited(source, &response, io, writer)
                 .await?;
         } else {
-            tracing::warn!(
-                "unexpected response with sequence: {}",
-                response.header.control.seq.value()
-            )
+            self.unexpected_sequence(source, response.header.control.seq, None);
         }
 
         Ok(())
@@ -659,6 +1182,37 @@ impl MasterSession {
         writer: &mut TransportWriter,
     ) -> Result<(), LinkError> {
         let association = match self.associations.get_mut(source).ok() {
+            Some(x) => x,
+            None => {
+                return self
+                    .handle_unknown_unsolicited(source, response, io, writer)
+                    .await
+            }
+        };
+
+        association.process_iin(response.header.iin);
+
+        let valid = association.handle_unsolicited_response(response, self.counters.anomalies());
+
+        // Send confirmation if required and wasn't ignored
+        if valid && response.header.control.con {
+            self.confirm_unsolicited(io, source, response.header.control.seq, writer)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl MasterSession {
+    async fn handle_unknown_unsolicited(
+        &mut self,
+        source: EndpointAddress,
+        response: &Response<'_>,
+        io: &mut PhysLayer,
+        writer: &mut TransportWriter,
+    ) -> Result<(), LinkError> {
+        let handler = match &mut self.catch_all {
             Some(x) => x,
             None => {
                 tracing::warn!(
@@ -669,12 +1223,18 @@ impl MasterSession {
             }
         };
 
-        association.process_iin(response.header.iin);
-
-        let valid = association.handle_unsolicited_response(response);
+        if let Ok(objects) = response.objects {
+            extract_measurements(
+                ReadType::Unsolicited,
+                response.header,
+                objects,
+                self.fragment_received,
+                None,
+                handler.get_read_handler(source),
+            );
+        }
 
-        // Send confirmation if required and wasn't ignored
-        if valid && response.header.control.con {
+        if response.header.control.con {
             self.confirm_unsolicited(io, source, response.header.control.seq, writer)
                 .await?;
         }
@@ -754,18 +1314,30 @@ impl MasterSession {
             .write_link_status_request(io, self.decode_level, destination.wrap())
             .await?;
 
+        let timeout = self.link.ack_timeout.unwrap_or(self.timeout);
+        let mut retries = self.link.retries;
+        let mut deadline = timeout.deadline_from_now(&self.clock);
+
         loop {
             // Wait for something on the link
             crate::tokio::select! {
-                _ = crate::tokio::time::sleep_until(self.timeout.deadline_from_now()) => {
-                    tracing::warn!("no response within timeout: {}", self.timeout);
-                    return Err(TaskError::ResponseTimeout);
+                _ = self.clock.sleep_until(deadline) => {
+                    if retries == 0 {
+                        tracing::warn!("no response within timeout: {}", timeout);
+                        return Err(TaskError::ResponseTimeout(RequestContext::link(destination)));
+                    }
+                    retries -= 1;
+                    tracing::warn!("no response within timeout: {} - retrying link status request", timeout);
+                    writer
+                        .write_link_status_request(io, self.decode_level, destination.wrap())
+                        .await?;
+                    deadline = timeout.deadline_from_now(&self.clock);
                 }
                 x = reader.read(io, self.decode_level) => {
                     x?;
                     match reader.pop_response() {
-                        Some(TransportResponse::Response(source, response)) => {
-                            self.notify_link_activity(source);
+                        Some(TransportResponse::Response(source, received, response)) => {
+                            self.notify_fragment_received(source, received);
                             self.handle_fragment_while_idle(io, writer, source, response).await?;
                             return Ok(LinkStatusResult::UnexpectedResponse);
                         }
@@ -789,4 +1361,50 @@ impl MasterSession {
             association.on_link_activity();
         }
     }
+
+    fn notify_fragment_received(&mut self, source: EndpointAddress, received: RxTimestamp) {
+        self.fragment_received = Some(received);
+        if let Ok(association) = self.associations.get_mut(source) {
+            association.on_link_activity();
+            association.on_fragment_received(received);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app::FunctionCode;
+
+    // g0v240 (maximum TX fragment size) and g0v241 (maximum RX fragment size) as unsigned integers
+    const ATTRIBUTES: &[u8] = &[
+        0x00, 0xF0, 0x00, 0x00, 0x00, 0x02, 0x02, 0x00, 0x10, 0x00, 0xF1, 0x00, 0x00, 0x00, 0x02,
+        0x02, 0x00, 0x04,
+    ];
+
+    fn mismatches(tx_buffer_size: usize, rx_buffer_size: usize) -> Vec<FragmentSizeMismatch> {
+        let objects = HeaderCollection::parse(FunctionCode::Response, ATTRIBUTES).unwrap();
+        MasterSession::fragment_size_mismatches(objects, tx_buffer_size, rx_buffer_size)
+    }
+
+    #[test]
+    fn matching_fragment_sizes_are_accepted() {
+        assert_eq!(mismatches(1024, 4096), Vec::new());
+        assert_eq!(mismatches(512, 8192), Vec::new());
+    }
+
+    #[test]
+    fn fragment_sizes_that_do_not_fit_the_buffers_are_reported() {
+        // the outstation sends fragments of up to 4096 bytes
+        assert_eq!(mismatches(1024, 2048), vec![FragmentSizeMismatch::Tx(4096)]);
+        // the outstation accepts fragments of up to 1024 bytes
+        assert_eq!(mismatches(2048, 4096), vec![FragmentSizeMismatch::Rx(1024)]);
+        assert_eq!(
+            mismatches(2048, 2048),
+            vec![
+                FragmentSizeMismatch::Tx(4096),
+                FragmentSizeMismatch::Rx(1024)
+            ]
+        );
+    }
 }
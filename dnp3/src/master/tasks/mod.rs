@@ -1,5 +1,6 @@
 use crate::app::format::write::HeaderWriter;
 use crate::app::parse::parser::{HeaderCollection, Response};
+use crate::app::variations::Variation;
 use crate::app::FunctionCode;
 use crate::app::ResponseHeader;
 use crate::link::{EndpointAddress, LinkStatusResult};
@@ -7,12 +8,13 @@ use crate::master::association::Association;
 use crate::master::error::TaskError;
 use crate::master::handle::Promise;
 use crate::master::poll::Poll;
-use crate::master::request::{Classes, EventClasses};
+use crate::master::request::{ClassZeroRequest, Classes, EventClasses};
 use crate::master::tasks::auto::AutoTask;
 use crate::master::tasks::command::CommandTask;
 use crate::master::tasks::read::SingleReadTask;
 use crate::master::tasks::restart::RestartTask;
 use crate::master::tasks::time::TimeSyncTask;
+use crate::master::tasks::write::WriteTask;
 use crate::util::cursor::WriteError;
 
 pub(crate) mod auto;
@@ -20,6 +22,7 @@ pub(crate) mod command;
 pub(crate) mod read;
 pub(crate) mod restart;
 pub(crate) mod time;
+pub(crate) mod write;
 
 /// Queued task requiring I/O
 pub(crate) struct AssociationTask {
@@ -27,11 +30,36 @@ pub(crate) struct AssociationTask {
     pub(crate) address: EndpointAddress,
     /// Actual task to perform
     pub(crate) details: Task,
+    /// Correlation ID supplied by the user who requested the task
+    pub(crate) correlation_id: Option<u64>,
 }
 
 impl AssociationTask {
-    pub(crate) fn new(address: EndpointAddress, details: Task) -> Self {
-        Self { address, details }
+    pub(crate) fn new(
+        address: EndpointAddress,
+        details: Task,
+        correlation_id: Option<u64>,
+    ) -> Self {
+        Self {
+            address,
+            details,
+            correlation_id,
+        }
+    }
+}
+
+/// Task requested by a user, queued along with its correlation ID
+pub(crate) struct UserTask {
+    pub(crate) task: Task,
+    pub(crate) correlation_id: Option<u64>,
+}
+
+impl UserTask {
+    pub(crate) fn new(task: Task, correlation_id: Option<u64>) -> Self {
+        Self {
+            task,
+            correlation_id,
+        }
     }
 }
 
@@ -61,6 +89,38 @@ impl Task {
         }
     }
 
+    pub(crate) fn is_single_read(&self) -> bool {
+        matches!(self, Task::Read(ReadTask::SingleRead(_)))
+    }
+
+    pub(crate) fn is_read(&self) -> bool {
+        matches!(self, Task::Read(_))
+    }
+
+    pub(crate) fn is_command(&self) -> bool {
+        matches!(self, Task::NonRead(NonReadTask::Command(_)))
+    }
+
+    /// merge `other` into this task if both are identical READ requests, returning `other` if
+    /// they can't be merged
+    pub(crate) fn coalesce(&mut self, other: Task, correlation_id: Option<u64>) -> Option<Task> {
+        match (self, other) {
+            (Task::Read(ReadTask::SingleRead(task)), Task::Read(ReadTask::SingleRead(other))) => {
+                task.coalesce(other, correlation_id)
+                    .map(|other| other.wrap().wrap())
+            }
+            (_, other) => Some(other),
+        }
+    }
+
+    /// correlation IDs of the requests merged into this task, which complete along with it
+    pub(crate) fn coalesced_ids(&self) -> Vec<u64> {
+        match self {
+            Task::Read(ReadTask::SingleRead(task)) => task.coalesced_ids(),
+            _ => Vec::new(),
+        }
+    }
+
     /// Perform operation before sending and check if the request should still be sent
     ///
     /// Returning `true` means the task should proceed, returning false means
@@ -91,7 +151,7 @@ pub(crate) enum ReadTask {
     /// Periodic polls that are configured when creating associations
     PeriodicPoll(Poll),
     /// Integrity poll that occurs during startup, or after outstation restarts
-    StartupIntegrity(Classes),
+    StartupIntegrity(Classes, ClassZeroRequest),
     /// Event scan when IIN bit is detected
     EventScan(EventClasses),
     /// One-time read request
@@ -107,6 +167,8 @@ pub(crate) enum NonReadTask {
     TimeSync(TimeSyncTask),
     /// restart operation
     Restart(RestartTask),
+    /// write of user-supplied objects
+    Write(WriteTask),
 }
 
 impl RequestWriter for ReadTask {
@@ -117,7 +179,7 @@ impl RequestWriter for ReadTask {
     fn write(&self, writer: &mut HeaderWriter) -> Result<(), WriteError> {
         match self {
             ReadTask::PeriodicPoll(poll) => poll.format(writer),
-            ReadTask::StartupIntegrity(classes) => classes.write(writer),
+            ReadTask::StartupIntegrity(classes, class0) => classes.write_with(class0, writer),
             ReadTask::EventScan(classes) => classes.write(writer),
             ReadTask::SingleRead(req) => req.format(writer),
         }
@@ -135,6 +197,7 @@ impl RequestWriter for NonReadTask {
             NonReadTask::Command(t) => t.write(writer),
             NonReadTask::TimeSync(t) => t.write(writer),
             NonReadTask::Restart(_) => Ok(()),
+            NonReadTask::Write(t) => t.write(writer),
         }
     }
 }
@@ -144,23 +207,42 @@ impl ReadTask {
         Task::Read(self)
     }
 
+    /// variations of the headers that request specific objects
+    pub(crate) fn variations_mut(&mut self) -> Vec<&mut Variation> {
+        match self {
+            ReadTask::PeriodicPoll(poll) => poll.variations_mut(),
+            ReadTask::StartupIntegrity(_, class0) => class0.variations_mut(),
+            ReadTask::EventScan(_) => Vec::new(),
+            ReadTask::SingleRead(task) => task.variations_mut(),
+        }
+    }
+
     pub(crate) fn process_response(
         &self,
         association: &mut Association,
         header: ResponseHeader,
         objects: HeaderCollection,
+        correlation_id: Option<u64>,
     ) {
         match self {
-            ReadTask::StartupIntegrity(_) => association.handle_integrity_response(header, objects),
-            ReadTask::PeriodicPoll(_) => association.handle_poll_response(header, objects),
-            ReadTask::EventScan(_) => association.handle_event_scan_response(header, objects),
-            ReadTask::SingleRead(_) => association.handle_read_response(header, objects),
+            ReadTask::StartupIntegrity(..) => {
+                association.handle_integrity_response(header, objects, correlation_id)
+            }
+            ReadTask::PeriodicPoll(_) => {
+                association.handle_poll_response(header, objects, correlation_id)
+            }
+            ReadTask::EventScan(_) => {
+                association.handle_event_scan_response(header, objects, correlation_id)
+            }
+            ReadTask::SingleRead(_) => {
+                association.handle_read_response(header, objects, correlation_id)
+            }
         }
     }
 
     pub(crate) fn complete(self, association: &mut Association) {
         match self {
-            ReadTask::StartupIntegrity(_) => association.on_integrity_scan_complete(),
+            ReadTask::StartupIntegrity(..) => association.on_integrity_scan_complete(),
             ReadTask::PeriodicPoll(poll) => association.complete_poll(poll.id),
             ReadTask::EventScan(_) => association.on_event_scan_complete(),
             ReadTask::SingleRead(task) => task.on_complete(),
@@ -169,7 +251,7 @@ impl ReadTask {
 
     pub(crate) fn on_task_error(self, association: Option<&mut Association>, err: TaskError) {
         match self {
-            ReadTask::StartupIntegrity(_) => {
+            ReadTask::StartupIntegrity(..) => {
                 if let Some(association) = association {
                     association.on_integrity_scan_failure();
                 }
@@ -201,6 +283,7 @@ impl NonReadTask {
             NonReadTask::Auto(_) => Some(self),
             NonReadTask::TimeSync(task) => task.start(association).map(|task| task.wrap()),
             NonReadTask::Restart(_) => Some(self),
+            NonReadTask::Write(_) => Some(self),
         }
     }
 
@@ -210,6 +293,7 @@ impl NonReadTask {
             NonReadTask::Auto(task) => task.function(),
             NonReadTask::TimeSync(task) => task.function(),
             NonReadTask::Restart(task) => task.function(),
+            NonReadTask::Write(task) => task.function(),
         }
     }
 
@@ -219,6 +303,7 @@ impl NonReadTask {
             NonReadTask::TimeSync(task) => task.on_task_error(association, err),
             NonReadTask::Auto(task) => task.on_task_error(association, err),
             NonReadTask::Restart(task) => task.on_task_error(err),
+            NonReadTask::Write(task) => task.on_task_error(err),
         }
     }
 
@@ -235,6 +320,7 @@ impl NonReadTask {
             },
             NonReadTask::TimeSync(task) => task.handle(association, response),
             NonReadTask::Restart(task) => task.handle(response),
+            NonReadTask::Write(task) => task.handle(response),
         }
     }
 }
@@ -1,4 +1,5 @@
 use crate::app::format::write::HeaderWriter;
+use crate::app::variations::Variation;
 use crate::master::error::TaskError;
 use crate::master::handle::Promise;
 use crate::master::request::ReadRequest;
@@ -8,11 +9,44 @@ use crate::util::cursor::WriteError;
 pub(crate) struct SingleReadTask {
     request: ReadRequest,
     promise: Promise<Result<(), TaskError>>,
+    // identical requests that complete along with this one, with their correlation IDs
+    coalesced: Vec<(Promise<Result<(), TaskError>>, u64)>,
 }
 
 impl SingleReadTask {
     pub(crate) fn new(request: ReadRequest, promise: Promise<Result<(), TaskError>>) -> Self {
-        Self { request, promise }
+        Self {
+            request,
+            promise,
+            coalesced: Vec::new(),
+        }
+    }
+
+    /// merge `other` into this task if the requests are identical, returning `other` otherwise
+    ///
+    /// The merged request keeps its own correlation ID, or is allocated one if it has none, so
+    /// that its completion can be reported separately.
+    pub(crate) fn coalesce(
+        &mut self,
+        other: SingleReadTask,
+        correlation_id: Option<u64>,
+    ) -> Option<SingleReadTask> {
+        if self.request != other.request {
+            return Some(other);
+        }
+        let id = correlation_id.unwrap_or_else(crate::decode::next_correlation_id);
+        tracing::info!(
+            "READ with cid {} coalesced into an identical queued READ",
+            id
+        );
+        self.coalesced.push((other.promise, id));
+        self.coalesced.extend(other.coalesced);
+        None
+    }
+
+    /// correlation IDs of the requests merged into this one
+    pub(crate) fn coalesced_ids(&self) -> Vec<u64> {
+        self.coalesced.iter().map(|(_, id)| *id).collect()
     }
 
     pub(crate) fn wrap(self) -> ReadTask {
@@ -23,11 +57,21 @@ impl SingleReadTask {
         self.request.format(writer)
     }
 
+    pub(crate) fn variations_mut(&mut self) -> Vec<&mut Variation> {
+        self.request.variations_mut()
+    }
+
     pub(crate) fn on_task_error(self, err: TaskError) {
+        for (promise, _) in self.coalesced {
+            promise.complete(Err(err))
+        }
         self.promise.complete(Err(err))
     }
 
     pub(crate) fn on_complete(self) {
+        for (promise, _) in self.coalesced {
+            promise.complete(Ok(()))
+        }
         self.promise.complete(Ok(()))
     }
 }
@@ -116,7 +116,7 @@ mod tests {
     use crate::app::format::write::{start_request, start_response};
     use crate::app::variations::{Group52Var1, Group52Var2};
     use crate::app::Sequence;
-    use crate::app::{ControlField, Iin, ResponseFunction};
+    use crate::app::{Clock, ControlField, Iin, ResponseFunction};
     use crate::link::EndpointAddress;
     use crate::master::association::{Association, AssociationConfig};
     use crate::master::tasks::RequestWriter;
@@ -132,6 +132,7 @@ mod tests {
             AssociationConfig::default(),
             NullReadHandler::boxed(),
             DefaultAssociationHandler::boxed(),
+            Clock::default(),
         );
         let (tx, mut rx) = crate::tokio::sync::oneshot::channel();
         let task = NonReadTask::Restart(RestartTask::new(
@@ -179,6 +180,7 @@ mod tests {
             AssociationConfig::default(),
             NullReadHandler::boxed(),
             DefaultAssociationHandler::boxed(),
+            Clock::default(),
         );
         let (tx, mut rx) = crate::tokio::sync::oneshot::channel();
         let task = NonReadTask::Restart(RestartTask::new(
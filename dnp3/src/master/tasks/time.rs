@@ -5,13 +5,13 @@ use crate::app::gen::count::CountVariation;
 use crate::app::parse::parser::Response;
 use crate::app::variations::{Group50Var1, Group50Var3};
 use crate::app::FunctionCode;
+use crate::app::Instant;
 use crate::app::Timestamp;
 use crate::master::association::Association;
 use crate::master::error::{TaskError, TimeSyncError};
 use crate::master::handle::Promise;
 use crate::master::request::TimeSyncProcedure;
 use crate::master::tasks::NonReadTask;
-use crate::tokio::time::Instant;
 use crate::util::cursor::WriteError;
 
 enum State {
@@ -58,7 +58,7 @@ impl TimeSyncTask {
     pub(crate) fn start(mut self, association: &mut Association) -> Option<Self> {
         match &mut self.state {
             State::MeasureDelay(time) => {
-                time.replace(Instant::now());
+                time.replace(association.now());
 
                 match association.get_system_time() {
                     Some(_) => Some(self),
@@ -136,13 +136,13 @@ impl TimeSyncTask {
         request_tx: Option<Instant>,
         response: Response,
     ) -> Option<NonReadTask> {
-        let request_tx = request_tx.unwrap_or_else(Instant::now);
-        let now = Instant::now();
+        let now = association.now();
+        let request_tx = request_tx.unwrap_or(now);
 
         let interval = match now.checked_duration_since(request_tx) {
             Some(x) => x,
             None => {
-                // This should NEVER happen. `crate::tokio::time::Instant` is guaranteed to be monotonic and nondecreasing.
+                // This should NEVER happen unless the `Timer` of a custom clock goes backwards. The instants of a clock are otherwise monotonic and nondecreasing.
                 tracing::error!("clock rollback detected while synchronizing outstation");
                 self.report_error(association, TimeSyncError::ClockRollback);
                 return None;
@@ -348,6 +348,7 @@ mod tests {
         use crate::app::QualifierCode;
         use crate::link::EndpointAddress;
         use crate::master::association::AssociationConfig;
+        use crate::master::error::RequestContext;
 
         use super::*;
 
@@ -527,11 +528,13 @@ mod tests {
                 crate::tokio::time::advance(Duration::from_millis(TOTAL_DELAY_MS as u64));
                 let task = send_measure_delay_response(task, &mut association).unwrap();
                 let task = check_write_request(task, &mut association, system_time);
-                task.on_task_error(Some(&mut association), TaskError::ResponseTimeout);
-                assert_eq!(
-                    rx.try_recv().unwrap(),
-                    Err(TimeSyncError::Task(TaskError::ResponseTimeout))
-                );
+                let err = TaskError::ResponseTimeout(RequestContext::request(
+                    EndpointAddress::from(1).unwrap(),
+                    FunctionCode::Write,
+                    Sequence::default(),
+                ));
+                task.on_task_error(Some(&mut association), err);
+                assert_eq!(rx.try_recv().unwrap(), Err(TimeSyncError::Task(err)));
             });
         }
 
@@ -579,6 +582,7 @@ mod tests {
                 AssociationConfig::default(),
                 NullReadHandler::boxed(),
                 Box::new(TestHandler::new(system_time)),
+                Clock::default(),
             );
             let (tx, rx) = crate::tokio::sync::oneshot::channel();
             let task = NonReadTask::TimeSync(TimeSyncTask::get_procedure(
@@ -603,6 +607,7 @@ mod tests {
                 AssociationConfig::default(),
                 NullReadHandler::boxed(),
                 Box::new(SingleTimestampTestHandler::new(system_time)),
+                Clock::default(),
             );
             let (tx, rx) = crate::tokio::sync::oneshot::channel();
             let task = NonReadTask::TimeSync(TimeSyncTask::get_procedure(
@@ -711,6 +716,7 @@ mod tests {
     mod lan {
         use crate::link::EndpointAddress;
         use crate::master::association::AssociationConfig;
+        use crate::master::error::RequestContext;
 
         use super::*;
 
@@ -819,11 +825,13 @@ mod tests {
                 crate::tokio::time::advance(Duration::from_millis(DELAY_MS as u64));
                 let task = send_record_current_time_response(task, &mut association).unwrap();
                 let task = check_write_request(task, &mut association, system_time);
-                task.on_task_error(Some(&mut association), TaskError::ResponseTimeout);
-                assert_eq!(
-                    rx.try_recv().unwrap(),
-                    Err(TimeSyncError::Task(TaskError::ResponseTimeout))
-                );
+                let err = TaskError::ResponseTimeout(RequestContext::request(
+                    EndpointAddress::from(1).unwrap(),
+                    FunctionCode::Write,
+                    Sequence::default(),
+                ));
+                task.on_task_error(Some(&mut association), err);
+                assert_eq!(rx.try_recv().unwrap(), Err(TimeSyncError::Task(err)));
             });
         }
 
@@ -854,6 +862,7 @@ mod tests {
                 AssociationConfig::default(),
                 NullReadHandler::boxed(),
                 Box::new(SingleTimestampTestHandler::new(system_time)),
+                Clock::default(),
             );
             let (tx, rx) = crate::tokio::sync::oneshot::channel();
             let task = NonReadTask::TimeSync(TimeSyncTask::get_procedure(
@@ -0,0 +1,88 @@
+use crate::app::format::write::HeaderWriter;
+use crate::app::parse::parser::Response;
+use crate::app::variations::Group50Var4;
+use crate::app::FunctionCode;
+use crate::app::IndexedTime;
+use crate::master::error::{TaskError, WriteRequestError};
+use crate::master::handle::Promise;
+use crate::master::tasks::NonReadTask;
+use crate::util::cursor::WriteError;
+
+pub(crate) struct WriteTask {
+    values: Vec<(Group50Var4, u16)>,
+    promise: Promise<Result<(), WriteRequestError>>,
+}
+
+impl WriteTask {
+    pub(crate) fn indexed_time(
+        values: Vec<(IndexedTime, u16)>,
+        promise: Promise<Result<(), WriteRequestError>>,
+    ) -> Self {
+        Self {
+            values: values.into_iter().map(|(v, i)| (v.into(), i)).collect(),
+            promise,
+        }
+    }
+
+    pub(crate) fn wrap(self) -> NonReadTask {
+        NonReadTask::Write(self)
+    }
+
+    pub(crate) fn function(&self) -> FunctionCode {
+        FunctionCode::Write
+    }
+
+    pub(crate) fn write(&self, writer: &mut HeaderWriter) -> Result<(), WriteError> {
+        writer.write_prefixed_items(self.values.iter())
+    }
+
+    pub(crate) fn on_task_error(self, err: TaskError) {
+        self.promise.complete(Err(err.into()))
+    }
+
+    pub(crate) fn handle(self, response: Response) -> Option<NonReadTask> {
+        if response.header.iin.has_request_error() {
+            self.promise
+                .complete(Err(WriteRequestError::IinError(response.header.iin.iin2)));
+        } else {
+            self.promise.complete(Ok(()));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::format::write::start_request;
+    use crate::app::gen::prefixed::PrefixedVariation;
+    use crate::app::parse::parser::HeaderDetails;
+    use crate::app::{ControlField, Sequence, Timestamp};
+
+    #[test]
+    fn writes_indexed_time_with_two_byte_prefix() {
+        let value = IndexedTime::new(Timestamp::new(0x0102_0304_0506), 15, 3);
+        let task = WriteTask::indexed_time(vec![(value, 7)], Promise::None);
+
+        let mut buffer = [0; 32];
+        let mut cursor = crate::util::cursor::WriteCursor::new(&mut buffer);
+        let mut writer = start_request(
+            ControlField::request(Sequence::default()),
+            task.function(),
+            &mut cursor,
+        )
+        .unwrap();
+        task.write(&mut writer).unwrap();
+
+        let request = writer.to_parsed().to_request().unwrap();
+        let header = request.objects.unwrap().get_only_header().unwrap();
+        match header.details {
+            HeaderDetails::TwoByteCountAndPrefix(1, PrefixedVariation::Group50Var4(seq)) => {
+                let item = seq.single().unwrap();
+                assert_eq!(item.index, 7);
+                assert_eq!(IndexedTime::from(item.value), value);
+            }
+            x => panic!("unexpected header: {:?}", x),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+
+use crate::app::Sequence;
+use crate::link::EndpointAddress;
+use crate::master::association::AssociationConfig;
+use crate::master::handle::{CatchAllHandler, ReadHandler};
+use crate::tokio::test::*;
+
+use super::harness::requests::*;
+use super::harness::{create_association, CountHandler};
+
+struct CatchAll {
+    sources: Arc<Mutex<Vec<EndpointAddress>>>,
+    handler: CountHandler,
+}
+
+impl CatchAllHandler for CatchAll {
+    fn get_read_handler(&mut self, source: EndpointAddress) -> &mut dyn ReadHandler {
+        self.sources.lock().unwrap().push(source);
+        &mut self.handler
+    }
+}
+
+#[test]
+fn unsolicited_responses_from_unknown_addresses_are_delivered_to_catch_all_handler() {
+    let unsol_seq = Sequence::default();
+    let mut harness = create_association(AssociationConfig::quiet());
+    let address = harness.association.address();
+
+    // the outstation no longer matches an association
+    let mut master = harness.master.clone();
+    assert_ready!(spawn(master.remove_association(address)).poll()).unwrap();
+    assert_pending!(harness.poll());
+
+    // without a catch-all handler, the response is ignored
+    unsol_with_data(&mut harness.io, unsol_seq, 42, false);
+    harness.assert_io();
+
+    let sources = Arc::new(Mutex::new(Vec::new()));
+    let handler = CountHandler::new();
+    let num_requests = handler.num_requests.clone();
+    let catch_all = CatchAll {
+        sources: sources.clone(),
+        handler,
+    };
+    assert_ready!(spawn(master.set_catch_all_handler(Some(Box::new(catch_all)))).poll()).unwrap();
+    assert_pending!(harness.poll());
+
+    // the response is delivered and confirmed
+    unsol_with_data(&mut harness.io, unsol_seq, 43, false);
+    unsol_confirm(&mut harness.io, unsol_seq);
+    harness.assert_io();
+
+    assert_eq!(num_requests.load(Ordering::SeqCst), 1);
+    assert_eq!(*sources.lock().unwrap(), [address]);
+    assert_eq!(harness.num_requests(), 0);
+}
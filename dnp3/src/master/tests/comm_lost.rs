@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use crate::app::variations::Variation;
+use crate::app::Sequence;
+use crate::master::association::AssociationConfig;
+use crate::master::error::TaskError;
+use crate::master::request::ReadRequest;
+use crate::tokio::test::*;
+use crate::tokio::time;
+
+use super::harness::create_association;
+use super::harness::requests::*;
+
+#[test]
+fn points_are_reported_after_consecutive_timeouts_and_on_recovery() {
+    let mut config = AssociationConfig::quiet();
+    config.comm_lost_timeouts = Some(2);
+    let mut seq = Sequence::default();
+    let unsol_seq = Sequence::default();
+    let mut harness = create_association(config);
+
+    // the value of the analog is cached
+    unsol_with_data(&mut harness.io, unsol_seq, 42, false);
+    unsol_confirm(&mut harness.io, unsol_seq);
+    harness.assert_io();
+    assert_eq!(harness.num_requests(), 1);
+
+    // only the second consecutive timeout reports the analog with COMM_LOST
+    for expected in [1, 2].iter() {
+        let mut association = harness.association.clone();
+        let mut read = spawn(association.read(ReadRequest::all_objects(Variation::Group30Var0)));
+        assert_pending!(read.poll());
+        read_request(&mut harness.io, seq.increment(), Variation::Group30Var0);
+        harness.assert_io();
+
+        time::advance(Duration::from_secs(1));
+        assert_pending!(harness.poll());
+        assert_matches!(
+            assert_ready!(read.poll()),
+            Err(TaskError::ResponseTimeout(_))
+        );
+        assert_eq!(harness.num_requests(), *expected);
+    }
+
+    // the analog wasn't updated by the response, so it is reported again without COMM_LOST
+    let mut association = harness.association.clone();
+    let mut read = spawn(association.read(ReadRequest::all_objects(Variation::Group1Var0)));
+    assert_pending!(read.poll());
+    read_request(&mut harness.io, seq, Variation::Group1Var0);
+    empty_response(&mut harness.io, seq.increment());
+    harness.assert_io();
+    assert_ready!(read.poll()).unwrap();
+    assert_eq!(harness.num_requests(), 3);
+}
+
+#[test]
+fn points_are_not_reported_when_disabled() {
+    let mut seq = Sequence::default();
+    let unsol_seq = Sequence::default();
+    let mut harness = create_association(AssociationConfig::quiet());
+
+    unsol_with_data(&mut harness.io, unsol_seq, 42, false);
+    unsol_confirm(&mut harness.io, unsol_seq);
+    harness.assert_io();
+
+    for _ in 0..3 {
+        let mut association = harness.association.clone();
+        let mut read = spawn(association.read(ReadRequest::all_objects(Variation::Group30Var0)));
+        assert_pending!(read.poll());
+        read_request(&mut harness.io, seq.increment(), Variation::Group30Var0);
+        harness.assert_io();
+
+        time::advance(Duration::from_secs(1));
+        assert_pending!(harness.poll());
+        assert_matches!(
+            assert_ready!(read.poll()),
+            Err(TaskError::ResponseTimeout(_))
+        );
+    }
+
+    assert_eq!(harness.num_requests(), 1);
+}
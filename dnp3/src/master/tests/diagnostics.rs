@@ -0,0 +1,85 @@
+use crate::app::format::write::start_response;
+use crate::app::variations::Variation;
+use crate::app::{AnomalyKind, ControlField, Iin, ResponseFunction, Sequence};
+use crate::master::association::AssociationConfig;
+use crate::master::error::TaskError;
+use crate::master::request::ReadRequest;
+use crate::tokio::test::*;
+use crate::util::cursor::WriteCursor;
+
+use super::harness::create_association;
+use super::harness::requests::*;
+
+fn response(io: &mut io::Handle, seq: u8, fir: bool, fin: bool) {
+    let mut buffer = [0; 4];
+    let mut cursor = WriteCursor::new(&mut buffer);
+    start_response(
+        ControlField::response(Sequence::new(seq), fir, fin, false),
+        ResponseFunction::Response,
+        Iin::default(),
+        &mut cursor,
+    )
+    .unwrap();
+
+    io.read(cursor.written());
+}
+
+fn count(anomalies: &[crate::app::Anomaly], kind: AnomalyKind) -> u64 {
+    anomalies
+        .iter()
+        .find(|x| x.kind == kind)
+        .map(|x| x.count)
+        .unwrap_or(0)
+}
+
+#[test]
+fn duplicate_and_out_of_sequence_responses_are_reported() {
+    let mut harness = create_association(AssociationConfig::quiet());
+    let mut association = harness.association.clone();
+
+    let mut read = spawn(association.read(ReadRequest::all_objects(Variation::Group30Var0)));
+    assert_pending!(read.poll());
+    read_request(&mut harness.io, Sequence::new(0), Variation::Group30Var0);
+    response(&mut harness.io, 0, true, true);
+    harness.assert_io();
+    assert_ready!(read.poll()).unwrap();
+
+    let mut read = spawn(association.read(ReadRequest::all_objects(Variation::Group1Var0)));
+    assert_pending!(read.poll());
+    read_request(&mut harness.io, Sequence::new(1), Variation::Group1Var0);
+    // repeats the response to the previous request
+    response(&mut harness.io, 0, true, true);
+    response(&mut harness.io, 7, true, true);
+    response(&mut harness.io, 1, true, true);
+    harness.assert_io();
+    assert_ready!(read.poll()).unwrap();
+
+    // repeated after the read completed
+    response(&mut harness.io, 1, true, true);
+    harness.assert_io();
+
+    let anomalies = harness.master.get_anomalies();
+    assert_eq!(count(&anomalies, AnomalyKind::DuplicateResponse), 2);
+    assert_eq!(count(&anomalies, AnomalyKind::UnexpectedSequence), 1);
+    assert_eq!(count(&anomalies, AnomalyKind::UnexpectedFragment), 0);
+}
+
+#[test]
+fn unexpected_fir_fin_is_reported() {
+    let mut harness = create_association(AssociationConfig::quiet());
+    let mut association = harness.association.clone();
+
+    let mut read = spawn(association.read(ReadRequest::all_objects(Variation::Group30Var0)));
+    assert_pending!(read.poll());
+    read_request(&mut harness.io, Sequence::new(0), Variation::Group30Var0);
+    response(&mut harness.io, 0, false, true);
+    harness.assert_io();
+
+    assert!(matches!(
+        assert_ready!(read.poll()),
+        Err(TaskError::NeverReceivedFir(_))
+    ));
+
+    let anomalies = harness.master.get_anomalies();
+    assert_eq!(count(&anomalies, AnomalyKind::UnexpectedFirFin), 1);
+}
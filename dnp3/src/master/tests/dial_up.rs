@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use crate::app::variations::Variation;
+use crate::app::{Clock, Sequence};
+use crate::link::EndpointAddress;
+use crate::master::association::{
+    Association, AssociationConfig, AssociationMap, Next, SchedulingConfig,
+};
+use crate::master::request::ReadRequest;
+use crate::master::session::{RunError, StateChange};
+use crate::master::{DefaultAssociationHandler, NullReadHandler};
+use crate::tokio::test::*;
+use crate::tokio::time;
+
+use super::harness::create_association_with_idle_timeout;
+use super::harness::requests::*;
+
+fn map_with(config: AssociationConfig) -> AssociationMap {
+    let mut map = AssociationMap::new(SchedulingConfig::default(), Clock::default());
+    map.register(Association::new(
+        EndpointAddress::from(1024).unwrap(),
+        config,
+        NullReadHandler::boxed(),
+        DefaultAssociationHandler::boxed(),
+        Clock::default(),
+    ))
+    .unwrap();
+    map
+}
+
+#[test]
+fn startup_tasks_require_a_connection() {
+    let map = map_with(AssociationConfig::default());
+    assert!(matches!(map.next_work(), Next::Now(())));
+}
+
+#[test]
+fn keep_alives_do_not_require_a_connection() {
+    let config = AssociationConfig {
+        keep_alive_timeout: Some(Duration::from_secs(5)),
+        ..AssociationConfig::quiet()
+    };
+    let mut map = map_with(config);
+    assert!(matches!(map.next_work(), Next::None));
+    assert!(matches!(map.next_task(), Next::NotBefore(_)));
+}
+
+#[test]
+fn connection_is_closed_once_idle() {
+    let mut seq = Sequence::default();
+    let mut harness = create_association_with_idle_timeout(
+        AssociationConfig::quiet(),
+        Some(Duration::from_secs(10)),
+    );
+    let mut association = harness.association.clone();
+    assert_pending!(harness.poll());
+
+    // a task restarts the idle timeout
+    time::advance(Duration::from_secs(5));
+    let mut read = spawn(association.read(ReadRequest::all_objects(Variation::Group30Var0)));
+    assert_pending!(read.poll());
+    read_request(&mut harness.io, seq, Variation::Group30Var0);
+    empty_response(&mut harness.io, seq.increment());
+    harness.assert_io();
+    assert_ready!(read.poll()).unwrap();
+
+    time::advance(Duration::from_secs(9));
+    assert_pending!(harness.poll());
+    time::advance(Duration::from_secs(1));
+    assert_eq!(
+        assert_ready!(harness.poll()),
+        RunError::State(StateChange::IdleTimeout)
+    );
+}
@@ -0,0 +1,85 @@
+use crate::app::variations::Variation;
+use crate::app::Sequence;
+use crate::app::{Iin, Iin1, Iin2};
+use crate::master::association::AssociationConfig;
+use crate::master::request::{ReadRequest, VariationFallback};
+use crate::tokio::test::*;
+
+use super::harness::create_association;
+use super::harness::requests::*;
+
+fn object_unknown() -> Iin {
+    Iin::new(Iin1::new(0x00), Iin2::OBJECT_UNKNOWN)
+}
+
+#[test]
+fn read_is_retried_with_the_listed_fallback_and_remembered() {
+    let mut config = AssociationConfig::quiet();
+    config.variation_fallback = VariationFallback::Listed(vec![
+        Variation::Group30Var5,
+        Variation::Group30Var1,
+        Variation::Group30Var0,
+    ]);
+    let mut seq = Sequence::default();
+    let mut harness = create_association(config);
+
+    let mut association = harness.association.clone();
+    let mut read_task = spawn(association.read(ReadRequest::all_objects(Variation::Group30Var5)));
+    assert_pending!(read_task.poll());
+
+    read_request(&mut harness.io, seq, Variation::Group30Var5);
+    empty_response_custom_iin(&mut harness.io, seq.increment(), object_unknown());
+    read_request(&mut harness.io, seq, Variation::Group30Var1);
+    empty_response(&mut harness.io, seq.increment());
+    harness.assert_io();
+
+    assert_ready!(read_task.poll()).unwrap();
+
+    // the working variation is requested directly
+    let mut read_task = spawn(association.read(ReadRequest::all_objects(Variation::Group30Var5)));
+    assert_pending!(read_task.poll());
+
+    read_request(&mut harness.io, seq, Variation::Group30Var1);
+    empty_response(&mut harness.io, seq.increment());
+    harness.assert_io();
+
+    assert_ready!(read_task.poll()).unwrap();
+}
+
+#[test]
+fn read_is_retried_with_the_default_variation_until_none_remains() {
+    let mut config = AssociationConfig::quiet();
+    config.variation_fallback = VariationFallback::DefaultVariation;
+    let mut seq = Sequence::default();
+    let mut harness = create_association(config);
+
+    let mut association = harness.association.clone();
+    let mut read_task = spawn(association.read(ReadRequest::all_objects(Variation::Group20Var1)));
+    assert_pending!(read_task.poll());
+
+    read_request(&mut harness.io, seq, Variation::Group20Var1);
+    empty_response_custom_iin(&mut harness.io, seq.increment(), object_unknown());
+    read_request(&mut harness.io, seq, Variation::Group20Var0);
+    empty_response_custom_iin(&mut harness.io, seq.increment(), object_unknown());
+    harness.assert_io();
+
+    // variation 0 has no fallback, so the READ completes with the response of the outstation
+    assert_ready!(read_task.poll()).unwrap();
+}
+
+#[test]
+fn read_is_not_retried_by_default() {
+    let config = AssociationConfig::quiet();
+    let mut seq = Sequence::default();
+    let mut harness = create_association(config);
+
+    let mut association = harness.association.clone();
+    let mut read_task = spawn(association.read(ReadRequest::all_objects(Variation::Group30Var5)));
+    assert_pending!(read_task.poll());
+
+    read_request(&mut harness.io, seq, Variation::Group30Var5);
+    empty_response_custom_iin(&mut harness.io, seq.increment(), object_unknown());
+    harness.assert_io();
+
+    assert_ready!(read_task.poll()).unwrap();
+}
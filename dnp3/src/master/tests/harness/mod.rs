@@ -2,13 +2,19 @@ use std::future::Future;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::task::Poll;
+use std::time::Duration;
 
-use crate::decode::AppDecodeLevel;
+use crate::decode::{AppDecodeLevel, TracingConfig};
 use crate::link::header::{FrameInfo, FrameType};
-use crate::link::{EndpointAddress, LinkErrorMode};
-use crate::master::association::AssociationConfig;
-use crate::master::handle::{AssociationHandle, HeaderInfo, MasterChannel, ReadHandler};
-use crate::master::session::{MasterSession, RunError};
+use crate::link::statistics::LinkCounters;
+use crate::link::{
+    EndpointAddress, LinkConfig, LinkErrorMode, TransportConfig, UnknownDestinationPolicy,
+};
+use crate::master::association::{AssociationConfig, SchedulingConfig};
+use crate::master::handle::{
+    AssociationHandle, AssociationHandler, HeaderInfo, MasterChannel, ReadHandler,
+};
+use crate::master::session::{MasterSession, RunError, StateChange};
 use crate::master::{DefaultAssociationHandler, ReadType};
 use crate::tokio::test::*;
 use crate::transport::create_master_transport_layer;
@@ -18,6 +24,44 @@ pub(crate) mod requests;
 
 pub(crate) fn create_association(
     config: AssociationConfig,
+) -> TestHarness<impl Future<Output = RunError>> {
+    create_association_with_idle_timeout(config, None)
+}
+
+/// create an association whose session closes the connection after `idle_timeout` without tasks
+pub(crate) fn create_association_with_idle_timeout(
+    config: AssociationConfig,
+    idle_timeout: Option<Duration>,
+) -> TestHarness<impl Future<Output = RunError>> {
+    create_association_impl(
+        config,
+        SchedulingConfig::default(),
+        idle_timeout,
+        DefaultAssociationHandler::boxed(),
+    )
+}
+
+/// create an association that reports to `handler`
+pub(crate) fn create_association_with_handler(
+    config: AssociationConfig,
+    handler: Box<dyn AssociationHandler>,
+) -> TestHarness<impl Future<Output = RunError>> {
+    create_association_impl(config, SchedulingConfig::default(), None, handler)
+}
+
+/// create an association on a channel with the specified scheduling
+pub(crate) fn create_association_with_scheduling(
+    config: AssociationConfig,
+    scheduling: SchedulingConfig,
+) -> TestHarness<impl Future<Output = RunError>> {
+    create_association_impl(config, scheduling, None, DefaultAssociationHandler::boxed())
+}
+
+fn create_association_impl(
+    config: AssociationConfig,
+    scheduling: SchedulingConfig,
+    idle_timeout: Option<Duration>,
+    assoc_handler: Box<dyn AssociationHandler>,
 ) -> TestHarness<impl Future<Output = RunError>> {
     let (io, io_handle) = io::mock();
 
@@ -27,26 +71,47 @@ pub(crate) fn create_association(
 
     // Create the master session
     let (tx, rx) = crate::util::channel::request_channel();
+    let link_counters = Arc::new(LinkCounters::default());
     let mut runner = MasterSession::new(
         true,
         AppDecodeLevel::ObjectValues.into(),
         crate::app::Timeout::from_secs(1).unwrap(),
+        scheduling,
+        LinkConfig::default(),
+        link_counters.clone(),
+        TracingConfig::default(),
         MasterSession::MIN_TX_BUFFER_SIZE,
+        MasterSession::MIN_RX_BUFFER_SIZE,
         rx,
     );
-    let mut master = MasterChannel::new(tx);
+    if let Some(idle_timeout) = idle_timeout {
+        runner.set_dial_up(idle_timeout);
+    }
+    let mut master = MasterChannel::new(tx, link_counters.clone());
 
     let (mut reader, mut writer) = create_master_transport_layer(
         LinkErrorMode::Close,
         EndpointAddress::from(1).unwrap(),
         MasterSession::MIN_RX_BUFFER_SIZE,
+        LinkConfig::default(),
+        Duration::from_secs(1),
+        TransportConfig::default(),
+        UnknownDestinationPolicy::default(),
+        link_counters,
     );
 
     reader
         .get_inner()
         .set_rx_frame_info(FrameInfo::new(outstation_address, None, FrameType::Data));
 
-    let mut master_task = spawn(async move { runner.run(&mut io, &mut writer, &mut reader).await });
+    let mut master_task = spawn(async move {
+        let err = runner.run(&mut io, &mut writer, &mut reader).await;
+        // complete the shutdown requests as the channel tasks do once the session exits
+        if err == RunError::State(StateChange::Shutdown) {
+            runner.shutdown().await;
+        }
+        err
+    });
 
     // Create the association
     let handler = CountHandler::new();
@@ -56,7 +121,7 @@ pub(crate) fn create_association(
             outstation_address,
             config,
             Box::new(handler),
-            DefaultAssociationHandler::boxed(),
+            assoc_handler,
         ));
         assert_pending!(add_task.poll());
         assert_pending!(master_task.poll());
@@ -72,12 +137,13 @@ pub(crate) fn create_association(
     }
 }
 
-struct CountHandler {
-    num_requests: Arc<AtomicU64>,
+/// counts the headers of analog values
+pub(crate) struct CountHandler {
+    pub(crate) num_requests: Arc<AtomicU64>,
 }
 
 impl CountHandler {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             num_requests: Arc::new(AtomicU64::new(0)),
         }
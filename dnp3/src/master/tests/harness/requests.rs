@@ -64,6 +64,18 @@ pub(crate) fn integrity_poll_request(io: &mut io::Handle, seq: Sequence) {
     io.write(cursor.written());
 }
 
+pub(crate) fn read_request(io: &mut io::Handle, seq: Sequence, variation: Variation) {
+    // READ request with a single all objects header
+    let mut buffer = [0; 20];
+    let mut cursor = WriteCursor::new(&mut buffer);
+    let mut request =
+        start_request(ControlField::request(seq), FunctionCode::Read, &mut cursor).unwrap();
+
+    request.write_all_objects_header(variation).unwrap();
+
+    io.write(cursor.written());
+}
+
 pub(crate) fn enable_unsol_request(io: &mut io::Handle, seq: Sequence) {
     // ENABLE_UNSOLICITED request
     let mut buffer = [0; 20];
@@ -100,6 +112,54 @@ pub(crate) fn clear_restart_iin(io: &mut io::Handle, seq: Sequence) {
     io.write(cursor.written());
 }
 
+// g41v2 with a value of 513 at index 7
+const G41V2_INDEX_7: &[u8] = &[41, 2, 0x17, 0x01, 0x07, 0x01, 0x02, 0x00];
+
+pub(crate) fn direct_operate_request(io: &mut io::Handle, seq: Sequence) {
+    // DIRECT_OPERATE of the g41v2 at index 7
+    let mut buffer = [0; 20];
+    let mut cursor = WriteCursor::new(&mut buffer);
+    start_request(
+        ControlField::request(seq),
+        FunctionCode::DirectOperate,
+        &mut cursor,
+    )
+    .unwrap();
+    cursor.write_slice(G41V2_INDEX_7).unwrap();
+
+    io.write(cursor.written());
+}
+
+pub(crate) fn direct_operate_response(io: &mut io::Handle, seq: Sequence) {
+    let mut buffer = [0; 20];
+    let mut cursor = WriteCursor::new(&mut buffer);
+    start_response(
+        ControlField::single_response(seq),
+        ResponseFunction::Response,
+        Iin::default(),
+        &mut cursor,
+    )
+    .unwrap();
+    cursor.write_slice(G41V2_INDEX_7).unwrap();
+
+    io.read(cursor.written());
+}
+
+pub(crate) fn first_fragment_response(io: &mut io::Handle, seq: Sequence) {
+    // FIR without FIN, requesting confirmation
+    let mut buffer = [0; 4];
+    let mut cursor = WriteCursor::new(&mut buffer);
+    start_response(
+        ControlField::response(seq, true, false, true),
+        ResponseFunction::Response,
+        Iin::default(),
+        &mut cursor,
+    )
+    .unwrap();
+
+    io.read(cursor.written());
+}
+
 pub(crate) fn empty_response(io: &mut io::Handle, seq: Sequence) {
     empty_response_custom_iin(io, seq, Iin::default());
 }
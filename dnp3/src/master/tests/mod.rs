@@ -1,4 +1,12 @@
 mod harness;
 
 mod auto_tasks;
+mod catch_all;
+mod comm_lost;
+mod diagnostics;
+mod dial_up;
+mod fallback;
+mod queue;
+mod scheduling;
+mod shutdown;
 mod startup;
@@ -0,0 +1,196 @@
+use std::sync::{Arc, Mutex};
+
+use crate::app::variations::Variation;
+use crate::app::Sequence;
+use crate::master::association::{AssociationConfig, QueueOverflowPolicy};
+use crate::master::error::TaskError;
+use crate::master::handle::{AssociationHandler, TaskCompletion};
+use crate::master::request::ReadRequest;
+use crate::tokio::test::*;
+
+use super::harness::requests::*;
+use super::harness::{create_association, create_association_with_handler};
+
+fn config(policy: QueueOverflowPolicy) -> AssociationConfig {
+    let mut config = AssociationConfig::quiet();
+    config.max_queued_user_requests = 1;
+    config.queue_overflow_policy = policy;
+    config
+}
+
+fn read(variation: Variation) -> ReadRequest {
+    ReadRequest::all_objects(variation)
+}
+
+#[test]
+fn new_request_is_rejected_when_the_queue_is_full() {
+    let mut seq = Sequence::default();
+    let mut harness = create_association(config(QueueOverflowPolicy::RejectNew));
+    let (mut a, mut b, mut c) = (
+        harness.association.clone(),
+        harness.association.clone(),
+        harness.association.clone(),
+    );
+
+    // the first read is in progress, the second fills the queue
+    let mut first = spawn(a.read(read(Variation::Group30Var0)));
+    assert_pending!(first.poll());
+    read_request(&mut harness.io, seq, Variation::Group30Var0);
+    harness.assert_io();
+
+    let mut second = spawn(b.read(read(Variation::Group1Var0)));
+    assert_pending!(second.poll());
+    let mut third = spawn(c.read(read(Variation::Group20Var0)));
+    assert_pending!(third.poll());
+    assert_pending!(harness.poll());
+
+    assert_eq!(assert_ready!(third.poll()), Err(TaskError::TooManyRequests));
+
+    empty_response(&mut harness.io, seq.increment());
+    read_request(&mut harness.io, seq, Variation::Group1Var0);
+    empty_response(&mut harness.io, seq.increment());
+    harness.assert_io();
+
+    assert_ready!(first.poll()).unwrap();
+    assert_ready!(second.poll()).unwrap();
+}
+
+#[test]
+fn oldest_read_is_dropped_when_the_queue_is_full() {
+    let mut seq = Sequence::default();
+    let mut harness = create_association(config(QueueOverflowPolicy::DropOldestRead));
+    let (mut a, mut b, mut c) = (
+        harness.association.clone(),
+        harness.association.clone(),
+        harness.association.clone(),
+    );
+
+    let mut first = spawn(a.read(read(Variation::Group30Var0)));
+    assert_pending!(first.poll());
+    read_request(&mut harness.io, seq, Variation::Group30Var0);
+    harness.assert_io();
+
+    let mut second = spawn(b.read(read(Variation::Group1Var0)));
+    assert_pending!(second.poll());
+    let mut third = spawn(c.read(read(Variation::Group20Var0)));
+    assert_pending!(third.poll());
+    assert_pending!(harness.poll());
+
+    assert_eq!(assert_ready!(second.poll()), Err(TaskError::Dropped));
+
+    empty_response(&mut harness.io, seq.increment());
+    read_request(&mut harness.io, seq, Variation::Group20Var0);
+    empty_response(&mut harness.io, seq.increment());
+    harness.assert_io();
+
+    assert_ready!(first.poll()).unwrap();
+    assert_ready!(third.poll()).unwrap();
+}
+
+#[test]
+fn duplicate_read_is_coalesced_when_the_queue_is_full() {
+    let mut seq = Sequence::default();
+    let mut harness = create_association(config(QueueOverflowPolicy::CoalesceDuplicateReads));
+    let (mut a, mut b, mut c, mut d) = (
+        harness.association.clone(),
+        harness.association.clone(),
+        harness.association.clone(),
+        harness.association.clone(),
+    );
+
+    let mut first = spawn(a.read(read(Variation::Group30Var0)));
+    assert_pending!(first.poll());
+    read_request(&mut harness.io, seq, Variation::Group30Var0);
+    harness.assert_io();
+
+    let mut second = spawn(b.read(read(Variation::Group1Var0)));
+    assert_pending!(second.poll());
+    let mut duplicate = spawn(c.read(read(Variation::Group1Var0)));
+    assert_pending!(duplicate.poll());
+    let mut other = spawn(d.read(read(Variation::Group20Var0)));
+    assert_pending!(other.poll());
+    assert_pending!(harness.poll());
+
+    // only an identical read can be coalesced
+    assert_eq!(assert_ready!(other.poll()), Err(TaskError::TooManyRequests));
+    assert_pending!(duplicate.poll());
+
+    // a single request is sent for both reads
+    empty_response(&mut harness.io, seq.increment());
+    read_request(&mut harness.io, seq, Variation::Group1Var0);
+    empty_response(&mut harness.io, seq.increment());
+    harness.assert_io();
+
+    assert_ready!(first.poll()).unwrap();
+    assert_ready!(second.poll()).unwrap();
+    assert_ready!(duplicate.poll()).unwrap();
+}
+
+struct CompletionHandler {
+    completions: Arc<Mutex<Vec<TaskCompletion>>>,
+}
+
+impl AssociationHandler for CompletionHandler {
+    fn task_completed(&mut self, completion: TaskCompletion) {
+        self.completions.lock().unwrap().push(completion);
+    }
+}
+
+#[test]
+fn coalesced_reads_are_reported_with_their_own_correlation_id() {
+    let mut seq = Sequence::default();
+    let completions = Arc::new(Mutex::new(Vec::new()));
+    let mut harness = create_association_with_handler(
+        config(QueueOverflowPolicy::CoalesceDuplicateReads),
+        Box::new(CompletionHandler {
+            completions: completions.clone(),
+        }),
+    );
+    let (mut a, mut b, mut c, mut d) = (
+        harness.association.with_correlation_id(1),
+        harness.association.with_correlation_id(2),
+        harness.association.with_correlation_id(3),
+        harness.association.with_correlation_id(4),
+    );
+
+    let mut first = spawn(a.read(read(Variation::Group30Var0)));
+    assert_pending!(first.poll());
+    read_request(&mut harness.io, seq, Variation::Group30Var0);
+    harness.assert_io();
+
+    let mut second = spawn(b.read(read(Variation::Group1Var0)));
+    assert_pending!(second.poll());
+    let mut duplicate = spawn(c.read(read(Variation::Group1Var0)));
+    assert_pending!(duplicate.poll());
+    let mut other = spawn(d.read(read(Variation::Group20Var0)));
+    assert_pending!(other.poll());
+    assert_pending!(harness.poll());
+
+    // the rejected request is reported right away
+    assert_eq!(assert_ready!(other.poll()), Err(TaskError::TooManyRequests));
+    assert_eq!(
+        *completions.lock().unwrap(),
+        [TaskCompletion::new(4, Err(TaskError::TooManyRequests))].to_vec()
+    );
+
+    empty_response(&mut harness.io, seq.increment());
+    read_request(&mut harness.io, seq, Variation::Group1Var0);
+    empty_response(&mut harness.io, seq.increment());
+    harness.assert_io();
+
+    assert_ready!(first.poll()).unwrap();
+    assert_ready!(second.poll()).unwrap();
+    assert_ready!(duplicate.poll()).unwrap();
+
+    // the merged read completes with the queued read that was sent
+    assert_eq!(
+        *completions.lock().unwrap(),
+        [
+            TaskCompletion::new(4, Err(TaskError::TooManyRequests)),
+            TaskCompletion::new(1, Ok(())),
+            TaskCompletion::new(2, Ok(())),
+            TaskCompletion::new(3, Ok(())),
+        ]
+        .to_vec()
+    );
+}
@@ -0,0 +1,252 @@
+use std::time::Duration;
+
+use crate::app::variations::{Group41Var2, Variation};
+use crate::app::{Clock, FunctionCode, RetryStrategy, Sequence, Timeout};
+use crate::link::EndpointAddress;
+use crate::master::association::{
+    Association, AssociationConfig, AssociationMap, Next, ReadPreemption, SchedulingConfig,
+};
+use crate::master::error::{RequestContext, TaskError};
+use crate::master::handle::Promise;
+use crate::master::messages::AssociationMsgType;
+use crate::master::request::{CommandBuilder, CommandMode, CommandSupport, ReadRequest};
+use crate::master::tasks::{Task, UserTask};
+use crate::master::{DefaultAssociationHandler, NullReadHandler};
+use crate::tokio::test::*;
+use crate::tokio::time;
+
+use super::harness::requests::*;
+use super::harness::{create_association, create_association_with_scheduling};
+
+fn address(value: u16) -> EndpointAddress {
+    EndpointAddress::from(value).unwrap()
+}
+
+fn register(map: &mut AssociationMap, value: u16, priority: u8) {
+    let config = AssociationConfig {
+        priority,
+        ..AssociationConfig::default()
+    };
+    map.register(Association::new(
+        address(value),
+        config,
+        NullReadHandler::boxed(),
+        DefaultAssociationHandler::boxed(),
+        Clock::default(),
+    ))
+    .unwrap();
+}
+
+fn timeout(value: u16) -> TaskError {
+    TaskError::ResponseTimeout(RequestContext::request(
+        address(value),
+        FunctionCode::Read,
+        Sequence::default(),
+    ))
+}
+
+fn next_address(map: &mut AssociationMap) -> Option<EndpointAddress> {
+    match map.next_task() {
+        Next::Now(task) => Some(task.address),
+        _ => None,
+    }
+}
+
+#[test]
+fn higher_priority_associations_are_scheduled_first() {
+    let mut map = AssociationMap::new(SchedulingConfig::default(), Clock::default());
+    register(&mut map, 1, 0);
+    register(&mut map, 2, 5);
+    register(&mut map, 3, 5);
+
+    assert_eq!(next_address(&mut map), Some(address(2)));
+    assert_eq!(next_address(&mut map), Some(address(3)));
+    assert_eq!(next_address(&mut map), Some(address(2)));
+    assert_eq!(next_address(&mut map), Some(address(3)));
+
+    map.remove(address(2));
+    map.remove(address(3));
+    assert_eq!(next_address(&mut map), Some(address(1)));
+}
+
+#[test]
+fn associations_that_time_out_are_held_off() {
+    let mut map = AssociationMap::new(
+        SchedulingConfig {
+            timeout_holdoff: Some(RetryStrategy::new(
+                Duration::from_secs(10),
+                Duration::from_secs(60),
+            )),
+            ..SchedulingConfig::default()
+        },
+        Clock::default(),
+    );
+    register(&mut map, 1, 0);
+    register(&mut map, 2, 0);
+
+    map.on_task_result(address(1), Err(timeout(1)));
+    assert_eq!(next_address(&mut map), Some(address(2)));
+    assert_eq!(next_address(&mut map), Some(address(2)));
+
+    time::advance(Duration::from_secs(10));
+    assert_eq!(next_address(&mut map), Some(address(1)));
+
+    // a second timeout doubles the hold-off
+    map.on_task_result(address(1), Err(timeout(1)));
+    time::advance(Duration::from_secs(10));
+    assert_eq!(next_address(&mut map), Some(address(2)));
+    time::advance(Duration::from_secs(10));
+    assert_eq!(next_address(&mut map), Some(address(1)));
+
+    // a response clears the hold-off
+    map.on_task_result(address(1), Err(timeout(1)));
+    map.on_task_result(address(1), Ok(()));
+    assert_eq!(next_address(&mut map), Some(address(2)));
+    assert_eq!(next_address(&mut map), Some(address(1)));
+}
+
+#[test]
+fn association_response_timeout_overrides_channel_timeout() {
+    let config = AssociationConfig {
+        auto_tasks_retry_strategy: RetryStrategy::new(
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+        ),
+        response_timeout: Some(Timeout::from_secs(3).unwrap()),
+        ..AssociationConfig::default()
+    };
+    let mut seq = Sequence::default();
+    let mut harness = create_association(config);
+
+    disable_unsol_request(&mut harness.io, seq.increment());
+    harness.assert_io();
+
+    // the channel timeout of 1 second does not apply
+    time::advance(Duration::from_secs(2));
+    assert_pending!(harness.poll());
+    time::advance(Duration::from_millis(999));
+    assert_pending!(harness.poll());
+    assert!(!harness.io.pending_write());
+
+    // the association times out and the task is retried after the retry delay
+    time::advance(Duration::from_millis(1));
+    assert_pending!(harness.poll());
+    time::advance(Duration::from_secs(1));
+    disable_unsol_request(&mut harness.io, seq.increment());
+    harness.assert_io();
+}
+
+#[test]
+fn commands_run_before_queued_reads() {
+    let mut seq = Sequence::default();
+    let mut harness = create_association(AssociationConfig::quiet());
+
+    let mut association = harness.association.clone();
+    let mut first_read = spawn(association.read(ReadRequest::all_objects(Variation::Group30Var0)));
+    assert_pending!(first_read.poll());
+    read_request(&mut harness.io, seq, Variation::Group30Var0);
+    harness.assert_io();
+
+    // queued while the first READ waits for its response
+    let mut association = harness.association.clone();
+    let mut second_read = spawn(association.read(ReadRequest::all_objects(Variation::Group1Var0)));
+    assert_pending!(second_read.poll());
+    let mut association = harness.association.clone();
+    let mut operate = spawn(association.operate(
+        CommandMode::DirectOperate,
+        CommandBuilder::single_header_u8(Group41Var2::new(513), 7),
+    ));
+    assert_pending!(operate.poll());
+    assert_pending!(harness.poll());
+
+    empty_response(&mut harness.io, seq.increment());
+    direct_operate_request(&mut harness.io, seq);
+    harness.assert_io();
+    assert_ready!(first_read.poll()).unwrap();
+
+    direct_operate_response(&mut harness.io, seq.increment());
+    read_request(&mut harness.io, seq, Variation::Group1Var0);
+    harness.assert_io();
+    assert_ready!(operate.poll()).unwrap();
+
+    empty_response(&mut harness.io, seq.increment());
+    harness.assert_io();
+    assert_ready!(second_read.poll()).unwrap();
+}
+
+fn interrupt_read_with_command(preemption: ReadPreemption) -> Result<(), TaskError> {
+    let mut seq = Sequence::default();
+    let mut harness = create_association_with_scheduling(
+        AssociationConfig::quiet(),
+        SchedulingConfig {
+            read_preemption: preemption,
+            ..SchedulingConfig::default()
+        },
+    );
+
+    let mut association = harness.association.clone();
+    let mut read = spawn(association.read(ReadRequest::all_objects(Variation::Group30Var0)));
+    assert_pending!(read.poll());
+    read_request(&mut harness.io, seq, Variation::Group30Var0);
+    harness.assert_io();
+
+    let mut association = harness.association.clone();
+    let mut operate = spawn(association.operate(
+        CommandMode::DirectOperate,
+        CommandBuilder::single_header_u8(Group41Var2::new(513), 7),
+    ));
+    assert_pending!(operate.poll());
+    assert_pending!(harness.poll());
+
+    // the fragment isn't confirmed, so that the outstation abandons the rest of the response
+    first_fragment_response(&mut harness.io, seq.increment());
+    direct_operate_request(&mut harness.io, seq);
+    harness.assert_io();
+
+    direct_operate_response(&mut harness.io, seq.increment());
+    if preemption == ReadPreemption::Postpone {
+        read_request(&mut harness.io, seq, Variation::Group30Var0);
+        empty_response(&mut harness.io, seq.increment());
+    }
+    harness.assert_io();
+    assert_ready!(operate.poll()).unwrap();
+
+    assert_ready!(read.poll())
+}
+
+#[test]
+fn command_cancels_read_in_progress() {
+    assert_eq!(
+        interrupt_read_with_command(ReadPreemption::Cancel),
+        Err(TaskError::Preempted)
+    );
+}
+
+#[test]
+fn command_postpones_read_in_progress() {
+    assert_eq!(
+        interrupt_read_with_command(ReadPreemption::Postpone),
+        Ok(())
+    );
+}
+
+#[test]
+fn user_tasks_keep_their_correlation_id() {
+    let mut map = AssociationMap::new(SchedulingConfig::default(), Clock::default());
+    register(&mut map, 1, 0);
+    map.get_mut(address(1)).unwrap().process_message(
+        AssociationMsgType::QueueTask(UserTask::new(Task::LinkStatus(Promise::None), Some(42))),
+        None,
+    );
+
+    match map.next_task() {
+        Next::Now(task) => assert_eq!(task.correlation_id, Some(42)),
+        _ => unreachable!(),
+    }
+
+    // the automatic tasks that follow get their ID from the library
+    match map.next_task() {
+        Next::Now(task) => assert_eq!(task.correlation_id, None),
+        _ => unreachable!(),
+    }
+}
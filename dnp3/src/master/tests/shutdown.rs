@@ -0,0 +1,130 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::app::variations::Variation;
+use crate::app::Sequence;
+use crate::decode::{AppDecodeLevel, TracingConfig};
+use crate::link::statistics::LinkCounters;
+use crate::link::LinkConfig;
+use crate::master::association::{AssociationConfig, SchedulingConfig};
+use crate::master::error::TaskError;
+use crate::master::handle::MasterChannel;
+use crate::master::request::ReadRequest;
+use crate::master::session::{MasterSession, RunError, StateChange};
+use crate::tokio::test::*;
+
+use super::harness::create_association;
+use super::harness::requests::*;
+
+fn read(variation: Variation) -> ReadRequest {
+    ReadRequest::all_objects(variation)
+}
+
+#[test]
+fn queued_requests_complete_before_the_shutdown() {
+    let mut seq = Sequence::default();
+    let mut harness = create_association(AssociationConfig::quiet());
+    let mut master = harness.master.clone();
+    let (mut a, mut b) = (harness.association.clone(), harness.association.clone());
+
+    let mut first = spawn(a.read(read(Variation::Group30Var0)));
+    assert_pending!(first.poll());
+    read_request(&mut harness.io, seq, Variation::Group30Var0);
+    harness.assert_io();
+
+    let mut second = spawn(b.read(read(Variation::Group1Var0)));
+    assert_pending!(second.poll());
+    assert_pending!(harness.poll());
+
+    let mut shutdown = spawn(master.shutdown());
+    assert_pending!(shutdown.poll());
+    assert_pending!(harness.poll());
+
+    // both requests are still run
+    empty_response(&mut harness.io, seq.increment());
+    read_request(&mut harness.io, seq, Variation::Group1Var0);
+    empty_response(&mut harness.io, seq.increment());
+
+    assert_eq!(
+        assert_ready!(harness.poll()),
+        RunError::State(StateChange::Shutdown)
+    );
+    assert!(harness.io.all_written());
+    assert!(harness.io.all_read());
+
+    assert_ready!(first.poll()).unwrap();
+    assert_ready!(second.poll()).unwrap();
+    assert_ready!(shutdown.poll());
+}
+
+#[test]
+fn requests_after_the_shutdown_fail() {
+    let mut seq = Sequence::default();
+    let mut harness = create_association(AssociationConfig::quiet());
+    let mut master = harness.master.clone();
+    let (mut a, mut b, mut c) = (
+        harness.association.clone(),
+        harness.association.clone(),
+        harness.association.clone(),
+    );
+
+    let mut first = spawn(a.read(read(Variation::Group30Var0)));
+    assert_pending!(first.poll());
+    read_request(&mut harness.io, seq, Variation::Group30Var0);
+    harness.assert_io();
+
+    let mut shutdown = spawn(master.shutdown());
+    assert_pending!(shutdown.poll());
+    assert_pending!(harness.poll());
+
+    // rejected while the request in progress completes
+    let mut draining = spawn(b.read(read(Variation::Group1Var0)));
+    assert_pending!(draining.poll());
+    assert_pending!(harness.poll());
+    assert_eq!(assert_ready!(draining.poll()), Err(TaskError::Shutdown));
+
+    empty_response(&mut harness.io, seq.increment());
+    assert_eq!(
+        assert_ready!(harness.poll()),
+        RunError::State(StateChange::Shutdown)
+    );
+    assert_ready!(first.poll()).unwrap();
+    assert_ready!(shutdown.poll());
+
+    // rejected once the session has exited
+    let mut after = spawn(c.read(read(Variation::Group20Var0)));
+    assert_eq!(assert_ready!(after.poll()), Err(TaskError::Shutdown));
+}
+
+#[test]
+fn pending_retry_fails_instead_of_waiting_for_the_delay() {
+    let (tx, rx) = crate::util::channel::request_channel();
+    let counters = Arc::new(LinkCounters::default());
+    let mut session = MasterSession::new(
+        true,
+        AppDecodeLevel::ObjectValues.into(),
+        crate::app::Timeout::from_secs(1).unwrap(),
+        SchedulingConfig::default(),
+        LinkConfig::default(),
+        counters.clone(),
+        TracingConfig::default(),
+        MasterSession::MIN_TX_BUFFER_SIZE,
+        MasterSession::MIN_RX_BUFFER_SIZE,
+        rx,
+    );
+    let mut master = MasterChannel::new(tx, counters);
+
+    let mut task = spawn(async move {
+        let result = session.wait_for_retry(Duration::from_secs(60)).await;
+        session.shutdown().await;
+        result
+    });
+    assert_pending!(task.poll());
+
+    let mut shutdown = spawn(master.shutdown());
+    assert_pending!(shutdown.poll());
+
+    // the retry fails without advancing the time to the end of the delay
+    assert_eq!(assert_ready!(task.poll()), Err(StateChange::Shutdown));
+    assert_ready!(shutdown.poll());
+}
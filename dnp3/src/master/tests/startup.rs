@@ -8,7 +8,7 @@ use crate::app::RetryStrategy;
 use crate::app::Sequence;
 use crate::app::{ControlField, Iin, Iin1, Iin2};
 use crate::master::association::AssociationConfig;
-use crate::master::request::{Classes, EventClasses};
+use crate::master::request::{ClassZeroRequest, Classes, EventClasses, ReadHeader};
 use crate::tokio::test::*;
 use crate::tokio::time;
 use crate::util::cursor::WriteCursor;
@@ -89,6 +89,42 @@ fn master_startup_procedure_skips_integrity_poll_if_none() {
     harness.assert_io();
 }
 
+#[test]
+fn integrity_poll_reads_configured_headers_instead_of_class_zero() {
+    let mut config = AssociationConfig::default();
+    config.disable_unsol_classes = EventClasses::none();
+    config.enable_unsol_classes = EventClasses::none();
+    config.class_zero_request = ClassZeroRequest::headers(&[
+        ReadHeader::all_objects(Variation::Group1Var0),
+        ReadHeader::two_byte_range(Variation::Group30Var0, 0, 9),
+    ]);
+    let mut seq = Sequence::default();
+    let mut harness = create_association(config);
+
+    let mut buffer = [0; 40];
+    let mut cursor = WriteCursor::new(&mut buffer);
+    let mut request =
+        start_request(ControlField::request(seq), FunctionCode::Read, &mut cursor).unwrap();
+    request
+        .write_all_objects_header(Variation::Group60Var2)
+        .unwrap();
+    request
+        .write_all_objects_header(Variation::Group60Var3)
+        .unwrap();
+    request
+        .write_all_objects_header(Variation::Group60Var4)
+        .unwrap();
+    request
+        .write_all_objects_header(Variation::Group1Var0)
+        .unwrap();
+    request
+        .write_range_only(Variation::Group30Var0, 0u16, 9u16)
+        .unwrap();
+    harness.io.write(cursor.written());
+    empty_response(&mut harness.io, seq.increment());
+    harness.assert_io();
+}
+
 #[test]
 fn clear_restart_iin_is_higher_priority() {
     let config = AssociationConfig::default();
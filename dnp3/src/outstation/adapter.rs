@@ -1,6 +1,10 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
 use tracing::Instrument;
 
-use crate::app::{Listener, Shutdown};
+use crate::app::{DisconnectReason, Listener, Shutdown};
 use crate::outstation::session::RunError;
 use crate::outstation::task::OutstationTask;
 use crate::outstation::ConnectionState;
@@ -12,12 +16,13 @@ use crate::util::phys::PhysLayer;
 #[derive(Debug)]
 pub(crate) struct NewSession {
     pub(crate) id: u64,
+    pub(crate) addr: SocketAddr,
     pub(crate) phys: PhysLayer,
 }
 
 impl NewSession {
-    pub(crate) fn new(id: u64, phys: PhysLayer) -> Self {
-        Self { id, phys }
+    pub(crate) fn new(id: u64, addr: SocketAddr, phys: PhysLayer) -> Self {
+        Self { id, addr, phys }
     }
 }
 
@@ -27,12 +32,15 @@ pub(crate) struct OutstationTaskAdapter {
     receiver: Receiver<NewSession>,
     task: OutstationTask,
     listener: Box<dyn Listener<ConnectionState>>,
+    // true while a session is running, read by the server to apply its connection limits
+    connected: Arc<AtomicBool>,
 }
 
 impl OutstationTaskAdapter {
     pub(crate) fn create(
         task: OutstationTask,
         listener: Box<dyn Listener<ConnectionState>>,
+        connected: Arc<AtomicBool>,
     ) -> (Self, Sender<NewSession>) {
         let (tx, rx) = request_channel();
         (
@@ -40,6 +48,7 @@ impl OutstationTaskAdapter {
                 receiver: rx,
                 task,
                 listener,
+                connected,
             },
             tx,
         )
@@ -80,12 +89,19 @@ impl OutstationTaskAdapter {
                 Some(mut s) => {
                     let id = s.id;
 
-                    self.listener.update(ConnectionState::Connected);
+                    self.connected.store(true, Ordering::Relaxed);
+                    self.listener.update(ConnectionState::Connected(s.addr));
                     let result = self
                         .run_one_session(&mut s.phys)
                         .instrument(tracing::info_span!("Session", "id" = id))
                         .await;
-                    self.listener.update(ConnectionState::Disconnected);
+                    let reason = match result {
+                        Ok(_) => DisconnectReason::Replaced,
+                        Err(RunError::Link(err)) => err.into(),
+                        Err(RunError::IdleTimeout) => DisconnectReason::IdleTimeout,
+                        Err(RunError::Shutdown) => DisconnectReason::Shutdown,
+                    };
+                    self.listener.update(ConnectionState::Disconnected(reason));
 
                     // reset outstation state in between sessions
                     self.task.reset();
@@ -103,11 +119,110 @@ impl OutstationTaskAdapter {
                         Err(RunError::Link(err)) => {
                             // go to next iteration to get a new session
                             tracing::warn!("Session error: {}", err);
+                            self.connected.store(false, Ordering::Relaxed);
+                        }
+                        Err(RunError::IdleTimeout) => {
+                            self.connected.store(false, Ordering::Relaxed);
+                        }
+                        Err(RunError::Shutdown) => {
+                            self.connected.store(false, Ordering::Relaxed);
+                            return Err(Shutdown);
                         }
-                        Err(RunError::Shutdown) => return Err(Shutdown),
                     }
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::app::listener_channel;
+    use crate::link::{EndpointAddress, LinkErrorMode};
+    use crate::outstation::database::EventBufferConfig;
+    use crate::outstation::{
+        DefaultControlHandler, DefaultOutstationApplication, DefaultOutstationInformation, Feature,
+        OutstationConfig,
+    };
+    use crate::tokio::test::*;
+    use crate::tokio::time;
+
+    const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn session(id: u64, io: Box<dyn crate::custom::PhysicalLayer>) -> NewSession {
+        NewSession::new(
+            id,
+            SocketAddr::from(([127, 0, 0, 1], 20000 + id as u16)),
+            PhysLayer::Custom(io),
+        )
+    }
+
+    #[test]
+    fn reports_the_reason_each_session_ended() {
+        let mut config = OutstationConfig::new(
+            EndpointAddress::from(10).unwrap(),
+            EndpointAddress::from(1).unwrap(),
+        );
+        config.features.unsolicited = Feature::Disabled;
+        let (mut task, handle) = OutstationTask::create(
+            LinkErrorMode::Close,
+            config,
+            EventBufferConfig::all_types(5),
+            DefaultOutstationApplication::create(),
+            DefaultOutstationInformation::create(),
+            DefaultControlHandler::create(),
+        );
+        task.set_idle_timeout(Some(IDLE_TIMEOUT));
+
+        let (listener, mut states) = listener_channel(16);
+        let connected = Arc::new(AtomicBool::new(false));
+        let (mut adapter, mut sessions) =
+            OutstationTaskAdapter::create(task, listener, connected.clone());
+        let mut run = spawn(async move { adapter.run().await });
+        assert_pending!(run.poll());
+
+        // the remote ends are kept open for the sessions to continue
+        let (io, _first_remote) = crate::tokio::io::duplex(1024);
+        let first = session(1, Box::new(io));
+        let first_addr = first.addr;
+        assert_ready!(spawn(sessions.send(first)).poll()).unwrap();
+        assert_pending!(run.poll());
+        assert_eq!(
+            states.try_next(),
+            Some(ConnectionState::Connected(first_addr))
+        );
+        assert!(connected.load(Ordering::Relaxed));
+
+        // a newer connection replaces the session
+        let (io, _second_remote) = crate::tokio::io::duplex(1024);
+        let second = session(2, Box::new(io));
+        let second_addr = second.addr;
+        assert_ready!(spawn(sessions.send(second)).poll()).unwrap();
+        assert_pending!(run.poll());
+        assert_eq!(
+            states.try_next(),
+            Some(ConnectionState::Disconnected(DisconnectReason::Replaced))
+        );
+        assert_eq!(
+            states.try_next(),
+            Some(ConnectionState::Connected(second_addr))
+        );
+
+        // nothing is received on the new session before the idle timeout
+        time::advance(IDLE_TIMEOUT);
+        assert_pending!(run.poll());
+        assert_eq!(
+            states.try_next(),
+            Some(ConnectionState::Disconnected(DisconnectReason::IdleTimeout))
+        );
+        assert_eq!(states.try_next(), None);
+        assert!(!connected.load(Ordering::Relaxed));
+
+        drop(handle);
+        drop(sessions);
+        assert_ready!(run.poll()).unwrap_err();
+    }
+}
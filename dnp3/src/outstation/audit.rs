@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use crate::app::parse::parser::HeaderCollection;
+use crate::app::Variation;
+use crate::app::{FunctionCode, ObjectParseError, QualifierCode, RequestHeader, ResponseHeader};
+use crate::link::EndpointAddress;
+
+/// Variation and qualifier of a single object header
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ObjectHeaderSummary {
+    /// variation of the object header
+    pub variation: Variation,
+    /// qualifier code of the object header
+    pub qualifier: QualifierCode,
+}
+
+/// Summary of the object headers contained in a request or response
+///
+/// The headers are lazily parsed only if the summary is inspected
+#[derive(Copy, Clone)]
+pub struct ObjectSummary<'a> {
+    function: FunctionCode,
+    data: &'a [u8],
+}
+
+impl<'a> ObjectSummary<'a> {
+    pub(crate) fn new(function: FunctionCode, data: &'a [u8]) -> Self {
+        Self { function, data }
+    }
+
+    /// iterate over the object headers
+    ///
+    /// nothing is produced if the object headers are malformed
+    pub fn iter(&self) -> impl Iterator<Item = ObjectHeaderSummary> + 'a {
+        HeaderCollection::parse(self.function, self.data)
+            .ok()
+            .into_iter()
+            .flat_map(|headers| headers.iter())
+            .map(|header| ObjectHeaderSummary {
+                variation: header.variation,
+                qualifier: header.details.qualifier(),
+            })
+    }
+}
+
+impl std::fmt::Display for ObjectSummary<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut first = true;
+        for header in self.iter() {
+            if !first {
+                f.write_str(", ")?;
+            }
+            write!(
+                f,
+                "{} (0x{:02X})",
+                header.variation,
+                header.qualifier.as_u8()
+            )?;
+            first = false;
+        }
+        if first {
+            f.write_str("none")?;
+        }
+        Ok(())
+    }
+}
+
+/// How the outstation disposed of a received request
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RequestOutcome {
+    /// request was processed as a new request
+    Processed,
+    /// request was identified as a repeat of the last request
+    Repeat,
+    /// request was received on a broadcast address
+    Broadcast,
+    /// object headers in the request could not be parsed
+    Malformed(ObjectParseError),
+    /// function code of the request is disabled in the configuration
+    FunctionDisabled,
+}
+
+/// Result of transmitting a response
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ResponseOutcome {
+    /// response was written to the communication channel
+    Transmitted,
+    /// an error occurred while writing the response to the communication channel
+    Failed,
+}
+
+/// Audit record for a received request
+#[derive(Copy, Clone)]
+pub struct RequestRecord<'a> {
+    /// link-layer address of the master that sent the request
+    pub source: EndpointAddress,
+    /// application-layer header of the request
+    pub header: RequestHeader,
+    /// object headers contained in the request
+    pub objects: ObjectSummary<'a>,
+    /// how the outstation disposed of the request
+    pub outcome: RequestOutcome,
+}
+
+/// Audit record for a transmitted response
+#[derive(Copy, Clone)]
+pub struct ResponseRecord<'a> {
+    /// link-layer address of the master to which the response was sent
+    pub destination: EndpointAddress,
+    /// application-layer header of the response
+    pub header: ResponseHeader,
+    /// object headers contained in the response
+    pub objects: ObjectSummary<'a>,
+    /// result of writing the response
+    pub outcome: ResponseOutcome,
+}
+
+/// Audit trail of the requests received and the responses transmitted by an outstation
+///
+/// Unlike logging, these callbacks are always invoked regardless of the configured
+/// [DecodeLevel](crate::decode::DecodeLevel) or tracing subscriber, making them suitable
+/// for producing compliance records. Implementations should return quickly as they are
+/// invoked from the outstation task. A logger may be shared by the configurations of several
+/// outstations, which then invoke it from their own tasks.
+pub trait AuditLogger: Sync + Send + 'static {
+    /// called for every request accepted by the transport layer
+    fn request_received(&self, _record: RequestRecord) {}
+
+    /// called for every response (solicited or unsolicited) written to the communication channel
+    fn response_transmitted(&self, _record: ResponseRecord) {}
+}
+
+/// Struct with a default implementation of [AuditLogger](crate::outstation::AuditLogger) that ignores all records
+#[derive(Copy, Clone)]
+pub struct DefaultAuditLogger;
+
+impl AuditLogger for DefaultAuditLogger {}
+
+impl DefaultAuditLogger {
+    /// create a shared implementation of [AuditLogger](crate::outstation::AuditLogger)
+    pub fn create() -> Arc<dyn AuditLogger> {
+        Arc::new(DefaultAuditLogger)
+    }
+}
@@ -1,10 +1,20 @@
-use crate::decode::DecodeLevel;
-use crate::link::EndpointAddress;
-use crate::outstation::database::ClassZeroConfig;
+use std::num::NonZeroU16;
+use std::sync::Arc;
+
+use crate::app::{BufferPool, Clock, ConfigError, FunctionCode, Spawner};
+use crate::decode::{DecodeLevel, TracingConfig};
+use crate::link::{
+    BroadcastConfirmMode, EndpointAddress, LinkConfig, SegmentSize, TransportConfig,
+    UnknownDestinationPolicy,
+};
+use crate::outstation::database::{ClassZeroConfig, StorageConfig};
+use crate::outstation::AuditLogger;
 use crate::util::buffer::Buffer;
 
 /// Validated buffer size for use in the outstation
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "usize", into = "usize"))]
 pub struct BufferSize {
     size: usize,
 }
@@ -14,16 +24,24 @@ pub struct BufferSize {
 pub enum BufferSizeError {
     /// provided size
     TooSmall(usize),
+    /// provided size
+    TooLarge(usize),
 }
 
 impl BufferSize {
     /// minimum allowed outstation buffer size corresponding to the payload of a link frame
     pub const MIN: usize = 249;
     /// default outstation buffer size
+    ///
+    /// Many masters can't receive fragments larger than this size. Larger buffers should only
+    /// be configured when the master is known to support them, e.g. from its documentation or
+    /// the maximum receive fragment size it reports.
     pub const DEFAULT: usize = 2048;
+    /// maximum allowed outstation buffer size
+    pub const MAX: usize = 65535;
 
-    pub(crate) fn create_buffer(&self) -> Buffer {
-        Buffer::new(self.size)
+    pub(crate) fn create_buffer(&self, pool: Option<Arc<BufferPool>>) -> Buffer {
+        Buffer::pooled(self.size, pool)
     }
 
     /// get the underlying value
@@ -41,8 +59,16 @@ impl BufferSize {
         if size < Self::MIN {
             return Err(BufferSizeError::TooSmall(size));
         }
+        if size > Self::MAX {
+            return Err(BufferSizeError::TooLarge(size));
+        }
         Ok(Self { size })
     }
+
+    /// true if fragments of this size may be rejected by masters that only support the default
+    pub(crate) fn exceeds_default(&self) -> bool {
+        self.size > Self::DEFAULT
+    }
 }
 
 impl Default for BufferSize {
@@ -55,6 +81,7 @@ impl Default for BufferSize {
 
 /// describes whether an optional feature is enabled or disabled
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Feature {
     /// feature is enabled
     Enabled,
@@ -64,6 +91,7 @@ pub enum Feature {
 
 /// Optional features that can be enabled or disabled
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Features {
     /// if enabled, the outstation responds to the self address (default == Disabled)
     pub self_address: Feature,
@@ -83,8 +111,141 @@ impl Default for Features {
     }
 }
 
+/// Controls which requests the outstation checks for duplicates
+///
+/// A request with the same sequence number, function, and object headers as the previous one is
+/// a retry by the master, to which the outstation replies with its last response instead of
+/// processing it again. Detecting retries requires hashing the object headers of each request.
+/// Targets processing many requests over links that don't produce duplicates, e.g. TCP, can
+/// reduce this work or disable it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DuplicateDetection {
+    /// every request is checked for duplicates
+    All,
+    /// only requests other than READ are checked, so repeated READ requests are answered with
+    /// fresh data
+    NonRead,
+    /// no request is treated as a duplicate
+    Disabled,
+}
+
+impl DuplicateDetection {
+    pub(crate) fn applies_to(&self, function: FunctionCode) -> bool {
+        match self {
+            Self::All => true,
+            Self::NonRead => function != FunctionCode::Read,
+            Self::Disabled => false,
+        }
+    }
+}
+
+impl Default for DuplicateDetection {
+    fn default() -> Self {
+        Self::All
+    }
+}
+
+/// Controls which broadcast addresses the outstation accepts when broadcast support is enabled
+///
+/// Requests sent to an address that is not accepted are ignored and reported to
+/// [`OutstationInformation::broadcast_received`](crate::outstation::OutstationInformation::broadcast_received)
+/// as [`BroadcastAction::IgnoredByConfiguration`](crate::outstation::BroadcastAction::IgnoredByConfiguration)
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BroadcastAddresses {
+    /// If true, requests sent to 0xFFFF (optional confirmation) are accepted
+    pub optional_confirm: bool,
+    /// If true, requests sent to 0xFFFE (mandatory confirmation) are accepted
+    pub mandatory_confirm: bool,
+    /// If true, requests sent to 0xFFFD (no confirmation) are accepted
+    pub no_confirm: bool,
+}
+
+impl BroadcastAddresses {
+    /// construct a `BroadcastAddresses` from its fields
+    pub fn new(optional_confirm: bool, mandatory_confirm: bool, no_confirm: bool) -> Self {
+        Self {
+            optional_confirm,
+            mandatory_confirm,
+            no_confirm,
+        }
+    }
+
+    pub(crate) fn accepts(&self, mode: BroadcastConfirmMode) -> bool {
+        match mode {
+            BroadcastConfirmMode::Optional => self.optional_confirm,
+            BroadcastConfirmMode::Mandatory => self.mandatory_confirm,
+            BroadcastConfirmMode::NotRequired => self.no_confirm,
+        }
+    }
+}
+
+impl Default for BroadcastAddresses {
+    fn default() -> Self {
+        Self::new(true, true, true)
+    }
+}
+
+/// Request function codes that the outstation processes
+///
+/// Requests with a disabled function code are answered with the NO_FUNC_CODE_SUPPORT IIN bit,
+/// or ignored if the function doesn't have a response, and are reported to the
+/// [AuditLogger](crate::outstation::AuditLogger) as
+/// [RequestOutcome::FunctionDisabled](crate::outstation::RequestOutcome::FunctionDisabled).
+/// Security hardening guidelines commonly require disabling the restart functions, for example.
+///
+/// CONFIRM and READ are always processed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionCodes {
+    /// bit N is set if the function code with value N is disabled
+    disabled: u64,
+}
+
+impl FunctionCodes {
+    /// all of the function codes are enabled
+    pub fn all() -> Self {
+        Self { disabled: 0 }
+    }
+
+    /// disable the function code
+    pub fn disable(self, function: FunctionCode) -> Self {
+        Self {
+            disabled: self.disabled | Self::bit(function),
+        }
+    }
+
+    /// enable the function code
+    pub fn enable(self, function: FunctionCode) -> Self {
+        Self {
+            disabled: self.disabled & !Self::bit(function),
+        }
+    }
+
+    /// true if the function code is processed
+    pub fn is_enabled(&self, function: FunctionCode) -> bool {
+        self.disabled & Self::bit(function) == 0
+    }
+
+    fn bit(function: FunctionCode) -> u64 {
+        match function {
+            FunctionCode::Confirm | FunctionCode::Read => 0,
+            // request function codes are all less than 64
+            _ => 1u64.checked_shl(function.as_u8() as u32).unwrap_or(0),
+        }
+    }
+}
+
+impl Default for FunctionCodes {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
 /// Outstation configuration parameters
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OutstationConfig {
     /// address of the outstation
     pub outstation_address: EndpointAddress,
@@ -102,8 +263,18 @@ pub struct OutstationConfig {
     pub confirm_timeout: std::time::Duration,
     /// timeout after which a matching OPERATE will fail with SELECT_TIMEOUT
     pub select_timeout: std::time::Duration,
+    /// maximum number of SELECTs of different object headers held at the same time
+    ///
+    /// With more than one, a master may select several points in consecutive requests and then
+    /// operate each of them in consecutive requests, within the select timeout. When the limit is
+    /// reached, a new SELECT replaces the oldest one.
+    pub max_concurrent_selects: u16,
     /// optional features that can be enabled
     pub features: Features,
+    /// request function codes that are processed
+    pub functions: FunctionCodes,
+    /// broadcast addresses accepted by the outstation when broadcast support is enabled
+    pub broadcast_addresses: BroadcastAddresses,
     /// number of non-regenerated unsolicited retries to perform
     pub max_unsolicited_retries: Option<usize>,
     /// amount of time to wait after a failed unsolicited response series before starting another series
@@ -112,6 +283,13 @@ pub struct OutstationConfig {
     ///
     /// A value of `None` will disable this feature
     pub keep_alive_timeout: Option<std::time::Duration>,
+    /// link-layer retries and timeouts
+    pub link: LinkConfig,
+    /// transport segment size and reassembly limits
+    pub transport: TransportConfig,
+    /// how frames sent to other destinations are handled
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub unknown_destination: UnknownDestinationPolicy,
     /// Maximum number of headers that will be processed
     /// in a READ request. Internally, this controls the size of a
     /// pre-allocated buffer used to process requests. A minimum
@@ -121,8 +299,58 @@ pub struct OutstationConfig {
     pub max_read_request_headers: Option<u16>,
     /// Maximum number of controls in a single request
     pub max_controls_per_request: Option<u16>,
+    /// Maximum amount of time the outstation waits for controls that complete asynchronously
+    /// before responding. Controls that have not completed are reported as `CommandStatus::Timeout`.
+    ///
+    /// This value should be less than the response timeout of the master.
+    pub control_completion_timeout: std::time::Duration,
+    /// Maximum number of events written into a single solicited response fragment
+    ///
+    /// Remaining events are written in subsequent fragments of the same response series.
+    /// A value of `None` means the number of events is only limited by the buffer size.
+    pub max_events_per_solicited_response: Option<NonZeroU16>,
+    /// Maximum number of events written into a single unsolicited response
+    ///
+    /// Remaining events are reported in the class IIN bits and sent in subsequent
+    /// unsolicited responses. A value of `None` means the number of events is only
+    /// limited by the buffer size.
+    pub max_events_per_unsolicited_response: Option<NonZeroU16>,
+    /// Maximum amount of time the outstation waits after a change to the database before
+    /// reporting it in an unsolicited response
+    ///
+    /// Changes made while waiting are reported together, so that a burst of transactions
+    /// produces a single unsolicited response instead of one per transaction. A value of `None`
+    /// reports each change as soon as the outstation is idle.
+    pub change_coalescing_delay: Option<std::time::Duration>,
+    /// controls which requests are checked for retries from the master
+    pub duplicate_detection: DuplicateDetection,
     /// controls responses to class 0 READ requests
     pub class_zero: ClassZeroConfig,
+    /// controls how the static values of each type are stored in the database
+    pub storage: StorageConfig,
+    /// clock used for timeouts, retry delays, and keep-alives
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub clock: Clock,
+    /// custom spans and redaction of the decoded output
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub tracing: TracingConfig,
+    /// pool from which the transmit and receive buffers are obtained when needed and to which
+    /// they are released when the outstation is idle
+    ///
+    /// A value of `None` allocates the buffers when the outstation is created and keeps them
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub buffer_pool: Option<Arc<BufferPool>>,
+    /// runtime onto which the `spawn_*` functions and servers spawn the outstation
+    ///
+    /// A value of `None` spawns the outstation onto the current runtime
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub spawner: Option<Arc<Spawner>>,
+    /// logger to which received requests and transmitted responses are reported
+    ///
+    /// A value of `None` doesn't produce audit records. Outstations whose configurations share
+    /// the same logger report to it from their own tasks.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub audit_logger: Option<Arc<dyn AuditLogger>>,
 }
 
 impl Feature {
@@ -142,6 +370,11 @@ impl OutstationConfig {
     pub const DEFAULT_CONFIRM_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
     /// Default select timeout
     pub const DEFAULT_SELECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+    /// Default number of SELECTs held at the same time
+    pub const DEFAULT_MAX_CONCURRENT_SELECTS: u16 = 1;
+    /// Default amount of time to wait for controls that complete asynchronously
+    pub const DEFAULT_CONTROL_COMPLETION_TIMEOUT: std::time::Duration =
+        std::time::Duration::from_secs(2);
     /// Default unsolicited retry delay between series
     pub const DEFAULT_UNSOLICITED_RETRY_DELAY: std::time::Duration =
         std::time::Duration::from_secs(5);
@@ -158,15 +391,501 @@ impl OutstationConfig {
             decode_level: DecodeLevel::nothing(),
             confirm_timeout: Self::DEFAULT_CONFIRM_TIMEOUT,
             select_timeout: Self::DEFAULT_SELECT_TIMEOUT,
+            max_concurrent_selects: Self::DEFAULT_MAX_CONCURRENT_SELECTS,
             features: Features::default(),
+            functions: FunctionCodes::default(),
+            broadcast_addresses: BroadcastAddresses::default(),
             max_unsolicited_retries: None,
             unsolicited_retry_delay: Self::DEFAULT_UNSOLICITED_RETRY_DELAY,
             keep_alive_timeout: Some(std::time::Duration::from_secs(60)),
+            link: LinkConfig::default(),
+            transport: TransportConfig::default(),
+            unknown_destination: UnknownDestinationPolicy::default(),
             max_read_request_headers: None,
             max_controls_per_request: None,
+            control_completion_timeout: Self::DEFAULT_CONTROL_COMPLETION_TIMEOUT,
+            max_events_per_solicited_response: None,
+            max_events_per_unsolicited_response: None,
+            change_coalescing_delay: None,
+            duplicate_detection: DuplicateDetection::default(),
             class_zero: ClassZeroConfig::default(),
+            storage: StorageConfig::default(),
+            clock: Clock::default(),
+            tracing: TracingConfig::default(),
+            buffer_pool: None,
+            spawner: None,
+            audit_logger: None,
+        }
+    }
+
+    /// constructs an `OutstationConfig` suited to a server hosting a large number of outstations
+    ///
+    /// The settings are those of [OutstationConfig::new], except that the buffers are obtained
+    /// from `pool` when needed and released when the outstation is idle. The memory used by each
+    /// outstation is then roughly:
+    ///
+    /// * at all times: the static values in the database, the buffered events, and a copy of the
+    ///   last solicited response so that it can be repeated if the master retries its request
+    /// * while a request is received: `rx_buffer_size` bytes
+    /// * while responding: `solicited_buffer_size` or `unsolicited_buffer_size` bytes, up to
+    ///   2336 bytes of link frames being written, and a copy of the static values selected by a
+    ///   READ request
+    ///
+    /// The storage of the event buffer grows with the number of buffered events, so the
+    /// [EventBufferConfig](crate::outstation::database::EventBufferConfig) limits its size
+    /// instead of determining it. Static values take less memory with
+    /// [PointStorage::Dense](crate::outstation::database::PointStorage::Dense) when the indices of
+    /// a type are contiguous.
+    pub fn compact(
+        outstation_address: EndpointAddress,
+        master_address: EndpointAddress,
+        pool: Arc<BufferPool>,
+    ) -> Self {
+        Self {
+            buffer_pool: Some(pool),
+            ..Self::new(outstation_address, master_address)
+        }
+    }
+
+    /// check that the settings are consistent with each other
+    ///
+    /// This is performed by [OutstationConfigBuilder::build] and is useful for configurations
+    /// that are constructed directly or loaded from a file.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.outstation_address == self.master_address {
+            return Err(ConfigError::AddressCollision(self.outstation_address));
+        }
+
+        ConfigError::check_timeout("confirm_timeout", self.confirm_timeout)?;
+        ConfigError::check_timeout("select_timeout", self.select_timeout)?;
+        ConfigError::check_timeout(
+            "control_completion_timeout",
+            self.control_completion_timeout,
+        )?;
+        if let Some(timeout) = self.keep_alive_timeout {
+            ConfigError::check_timeout("keep_alive_timeout", timeout)?;
+        }
+
+        if self.features.unsolicited.is_enabled()
+            && self.unsolicited_retry_delay < self.confirm_timeout
+        {
+            return Err(ConfigError::UnsolicitedRetryDelayTooShort {
+                retry_delay: self.unsolicited_retry_delay,
+                confirm_timeout: self.confirm_timeout,
+            });
+        }
+
+        ConfigError::check_limit("max_concurrent_selects", Some(self.max_concurrent_selects))?;
+
+        // received segments may be as large as a link frame allows, regardless of our segment size
+        if let Some(segments) = self.transport.max_rx_segments {
+            let max_fragment_size = segments.get().saturating_mul(SegmentSize::MAX);
+            if self.rx_buffer_size.value() > max_fragment_size {
+                return Err(ConfigError::RxBufferExceedsSegments {
+                    rx_buffer_size: self.rx_buffer_size.value(),
+                    max_fragment_size,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for OutstationConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let Self {
+            outstation_address,
+            master_address,
+            solicited_buffer_size,
+            unsolicited_buffer_size,
+            rx_buffer_size,
+            decode_level,
+            confirm_timeout,
+            select_timeout,
+            max_concurrent_selects,
+            features,
+            functions,
+            broadcast_addresses,
+            max_unsolicited_retries,
+            unsolicited_retry_delay,
+            keep_alive_timeout,
+            link,
+            transport,
+            unknown_destination,
+            max_read_request_headers,
+            max_controls_per_request,
+            control_completion_timeout,
+            max_events_per_solicited_response,
+            max_events_per_unsolicited_response,
+            change_coalescing_delay,
+            duplicate_detection,
+            class_zero,
+            storage,
+            clock,
+            tracing,
+            buffer_pool,
+            spawner,
+            audit_logger,
+        } = self;
+
+        f.debug_struct("OutstationConfig")
+            .field("outstation_address", outstation_address)
+            .field("master_address", master_address)
+            .field("solicited_buffer_size", solicited_buffer_size)
+            .field("unsolicited_buffer_size", unsolicited_buffer_size)
+            .field("rx_buffer_size", rx_buffer_size)
+            .field("decode_level", decode_level)
+            .field("confirm_timeout", confirm_timeout)
+            .field("select_timeout", select_timeout)
+            .field("max_concurrent_selects", max_concurrent_selects)
+            .field("features", features)
+            .field("functions", functions)
+            .field("broadcast_addresses", broadcast_addresses)
+            .field("max_unsolicited_retries", max_unsolicited_retries)
+            .field("unsolicited_retry_delay", unsolicited_retry_delay)
+            .field("keep_alive_timeout", keep_alive_timeout)
+            .field("link", link)
+            .field("transport", transport)
+            .field("unknown_destination", unknown_destination)
+            .field("max_read_request_headers", max_read_request_headers)
+            .field("max_controls_per_request", max_controls_per_request)
+            .field("control_completion_timeout", control_completion_timeout)
+            .field(
+                "max_events_per_solicited_response",
+                max_events_per_solicited_response,
+            )
+            .field(
+                "max_events_per_unsolicited_response",
+                max_events_per_unsolicited_response,
+            )
+            .field("change_coalescing_delay", change_coalescing_delay)
+            .field("duplicate_detection", duplicate_detection)
+            .field("class_zero", class_zero)
+            .field("storage", storage)
+            .field("clock", clock)
+            .field("tracing", tracing)
+            .field("buffer_pool", buffer_pool)
+            .field("spawner", spawner)
+            .field("audit_logger", &audit_logger.is_some())
+            .finish()
+    }
+}
+
+// the audit logger is a handler, like those passed to the spawn functions, and isn't compared
+impl PartialEq for OutstationConfig {
+    fn eq(&self, other: &Self) -> bool {
+        let Self {
+            outstation_address,
+            master_address,
+            solicited_buffer_size,
+            unsolicited_buffer_size,
+            rx_buffer_size,
+            decode_level,
+            confirm_timeout,
+            select_timeout,
+            max_concurrent_selects,
+            features,
+            functions,
+            broadcast_addresses,
+            max_unsolicited_retries,
+            unsolicited_retry_delay,
+            keep_alive_timeout,
+            link,
+            transport,
+            unknown_destination,
+            max_read_request_headers,
+            max_controls_per_request,
+            control_completion_timeout,
+            max_events_per_solicited_response,
+            max_events_per_unsolicited_response,
+            change_coalescing_delay,
+            duplicate_detection,
+            class_zero,
+            storage,
+            clock,
+            tracing,
+            buffer_pool,
+            spawner,
+            audit_logger: _,
+        } = self;
+
+        outstation_address == &other.outstation_address
+            && master_address == &other.master_address
+            && solicited_buffer_size == &other.solicited_buffer_size
+            && unsolicited_buffer_size == &other.unsolicited_buffer_size
+            && rx_buffer_size == &other.rx_buffer_size
+            && decode_level == &other.decode_level
+            && confirm_timeout == &other.confirm_timeout
+            && select_timeout == &other.select_timeout
+            && max_concurrent_selects == &other.max_concurrent_selects
+            && features == &other.features
+            && functions == &other.functions
+            && broadcast_addresses == &other.broadcast_addresses
+            && max_unsolicited_retries == &other.max_unsolicited_retries
+            && unsolicited_retry_delay == &other.unsolicited_retry_delay
+            && keep_alive_timeout == &other.keep_alive_timeout
+            && link == &other.link
+            && transport == &other.transport
+            && unknown_destination == &other.unknown_destination
+            && max_read_request_headers == &other.max_read_request_headers
+            && max_controls_per_request == &other.max_controls_per_request
+            && control_completion_timeout == &other.control_completion_timeout
+            && max_events_per_solicited_response == &other.max_events_per_solicited_response
+            && max_events_per_unsolicited_response == &other.max_events_per_unsolicited_response
+            && change_coalescing_delay == &other.change_coalescing_delay
+            && duplicate_detection == &other.duplicate_detection
+            && class_zero == &other.class_zero
+            && storage == &other.storage
+            && clock == &other.clock
+            && tracing == &other.tracing
+            && buffer_pool == &other.buffer_pool
+            && spawner == &other.spawner
+    }
+}
+
+/// Builds an [OutstationConfig] whose settings are checked for consistency
+///
+/// Settings that are not specified have the values of [OutstationConfig::new].
+#[derive(Clone, Debug)]
+pub struct OutstationConfigBuilder {
+    config: OutstationConfig,
+}
+
+impl OutstationConfigBuilder {
+    /// create a builder with the default settings and the specified link addresses
+    pub fn new(outstation_address: EndpointAddress, master_address: EndpointAddress) -> Self {
+        Self {
+            config: OutstationConfig::new(outstation_address, master_address),
         }
     }
+
+    /// set the buffer size for transmitted solicited responses
+    pub fn with_solicited_buffer_size(mut self, solicited_buffer_size: BufferSize) -> Self {
+        self.config.solicited_buffer_size = solicited_buffer_size;
+        self
+    }
+
+    /// set the buffer size for transmitted unsolicited responses
+    pub fn with_unsolicited_buffer_size(mut self, unsolicited_buffer_size: BufferSize) -> Self {
+        self.config.unsolicited_buffer_size = unsolicited_buffer_size;
+        self
+    }
+
+    /// set the buffer size for received requests
+    pub fn with_rx_buffer_size(mut self, rx_buffer_size: BufferSize) -> Self {
+        self.config.rx_buffer_size = rx_buffer_size;
+        self
+    }
+
+    /// set the initial decoding level
+    pub fn with_decode_level(mut self, decode_level: DecodeLevel) -> Self {
+        self.config.decode_level = decode_level;
+        self
+    }
+
+    /// set the confirm timeout for solicited and unsolicited responses
+    pub fn with_confirm_timeout(mut self, confirm_timeout: std::time::Duration) -> Self {
+        self.config.confirm_timeout = confirm_timeout;
+        self
+    }
+
+    /// set the timeout after which a matching OPERATE will fail
+    pub fn with_select_timeout(mut self, select_timeout: std::time::Duration) -> Self {
+        self.config.select_timeout = select_timeout;
+        self
+    }
+
+    /// set the maximum number of SELECTs held at the same time
+    pub fn with_max_concurrent_selects(mut self, max_concurrent_selects: u16) -> Self {
+        self.config.max_concurrent_selects = max_concurrent_selects;
+        self
+    }
+
+    /// set the optional features that are enabled
+    pub fn with_features(mut self, features: Features) -> Self {
+        self.config.features = features;
+        self
+    }
+
+    /// set the request function codes that are processed
+    pub fn with_functions(mut self, functions: FunctionCodes) -> Self {
+        self.config.functions = functions;
+        self
+    }
+
+    /// set the broadcast addresses accepted by the outstation
+    pub fn with_broadcast_addresses(mut self, broadcast_addresses: BroadcastAddresses) -> Self {
+        self.config.broadcast_addresses = broadcast_addresses;
+        self
+    }
+
+    /// set the number of non-regenerated unsolicited retries
+    pub fn with_max_unsolicited_retries(mut self, max_unsolicited_retries: Option<usize>) -> Self {
+        self.config.max_unsolicited_retries = max_unsolicited_retries;
+        self
+    }
+
+    /// set the delay after a failed unsolicited response series
+    pub fn with_unsolicited_retry_delay(
+        mut self,
+        unsolicited_retry_delay: std::time::Duration,
+    ) -> Self {
+        self.config.unsolicited_retry_delay = unsolicited_retry_delay;
+        self
+    }
+
+    /// set the time without link activity before a REQUEST_LINK_STATES
+    pub fn with_keep_alive_timeout(
+        mut self,
+        keep_alive_timeout: Option<std::time::Duration>,
+    ) -> Self {
+        self.config.keep_alive_timeout = keep_alive_timeout;
+        self
+    }
+
+    /// set the link-layer retries and timeouts
+    pub fn with_link(mut self, link: LinkConfig) -> Self {
+        self.config.link = link;
+        self
+    }
+
+    /// set the transport segment size and reassembly limits
+    pub fn with_transport(mut self, transport: TransportConfig) -> Self {
+        self.config.transport = transport;
+        self
+    }
+
+    /// set how frames sent to other destinations are handled
+    pub fn with_unknown_destination(
+        mut self,
+        unknown_destination: UnknownDestinationPolicy,
+    ) -> Self {
+        self.config.unknown_destination = unknown_destination;
+        self
+    }
+
+    /// set the maximum number of headers processed in a READ request
+    pub fn with_max_read_request_headers(mut self, max_read_request_headers: Option<u16>) -> Self {
+        self.config.max_read_request_headers = max_read_request_headers;
+        self
+    }
+
+    /// set the maximum number of controls in a single request
+    pub fn with_max_controls_per_request(mut self, max_controls_per_request: Option<u16>) -> Self {
+        self.config.max_controls_per_request = max_controls_per_request;
+        self
+    }
+
+    /// set the maximum time to wait for controls that complete asynchronously
+    pub fn with_control_completion_timeout(
+        mut self,
+        control_completion_timeout: std::time::Duration,
+    ) -> Self {
+        self.config.control_completion_timeout = control_completion_timeout;
+        self
+    }
+
+    /// set the maximum number of events in a solicited response fragment
+    pub fn with_max_events_per_solicited_response(
+        mut self,
+        max_events_per_solicited_response: Option<NonZeroU16>,
+    ) -> Self {
+        self.config.max_events_per_solicited_response = max_events_per_solicited_response;
+        self
+    }
+
+    /// set the maximum number of events in an unsolicited response
+    pub fn with_max_events_per_unsolicited_response(
+        mut self,
+        max_events_per_unsolicited_response: Option<NonZeroU16>,
+    ) -> Self {
+        self.config.max_events_per_unsolicited_response = max_events_per_unsolicited_response;
+        self
+    }
+
+    /// set the maximum delay before reporting a change in an unsolicited response
+    pub fn with_change_coalescing_delay(
+        mut self,
+        change_coalescing_delay: Option<std::time::Duration>,
+    ) -> Self {
+        self.config.change_coalescing_delay = change_coalescing_delay;
+        self
+    }
+
+    /// set the which requests are checked for retries from the master
+    pub fn with_duplicate_detection(mut self, duplicate_detection: DuplicateDetection) -> Self {
+        self.config.duplicate_detection = duplicate_detection;
+        self
+    }
+
+    /// set the which types are reported in class 0 READ requests
+    pub fn with_class_zero(mut self, class_zero: ClassZeroConfig) -> Self {
+        self.config.class_zero = class_zero;
+        self
+    }
+
+    /// set the how the static values of each type are stored
+    pub fn with_storage(mut self, storage: StorageConfig) -> Self {
+        self.config.storage = storage;
+        self
+    }
+
+    /// set the clock used for timeouts, retry delays, and keep-alives
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.config.clock = clock;
+        self
+    }
+
+    /// set the custom spans and redaction of the decoded output
+    pub fn with_tracing(mut self, tracing: TracingConfig) -> Self {
+        self.config.tracing = tracing;
+        self
+    }
+
+    /// set the pool from which buffers are obtained when needed
+    pub fn with_buffer_pool(mut self, buffer_pool: Option<Arc<BufferPool>>) -> Self {
+        self.config.buffer_pool = buffer_pool;
+        self
+    }
+
+    /// set the runtime onto which the outstation is spawned
+    pub fn with_spawner(mut self, spawner: Option<Arc<Spawner>>) -> Self {
+        self.config.spawner = spawner;
+        self
+    }
+
+    /// set the logger to which requests and responses are reported
+    pub fn with_audit_logger(mut self, audit_logger: Option<Arc<dyn AuditLogger>>) -> Self {
+        self.config.audit_logger = audit_logger;
+        self
+    }
+
+    /// check the settings and return the configuration
+    pub fn build(self) -> Result<OutstationConfig, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+impl From<OutstationConfig> for OutstationConfigBuilder {
+    fn from(config: OutstationConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl std::convert::TryFrom<usize> for BufferSize {
+    type Error = BufferSizeError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<BufferSize> for usize {
+    fn from(value: BufferSize) -> Self {
+        value.size
+    }
 }
 
 impl std::fmt::Display for BufferSizeError {
@@ -178,8 +897,143 @@ impl std::fmt::Display for BufferSizeError {
                 size,
                 BufferSize::MIN
             ),
+            Self::TooLarge(size) => write!(
+                f,
+                "provided size {} is greater than the maximum allowed size of {}",
+                size,
+                BufferSize::MAX
+            ),
         }
     }
 }
 
 impl std::error::Error for BufferSizeError {}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroUsize;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::app::RangeError;
+
+    fn address(value: u16) -> EndpointAddress {
+        EndpointAddress::from(value).unwrap()
+    }
+
+    #[test]
+    fn default_settings_are_valid() {
+        let config = OutstationConfigBuilder::new(address(1024), address(1))
+            .build()
+            .unwrap();
+        assert_eq!(config, OutstationConfig::new(address(1024), address(1)));
+    }
+
+    #[test]
+    fn buffer_size_is_bounded() {
+        assert_eq!(
+            BufferSize::new(BufferSize::MIN - 1),
+            Err(BufferSizeError::TooSmall(248))
+        );
+        assert_eq!(BufferSize::new(BufferSize::MAX).unwrap().value(), 65535);
+        assert_eq!(
+            BufferSize::new(BufferSize::MAX + 1),
+            Err(BufferSizeError::TooLarge(65536))
+        );
+        assert!(!BufferSize::default().exceeds_default());
+        assert!(BufferSize::new(4096).unwrap().exceeds_default());
+    }
+
+    #[test]
+    fn confirm_and_read_cannot_be_disabled() {
+        let functions = FunctionCodes::all()
+            .disable(FunctionCode::Confirm)
+            .disable(FunctionCode::Read)
+            .disable(FunctionCode::ColdRestart)
+            .disable(FunctionCode::FreezeClear);
+
+        assert!(functions.is_enabled(FunctionCode::Confirm));
+        assert!(functions.is_enabled(FunctionCode::Read));
+        assert!(functions.is_enabled(FunctionCode::WarmRestart));
+        assert!(!functions.is_enabled(FunctionCode::ColdRestart));
+        assert!(!functions.is_enabled(FunctionCode::FreezeClear));
+        assert!(functions
+            .enable(FunctionCode::ColdRestart)
+            .is_enabled(FunctionCode::ColdRestart));
+    }
+
+    #[test]
+    fn builder_rejects_inconsistent_settings() {
+        let builder = OutstationConfigBuilder::new(address(1024), address(1));
+
+        assert_eq!(
+            OutstationConfigBuilder::new(address(7), address(7)).build(),
+            Err(ConfigError::AddressCollision(address(7)))
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .with_unsolicited_retry_delay(Duration::from_secs(1))
+                .build(),
+            Err(ConfigError::UnsolicitedRetryDelayTooShort {
+                retry_delay: Duration::from_secs(1),
+                confirm_timeout: OutstationConfig::DEFAULT_CONFIRM_TIMEOUT,
+            })
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .with_select_timeout(Duration::from_secs(0))
+                .build(),
+            Err(ConfigError::InvalidTimeout {
+                field: "select_timeout",
+                error: RangeError::TooSmall(Duration::from_secs(0)),
+            })
+        );
+        assert_eq!(
+            builder.clone().with_max_concurrent_selects(0).build(),
+            Err(ConfigError::ZeroLimit {
+                field: "max_concurrent_selects"
+            })
+        );
+        assert_eq!(
+            builder
+                .with_transport(TransportConfig {
+                    max_rx_segments: NonZeroUsize::new(2),
+                    ..TransportConfig::default()
+                })
+                .build(),
+            Err(ConfigError::RxBufferExceedsSegments {
+                rx_buffer_size: BufferSize::DEFAULT,
+                max_fragment_size: 2 * SegmentSize::MAX,
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn configuration_serde_round_trip() {
+        let mut config = OutstationConfig::new(address(1024), address(1));
+        config.solicited_buffer_size = BufferSize::new(4096).unwrap();
+        config.keep_alive_timeout = None;
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains(r#""outstation_address":1024"#));
+        assert!(json.contains(r#""solicited_buffer_size":4096"#));
+        assert_eq!(
+            serde_json::from_str::<OutstationConfig>(&json).unwrap(),
+            config
+        );
+
+        // validated types reject invalid values
+        let json = json.replace(r#""master_address":1"#, r#""master_address":65535"#);
+        assert!(serde_json::from_str::<OutstationConfig>(&json).is_err());
+        assert!(serde_json::from_str::<BufferSize>("100").is_err());
+        // as are event limits of zero, which would produce endless series of empty fragments
+        let json = serde_json::to_string(&config).unwrap().replace(
+            r#""max_events_per_solicited_response":null"#,
+            r#""max_events_per_solicited_response":0"#,
+        );
+        assert!(serde_json::from_str::<OutstationConfig>(&json).is_err());
+    }
+}
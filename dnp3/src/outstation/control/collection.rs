@@ -8,12 +8,55 @@ use crate::app::{QualifierCode, Variation};
 use crate::outstation::control::control_type::ControlType;
 use crate::outstation::control::prefix::PrefixWriter;
 use crate::outstation::database::Database;
-use crate::outstation::traits::{ControlHandler, ControlSupport, OperateType};
+use crate::outstation::traits::{
+    ControlHandler, ControlSupport, OperateResult, OperateType, PendingOperate,
+};
 use crate::util::cursor::{WriteCursor, WriteError};
 
+/// an operation that will complete asynchronously and the position of
+/// its status byte in the response
+pub(crate) struct PendingControl {
+    pub(crate) status_offset: usize,
+    pub(crate) operation: PendingOperate,
+    // status received from the application, `None` until the operation completes
+    status: Option<CommandStatus>,
+}
+
+impl PendingControl {
+    /// wait for the operation to complete. This future is cancel safe.
+    pub(crate) async fn wait(&mut self) {
+        if self.status.is_none() {
+            // the application dropped the completion handle
+            let status = (&mut self.operation.rx)
+                .await
+                .unwrap_or(CommandStatus::Canceled);
+            self.status = Some(status);
+        }
+    }
+
+    /// final status of the operation, `CommandStatus::Timeout` if it hasn't completed yet
+    pub(crate) fn status(&mut self) -> CommandStatus {
+        if let Some(status) = self.status {
+            return status;
+        }
+
+        match self.operation.rx.try_recv() {
+            Ok(status) => status,
+            Err(crate::tokio::sync::oneshot::error::TryRecvError::Empty) => {
+                tracing::warn!("control did not complete before the response was sent");
+                CommandStatus::Timeout
+            }
+            Err(crate::tokio::sync::oneshot::error::TryRecvError::Closed) => {
+                CommandStatus::Canceled
+            }
+        }
+    }
+}
+
 pub(crate) struct ControlTransaction<'a> {
     stared: bool,
     handler: &'a mut dyn ControlHandler,
+    pending: Vec<PendingControl>,
 }
 
 impl<'a> ControlTransaction<'a> {
@@ -21,9 +64,23 @@ impl<'a> ControlTransaction<'a> {
         ControlTransaction {
             stared: false,
             handler,
+            pending: Vec::new(),
         }
     }
 
+    /// take the operations that will complete asynchronously
+    pub(crate) fn take_pending(&mut self) -> Vec<PendingControl> {
+        std::mem::take(&mut self.pending)
+    }
+
+    fn defer(&mut self, status_offset: usize, operation: PendingOperate) {
+        self.pending.push(PendingControl {
+            status_offset,
+            operation,
+            status: None,
+        });
+    }
+
     fn start(&mut self) {
         if !self.stared {
             self.stared = true;
@@ -47,6 +104,9 @@ impl<'a> ControlSupport<Group12Var1> for ControlTransaction<'a> {
         index: u16,
         database: &mut Database,
     ) -> CommandStatus {
+        if !database.inner.is_binary_output_controllable(index) {
+            return CommandStatus::NotSupported;
+        }
         self.start();
         self.handler.select(control, index, database)
     }
@@ -58,9 +118,27 @@ impl<'a> ControlSupport<Group12Var1> for ControlTransaction<'a> {
         op_type: OperateType,
         database: &mut Database,
     ) -> CommandStatus {
+        if !database.inner.is_binary_output_controllable(index) {
+            return CommandStatus::NotSupported;
+        }
         self.start();
         self.handler.operate(control, index, op_type, database)
     }
+
+    fn begin_operate(
+        &mut self,
+        control: Group12Var1,
+        index: u16,
+        op_type: OperateType,
+        database: &mut Database,
+    ) -> OperateResult {
+        if !database.inner.is_binary_output_controllable(index) {
+            return OperateResult::Complete(CommandStatus::NotSupported);
+        }
+        self.start();
+        self.handler
+            .begin_operate(control, index, op_type, database)
+    }
 }
 
 impl<'a> ControlSupport<Group41Var1> for ControlTransaction<'a> {
@@ -70,6 +148,9 @@ impl<'a> ControlSupport<Group41Var1> for ControlTransaction<'a> {
         index: u16,
         database: &mut Database,
     ) -> CommandStatus {
+        if !database.inner.is_analog_output_controllable(index) {
+            return CommandStatus::NotSupported;
+        }
         self.start();
         self.handler.select(control, index, database)
     }
@@ -81,9 +162,27 @@ impl<'a> ControlSupport<Group41Var1> for ControlTransaction<'a> {
         op_type: OperateType,
         database: &mut Database,
     ) -> CommandStatus {
+        if !database.inner.is_analog_output_controllable(index) {
+            return CommandStatus::NotSupported;
+        }
         self.start();
         self.handler.operate(control, index, op_type, database)
     }
+
+    fn begin_operate(
+        &mut self,
+        control: Group41Var1,
+        index: u16,
+        op_type: OperateType,
+        database: &mut Database,
+    ) -> OperateResult {
+        if !database.inner.is_analog_output_controllable(index) {
+            return OperateResult::Complete(CommandStatus::NotSupported);
+        }
+        self.start();
+        self.handler
+            .begin_operate(control, index, op_type, database)
+    }
 }
 
 impl<'a> ControlSupport<Group41Var2> for ControlTransaction<'a> {
@@ -93,6 +192,9 @@ impl<'a> ControlSupport<Group41Var2> for ControlTransaction<'a> {
         index: u16,
         database: &mut Database,
     ) -> CommandStatus {
+        if !database.inner.is_analog_output_controllable(index) {
+            return CommandStatus::NotSupported;
+        }
         self.start();
         self.handler.select(control, index, database)
     }
@@ -104,9 +206,27 @@ impl<'a> ControlSupport<Group41Var2> for ControlTransaction<'a> {
         op_type: OperateType,
         database: &mut Database,
     ) -> CommandStatus {
+        if !database.inner.is_analog_output_controllable(index) {
+            return CommandStatus::NotSupported;
+        }
         self.start();
         self.handler.operate(control, index, op_type, database)
     }
+
+    fn begin_operate(
+        &mut self,
+        control: Group41Var2,
+        index: u16,
+        op_type: OperateType,
+        database: &mut Database,
+    ) -> OperateResult {
+        if !database.inner.is_analog_output_controllable(index) {
+            return OperateResult::Complete(CommandStatus::NotSupported);
+        }
+        self.start();
+        self.handler
+            .begin_operate(control, index, op_type, database)
+    }
 }
 
 impl<'a> ControlSupport<Group41Var3> for ControlTransaction<'a> {
@@ -116,6 +236,9 @@ impl<'a> ControlSupport<Group41Var3> for ControlTransaction<'a> {
         index: u16,
         database: &mut Database,
     ) -> CommandStatus {
+        if !database.inner.is_analog_output_controllable(index) {
+            return CommandStatus::NotSupported;
+        }
         self.start();
         self.handler.select(control, index, database)
     }
@@ -127,9 +250,27 @@ impl<'a> ControlSupport<Group41Var3> for ControlTransaction<'a> {
         op_type: OperateType,
         database: &mut Database,
     ) -> CommandStatus {
+        if !database.inner.is_analog_output_controllable(index) {
+            return CommandStatus::NotSupported;
+        }
         self.start();
         self.handler.operate(control, index, op_type, database)
     }
+
+    fn begin_operate(
+        &mut self,
+        control: Group41Var3,
+        index: u16,
+        op_type: OperateType,
+        database: &mut Database,
+    ) -> OperateResult {
+        if !database.inner.is_analog_output_controllable(index) {
+            return OperateResult::Complete(CommandStatus::NotSupported);
+        }
+        self.start();
+        self.handler
+            .begin_operate(control, index, op_type, database)
+    }
 }
 
 impl<'a> ControlSupport<Group41Var4> for ControlTransaction<'a> {
@@ -139,6 +280,9 @@ impl<'a> ControlSupport<Group41Var4> for ControlTransaction<'a> {
         index: u16,
         database: &mut Database,
     ) -> CommandStatus {
+        if !database.inner.is_analog_output_controllable(index) {
+            return CommandStatus::NotSupported;
+        }
         self.start();
         self.handler.select(control, index, database)
     }
@@ -150,9 +294,27 @@ impl<'a> ControlSupport<Group41Var4> for ControlTransaction<'a> {
         op_type: OperateType,
         database: &mut Database,
     ) -> CommandStatus {
+        if !database.inner.is_analog_output_controllable(index) {
+            return CommandStatus::NotSupported;
+        }
         self.start();
         self.handler.operate(control, index, op_type, database)
     }
+
+    fn begin_operate(
+        &mut self,
+        control: Group41Var4,
+        index: u16,
+        op_type: OperateType,
+        database: &mut Database,
+    ) -> OperateResult {
+        if !database.inner.is_analog_output_controllable(index) {
+            return OperateResult::Complete(CommandStatus::NotSupported);
+        }
+        self.start();
+        self.handler
+            .begin_operate(control, index, op_type, database)
+    }
 }
 
 impl<'a> ObjectHeader<'a> {
@@ -704,7 +866,7 @@ where
     let mut writer = PrefixWriter::new();
     let mut ret = CommandStatus::Success;
     for item in seq.iter() {
-        let status = if max_controls_per_request.map_or(true, |max| *num_controls < max) {
+        let result = if max_controls_per_request.map_or(true, |max| *num_controls < max) {
             item.value.operate(
                 transaction,
                 item.index.widen_to_u16(),
@@ -712,10 +874,24 @@ where
                 database,
             )
         } else {
-            CommandStatus::TooManyOps
+            OperateResult::Complete(CommandStatus::TooManyOps)
         };
-        writer.write(cursor, item.value.with_status(status), item.index)?;
-        ret = ret.first_error(status);
+        match result {
+            OperateResult::Complete(status) => {
+                writer.write(cursor, item.value.with_status(status), item.index)?;
+                ret = ret.first_error(status);
+            }
+            OperateResult::Pending(operation) => {
+                // the final status is written once the operation completes
+                writer.write(
+                    cursor,
+                    item.value.with_status(CommandStatus::Success),
+                    item.index,
+                )?;
+                // the status is always the last byte of a control object
+                transaction.defer(cursor.position() - 1, operation);
+            }
+        }
         *num_controls += 1;
     }
     Ok(ret)
@@ -733,7 +909,8 @@ fn operate_header_no_ack<I, V>(
 {
     for item in seq.iter() {
         if max_controls_per_request.map_or(true, |max| *num_controls < max) {
-            item.value.operate(
+            // a pending operation is dropped since no response is sent
+            let _ = item.value.operate(
                 transaction,
                 item.index.widen_to_u16(),
                 OperateType::DirectOperateNoAck,
@@ -10,7 +10,7 @@ pub(crate) struct SelectState {
     /// without requests in between
     frame_id: u32,
     /// time at which the SELECT occurred
-    time: crate::tokio::time::Instant,
+    time: crate::app::Instant,
     /// the hash of the object headers
     object_hash: u64,
 }
@@ -19,7 +19,7 @@ impl SelectState {
     pub(crate) fn new(
         seq: Sequence,
         frame_id: u32,
-        time: crate::tokio::time::Instant,
+        time: crate::app::Instant,
         object_hash: u64,
     ) -> Self {
         Self {
@@ -30,18 +30,34 @@ impl SelectState {
         }
     }
 
+    pub(crate) fn object_hash(&self) -> u64 {
+        self.object_hash
+    }
+
     pub(crate) fn update_frame_id(&mut self, new_frame_id: u32) {
         self.frame_id = new_frame_id;
     }
 
+    /// true if the request directly follows the SELECT or the last request of its chain
+    pub(crate) fn is_followed_by(&self, seq: Sequence, frame_id: u32) -> bool {
+        self.seq.next() == seq.value() && self.frame_id.wrapping_add(1) == frame_id
+    }
+
+    /// record a SELECT or OPERATE of other points that directly followed this SELECT, so that
+    /// this SELECT can still be operated by the next request
+    pub(crate) fn chain(&mut self, seq: Sequence, frame_id: u32) {
+        self.seq = seq;
+        self.frame_id = frame_id;
+    }
+
     pub(crate) fn match_operate(
         &self,
+        now: crate::app::Instant,
         timeout: std::time::Duration,
         seq: Sequence,
         frame_id: u32,
-        object_hash: u64,
     ) -> Result<(), CommandStatus> {
-        let elapsed = crate::tokio::time::Instant::now().checked_duration_since(self.time);
+        let elapsed = now.checked_duration_since(self.time);
 
         // check the sequence number
         if self.seq.next() != seq.value() {
@@ -55,12 +71,6 @@ impl SelectState {
             return Err(CommandStatus::NoSelect);
         }
 
-        // check the object hash
-        if self.object_hash != object_hash {
-            tracing::warn!("received OPERATE with different header than SELECT");
-            return Err(CommandStatus::NoSelect);
-        }
-
         // check the time last
         match elapsed {
             None => {
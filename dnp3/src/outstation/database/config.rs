@@ -101,6 +101,10 @@ pub enum EventAnalogOutputStatusVariation {
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub(crate) struct EventOctetStringVariation;
 
+// This is always g102v1 with a 2-byte index prefix
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct EventUnsignedIntegerVariation;
+
 /// Enum representing all possible `Binary` static variations
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum StaticBinaryVariation {
@@ -192,6 +196,10 @@ pub enum StaticAnalogOutputStatusVariation {
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub(crate) struct StaticOctetStringVariation;
 
+// This is always g102v1
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct StaticUnsignedIntegerVariation;
+
 /// configuration for a `Binary` point
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct BinaryConfig {
@@ -263,10 +271,131 @@ pub struct AnalogOutputStatusConfig {
     pub deadband: f64,
 }
 
+/// Linear scaling between the raw value reported over DNP3 and an engineering value
+///
+/// engineering value = raw value * `multiplier` + `offset`
+///
+/// The multiplier is never zero so that the scaling can always be inverted.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Scaling {
+    multiplier: f64,
+    offset: f64,
+}
+
+/// Reasons a [Scaling] cannot be constructed
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScalingError {
+    /// the multiplier is zero, so raw values could not be computed from engineering values
+    ZeroMultiplier,
+    /// the multiplier or the offset is NaN or infinite
+    NotFinite,
+}
+
+impl Scaling {
+    /// construct a `Scaling` from its fields
+    pub fn new(multiplier: f64, offset: f64) -> Result<Self, ScalingError> {
+        if !multiplier.is_finite() || !offset.is_finite() {
+            return Err(ScalingError::NotFinite);
+        }
+        if multiplier == 0.0 {
+            return Err(ScalingError::ZeroMultiplier);
+        }
+        Ok(Self { multiplier, offset })
+    }
+
+    /// value by which the raw value is multiplied
+    pub fn multiplier(&self) -> f64 {
+        self.multiplier
+    }
+
+    /// value added to the multiplied raw value
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// convert a raw value to an engineering value
+    pub fn to_engineering(&self, raw: f64) -> f64 {
+        raw * self.multiplier + self.offset
+    }
+
+    /// convert an engineering value to a raw value
+    pub fn to_raw(&self, engineering: f64) -> f64 {
+        (engineering - self.offset) / self.multiplier
+    }
+}
+
+impl std::fmt::Display for ScalingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScalingError::ZeroMultiplier => f.write_str("scaling multiplier may not be zero"),
+            ScalingError::NotFinite => {
+                f.write_str("scaling multiplier and offset must be finite numbers")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScalingError {}
+
+/// Descriptive information that may be attached to a point in the database
+///
+/// The outstation does not interpret this information. It's stored alongside the point
+/// so that applications, e.g. gateways, have a single source of truth for point descriptions.
+///
+/// Masters can read the metadata of a point as Group 0 device attributes if it is assigned a
+/// private attribute set. The variations of the set are:
+///
+/// * 1 - group of the static objects of the point, e.g. 30 for analog inputs (UINT)
+/// * 2 - index of the point (UINT)
+/// * 3 - name (VSTR)
+/// * 4 - units (VSTR), if present
+/// * 5 - scaling multiplier (FLT), if scaling is present
+/// * 6 - scaling offset (FLT), if scaling is present
+/// * 255 - list of the variations above that are present
+///
+/// Visible strings are limited to 255 bytes, so longer names and units are truncated in the
+/// attributes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PointMetadata {
+    /// name of the point
+    pub name: String,
+    /// engineering units of the point, e.g. "kV"
+    pub units: Option<String>,
+    /// scaling between raw and engineering values
+    pub scaling: Option<Scaling>,
+    /// private device attribute set in the range [1, 32767] through which masters can read the
+    /// metadata, or `None` if it isn't exposed over DNP3
+    pub attribute_set: Option<u16>,
+}
+
+impl PointMetadata {
+    /// lowest private device attribute set
+    pub const MIN_ATTRIBUTE_SET: u16 = 1;
+    /// highest private device attribute set
+    pub const MAX_ATTRIBUTE_SET: u16 = 32767;
+
+    /// construct a `PointMetadata` with a name and no units, scaling, or attribute set
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            units: None,
+            scaling: None,
+            attribute_set: None,
+        }
+    }
+}
+
 ///  Placeholder object required by a couple of traits
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct OctetStringConfig;
 
+/// configuration for an `UnsignedInteger` point
+///
+/// Group 102 only has a single variation, so there is nothing to configure. Any change in value
+/// produces an event.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct UnsignedIntegerConfig;
+
 impl BinaryConfig {
     /// construct a `BinaryConfig` from its fields
     pub fn new(s_var: StaticBinaryVariation, e_var: EventBinaryVariation) -> Self {
@@ -412,3 +541,22 @@ impl Default for AnalogOutputStatusConfig {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaling_must_be_invertible() {
+        assert_eq!(Scaling::new(0.0, 1.0), Err(ScalingError::ZeroMultiplier));
+        assert_eq!(Scaling::new(f64::NAN, 1.0), Err(ScalingError::NotFinite));
+        assert_eq!(
+            Scaling::new(1.0, f64::INFINITY),
+            Err(ScalingError::NotFinite)
+        );
+
+        let scaling = Scaling::new(0.5, 2.0).unwrap();
+        assert_eq!(scaling.to_engineering(10.0), 7.0);
+        assert_eq!(scaling.to_raw(7.0), 10.0);
+    }
+}
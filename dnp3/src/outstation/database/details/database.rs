@@ -1,34 +1,67 @@
+use crate::app::measurement::Flags;
 use crate::app::Iin2;
 use crate::master::EventClasses;
 use crate::outstation::database::details::event::buffer::EventBuffer;
 use crate::outstation::database::details::range::static_db::{
-    PointConfig, StaticDatabase, Updatable,
+    HasFlags, IndexRange, PointConfig, StaticDatabase, StaticSelection, Updatable,
 };
 use crate::outstation::database::read::ReadHeader;
 use crate::outstation::database::{
-    ClassZeroConfig, EventBufferConfig, ResponseInfo, UpdateOptions,
+    ClassZeroConfig, EventBufferConfig, PointMetadata, StorageConfig, UpdateOptions,
 };
 use crate::util::cursor::WriteCursor;
 
+use std::collections::BTreeSet;
+use std::num::NonZeroU16;
+
+/// Indices of output points declared as controllable
+///
+/// `None` means that nothing has been declared for the type and every control is forwarded
+/// to the `ControlHandler`
+#[derive(Default)]
+struct ControllablePoints {
+    binary_outputs: Option<BTreeSet<u16>>,
+    analog_outputs: Option<BTreeSet<u16>>,
+}
+
+impl ControllablePoints {
+    fn set(points: &mut Option<BTreeSet<u16>>, index: u16, controllable: bool) {
+        let points = points.get_or_insert_with(BTreeSet::new);
+        if controllable {
+            points.insert(index);
+        } else {
+            points.remove(&index);
+        }
+    }
+
+    fn contains(points: &Option<BTreeSet<u16>>, index: u16) -> bool {
+        match points {
+            None => true,
+            Some(points) => points.contains(&index),
+        }
+    }
+}
+
 pub(crate) struct Database {
     static_db: StaticDatabase,
     event_buffer: EventBuffer,
+    controllable: ControllablePoints,
 }
 
 impl Database {
     pub(crate) fn new(
-        max_read_selection: Option<u16>,
         class_zero_config: ClassZeroConfig,
+        storage_config: StorageConfig,
         config: EventBufferConfig,
     ) -> Self {
         Self {
-            static_db: StaticDatabase::new(max_read_selection, class_zero_config),
+            static_db: StaticDatabase::new(class_zero_config, storage_config),
             event_buffer: EventBuffer::new(config),
+            controllable: ControllablePoints::default(),
         }
     }
 
     pub(crate) fn reset(&mut self) {
-        self.static_db.reset();
         self.event_buffer.reset();
     }
 
@@ -44,9 +77,18 @@ impl Database {
         self.event_buffer.is_overflown()
     }
 
-    pub(crate) fn select_by_header(&mut self, header: ReadHeader) -> Iin2 {
+    pub(crate) fn num_events(&self) -> usize {
+        self.event_buffer.len()
+    }
+
+    /// select the events of a header, or copy the static values it requests into `selection`
+    pub(crate) fn select_by_header(
+        &mut self,
+        header: ReadHeader,
+        selection: &mut StaticSelection,
+    ) -> Iin2 {
         match header {
-            ReadHeader::Static(header) => self.static_db.select(header),
+            ReadHeader::Static(header) => self.static_db.select(header, selection),
             ReadHeader::Event(header) => {
                 self.event_buffer.select_by_header(header);
                 Iin2::default()
@@ -58,6 +100,22 @@ impl Database {
         self.event_buffer.select_by_class(classes, None)
     }
 
+    pub(crate) fn set_binary_output_controllable(&mut self, index: u16, controllable: bool) {
+        ControllablePoints::set(&mut self.controllable.binary_outputs, index, controllable)
+    }
+
+    pub(crate) fn set_analog_output_controllable(&mut self, index: u16, controllable: bool) {
+        ControllablePoints::set(&mut self.controllable.analog_outputs, index, controllable)
+    }
+
+    pub(crate) fn is_binary_output_controllable(&self, index: u16) -> bool {
+        ControllablePoints::contains(&self.controllable.binary_outputs, index)
+    }
+
+    pub(crate) fn is_analog_output_controllable(&self, index: u16) -> bool {
+        ControllablePoints::contains(&self.controllable.analog_outputs, index)
+    }
+
     pub(crate) fn add<T>(&mut self, index: u16, config: PointConfig<T>) -> bool
     where
         T: Updatable,
@@ -79,6 +137,41 @@ impl Database {
         self.static_db.get::<T>(index)
     }
 
+    pub(crate) fn set_metadata<T>(&mut self, index: u16, metadata: Option<PointMetadata>) -> bool
+    where
+        T: Updatable,
+    {
+        self.static_db.set_metadata::<T>(index, metadata)
+    }
+
+    pub(crate) fn get_metadata<T>(&self, index: u16) -> Option<PointMetadata>
+    where
+        T: Updatable,
+    {
+        self.static_db.get_metadata::<T>(index)
+    }
+
+    pub(crate) fn find_by_name<T>(&self, name: &str) -> Option<u16>
+    where
+        T: Updatable,
+    {
+        self.static_db.find_by_name::<T>(name)
+    }
+
+    pub(crate) fn set_class_zero<T>(&mut self, index: u16, included: bool) -> bool
+    where
+        T: Updatable,
+    {
+        self.static_db.set_class_zero::<T>(index, included)
+    }
+
+    pub(crate) fn get_class_zero<T>(&self, index: u16) -> Option<bool>
+    where
+        T: Updatable,
+    {
+        self.static_db.get_class_zero::<T>(index)
+    }
+
     pub(crate) fn update<T>(&mut self, value: &T, index: u16, options: UpdateOptions) -> bool
     where
         T: Updatable,
@@ -94,31 +187,44 @@ impl Database {
         exists
     }
 
-    pub(crate) fn write_response_headers(&mut self, cursor: &mut WriteCursor) -> ResponseInfo {
-        // first we write events
-        let result = self.event_buffer.write_events(cursor);
-        let has_events = match result {
-            Ok(count) => count > 0,
-            Err(count) => count > 0,
-        };
-
-        let complete = if result.is_err() {
-            // unable to write all the events in this response, so we can't any static data
-            false
-        } else {
-            // write all events to we can try to write all static data
-            self.static_db.write(cursor).is_ok()
-        };
-
-        ResponseInfo {
-            has_events,
-            complete,
+    /// set and clear flag bits of the points within a range, returning the number of points
+    pub(crate) fn update_flags<T>(
+        &mut self,
+        range: IndexRange,
+        set: Flags,
+        clear: Flags,
+        options: UpdateOptions,
+    ) -> usize
+    where
+        T: Updatable + HasFlags,
+    {
+        let indices = self.static_db.indices::<T>(range);
+        for index in indices.iter() {
+            if let Some(mut value) = self.static_db.get::<T>(*index) {
+                let flags = value.flags();
+                value.set_flags(Flags::new((flags.value & !clear.value) | set.value));
+                self.update(&value, *index, options);
+            }
         }
+        indices.len()
+    }
+
+    /// write the selected events, returning `Err` if not all of them could be written
+    pub(crate) fn write_events(
+        &mut self,
+        max_events: Option<NonZeroU16>,
+        cursor: &mut WriteCursor,
+    ) -> Result<usize, usize> {
+        self.event_buffer.write_events(max_events, cursor)
     }
 
-    pub(crate) fn write_events_only(&mut self, cursor: &mut WriteCursor) -> usize {
+    pub(crate) fn write_events_only(
+        &mut self,
+        max_events: Option<NonZeroU16>,
+        cursor: &mut WriteCursor,
+    ) -> usize {
         // doesn't matter if we wrote all of them or not
-        match self.event_buffer.write_events(cursor) {
+        match self.event_buffer.write_events(max_events, cursor) {
             Ok(x) => x,
             Err(x) => x,
         }
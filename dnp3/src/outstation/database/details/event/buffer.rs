@@ -1,5 +1,6 @@
 use std::cell::Cell;
 use std::fmt::Debug;
+use std::num::NonZeroU16;
 use std::ops::BitOr;
 
 use crate::app::measurement::{self};
@@ -9,7 +10,7 @@ use crate::outstation::database::read::EventReadHeader;
 use crate::outstation::database::{EventBufferConfig, EventClass};
 use crate::util::cursor::{WriteCursor, WriteError};
 
-use super::list::VecList;
+use super::list::{Index, ListIterator, VecList, MAX_MERGED_KEYS};
 use super::writer::EventWriter;
 
 impl From<EventClass> for EventClasses {
@@ -150,6 +151,7 @@ pub(crate) struct TypeCounter {
     num_analog: Count,
     num_analog_output_status: Count,
     num_octet_string: Count,
+    num_unsigned_integer: Count,
 }
 
 impl TypeCounter {
@@ -163,6 +165,7 @@ impl TypeCounter {
             num_analog: Count::new(),
             num_analog_output_status: Count::new(),
             num_octet_string: Count::new(),
+            num_unsigned_integer: Count::new(),
         }
     }
 
@@ -175,6 +178,7 @@ impl TypeCounter {
         self.num_analog.zero();
         self.num_analog_output_status.zero();
         self.num_octet_string.zero();
+        self.num_unsigned_integer.zero();
     }
 
     fn increment(&mut self, event: &Event) {
@@ -200,6 +204,7 @@ impl TypeCounter {
             Event::Analog(_, _) => op(&mut self.num_analog),
             Event::AnalogOutputStatus(_, _) => op(&mut self.num_analog_output_status),
             Event::OctetString(_, _) => op(&mut self.num_octet_string),
+            Event::UnsignedInteger(_, _) => op(&mut self.num_unsigned_integer),
         }
     }
 }
@@ -239,6 +244,7 @@ impl Counters {
             Event::Analog(_, _) => self.types.num_analog.decrement(),
             Event::AnalogOutputStatus(_, _) => self.types.num_analog_output_status.decrement(),
             Event::OctetString(_, _) => self.types.num_octet_string.decrement(),
+            Event::UnsignedInteger(_, _) => self.types.num_unsigned_integer.decrement(),
         }
     }
 }
@@ -290,6 +296,40 @@ enum Event {
         Variation<EventAnalogOutputStatusVariation>,
     ),
     OctetString(Box<[u8]>, Variation<EventOctetStringVariation>),
+    UnsignedInteger(
+        measurement::UnsignedInteger,
+        Variation<EventUnsignedIntegerVariation>,
+    ),
+}
+
+/// key of the list of each type of event within the event buffer
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum EventType {
+    Binary,
+    DoubleBitBinary,
+    BinaryOutputStatus,
+    Counter,
+    FrozenCounter,
+    Analog,
+    AnalogOutputStatus,
+    OctetString,
+    UnsignedInteger,
+}
+
+impl EventType {
+    const COUNT: usize = 9;
+}
+
+// groupings of the event list
+const CLASS_GROUP: usize = 0;
+const TYPE_GROUP: usize = 1;
+
+fn class_key(class: EventClass) -> usize {
+    match class {
+        EventClass::Class1 => 0,
+        EventClass::Class2 => 1,
+        EventClass::Class3 => 2,
+    }
 }
 
 impl Event {
@@ -303,6 +343,7 @@ impl Event {
             Event::Analog(_, v) => v.select_default(),
             Event::AnalogOutputStatus(_, v) => v.select_default(),
             Event::OctetString(_, v) => v.select_default(),
+            Event::UnsignedInteger(_, v) => v.select_default(),
         }
     }
 
@@ -321,6 +362,7 @@ impl Event {
             Event::Analog(evt, v) => writer.write(cursor, evt, index, v.selected.get()),
             Event::AnalogOutputStatus(evt, v) => writer.write(cursor, evt, index, v.selected.get()),
             Event::OctetString(evt, v) => writer.write(cursor, evt, index, v.selected.get()),
+            Event::UnsignedInteger(evt, v) => writer.write(cursor, evt, index, v.selected.get()),
         }
     }
 }
@@ -359,10 +401,10 @@ impl EventRecord {
 pub(crate) trait Insertable: Sized {
     type EventVariation: Copy;
 
+    const EVENT_TYPE: EventType;
+
     fn get_max(config: &EventBufferConfig) -> u16;
     fn get_type_count(counter: &TypeCounter) -> usize;
-    fn is_type(record: &EventRecord) -> bool;
-    fn decrement_type(counter: &mut TypeCounter);
     fn increment_type(counter: &mut TypeCounter);
     fn create_event_record(
         &self,
@@ -374,9 +416,57 @@ pub(crate) trait Insertable: Sized {
     fn select_variation(record: &EventRecord, variation: Self::EventVariation) -> bool;
 }
 
+/// events selected by a request, written in insertion order across the fragments of a response
+///
+/// Each fragment resumes where the previous one stopped, so the events are only sorted once and
+/// the written events aren't removed from the front of the vector.
+#[derive(Default)]
+struct SelectedEvents {
+    indices: Vec<Index>,
+    // position of the next event to write
+    next: usize,
+    sorted: bool,
+}
+
+impl SelectedEvents {
+    fn push(&mut self, index: Index) {
+        self.indices.push(index);
+        self.sorted = false;
+    }
+
+    /// events that haven't been written yet, in insertion order
+    fn remaining(&mut self) -> &[Index] {
+        if !self.sorted {
+            // events are written in the order they were inserted, regardless of how they were
+            // selected
+            self.indices[self.next..].sort_unstable();
+            self.sorted = true;
+        }
+        &self.indices[self.next..]
+    }
+
+    /// mark the first `count` remaining events as written
+    fn advance(&mut self, count: usize) {
+        self.next += count;
+        if self.next >= self.indices.len() {
+            self.clear();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.indices.clear();
+        self.next = 0;
+        self.sorted = true;
+    }
+}
+
 pub(crate) struct EventBuffer {
     config: EventBufferConfig,
     events: VecList<EventRecord>,
+    // selected events that haven't been written yet
+    selected: SelectedEvents,
+    // written events that will be removed when the response is confirmed
+    unconfirmed: Vec<Index>,
     total: Counters,
     written: Counters,
     is_overflown: bool,
@@ -398,7 +488,9 @@ impl EventBuffer {
         let max_size = config.max_events();
         Self {
             config,
-            events: VecList::new(max_size),
+            events: VecList::new(max_size, [3, EventType::COUNT]),
+            selected: SelectedEvents::default(),
+            unconfirmed: Vec::new(),
             total: Counters::new(),
             written: Counters::new(),
             is_overflown: false,
@@ -431,9 +523,12 @@ impl EventBuffer {
         }
 
         let ret = if T::get_type_count(&self.total.types) == max as usize {
-            if let Some(record) = self.events.remove_first(T::is_type) {
-                T::decrement_type(&mut self.total.types);
-                self.total.classes.decrement(record.class);
+            let oldest = self.events.first(TYPE_GROUP, T::EVENT_TYPE as usize);
+            if let Some(record) = oldest.and_then(|x| self.events.remove_at(x)) {
+                self.total.decrement(&record);
+                if record.state.get() == EventState::Written {
+                    self.written.decrement(&record);
+                }
                 self.is_overflown = true;
             }
             Err(InsertError::Overflow)
@@ -441,8 +536,11 @@ impl EventBuffer {
             Ok(())
         };
 
-        self.events
-            .add(event.create_event_record(index, class, default_variation));
+        let keys = [class_key(class), T::EVENT_TYPE as usize];
+        self.events.add(
+            event.create_event_record(index, class, default_variation),
+            keys,
+        );
         self.total.classes.increment(class);
         T::increment_type(&mut self.total.types);
 
@@ -488,13 +586,18 @@ impl EventBuffer {
     }
 
     pub(crate) fn select_by_class(&mut self, classes: EventClasses, limit: Option<usize>) -> usize {
-        self.select(limit, |e| {
-            if classes.matches(e.class) {
-                e.event.select_default_variation();
-                true
-            } else {
-                false
+        let mut keys = [0; MAX_MERGED_KEYS];
+        let mut count = 0;
+        for class in [EventClass::Class1, EventClass::Class2, EventClass::Class3].iter() {
+            if classes.matches(*class) {
+                keys[count] = class_key(*class);
+                count += 1;
             }
+        }
+        let candidates = self.events.iter_keys(CLASS_GROUP, &keys[..count]);
+        Self::select(&mut self.selected, candidates, limit, |e| {
+            e.event.select_default_variation();
+            true
         })
     }
 
@@ -520,29 +623,50 @@ impl EventBuffer {
     where
         T: Insertable,
     {
-        self.select(limit, |e| T::select_variation(e, variation))
+        let candidates = self.events.iter_keys(TYPE_GROUP, &[T::EVENT_TYPE as usize]);
+        Self::select(&mut self.selected, candidates, limit, |e| {
+            T::select_variation(e, variation)
+        })
     }
 
     fn select_default_variation<T>(&mut self, limit: Option<usize>) -> usize
     where
         T: Insertable,
     {
-        self.select(limit, |rec| {
-            if T::is_type(rec) {
-                rec.event.select_default_variation();
-                true
-            } else {
-                false
-            }
+        let candidates = self.events.iter_keys(TYPE_GROUP, &[T::EVENT_TYPE as usize]);
+        Self::select(&mut self.selected, candidates, limit, |rec| {
+            rec.event.select_default_variation();
+            true
         })
     }
 
-    pub(crate) fn write_events(&mut self, cursor: &mut WriteCursor) -> Result<usize, usize> {
+    pub(crate) fn write_events(
+        &mut self,
+        max_events: Option<NonZeroU16>,
+        cursor: &mut WriteCursor,
+    ) -> Result<usize, usize> {
         let mut count = 0;
         let mut writer = EventWriter::new();
         let mut counters = self.written.clone();
         let mut complete = true;
-        for record in self.selected_iter() {
+        let mut consumed = 0;
+        for index in self.selected.remaining().iter() {
+            let record = match self.events.get(*index) {
+                Some(x) if x.state.get() == EventState::Selected => x,
+                _ => {
+                    // removed by an overflow since it was selected
+                    consumed += 1;
+                    continue;
+                }
+            };
+
+            if let Some(max) = max_events {
+                if count >= max.get() as usize {
+                    complete = false;
+                    break; // reached the limit with events remaining
+                }
+            }
+
             if record
                 .event
                 .write(record.index, cursor, &mut writer)
@@ -552,10 +676,13 @@ impl EventBuffer {
                 break; // out of space
             }
 
-            counters.increment(&record);
+            counters.increment(record);
             record.state.set(EventState::Written);
+            self.unconfirmed.push(*index);
+            consumed += 1;
             count += 1;
         }
+        self.selected.advance(consumed);
         self.written = counters;
 
         if complete {
@@ -566,15 +693,13 @@ impl EventBuffer {
     }
 
     pub(crate) fn clear_written(&mut self) -> usize {
-        let total = &mut self.total;
-        let count = self.events.remove_all(|event| {
-            if event.state.get() == EventState::Written {
-                total.decrement(event);
-                true
-            } else {
-                false
+        let mut count = 0;
+        for index in self.unconfirmed.drain(..) {
+            if let Some(record) = self.events.remove_at(index) {
+                self.total.decrement(&record);
+                count += 1;
             }
-        });
+        }
 
         self.written.zero();
         if !self.is_any_full() {
@@ -584,12 +709,21 @@ impl EventBuffer {
     }
 
     pub(crate) fn reset(&mut self) {
-        for (_, r) in self.events.iter() {
-            r.state.set(EventState::Unselected);
+        for index in self.selected.indices.iter().chain(self.unconfirmed.iter()) {
+            if let Some(record) = self.events.get(*index) {
+                record.state.set(EventState::Unselected);
+            }
         }
+        self.selected.clear();
+        self.unconfirmed.clear();
         self.written.zero();
     }
 
+    /// number of events in the buffer, including those written but not yet confirmed
+    pub(crate) fn len(&self) -> usize {
+        self.events.len()
+    }
+
     pub(crate) fn is_overflown(&self) -> bool {
         self.is_overflown
     }
@@ -603,6 +737,7 @@ impl EventBuffer {
             || self.is_full::<measurement::Analog>()
             || self.is_full::<measurement::AnalogOutputStatus>()
             || self.is_full::<measurement::OctetString>()
+            || self.is_full::<measurement::UnsignedInteger>()
     }
 
     fn is_full<T>(&self) -> bool
@@ -618,37 +753,35 @@ impl EventBuffer {
         T::get_type_count(&self.total.types) >= max as usize
     }
 
-    fn select<F>(&mut self, limit: Option<usize>, selector: F) -> usize
+    fn select<F>(
+        selected: &mut SelectedEvents,
+        candidates: ListIterator<EventRecord>,
+        limit: Option<usize>,
+        selector: F,
+    ) -> usize
     where
         F: Fn(&EventRecord) -> bool,
     {
         let mut count = 0;
 
-        for (_, evt) in self
-            .events
-            .iter()
+        for (index, evt) in candidates
             .filter(|(_, e)| e.state.get() == EventState::Unselected && selector(e))
             .take(limit.unwrap_or(usize::max_value()))
         {
             evt.state.set(EventState::Selected);
+            selected.push(index);
             count += 1;
         }
 
         count
     }
-
-    /// iterator over selected values that need to be written
-    fn selected_iter(&self) -> impl Iterator<Item = &EventRecord> {
-        self.events
-            .iter()
-            .map(|x| x.1)
-            .filter(|x| x.state.get() == EventState::Selected)
-    }
 }
 
 impl Insertable for measurement::Binary {
     type EventVariation = EventBinaryVariation;
 
+    const EVENT_TYPE: EventType = EventType::Binary;
+
     fn get_max(config: &EventBufferConfig) -> u16 {
         config.max_binary
     }
@@ -657,14 +790,6 @@ impl Insertable for measurement::Binary {
         counter.num_binary.get()
     }
 
-    fn is_type(record: &EventRecord) -> bool {
-        std::matches!(record.event, Event::Binary(_, _))
-    }
-
-    fn decrement_type(counter: &mut TypeCounter) {
-        counter.num_binary.decrement();
-    }
-
     fn increment_type(counter: &mut TypeCounter) {
         counter.num_binary.increment();
     }
@@ -695,6 +820,8 @@ impl Insertable for measurement::Binary {
 impl Insertable for measurement::DoubleBitBinary {
     type EventVariation = EventDoubleBitBinaryVariation;
 
+    const EVENT_TYPE: EventType = EventType::DoubleBitBinary;
+
     fn get_max(config: &EventBufferConfig) -> u16 {
         config.max_double_binary
     }
@@ -703,14 +830,6 @@ impl Insertable for measurement::DoubleBitBinary {
         counter.num_double_binary.get()
     }
 
-    fn is_type(record: &EventRecord) -> bool {
-        std::matches!(record.event, Event::DoubleBitBinary(_, _))
-    }
-
-    fn decrement_type(counter: &mut TypeCounter) {
-        counter.num_double_binary.decrement();
-    }
-
     fn increment_type(counter: &mut TypeCounter) {
         counter.num_double_binary.increment();
     }
@@ -741,6 +860,8 @@ impl Insertable for measurement::DoubleBitBinary {
 impl Insertable for measurement::BinaryOutputStatus {
     type EventVariation = EventBinaryOutputStatusVariation;
 
+    const EVENT_TYPE: EventType = EventType::BinaryOutputStatus;
+
     fn get_max(config: &EventBufferConfig) -> u16 {
         config.max_binary_output_status
     }
@@ -749,14 +870,6 @@ impl Insertable for measurement::BinaryOutputStatus {
         counter.num_binary_output_status.get()
     }
 
-    fn is_type(record: &EventRecord) -> bool {
-        std::matches!(record.event, Event::BinaryOutputStatus(_, _))
-    }
-
-    fn decrement_type(counter: &mut TypeCounter) {
-        counter.num_binary_output_status.decrement();
-    }
-
     fn increment_type(counter: &mut TypeCounter) {
         counter.num_binary_output_status.increment();
     }
@@ -787,6 +900,8 @@ impl Insertable for measurement::BinaryOutputStatus {
 impl Insertable for measurement::Counter {
     type EventVariation = EventCounterVariation;
 
+    const EVENT_TYPE: EventType = EventType::Counter;
+
     fn get_max(config: &EventBufferConfig) -> u16 {
         config.max_counter
     }
@@ -795,14 +910,6 @@ impl Insertable for measurement::Counter {
         counter.num_counter.get()
     }
 
-    fn is_type(record: &EventRecord) -> bool {
-        std::matches!(record.event, Event::Counter(_, _))
-    }
-
-    fn decrement_type(counter: &mut TypeCounter) {
-        counter.num_counter.decrement();
-    }
-
     fn increment_type(counter: &mut TypeCounter) {
         counter.num_counter.increment();
     }
@@ -833,6 +940,8 @@ impl Insertable for measurement::Counter {
 impl Insertable for measurement::FrozenCounter {
     type EventVariation = EventFrozenCounterVariation;
 
+    const EVENT_TYPE: EventType = EventType::FrozenCounter;
+
     fn get_max(config: &EventBufferConfig) -> u16 {
         config.max_frozen_counter
     }
@@ -841,14 +950,6 @@ impl Insertable for measurement::FrozenCounter {
         counter.num_frozen_counter.get()
     }
 
-    fn is_type(record: &EventRecord) -> bool {
-        std::matches!(record.event, Event::FrozenCounter(_, _))
-    }
-
-    fn decrement_type(counter: &mut TypeCounter) {
-        counter.num_frozen_counter.decrement();
-    }
-
     fn increment_type(counter: &mut TypeCounter) {
         counter.num_frozen_counter.increment();
     }
@@ -879,6 +980,8 @@ impl Insertable for measurement::FrozenCounter {
 impl Insertable for measurement::Analog {
     type EventVariation = EventAnalogVariation;
 
+    const EVENT_TYPE: EventType = EventType::Analog;
+
     fn get_max(config: &EventBufferConfig) -> u16 {
         config.max_analog
     }
@@ -887,14 +990,6 @@ impl Insertable for measurement::Analog {
         counter.num_analog.get()
     }
 
-    fn is_type(record: &EventRecord) -> bool {
-        std::matches!(record.event, Event::Analog(_, _))
-    }
-
-    fn decrement_type(counter: &mut TypeCounter) {
-        counter.num_analog.decrement();
-    }
-
     fn increment_type(counter: &mut TypeCounter) {
         counter.num_analog.increment();
     }
@@ -925,6 +1020,8 @@ impl Insertable for measurement::Analog {
 impl Insertable for measurement::AnalogOutputStatus {
     type EventVariation = EventAnalogOutputStatusVariation;
 
+    const EVENT_TYPE: EventType = EventType::AnalogOutputStatus;
+
     fn get_max(config: &EventBufferConfig) -> u16 {
         config.max_analog_output_status
     }
@@ -933,14 +1030,6 @@ impl Insertable for measurement::AnalogOutputStatus {
         counter.num_analog_output_status.get()
     }
 
-    fn is_type(record: &EventRecord) -> bool {
-        std::matches!(record.event, Event::AnalogOutputStatus(_, _))
-    }
-
-    fn decrement_type(counter: &mut TypeCounter) {
-        counter.num_analog_output_status.decrement();
-    }
-
     fn increment_type(counter: &mut TypeCounter) {
         counter.num_analog_output_status.increment();
     }
@@ -971,6 +1060,8 @@ impl Insertable for measurement::AnalogOutputStatus {
 impl Insertable for measurement::OctetString {
     type EventVariation = EventOctetStringVariation;
 
+    const EVENT_TYPE: EventType = EventType::OctetString;
+
     fn get_max(config: &EventBufferConfig) -> u16 {
         config.max_octet_string
     }
@@ -979,14 +1070,6 @@ impl Insertable for measurement::OctetString {
         counter.num_octet_string.get()
     }
 
-    fn is_type(record: &EventRecord) -> bool {
-        std::matches!(record.event, Event::OctetString(_, _))
-    }
-
-    fn decrement_type(counter: &mut TypeCounter) {
-        counter.num_octet_string.decrement();
-    }
-
     fn increment_type(counter: &mut TypeCounter) {
         counter.num_octet_string.increment();
     }
@@ -1014,6 +1097,46 @@ impl Insertable for measurement::OctetString {
     }
 }
 
+impl Insertable for measurement::UnsignedInteger {
+    type EventVariation = EventUnsignedIntegerVariation;
+
+    const EVENT_TYPE: EventType = EventType::UnsignedInteger;
+
+    fn get_max(config: &EventBufferConfig) -> u16 {
+        config.max_unsigned_integer
+    }
+
+    fn get_type_count(counter: &TypeCounter) -> usize {
+        counter.num_unsigned_integer.get()
+    }
+
+    fn increment_type(counter: &mut TypeCounter) {
+        counter.num_unsigned_integer.increment();
+    }
+
+    fn create_event_record(
+        &self,
+        index: u16,
+        class: EventClass,
+        default_variation: EventUnsignedIntegerVariation,
+    ) -> EventRecord {
+        EventRecord::new(
+            index,
+            class,
+            Event::UnsignedInteger(*self, Variation::new(default_variation)),
+        )
+    }
+
+    fn select_variation(record: &EventRecord, variation: Self::EventVariation) -> bool {
+        if let Event::UnsignedInteger(_, v) = &record.event {
+            v.selected.set(variation);
+            true
+        } else {
+            false
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::app::measurement::*;
@@ -1112,6 +1235,66 @@ mod tests {
         )
     }
 
+    #[test]
+    fn overflow_removes_written_events_from_the_written_counts() {
+        let mut buffer = EventBuffer::new(EventBufferConfig::all_types(1));
+        let binary = Binary::new(true, Flags::ONLINE, Time::synchronized(0));
+
+        buffer
+            .insert(
+                1,
+                EventClass::Class1,
+                &binary,
+                EventBinaryVariation::Group2Var1,
+            )
+            .unwrap();
+        assert_eq!(1, buffer.select_by_class(EventClasses::all(), None));
+
+        let mut backing = [0u8; 64];
+        let mut cursor = WriteCursor::new(backing.as_mut());
+        assert_eq!(buffer.write_events(None, &mut cursor), Ok(1));
+        assert_eq!(buffer.unwritten_classes(), EventClasses::none());
+
+        // the written event is discarded before the response is confirmed
+        assert_matches!(
+            buffer.insert(
+                2,
+                EventClass::Class1,
+                &binary,
+                EventBinaryVariation::Group2Var1
+            ),
+            Err(InsertError::Overflow)
+        );
+        assert_eq!(
+            buffer.unwritten_classes(),
+            EventClasses::new(true, false, false)
+        );
+        assert_eq!(buffer.clear_written(), 0);
+
+        // the new event is still available
+        assert_eq!(1, buffer.select_by_class(EventClasses::all(), None));
+        let mut cursor = WriteCursor::new(backing.as_mut());
+        assert_eq!(buffer.write_events(None, &mut cursor), Ok(1));
+        assert_eq!(buffer.clear_written(), 1);
+        assert_eq!(buffer.unwritten_classes(), EventClasses::none());
+    }
+
+    #[test]
+    fn reset_makes_selected_and_written_events_available_again() {
+        let mut buffer = EventBuffer::new(EventBufferConfig::all_types(3));
+
+        insert_events(&mut buffer);
+
+        assert_eq!(5, buffer.select_by_class(EventClasses::all(), None));
+        let mut backing = [0u8; 64];
+        let mut cursor = WriteCursor::new(backing.as_mut());
+        assert_eq!(buffer.write_events(NonZeroU16::new(2), &mut cursor), Err(2));
+
+        buffer.reset();
+        assert_eq!(buffer.clear_written(), 0);
+        assert_eq!(5, buffer.select_by_class(EventClasses::all(), None));
+    }
+
     #[test]
     fn can_select_events_by_class_and_write_some() {
         let mut buffer = EventBuffer::new(EventBufferConfig::all_types(3));
@@ -1128,7 +1311,7 @@ mod tests {
 
         {
             let mut cursor = WriteCursor::new(backing.as_mut());
-            assert_eq!(buffer.write_events(&mut cursor), Err(1)); // not enough space to write both events
+            assert_eq!(buffer.write_events(None, &mut cursor), Err(1)); // not enough space to write both events
             let remaining_classes = EventClasses::all();
             assert_eq!(buffer.unwritten_classes(), remaining_classes);
             assert_eq!(buffer.clear_written(), 1);
@@ -1137,7 +1320,7 @@ mod tests {
 
         {
             let mut cursor = WriteCursor::new(backing.as_mut());
-            assert_eq!(buffer.write_events(&mut cursor), Err(1));
+            assert_eq!(buffer.write_events(None, &mut cursor), Err(1));
             let remaining_classes = EventClass::Class1 | EventClass::Class2; //  we just wrote the only class 3 event
             assert_eq!(buffer.unwritten_classes(), remaining_classes);
             assert_eq!(buffer.clear_written(), 1);
@@ -1145,6 +1328,155 @@ mod tests {
         }
     }
 
+    #[test]
+    fn stops_writing_events_at_the_limit() {
+        let mut buffer = EventBuffer::new(EventBufferConfig::all_types(3));
+
+        insert_events(&mut buffer);
+
+        assert_eq!(5, buffer.select_by_class(EventClasses::all(), None));
+
+        let mut backing = [0u8; 64];
+
+        {
+            let mut cursor = WriteCursor::new(backing.as_mut());
+            assert_eq!(buffer.write_events(NonZeroU16::new(3), &mut cursor), Err(3));
+            assert_eq!(buffer.clear_written(), 3);
+            assert_eq!(
+                buffer.unwritten_classes(),
+                EventClasses::new(true, true, false)
+            );
+        }
+
+        {
+            let mut cursor = WriteCursor::new(backing.as_mut());
+            assert_eq!(buffer.write_events(NonZeroU16::new(3), &mut cursor), Ok(2));
+            assert_eq!(buffer.clear_written(), 2);
+            assert_eq!(buffer.unwritten_classes(), EventClasses::none());
+        }
+    }
+
+    #[test]
+    fn writes_selected_events_in_insertion_order_across_fragments() {
+        let mut buffer = EventBuffer::new(EventBufferConfig::all_types(10));
+
+        // interleave the two types so that selecting them by type is out of insertion order
+        for i in 0..6 {
+            buffer
+                .insert(
+                    i,
+                    EventClass::Class1,
+                    &Binary::new(true, Flags::ONLINE, Time::synchronized(0)),
+                    EventBinaryVariation::Group2Var1,
+                )
+                .unwrap();
+            buffer
+                .insert(
+                    100 + i,
+                    EventClass::Class1,
+                    &Counter::new(23, Flags::ONLINE, Time::synchronized(0)),
+                    EventCounterVariation::Group22Var2,
+                )
+                .unwrap();
+        }
+
+        assert_eq!(6, buffer.select_default_variation::<Counter>(None));
+        assert_eq!(6, buffer.select_default_variation::<Binary>(None));
+
+        // inserted after the selection, so it isn't part of the response
+        buffer
+            .insert(
+                6,
+                EventClass::Class1,
+                &Binary::new(true, Flags::ONLINE, Time::synchronized(0)),
+                EventBinaryVariation::Group2Var1,
+            )
+            .unwrap();
+
+        let mut written = Vec::new();
+        let mut backing = [0u8; 64];
+        loop {
+            let mut cursor = WriteCursor::new(backing.as_mut());
+            let result = buffer.write_events(NonZeroU16::new(5), &mut cursor);
+            for index in buffer.unconfirmed.iter() {
+                written.push(buffer.events.get(*index).unwrap().index);
+            }
+            buffer.clear_written();
+            if result.is_ok() {
+                break;
+            }
+        }
+
+        assert_eq!(
+            written,
+            [0, 100, 1, 101, 2, 102, 3, 103, 4, 104, 5, 105].to_vec()
+        );
+        assert_eq!(
+            buffer.unwritten_classes(),
+            EventClasses::new(true, false, false)
+        );
+    }
+
+    #[test]
+    fn writes_events_selected_by_class_in_insertion_order() {
+        let mut buffer = EventBuffer::new(EventBufferConfig::all_types(10));
+
+        // interleave the classes and the types
+        let classes = [EventClass::Class1, EventClass::Class2, EventClass::Class3];
+        for i in 0..9 {
+            let class = classes[i as usize % 3];
+            if i % 2 == 0 {
+                buffer
+                    .insert(
+                        i,
+                        class,
+                        &Binary::new(true, Flags::ONLINE, Time::synchronized(0)),
+                        EventBinaryVariation::Group2Var1,
+                    )
+                    .unwrap();
+            } else {
+                buffer
+                    .insert(
+                        i,
+                        class,
+                        &Counter::new(23, Flags::ONLINE, Time::synchronized(0)),
+                        EventCounterVariation::Group22Var2,
+                    )
+                    .unwrap();
+            }
+        }
+
+        let write_selected = |buffer: &mut EventBuffer| {
+            let mut written = Vec::new();
+            let mut backing = [0u8; 64];
+            loop {
+                let mut cursor = WriteCursor::new(backing.as_mut());
+                let result = buffer.write_events(NonZeroU16::new(2), &mut cursor);
+                for index in buffer.unconfirmed.iter() {
+                    written.push(buffer.events.get(*index).unwrap().index);
+                }
+                buffer.clear_written();
+                if result.is_ok() {
+                    return written;
+                }
+            }
+        };
+
+        assert_eq!(
+            6,
+            buffer.select_by_class(EventClass::Class1 | EventClass::Class3, None)
+        );
+        assert_eq!(write_selected(&mut buffer), [0, 2, 3, 5, 6, 8].to_vec());
+        assert_eq!(
+            buffer.unwritten_classes(),
+            EventClasses::new(false, true, false)
+        );
+
+        assert_eq!(3, buffer.select_by_class(EventClasses::all(), None));
+        assert_eq!(write_selected(&mut buffer), [1, 4, 7].to_vec());
+        assert_eq!(buffer.unwritten_classes(), EventClasses::none());
+    }
+
     #[test]
     fn can_select_events_by_type() {
         let mut buffer = EventBuffer::new(EventBufferConfig::all_types(3));
@@ -1163,7 +1495,7 @@ mod tests {
         let mut backing = [0u8; 64];
         let mut cursor = WriteCursor::new(backing.as_mut());
 
-        assert_eq!(2, buffer.write_events(&mut cursor).unwrap());
+        assert_eq!(2, buffer.write_events(None, &mut cursor).unwrap());
 
         assert_eq!(
             cursor.written(),
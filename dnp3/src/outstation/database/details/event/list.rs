@@ -1,37 +1,39 @@
 use std::collections::VecDeque;
 
-#[derive(Copy, Clone)]
-struct MetaData {
-    version: u64,
+/// number of groupings an entry belongs to in addition to the list of all entries
+///
+/// the event buffer groups events by class and by type
+pub(crate) const GROUPS: usize = 2;
+
+/// maximum number of keys of a grouping that can be iterated over together
+pub(crate) const MAX_MERGED_KEYS: usize = 3;
+
+#[derive(Copy, Clone, Default)]
+struct Link {
     prev: Option<usize>,
     next: Option<usize>,
 }
 
-impl MetaData {
-    fn new(version: u64, prev: Option<usize>, next: Option<usize>) -> Self {
-        Self {
-            version,
-            prev,
-            next,
-        }
-    }
-
-    fn create_index(&self, index: usize) -> Index {
-        Index {
-            version: self.version,
-            value: index,
-        }
-    }
+#[derive(Copy, Clone, Default)]
+struct Chain {
+    head: Option<usize>,
+    tail: Option<usize>,
+    len: usize,
 }
 
 struct Entry<T> {
-    data: T,
-    is_free: bool,
-    metadata: MetaData,
+    // None when the entry is free
+    data: Option<T>,
+    version: u64,
+    // key of the entry within each grouping
+    keys: [usize; GROUPS],
+    // position in the list of all entries followed by the position in the list of each grouping
+    links: [Link; GROUPS + 1],
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) struct Index {
+    // versions increase with each insertion, so indices are ordered by insertion
     version: u64,
     value: usize,
 }
@@ -42,282 +44,210 @@ impl Index {
     }
 }
 
-impl<T> Entry<T> {
-    fn last(version: u64, data: T, prev: Option<usize>) -> Self {
-        Self {
-            data,
-            is_free: false,
-            metadata: MetaData::new(version, prev, None),
-        }
-    }
-
-    fn first(version: u64, data: T) -> Self {
-        Self {
-            data,
-            is_free: false,
-            metadata: MetaData::new(version, None, None),
-        }
-    }
-
-    fn create_index(&self, idx: usize) -> Index {
-        Index::new(self.metadata.version, idx)
-    }
-}
-
-#[derive(Copy, Clone)]
-struct State {
-    head: usize,
-    tail: usize,
-    size: usize,
-}
-
-impl State {
-    fn new(head: usize, tail: usize, size: usize) -> State {
-        State { head, tail, size }
-    }
-
-    fn append(&self, new_tail: usize) -> State {
-        Self {
-            head: self.head,
-            tail: new_tail,
-            size: self.size + 1,
-        }
-    }
-
-    fn single(index: usize) -> State {
-        State::new(index, index, 1)
-    }
-
-    fn from(size: usize, head: Option<usize>, tail: Option<usize>) -> Option<State> {
-        if size == 0 {
-            None
-        } else {
-            Some(State::new(head.unwrap(), tail.unwrap(), size))
-        }
-    }
-}
-
+/// doubly-linked list stored in a single allocation, in which every entry is also linked into
+/// one list per grouping, e.g. the list of its class and the list of its type
+///
+/// Adding, removing, and finding the first entry of a group are O(1), and iterating over a group
+/// only visits the entries of that group. The storage grows with the number of entries up to the
+/// capacity instead of being allocated up front, so a list that never fills up only uses memory
+/// for the entries it has held at once.
 pub(crate) struct VecList<T> {
     version: u64,
+    capacity: usize,
     storage: Vec<Entry<T>>,
     free_stack: VecDeque<usize>,
-    state: Option<State>,
+    // start of the chains of each grouping in `chains`
+    offsets: [usize; GROUPS],
+    sizes: [usize; GROUPS],
+    // list of all entries followed by the list of each key of each grouping
+    chains: Vec<Chain>,
 }
 
 pub(crate) struct ListIterator<'a, T> {
     list: &'a VecList<T>,
-    current: Option<usize>,
+    slot: usize,
+    // next entry of each chain being merged
+    cursors: [Option<usize>; MAX_MERGED_KEYS],
 }
 
 impl<'a, T> Iterator for ListIterator<'a, T> {
     type Item = (Index, &'a T);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let idx = self.current?;
-        let entry = &self.list.storage[idx];
-        self.current = entry.metadata.next;
-        Some((entry.metadata.create_index(idx), &entry.data))
+        let storage = &self.list.storage;
+        // the chains are each in insertion order, so merge them by version
+        let cursor = self
+            .cursors
+            .iter_mut()
+            .filter(|x| x.is_some())
+            .min_by_key(|x| x.map(|idx| storage[idx].version))?;
+        let idx = cursor.take()?;
+        let entry = &storage[idx];
+        *cursor = entry.links[self.slot].next;
+        let data = entry.data.as_ref()?;
+        Some((Index::new(entry.version, idx), data))
     }
 }
 
 impl<T> VecList<T> {
+    /// create a list with room for `capacity` entries whose keys within grouping `g` are less
+    /// than `sizes[g]`
+    pub(crate) fn new(capacity: usize, sizes: [usize; GROUPS]) -> Self {
+        let mut offsets = [0; GROUPS];
+        let mut num_chains = 1;
+        for (offset, size) in offsets.iter_mut().zip(sizes.iter()) {
+            *offset = num_chains;
+            num_chains += size;
+        }
+
+        Self {
+            version: 0,
+            capacity,
+            storage: Vec::new(),
+            free_stack: VecDeque::new(),
+            offsets,
+            sizes,
+            chains: vec![Chain::default(); num_chains],
+        }
+    }
+
+    /// iterate over all entries in insertion order
+    #[cfg(test)]
     pub(crate) fn iter(&self) -> ListIterator<T> {
         ListIterator {
             list: self,
-            current: self.state.map(|x| x.head),
+            slot: 0,
+            cursors: [self.chains[0].head, None, None],
         }
     }
 
-    pub(crate) fn new(capacity: usize) -> Self {
-        Self {
-            version: 0,
-            storage: Vec::with_capacity(capacity),
-            free_stack: VecDeque::with_capacity(capacity),
-            state: None,
+    /// iterate in insertion order over the entries whose key within `group` is one of `keys`
+    ///
+    /// at most [MAX_MERGED_KEYS] keys may be merged
+    pub(crate) fn iter_keys(&self, group: usize, keys: &[usize]) -> ListIterator<T> {
+        debug_assert!(keys.len() <= MAX_MERGED_KEYS);
+        let mut cursors = [None; MAX_MERGED_KEYS];
+        for (cursor, key) in cursors.iter_mut().zip(keys.iter()) {
+            if *key < self.sizes[group] {
+                *cursor = self.chains[self.offsets[group] + key].head;
+            }
+        }
+
+        ListIterator {
+            list: self,
+            slot: group + 1,
+            cursors,
         }
     }
 
     pub(crate) fn len(&self) -> usize {
-        self.state.map_or(0, |x| x.size)
+        self.chains[0].len
     }
 
     pub(crate) fn is_full(&self) -> bool {
-        self.len() == self.storage.capacity()
+        self.len() == self.capacity
     }
 
-    pub(crate) fn add(&mut self, item: T) -> Option<Index> {
-        if self.is_full() {
+    /// oldest entry whose key within `group` is `key`
+    pub(crate) fn first(&self, group: usize, key: usize) -> Option<Index> {
+        if key >= self.sizes[group] {
             return None;
         }
-
-        // do we have an entry already?
-        let index: Index = match self.state {
-            Some(current) => {
-                if let Some(idx) = self.free_stack.pop_front() {
-                    self.storage[idx] = Entry::last(self.version, item, Some(current.tail));
-
-                    // update the previous tail
-                    self.storage[current.tail].metadata.next = Some(idx);
-
-                    self.state = Some(current.append(idx));
-                    Index::new(self.version, idx)
-                } else {
-                    let idx = self.storage.len();
-                    self.storage
-                        .push(Entry::last(self.version, item, Some(current.tail)));
-
-                    // update the previous tail
-                    self.storage[current.tail].metadata.next = Some(idx);
-
-                    // set the new tail
-                    self.state = Some(current.append(idx));
-                    Index::new(self.version, idx)
-                }
-            }
-            None => {
-                if let Some(idx) = self.free_stack.pop_front() {
-                    self.storage[idx] = Entry::first(self.version, item);
-                    self.state = Some(State::single(idx));
-                    Index::new(self.version, idx)
-                } else {
-                    let idx = self.storage.len();
-                    self.storage.push(Entry::first(self.version, item));
-                    self.state = Some(State::single(idx));
-                    Index::new(self.version, idx)
-                }
-            }
-        };
-
-        self.version = self.version.wrapping_add(1);
-        Some(index)
+        let idx = self.chains[self.offsets[group] + key].head?;
+        Some(Index::new(self.storage[idx].version, idx))
     }
 
-    pub(crate) fn remove_first<F>(&mut self, predicate: F) -> Option<&T>
-    where
-        F: Fn(&T) -> bool,
-    {
-        let index = match self.find_first(&predicate) {
-            None => return None,
-            Some(x) => x,
-        };
-
-        if self.remove_at(index) {
-            return self.storage.get(index.value).map(|x| &x.data);
+    pub(crate) fn get(&self, index: Index) -> Option<&T> {
+        let entry = self.storage.get(index.value)?;
+        if entry.version != index.version {
+            return None;
         }
-
-        None
+        entry.data.as_ref()
     }
 
-    pub(crate) fn remove_all<F>(&mut self, mut predicate: F) -> usize
-    where
-        F: FnMut(&T) -> bool,
-    {
-        let mut count = 0;
-        let mut current = match self.state {
-            None => return count,
-            Some(x) => x.head,
+    /// add an entry at the end of the list and of the list of its key within each grouping
+    ///
+    /// returns `None` if the list is full or a key is out of range
+    pub(crate) fn add(&mut self, item: T, keys: [usize; GROUPS]) -> Option<Index> {
+        if self.is_full() || keys.iter().zip(self.sizes.iter()).any(|(k, s)| k >= s) {
+            return None;
+        }
+
+        let entry = Entry {
+            data: Some(item),
+            version: self.version,
+            keys,
+            links: [Link::default(); GROUPS + 1],
         };
 
-        loop {
-            let (next, entry_to_remove) = {
-                let entry = &self.storage[current];
-                let entry_to_delete = match predicate(&entry.data) {
-                    true => Some(entry.create_index(current)),
-                    false => None,
-                };
-                (entry.metadata.next, entry_to_delete)
-            };
-
-            if let Some(index) = entry_to_remove {
-                self.remove_at(index);
-                count += 1;
+        let idx = match self.free_stack.pop_front() {
+            Some(idx) => {
+                self.storage[idx] = entry;
+                idx
             }
-
-            match next {
-                Some(next) => {
-                    current = next;
-                }
-                None => return count,
+            None => {
+                self.storage.push(entry);
+                self.storage.len() - 1
             }
+        };
+
+        for (slot, chain) in self.chains_of(idx).iter() {
+            self.append(*chain, *slot, idx);
         }
-    }
 
-    fn find_first<F>(&self, predicate: &F) -> Option<Index>
-    where
-        F: Fn(&T) -> bool,
-    {
-        self.state
-            .and_then(|x| self.find_first_from(x.head, predicate))
+        let index = Index::new(self.version, idx);
+        self.version = self.version.wrapping_add(1);
+        Some(index)
     }
 
-    fn find_first_from<F>(&self, start: usize, predicate: &F) -> Option<Index>
-    where
-        F: Fn(&T) -> bool,
-    {
-        let mut current = start;
-
-        loop {
-            let entry = &self.storage[current];
-            if predicate(&entry.data) {
-                return Some(entry.create_index(current));
-            }
+    /// remove an entry from the list and from the list of each of its groups
+    pub(crate) fn remove_at(&mut self, index: Index) -> Option<T> {
+        self.get(index)?;
 
-            match entry.metadata.next {
-                Some(next) => {
-                    current = next;
-                }
-                None => return None,
-            }
+        for (slot, chain) in self.chains_of(index.value).iter() {
+            self.detach(*chain, *slot, index.value);
         }
-    }
-
-    pub(crate) fn remove_at(&mut self, index: Index) -> bool {
-        let current = match self.state {
-            None => return false,
-            Some(x) => x,
-        };
-
-        let metadata = match self.storage.get_mut(index.value) {
-            None => return false,
-            Some(x) => {
-                if x.is_free {
-                    // already free
-                    return false;
-                }
-                if x.metadata.version != index.version {
-                    // bad version
-                    return false;
-                }
-                x.is_free = true;
-                x.metadata
-            }
-        };
 
         // return this index to the free list
         self.free_stack.push_back(index.value);
+        self.storage[index.value].data.take()
+    }
 
-        let new_head = if index.value == current.head {
-            metadata.next // new head
-        } else {
-            Some(current.head) // same head
-        };
+    /// (slot, chain) of each list the entry belongs to
+    fn chains_of(&self, idx: usize) -> [(usize, usize); GROUPS + 1] {
+        let keys = self.storage[idx].keys;
+        let mut chains = [(0, 0); GROUPS + 1];
+        for (group, chain) in chains.iter_mut().skip(1).enumerate() {
+            *chain = (group + 1, self.offsets[group] + keys[group]);
+        }
+        chains
+    }
 
-        let new_tail = if index.value == current.tail {
-            metadata.prev // new tail
-        } else {
-            Some(current.tail) // same tail
+    fn append(&mut self, chain: usize, slot: usize, idx: usize) {
+        let tail = self.chains[chain].tail;
+        self.storage[idx].links[slot] = Link {
+            prev: tail,
+            next: None,
         };
-
-        if let Some(prev) = metadata.prev {
-            self.storage[prev].metadata.next = metadata.next;
+        match tail {
+            Some(tail) => self.storage[tail].links[slot].next = Some(idx),
+            None => self.chains[chain].head = Some(idx),
         }
+        self.chains[chain].tail = Some(idx);
+        self.chains[chain].len += 1;
+    }
 
-        if let Some(next) = metadata.next {
-            self.storage[next].metadata.prev = metadata.prev;
+    fn detach(&mut self, chain: usize, slot: usize, idx: usize) {
+        let link = self.storage[idx].links[slot];
+        match link.prev {
+            Some(prev) => self.storage[prev].links[slot].next = link.next,
+            None => self.chains[chain].head = link.next,
         }
-
-        self.state = State::from(current.size - 1, new_head, new_tail);
-        true
+        match link.next {
+            Some(next) => self.storage[next].links[slot].prev = link.prev,
+            None => self.chains[chain].tail = link.prev,
+        }
+        self.chains[chain].len -= 1;
     }
 }
 
@@ -325,59 +255,90 @@ impl<T> VecList<T> {
 mod tests {
     use super::*;
 
+    fn values<'a>(iter: ListIterator<'a, &'static str>) -> Vec<&'static str> {
+        iter.map(|(_, x)| *x).collect()
+    }
+
     #[test]
     fn cannot_add_entries_past_capacity() {
-        let mut list = VecList::new(2);
-        assert_eq!(list.add("hello"), Some(Index::new(0, 0)));
-        assert_eq!(list.add("world"), Some(Index::new(1, 1)));
+        let mut list = VecList::new(2, [1, 1]);
+        assert_eq!(list.add("hello", [0, 0]), Some(Index::new(0, 0)));
+        assert_eq!(list.add("world", [0, 0]), Some(Index::new(1, 1)));
         assert_eq!(list.len(), 2);
-        assert_eq!(list.add("NOPE"), None);
+        assert_eq!(list.add("NOPE", [0, 0]), None);
         assert_eq!(list.len(), 2);
     }
 
     #[test]
-    fn can_remove_first_entry_matching_predicate() {
-        let mut list = VecList::new(2);
+    fn storage_grows_with_the_number_of_entries() {
+        let mut list = VecList::new(1000, [1, 1]);
+        assert_eq!(list.storage.capacity(), 0);
+        let index = list.add("hello", [0, 0]).unwrap();
+        list.remove_at(index).unwrap();
+        list.add("world", [0, 0]).unwrap();
+        // the free entry is reused
+        assert_eq!(list.storage.len(), 1);
+    }
+
+    #[test]
+    fn cannot_add_entries_with_keys_out_of_range() {
+        let mut list = VecList::new(2, [1, 2]);
+        assert_eq!(list.add("hello", [0, 2]), None);
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn can_remove_first_entry_of_group() {
+        let mut list = VecList::new(2, [2, 1]);
 
         // run this a few times to ensure the data structure
         // isn't put into a bad state
         for _ in 0..3 {
-            assert_eq!(list.add("hello").map(|x| x.value), Some(0));
-            assert_eq!(list.add("world").map(|x| x.value), Some(1));
-            assert_eq!(list.remove_first(|x| x == &"test"), None);
+            list.add("hello", [0, 0]).unwrap();
+            list.add("world", [1, 0]).unwrap();
             assert_eq!(list.len(), 2);
-            assert_eq!(list.remove_first(|x| x == &"hello"), Some(&"hello"));
+            let first = list.first(0, 0).unwrap();
+            assert_eq!(list.remove_at(first), Some("hello"));
+            assert_eq!(list.first(0, 0), None);
             assert_eq!(list.len(), 1);
-            assert_eq!(list.remove_first(|x| x == &"world"), Some(&"world"));
+            let first = list.first(1, 0).unwrap();
+            assert_eq!(list.remove_at(first), Some("world"));
             assert_eq!(list.len(), 0);
+            assert_eq!(values(list.iter()), Vec::<&str>::new());
         }
     }
 
     #[test]
     fn can_add_after_remove() {
-        let mut list = VecList::new(3);
-        assert_eq!(list.add("hello"), Some(Index::new(0, 0)));
-        assert_eq!(list.add("my"), Some(Index::new(1, 1)));
-        assert_eq!(list.add("friends"), Some(Index::new(2, 2)));
-
-        assert!(list.remove_at(Index::new(1, 1)));
-        assert_eq!(list.add("yolo"), Some(Index::new(3, 1)));
+        let mut list = VecList::new(3, [1, 1]);
+        assert_eq!(list.add("hello", [0, 0]), Some(Index::new(0, 0)));
+        assert_eq!(list.add("my", [0, 0]), Some(Index::new(1, 1)));
+        assert_eq!(list.add("friends", [0, 0]), Some(Index::new(2, 2)));
+
+        assert_eq!(list.remove_at(Index::new(1, 1)), Some("my"));
+        assert_eq!(list.add("yolo", [0, 0]), Some(Index::new(3, 1)));
+        assert_eq!(values(list.iter()), vec!["hello", "friends", "yolo"]);
     }
 
     #[test]
-    fn can_remove_with_bad_version() {
-        let mut list = VecList::new(3);
-        let index = list.add("hello").unwrap();
-        assert!(!list.remove_at(Index::new(index.version + 1, index.value)));
+    fn cannot_remove_with_bad_version() {
+        let mut list = VecList::new(3, [1, 1]);
+        let index = list.add("hello", [0, 0]).unwrap();
+        assert_eq!(
+            list.remove_at(Index::new(index.version + 1, index.value)),
+            None
+        );
+        assert_eq!(list.remove_at(index), Some("hello"));
+        assert_eq!(list.remove_at(index), None);
     }
 
     #[test]
     fn can_iterate_over_values() {
-        let mut list = VecList::new(3);
+        let mut list = VecList::new(3, [1, 1]);
 
-        let index_a = list.add("A").unwrap();
-        let index_b = list.add("B").unwrap();
-        let index_c = list.add("C").unwrap();
+        let index_a = list.add("A", [0, 0]).unwrap();
+        let index_b = list.add("B", [0, 0]).unwrap();
+        let index_c = list.add("C", [0, 0]).unwrap();
 
         let mut iter = list.iter();
         assert_eq!(iter.next(), Some((index_a, &"A")));
@@ -385,4 +346,22 @@ mod tests {
         assert_eq!(iter.next(), Some((index_c, &"C")));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn iterates_over_groups_in_insertion_order() {
+        let mut list = VecList::new(6, [3, 2]);
+
+        list.add("A", [0, 0]).unwrap();
+        let b = list.add("B", [2, 1]).unwrap();
+        list.add("C", [1, 0]).unwrap();
+        list.add("D", [0, 1]).unwrap();
+        list.add("E", [2, 0]).unwrap();
+        list.remove_at(b).unwrap();
+        list.add("F", [2, 1]).unwrap();
+
+        assert_eq!(values(list.iter_keys(0, &[0])), vec!["A", "D"]);
+        assert_eq!(values(list.iter_keys(0, &[0, 2])), vec!["A", "D", "E", "F"]);
+        assert_eq!(values(list.iter_keys(1, &[1])), vec!["D", "F"]);
+        assert_eq!(values(list.iter()), vec!["A", "C", "D", "E", "F"]);
+    }
 }
@@ -309,3 +309,23 @@ impl EventVariation<Box<[u8]>> for EventOctetStringVariation {
         (111, event.len() as u8)
     }
 }
+
+impl EventVariation<UnsignedInteger> for EventUnsignedIntegerVariation {
+    fn write(
+        &self,
+        cursor: &mut WriteCursor,
+        event: &UnsignedInteger,
+        index: u16,
+        cto: Time,
+    ) -> Result<Continue, WriteError> {
+        write_fixed_size::<Group102Var1, UnsignedInteger>(cursor, event, index, cto)
+    }
+
+    fn wrap(&self) -> HeaderType {
+        HeaderType::UnsignedInteger(*self)
+    }
+
+    fn get_group_var(&self, _event: &UnsignedInteger) -> (u8, u8) {
+        (102, 1)
+    }
+}
@@ -45,6 +45,7 @@ pub(crate) enum HeaderType {
     Analog(EventAnalogVariation),
     AnalogOutputStatus(EventAnalogOutputStatusVariation),
     OctetString(EventOctetStringVariation),
+    UnsignedInteger(EventUnsignedIntegerVariation),
 }
 
 #[derive(Copy, Clone)]
@@ -321,6 +322,21 @@ impl Writable for AnalogOutputStatus {
     }
 }
 
+impl Writable for UnsignedInteger {
+    type EventVariation = EventUnsignedIntegerVariation;
+
+    fn get_header_variation(&self, header: &HeaderType) -> Option<Self::EventVariation> {
+        match header {
+            HeaderType::UnsignedInteger(var) => Some(*var),
+            _ => None,
+        }
+    }
+
+    fn get_time(&self) -> Option<Time> {
+        None
+    }
+}
+
 impl Writable for Box<[u8]> {
     type EventVariation = EventOctetStringVariation;
 
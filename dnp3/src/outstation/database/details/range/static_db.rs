@@ -1,15 +1,18 @@
-use std::collections::{BTreeMap, Bound, VecDeque};
+use std::collections::{btree_map, BTreeMap, Bound, VecDeque};
 use std::ops::RangeBounds;
 
 use crate::app::measurement::*;
-use crate::app::Iin2;
+use crate::app::{AttrList, AttrValue, Attribute, Iin2};
 use crate::outstation::config::OutstationConfig;
 use crate::outstation::database::config::*;
 use crate::outstation::database::details::event::buffer::Insertable;
 use crate::outstation::database::details::range::traits::StaticVariation;
 use crate::outstation::database::details::range::writer::RangeWriter;
 use crate::outstation::database::read::StaticReadHeader;
-use crate::outstation::database::{ClassZeroConfig, EventClass, EventMode, UpdateOptions};
+use crate::outstation::database::{
+    ClassZeroConfig, EventClass, EventMode, PointMetadata, PointStorage, StorageConfig,
+    UpdateOptions,
+};
 use crate::util::cursor::{WriteCursor, WriteError};
 
 pub(crate) trait EventDetector<T>
@@ -22,9 +25,13 @@ where
 pub(crate) trait Updatable: Insertable + Clone + Default {
     type StaticVariation: StaticVariation<Self>;
     type Detector: EventDetector<Self>;
+    // group of the static objects, reported in the device attributes of the metadata
+    const STATIC_GROUP: u8;
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self>;
     fn get_mut_map(maps: &mut StaticDatabase) -> &mut PointMap<Self>;
-    fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange;
+    fn get_selected(selection: &StaticSelection) -> &Vec<SelectedPoint<Self>>;
+    fn get_mut_selected(selection: &mut StaticSelection) -> &mut Vec<SelectedPoint<Self>>;
+    fn wrap(variation: Option<Self::StaticVariation>) -> SpecificVariation;
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool;
 }
 
@@ -49,18 +56,14 @@ impl RangeBounds<u16> for IndexRange {
     }
 }
 
+/// values of a READ header stored in `start..end` of the selected values of its type
 #[derive(Copy, Clone)]
-pub(crate) struct VariationRange {
-    range: IndexRange,
+struct Selection {
+    start: usize,
+    end: usize,
     variation: SpecificVariation,
 }
 
-impl VariationRange {
-    fn new(range: IndexRange, variation: SpecificVariation) -> Self {
-        Self { range, variation }
-    }
-}
-
 #[derive(Copy, Clone)]
 pub(crate) enum SpecificVariation {
     Binary(Option<StaticBinaryVariation>),
@@ -71,28 +74,26 @@ pub(crate) enum SpecificVariation {
     Analog(Option<StaticAnalogVariation>),
     AnalogOutputStatus(Option<StaticAnalogOutputStatusVariation>),
     OctetString,
-}
-
-impl SpecificVariation {
-    fn with(self, range: IndexRange) -> VariationRange {
-        VariationRange::new(range, self)
-    }
+    UnsignedInteger,
+    Attribute,
 }
 
 struct SelectionQueue {
-    queue: VecDeque<VariationRange>,
+    max_selections: usize,
+    queue: VecDeque<Selection>,
     capacity_exceeded: usize,
 }
 
 impl SelectionQueue {
     fn new(max_selections: u16) -> Self {
         Self {
-            queue: VecDeque::with_capacity(max_selections as usize),
+            max_selections: max_selections as usize,
+            queue: VecDeque::new(),
             capacity_exceeded: 0,
         }
     }
 
-    fn peek(&self) -> Option<VariationRange> {
+    fn peek(&self) -> Option<Selection> {
         self.queue.front().copied()
     }
 
@@ -100,21 +101,21 @@ impl SelectionQueue {
         self.queue.pop_front();
     }
 
-    fn update_front(&mut self, range: VariationRange) -> bool {
+    fn update_front(&mut self, selection: Selection) -> bool {
         if let Some(front) = self.queue.front_mut() {
-            *front = range;
+            *front = selection;
             true
         } else {
             false
         }
     }
 
-    fn push_back(&mut self, range: VariationRange) -> bool {
-        if self.queue.len() == self.queue.capacity() {
+    fn push_back(&mut self, selection: Selection) -> bool {
+        if self.queue.len() >= self.max_selections {
             self.capacity_exceeded += 1;
             return false;
         }
-        self.queue.push_back(range);
+        self.queue.push_back(selection);
         true
     }
 
@@ -122,6 +123,11 @@ impl SelectionQueue {
         self.capacity_exceeded = 0;
         self.queue.clear();
     }
+
+    fn release(&mut self) {
+        self.capacity_exceeded = 0;
+        self.queue = VecDeque::new();
+    }
 }
 
 pub(crate) struct PointConfig<T>
@@ -159,12 +165,14 @@ where
 {
     // current value
     current: T,
-    // value that is frozen during READ requests to be reported
-    selected: T,
     // last value that produced an event
     last_event: T,
     // configuration
     config: PointConfig<T>,
+    // optional descriptive information
+    metadata: Option<PointMetadata>,
+    // false if the point is omitted from class 0 READ requests
+    class_zero: bool,
 }
 
 impl<T> Point<T>
@@ -174,9 +182,10 @@ where
     pub(crate) fn new(config: PointConfig<T>) -> Self {
         Self {
             current: T::default(),
-            selected: T::default(),
             last_event: T::default(),
             config,
+            metadata: None,
+            class_zero: true,
         }
     }
 }
@@ -185,49 +194,125 @@ pub(crate) struct PointMap<T>
 where
     T: Updatable,
 {
-    inner: BTreeMap<u16, Point<T>>,
+    inner: Storage<T>,
+}
+
+enum Storage<T>
+where
+    T: Updatable,
+{
+    Sparse(BTreeMap<u16, Point<T>>),
+    // indexed by point index, trailing empty slots are never kept
+    Dense(Vec<Option<Point<T>>>),
+}
+
+/// iterator over the points of a map within an index range in ascending order
+enum PointIter<'a, T>
+where
+    T: Updatable,
+{
+    Sparse(btree_map::Range<'a, u16, Point<T>>),
+    Dense(
+        u16,
+        std::iter::Enumerate<std::slice::Iter<'a, Option<Point<T>>>>,
+    ),
+}
+
+impl<'a, T> Iterator for PointIter<'a, T>
+where
+    T: Updatable,
+{
+    type Item = (u16, &'a Point<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            PointIter::Sparse(iter) => iter.next().map(|(index, point)| (*index, point)),
+            PointIter::Dense(start, iter) => {
+                let start = *start;
+                iter.find_map(|(offset, point)| {
+                    point.as_ref().map(|point| (start + offset as u16, point))
+                })
+            }
+        }
+    }
 }
 
 impl<T> PointMap<T>
 where
     T: Updatable,
 {
-    fn empty() -> Self {
-        Self {
-            inner: BTreeMap::new(),
+    fn new(storage: PointStorage) -> Self {
+        let inner = match storage {
+            PointStorage::Sparse => Storage::Sparse(BTreeMap::new()),
+            PointStorage::Dense => Storage::Dense(Vec::new()),
+        };
+        Self { inner }
+    }
+
+    fn contains(&self, index: u16) -> bool {
+        self.get(index).is_some()
+    }
+
+    fn insert(&mut self, index: u16, point: Point<T>) {
+        match &mut self.inner {
+            Storage::Sparse(map) => {
+                map.insert(index, point);
+            }
+            Storage::Dense(vec) => {
+                let slot = index as usize;
+                if slot >= vec.len() {
+                    vec.resize_with(slot + 1, || None);
+                }
+                vec[slot] = Some(point);
+            }
         }
     }
 
-    fn get_mut(&mut self, index: u16) -> Option<&mut Point<T>> {
-        self.inner.get_mut(&index)
+    fn remove(&mut self, index: u16) -> bool {
+        match &mut self.inner {
+            Storage::Sparse(map) => map.remove(&index).is_some(),
+            Storage::Dense(vec) => {
+                let removed = vec.get_mut(index as usize).and_then(|x| x.take()).is_some();
+                while let Some(None) = vec.last() {
+                    vec.pop();
+                }
+                removed
+            }
+        }
     }
 
-    fn select_all(&mut self) -> Option<VariationRange> {
-        self.select_all_with_variation(None)
+    fn get(&self, index: u16) -> Option<&Point<T>> {
+        match &self.inner {
+            Storage::Sparse(map) => map.get(&index),
+            Storage::Dense(vec) => vec.get(index as usize).and_then(|x| x.as_ref()),
+        }
     }
 
-    fn select_all_with_variation(
-        &mut self,
-        variation: Option<T::StaticVariation>,
-    ) -> Option<VariationRange> {
-        self.inner
-            .values_mut()
-            .for_each(|x| x.selected = x.current.clone());
+    fn get_mut(&mut self, index: u16) -> Option<&mut Point<T>> {
+        match &mut self.inner {
+            Storage::Sparse(map) => map.get_mut(&index),
+            Storage::Dense(vec) => vec.get_mut(index as usize).and_then(|x| x.as_mut()),
+        }
+    }
+
+    fn range(&self, range: IndexRange) -> PointIter<T> {
+        match &self.inner {
+            Storage::Sparse(map) => PointIter::Sparse(map.range(range)),
+            Storage::Dense(vec) => {
+                let start = (range.start as usize).min(vec.len());
+                let stop = (range.stop as usize + 1).min(vec.len()).max(start);
+                PointIter::Dense(range.start, vec[start..stop].iter().enumerate())
+            }
+        }
+    }
 
-        /*
-          TODO - when this feature is stabilized we can use it here
-          TODO - #![feature(map_first_last)]
-          TODO - It'll be more efficient than finding first/last using iterators
-        */
-        let start = self.inner.iter().next().map(|(key, _)| *key)?;
-        let stop = self.inner.iter().next_back().map(|(key, _)| *key)?;
-        Some(T::wrap(IndexRange::new(start, stop), variation))
+    fn iter(&self) -> PointIter<T> {
+        self.range(IndexRange::new(0, u16::MAX))
     }
 }
 
 pub(crate) struct StaticDatabase {
     class_zero: ClassZeroConfig,
-    selected: SelectionQueue,
     // maps for the various types
     binary: PointMap<Binary>,
     double_bit_binary: PointMap<DoubleBitBinary>,
@@ -237,51 +322,32 @@ pub(crate) struct StaticDatabase {
     analog: PointMap<Analog>,
     analog_output_status: PointMap<AnalogOutputStatus>,
     octet_strings: PointMap<OctetString>,
+    unsigned_integers: PointMap<UnsignedInteger>,
+    // group and index of the point described by each attribute set
+    attribute_sets: BTreeMap<u16, (u8, u16)>,
 }
 
 impl Default for StaticDatabase {
     fn default() -> Self {
-        Self::new(None, ClassZeroConfig::default())
+        Self::new(ClassZeroConfig::default(), StorageConfig::default())
     }
 }
 
 impl StaticDatabase {
-    pub(crate) fn new(max_read_selection: Option<u16>, class_zero: ClassZeroConfig) -> Self {
-        // don't allow values smaller than the default
-        let max_read_selection = max_read_selection
-            .map(|x| x.max(OutstationConfig::DEFAULT_MAX_READ_REQUEST_HEADERS))
-            .unwrap_or(OutstationConfig::DEFAULT_MAX_READ_REQUEST_HEADERS);
-
+    pub(crate) fn new(class_zero: ClassZeroConfig, storage: StorageConfig) -> Self {
         Self {
             class_zero,
-            selected: SelectionQueue::new(max_read_selection),
-            binary: PointMap::empty(),
-            double_bit_binary: PointMap::empty(),
-            binary_output_status: PointMap::empty(),
-            counter: PointMap::empty(),
-            frozen_counter: PointMap::empty(),
-            analog: PointMap::empty(),
-            analog_output_status: PointMap::empty(),
-            octet_strings: PointMap::empty(),
-        }
-    }
-    /*
-       pub(crate) fn exceeded_capacity(&self) -> Option<usize> {
-           if self.selected.capacity_exceeded > 0 {
-               Some(self.selected.capacity_exceeded)
-           } else {
-               None
-           }
-       }
-    */
-
-    #[cfg(test)]
-    pub(crate) fn selection_capacity(&self) -> usize {
-        self.selected.queue.capacity()
-    }
-
-    pub(crate) fn reset(&mut self) {
-        self.selected.reset();
+            binary: PointMap::new(storage.binary),
+            double_bit_binary: PointMap::new(storage.double_bit_binary),
+            binary_output_status: PointMap::new(storage.binary_output_status),
+            counter: PointMap::new(storage.counter),
+            frozen_counter: PointMap::new(storage.frozen_counter),
+            analog: PointMap::new(storage.analog),
+            analog_output_status: PointMap::new(storage.analog_output_status),
+            octet_strings: PointMap::new(storage.octet_strings),
+            unsigned_integers: PointMap::new(storage.unsigned_integers),
+            attribute_sets: BTreeMap::new(),
+        }
     }
 
     pub(crate) fn add<T>(&mut self, index: u16, config: PointConfig<T>) -> bool
@@ -290,11 +356,11 @@ impl StaticDatabase {
     {
         let map = self.get_mut_map::<T>();
 
-        if map.inner.contains_key(&index) {
+        if map.contains(index) {
             return false;
         }
 
-        map.inner.insert(index, Point::new(config));
+        map.insert(index, Point::new(config));
 
         true
     }
@@ -303,7 +369,13 @@ impl StaticDatabase {
     where
         T: Updatable,
     {
-        self.get_mut_map::<T>().inner.remove(&index).is_some()
+        if let Some(set) = self
+            .point_metadata::<T>(index)
+            .and_then(|metadata| metadata.attribute_set)
+        {
+            self.attribute_sets.remove(&set);
+        }
+        self.get_mut_map::<T>().remove(index)
     }
 
     pub(crate) fn get<T>(&self, index: u16) -> Option<T>
@@ -311,11 +383,102 @@ impl StaticDatabase {
         T: Updatable,
     {
         self.get_map::<T>()
-            .inner
-            .get(&index)
+            .get(index)
             .map(|point| point.current.clone())
     }
 
+    pub(crate) fn set_metadata<T>(&mut self, index: u16, metadata: Option<PointMetadata>) -> bool
+    where
+        T: Updatable,
+    {
+        let set = metadata.as_ref().and_then(|x| x.attribute_set);
+        if let Some(set) = set {
+            let private_sets = PointMetadata::MIN_ATTRIBUTE_SET..=PointMetadata::MAX_ATTRIBUTE_SET;
+            if !private_sets.contains(&set) {
+                return false;
+            }
+            // each attribute set describes a single point
+            if self
+                .attribute_sets
+                .get(&set)
+                .map_or(false, |owner| *owner != (T::STATIC_GROUP, index))
+            {
+                return false;
+            }
+        }
+
+        let point = match self.get_mut_map::<T>().get_mut(index) {
+            None => return false,
+            Some(x) => x,
+        };
+        let previous = point.metadata.as_ref().and_then(|x| x.attribute_set);
+        point.metadata = metadata;
+
+        if let Some(previous) = previous {
+            self.attribute_sets.remove(&previous);
+        }
+        if let Some(set) = set {
+            self.attribute_sets.insert(set, (T::STATIC_GROUP, index));
+        }
+        true
+    }
+
+    pub(crate) fn get_metadata<T>(&self, index: u16) -> Option<PointMetadata>
+    where
+        T: Updatable,
+    {
+        self.get_map::<T>()
+            .get(index)
+            .and_then(|point| point.metadata.clone())
+    }
+
+    pub(crate) fn set_class_zero<T>(&mut self, index: u16, included: bool) -> bool
+    where
+        T: Updatable,
+    {
+        match self.get_mut_map::<T>().get_mut(index) {
+            None => false,
+            Some(x) => {
+                x.class_zero = included;
+                true
+            }
+        }
+    }
+
+    pub(crate) fn get_class_zero<T>(&self, index: u16) -> Option<bool>
+    where
+        T: Updatable,
+    {
+        self.get_map::<T>().get(index).map(|point| point.class_zero)
+    }
+
+    /// indices of the points of a type within a range
+    pub(crate) fn indices<T>(&self, range: IndexRange) -> Vec<u16>
+    where
+        T: Updatable,
+    {
+        self.get_map::<T>()
+            .range(range)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub(crate) fn find_by_name<T>(&self, name: &str) -> Option<u16>
+    where
+        T: Updatable,
+    {
+        self.get_map::<T>()
+            .iter()
+            .find(|(_, point)| {
+                point
+                    .metadata
+                    .as_ref()
+                    .map(|metadata| metadata.name.as_str())
+                    == Some(name)
+            })
+            .map(|(index, _)| index)
+    }
+
     pub(crate) fn update<T>(
         &mut self,
         value: &T,
@@ -354,140 +517,121 @@ impl StaticDatabase {
         }
     }
 
-    pub(crate) fn write(&mut self, cursor: &mut WriteCursor) -> Result<(), WriteError> {
-        while let Some(range) = self.selected.peek() {
-            match self.write_range(cursor, range) {
-                // done with this header
-                Ok(()) => {
-                    self.selected.pop();
-                }
-                // ran out of space
-                Err(s) => {
-                    // possibly wrote some data though, so update the selection
-                    self.selected.update_front(s);
-                    return Err(WriteError);
-                }
-            };
-        }
-
-        Ok(())
-    }
-
-    fn write_range(
-        &mut self,
-        cursor: &mut WriteCursor,
-        range: VariationRange,
-    ) -> Result<(), VariationRange> {
-        match range.variation {
-            SpecificVariation::Binary(var) => {
-                self.write_typed_range::<Binary>(cursor, range.range, var)
-            }
-            SpecificVariation::DoubleBitBinary(var) => {
-                self.write_typed_range::<DoubleBitBinary>(cursor, range.range, var)
-            }
-            SpecificVariation::BinaryOutputStatus(var) => {
-                self.write_typed_range::<BinaryOutputStatus>(cursor, range.range, var)
-            }
-            SpecificVariation::Counter(var) => {
-                self.write_typed_range::<Counter>(cursor, range.range, var)
-            }
-            SpecificVariation::FrozenCounter(var) => {
-                self.write_typed_range::<FrozenCounter>(cursor, range.range, var)
-            }
-            SpecificVariation::Analog(var) => {
-                self.write_typed_range::<Analog>(cursor, range.range, var)
-            }
-            SpecificVariation::AnalogOutputStatus(var) => {
-                self.write_typed_range::<AnalogOutputStatus>(cursor, range.range, var)
-            }
-            SpecificVariation::OctetString => {
-                self.write_typed_range::<OctetString>(cursor, range.range, None)
-            }
-        }
-    }
-
-    fn write_typed_range<T>(
-        &mut self,
-        cursor: &mut WriteCursor,
-        range: IndexRange,
-        variation: Option<T::StaticVariation>,
-    ) -> Result<(), VariationRange>
-    where
-        T: Updatable,
-    {
-        let mut writer = RangeWriter::new();
-        for (index, item) in self.get_map::<T>().inner.range(range) {
-            // first determine what variation should be written
-            let info = variation
-                .unwrap_or(item.config.s_var)
-                .promote(&item.selected)
-                .get_write_info(&item.selected);
-
-            if writer.write(cursor, *index, &item.selected, info).is_err() {
-                // ran out of space, tell calling code to resume at this index
-                return Err(T::wrap(IndexRange::new(*index, range.stop), variation));
-            }
-        }
-
-        Ok(())
-    }
-
-    pub(crate) fn select(&mut self, variation: StaticReadHeader) -> Iin2 {
+    /// copy the current values of the points requested by a READ header into the selection
+    pub(crate) fn select(
+        &self,
+        variation: StaticReadHeader,
+        selection: &mut StaticSelection,
+    ) -> Iin2 {
         match variation {
-            StaticReadHeader::Class0 => self.select_class_zero(),
+            StaticReadHeader::Class0 => self.select_class_zero(selection),
             StaticReadHeader::Binary(variation, range) => {
-                self.select_by_type::<Binary>(variation, range)
+                self.select_by_type::<Binary>(variation, range, selection)
             }
             StaticReadHeader::DoubleBitBinary(variation, range) => {
-                self.select_by_type::<DoubleBitBinary>(variation, range)
+                self.select_by_type::<DoubleBitBinary>(variation, range, selection)
             }
             StaticReadHeader::BinaryOutputStatus(variation, range) => {
-                self.select_by_type::<BinaryOutputStatus>(variation, range)
+                self.select_by_type::<BinaryOutputStatus>(variation, range, selection)
             }
             StaticReadHeader::Counter(variation, range) => {
-                self.select_by_type::<Counter>(variation, range)
+                self.select_by_type::<Counter>(variation, range, selection)
             }
             StaticReadHeader::FrozenCounter(variation, range) => {
-                self.select_by_type::<FrozenCounter>(variation, range)
+                self.select_by_type::<FrozenCounter>(variation, range, selection)
             }
             StaticReadHeader::Analog(variation, range) => {
-                self.select_by_type::<Analog>(variation, range)
+                self.select_by_type::<Analog>(variation, range, selection)
             }
             StaticReadHeader::AnalogOutputStatus(variation, range) => {
-                self.select_by_type::<AnalogOutputStatus>(variation, range)
+                self.select_by_type::<AnalogOutputStatus>(variation, range, selection)
+            }
+            StaticReadHeader::OctetString(range) => {
+                self.select_by_type::<OctetString>(None, range, selection)
+            }
+            StaticReadHeader::UnsignedInteger(range) => {
+                self.select_by_type::<UnsignedInteger>(None, range, selection)
+            }
+            StaticReadHeader::Attributes(variation, range) => {
+                self.select_attributes(variation, range, selection)
             }
-            StaticReadHeader::OctetString(range) => self.select_by_type::<OctetString>(None, range),
         }
     }
 
+    fn select_attributes(
+        &self,
+        variation: u8,
+        range: IndexRange,
+        selection: &mut StaticSelection,
+    ) -> Iin2 {
+        // sets are visited in ascending order
+        let exposed: Vec<ExposedMetadata> = self
+            .attribute_sets
+            .range(range)
+            .filter_map(|(set, (group, index))| self.exposed_metadata(*set, *group, *index))
+            .collect();
+
+        // neither the standard set nor any other set without metadata is supported
+        if exposed.is_empty() {
+            return Iin2::OBJECT_UNKNOWN;
+        }
+
+        selection.push_attributes(exposed.iter().flat_map(|x| x.select(variation)))
+    }
+
+    /// metadata of the point described by an attribute set
+    fn exposed_metadata(&self, set: u16, group: u8, index: u16) -> Option<ExposedMetadata> {
+        let metadata = match group {
+            Binary::STATIC_GROUP => self.point_metadata::<Binary>(index),
+            DoubleBitBinary::STATIC_GROUP => self.point_metadata::<DoubleBitBinary>(index),
+            BinaryOutputStatus::STATIC_GROUP => self.point_metadata::<BinaryOutputStatus>(index),
+            Counter::STATIC_GROUP => self.point_metadata::<Counter>(index),
+            FrozenCounter::STATIC_GROUP => self.point_metadata::<FrozenCounter>(index),
+            Analog::STATIC_GROUP => self.point_metadata::<Analog>(index),
+            AnalogOutputStatus::STATIC_GROUP => self.point_metadata::<AnalogOutputStatus>(index),
+            OctetString::STATIC_GROUP => self.point_metadata::<OctetString>(index),
+            UnsignedInteger::STATIC_GROUP => self.point_metadata::<UnsignedInteger>(index),
+            _ => None,
+        }?;
+        Some(ExposedMetadata {
+            set,
+            group,
+            index,
+            metadata,
+        })
+    }
+
+    fn point_metadata<T>(&self, index: u16) -> Option<&PointMetadata>
+    where
+        T: Updatable,
+    {
+        self.get_map::<T>()
+            .get(index)
+            .and_then(|point| point.metadata.as_ref())
+    }
+
     fn select_by_type<T>(
-        &mut self,
+        &self,
         variation: Option<T::StaticVariation>,
         range: Option<IndexRange>,
+        selection: &mut StaticSelection,
     ) -> Iin2
     where
         T: Updatable,
     {
+        let map = self.get_map::<T>();
         match range {
-            Some(range) => self.push_selection(T::wrap(range, variation)),
+            Some(range) => selection.push(map.range(range), variation),
             None => {
-                if let Some(x) = T::get_mut_map(self).select_all_with_variation(variation) {
-                    self.push_selection(x)
-                } else {
-                    Iin2::default()
+                // nothing to report when selecting all the points of an empty type
+                if map.iter().next().is_none() {
+                    return Iin2::default();
                 }
+                selection.push(map.iter(), variation)
             }
         }
     }
 
-    fn push_selection(&mut self, range: VariationRange) -> Iin2 {
-        if self.selected.push_back(range) {
-            Iin2::default()
-        } else {
-            Iin2::PARAMETER_ERROR
-        }
-    }
-
     fn get_map<T>(&self) -> &PointMap<T>
     where
         T: Updatable,
@@ -502,36 +646,381 @@ impl StaticDatabase {
         T::get_mut_map(self)
     }
 
-    fn select_class_zero_type<T>(&mut self) -> Iin2
+    fn select_class_zero_type<T>(&self, selection: &mut StaticSelection) -> Iin2
     where
         T: Updatable,
     {
-        if T::enabled_class_zero(&self.class_zero) {
-            let full_range = match T::get_mut_map(self).select_all() {
-                None => return Iin2::default(),
-                Some(x) => x,
-            };
+        if !T::enabled_class_zero(&self.class_zero) {
+            return Iin2::default();
+        }
+
+        let map = self.get_map::<T>();
+        let mut points = map.iter().filter(|(_, point)| point.class_zero).peekable();
+        // nothing to report if every point of the type is excluded
+        if points.peek().is_none() {
+            return Iin2::default();
+        }
+        selection.push(points, None)
+    }
+
+    fn select_class_zero(&self, selection: &mut StaticSelection) -> Iin2 {
+        self.select_class_zero_type::<Binary>(selection)
+            | self.select_class_zero_type::<DoubleBitBinary>(selection)
+            | self.select_class_zero_type::<BinaryOutputStatus>(selection)
+            | self.select_class_zero_type::<Counter>(selection)
+            | self.select_class_zero_type::<FrozenCounter>(selection)
+            | self.select_class_zero_type::<Analog>(selection)
+            | self.select_class_zero_type::<AnalogOutputStatus>(selection)
+            | self.select_class_zero_type::<OctetString>(selection)
+            | self.select_class_zero_type::<UnsignedInteger>(selection)
+    }
+}
+
+/// metadata of a point that has a device attribute set
+struct ExposedMetadata<'a> {
+    set: u16,
+    group: u8,
+    index: u16,
+    metadata: &'a PointMetadata,
+}
+
+impl ExposedMetadata<'_> {
+    const GROUP: u8 = 1;
+    const INDEX: u8 = 2;
+    const NAME: u8 = 3;
+    const UNITS: u8 = 4;
+    const MULTIPLIER: u8 = 5;
+    const OFFSET: u8 = 6;
+    const ALL: u8 = 254;
+    const LIST: u8 = 255;
+
+    // longest string such that an attribute fits in a response of the minimum size
+    const MAX_STRING_LENGTH: usize = 236;
+
+    fn attributes(&self) -> Vec<(u8, AttrData)> {
+        let mut attributes = vec![
+            (Self::GROUP, AttrData::UnsignedInt(self.group as u32)),
+            (Self::INDEX, AttrData::UnsignedInt(self.index as u32)),
+            (Self::NAME, Self::visible_string(&self.metadata.name)),
+        ];
+        if let Some(units) = &self.metadata.units {
+            attributes.push((Self::UNITS, Self::visible_string(units)));
+        }
+        if let Some(scaling) = &self.metadata.scaling {
+            attributes.push((
+                Self::MULTIPLIER,
+                AttrData::FloatingPoint(scaling.multiplier()),
+            ));
+            attributes.push((Self::OFFSET, AttrData::FloatingPoint(scaling.offset())));
+        }
+        attributes
+    }
 
-            if self.selected.push_back(full_range) {
-                Iin2::default()
-            } else {
-                // out of space for read headers
-                Iin2::PARAMETER_ERROR
+    fn select(&self, variation: u8) -> Vec<SelectedAttribute> {
+        let attributes = self.attributes();
+        match variation {
+            Self::ALL => attributes
+                .into_iter()
+                .map(|(variation, value)| self.selected(variation, value))
+                .collect(),
+            Self::LIST => {
+                // none of the attributes are writable
+                let list = attributes
+                    .iter()
+                    .flat_map(|(variation, _)| [*variation, 0])
+                    .collect();
+                vec![self.selected(Self::LIST, AttrData::AttrList(list))]
             }
+            _ => attributes
+                .into_iter()
+                .filter(|(x, _)| *x == variation)
+                .map(|(variation, value)| self.selected(variation, value))
+                .collect(),
+        }
+    }
+
+    fn selected(&self, variation: u8, value: AttrData) -> SelectedAttribute {
+        SelectedAttribute {
+            set: self.set,
+            variation,
+            value,
+        }
+    }
+
+    fn visible_string(value: &str) -> AttrData {
+        let mut end = value.len().min(Self::MAX_STRING_LENGTH);
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        AttrData::VisibleString(value[..end].to_string())
+    }
+}
+
+/// value of a device attribute copied from the metadata of a point
+enum AttrData {
+    UnsignedInt(u32),
+    VisibleString(String),
+    FloatingPoint(f64),
+    AttrList(Vec<u8>),
+}
+
+impl AttrData {
+    fn value(&self) -> AttrValue {
+        match self {
+            AttrData::UnsignedInt(x) => AttrValue::UnsignedInt(*x),
+            AttrData::VisibleString(x) => AttrValue::VisibleString(x),
+            AttrData::FloatingPoint(x) => AttrValue::FloatingPoint(*x),
+            AttrData::AttrList(x) => AttrValue::AttrList(AttrList::new(x)),
+        }
+    }
+}
+
+/// device attribute selected by a READ request
+struct SelectedAttribute {
+    set: u16,
+    variation: u8,
+    value: AttrData,
+}
+
+/// value of a point copied from the database when it was selected by a READ request
+pub(crate) struct SelectedPoint<T>
+where
+    T: Updatable,
+{
+    index: u16,
+    value: T,
+    // static variation configured for the point
+    variation: T::StaticVariation,
+}
+
+/// Static values selected by READ requests
+///
+/// Values are copied out of the database when they are selected, so responses are written from
+/// a consistent snapshot without holding the lock of the database while they are formatted.
+pub(crate) struct StaticSelection {
+    queue: SelectionQueue,
+    // values selected for each type, in the order of the headers that selected them
+    binary: Vec<SelectedPoint<Binary>>,
+    double_bit_binary: Vec<SelectedPoint<DoubleBitBinary>>,
+    binary_output_status: Vec<SelectedPoint<BinaryOutputStatus>>,
+    counter: Vec<SelectedPoint<Counter>>,
+    frozen_counter: Vec<SelectedPoint<FrozenCounter>>,
+    analog: Vec<SelectedPoint<Analog>>,
+    analog_output_status: Vec<SelectedPoint<AnalogOutputStatus>>,
+    octet_strings: Vec<SelectedPoint<OctetString>>,
+    unsigned_integers: Vec<SelectedPoint<UnsignedInteger>>,
+    attributes: Vec<SelectedAttribute>,
+}
+
+impl Default for StaticSelection {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl StaticSelection {
+    pub(crate) fn new(max_read_selection: Option<u16>) -> Self {
+        // don't allow values smaller than the default
+        let max_read_selection = max_read_selection
+            .map(|x| x.max(OutstationConfig::DEFAULT_MAX_READ_REQUEST_HEADERS))
+            .unwrap_or(OutstationConfig::DEFAULT_MAX_READ_REQUEST_HEADERS);
+
+        Self {
+            queue: SelectionQueue::new(max_read_selection),
+            binary: Vec::new(),
+            double_bit_binary: Vec::new(),
+            binary_output_status: Vec::new(),
+            counter: Vec::new(),
+            frozen_counter: Vec::new(),
+            analog: Vec::new(),
+            analog_output_status: Vec::new(),
+            octet_strings: Vec::new(),
+            unsigned_integers: Vec::new(),
+            attributes: Vec::new(),
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.queue.reset();
+        self.clear_values();
+    }
+
+    /// reset the selection and free the memory it retained to avoid allocating on each poll
+    pub(crate) fn release(&mut self) {
+        self.queue.release();
+        self.binary = Vec::new();
+        self.double_bit_binary = Vec::new();
+        self.binary_output_status = Vec::new();
+        self.counter = Vec::new();
+        self.frozen_counter = Vec::new();
+        self.analog = Vec::new();
+        self.analog_output_status = Vec::new();
+        self.octet_strings = Vec::new();
+        self.unsigned_integers = Vec::new();
+        self.attributes = Vec::new();
+    }
+
+    // the vectors keep their allocations so that subsequent polls don't allocate
+    fn clear_values(&mut self) {
+        self.binary.clear();
+        self.double_bit_binary.clear();
+        self.binary_output_status.clear();
+        self.counter.clear();
+        self.frozen_counter.clear();
+        self.analog.clear();
+        self.analog_output_status.clear();
+        self.octet_strings.clear();
+        self.unsigned_integers.clear();
+        self.attributes.clear();
+    }
+
+    fn push<'a, T, I>(&mut self, points: I, variation: Option<T::StaticVariation>) -> Iin2
+    where
+        T: Updatable + 'a,
+        I: Iterator<Item = (u16, &'a Point<T>)>,
+    {
+        let values = T::get_mut_selected(self);
+        let start = values.len();
+        values.extend(points.map(|(index, point)| SelectedPoint {
+            index,
+            value: point.current.clone(),
+            variation: point.config.s_var,
+        }));
+        let end = values.len();
+
+        let selection = Selection {
+            start,
+            end,
+            variation: T::wrap(variation),
+        };
+
+        if self.queue.push_back(selection) {
+            Iin2::default()
         } else {
+            // out of space for read headers
+            T::get_mut_selected(self).truncate(start);
+            Iin2::PARAMETER_ERROR
+        }
+    }
+
+    fn push_attributes<I>(&mut self, attributes: I) -> Iin2
+    where
+        I: Iterator<Item = SelectedAttribute>,
+    {
+        let start = self.attributes.len();
+        self.attributes.extend(attributes);
+        let end = self.attributes.len();
+
+        let selection = Selection {
+            start,
+            end,
+            variation: SpecificVariation::Attribute,
+        };
+
+        if self.queue.push_back(selection) {
             Iin2::default()
+        } else {
+            // out of space for read headers
+            self.attributes.truncate(start);
+            Iin2::PARAMETER_ERROR
+        }
+    }
+
+    pub(crate) fn write(&mut self, cursor: &mut WriteCursor) -> Result<(), WriteError> {
+        while let Some(selection) = self.queue.peek() {
+            match self.write_selection(cursor, selection) {
+                // done with this header
+                Ok(()) => {
+                    self.queue.pop();
+                }
+                // ran out of space
+                Err(start) => {
+                    // possibly wrote some data though, so update the selection
+                    self.queue.update_front(Selection { start, ..selection });
+                    return Err(WriteError);
+                }
+            };
         }
+
+        self.clear_values();
+        Ok(())
     }
 
-    fn select_class_zero(&mut self) -> Iin2 {
-        self.select_class_zero_type::<Binary>()
-            | self.select_class_zero_type::<DoubleBitBinary>()
-            | self.select_class_zero_type::<BinaryOutputStatus>()
-            | self.select_class_zero_type::<Counter>()
-            | self.select_class_zero_type::<FrozenCounter>()
-            | self.select_class_zero_type::<Analog>()
-            | self.select_class_zero_type::<AnalogOutputStatus>()
-            | self.select_class_zero_type::<OctetString>()
+    fn write_selection(&self, cursor: &mut WriteCursor, selection: Selection) -> Result<(), usize> {
+        match selection.variation {
+            SpecificVariation::Binary(var) => {
+                self.write_typed_selection::<Binary>(cursor, selection, var)
+            }
+            SpecificVariation::DoubleBitBinary(var) => {
+                self.write_typed_selection::<DoubleBitBinary>(cursor, selection, var)
+            }
+            SpecificVariation::BinaryOutputStatus(var) => {
+                self.write_typed_selection::<BinaryOutputStatus>(cursor, selection, var)
+            }
+            SpecificVariation::Counter(var) => {
+                self.write_typed_selection::<Counter>(cursor, selection, var)
+            }
+            SpecificVariation::FrozenCounter(var) => {
+                self.write_typed_selection::<FrozenCounter>(cursor, selection, var)
+            }
+            SpecificVariation::Analog(var) => {
+                self.write_typed_selection::<Analog>(cursor, selection, var)
+            }
+            SpecificVariation::AnalogOutputStatus(var) => {
+                self.write_typed_selection::<AnalogOutputStatus>(cursor, selection, var)
+            }
+            SpecificVariation::OctetString => {
+                self.write_typed_selection::<OctetString>(cursor, selection, None)
+            }
+            SpecificVariation::UnsignedInteger => {
+                self.write_typed_selection::<UnsignedInteger>(cursor, selection, None)
+            }
+            SpecificVariation::Attribute => self.write_attributes(cursor, selection),
+        }
+    }
+
+    fn write_attributes(
+        &self,
+        cursor: &mut WriteCursor,
+        selection: Selection,
+    ) -> Result<(), usize> {
+        let attributes = &self.attributes[selection.start..selection.end];
+        for (pos, item) in attributes.iter().enumerate() {
+            let attr = Attribute::new(item.set, item.variation, item.value.value());
+            if attr.write(cursor).is_err() {
+                // ran out of space, tell calling code to resume at this attribute
+                return Err(selection.start + pos);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_typed_selection<T>(
+        &self,
+        cursor: &mut WriteCursor,
+        selection: Selection,
+        variation: Option<T::StaticVariation>,
+    ) -> Result<(), usize>
+    where
+        T: Updatable,
+    {
+        let mut writer = RangeWriter::new();
+        let values = &T::get_selected(self)[selection.start..selection.end];
+        for (pos, item) in values.iter().enumerate() {
+            // first determine what variation should be written
+            let info = variation
+                .unwrap_or(item.variation)
+                .promote(&item.value)
+                .get_write_info(&item.value);
+
+            if writer.write(cursor, item.index, &item.value, info).is_err() {
+                // ran out of space, tell calling code to resume at this value
+                return Err(selection.start + pos);
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -545,6 +1034,8 @@ where
 
 pub(crate) struct OctetStringDetector;
 
+pub(crate) struct UnsignedIntegerDetector;
+
 impl<N> Deadband<N>
 where
     N: std::ops::Sub<N, Output = N> + PartialOrd<N>,
@@ -606,6 +1097,38 @@ impl HasValue<f64> for AnalogOutputStatus {
     }
 }
 
+/// types whose flags can be modified independently of their value
+pub(crate) trait HasFlags {
+    fn flags(&self) -> Flags;
+    fn set_flags(&mut self, flags: Flags);
+}
+
+macro_rules! impl_has_flags {
+    ($($name:ident),*) => {
+        $(
+            impl HasFlags for $name {
+                fn flags(&self) -> Flags {
+                    self.flags
+                }
+
+                fn set_flags(&mut self, flags: Flags) {
+                    self.flags = flags;
+                }
+            }
+        )*
+    };
+}
+
+impl_has_flags!(
+    Binary,
+    DoubleBitBinary,
+    BinaryOutputStatus,
+    Counter,
+    FrozenCounter,
+    Analog,
+    AnalogOutputStatus
+);
+
 impl<T, N> EventDetector<T> for Deadband<N>
 where
     T: Updatable + HasValue<N> + WireFlags,
@@ -626,9 +1149,16 @@ impl EventDetector<OctetString> for OctetStringDetector {
     }
 }
 
+impl EventDetector<UnsignedInteger> for UnsignedIntegerDetector {
+    fn is_event(&self, new: &UnsignedInteger, old: &UnsignedInteger) -> bool {
+        new.value != old.value
+    }
+}
+
 impl Updatable for Binary {
     type StaticVariation = StaticBinaryVariation;
     type Detector = FlagsDetector;
+    const STATIC_GROUP: u8 = 1;
 
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
         &maps.binary
@@ -638,8 +1168,16 @@ impl Updatable for Binary {
         &mut maps.binary
     }
 
-    fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
-        SpecificVariation::Binary(variation).with(range)
+    fn get_selected(selection: &StaticSelection) -> &Vec<SelectedPoint<Self>> {
+        &selection.binary
+    }
+
+    fn get_mut_selected(selection: &mut StaticSelection) -> &mut Vec<SelectedPoint<Self>> {
+        &mut selection.binary
+    }
+
+    fn wrap(variation: Option<Self::StaticVariation>) -> SpecificVariation {
+        SpecificVariation::Binary(variation)
     }
 
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
@@ -650,6 +1188,7 @@ impl Updatable for Binary {
 impl Updatable for DoubleBitBinary {
     type StaticVariation = StaticDoubleBitBinaryVariation;
     type Detector = FlagsDetector;
+    const STATIC_GROUP: u8 = 3;
 
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
         &maps.double_bit_binary
@@ -659,8 +1198,16 @@ impl Updatable for DoubleBitBinary {
         &mut maps.double_bit_binary
     }
 
-    fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
-        SpecificVariation::DoubleBitBinary(variation).with(range)
+    fn get_selected(selection: &StaticSelection) -> &Vec<SelectedPoint<Self>> {
+        &selection.double_bit_binary
+    }
+
+    fn get_mut_selected(selection: &mut StaticSelection) -> &mut Vec<SelectedPoint<Self>> {
+        &mut selection.double_bit_binary
+    }
+
+    fn wrap(variation: Option<Self::StaticVariation>) -> SpecificVariation {
+        SpecificVariation::DoubleBitBinary(variation)
     }
 
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
@@ -671,6 +1218,7 @@ impl Updatable for DoubleBitBinary {
 impl Updatable for BinaryOutputStatus {
     type StaticVariation = StaticBinaryOutputStatusVariation;
     type Detector = FlagsDetector;
+    const STATIC_GROUP: u8 = 10;
 
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
         &maps.binary_output_status
@@ -680,8 +1228,16 @@ impl Updatable for BinaryOutputStatus {
         &mut maps.binary_output_status
     }
 
-    fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
-        SpecificVariation::BinaryOutputStatus(variation).with(range)
+    fn get_selected(selection: &StaticSelection) -> &Vec<SelectedPoint<Self>> {
+        &selection.binary_output_status
+    }
+
+    fn get_mut_selected(selection: &mut StaticSelection) -> &mut Vec<SelectedPoint<Self>> {
+        &mut selection.binary_output_status
+    }
+
+    fn wrap(variation: Option<Self::StaticVariation>) -> SpecificVariation {
+        SpecificVariation::BinaryOutputStatus(variation)
     }
 
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
@@ -692,6 +1248,7 @@ impl Updatable for BinaryOutputStatus {
 impl Updatable for Counter {
     type StaticVariation = StaticCounterVariation;
     type Detector = Deadband<u32>;
+    const STATIC_GROUP: u8 = 20;
 
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
         &maps.counter
@@ -701,8 +1258,16 @@ impl Updatable for Counter {
         &mut maps.counter
     }
 
-    fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
-        SpecificVariation::Counter(variation).with(range)
+    fn get_selected(selection: &StaticSelection) -> &Vec<SelectedPoint<Self>> {
+        &selection.counter
+    }
+
+    fn get_mut_selected(selection: &mut StaticSelection) -> &mut Vec<SelectedPoint<Self>> {
+        &mut selection.counter
+    }
+
+    fn wrap(variation: Option<Self::StaticVariation>) -> SpecificVariation {
+        SpecificVariation::Counter(variation)
     }
 
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
@@ -713,6 +1278,7 @@ impl Updatable for Counter {
 impl Updatable for FrozenCounter {
     type StaticVariation = StaticFrozenCounterVariation;
     type Detector = Deadband<u32>;
+    const STATIC_GROUP: u8 = 21;
 
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
         &maps.frozen_counter
@@ -722,8 +1288,16 @@ impl Updatable for FrozenCounter {
         &mut maps.frozen_counter
     }
 
-    fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
-        SpecificVariation::FrozenCounter(variation).with(range)
+    fn get_selected(selection: &StaticSelection) -> &Vec<SelectedPoint<Self>> {
+        &selection.frozen_counter
+    }
+
+    fn get_mut_selected(selection: &mut StaticSelection) -> &mut Vec<SelectedPoint<Self>> {
+        &mut selection.frozen_counter
+    }
+
+    fn wrap(variation: Option<Self::StaticVariation>) -> SpecificVariation {
+        SpecificVariation::FrozenCounter(variation)
     }
 
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
@@ -734,6 +1308,7 @@ impl Updatable for FrozenCounter {
 impl Updatable for Analog {
     type StaticVariation = StaticAnalogVariation;
     type Detector = Deadband<f64>;
+    const STATIC_GROUP: u8 = 30;
 
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
         &maps.analog
@@ -743,8 +1318,16 @@ impl Updatable for Analog {
         &mut maps.analog
     }
 
-    fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
-        SpecificVariation::Analog(variation).with(range)
+    fn get_selected(selection: &StaticSelection) -> &Vec<SelectedPoint<Self>> {
+        &selection.analog
+    }
+
+    fn get_mut_selected(selection: &mut StaticSelection) -> &mut Vec<SelectedPoint<Self>> {
+        &mut selection.analog
+    }
+
+    fn wrap(variation: Option<Self::StaticVariation>) -> SpecificVariation {
+        SpecificVariation::Analog(variation)
     }
 
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
@@ -755,6 +1338,7 @@ impl Updatable for Analog {
 impl Updatable for AnalogOutputStatus {
     type StaticVariation = StaticAnalogOutputStatusVariation;
     type Detector = Deadband<f64>;
+    const STATIC_GROUP: u8 = 40;
 
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
         &maps.analog_output_status
@@ -764,8 +1348,16 @@ impl Updatable for AnalogOutputStatus {
         &mut maps.analog_output_status
     }
 
-    fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
-        SpecificVariation::AnalogOutputStatus(variation).with(range)
+    fn get_selected(selection: &StaticSelection) -> &Vec<SelectedPoint<Self>> {
+        &selection.analog_output_status
+    }
+
+    fn get_mut_selected(selection: &mut StaticSelection) -> &mut Vec<SelectedPoint<Self>> {
+        &mut selection.analog_output_status
+    }
+
+    fn wrap(variation: Option<Self::StaticVariation>) -> SpecificVariation {
+        SpecificVariation::AnalogOutputStatus(variation)
     }
 
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
@@ -776,6 +1368,7 @@ impl Updatable for AnalogOutputStatus {
 impl Updatable for OctetString {
     type StaticVariation = StaticOctetStringVariation;
     type Detector = OctetStringDetector;
+    const STATIC_GROUP: u8 = 110;
 
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
         &maps.octet_strings
@@ -785,8 +1378,16 @@ impl Updatable for OctetString {
         &mut maps.octet_strings
     }
 
-    fn wrap(range: IndexRange, _variation: Option<Self::StaticVariation>) -> VariationRange {
-        SpecificVariation::OctetString.with(range)
+    fn get_selected(selection: &StaticSelection) -> &Vec<SelectedPoint<Self>> {
+        &selection.octet_strings
+    }
+
+    fn get_mut_selected(selection: &mut StaticSelection) -> &mut Vec<SelectedPoint<Self>> {
+        &mut selection.octet_strings
+    }
+
+    fn wrap(_variation: Option<Self::StaticVariation>) -> SpecificVariation {
+        SpecificVariation::OctetString
     }
 
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
@@ -794,6 +1395,36 @@ impl Updatable for OctetString {
     }
 }
 
+impl Updatable for UnsignedInteger {
+    type StaticVariation = StaticUnsignedIntegerVariation;
+    type Detector = UnsignedIntegerDetector;
+    const STATIC_GROUP: u8 = 102;
+
+    fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
+        &maps.unsigned_integers
+    }
+
+    fn get_mut_map(maps: &mut StaticDatabase) -> &mut PointMap<Self> {
+        &mut maps.unsigned_integers
+    }
+
+    fn get_selected(selection: &StaticSelection) -> &Vec<SelectedPoint<Self>> {
+        &selection.unsigned_integers
+    }
+
+    fn get_mut_selected(selection: &mut StaticSelection) -> &mut Vec<SelectedPoint<Self>> {
+        &mut selection.unsigned_integers
+    }
+
+    fn wrap(_variation: Option<Self::StaticVariation>) -> SpecificVariation {
+        SpecificVariation::UnsignedInteger
+    }
+
+    fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
+        config.unsigned_integers
+    }
+}
+
 impl Default for Binary {
     fn default() -> Self {
         Self::new(false, Flags::RESTART, Time::not_synchronized(0))
@@ -846,8 +1477,18 @@ impl Default for OctetString {
     }
 }
 
+impl Default for UnsignedInteger {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::app::gen::ranged::RangedVariation;
+    use crate::app::parse::parser::{HeaderCollection, HeaderDetails};
+    use crate::app::FunctionCode;
+
     use super::*;
 
     fn binary_config(var: StaticBinaryVariation) -> PointConfig<Binary> {
@@ -885,12 +1526,13 @@ mod tests {
         assert!(db.add(1, counter_config(StaticCounterVariation::Group20Var1)));
         assert!(db.add(2, analog_config(StaticAnalogVariation::Group30Var1)));
 
-        db.select_class_zero();
+        let mut selection = StaticSelection::default();
+        db.select_class_zero(&mut selection);
 
         let mut buffer = [0u8; 64];
         let mut cursor = WriteCursor::new(buffer.as_mut());
 
-        db.write(&mut cursor).unwrap();
+        selection.write(&mut cursor).unwrap();
 
         assert_eq!(
             cursor.written(),
@@ -913,13 +1555,14 @@ mod tests {
         assert!(db.add(1, counter_config(StaticCounterVariation::Group20Var1)));
         assert!(db.add(2, analog_config(StaticAnalogVariation::Group30Var1)));
 
-        db.select_class_zero();
+        let mut selection = StaticSelection::default();
+        db.select_class_zero(&mut selection);
 
         let mut buffer = [0u8; 12]; // can only fit one header at a time
 
         {
             let mut cursor = WriteCursor::new(buffer.as_mut());
-            db.write(&mut cursor).unwrap_err(); // incomplete !
+            selection.write(&mut cursor).unwrap_err(); // incomplete !
 
             assert_eq!(
                 cursor.written(),
@@ -932,7 +1575,7 @@ mod tests {
 
         {
             let mut cursor = WriteCursor::new(buffer.as_mut());
-            db.write(&mut cursor).unwrap_err(); // incomplete !
+            selection.write(&mut cursor).unwrap_err(); // incomplete !
 
             assert_eq!(
                 cursor.written(),
@@ -945,7 +1588,7 @@ mod tests {
 
         {
             let mut cursor = WriteCursor::new(buffer.as_mut());
-            db.write(&mut cursor).unwrap(); // complete !
+            selection.write(&mut cursor).unwrap(); // complete !
 
             assert_eq!(
                 cursor.written(),
@@ -963,12 +1606,13 @@ mod tests {
 
         assert!(db.add(0, binary_config(StaticBinaryVariation::Group1Var1)));
 
-        db.select_class_zero();
+        let mut selection = StaticSelection::default();
+        db.select_class_zero(&mut selection);
 
         let mut buffer = [0u8; 64];
         let mut cursor = WriteCursor::new(buffer.as_mut());
 
-        db.write(&mut cursor).unwrap();
+        selection.write(&mut cursor).unwrap();
 
         assert_eq!(
             cursor.written(),
@@ -978,4 +1622,328 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn writes_unsigned_integers_as_g102v1() {
+        let mut db = StaticDatabase::default();
+
+        let config = PointConfig::<UnsignedInteger>::new(
+            Some(EventClass::Class1),
+            UnsignedIntegerDetector,
+            StaticUnsignedIntegerVariation,
+            EventUnsignedIntegerVariation,
+        );
+        assert!(db.add(3, config));
+        let (exists, event) = db.update(&UnsignedInteger::new(0x2A), 3, UpdateOptions::default());
+        assert!(exists);
+        assert_eq!(
+            event,
+            Some((EventUnsignedIntegerVariation, EventClass::Class1))
+        );
+
+        let mut selection = StaticSelection::default();
+        db.select_class_zero(&mut selection);
+
+        let mut buffer = [0u8; 64];
+        let mut cursor = WriteCursor::new(buffer.as_mut());
+
+        selection.write(&mut cursor).unwrap();
+
+        assert_eq!(
+            cursor.written(),
+            [
+                // g102v1 - s/s == 3, value == 42
+                102, 01, 0x01, 03, 00, 03, 00, 0x2A,
+            ]
+        )
+    }
+
+    #[test]
+    fn writes_values_captured_when_selected() {
+        let mut db = StaticDatabase::default();
+
+        assert!(db.add(1, counter_config(StaticCounterVariation::Group20Var1)));
+
+        let mut selection = StaticSelection::default();
+        db.select(
+            StaticReadHeader::Counter(None, Some(IndexRange::new(0, 5))),
+            &mut selection,
+        );
+
+        // updates after the selection are reported by the next READ
+        let value = Counter::new(42, Flags::ONLINE, Time::synchronized(0));
+        db.update(&value, 1, UpdateOptions::default());
+
+        let mut buffer = [0u8; 64];
+        let mut cursor = WriteCursor::new(buffer.as_mut());
+        selection.write(&mut cursor).unwrap();
+
+        assert_eq!(
+            cursor.written(),
+            [
+                // g20v1 - s/s == 1, restart, value == 0
+                20, 01, 0x01, 01, 00, 01, 00, 0x02, 0, 0, 0, 0,
+            ]
+        );
+    }
+
+    #[test]
+    fn can_attach_and_query_metadata() {
+        let mut db = StaticDatabase::default();
+
+        assert!(db.add(0, analog_config(StaticAnalogVariation::Group30Var1)));
+        assert!(db.add(1, analog_config(StaticAnalogVariation::Group30Var1)));
+
+        let metadata = PointMetadata {
+            name: "feeder voltage".to_string(),
+            units: Some("kV".to_string()),
+            scaling: Some(Scaling::new(0.1, 0.0).unwrap()),
+            attribute_set: None,
+        };
+
+        // points must exist
+        assert!(!db.set_metadata::<Analog>(2, Some(metadata.clone())));
+        // metadata is per type
+        assert!(!db.set_metadata::<Binary>(1, Some(metadata.clone())));
+
+        assert!(db.set_metadata::<Analog>(1, Some(metadata.clone())));
+        assert_eq!(db.get_metadata::<Analog>(0), None);
+        assert_eq!(db.get_metadata::<Analog>(1), Some(metadata));
+        assert_eq!(db.find_by_name::<Analog>("feeder voltage"), Some(1));
+        assert_eq!(db.find_by_name::<Counter>("feeder voltage"), None);
+
+        // metadata is removed along with the point
+        assert!(db.remove::<Analog>(1));
+        assert!(db.add(1, analog_config(StaticAnalogVariation::Group30Var1)));
+        assert_eq!(db.get_metadata::<Analog>(1), None);
+        assert_eq!(db.find_by_name::<Analog>("feeder voltage"), None);
+    }
+
+    #[test]
+    fn excluded_points_are_omitted_from_class_zero_only() {
+        let mut db = StaticDatabase::default();
+
+        assert!(db.add(0, binary_config(StaticBinaryVariation::Group1Var2)));
+        assert!(db.add(1, binary_config(StaticBinaryVariation::Group1Var2)));
+        assert!(db.add(0, counter_config(StaticCounterVariation::Group20Var1)));
+
+        assert!(!db.set_class_zero::<Binary>(2, false));
+        assert!(db.set_class_zero::<Binary>(0, false));
+        assert!(db.set_class_zero::<Counter>(0, false));
+        assert_eq!(db.get_class_zero::<Binary>(0), Some(false));
+        assert_eq!(db.get_class_zero::<Binary>(1), Some(true));
+        assert_eq!(db.get_class_zero::<Binary>(2), None);
+
+        let mut buffer = [0u8; 64];
+
+        let mut selection = StaticSelection::default();
+        db.select_class_zero(&mut selection);
+        let mut cursor = WriteCursor::new(buffer.as_mut());
+        selection.write(&mut cursor).unwrap();
+        assert_eq!(
+            cursor.written(),
+            [
+                // g1v2 - s/s == 1, restart
+                01, 02, 0x01, 01, 00, 01, 00, 0x02,
+            ]
+        );
+
+        // the points can still be read by type
+        let mut selection = StaticSelection::default();
+        db.select(
+            StaticReadHeader::Binary(None, Some(IndexRange::new(0, 1))),
+            &mut selection,
+        );
+        let mut cursor = WriteCursor::new(buffer.as_mut());
+        selection.write(&mut cursor).unwrap();
+        assert_eq!(
+            cursor.written(),
+            [
+                // g1v2 - s/s == 0..1, restart
+                01, 02, 0x01, 00, 00, 01, 00, 0x02, 0x02,
+            ]
+        );
+    }
+
+    fn write_binaries(
+        storage: PointStorage,
+        indices: &[u16],
+        range: Option<IndexRange>,
+    ) -> Vec<u8> {
+        let mut db = StaticDatabase::new(ClassZeroConfig::default(), StorageConfig::all(storage));
+        for index in indices {
+            assert!(db.add(*index, binary_config(StaticBinaryVariation::Group1Var2)));
+        }
+        assert!(!db.add(indices[0], binary_config(StaticBinaryVariation::Group1Var2)));
+
+        let mut selection = StaticSelection::default();
+        db.select_by_type::<Binary>(None, range, &mut selection);
+
+        let mut buffer = [0u8; 64];
+        let mut cursor = WriteCursor::new(buffer.as_mut());
+        selection.write(&mut cursor).unwrap();
+        cursor.written().to_vec()
+    }
+
+    #[test]
+    fn dense_storage_writes_same_responses_as_sparse_storage() {
+        let indices = [2, 3, 5];
+        for range in [
+            None,
+            Some(IndexRange::new(0, 4)),
+            Some(IndexRange::new(4, 100)),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                write_binaries(PointStorage::Dense, &indices, *range),
+                write_binaries(PointStorage::Sparse, &indices, *range)
+            );
+        }
+
+        assert_eq!(
+            write_binaries(PointStorage::Dense, &indices, None),
+            [
+                // g1v2 - s/s == 2..3, restart, followed by g1v2 - s/s == 5, restart
+                01, 02, 0x01, 02, 00, 03, 00, 0x02, 0x02, 01, 02, 0x01, 05, 00, 05, 00, 0x02,
+            ]
+        );
+    }
+
+    #[test]
+    fn dense_storage_can_remove_points() {
+        let mut db = StaticDatabase::new(
+            ClassZeroConfig::default(),
+            StorageConfig::all(PointStorage::Dense),
+        );
+
+        assert!(db.add(1, counter_config(StaticCounterVariation::Group20Var1)));
+        assert!(db.add(4, counter_config(StaticCounterVariation::Group20Var1)));
+        assert!(db.remove::<Counter>(4));
+        assert!(!db.remove::<Counter>(4));
+        assert!(!db.remove::<Counter>(u16::MAX));
+        assert_eq!(db.get::<Counter>(4), None);
+        assert!(db.get::<Counter>(1).is_some());
+
+        let mut selection = StaticSelection::default();
+        db.select_class_zero(&mut selection);
+
+        let mut buffer = [0u8; 64];
+        let mut cursor = WriteCursor::new(buffer.as_mut());
+        selection.write(&mut cursor).unwrap();
+
+        assert_eq!(
+            cursor.written(),
+            [
+                // g20v1 - s/s == 1, restart, value == 0
+                20, 01, 0x01, 01, 00, 01, 00, 0x02, 0, 0, 0, 0,
+            ]
+        );
+    }
+
+    #[test]
+    fn attribute_sets_are_private_and_describe_one_point() {
+        let mut db = StaticDatabase::default();
+
+        assert!(db.add(0, analog_config(StaticAnalogVariation::Group30Var1)));
+        assert!(db.add(1, analog_config(StaticAnalogVariation::Group30Var1)));
+        assert!(db.add(0, binary_config(StaticBinaryVariation::Group1Var1)));
+
+        let with_set = |set| PointMetadata {
+            attribute_set: Some(set),
+            ..PointMetadata::new("point")
+        };
+
+        // set 0 contains the standard attributes
+        assert!(!db.set_metadata::<Analog>(0, Some(with_set(0))));
+        assert!(!db.set_metadata::<Analog>(0, Some(with_set(32768))));
+        assert!(db.set_metadata::<Analog>(0, Some(with_set(1))));
+        // the same point may replace its metadata
+        assert!(db.set_metadata::<Analog>(0, Some(with_set(1))));
+        // but no other point of any type may use the set
+        assert!(!db.set_metadata::<Analog>(1, Some(with_set(1))));
+        assert!(!db.set_metadata::<Binary>(0, Some(with_set(1))));
+        assert!(db.set_metadata::<Binary>(0, Some(with_set(32767))));
+    }
+
+    #[test]
+    fn attribute_sets_are_released_with_their_point() {
+        let mut db = StaticDatabase::default();
+
+        assert!(db.add(0, analog_config(StaticAnalogVariation::Group30Var1)));
+        assert!(db.add(1, analog_config(StaticAnalogVariation::Group30Var1)));
+
+        let with_set = |set| PointMetadata {
+            attribute_set: Some(set),
+            ..PointMetadata::new("point")
+        };
+
+        // replacing the metadata releases the previous set
+        assert!(db.set_metadata::<Analog>(0, Some(with_set(1))));
+        assert!(db.set_metadata::<Analog>(0, Some(with_set(2))));
+        assert!(db.set_metadata::<Analog>(1, Some(with_set(1))));
+
+        // clearing the metadata releases the set
+        assert!(db.set_metadata::<Analog>(1, None));
+        assert!(db.set_metadata::<Analog>(0, Some(with_set(1))));
+
+        // so does removing the point
+        assert!(db.remove::<Analog>(0));
+        assert!(db.set_metadata::<Analog>(1, Some(with_set(1))));
+    }
+
+    #[test]
+    fn writes_metadata_as_device_attributes() {
+        let mut db = StaticDatabase::default();
+
+        assert!(db.add(7, analog_config(StaticAnalogVariation::Group30Var1)));
+        assert!(db.set_metadata::<Analog>(
+            7,
+            Some(PointMetadata {
+                name: "feeder voltage".to_string(),
+                units: Some("kV".to_string()),
+                scaling: Some(Scaling::new(0.5, 2.0).unwrap()),
+                attribute_set: Some(300),
+            })
+        ));
+
+        let mut selection = StaticSelection::default();
+        let all = StaticReadHeader::Attributes(254, IndexRange::new(0, 1000));
+        let list = StaticReadHeader::Attributes(255, IndexRange::new(300, 300));
+        let units = StaticReadHeader::Attributes(4, IndexRange::new(300, 300));
+        let unknown = StaticReadHeader::Attributes(254, IndexRange::new(0, 299));
+        assert_eq!(db.select(all, &mut selection), Iin2::default());
+        assert_eq!(db.select(list, &mut selection), Iin2::default());
+        assert_eq!(db.select(units, &mut selection), Iin2::default());
+        assert_eq!(db.select(unknown, &mut selection), Iin2::OBJECT_UNKNOWN);
+
+        let mut buffer = [0u8; 256];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        selection.write(&mut cursor).unwrap();
+
+        let headers = HeaderCollection::parse(FunctionCode::Response, cursor.written()).unwrap();
+        let attributes: Vec<String> = headers
+            .iter()
+            .map(|header| match header.details {
+                HeaderDetails::TwoByteStartStop(_, _, RangedVariation::Group0VarX(attr)) => {
+                    attr.to_string()
+                }
+                _ => unreachable!(),
+            })
+            .collect();
+
+        assert_eq!(
+            attributes,
+            vec![
+                "set: 300 var: 1 uint: 30",
+                "set: 300 var: 2 uint: 7",
+                "set: 300 var: 3 vstr: \"feeder voltage\"",
+                "set: 300 var: 4 vstr: \"kV\"",
+                "set: 300 var: 5 float: 0.5",
+                "set: 300 var: 6 float: 2",
+                "set: 300 var: 255 list: (var: 1 writable: false) (var: 2 writable: false) (var: 3 writable: false) (var: 4 writable: false) (var: 5 writable: false) (var: 6 writable: false)",
+                "set: 300 var: 4 vstr: \"kV\"",
+            ]
+        );
+    }
 }
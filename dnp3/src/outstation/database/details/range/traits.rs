@@ -201,3 +201,9 @@ impl StaticVariation<OctetString> for StaticOctetStringVariation {
         octet_string(value)
     }
 }
+
+impl StaticVariation<UnsignedInteger> for StaticUnsignedIntegerVariation {
+    fn get_write_info(&self, _value: &UnsignedInteger) -> WriteInfo<UnsignedInteger> {
+        fixed_type::<UnsignedInteger, Group102Var1>()
+    }
+}
@@ -1,7 +1,12 @@
+use std::num::NonZeroU16;
+use std::ops::RangeInclusive;
 use std::sync::{Arc, Mutex};
 
 pub use config::*;
-use details::range::static_db::{Deadband, FlagsDetector, OctetStringDetector, PointConfig};
+use details::range::static_db::{
+    Deadband, FlagsDetector, HasFlags, IndexRange, OctetStringDetector, PointConfig,
+    StaticSelection, UnsignedIntegerDetector, Updatable,
+};
 
 use crate::app::measurement::*;
 use crate::app::parse::parser::HeaderCollection;
@@ -39,7 +44,10 @@ pub enum EventClass {
 }
 
 /// Controls which types are reported during a class 0 READ
+///
+/// Individual points of an enabled type may also be omitted using the [ClassZero] trait
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassZeroConfig {
     /// If true, Binary Inputs are reported in Class 0 READ requests
     pub binary: bool,
@@ -58,6 +66,8 @@ pub struct ClassZeroConfig {
     /// If true, Octet Strings are reported in Class 0 READ requests
     /// This field defaults to `false` for conformance to the standard
     pub octet_strings: bool,
+    /// If true, Unsigned Integers are reported in Class 0 READ requests
+    pub unsigned_integers: bool,
 }
 
 impl ClassZeroConfig {
@@ -72,6 +82,7 @@ impl ClassZeroConfig {
         analog: bool,
         analog_output_status: bool,
         octet_strings: bool,
+        unsigned_integers: bool,
     ) -> Self {
         ClassZeroConfig {
             binary,
@@ -82,6 +93,7 @@ impl ClassZeroConfig {
             analog,
             analog_output_status,
             octet_strings,
+            unsigned_integers,
         }
     }
 }
@@ -97,14 +109,96 @@ impl Default for ClassZeroConfig {
             analog: true,
             analog_output_status: true,
             octet_strings: false,
+            unsigned_integers: true,
+        }
+    }
+}
+
+/// How the static values of a point type are stored in the database
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PointStorage {
+    /// Points are kept in an ordered map keyed by index. This is the default and suits any
+    /// assignment of indices.
+    Sparse,
+    /// Points are kept in a vector indexed directly by point index, so reading a range of
+    /// points is a linear scan of memory.
+    ///
+    /// The vector is sized by the highest index of the type, so this mode should only be used
+    /// when the indices are contiguous or nearly so, e.g. 0 to N-1.
+    Dense,
+}
+
+impl Default for PointStorage {
+    fn default() -> Self {
+        Self::Sparse
+    }
+}
+
+/// Controls how the static values of each type are stored
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageConfig {
+    /// storage of Binary Inputs
+    pub binary: PointStorage,
+    /// storage of Double-bit Binary Inputs
+    pub double_bit_binary: PointStorage,
+    /// storage of Binary Output Status points
+    pub binary_output_status: PointStorage,
+    /// storage of Counters
+    pub counter: PointStorage,
+    /// storage of Frozen Counters
+    pub frozen_counter: PointStorage,
+    /// storage of Analog Inputs
+    pub analog: PointStorage,
+    /// storage of Analog Output Status points
+    pub analog_output_status: PointStorage,
+    /// storage of Octet Strings
+    pub octet_strings: PointStorage,
+    /// storage of Unsigned Integers
+    pub unsigned_integers: PointStorage,
+}
+
+impl StorageConfig {
+    /// construct a `StorageConfig` from its fields
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        binary: PointStorage,
+        double_bit_binary: PointStorage,
+        binary_output_status: PointStorage,
+        counter: PointStorage,
+        frozen_counter: PointStorage,
+        analog: PointStorage,
+        analog_output_status: PointStorage,
+        octet_strings: PointStorage,
+        unsigned_integers: PointStorage,
+    ) -> Self {
+        StorageConfig {
+            binary,
+            double_bit_binary,
+            binary_output_status,
+            counter,
+            frozen_counter,
+            analog,
+            analog_output_status,
+            octet_strings,
+            unsigned_integers,
         }
     }
+
+    /// construct a `StorageConfig` that uses the same storage for every type
+    pub fn all(storage: PointStorage) -> Self {
+        Self::new(
+            storage, storage, storage, storage, storage, storage, storage, storage, storage,
+        )
+    }
 }
 
 /// Maximum number of events for each type.
 ///
 /// A value of zero means that events will not be buffered for that type.
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EventBufferConfig {
     /// maximum number of binary input events (g2)
     pub max_binary: u16,
@@ -122,12 +216,14 @@ pub struct EventBufferConfig {
     pub max_analog_output_status: u16,
     /// maximum number of octet string events (g111)
     pub max_octet_string: u16,
+    /// maximum number of unsigned integer events (g102)
+    pub max_unsigned_integer: u16,
 }
 
 impl EventBufferConfig {
     /// initialize with the same maximum values for all types
     pub fn all_types(max: u16) -> Self {
-        Self::new(max, max, max, max, max, max, max, max)
+        Self::new(max, max, max, max, max, max, max, max, max)
     }
 
     /// initialize the configuration to support no events
@@ -146,6 +242,7 @@ impl EventBufferConfig {
         max_analog: u16,
         max_analog_output_status: u16,
         max_octet_string: u16,
+        max_unsigned_integer: u16,
     ) -> Self {
         Self {
             max_binary,
@@ -156,6 +253,7 @@ impl EventBufferConfig {
             max_analog,
             max_analog_output_status,
             max_octet_string,
+            max_unsigned_integer,
         }
     }
 
@@ -167,6 +265,7 @@ impl EventBufferConfig {
             + self.max_frozen_counter as usize
             + self.max_analog as usize
             + self.max_analog_output_status as usize
+            + self.max_unsigned_integer as usize
     }
 }
 
@@ -261,6 +360,77 @@ pub trait Get<T> {
     fn get(&self, index: u16) -> Option<T>;
 }
 
+/// trait for attaching descriptive metadata to existing points and querying it
+///
+/// Metadata is removed along with the point
+pub trait Metadata<T> {
+    /// attach metadata to the point at the specified index, replacing any existing metadata
+    ///
+    /// Returns false if the point doesn't exist, or if the attribute set of the metadata is
+    /// outside the range of private sets or already describes another point
+    fn set_metadata(&mut self, index: u16, metadata: PointMetadata) -> bool;
+
+    /// remove any metadata attached to the point at the specified index
+    ///
+    /// Returns true if the point exists
+    fn clear_metadata(&mut self, index: u16) -> bool;
+
+    /// retrieve the metadata attached to the point at the specified index
+    fn get_metadata(&self, index: u16) -> Option<PointMetadata>;
+
+    /// find the index of the first point whose metadata has the specified name
+    fn find_by_name(&self, name: &str) -> Option<u16>;
+}
+
+/// trait for assigning existing points to class 0 or omitting them from it
+///
+/// Points are included in class 0 when added, as long as their type is enabled in the
+/// [ClassZeroConfig]. A point that is omitted, e.g. an event-only point, is not reported in class 0
+/// READ requests but still produces events and may still be read by its type.
+pub trait ClassZero<T> {
+    /// include or omit the point at the specified index in class 0 READ requests
+    ///
+    /// Returns true if the point exists
+    fn set_class_zero(&mut self, index: u16, included: bool) -> bool;
+
+    /// retrieve whether the point at the specified index is included in class 0 READ requests
+    fn get_class_zero(&self, index: u16) -> Option<bool>;
+}
+
+/// trait for declaring which output points accept controls
+///
+/// `BinaryOutputStatus` indices govern CROB (g12v1) controls and `AnalogOutputStatus` indices govern
+/// analog output (g41) controls. By default, every control is passed to the `ControlHandler`. Once at
+/// least one index of a type has been declared, SELECT/OPERATE on any index of that type which is not
+/// controllable is answered with `CommandStatus::NotSupported` without invoking the `ControlHandler`.
+pub trait SetControllable<T> {
+    /// declare whether the output at the specified index accepts controls
+    fn set_controllable(&mut self, index: u16, controllable: bool);
+}
+
+/// trait for setting and clearing flag bits of every existing point of a type within an index range
+///
+/// This is typically used by gateways to set `COMM_LOST` on the points mapped from a downstream
+/// device when the connection to it fails, and to clear it once the connection is restored.
+/// Performed within a single transaction, the master never observes a partial update.
+pub trait UpdateFlags<T> {
+    /// set the bits of `set` and clear the bits of `clear` in the flags of the points whose
+    /// index is within `range`, e.g. `0..=u16::MAX` for every point of the type
+    ///
+    /// Bits present in both `set` and `clear` are set. The values and timestamps are unchanged.
+    /// Each point is updated as if by [Update::update] with the specified options, so events are
+    /// produced according to the configuration of the point.
+    ///
+    /// Returns the number of points within the range
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        options: UpdateOptions,
+    ) -> usize;
+}
+
 /// Core database implementation shared between an outstation task and the user facing API.
 /// This type is always guarded by a `DatabaseHandle` which provides a transactional API.
 pub struct Database {
@@ -270,24 +440,48 @@ pub struct Database {
 impl Database {
     /// Create a database by specified how it will buffer events
     pub(crate) fn new(
-        max_read_selection: Option<u16>,
         class_zero_config: ClassZeroConfig,
+        storage_config: StorageConfig,
         config: EventBufferConfig,
     ) -> Self {
         Self {
             inner: crate::outstation::database::details::database::Database::new(
-                max_read_selection,
                 class_zero_config,
+                storage_config,
                 config,
             ),
         }
     }
+
+    fn update_flags_impl<T>(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        options: UpdateOptions,
+    ) -> usize
+    where
+        T: Updatable + HasFlags,
+    {
+        if range.is_empty() {
+            return 0;
+        }
+        self.inner.update_flags::<T>(
+            IndexRange::new(*range.start(), *range.end()),
+            set,
+            clear,
+            options,
+        )
+    }
 }
 
 /// Handle type that can be used to perform transactions on an underlying database
 #[derive(Clone)]
 pub(crate) struct DatabaseHandle {
     inner: Arc<Mutex<Database>>,
+    // static values selected by READ requests, behind a separate lock so that formatting them
+    // into responses doesn't block updates. When both are held, `inner` is locked first.
+    selection: Arc<Mutex<StaticSelection>>,
     notify: Arc<crate::tokio::sync::Notify>,
 }
 
@@ -309,14 +503,16 @@ impl DatabaseHandle {
     pub(crate) fn new(
         max_read_selection: Option<u16>,
         class_zero_config: ClassZeroConfig,
+        storage_config: StorageConfig,
         event_config: EventBufferConfig,
     ) -> Self {
         Self {
             inner: Arc::new(Mutex::new(Database::new(
-                max_read_selection,
                 class_zero_config,
+                storage_config,
                 event_config,
             ))),
+            selection: Arc::new(Mutex::new(StaticSelection::new(max_read_selection))),
             notify: Arc::new(crate::tokio::sync::Notify::new()),
         }
     }
@@ -325,6 +521,10 @@ impl DatabaseHandle {
         self.inner.lock().unwrap().inner.clear_written_events();
     }
 
+    pub(crate) fn num_events(&self) -> usize {
+        self.inner.lock().unwrap().inner.num_events()
+    }
+
     pub(crate) fn get_events_info(&self) -> EventsInfo {
         let guard = self.inner.lock().unwrap();
 
@@ -337,41 +537,102 @@ impl DatabaseHandle {
     pub(crate) fn select(&mut self, headers: &HeaderCollection) -> Iin2 {
         let mut iin2 = Iin2::default();
         let mut guard = self.inner.lock().unwrap();
+        let mut selection = self.selection.lock().unwrap();
         for header in headers.iter() {
             match ReadHeader::get(&header) {
                 None => {
                     iin2 |= Iin2::NO_FUNC_CODE_SUPPORT;
                 }
-                Some(x) => iin2 |= guard.inner.select_by_header(x),
+                Some(x) => iin2 |= guard.inner.select_by_header(x, &mut selection),
             }
         }
         iin2
     }
 
-    pub(crate) fn write_response_headers(&mut self, cursor: &mut WriteCursor) -> ResponseInfo {
-        self.inner
+    /// reset the current selection and select the headers of a deferred READ
+    pub(crate) fn select_deferred(&mut self, headers: &[ReadHeader]) -> Iin2 {
+        let mut iin2 = Iin2::default();
+        let mut guard = self.inner.lock().unwrap();
+        let mut selection = self.selection.lock().unwrap();
+        guard.inner.reset();
+        selection.reset();
+        for header in headers.iter() {
+            iin2 |= guard.inner.select_by_header(*header, &mut selection);
+        }
+        iin2
+    }
+
+    pub(crate) fn write_response_headers(
+        &mut self,
+        max_events: Option<NonZeroU16>,
+        cursor: &mut WriteCursor,
+    ) -> ResponseInfo {
+        // first we write events
+        let result = self
+            .inner
             .lock()
             .unwrap()
             .inner
-            .write_response_headers(cursor)
+            .write_events(max_events, cursor);
+        let has_events = match result {
+            Ok(count) => count > 0,
+            Err(count) => count > 0,
+        };
+
+        let complete = if result.is_err() {
+            // unable to write all the events in this response (out of space or the event limit
+            // was reached), so we can't write any static data
+            false
+        } else {
+            // static values were copied when selected, so the database isn't locked while
+            // they're written
+            self.selection.lock().unwrap().write(cursor).is_ok()
+        };
+
+        ResponseInfo {
+            has_events,
+            complete,
+        }
     }
 
     pub(crate) fn write_unsolicited(
         &mut self,
         classes: EventClasses,
+        max_events: Option<NonZeroU16>,
         cursor: &mut WriteCursor,
     ) -> usize {
+        self.selection.lock().unwrap().reset();
         let mut guard = self.inner.lock().unwrap();
         guard.inner.reset();
         let count = guard.inner.select_event_classes(classes);
         if count == 0 {
             return 0;
         }
-        guard.inner.write_events_only(cursor)
+        guard.inner.write_events_only(max_events, cursor)
     }
 
     pub(crate) fn reset(&mut self) {
-        self.inner.lock().unwrap().inner.reset()
+        self.inner.lock().unwrap().inner.reset();
+        self.selection.lock().unwrap().reset();
+    }
+
+    /// reset the selection and free the memory it kept for subsequent READ requests
+    pub(crate) fn release_selection(&mut self) {
+        self.selection.lock().unwrap().release();
+    }
+}
+
+impl SetControllable<BinaryOutputStatus> for Database {
+    fn set_controllable(&mut self, index: u16, controllable: bool) {
+        self.inner
+            .set_binary_output_controllable(index, controllable)
+    }
+}
+
+impl SetControllable<AnalogOutputStatus> for Database {
+    fn set_controllable(&mut self, index: u16, controllable: bool) {
+        self.inner
+            .set_analog_output_controllable(index, controllable)
     }
 }
 
@@ -423,6 +684,96 @@ impl Update<OctetString> for Database {
     }
 }
 
+impl Update<UnsignedInteger> for Database {
+    fn update(&mut self, index: u16, value: &UnsignedInteger, options: UpdateOptions) -> bool {
+        self.inner.update(value, index, options)
+    }
+}
+
+impl UpdateFlags<Binary> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        options: UpdateOptions,
+    ) -> usize {
+        self.update_flags_impl::<Binary>(range, set, clear, options)
+    }
+}
+
+impl UpdateFlags<DoubleBitBinary> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        options: UpdateOptions,
+    ) -> usize {
+        self.update_flags_impl::<DoubleBitBinary>(range, set, clear, options)
+    }
+}
+
+impl UpdateFlags<BinaryOutputStatus> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        options: UpdateOptions,
+    ) -> usize {
+        self.update_flags_impl::<BinaryOutputStatus>(range, set, clear, options)
+    }
+}
+
+impl UpdateFlags<Counter> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        options: UpdateOptions,
+    ) -> usize {
+        self.update_flags_impl::<Counter>(range, set, clear, options)
+    }
+}
+
+impl UpdateFlags<FrozenCounter> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        options: UpdateOptions,
+    ) -> usize {
+        self.update_flags_impl::<FrozenCounter>(range, set, clear, options)
+    }
+}
+
+impl UpdateFlags<Analog> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        options: UpdateOptions,
+    ) -> usize {
+        self.update_flags_impl::<Analog>(range, set, clear, options)
+    }
+}
+
+impl UpdateFlags<AnalogOutputStatus> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        options: UpdateOptions,
+    ) -> usize {
+        self.update_flags_impl::<AnalogOutputStatus>(range, set, clear, options)
+    }
+}
+
 impl Add<BinaryConfig> for Database {
     fn add(&mut self, index: u16, class: Option<EventClass>, config: BinaryConfig) -> bool {
         let config =
@@ -530,6 +881,23 @@ impl Add<OctetStringConfig> for Database {
     }
 }
 
+impl Add<UnsignedIntegerConfig> for Database {
+    fn add(
+        &mut self,
+        index: u16,
+        class: Option<EventClass>,
+        _config: UnsignedIntegerConfig,
+    ) -> bool {
+        let config = PointConfig::<UnsignedInteger>::new(
+            class,
+            UnsignedIntegerDetector,
+            StaticUnsignedIntegerVariation,
+            EventUnsignedIntegerVariation,
+        );
+        self.inner.add(index, config)
+    }
+}
+
 impl Remove<Binary> for Database {
     fn remove(&mut self, index: u16) -> bool {
         self.inner.remove::<Binary>(index)
@@ -578,6 +946,12 @@ impl Remove<OctetString> for Database {
     }
 }
 
+impl Remove<UnsignedInteger> for Database {
+    fn remove(&mut self, index: u16) -> bool {
+        self.inner.remove::<UnsignedInteger>(index)
+    }
+}
+
 impl Get<Binary> for Database {
     fn get(&self, index: u16) -> Option<Binary> {
         self.inner.get::<Binary>(index)
@@ -625,3 +999,220 @@ impl Get<OctetString> for Database {
         self.inner.get::<OctetString>(index)
     }
 }
+
+impl Get<UnsignedInteger> for Database {
+    fn get(&self, index: u16) -> Option<UnsignedInteger> {
+        self.inner.get::<UnsignedInteger>(index)
+    }
+}
+
+macro_rules! impl_metadata {
+    ($($name:ident),*) => {
+        $(
+            impl Metadata<$name> for Database {
+                fn set_metadata(&mut self, index: u16, metadata: PointMetadata) -> bool {
+                    self.inner.set_metadata::<$name>(index, Some(metadata))
+                }
+
+                fn clear_metadata(&mut self, index: u16) -> bool {
+                    self.inner.set_metadata::<$name>(index, None)
+                }
+
+                fn get_metadata(&self, index: u16) -> Option<PointMetadata> {
+                    self.inner.get_metadata::<$name>(index)
+                }
+
+                fn find_by_name(&self, name: &str) -> Option<u16> {
+                    self.inner.find_by_name::<$name>(name)
+                }
+            }
+        )*
+    };
+}
+
+impl_metadata!(
+    Binary,
+    DoubleBitBinary,
+    BinaryOutputStatus,
+    Counter,
+    FrozenCounter,
+    Analog,
+    AnalogOutputStatus,
+    OctetString,
+    UnsignedInteger
+);
+
+impl ClassZero<Binary> for Database {
+    fn set_class_zero(&mut self, index: u16, included: bool) -> bool {
+        self.inner.set_class_zero::<Binary>(index, included)
+    }
+
+    fn get_class_zero(&self, index: u16) -> Option<bool> {
+        self.inner.get_class_zero::<Binary>(index)
+    }
+}
+
+impl ClassZero<DoubleBitBinary> for Database {
+    fn set_class_zero(&mut self, index: u16, included: bool) -> bool {
+        self.inner
+            .set_class_zero::<DoubleBitBinary>(index, included)
+    }
+
+    fn get_class_zero(&self, index: u16) -> Option<bool> {
+        self.inner.get_class_zero::<DoubleBitBinary>(index)
+    }
+}
+
+impl ClassZero<BinaryOutputStatus> for Database {
+    fn set_class_zero(&mut self, index: u16, included: bool) -> bool {
+        self.inner
+            .set_class_zero::<BinaryOutputStatus>(index, included)
+    }
+
+    fn get_class_zero(&self, index: u16) -> Option<bool> {
+        self.inner.get_class_zero::<BinaryOutputStatus>(index)
+    }
+}
+
+impl ClassZero<Counter> for Database {
+    fn set_class_zero(&mut self, index: u16, included: bool) -> bool {
+        self.inner.set_class_zero::<Counter>(index, included)
+    }
+
+    fn get_class_zero(&self, index: u16) -> Option<bool> {
+        self.inner.get_class_zero::<Counter>(index)
+    }
+}
+
+impl ClassZero<FrozenCounter> for Database {
+    fn set_class_zero(&mut self, index: u16, included: bool) -> bool {
+        self.inner.set_class_zero::<FrozenCounter>(index, included)
+    }
+
+    fn get_class_zero(&self, index: u16) -> Option<bool> {
+        self.inner.get_class_zero::<FrozenCounter>(index)
+    }
+}
+
+impl ClassZero<Analog> for Database {
+    fn set_class_zero(&mut self, index: u16, included: bool) -> bool {
+        self.inner.set_class_zero::<Analog>(index, included)
+    }
+
+    fn get_class_zero(&self, index: u16) -> Option<bool> {
+        self.inner.get_class_zero::<Analog>(index)
+    }
+}
+
+impl ClassZero<AnalogOutputStatus> for Database {
+    fn set_class_zero(&mut self, index: u16, included: bool) -> bool {
+        self.inner
+            .set_class_zero::<AnalogOutputStatus>(index, included)
+    }
+
+    fn get_class_zero(&self, index: u16) -> Option<bool> {
+        self.inner.get_class_zero::<AnalogOutputStatus>(index)
+    }
+}
+
+impl ClassZero<OctetString> for Database {
+    fn set_class_zero(&mut self, index: u16, included: bool) -> bool {
+        self.inner.set_class_zero::<OctetString>(index, included)
+    }
+
+    fn get_class_zero(&self, index: u16) -> Option<bool> {
+        self.inner.get_class_zero::<OctetString>(index)
+    }
+}
+
+impl ClassZero<UnsignedInteger> for Database {
+    fn set_class_zero(&mut self, index: u16, included: bool) -> bool {
+        self.inner
+            .set_class_zero::<UnsignedInteger>(index, included)
+    }
+
+    fn get_class_zero(&self, index: u16) -> Option<bool> {
+        self.inner.get_class_zero::<UnsignedInteger>(index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app::Timestamp;
+
+    fn database() -> Database {
+        let mut db = Database::new(
+            ClassZeroConfig::default(),
+            StorageConfig::default(),
+            EventBufferConfig::all_types(10),
+        );
+        for index in 0..4 {
+            db.add(index, Some(EventClass::Class1), BinaryConfig::default());
+            db.update(
+                index,
+                &Binary::new(true, Flags::ONLINE, Time::Synchronized(Timestamp::new(0))),
+                UpdateOptions::initialize(),
+            );
+        }
+        db
+    }
+
+    fn flags(db: &Database, index: u16) -> Flags {
+        Get::<Binary>::get(db, index).unwrap().flags
+    }
+
+    #[test]
+    fn updates_flags_of_points_within_range_and_produces_events() {
+        let mut db = database();
+
+        let count = UpdateFlags::<Binary>::update_flags(
+            &mut db,
+            1..=2,
+            Flags::COMM_LOST,
+            Flags::ONLINE,
+            UpdateOptions::default(),
+        );
+
+        assert_eq!(count, 2);
+        assert_eq!(flags(&db, 0), Flags::ONLINE);
+        assert_eq!(flags(&db, 1), Flags::COMM_LOST);
+        assert_eq!(flags(&db, 2), Flags::COMM_LOST);
+        assert_eq!(flags(&db, 3), Flags::ONLINE);
+        assert_eq!(db.inner.num_events(), 2);
+        assert!(Get::<Binary>::get(&db, 1).unwrap().value);
+    }
+
+    #[test]
+    fn unchanged_flags_do_not_produce_events() {
+        let mut db = database();
+
+        let count = UpdateFlags::<Binary>::update_flags(
+            &mut db,
+            0..=u16::MAX,
+            Flags::ONLINE,
+            Flags::new(0),
+            UpdateOptions::default(),
+        );
+
+        assert_eq!(count, 4);
+        assert_eq!(db.inner.num_events(), 0);
+    }
+
+    #[test]
+    fn empty_range_updates_nothing() {
+        let mut db = database();
+
+        let (start, stop) = (3, 0);
+        let count = UpdateFlags::<Binary>::update_flags(
+            &mut db,
+            start..=stop,
+            Flags::COMM_LOST,
+            Flags::new(0),
+            UpdateOptions::default(),
+        );
+
+        assert_eq!(count, 0);
+        assert_eq!(db.inner.num_events(), 0);
+    }
+}
@@ -22,6 +22,9 @@ pub(crate) enum StaticReadHeader {
         Option<IndexRange>,
     ),
     OctetString(Option<IndexRange>),
+    UnsignedInteger(Option<IndexRange>),
+    // device attribute variation and range of attribute sets
+    Attributes(u8, IndexRange),
 }
 
 #[derive(Copy, Clone)]
@@ -88,11 +91,16 @@ impl ReadHeader {
             }
             HeaderDetails::OneByteCountAndPrefix(_, _) => None,
             HeaderDetails::TwoByteCountAndPrefix(_, _) => None,
+            HeaderDetails::TwoByteFreeFormat(_, _) => None,
         }
     }
 
     fn from_all_objects(header: &AllObjectsVariation) -> Option<ReadHeader> {
         match header {
+            // group 0
+            AllObjectsVariation::Group0(_) => None,
+            AllObjectsVariation::Group0Var254 => None,
+            AllObjectsVariation::Group0Var255 => None,
             // group 1
             AllObjectsVariation::Group1Var0 => Some(StaticReadHeader::Binary(None, None).into()),
             AllObjectsVariation::Group1Var1 => {
@@ -442,6 +450,14 @@ impl ReadHeader {
             AllObjectsVariation::Group60Var4 => Some(EventReadHeader::Class3(None).into()),
             // group 80
             AllObjectsVariation::Group80Var1 => None,
+            AllObjectsVariation::Group90Var1 => None,
+            // group 102
+            AllObjectsVariation::Group102Var0 => {
+                Some(StaticReadHeader::UnsignedInteger(None).into())
+            }
+            AllObjectsVariation::Group102Var1 => {
+                Some(StaticReadHeader::UnsignedInteger(None).into())
+            }
             // group 110
             AllObjectsVariation::Group110Var0 => Some(StaticReadHeader::OctetString(None).into()),
             // group 111
@@ -654,11 +670,18 @@ impl ReadHeader {
             CountVariation::Group60Var4 => Some(EventReadHeader::Class3(Some(count)).into()),
             CountVariation::Group111Var0 => Some(EventReadHeader::OctetString(Some(count)).into()),
             CountVariation::Group111VarX(_) => None,
+            CountVariation::Group120Var3(_) => None,
+            CountVariation::Group120Var4(_) => None,
         }
     }
 
     fn from_range(header: &RangedVariation, range: IndexRange) -> Option<ReadHeader> {
         match header {
+            // group 0
+            RangedVariation::Group0(x) => Some(StaticReadHeader::Attributes(*x, range).into()),
+            RangedVariation::Group0Var254 => Some(StaticReadHeader::Attributes(254, range).into()),
+            RangedVariation::Group0Var255 => Some(StaticReadHeader::Attributes(255, range).into()),
+            RangedVariation::Group0VarX(_) => None,
             // group 1
             RangedVariation::Group1Var0 => Some(StaticReadHeader::Binary(None, Some(range)).into()),
             RangedVariation::Group1Var1(_) => Some(
@@ -832,7 +855,15 @@ impl ReadHeader {
                 .into(),
             ),
             // group 80
+            RangedVariation::Group50Var4(_) => None,
             RangedVariation::Group80Var1(_) => None,
+            // group 102
+            RangedVariation::Group102Var0 => {
+                Some(StaticReadHeader::UnsignedInteger(Some(range)).into())
+            }
+            RangedVariation::Group102Var1(_) => {
+                Some(StaticReadHeader::UnsignedInteger(Some(range)).into())
+            }
             // group 110
             RangedVariation::Group110Var0 => {
                 Some(StaticReadHeader::OctetString(Some(range)).into())
@@ -7,14 +7,14 @@ use crate::transport::FragmentInfo;
 
 #[derive(Copy, Clone)]
 pub(crate) struct DeferredInfo {
-    pub(crate) hash: u64,
+    pub(crate) hash: Option<u64>,
     pub(crate) seq: Sequence,
     pub(crate) info: FragmentInfo,
     pub(crate) iin2: Iin2,
 }
 
 impl DeferredInfo {
-    fn new(hash: u64, seq: Sequence, info: FragmentInfo, iin2: Iin2) -> Self {
+    fn new(hash: Option<u64>, seq: Sequence, info: FragmentInfo, iin2: Iin2) -> Self {
         DeferredInfo {
             hash,
             seq,
@@ -30,6 +30,8 @@ impl DeferredInfo {
 
 pub(crate) struct DeferredRead {
     info: Option<DeferredInfo>,
+    max_headers: usize,
+    // only allocated while a READ is deferred, which is rare for most sessions
     vec: Vec<ReadHeader>,
 }
 
@@ -37,7 +39,8 @@ impl DeferredRead {
     pub(crate) fn new(header_capacity: u16) -> Self {
         Self {
             info: None,
-            vec: Vec::with_capacity(header_capacity as usize),
+            max_headers: header_capacity as usize,
+            vec: Vec::new(),
         }
     }
 
@@ -52,7 +55,7 @@ impl DeferredRead {
 
     pub(crate) fn set(
         &mut self,
-        hash: u64,
+        hash: Option<u64>,
         seq: Sequence,
         info: FragmentInfo,
         headers: HeaderCollection,
@@ -63,12 +66,12 @@ impl DeferredRead {
 
         for h in headers.iter() {
             if let Some(r) = ReadHeader::get(&h) {
-                if self.vec.len() < self.vec.capacity() {
+                if self.vec.len() < self.max_headers {
                     self.vec.push(r)
                 } else {
                     tracing::warn!(
                         "Insufficient capacity ({}) for READ header: {} - {}",
-                        self.vec.capacity(),
+                        self.max_headers,
                         h.variation,
                         h.details.qualifier()
                     )
@@ -85,15 +88,9 @@ impl DeferredRead {
         match self.info {
             None => None,
             Some(x) => {
-                let iin2 = database.transaction(|db| {
-                    db.inner.reset();
-                    let mut iin2 = Iin2::default();
-                    for header in self.vec.iter() {
-                        iin2 |= db.inner.select_by_header(*header);
-                    }
-                    iin2
-                });
-                self.clear();
+                let iin2 = database.select_deferred(&self.vec);
+                self.info = None;
+                self.vec = Vec::new();
                 Some(x.merge(iin2))
             }
         }
@@ -0,0 +1,335 @@
+use crate::app::control::*;
+use crate::app::{
+    event_channel, EventReceiver, EventSender, FunctionCode, RequestHeader, Sequence,
+};
+use crate::link::BroadcastConfirmMode;
+use crate::outstation::database::Database;
+use crate::outstation::traits::*;
+
+/// Notification of [OutstationInformation] delivered as a value on a channel
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum InformationEvent {
+    /// a request is processed from the IDLE state
+    ProcessRequestFromIdle(RequestHeader),
+    /// a broadcast request is received by the outstation
+    BroadcastReceived {
+        /// function code of the request
+        function: FunctionCode,
+        /// how the outstation processed the request
+        action: BroadcastAction,
+        /// broadcast address on which the request was received
+        mode: BroadcastConfirmMode,
+    },
+    /// outstation has begun waiting for a solicited confirm
+    EnterSolicitedConfirmWait(Sequence),
+    /// failed to receive a solicited confirm before the timeout occurred
+    SolicitedConfirmTimeout(Sequence),
+    /// received the expected solicited confirm
+    SolicitedConfirmReceived(Sequence),
+    /// received a new request while waiting for a solicited confirm, aborting the response series
+    SolicitedConfirmWaitNewRequest,
+    /// received a solicited confirm with the wrong sequence number
+    WrongSolicitedConfirmSeq {
+        /// expected sequence number
+        ecsn: Sequence,
+        /// received sequence number
+        seq: Sequence,
+    },
+    /// received a confirm when not expecting one
+    UnexpectedConfirm {
+        /// true if the confirm has the UNS bit set
+        unsolicited: bool,
+        /// received sequence number
+        seq: Sequence,
+    },
+    /// outstation has begun waiting for an unsolicited confirm
+    EnterUnsolicitedConfirmWait(Sequence),
+    /// failed to receive an unsolicited confirm before the timeout occurred
+    UnsolicitedConfirmTimeout {
+        /// expected sequence number
+        ecsn: Sequence,
+        /// true if the response will be retried
+        retry: bool,
+    },
+    /// master confirmed an unsolicited response
+    UnsolicitedConfirmed(Sequence),
+    /// master cleared the restart IIN bit
+    ClearRestartIin,
+}
+
+/// Create an [OutstationInformation] that sends each notification to a bounded channel
+///
+/// The returned implementation is passed to the `spawn_*` function of the outstation in place of
+/// a custom one. Notifications are discarded if more than `capacity` are waiting.
+pub fn information_channel(
+    capacity: usize,
+) -> (
+    Box<dyn OutstationInformation>,
+    EventReceiver<InformationEvent>,
+) {
+    let (tx, rx) = event_channel(capacity);
+    (Box::new(tx), rx)
+}
+
+impl OutstationInformation for EventSender<InformationEvent> {
+    fn process_request_from_idle(&mut self, header: RequestHeader) {
+        self.send(InformationEvent::ProcessRequestFromIdle(header));
+    }
+
+    fn broadcast_received(
+        &mut self,
+        function: FunctionCode,
+        action: BroadcastAction,
+        mode: BroadcastConfirmMode,
+    ) {
+        self.send(InformationEvent::BroadcastReceived {
+            function,
+            action,
+            mode,
+        });
+    }
+
+    fn enter_solicited_confirm_wait(&mut self, ecsn: Sequence) {
+        self.send(InformationEvent::EnterSolicitedConfirmWait(ecsn));
+    }
+
+    fn solicited_confirm_timeout(&mut self, ecsn: Sequence) {
+        self.send(InformationEvent::SolicitedConfirmTimeout(ecsn));
+    }
+
+    fn solicited_confirm_received(&mut self, ecsn: Sequence) {
+        self.send(InformationEvent::SolicitedConfirmReceived(ecsn));
+    }
+
+    fn solicited_confirm_wait_new_request(&mut self) {
+        self.send(InformationEvent::SolicitedConfirmWaitNewRequest);
+    }
+
+    fn wrong_solicited_confirm_seq(&mut self, ecsn: Sequence, seq: Sequence) {
+        self.send(InformationEvent::WrongSolicitedConfirmSeq { ecsn, seq });
+    }
+
+    fn unexpected_confirm(&mut self, unsolicited: bool, seq: Sequence) {
+        self.send(InformationEvent::UnexpectedConfirm { unsolicited, seq });
+    }
+
+    fn enter_unsolicited_confirm_wait(&mut self, ecsn: Sequence) {
+        self.send(InformationEvent::EnterUnsolicitedConfirmWait(ecsn));
+    }
+
+    fn unsolicited_confirm_timeout(&mut self, ecsn: Sequence, retry: bool) {
+        self.send(InformationEvent::UnsolicitedConfirmTimeout { ecsn, retry });
+    }
+
+    fn unsolicited_confirmed(&mut self, ecsn: Sequence) {
+        self.send(InformationEvent::UnsolicitedConfirmed(ecsn));
+    }
+
+    fn clear_restart_iin(&mut self) {
+        self.send(InformationEvent::ClearRestartIin);
+    }
+}
+
+/// Value of a control of any type
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Control {
+    /// binary command (CROB)
+    G12V1(Group12Var1),
+    /// 32-bit analog output
+    G41V1(Group41Var1),
+    /// 16-bit analog output
+    G41V2(Group41Var2),
+    /// single-precision analog output
+    G41V3(Group41Var3),
+    /// double-precision analog output
+    G41V4(Group41Var4),
+}
+
+impl From<Group12Var1> for Control {
+    fn from(x: Group12Var1) -> Self {
+        Control::G12V1(x)
+    }
+}
+
+impl From<Group41Var1> for Control {
+    fn from(x: Group41Var1) -> Self {
+        Control::G41V1(x)
+    }
+}
+
+impl From<Group41Var2> for Control {
+    fn from(x: Group41Var2) -> Self {
+        Control::G41V2(x)
+    }
+}
+
+impl From<Group41Var3> for Control {
+    fn from(x: Group41Var3) -> Self {
+        Control::G41V3(x)
+    }
+}
+
+impl From<Group41Var4> for Control {
+    fn from(x: Group41Var4) -> Self {
+        Control::G41V4(x)
+    }
+}
+
+/// Notification of a [ControlHandler] delivered as a value on a channel
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ControlEvent {
+    /// the controls of a request are about to be processed
+    BeginFragment,
+    /// a control point was selected
+    Select {
+        /// value of the control
+        control: Control,
+        /// index of the control
+        index: u16,
+        /// status returned by the handler
+        status: CommandStatus,
+    },
+    /// a control point was operated
+    Operate {
+        /// value of the control
+        control: Control,
+        /// index of the control
+        index: u16,
+        /// how the master requested the operation
+        op_type: OperateType,
+        /// status returned by the handler, `None` if the operation completes asynchronously
+        status: Option<CommandStatus>,
+    },
+    /// all of the controls of a request have been processed
+    EndFragment,
+}
+
+/// Wrap a [ControlHandler] so that every select and operate, along with its outcome, is also sent
+/// to a bounded channel
+///
+/// The statuses of the controls are still decided by `handler`, since they are part of the
+/// response. Notifications are discarded if more than `capacity` are waiting.
+pub fn control_channel(
+    handler: Box<dyn ControlHandler>,
+    capacity: usize,
+) -> (Box<dyn ControlHandler>, EventReceiver<ControlEvent>) {
+    let (tx, rx) = event_channel(capacity);
+    (
+        Box::new(ControlEventHandler {
+            inner: handler,
+            events: tx,
+        }),
+        rx,
+    )
+}
+
+struct ControlEventHandler {
+    inner: Box<dyn ControlHandler>,
+    events: EventSender<ControlEvent>,
+}
+
+impl ControlHandler for ControlEventHandler {
+    fn begin_fragment(&mut self) {
+        self.inner.begin_fragment();
+        self.events.send(ControlEvent::BeginFragment);
+    }
+
+    fn end_fragment(&mut self) {
+        self.inner.end_fragment();
+        self.events.send(ControlEvent::EndFragment);
+    }
+}
+
+impl<T> ControlSupport<T> for ControlEventHandler
+where
+    T: Copy + Into<Control>,
+    dyn ControlHandler: ControlSupport<T>,
+{
+    fn select(&mut self, control: T, index: u16, database: &mut Database) -> CommandStatus {
+        let status = self.inner.select(control, index, database);
+        self.events.send(ControlEvent::Select {
+            control: control.into(),
+            index,
+            status,
+        });
+        status
+    }
+
+    fn operate(
+        &mut self,
+        control: T,
+        index: u16,
+        op_type: OperateType,
+        database: &mut Database,
+    ) -> CommandStatus {
+        let status = self.inner.operate(control, index, op_type, database);
+        self.events.send(ControlEvent::Operate {
+            control: control.into(),
+            index,
+            op_type,
+            status: Some(status),
+        });
+        status
+    }
+
+    fn begin_operate(
+        &mut self,
+        control: T,
+        index: u16,
+        op_type: OperateType,
+        database: &mut Database,
+    ) -> OperateResult {
+        let result = self.inner.begin_operate(control, index, op_type, database);
+        let status = match &result {
+            OperateResult::Complete(status) => Some(*status),
+            OperateResult::Pending(_) => None,
+        };
+        self.events.send(ControlEvent::Operate {
+            control: control.into(),
+            index,
+            op_type,
+            status,
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn information_is_sent_to_the_channel() {
+        let (mut info, mut rx) = information_channel(8);
+        info.enter_unsolicited_confirm_wait(Sequence::new(2));
+        info.unsolicited_confirm_timeout(Sequence::new(2), true);
+        info.clear_restart_iin();
+
+        assert_eq!(
+            rx.try_next(),
+            Some(InformationEvent::EnterUnsolicitedConfirmWait(
+                Sequence::new(2)
+            ))
+        );
+        assert_eq!(
+            rx.try_next(),
+            Some(InformationEvent::UnsolicitedConfirmTimeout {
+                ecsn: Sequence::new(2),
+                retry: true
+            })
+        );
+        assert_eq!(rx.try_next(), Some(InformationEvent::ClearRestartIin));
+        assert_eq!(rx.try_next(), None);
+    }
+
+    #[test]
+    fn control_fragments_are_sent_to_the_channel() {
+        let (mut handler, mut rx) = control_channel(DefaultControlHandler::create(), 8);
+        handler.begin_fragment();
+        handler.end_fragment();
+
+        assert_eq!(rx.try_next(), Some(ControlEvent::BeginFragment));
+        assert_eq!(rx.try_next(), Some(ControlEvent::EndFragment));
+        assert_eq!(rx.try_next(), None);
+    }
+}
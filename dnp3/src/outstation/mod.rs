@@ -1,9 +1,18 @@
+pub use audit::*;
 pub use config::*;
+pub use events::*;
+pub use statistics::UnsolicitedStatistics;
 pub use traits::*;
 
-use crate::app::Shutdown;
-use crate::decode::DecodeLevel;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::app::{Anomaly, Shutdown};
+use crate::decode::{DecodeEventHandler, DecodeLevel, DecodeSink, RecentFragment, WireTap};
+use crate::link::statistics::LinkCounters;
+use crate::link::{EndpointAddress, LinkActivity, LinkStatistics, TransportStatistics};
 use crate::outstation::database::{Database, DatabaseHandle};
+use crate::outstation::statistics::UnsolicitedCounters;
 use crate::outstation::task::{ConfigurationChange, OutstationMessage};
 use crate::util::channel::Sender;
 
@@ -14,13 +23,18 @@ pub mod database;
 
 /// wraps an outstation task so that it can switch communication sessions
 pub(crate) mod adapter;
+mod audit;
 mod config;
 /// functionality for processing control requests
 pub(crate) mod control;
 /// handling of deferred read requests
 pub(crate) mod deferred;
+/// notifications delivered on channels instead of callbacks
+mod events;
 /// outstation session
 pub(crate) mod session;
+/// counters of the unsolicited reporting
+pub(crate) mod statistics;
 /// async outstation task
 pub(crate) mod task;
 mod traits;
@@ -33,6 +47,9 @@ mod tests;
 pub struct OutstationHandle {
     database: DatabaseHandle,
     sender: Sender<OutstationMessage>,
+    link_counters: Arc<LinkCounters>,
+    unsolicited_counters: Arc<UnsolicitedCounters>,
+    master_address: EndpointAddress,
 }
 
 impl OutstationHandle {
@@ -52,7 +69,135 @@ impl OutstationHandle {
         Ok(())
     }
 
-    pub(crate) async fn shutdown(&mut self) -> Result<(), Shutdown> {
+    /// Delay every subsequent solicited response by the specified amount, e.g. to exercise the
+    /// response timeout of a master. `None` removes the delay.
+    #[cfg(any(test, feature = "sim"))]
+    pub async fn set_response_delay(&mut self, delay: Option<Duration>) -> Result<(), Shutdown> {
+        self.sender
+            .send(ConfigurationChange::SetResponseDelay(delay).into())
+            .await?;
+        Ok(())
+    }
+
+    /// Set IIN bits in every subsequent response regardless of the state of the outstation,
+    /// including bits that are reserved or that contradict that state. `Iin::default()` clears
+    /// the forced bits.
+    #[cfg(any(test, feature = "sim"))]
+    pub async fn set_forced_iin(&mut self, iin: crate::app::Iin) -> Result<(), Shutdown> {
+        self.sender
+            .send(ConfigurationChange::SetForcedIin(iin).into())
+            .await?;
+        Ok(())
+    }
+
+    /// Get a snapshot of the link-layer counters of the outstation
+    pub fn get_link_statistics(&self) -> LinkStatistics {
+        self.link_counters.snapshot()
+    }
+
+    /// Get a snapshot of the transport-layer counters of the outstation
+    pub fn get_transport_statistics(&self) -> TransportStatistics {
+        self.link_counters.transport_snapshot()
+    }
+
+    /// Get a snapshot of the unsolicited reporting of the outstation, including the number of
+    /// events waiting to be confirmed by the master
+    pub fn get_unsolicited_statistics(&self) -> UnsolicitedStatistics {
+        self.unsolicited_counters
+            .snapshot(self.database.num_events())
+    }
+
+    /// Get the age of the most recent link-layer activity from the master
+    ///
+    /// Returns `None` if no valid frame has been received from the master
+    pub fn get_link_activity(&self) -> Option<LinkActivity> {
+        self.link_counters.activity(self.master_address)
+    }
+
+    /// Send the decoded link, transport, and application layer output of the outstation to a sink
+    ///
+    /// `None` reverts to logging the output with `tracing`
+    pub fn set_decode_sink(&self, sink: Option<DecodeSink>) {
+        self.link_counters.decode().set_sink(sink);
+    }
+
+    /// Receive structured events for every frame, segment, fragment, and object header sent or
+    /// received by the outstation
+    ///
+    /// `None` removes the handler
+    pub fn set_decode_event_handler(&self, handler: Option<Box<dyn DecodeEventHandler>>) {
+        self.link_counters.decode().set_event_handler(handler);
+    }
+
+    /// Keep the last `capacity` fragments sent or received by the outstation, retrievable with
+    /// [OutstationHandle::get_recent_fragments]
+    ///
+    /// A capacity of zero stops recording and discards the fragments already kept
+    pub fn set_recent_fragment_capacity(&self, capacity: usize) {
+        self.link_counters.decode().set_recent_capacity(capacity);
+    }
+
+    /// Get the fragments recently sent or received by the outstation, oldest first
+    pub fn get_recent_fragments(&self) -> Vec<RecentFragment> {
+        self.link_counters.decode().recent_fragments()
+    }
+
+    /// Receive the raw bytes, link frames, transport segments, and application fragments sent or
+    /// received by the outstation, tagged with their layer, direction, and time
+    ///
+    /// `None` removes the tap
+    pub fn set_wire_tap(&self, tap: Option<Box<dyn WireTap>>) {
+        self.link_counters.decode().set_wire_tap(tap);
+    }
+
+    /// Get the deviations from the specification observed from the master, with a count per
+    /// type of anomaly
+    pub fn get_anomalies(&self) -> Vec<Anomaly> {
+        self.link_counters.anomalies().snapshot()
+    }
+
+    /// Reset the counts of the anomalies observed from the master
+    pub fn clear_anomalies(&self) {
+        self.link_counters.anomalies().clear()
+    }
+
+    /// Start recording every link-layer frame sent or received by the outstation
+    ///
+    /// Replaces any capture already in progress. The capture stops automatically if writing to
+    /// it fails.
+    #[cfg(feature = "pcap")]
+    pub fn start_capture(&self, capture: crate::link::FrameCapture) {
+        self.link_counters.capture().start(capture);
+    }
+
+    /// Stop recording frames, flushing the capture if one is in progress
+    #[cfg(feature = "pcap")]
+    pub fn stop_capture(&self) -> std::io::Result<()> {
+        self.link_counters.capture().stop()
+    }
+
+    /// Returns true if a valid frame was received from the master within `max_age`
+    pub fn is_alive(&self, max_age: Duration) -> bool {
+        self.get_link_activity()
+            .map(|activity| activity.is_alive(max_age))
+            .unwrap_or(false)
+    }
+
+    /// Shut down the outstation once it has finished the request or unsolicited series that is
+    /// in progress, if any
+    ///
+    /// No new requests are handled or unsolicited responses started once the shutdown has been
+    /// requested. Completes when the outstation task has exited, including if it had already
+    /// exited before this was called.
+    pub async fn shutdown(&mut self) {
+        let (tx, rx) = crate::tokio::sync::oneshot::channel();
+        if self.sender.send(OutstationMessage::Drain(tx)).await.is_ok() {
+            // the sender is dropped when the task exits
+            let _ = rx.await;
+        }
+    }
+
+    pub(crate) async fn stop(&mut self) -> Result<(), Shutdown> {
         self.sender.send(OutstationMessage::Shutdown).await?;
         Ok(())
     }
@@ -1,4 +1,6 @@
 use std::borrow::BorrowMut;
+use std::num::NonZeroU16;
+use std::sync::Arc;
 
 use tracing::Instrument;
 use xxhash_rust::xxh64::xxh64;
@@ -7,25 +9,38 @@ use crate::app::control::CommandStatus;
 use crate::app::format::write::HeaderWriter;
 use crate::app::gen::all::AllObjectsVariation;
 use crate::app::gen::count::CountVariation;
+use crate::app::gen::prefixed::PrefixedVariation;
 use crate::app::gen::ranged::RangedVariation;
 use crate::app::parse::count::CountSequence;
+use crate::app::parse::free_format::FreeFormatVariation;
 use crate::app::parse::parser::{HeaderCollection, HeaderDetails, Request};
-use crate::app::variations::{Group50Var3, Group52Var1, Group52Var2};
+use crate::app::parse::prefix::Prefix;
+use crate::app::parse::traits::Index;
+use crate::app::variations::{Group50Var3, Group50Var4, Group52Var1, Group52Var2};
 use crate::app::*;
 use crate::app::{ControlField, Iin, Iin1, Iin2, ResponseFunction, ResponseHeader};
-use crate::decode::DecodeLevel;
+use crate::decode::{DecodeLevel, TracingConfig};
 use crate::link::error::LinkError;
 use crate::link::header::BroadcastConfirmMode;
-use crate::link::EndpointAddress;
+use crate::link::statistics::LinkCounters;
+use crate::link::{EndpointAddress, LinkConfig};
 use crate::master::EventClasses;
 use crate::outstation::config::OutstationConfig;
-use crate::outstation::config::{BufferSize, Feature};
-use crate::outstation::control::collection::{ControlCollection, ControlTransaction};
+use crate::outstation::config::{
+    BroadcastAddresses, BufferSize, DuplicateDetection, Feature, FunctionCodes,
+};
+use crate::outstation::control::collection::{
+    ControlCollection, ControlTransaction, PendingControl,
+};
 use crate::outstation::control::select::SelectState;
 use crate::outstation::database::{DatabaseHandle, ResponseInfo};
 use crate::outstation::deferred::DeferredRead;
+use crate::outstation::statistics::UnsolicitedCounters;
 use crate::outstation::task::{ConfigurationChange, OutstationMessage};
 use crate::outstation::traits::*;
+use crate::outstation::{
+    AuditLogger, ObjectSummary, RequestOutcome, RequestRecord, ResponseOutcome, ResponseRecord,
+};
 use crate::transport::{
     FragmentInfo, RequestGuard, TransportReader, TransportRequest, TransportRequestError,
     TransportWriter,
@@ -115,7 +130,9 @@ impl ResponseInfo {
 #[derive(Copy, Clone)]
 struct LastValidRequest {
     seq: Sequence,
-    request_hash: u64,
+    function: FunctionCode,
+    // hash of the object headers, `None` if the request wasn't checked for duplicates
+    request_hash: Option<u64>,
     response: Option<Response>,
     series: Option<ResponseSeries>,
 }
@@ -123,12 +140,14 @@ struct LastValidRequest {
 impl LastValidRequest {
     fn new(
         seq: Sequence,
-        request_hash: u64,
+        function: FunctionCode,
+        request_hash: Option<u64>,
         response: Option<Response>,
         series: Option<ResponseSeries>,
     ) -> Self {
         LastValidRequest {
             seq,
+            function,
             request_hash,
             response,
             series,
@@ -141,12 +160,24 @@ pub(crate) struct SessionConfig {
     master_address: EndpointAddress,
     confirm_timeout: std::time::Duration,
     select_timeout: std::time::Duration,
+    max_concurrent_selects: u16,
     broadcast: Feature,
+    functions: FunctionCodes,
+    broadcast_addresses: BroadcastAddresses,
     unsolicited: Feature,
     max_unsolicited_retries: Option<usize>,
     unsolicited_retry_delay: std::time::Duration,
     keep_alive_timeout: Option<std::time::Duration>,
+    link: LinkConfig,
     max_controls_per_request: Option<u16>,
+    control_completion_timeout: std::time::Duration,
+    max_events_per_solicited_response: Option<NonZeroU16>,
+    max_events_per_unsolicited_response: Option<NonZeroU16>,
+    change_coalescing_delay: Option<std::time::Duration>,
+    duplicate_detection: DuplicateDetection,
+    clock: Clock,
+    tracing: TracingConfig,
+    audit_logger: Option<Arc<dyn AuditLogger>>,
 }
 
 pub(crate) struct SessionParameters {
@@ -162,18 +193,30 @@ impl From<OutstationConfig> for SessionConfig {
             master_address: config.master_address,
             confirm_timeout: config.confirm_timeout,
             select_timeout: config.select_timeout,
+            max_concurrent_selects: config.max_concurrent_selects,
             broadcast: config.features.broadcast,
+            functions: config.functions,
+            broadcast_addresses: config.broadcast_addresses,
             unsolicited: config.features.unsolicited,
             max_unsolicited_retries: config.max_unsolicited_retries,
             unsolicited_retry_delay: config.unsolicited_retry_delay,
             keep_alive_timeout: config.keep_alive_timeout,
+            link: config.link,
             max_controls_per_request: config.max_controls_per_request,
+            control_completion_timeout: config.control_completion_timeout,
+            max_events_per_solicited_response: config.max_events_per_solicited_response,
+            max_events_per_unsolicited_response: config.max_events_per_unsolicited_response,
+            change_coalescing_delay: config.change_coalescing_delay,
+            duplicate_detection: config.duplicate_detection,
+            clock: config.clock,
+            tracing: config.tracing,
+            audit_logger: config.audit_logger,
         }
     }
 }
 
-impl From<OutstationConfig> for SessionParameters {
-    fn from(x: OutstationConfig) -> Self {
+impl From<&OutstationConfig> for SessionParameters {
+    fn from(x: &OutstationConfig) -> Self {
         SessionParameters {
             max_read_headers_per_request: x
                 .max_read_request_headers
@@ -188,7 +231,39 @@ impl From<OutstationConfig> for SessionParameters {
 enum UnsolicitedState {
     /// need to perform NULL unsolicited
     NullRequired,
-    Ready(Option<crate::tokio::time::Instant>),
+    Ready(Option<crate::app::Instant>),
+}
+
+/// response to a request with controls that complete asynchronously
+///
+/// The response is held back while the session keeps running, and is written once every control
+/// completes, the completion timeout elapses, or another request arrives.
+struct PendingResponse {
+    request: LastValidRequest,
+    controls: Vec<PendingControl>,
+    deadline: crate::app::Instant,
+}
+
+impl PendingResponse {
+    /// wait until every control completes or the completion timeout elapses, never returning if
+    /// no response is held back
+    async fn wait(pending: Option<&mut Self>, clock: Clock) {
+        let pending = match pending {
+            Some(x) => x,
+            None => return crate::util::future::forever().await,
+        };
+
+        let deadline = pending.deadline;
+        let controls = &mut pending.controls;
+        crate::tokio::select! {
+            _ = async {
+                for control in controls.iter_mut() {
+                    control.wait().await;
+                }
+            } => {}
+            _ = clock.sleep_until(deadline) => {}
+        }
+    }
 }
 
 /// state that mutates while the session runs
@@ -196,11 +271,13 @@ struct SessionState {
     restart_iin_asserted: bool,
     enabled_unsolicited_classes: EventClasses,
     last_valid_request: Option<LastValidRequest>,
-    select: Option<SelectState>,
+    selects: Vec<SelectState>,
+    pending_controls: Vec<PendingControl>,
+    pending_response: Option<PendingResponse>,
     unsolicited: UnsolicitedState,
     unsolicited_seq: Sequence,
     deferred_read: DeferredRead,
-    last_recorded_time: Option<crate::tokio::time::Instant>,
+    last_recorded_time: Option<crate::app::Instant>,
     last_broadcast_type: Option<BroadcastConfirmMode>,
 }
 
@@ -210,7 +287,9 @@ impl SessionState {
             enabled_unsolicited_classes: EventClasses::none(),
             restart_iin_asserted: true,
             last_valid_request: None,
-            select: None,
+            selects: Vec::new(),
+            pending_controls: Vec::new(),
+            pending_response: None,
             unsolicited: UnsolicitedState::NullRequired,
             unsolicited_seq: Sequence::default(),
             deferred_read: DeferredRead::new(max_read_headers),
@@ -222,9 +301,46 @@ impl SessionState {
     // reset items that should reset between communication (TCP) sessions
     fn reset(&mut self) {
         self.last_valid_request = None;
-        self.select = None;
+        self.selects.clear();
+        self.pending_controls.clear();
+        self.pending_response = None;
         self.deferred_read.clear();
     }
+
+    /// record a successful SELECT, replacing the oldest one when `max` are already held
+    fn add_select(&mut self, select: SelectState, max: u16) {
+        self.selects
+            .retain(|x| x.object_hash() != select.object_hash());
+        if self.selects.len() >= max as usize {
+            self.selects.remove(0);
+        }
+        self.selects.push(select);
+    }
+
+    /// remove the SELECT of the same object headers as an OPERATE
+    fn take_select(&mut self, object_hash: u64) -> Option<SelectState> {
+        let pos = self
+            .selects
+            .iter()
+            .position(|x| x.object_hash() == object_hash);
+        match pos {
+            Some(pos) => Some(self.selects.remove(pos)),
+            None => {
+                if !self.selects.is_empty() {
+                    tracing::warn!("received OPERATE with different header than SELECT");
+                }
+                None
+            }
+        }
+    }
+
+    /// discard the SELECTs that the request doesn't directly follow, and chain the others to it
+    fn chain_selects(&mut self, seq: Sequence, frame_id: u32) {
+        self.selects.retain(|x| x.is_followed_by(seq, frame_id));
+        for select in self.selects.iter_mut() {
+            select.chain(seq, frame_id);
+        }
+    }
 }
 
 pub(crate) struct OutstationSession {
@@ -236,7 +352,22 @@ pub(crate) struct OutstationSession {
     application: Box<dyn OutstationApplication>,
     info: Box<dyn OutstationInformation>,
     control_handler: Box<dyn ControlHandler>,
-    next_link_status: Option<crate::tokio::time::Instant>,
+    next_link_status: Option<crate::app::Instant>,
+    unanswered_link_status_requests: usize,
+    /// unsolicited responses are held back until this time so that changes accumulate
+    coalesce_changes_until: Option<crate::app::Instant>,
+    counters: Arc<LinkCounters>,
+    unsolicited_counters: Arc<UnsolicitedCounters>,
+    /// delay applied before each solicited response, used by simulations
+    response_delay: Option<std::time::Duration>,
+    /// bits set in every response regardless of the state of the outstation, used by simulations
+    forced_iin: Iin,
+    /// completed when the session is dropped after a shutdown has been requested
+    shutdown: Vec<crate::tokio::sync::oneshot::Sender<()>>,
+    /// connections are closed after this time without responses, used by dial-up clients
+    idle_timeout: Option<std::time::Duration>,
+    /// time at which the last solicited or unsolicited response was written
+    last_activity: crate::app::Instant,
 }
 
 enum Confirm {
@@ -259,12 +390,13 @@ enum UnsolicitedWaitResult {
     Complete(UnsolicitedResult),
 }
 
+// the hash of the object headers is only calculated when the request is checked for duplicates
 enum FragmentType<'a> {
-    MalformedRequest(u64, ObjectParseError),
-    NewRead(u64, HeaderCollection<'a>),
-    RepeatRead(u64, Option<Response>, HeaderCollection<'a>),
-    NewNonRead(u64, HeaderCollection<'a>),
-    RepeatNonRead(u64, Option<Response>),
+    MalformedRequest(Option<u64>, ObjectParseError),
+    NewRead(Option<u64>, HeaderCollection<'a>),
+    RepeatRead(Option<u64>, Option<Response>, HeaderCollection<'a>),
+    NewNonRead(Option<u64>, HeaderCollection<'a>),
+    RepeatNonRead(Option<u64>, Option<Response>),
     Broadcast(BroadcastConfirmMode),
     SolicitedConfirm(Sequence),
     UnsolicitedConfirm(Sequence),
@@ -281,6 +413,8 @@ enum ConfirmAction {
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub(crate) enum RunError {
     Link(LinkError),
+    /// no response was written for the idle timeout
+    IdleTimeout,
     Shutdown,
 }
 
@@ -297,6 +431,7 @@ impl From<LinkError> for RunError {
 }
 
 impl OutstationSession {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         messages: Receiver<OutstationMessage>,
         config: SessionConfig,
@@ -304,31 +439,67 @@ impl OutstationSession {
         application: Box<dyn OutstationApplication>,
         information: Box<dyn OutstationInformation>,
         control_handler: Box<dyn ControlHandler>,
+        counters: Arc<LinkCounters>,
+        unsolicited_counters: Arc<UnsolicitedCounters>,
     ) -> Self {
-        let next_link_status = config
-            .keep_alive_timeout
-            .map(|delay| crate::tokio::time::Instant::now() + delay);
+        let now = config.clock.now();
+        let next_link_status = config.keep_alive_timeout.map(|delay| now + delay);
 
         Self {
             messages,
             config,
-            sol_tx_buffer: param.sol_tx_buffer_size.create_buffer(),
-            unsol_tx_buffer: param.unsol_tx_buffer_size.create_buffer(),
+            sol_tx_buffer: param
+                .sol_tx_buffer_size
+                .create_buffer(counters.buffer_pool()),
+            unsol_tx_buffer: param
+                .unsol_tx_buffer_size
+                .create_buffer(counters.buffer_pool()),
             state: SessionState::new(param.max_read_headers_per_request),
             application,
             info: information,
             control_handler,
             next_link_status,
+            unanswered_link_status_requests: 0,
+            coalesce_changes_until: None,
+            counters,
+            unsolicited_counters,
+            response_delay: None,
+            forced_iin: Iin::default(),
+            shutdown: Vec::new(),
+            idle_timeout: None,
+            last_activity: now,
         }
     }
 
+    /// Clock used for the timeouts of the session
+    pub(crate) fn clock(&self) -> Clock {
+        self.config.clock.clone()
+    }
+
+    /// end sessions once no response has been written for `idle_timeout`
+    #[cfg(any(test, feature = "tcp"))]
+    pub(crate) fn set_idle_timeout(&mut self, idle_timeout: Option<std::time::Duration>) {
+        self.idle_timeout = idle_timeout;
+    }
+
+    fn anomaly(&self, kind: AnomalyKind, detail: std::fmt::Arguments) {
+        self.counters.anomalies().record(kind, detail);
+    }
+
     /// used when the there is no running IO to process outstation messages
     pub(crate) async fn process_messages(&mut self) -> Result<(), Shutdown> {
         loop {
+            if self.is_shutting_down() {
+                return Err(Shutdown);
+            }
             self.handle_next_message().await?;
         }
     }
 
+    fn is_shutting_down(&self) -> bool {
+        !self.shutdown.is_empty()
+    }
+
     pub(crate) async fn run(
         &mut self,
         io: &mut PhysLayer,
@@ -336,6 +507,7 @@ impl OutstationSession {
         writer: &mut TransportWriter,
         database: &mut DatabaseHandle,
     ) -> RunError {
+        self.last_activity = self.config.clock.now();
         loop {
             if let Err(err) = self.run_idle_state(io, reader, writer, database).await {
                 return err;
@@ -372,14 +544,25 @@ impl OutstationSession {
 
         let len = std::cmp::max(cursor.written().len(), response.size);
 
-        writer
+        let result = writer
             .write(
                 io,
                 self.config.decode_level,
                 self.config.master_address.wrap(),
                 self.unsol_tx_buffer.get(len).unwrap(),
             )
-            .await
+            .await;
+        self.last_activity = self.config.clock.now();
+
+        Self::audit_response(
+            self.config.audit_logger.as_deref(),
+            self.config.master_address,
+            response.header,
+            self.unsol_tx_buffer.get(len).unwrap(),
+            &result,
+        );
+
+        result
     }
 
     async fn write_solicited(
@@ -398,11 +581,53 @@ impl OutstationSession {
             }
         }
 
+        if let Some(delay) = self.response_delay {
+            let clock = self.config.clock.clone();
+            clock.sleep_until(clock.now() + delay).await;
+        }
+
         self.repeat_solicited(io, writer, response).await?;
 
         Ok(response)
     }
 
+    /// hold back the response to a request with controls that complete asynchronously, returning
+    /// false if the response can be written right away
+    fn defer_response(&mut self, request: LastValidRequest) -> bool {
+        let controls = std::mem::take(&mut self.state.pending_controls);
+        if controls.is_empty() || request.response.is_none() {
+            return false;
+        }
+
+        self.state.pending_response = Some(PendingResponse {
+            request,
+            controls,
+            deadline: self.config.clock.now() + self.config.control_completion_timeout,
+        });
+        true
+    }
+
+    /// take the held back response after writing the final status of each control into it
+    fn take_pending_response(&mut self) -> Option<LastValidRequest> {
+        let pending = self.state.pending_response.take()?;
+        let mut request = pending.request;
+
+        let mut cursor = self.sol_tx_buffer.write_cursor();
+        for mut control in pending.controls {
+            let status = control.status();
+            if status == CommandStatus::NotSupported {
+                if let Some(response) = &mut request.response {
+                    response.header.iin |= Iin2::PARAMETER_ERROR;
+                }
+            }
+            let _ = cursor.at_pos(control.status_offset, |cursor| {
+                cursor.write_u8(status.as_u8())
+            });
+        }
+
+        Some(request)
+    }
+
     async fn repeat_solicited(
         &mut self,
         io: &mut PhysLayer,
@@ -414,14 +639,25 @@ impl OutstationSession {
 
         let len = std::cmp::max(cursor.written().len(), response.size);
 
-        writer
+        let result = writer
             .write(
                 io,
                 self.config.decode_level,
                 self.config.master_address.wrap(),
                 self.sol_tx_buffer.get(len).unwrap(),
             )
-            .await
+            .await;
+        self.last_activity = self.config.clock.now();
+
+        Self::audit_response(
+            self.config.audit_logger.as_deref(),
+            self.config.master_address,
+            response.header,
+            self.sol_tx_buffer.get(len).unwrap(),
+            &result,
+        );
+
+        result
     }
 
     async fn run_idle_state(
@@ -435,8 +671,18 @@ impl OutstationSession {
         self.handle_one_request_from_idle(io, reader, writer, database)
             .await?;
 
-        // check to see if we should perform unsolicited
-        let deadline = self.check_unsolicited(io, reader, writer, database).await?;
+        // a shutdown waits for the request above, including controls that complete
+        // asynchronously, but no further work is started
+        if self.is_shutting_down() && self.state.pending_response.is_none() {
+            return Err(RunError::Shutdown);
+        }
+
+        // check to see if we should perform unsolicited, which waits for a held back response
+        let deadline = if self.state.pending_response.is_none() {
+            self.check_unsolicited(io, reader, writer, database).await?
+        } else {
+            None
+        };
 
         // handle a deferred read request if it was produced during unsolicited
         self.handle_deferred_read(io, reader, writer, database)
@@ -447,14 +693,34 @@ impl OutstationSession {
 
         let deadline = match deadline {
             Some(deadline) => match self.next_link_status {
-                Some(link_deadline) => {
-                    Some(crate::tokio::time::Instant::min(deadline, link_deadline))
-                }
+                Some(link_deadline) => Some(crate::app::Instant::min(deadline, link_deadline)),
                 None => Some(deadline),
             },
             None => self.next_link_status,
         };
 
+        // close the connection of a dial-up client once the work is done
+        let deadline = match self.idle_timeout {
+            Some(timeout) => {
+                let idle_deadline = self.last_activity + timeout;
+                if self.config.clock.now() >= idle_deadline {
+                    return Err(RunError::IdleTimeout);
+                }
+                Some(deadline.map_or(idle_deadline, |x| {
+                    crate::app::Instant::min(x, idle_deadline)
+                }))
+            }
+            None => deadline,
+        };
+
+        self.release_buffers(database);
+
+        // the held back response is moved out so that its controls can be awaited alongside the
+        // other events
+        let mut pending = self.state.pending_response.take();
+        let mut controls_complete = false;
+        let clock = self.config.clock.clone();
+
         // wait for an event
         crate::tokio::select! {
             frame_read = reader.read(io, self.config.decode_level) => {
@@ -463,23 +729,67 @@ impl OutstationSession {
             }
             _ = database.wait_for_change() => {
                 // wake for unsolicited here
+                self.on_database_change();
             }
-            res = self.sleep_until(deadline) => {
+            res = self.sleep_until(deadline, true) => {
                 res?
                 // just wake up
             }
+            _ = PendingResponse::wait(pending.as_mut(), clock) => {
+                controls_complete = true;
+            }
+        }
+
+        self.state.pending_response = pending;
+        if controls_complete {
+            if let Some(result) = self.take_pending_response() {
+                self.respond_from_idle(io, reader, writer, database, result)
+                    .await?;
+            }
         }
 
         Ok(())
     }
 
+    /// start holding back unsolicited responses if configured to coalesce changes
+    ///
+    /// The delay starts with the first change so that a steady stream of changes can't postpone
+    /// the report indefinitely.
+    fn on_database_change(&mut self) {
+        if self.coalesce_changes_until.is_some() {
+            return;
+        }
+        if let Some(delay) = self.config.change_coalescing_delay {
+            self.coalesce_changes_until = Some(self.config.clock.now() + delay);
+        }
+    }
+
+    /// return the buffers to the pool while waiting, keeping the held back response or the last
+    /// solicited response so that it can be repeated if the master retries its request
+    fn release_buffers(&mut self, database: &mut DatabaseHandle) {
+        let retain = self
+            .state
+            .pending_response
+            .as_ref()
+            .map(|x| x.request)
+            .or(self.state.last_valid_request)
+            .and_then(|x| x.response)
+            .map_or(0, |x| x.size);
+        self.sol_tx_buffer.release(retain);
+        self.unsol_tx_buffer.release(0);
+        // sessions that pool their buffers also give up the values copied for READ requests
+        if self.counters.buffer_pool().is_some() {
+            database.release_selection();
+        }
+    }
+
     async fn check_unsolicited(
         &mut self,
         io: &mut PhysLayer,
         reader: &mut TransportReader,
         writer: &mut TransportWriter,
         database: &mut DatabaseHandle,
-    ) -> Result<Option<crate::tokio::time::Instant>, RunError> {
+    ) -> Result<Option<crate::app::Instant>, RunError> {
         if self.config.unsolicited.is_disabled() {
             return Ok(None);
         }
@@ -493,7 +803,7 @@ impl OutstationSession {
                 {
                     UnsolicitedResult::Timeout | UnsolicitedResult::ReturnToIdle => {
                         self.state.unsolicited = UnsolicitedState::NullRequired;
-                        Ok(Some(crate::tokio::time::Instant::now()))
+                        Ok(Some(self.config.clock.now()))
                     }
                     UnsolicitedResult::Confirmed => {
                         self.state.unsolicited = UnsolicitedState::Ready(None);
@@ -503,11 +813,28 @@ impl OutstationSession {
             }
             UnsolicitedState::Ready(deadline) => {
                 if let Some(deadline) = deadline {
-                    if crate::tokio::time::Instant::now() < deadline {
+                    if self.config.clock.now() < deadline {
                         return Ok(Some(deadline)); // not ready yet
                     }
                 }
 
+                // give further changes time to accumulate
+                if let Some(until) = self.coalesce_changes_until {
+                    if self.config.clock.now() < until {
+                        return Ok(Some(until));
+                    }
+                    self.coalesce_changes_until = None;
+                }
+
+                // build the response once the link has transmitted the previous frames, so that
+                // events accumulate instead of queueing up in responses behind them
+                if let Some(until) = writer.busy_until() {
+                    if self.has_unsolicited_events(database) {
+                        self.unsolicited_counters.on_deferred();
+                    }
+                    return Ok(Some(until));
+                }
+
                 // perform regular unsolicited
                 match self
                     .maybe_perform_unsolicited(io, reader, writer, database)
@@ -532,6 +859,14 @@ impl OutstationSession {
         }
     }
 
+    fn has_unsolicited_events(&self, database: &DatabaseHandle) -> bool {
+        let unwritten = database.get_events_info().unwritten_classes;
+        let enabled = self.state.enabled_unsolicited_classes;
+        (unwritten.class1 && enabled.class1)
+            || (unwritten.class2 && enabled.class2)
+            || (unwritten.class3 && enabled.class3)
+    }
+
     async fn check_link_status(
         &mut self,
         io: &mut PhysLayer,
@@ -539,7 +874,7 @@ impl OutstationSession {
     ) -> Result<(), RunError> {
         if let Some(next) = self.next_link_status {
             // Wait until we need to send the link status
-            if next > crate::tokio::time::Instant::now() {
+            if next > self.config.clock.now() {
                 return Ok(());
             }
 
@@ -551,7 +886,14 @@ impl OutstationSession {
                 )
                 .await?;
 
-            self.on_link_activity();
+            self.unanswered_link_status_requests += 1;
+            if self.unanswered_link_status_requests > self.config.link.retries {
+                // give up until the next keep-alive period
+                self.on_link_activity();
+            } else {
+                let timeout = self.config.link.ack_timeout_or(self.config.confirm_timeout);
+                self.next_link_status = Some(self.config.clock.now() + timeout);
+            }
         }
 
         Ok(())
@@ -605,7 +947,11 @@ impl OutstationSession {
     fn write_unsolicited_data(&mut self, database: &mut DatabaseHandle) -> Option<Response> {
         let mut cursor = self.unsol_tx_buffer.write_cursor();
         let _ = cursor.skip(ResponseHeader::LENGTH);
-        let count = database.write_unsolicited(self.state.enabled_unsolicited_classes, &mut cursor);
+        let count = database.write_unsolicited(
+            self.state.enabled_unsolicited_classes,
+            self.config.max_events_per_unsolicited_response,
+            &mut cursor,
+        );
 
         if count == 0 {
             return None;
@@ -635,6 +981,7 @@ impl OutstationSession {
         let response = self
             .write_unsolicited(io, writer, response, database)
             .await?;
+        self.unsolicited_counters.on_response();
 
         // enter unsolicited confirm wait state
         self.info.enter_unsolicited_confirm_wait(response.seq());
@@ -646,9 +993,19 @@ impl OutstationSession {
             retry_count = RetryCounter::new(Some(0));
         }
 
-        let mut deadline = self.new_confirm_deadline();
+        let mut deadline = self.new_unsolicited_confirm_deadline(writer);
+        // retries of the same response share a correlation ID
+        let cid = crate::decode::next_correlation_id();
 
         loop {
+            let span = self.config.tracing.task_span(
+                tracing::info_span!(
+                    "UnsolConfirmWait",
+                    "seq" = response.seq().value(),
+                    "cid" = cid
+                ),
+                cid,
+            );
             match self
                 .wait_for_unsolicited_confirm(
                     response.seq(),
@@ -658,10 +1015,7 @@ impl OutstationSession {
                     writer,
                     database,
                 )
-                .instrument(tracing::info_span!(
-                    "UnsolConfirmWait",
-                    "seq" = response.seq().value()
-                ))
+                .instrument(span)
                 .await?
             {
                 UnsolicitedWaitResult::ReadNext => {
@@ -671,8 +1025,8 @@ impl OutstationSession {
                 UnsolicitedWaitResult::Timeout => {
                     let mut retry = retry_count.decrement();
 
-                    // If a deferred read is pending, we want to exit
-                    if self.state.deferred_read.is_set() {
+                    // If a deferred read or a shutdown is pending, we want to exit
+                    if self.state.deferred_read.is_set() || self.is_shutting_down() {
                         retry = false;
                     }
 
@@ -684,9 +1038,10 @@ impl OutstationSession {
 
                     // perform a retry
                     self.repeat_unsolicited(io, writer, response).await?;
+                    self.unsolicited_counters.on_retry();
 
                     // update the deadline
-                    deadline = self.new_confirm_deadline();
+                    deadline = self.new_unsolicited_confirm_deadline(writer);
                 }
             }
         }
@@ -695,7 +1050,7 @@ impl OutstationSession {
     async fn wait_for_unsolicited_confirm(
         &mut self,
         uns_ecsn: Sequence,
-        deadline: crate::tokio::time::Instant,
+        deadline: crate::app::Instant,
         io: &mut PhysLayer,
         reader: &mut TransportReader,
         writer: &mut TransportWriter,
@@ -710,6 +1065,16 @@ impl OutstationSession {
             None => return Ok(UnsolicitedWaitResult::ReadNext),
             Some(TransportRequest::Request(info, request)) => {
                 self.on_link_activity();
+                // answer a previous request with controls that complete asynchronously first
+                if let Some(result) = self.take_pending_response() {
+                    if let Some(response) = result.response {
+                        let response = self.write_solicited(io, writer, response, database).await?;
+                        self.state.last_valid_request = Some(LastValidRequest {
+                            response: Some(response),
+                            ..result
+                        });
+                    }
+                }
                 (info, request)
             }
             Some(TransportRequest::LinkLayerMessage(_)) => {
@@ -732,9 +1097,12 @@ impl OutstationSession {
                         UnsolicitedResult::Confirmed,
                     ))
                 } else {
-                    tracing::warn!(
-                        "ignoring unsolicited confirm with wrong sequence number ({})",
-                        seq.value()
+                    self.anomaly(
+                        AnomalyKind::UnexpectedSequence,
+                        format_args!(
+                            "ignoring unsolicited confirm with wrong sequence number ({})",
+                            seq.value()
+                        ),
                     );
                     Ok(UnsolicitedWaitResult::ReadNext)
                 }
@@ -743,7 +1111,10 @@ impl OutstationSession {
                 if let Some(BroadcastConfirmMode::Mandatory) = self.state.last_broadcast_type {
                     self.state.last_broadcast_type = None
                 } else {
-                    tracing::warn!("ignoring solicited confirm");
+                    self.anomaly(
+                        AnomalyKind::UnexpectedFragment,
+                        format_args!("ignoring solicited confirm"),
+                    );
                 }
                 Ok(UnsolicitedWaitResult::ReadNext)
             }
@@ -769,18 +1140,24 @@ impl OutstationSession {
                     request.header.control.seq,
                     info.id,
                     objects,
+                    hash,
                 );
-                if let Some(response) = &mut response {
-                    *response = self
-                        .write_solicited(io, writer, *response, database)
-                        .await?;
-                }
-                self.state.last_valid_request = Some(LastValidRequest::new(
+                let result = LastValidRequest::new(
                     request.header.control.seq,
+                    request.header.function,
                     hash,
                     response,
                     None,
-                ));
+                );
+                // controls that complete asynchronously are reported once the session is idle
+                if !self.defer_response(result) {
+                    if let Some(response) = &mut response {
+                        *response = self
+                            .write_solicited(io, writer, *response, database)
+                            .await?;
+                    }
+                    self.state.last_valid_request = Some(LastValidRequest { response, ..result });
+                }
 
                 // Cancel unsolicited series if it's a DISABLE_UNSOLICITED
                 if request.header.function == FunctionCode::DisableUnsolicited {
@@ -819,12 +1196,12 @@ impl OutstationSession {
         &mut self,
         io: &mut PhysLayer,
         reader: &mut TransportReader,
-        deadline: crate::tokio::time::Instant,
+        deadline: crate::app::Instant,
     ) -> Result<Timeout, RunError> {
         loop {
             let decode_level = self.config.decode_level;
             crate::tokio::select! {
-                 res = self.sleep_until(Some(deadline)) => {
+                 res = self.sleep_until(Some(deadline), false) => {
                      res?;
                      return Ok(Timeout::Yes);
                  }
@@ -836,13 +1213,16 @@ impl OutstationSession {
         }
     }
 
+    /// sleep until the instant while handling messages, returning early on a shutdown request
+    /// if `wake_on_shutdown` is set
     async fn sleep_until(
         &mut self,
-        instant: Option<crate::tokio::time::Instant>,
+        instant: Option<crate::app::Instant>,
+        wake_on_shutdown: bool,
     ) -> Result<(), RunError> {
-        async fn sleep_only(instant: Option<crate::tokio::time::Instant>) {
+        async fn sleep_only(clock: Clock, instant: Option<crate::app::Instant>) {
             match instant {
-                Some(x) => crate::tokio::time::sleep_until(x).await,
+                Some(x) => clock.sleep_until(x).await,
                 None => {
                     // sleep forever
                     crate::util::future::forever().await;
@@ -851,12 +1231,16 @@ impl OutstationSession {
         }
 
         loop {
+            let clock = self.config.clock.clone();
             crate::tokio::select! {
-                 _ = sleep_only(instant) => {
+                 _ = sleep_only(clock, instant) => {
                         return Ok(());
                  }
                  res = self.handle_next_message() => {
                      res?;
+                     if wake_on_shutdown && self.is_shutting_down() {
+                         return Ok(());
+                     }
                  }
             }
         }
@@ -865,6 +1249,11 @@ impl OutstationSession {
     async fn handle_next_message(&mut self) -> Result<(), Shutdown> {
         match self.messages.receive().await? {
             OutstationMessage::Shutdown => Err(Shutdown),
+            OutstationMessage::Drain(tx) => {
+                tracing::info!("shutdown requested");
+                self.shutdown.push(tx);
+                Ok(())
+            }
             OutstationMessage::Configuration(change) => {
                 self.handle_config_change(change);
                 Ok(())
@@ -878,6 +1267,16 @@ impl OutstationSession {
                 tracing::info!("decode level changed to: {:?}", level);
                 self.config.decode_level = level;
             }
+            #[cfg(any(test, feature = "sim"))]
+            ConfigurationChange::SetResponseDelay(delay) => {
+                tracing::info!("response delay changed to: {:?}", delay);
+                self.response_delay = delay;
+            }
+            #[cfg(any(test, feature = "sim"))]
+            ConfigurationChange::SetForcedIin(iin) => {
+                tracing::info!("forced IIN changed to: {:?}", iin);
+                self.forced_iin = iin;
+            }
         }
     }
 
@@ -892,8 +1291,13 @@ impl OutstationSession {
             tracing::info!("handling deferred READ request");
             let (response, mut series) = self.write_read_response(database, true, x.seq, x.iin2);
             let response = self.write_solicited(io, writer, response, database).await?;
-            self.state.last_valid_request =
-                Some(LastValidRequest::new(x.seq, x.hash, Some(response), series));
+            self.state.last_valid_request = Some(LastValidRequest::new(
+                x.seq,
+                FunctionCode::Read,
+                x.hash,
+                Some(response),
+                series,
+            ));
 
             // check if an extra confirmation was added due to broadcast
             if response.header.control.con && series.is_none() {
@@ -902,11 +1306,9 @@ impl OutstationSession {
 
             if let Some(series) = series {
                 // enter the solicited confirm wait state
+                let span = self.sol_confirm_wait_span(series);
                 self.sol_confirm_wait(io, reader, writer, database, series)
-                    .instrument(tracing::info_span!(
-                        "SolConfirmWait",
-                        "ecsn" = series.ecsn.value()
-                    ))
+                    .instrument(span)
                     .await?;
             }
             self.state.deferred_read.clear();
@@ -925,32 +1327,21 @@ impl OutstationSession {
         let mut guard = reader.pop_request();
         match guard.get() {
             Some(TransportRequest::Request(info, request)) => {
-                self.on_link_activity();
-                if let Some(mut result) = self.process_request_from_idle(info, request, database) {
-                    // optional response
-                    if let Some(response) = &mut result.response {
-                        *response = self
-                            .write_solicited(io, writer, *response, database)
-                            .await?;
-
-                        // check if an extra confirmation was added due to broadcast
-                        if response.header.control.con && result.series.is_none() {
-                            result.series =
-                                Some(ResponseSeries::new(response.header.control.seq, true));
-                        }
-                    }
+                if let Some(result) = self.take_pending_response() {
+                    // answer the previous request first, leaving this one for the next pass
+                    guard.retain();
+                    drop(guard);
+                    return self
+                        .respond_from_idle(io, reader, writer, database, result)
+                        .await;
+                }
 
-                    self.state.last_valid_request = Some(result);
-
-                    // maybe start a response series
-                    if let Some(series) = result.series {
-                        drop(guard);
-                        // enter the solicited confirm wait state
-                        self.sol_confirm_wait(io, reader, writer, database, series)
-                            .instrument(tracing::info_span!(
-                                "SolConfirmWait",
-                                "ecsn" = series.ecsn.value()
-                            ))
+                self.on_link_activity();
+                let result = self.process_request_from_idle(info, request, database);
+                drop(guard);
+                if let Some(result) = result {
+                    if !self.defer_response(result) {
+                        self.respond_from_idle(io, reader, writer, database, result)
                             .await?;
                     }
                 }
@@ -968,6 +1359,42 @@ impl OutstationSession {
         Ok(())
     }
 
+    /// write the response to a request received from idle, entering the solicited confirm wait
+    /// state if the response requires confirmation
+    async fn respond_from_idle(
+        &mut self,
+        io: &mut PhysLayer,
+        reader: &mut TransportReader,
+        writer: &mut TransportWriter,
+        database: &mut DatabaseHandle,
+        mut result: LastValidRequest,
+    ) -> Result<(), RunError> {
+        // optional response
+        if let Some(response) = &mut result.response {
+            *response = self
+                .write_solicited(io, writer, *response, database)
+                .await?;
+
+            // check if an extra confirmation was added due to broadcast
+            if response.header.control.con && result.series.is_none() {
+                result.series = Some(ResponseSeries::new(response.header.control.seq, true));
+            }
+        }
+
+        self.state.last_valid_request = Some(result);
+
+        // maybe start a response series
+        if let Some(series) = result.series {
+            // enter the solicited confirm wait state
+            let span = self.sol_confirm_wait_span(series);
+            self.sol_confirm_wait(io, reader, writer, database, series)
+                .instrument(span)
+                .await?;
+        }
+
+        Ok(())
+    }
+
     fn process_request_from_idle(
         &mut self,
         info: FragmentInfo,
@@ -977,17 +1404,30 @@ impl OutstationSession {
         self.info.process_request_from_idle(request.header);
 
         let seq = request.header.control.seq;
+        let function = request.header.function;
 
         match self.classify(info, request) {
             FragmentType::MalformedRequest(hash, err) => {
                 let response = Response::empty_solicited(seq, Iin::default() | Iin2::from(err));
 
                 // TODO: Shouldn't we return None here?
-                Some(LastValidRequest::new(seq, hash, Some(response), None))
+                Some(LastValidRequest::new(
+                    seq,
+                    function,
+                    hash,
+                    Some(response),
+                    None,
+                ))
             }
             FragmentType::NewRead(hash, objects) => {
                 let (response, series) = self.write_first_read_response(database, seq, objects);
-                Some(LastValidRequest::new(seq, hash, Some(response), series))
+                Some(LastValidRequest::new(
+                    seq,
+                    function,
+                    hash,
+                    Some(response),
+                    series,
+                ))
             }
             FragmentType::RepeatRead(hash, _, objects) => {
                 // this deviates a bit from the spec, the specification says to
@@ -995,37 +1435,55 @@ impl OutstationSession {
                 // is plainly wrong since it can't possibly handle a multi-fragmented
                 // response correctly. Answering a repeat READ with a fresh response is harmless
                 let (response, series) = self.write_first_read_response(database, seq, objects);
-                Some(LastValidRequest::new(seq, hash, Some(response), series))
+                Some(LastValidRequest::new(
+                    seq,
+                    function,
+                    hash,
+                    Some(response),
+                    series,
+                ))
             }
             FragmentType::NewNonRead(hash, objects) => {
                 let response =
-                    self.handle_non_read(database, request.header.function, seq, info.id, objects);
-                Some(LastValidRequest::new(seq, hash, response, None))
+                    self.handle_non_read(database, function, seq, info.id, objects, hash);
+                Some(LastValidRequest::new(seq, function, hash, response, None))
             }
             FragmentType::RepeatNonRead(hash, last_response) => {
-                // If we have a pending select, update the sequence number
-                if let Some(select) = &mut self.state.select {
+                // If we have pending selects, update their frame ids
+                for select in self.state.selects.iter_mut() {
                     select.update_frame_id(info.id);
                 }
 
                 // per the spec, we just echo the last response
-                Some(LastValidRequest::new(seq, hash, last_response, None))
+                Some(LastValidRequest::new(
+                    seq,
+                    function,
+                    hash,
+                    last_response,
+                    None,
+                ))
             }
             FragmentType::Broadcast(mode) => {
                 self.process_broadcast(database, mode, request);
                 None
             }
             FragmentType::SolicitedConfirm(seq) => {
-                tracing::warn!(
-                    "ignoring solicited CONFIRM from idle state with seq: {}",
-                    seq.value()
+                self.anomaly(
+                    AnomalyKind::UnexpectedFragment,
+                    format_args!(
+                        "ignoring solicited CONFIRM from idle state with seq: {}",
+                        seq.value()
+                    ),
                 );
                 None
             }
             FragmentType::UnsolicitedConfirm(seq) => {
-                tracing::warn!(
-                    "ignoring unsolicited CONFIRM from idle state with seq: {}",
-                    seq.value()
+                self.anomaly(
+                    AnomalyKind::UnexpectedFragment,
+                    format_args!(
+                        "ignoring unsolicited CONFIRM from idle state with seq: {}",
+                        seq.value()
+                    ),
                 );
                 None
             }
@@ -1075,7 +1533,8 @@ impl OutstationSession {
         let (len, info) = {
             let mut cursor = self.sol_tx_buffer.write_cursor();
             let _ = cursor.skip(ResponseHeader::LENGTH);
-            let info = database.write_response_headers(&mut cursor);
+            let info = database
+                .write_response_headers(self.config.max_events_per_solicited_response, &mut cursor);
             (cursor.written().len(), info)
         };
 
@@ -1094,7 +1553,26 @@ impl OutstationSession {
         seq: Sequence,
         frame_id: u32,
         object_headers: HeaderCollection,
+        object_hash: Option<u64>,
     ) -> Option<Response> {
+        if !self.config.functions.is_enabled(function) {
+            tracing::warn!(
+                "ignoring request with disabled function code: {:?}",
+                function
+            );
+            return match function {
+                FunctionCode::DirectOperateNoResponse
+                | FunctionCode::ImmediateFreezeNoResponse
+                | FunctionCode::FreezeClearNoResponse
+                | FunctionCode::FreezeAtTimeNoResponse
+                | FunctionCode::AuthRequestNoAck => None,
+                _ => Some(Response::empty_solicited(
+                    seq,
+                    Iin::default() | Iin2::NO_FUNC_CODE_SUPPORT,
+                )),
+            };
+        }
+
         let mut result = match function {
             FunctionCode::Write => Some(self.handle_write(seq, object_headers)),
             // these function don't process objects
@@ -1109,11 +1587,14 @@ impl OutstationSession {
                 Some(self.handle_restart(seq, delay))
             }
             // controls
+            // reuse the hash calculated for duplicate detection if there is one
             FunctionCode::Select => {
-                Some(self.handle_select(database, seq, frame_id, object_headers))
+                let object_hash = object_hash.unwrap_or_else(|| object_headers.hash());
+                Some(self.handle_select(database, seq, frame_id, object_headers, object_hash))
             }
             FunctionCode::Operate => {
-                Some(self.handle_operate(database, seq, frame_id, object_headers))
+                let object_hash = object_hash.unwrap_or_else(|| object_headers.hash());
+                Some(self.handle_operate(database, seq, frame_id, object_headers, object_hash))
             }
             FunctionCode::DirectOperate => {
                 Some(self.handle_direct_operate(database, seq, object_headers))
@@ -1150,12 +1631,22 @@ impl OutstationSession {
                 FreezeType::FreezeAndClear,
                 false,
             ),
+            FunctionCode::InitializeApplication
+            | FunctionCode::StartApplication
+            | FunctionCode::StopApplication => {
+                Some(self.handle_application_operation(function, seq, object_headers))
+            }
             FunctionCode::EnableUnsolicited => {
                 Some(self.handle_enable_or_disable_unsolicited(true, seq, object_headers))
             }
             FunctionCode::DisableUnsolicited => {
                 Some(self.handle_enable_or_disable_unsolicited(false, seq, object_headers))
             }
+            FunctionCode::AuthRequestNoAck => {
+                // secure authentication isn't supported and this function never gets a response
+                tracing::warn!("unsupported function code: {:?}", function);
+                None
+            }
 
             _ => {
                 tracing::warn!("unsupported function code: {:?}", function);
@@ -1203,12 +1694,17 @@ impl OutstationSession {
                                 self.info.clear_restart_iin();
                             }
                         } else {
-                            tracing::warn!(
-                                "ignoring write of IIN index {} to value {}",
-                                index,
-                                value
-                            );
-                            iin2 |= Iin2::PARAMETER_ERROR;
+                            match self.application.write_iin_bit(index, value) {
+                                WriteIinResult::Ok => {}
+                                WriteIinResult::NotSupported => {
+                                    tracing::warn!(
+                                        "ignoring write of IIN index {} to value {}",
+                                        index,
+                                        value
+                                    );
+                                    iin2 |= Iin2::PARAMETER_ERROR;
+                                }
+                            }
                         }
                     }
                     iin2
@@ -1228,6 +1724,12 @@ impl OutstationSession {
                 HeaderDetails::OneByteCount(_, CountVariation::Group50Var3(seq)) => {
                     self.handle_g50v3(seq)
                 }
+                HeaderDetails::OneByteCountAndPrefix(_, PrefixedVariation::Group50Var4(seq)) => {
+                    self.handle_g50v4(seq)
+                }
+                HeaderDetails::TwoByteCountAndPrefix(_, PrefixedVariation::Group50Var4(seq)) => {
+                    self.handle_g50v4(seq)
+                }
                 _ => {
                     tracing::warn!(
                         "WRITE not supported with qualifier: {} and variation: {}",
@@ -1245,6 +1747,28 @@ impl OutstationSession {
         Response::empty_solicited(seq, Iin::default() | iin2)
     }
 
+    fn handle_g50v4<I>(&mut self, seq: CountSequence<Prefix<I, Group50Var4>>) -> Iin2
+    where
+        I: Index,
+    {
+        let mut iin2 = Iin2::default();
+        for item in seq.iter() {
+            let index = item.index.widen_to_u16();
+            match self
+                .application
+                .write_indexed_time(index, item.value.into())
+            {
+                WriteTimeResult::Ok => {}
+                WriteTimeResult::NotSupported => iin2 |= Iin2::NO_FUNC_CODE_SUPPORT,
+                WriteTimeResult::InvalidValue => {
+                    tracing::warn!("invalid g50v4 value for index {}", index);
+                    iin2 |= Iin2::PARAMETER_ERROR
+                }
+            }
+        }
+        iin2
+    }
+
     fn handle_g50v3(&mut self, seq: CountSequence<Group50Var3>) -> Iin2 {
         let value = if let Some(value) = seq.single() {
             value
@@ -1260,7 +1784,7 @@ impl OutstationSession {
             return Iin2::PARAMETER_ERROR;
         };
 
-        let now = crate::tokio::time::Instant::now();
+        let now = self.config.clock.now();
         let delay = if let Some(delay) = now.checked_duration_since(last_recorded_time) {
             delay
         } else {
@@ -1302,7 +1826,7 @@ impl OutstationSession {
     }
 
     fn handle_record_current_time(&mut self, seq: Sequence) -> Response {
-        self.state.last_recorded_time = Some(crate::tokio::time::Instant::now());
+        self.state.last_recorded_time = Some(self.config.clock.now());
         Response::empty_solicited(seq, Iin::default())
     }
 
@@ -1376,6 +1900,8 @@ impl OutstationSession {
                 )
             });
 
+            self.state.pending_controls = control_tx.take_pending();
+
             (result, cursor.written().len())
         };
 
@@ -1394,6 +1920,57 @@ impl OutstationSession {
         Response::new(header, len)
     }
 
+    fn handle_application_operation(
+        &mut self,
+        function: FunctionCode,
+        seq: Sequence,
+        object_headers: HeaderCollection,
+    ) -> Response {
+        if object_headers.is_empty() {
+            tracing::warn!("{:?} request without any application identifiers", function);
+            return Response::empty_solicited(seq, Iin::default() | Iin2::PARAMETER_ERROR);
+        }
+
+        let mut iin2 = Iin2::default();
+
+        for header in object_headers.iter() {
+            let application = match &header.details {
+                HeaderDetails::AllObjects(AllObjectsVariation::Group90Var1) => {
+                    ApplicationIdentifier::All
+                }
+                HeaderDetails::TwoByteFreeFormat(_, FreeFormatVariation::Group90Var1(name)) => {
+                    ApplicationIdentifier::Name(name.value)
+                }
+                _ => {
+                    tracing::warn!(
+                        "{:?} not supported with qualifier: {} and variation: {}",
+                        function,
+                        header.details.qualifier(),
+                        header.variation
+                    );
+                    iin2 |= Iin2::NO_FUNC_CODE_SUPPORT;
+                    continue;
+                }
+            };
+
+            let result = match function {
+                FunctionCode::InitializeApplication => {
+                    self.application.initialize_application(application)
+                }
+                FunctionCode::StartApplication => self.application.start_application(application),
+                _ => self.application.stop_application(application),
+            };
+
+            match result {
+                ApplicationOperationResult::NotSupported => iin2 |= Iin2::NO_FUNC_CODE_SUPPORT,
+                ApplicationOperationResult::UnknownApplication => iin2 |= Iin2::PARAMETER_ERROR,
+                ApplicationOperationResult::Ok => {}
+            }
+        }
+
+        Response::empty_solicited(seq, Iin::default() | iin2)
+    }
+
     fn handle_enable_or_disable_unsolicited(
         &mut self,
         enable: bool,
@@ -1467,6 +2044,7 @@ impl OutstationSession {
         seq: Sequence,
         frame_id: u32,
         object_headers: HeaderCollection,
+        object_hash: u64,
     ) -> Response {
         let controls = match ControlCollection::from(object_headers) {
             Err(err) => {
@@ -1501,13 +2079,12 @@ impl OutstationSession {
         };
 
         // Record the select state
+        self.state.chain_selects(seq, frame_id);
         if let Ok(CommandStatus::Success) = result {
-            self.state.select = Some(SelectState::new(
-                seq,
-                frame_id,
-                crate::tokio::time::Instant::now(),
-                object_headers.hash(),
-            ))
+            self.state.add_select(
+                SelectState::new(seq, frame_id, self.config.clock.now(), object_hash),
+                self.config.max_concurrent_selects,
+            );
         }
 
         // Calculate IIN and return response
@@ -1531,6 +2108,7 @@ impl OutstationSession {
         seq: Sequence,
         frame_id: u32,
         object_headers: HeaderCollection,
+        object_hash: u64,
     ) -> Response {
         let controls = match ControlCollection::from(object_headers) {
             Err(err) => {
@@ -1550,13 +2128,13 @@ impl OutstationSession {
             let _ = cursor.skip(ResponseHeader::LENGTH);
 
             // determine if we have a matching SELECT
-            let status = match self.state.select {
+            let status = match self.state.take_select(object_hash) {
                 Some(s) => {
                     match s.match_operate(
+                        self.config.clock.now(),
                         self.config.select_timeout,
                         seq,
                         frame_id,
-                        object_headers.hash(),
                     ) {
                         Err(status) => {
                             controls.respond_with_status(&mut cursor, status).unwrap();
@@ -1566,7 +2144,7 @@ impl OutstationSession {
                             let mut control_tx =
                                 ControlTransaction::new(self.control_handler.borrow_mut());
                             let max_controls_per_request = self.config.max_controls_per_request;
-                            database
+                            let status = database
                                 .transaction(|db| {
                                     controls.operate_with_response(
                                         &mut cursor,
@@ -1576,7 +2154,9 @@ impl OutstationSession {
                                         max_controls_per_request,
                                     )
                                 })
-                                .unwrap()
+                                .unwrap();
+                            self.state.pending_controls = control_tx.take_pending();
+                            status
                         }
                     }
                 }
@@ -1590,6 +2170,9 @@ impl OutstationSession {
             (status, cursor.written().len())
         };
 
+        // the other SELECTs may still be operated by the next request
+        self.state.chain_selects(seq, frame_id);
+
         // Calculate IIN and return it
         let mut iin = Iin::default();
 
@@ -1687,7 +2270,7 @@ impl OutstationSession {
         // Application-controlled IIN bits
         iin |= self.application.get_application_iin();
 
-        iin
+        iin | self.forced_iin
     }
 
     fn process_broadcast(
@@ -1696,10 +2279,25 @@ impl OutstationSession {
         mode: BroadcastConfirmMode,
         request: Request,
     ) {
+        if self.config.broadcast.is_enabled() && !self.config.broadcast_addresses.accepts(mode) {
+            // the broadcast IIN bit and any mandatory confirmation only apply to accepted requests
+            tracing::warn!(
+                "ignoring broadcast request sent to 0x{:04X} (address disabled): {:?}",
+                mode.address(),
+                request.header.function
+            );
+            self.info.broadcast_received(
+                request.header.function,
+                BroadcastAction::IgnoredByConfiguration,
+                mode,
+            );
+            return;
+        }
+
         self.state.last_broadcast_type = Some(mode);
         let action = self.process_broadcast_get_action(database, request);
         self.info
-            .broadcast_received(request.header.function, action)
+            .broadcast_received(request.header.function, action, mode)
     }
 
     fn process_broadcast_get_action(
@@ -1715,6 +2313,14 @@ impl OutstationSession {
             return BroadcastAction::IgnoredByConfiguration;
         }
 
+        if !self.config.functions.is_enabled(request.header.function) {
+            tracing::warn!(
+                "ignoring broadcast request (function code disabled): {:?}",
+                request.header.function
+            );
+            return BroadcastAction::IgnoredByConfiguration;
+        }
+
         let objects = match request.objects {
             Ok(x) => x,
             Err(err) => {
@@ -1767,12 +2373,29 @@ impl OutstationSession {
         }
     }
 
-    fn new_confirm_deadline(&self) -> crate::tokio::time::Instant {
-        crate::tokio::time::Instant::now() + self.config.confirm_timeout
+    fn new_confirm_deadline(&self) -> crate::app::Instant {
+        self.config.clock.now() + self.config.confirm_timeout
+    }
+
+    /// the confirm timeout starts once the link has transmitted the response, so that a paced
+    /// response isn't repeated before the master could have received it
+    fn new_unsolicited_confirm_deadline(&self, writer: &TransportWriter) -> crate::app::Instant {
+        writer
+            .busy_until()
+            .unwrap_or_else(|| self.config.clock.now())
+            + self.config.confirm_timeout
     }
 
-    fn new_unsolicited_retry_deadline(&self) -> crate::tokio::time::Instant {
-        crate::tokio::time::Instant::now() + self.config.unsolicited_retry_delay
+    fn new_unsolicited_retry_deadline(&self) -> crate::app::Instant {
+        self.config.clock.now() + self.config.unsolicited_retry_delay
+    }
+
+    fn sol_confirm_wait_span(&self, series: ResponseSeries) -> tracing::Span {
+        let cid = crate::decode::next_correlation_id();
+        self.config.tracing.task_span(
+            tracing::info_span!("SolConfirmWait", "ecsn" = series.ecsn.value(), "cid" = cid),
+            cid,
+        )
     }
 
     async fn sol_confirm_wait(
@@ -1899,22 +2522,91 @@ impl OutstationSession {
                 } else {
                     self.info
                         .wrong_solicited_confirm_seq(ecsn, request.header.control.seq);
-                    tracing::warn!(
-                        "ignoring confirm with wrong sequence number: {}",
-                        seq.value()
+                    self.anomaly(
+                        AnomalyKind::UnexpectedSequence,
+                        format_args!(
+                            "ignoring confirm with wrong sequence number: {}",
+                            seq.value()
+                        ),
                     );
                     ConfirmAction::ContinueWait
                 }
             }
             FragmentType::UnsolicitedConfirm(seq) => {
                 self.info.unexpected_confirm(true, seq);
-                tracing::warn!("ignoring unsolicited confirm with seq: {}", seq.value());
+                self.anomaly(
+                    AnomalyKind::UnexpectedFragment,
+                    format_args!("ignoring unsolicited confirm with seq: {}", seq.value()),
+                );
                 ConfirmAction::ContinueWait
             }
         }
     }
 
-    fn classify<'a>(&self, info: FragmentInfo, request: Request<'a>) -> FragmentType<'a> {
+    fn classify<'a>(&mut self, info: FragmentInfo, request: Request<'a>) -> FragmentType<'a> {
+        let fragment_type = self.classify_fragment(info, request);
+        self.audit_request(info, request, &fragment_type);
+        fragment_type
+    }
+
+    fn audit_request(&self, info: FragmentInfo, request: Request, fragment_type: &FragmentType) {
+        let audit_logger = match &self.config.audit_logger {
+            Some(x) => x,
+            None => return,
+        };
+
+        let outcome = match fragment_type {
+            FragmentType::MalformedRequest(_, err) => RequestOutcome::Malformed(*err),
+            _ if !self.config.functions.is_enabled(request.header.function) => {
+                RequestOutcome::FunctionDisabled
+            }
+            FragmentType::RepeatRead(_, _, _) | FragmentType::RepeatNonRead(_, _) => {
+                RequestOutcome::Repeat
+            }
+            FragmentType::Broadcast(_) => RequestOutcome::Broadcast,
+            FragmentType::NewRead(_, _)
+            | FragmentType::NewNonRead(_, _)
+            | FragmentType::SolicitedConfirm(_)
+            | FragmentType::UnsolicitedConfirm(_) => RequestOutcome::Processed,
+        };
+
+        audit_logger.request_received(RequestRecord {
+            source: info.source,
+            header: request.header,
+            objects: ObjectSummary::new(request.header.function, request.raw_objects),
+            outcome,
+        });
+    }
+
+    fn audit_response(
+        audit_logger: Option<&dyn AuditLogger>,
+        destination: EndpointAddress,
+        header: ResponseHeader,
+        fragment: &[u8],
+        result: &Result<(), LinkError>,
+    ) {
+        let audit_logger = match audit_logger {
+            Some(x) => x,
+            None => return,
+        };
+
+        let outcome = match result {
+            Ok(()) => ResponseOutcome::Transmitted,
+            Err(_) => ResponseOutcome::Failed,
+        };
+
+        audit_logger.response_transmitted(ResponseRecord {
+            destination,
+            header,
+            objects: ObjectSummary::new(
+                header.function.function(),
+                fragment.get(ResponseHeader::LENGTH..).unwrap_or(&[]),
+            ),
+            outcome,
+        });
+    }
+
+    fn classify_fragment<'a>(&self, info: FragmentInfo, request: Request<'a>) -> FragmentType<'a> {
         if request.header.function == FunctionCode::Confirm {
             return if request.header.control.uns {
                 FragmentType::UnsolicitedConfirm(request.header.control.seq)
@@ -1928,7 +2620,15 @@ impl OutstationSession {
         }
 
         // we need to calculate a digest to deduplicate
-        let this_hash = xxh64(request.raw_fragment, 0);
+        let this_hash = if self
+            .config
+            .duplicate_detection
+            .applies_to(request.header.function)
+        {
+            Some(xxh64(request.raw_objects, 0))
+        } else {
+            None
+        };
 
         let object_headers = match request.objects {
             Ok(x) => x,
@@ -1936,8 +2636,11 @@ impl OutstationSession {
         };
 
         // detect duplicate requests
-        if let Some(last) = self.state.last_valid_request {
-            if last.seq == request.header.control.seq && last.request_hash == this_hash {
+        if let (Some(last), Some(hash)) = (self.state.last_valid_request, this_hash) {
+            if last.seq == request.header.control.seq
+                && last.function == request.header.function
+                && last.request_hash == Some(hash)
+            {
                 return if request.header.function == FunctionCode::Read {
                     FragmentType::RepeatRead(this_hash, last.response, object_headers)
                 } else {
@@ -1954,10 +2657,11 @@ impl OutstationSession {
     }
 
     fn on_link_activity(&mut self) {
+        self.unanswered_link_status_requests = 0;
         self.next_link_status = self
             .config
             .keep_alive_timeout
-            .map(|timeout| crate::tokio::time::Instant::now() + timeout);
+            .map(|timeout| self.config.clock.now() + timeout);
     }
 }
 
@@ -1972,6 +2676,8 @@ impl From<ObjectParseError> for Iin2 {
             ObjectParseError::UnsupportedQualifierCode(_) => Iin2::PARAMETER_ERROR,
             ObjectParseError::UnknownQualifier(_) => Iin2::PARAMETER_ERROR,
             ObjectParseError::ZeroLengthOctetData => Iin2::PARAMETER_ERROR,
+            ObjectParseError::UnsupportedFreeFormatCount(_) => Iin2::PARAMETER_ERROR,
+            ObjectParseError::BadAttribute(_) => Iin2::PARAMETER_ERROR,
         }
     }
 }
@@ -0,0 +1,48 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Snapshot of the unsolicited reporting of an outstation
+///
+/// Counters start at zero when the outstation is created and are never reset
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct UnsolicitedStatistics {
+    /// number of events in the buffer that have not yet been confirmed by the master
+    pub buffered_events: usize,
+    /// number of times an unsolicited response with pending events was deferred because the
+    /// link was still transmitting the previous frames, e.g. due to pacing
+    pub deferred: u64,
+    /// number of unsolicited responses transmitted, excluding retries
+    pub responses: u64,
+    /// number of unsolicited responses repeated after a confirm timeout
+    pub retries: u64,
+}
+
+/// counters shared between the outstation session and its handle
+#[derive(Debug, Default)]
+pub(crate) struct UnsolicitedCounters {
+    deferred: AtomicU64,
+    responses: AtomicU64,
+    retries: AtomicU64,
+}
+
+impl UnsolicitedCounters {
+    pub(crate) fn on_deferred(&self) {
+        self.deferred.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn on_response(&self) {
+        self.responses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn on_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self, buffered_events: usize) -> UnsolicitedStatistics {
+        UnsolicitedStatistics {
+            buffered_events,
+            deferred: self.deferred.load(Ordering::Relaxed),
+            responses: self.responses.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -1,9 +1,13 @@
+use std::sync::Arc;
+
 use crate::app::Shutdown;
 use crate::decode::DecodeLevel;
+use crate::link::statistics::LinkCounters;
 use crate::link::LinkErrorMode;
 use crate::outstation::config::*;
 use crate::outstation::database::{DatabaseHandle, EventBufferConfig};
-use crate::outstation::session::{OutstationSession, RunError};
+use crate::outstation::session::{OutstationSession, RunError, SessionParameters};
+use crate::outstation::statistics::UnsolicitedCounters;
 use crate::outstation::traits::{ControlHandler, OutstationApplication, OutstationInformation};
 use crate::outstation::OutstationHandle;
 use crate::transport::{TransportReader, TransportWriter};
@@ -11,6 +15,10 @@ use crate::util::phys::PhysLayer;
 
 pub(crate) enum ConfigurationChange {
     SetDecodeLevel(DecodeLevel),
+    #[cfg(any(test, feature = "sim"))]
+    SetResponseDelay(Option<std::time::Duration>),
+    #[cfg(any(test, feature = "sim"))]
+    SetForcedIin(crate::app::Iin),
 }
 
 impl From<ConfigurationChange> for OutstationMessage {
@@ -21,6 +29,8 @@ impl From<ConfigurationChange> for OutstationMessage {
 
 pub(crate) enum OutstationMessage {
     Shutdown,
+    /// finish the request or unsolicited series in progress before shutting down
+    Drain(crate::tokio::sync::oneshot::Sender<()>),
     Configuration(ConfigurationChange),
 }
 
@@ -33,6 +43,7 @@ pub(crate) struct OutstationTask {
 
 impl OutstationTask {
     /// create an `OutstationTask` and return it along with a `DatabaseHandle` for updating it
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn create(
         link_error_mode: LinkErrorMode,
         config: OutstationConfig,
@@ -42,9 +53,31 @@ impl OutstationTask {
         control_handler: Box<dyn ControlHandler>,
     ) -> (Self, OutstationHandle) {
         let (tx, rx) = crate::util::channel::request_channel();
+        for (name, size) in [
+            ("solicited", config.solicited_buffer_size),
+            ("unsolicited", config.unsolicited_buffer_size),
+        ]
+        .iter()
+        {
+            if size.exceeds_default() {
+                tracing::warn!(
+                    "{} responses may be up to {} bytes, which masters that only accept {} byte fragments will reject",
+                    name,
+                    size.value(),
+                    BufferSize::DEFAULT
+                );
+            }
+        }
+        let link_counters = Arc::new(LinkCounters::new(
+            config.clock.clone(),
+            config.tracing,
+            config.buffer_pool,
+        ));
+        let unsolicited_counters = Arc::new(UnsolicitedCounters::default());
         let handle = DatabaseHandle::new(
             config.max_read_request_headers,
             config.class_zero,
+            config.storage,
             event_config,
         );
         let (reader, writer) = crate::transport::create_outstation_transport_layer(
@@ -52,15 +85,24 @@ impl OutstationTask {
             config.outstation_address,
             config.features.self_address,
             config.rx_buffer_size,
+            config.link,
+            config.confirm_timeout,
+            config.transport,
+            config.unknown_destination,
+            link_counters.clone(),
         );
+        let master_address = config.master_address;
+        let parameters = SessionParameters::from(&config);
         let task = Self {
             session: OutstationSession::new(
                 rx,
                 config.into(),
-                config.into(),
+                parameters,
                 application,
                 information,
                 control_handler,
+                link_counters.clone(),
+                unsolicited_counters.clone(),
             ),
             reader,
             writer,
@@ -71,6 +113,9 @@ impl OutstationTask {
             OutstationHandle {
                 database: handle,
                 sender: tx,
+                link_counters,
+                unsolicited_counters,
+                master_address,
             },
         )
     }
@@ -89,6 +134,45 @@ impl OutstationTask {
         }
     }
 
+    /// process received outstation messages while idle without a session until events of
+    /// `classes` are waiting to be reported or the deadline is reached
+    #[cfg(feature = "tcp")]
+    pub(crate) async fn wait_for_events(
+        &mut self,
+        classes: crate::master::EventClasses,
+        deadline: Option<crate::app::Instant>,
+    ) -> Result<(), Shutdown> {
+        let clock = self.session.clock();
+        loop {
+            if (self.database.get_events_info().unwritten_classes & classes).any() {
+                return Ok(());
+            }
+
+            crate::tokio::select! {
+                ret = self.session.process_messages() => {
+                    return ret;
+                }
+                _ = self.database.wait_for_change() => {
+                    // check the events again
+                }
+                _ = async {
+                    match deadline {
+                        Some(x) => clock.sleep_until(x).await,
+                        None => crate::util::future::forever().await,
+                    }
+                } => {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// end sessions once no response has been written for `idle_timeout`
+    #[cfg(any(test, feature = "tcp"))]
+    pub(crate) fn set_idle_timeout(&mut self, idle_timeout: Option<std::time::Duration>) {
+        self.session.set_idle_timeout(idle_timeout);
+    }
+
     pub(crate) fn reset(&mut self) {
         self.session.reset();
         self.reader.reset();
@@ -0,0 +1,47 @@
+use crate::outstation::tests::harness::*;
+
+const START_ALL_APPLICATIONS: &[u8] = &[0xC0, 0x11, 90, 1, 0x06];
+const START_KNOWN_APPLICATION: &[u8] =
+    &[0xC0, 0x11, 90, 1, 0x5B, 0x01, 0x03, 0x00, b'a', b'p', b'p'];
+const START_UNKNOWN_APPLICATION: &[u8] =
+    &[0xC0, 0x11, 90, 1, 0x5B, 0x01, 0x03, 0x00, b'f', b'o', b'o'];
+const STOP_ALL_APPLICATIONS: &[u8] = &[0xC0, 0x12, 90, 1, 0x06];
+const START_WITHOUT_OBJECTS: &[u8] = &[0xC0, 0x11];
+const EMPTY_RESPONSE: &[u8] = &[0xC0, 0x81, 0x80, 0x00];
+const RESPONSE_NO_FUNCTION_SUPPORT: &[u8] = &[0xC0, 0x81, 0x80, 0x01];
+const RESPONSE_PARAMETER_ERROR: &[u8] = &[0xC0, 0x81, 0x80, 0x04];
+
+#[test]
+fn can_start_all_applications() {
+    let mut harness = new_harness(get_default_config());
+    harness.test_request_response(START_ALL_APPLICATIONS, EMPTY_RESPONSE);
+    harness.check_events(&[Event::StartApplication(false)]);
+}
+
+#[test]
+fn can_start_application_by_name() {
+    let mut harness = new_harness(get_default_config());
+    harness.test_request_response(START_KNOWN_APPLICATION, EMPTY_RESPONSE);
+    harness.check_events(&[Event::StartApplication(true)]);
+}
+
+#[test]
+fn rejects_start_of_unknown_application_with_parameter_error() {
+    let mut harness = new_harness(get_default_config());
+    harness.test_request_response(START_UNKNOWN_APPLICATION, RESPONSE_PARAMETER_ERROR);
+    harness.check_events(&[Event::StartApplication(true)]);
+}
+
+#[test]
+fn rejects_request_without_application_identifier() {
+    let mut harness = new_harness(get_default_config());
+    harness.test_request_response(START_WITHOUT_OBJECTS, RESPONSE_PARAMETER_ERROR);
+    harness.check_no_events();
+}
+
+#[test]
+fn responds_with_no_func_code_support_when_application_does_not_implement_operation() {
+    let mut harness = new_harness(get_default_config());
+    harness.test_request_response(STOP_ALL_APPLICATIONS, RESPONSE_NO_FUNCTION_SUPPORT);
+    harness.check_no_events();
+}
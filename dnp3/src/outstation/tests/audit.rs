@@ -0,0 +1,96 @@
+use crate::app::FunctionCode;
+use crate::outstation::tests::data::*;
+use crate::outstation::tests::harness::*;
+use crate::outstation::{DuplicateDetection, FunctionCodes, RequestOutcome, ResponseOutcome};
+
+#[test]
+fn records_requests_and_responses() {
+    let mut harness = new_harness(get_default_config());
+    harness.test_request_response(DELAY_MEASURE, RESPONSE_TIME_DELAY_FINE_ZERO);
+    // the repeated request is answered with the previous response
+    harness.test_request_response(DELAY_MEASURE, RESPONSE_TIME_DELAY_FINE_ZERO);
+
+    let response = AuditRecord::Response(
+        1,
+        FunctionCode::Response,
+        ResponseOutcome::Transmitted,
+        "g52v2 (0x07)".to_string(),
+    );
+
+    assert_eq!(
+        harness.audit_records.lock().unwrap().as_slice(),
+        &[
+            AuditRecord::Request(
+                1,
+                FunctionCode::DelayMeasure,
+                RequestOutcome::Processed,
+                "none".to_string()
+            ),
+            response.clone(),
+            AuditRecord::Request(
+                1,
+                FunctionCode::DelayMeasure,
+                RequestOutcome::Repeat,
+                "none".to_string()
+            ),
+            response,
+        ]
+    );
+}
+
+#[test]
+fn records_malformed_requests() {
+    let mut harness = new_harness(get_default_config());
+    // g1v2 with an unknown qualifier
+    harness.test_request_response(&[0xC0, 0x01, 0x01, 0x02, 0xFF], &[0xC0, 0x81, 0x80, 0x04]);
+
+    let records = harness.audit_records.lock().unwrap();
+    assert_eq!(records.len(), 2);
+    assert!(matches!(
+        records[0],
+        AuditRecord::Request(1, FunctionCode::Read, RequestOutcome::Malformed(_), _)
+    ));
+}
+
+#[test]
+fn records_requests_with_disabled_function_codes() {
+    let mut config = get_default_config();
+    config.functions = FunctionCodes::all().disable(FunctionCode::DelayMeasure);
+    let mut harness = new_harness(config);
+    harness.test_request_response(DELAY_MEASURE, &[0xC0, 0x81, 0x80, 0x01]);
+
+    let records = harness.audit_records.lock().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(
+        records[0],
+        AuditRecord::Request(
+            1,
+            FunctionCode::DelayMeasure,
+            RequestOutcome::FunctionDisabled,
+            "none".to_string()
+        )
+    );
+}
+
+#[test]
+fn processes_repeated_requests_when_duplicate_detection_is_disabled() {
+    let mut config = get_default_config();
+    config.duplicate_detection = DuplicateDetection::Disabled;
+    let mut harness = new_harness(config);
+    harness.test_request_response(DELAY_MEASURE, RESPONSE_TIME_DELAY_FINE_ZERO);
+    harness.test_request_response(DELAY_MEASURE, RESPONSE_TIME_DELAY_FINE_ZERO);
+
+    let records = harness.audit_records.lock().unwrap();
+    assert_eq!(records.len(), 4);
+    for record in [&records[0], &records[2]].iter() {
+        assert_eq!(
+            **record,
+            AuditRecord::Request(
+                1,
+                FunctionCode::DelayMeasure,
+                RequestOutcome::Processed,
+                "none".to_string()
+            )
+        );
+    }
+}
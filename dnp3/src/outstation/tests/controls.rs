@@ -1,9 +1,12 @@
 use tokio::time::Duration;
 
+use crate::app::control::CommandStatus;
+use crate::app::measurement::AnalogOutputStatus;
 use crate::app::variations::Group41Var2;
 use crate::app::FunctionCode;
-use crate::link::header::BroadcastConfirmMode;
+use crate::link::BroadcastConfirmMode;
 use crate::outstation::config::Feature;
+use crate::outstation::database::SetControllable;
 use crate::outstation::tests::harness::*;
 use crate::outstation::traits::{BroadcastAction, OperateType};
 
@@ -37,6 +40,26 @@ const RESPONSE_SEQ2_G41V2_NO_SELECT: &[u8] = &[
 const RESPONSE_SEQ1_G41V2_INDEX8_NO_SELECT: &[u8] = &[
     0xC1, 0x81, 0x80, 0x00, 41, 2, 0x17, 0x1, 0x08, 0x01, 0x02, 0x02,
 ];
+// select, seq == 1, g41v2 - count == 1, index == 8, value = 513, status == SUCCESS
+const SELECT_SEQ1_G41V2_INDEX_8: &[u8] = &[0xC1, 0x03, 41, 2, 0x17, 0x01, 0x08, 0x01, 0x02, 0x00];
+// operate, seq == 3, g41v2 - count == 1, index == 8, value = 513, status == SUCCESS
+const OPERATE_SEQ3_G41V2_INDEX_8: &[u8] = &[0xC3, 0x04, 41, 2, 0x17, 0x01, 0x08, 0x01, 0x02, 0x00];
+// response, seq == 1, restart IIN + echo of request headers, index == 8
+const RESPONSE_SEQ1_G41V2_INDEX8_SUCCESS: &[u8] = &[
+    0xC1, 0x81, 0x80, 0x00, 41, 2, 0x17, 0x1, 0x08, 0x01, 0x02, 0x00,
+];
+// response, seq == 2, restart IIN + echo of request headers
+const RESPONSE_SEQ2_G41V2_SUCCESS: &[u8] = &[
+    0xC2, 0x81, 0x80, 0x00, 41, 2, 0x17, 0x1, 0x07, 0x01, 0x02, 0x00,
+];
+// response, seq == 3, restart IIN + echo of request headers, index == 8
+const RESPONSE_SEQ3_G41V2_INDEX8_SUCCESS: &[u8] = &[
+    0xC3, 0x81, 0x80, 0x00, 41, 2, 0x17, 0x1, 0x08, 0x01, 0x02, 0x00,
+];
+// response, seq == 0, restart IIN + PARAMETER_ERROR + echo of request headers with status == 4 (NOT_SUPPORTED)
+const RESPONSE_SEQ0_G41V2_NOT_SUPPORTED: &[u8] = &[
+    0xC0, 0x81, 0x80, 0x04, 41, 2, 0x17, 0x1, 0x07, 0x01, 0x02, 0x04,
+];
 // response, seq == 1, restart IIN + echo of request headers but with STATUS == 1 (TIMEOUT)
 const RESPONSE_SEQ1_G41V2_SELECT_TIMEOUT: &[u8] = &[
     0xC1, 0x81, 0x80, 0x00, 41, 2, 0x17, 0x1, 0x07, 0x01, 0x02, 0x01,
@@ -58,6 +81,37 @@ fn performs_direct_operate() {
     ]);
 }
 
+#[test]
+fn rejects_direct_operate_on_output_not_declared_controllable() {
+    let mut harness = new_harness(get_default_config());
+
+    harness.handle.database.transaction(|db| {
+        SetControllable::<AnalogOutputStatus>::set_controllable(db, 8, true);
+    });
+
+    harness.test_request_response(DIRECT_OPERATE_SEQ0_G41V2, RESPONSE_SEQ0_G41V2_NOT_SUPPORTED);
+
+    // the control handler is never invoked
+    harness.check_no_events();
+}
+
+#[test]
+fn performs_direct_operate_on_output_declared_controllable() {
+    let mut harness = new_harness(get_default_config());
+
+    harness.handle.database.transaction(|db| {
+        SetControllable::<AnalogOutputStatus>::set_controllable(db, 7, true);
+    });
+
+    harness.test_request_response(DIRECT_OPERATE_SEQ0_G41V2, RESPONSE_SEQ0_G41V2_SUCCESS);
+
+    harness.check_events(&[
+        Event::BeginControls,
+        Event::Operate(G41V2_INDEX_7, OperateType::DirectOperate),
+        Event::EndControls,
+    ]);
+}
+
 #[test]
 fn performs_direct_operate_no_ack() {
     let mut harness = new_harness(get_default_config());
@@ -85,6 +139,7 @@ fn performs_direct_operate_no_ack_via_broadcast() {
         Event::BroadcastReceived(
             FunctionCode::DirectOperateNoResponse,
             BroadcastAction::Processed,
+            BroadcastConfirmMode::Mandatory,
         ),
     ]);
 }
@@ -101,6 +156,23 @@ fn broadcast_support_can_be_disabled() {
     harness.check_events(&[Event::BroadcastReceived(
         FunctionCode::DirectOperateNoResponse,
         BroadcastAction::IgnoredByConfiguration,
+        BroadcastConfirmMode::Mandatory,
+    )]);
+}
+
+#[test]
+fn broadcast_addresses_can_be_disabled_individually() {
+    let mut config = get_default_config();
+    config.broadcast_addresses.mandatory_confirm = false;
+
+    let mut harness = new_harness_for_broadcast(config, BroadcastConfirmMode::Mandatory);
+
+    harness.test_request_no_response(DIRECT_OPERATE_NO_ACK_SEQ0_G41V2);
+
+    harness.check_events(&[Event::BroadcastReceived(
+        FunctionCode::DirectOperateNoResponse,
+        BroadcastAction::IgnoredByConfiguration,
+        BroadcastConfirmMode::Mandatory,
     )]);
 }
 
@@ -223,3 +295,158 @@ fn accept_two_identical_selects_before_operate() {
         Event::EndControls,
     ]);
 }
+
+#[test]
+fn operates_multiple_selects_when_enabled() {
+    let mut config = get_default_config();
+    config.max_concurrent_selects = 2;
+    let mut harness = new_harness(config);
+
+    // ------------ selects -------------
+    harness.test_request_response(SELECT_SEQ0_G41V2, RESPONSE_SEQ0_G41V2_SUCCESS);
+    harness.test_request_response(
+        SELECT_SEQ1_G41V2_INDEX_8,
+        RESPONSE_SEQ1_G41V2_INDEX8_SUCCESS,
+    );
+
+    harness.check_events(&[
+        Event::BeginControls,
+        Event::Select(G41V2_INDEX_7),
+        Event::EndControls,
+        Event::BeginControls,
+        Event::Select(Control::G41V2(Group41Var2::new(513), 8)),
+        Event::EndControls,
+    ]);
+
+    // ------------ operates -------------
+    harness.test_request_response(OPERATE_SEQ2_G41V2, RESPONSE_SEQ2_G41V2_SUCCESS);
+    harness.test_request_response(
+        OPERATE_SEQ3_G41V2_INDEX_8,
+        RESPONSE_SEQ3_G41V2_INDEX8_SUCCESS,
+    );
+
+    harness.check_events(&[
+        Event::BeginControls,
+        Event::Operate(G41V2_INDEX_7, OperateType::SelectBeforeOperate),
+        Event::EndControls,
+        Event::BeginControls,
+        Event::Operate(
+            Control::G41V2(Group41Var2::new(513), 8),
+            OperateType::SelectBeforeOperate,
+        ),
+        Event::EndControls,
+    ]);
+}
+
+#[test]
+fn new_select_replaces_previous_one_by_default() {
+    let mut harness = new_harness(get_default_config());
+
+    // ------------ selects -------------
+    harness.test_request_response(SELECT_SEQ0_G41V2, RESPONSE_SEQ0_G41V2_SUCCESS);
+    harness.test_request_response(
+        SELECT_SEQ1_G41V2_INDEX_8,
+        RESPONSE_SEQ1_G41V2_INDEX8_SUCCESS,
+    );
+
+    harness.check_events(&[
+        Event::BeginControls,
+        Event::Select(G41V2_INDEX_7),
+        Event::EndControls,
+        Event::BeginControls,
+        Event::Select(Control::G41V2(Group41Var2::new(513), 8)),
+        Event::EndControls,
+    ]);
+
+    // ------------ operate -------------
+    harness.test_request_response(OPERATE_SEQ2_G41V2, RESPONSE_SEQ2_G41V2_NO_SELECT);
+
+    harness.check_no_events();
+}
+
+// response, seq == 0, restart IIN + echo of request headers but with STATUS == 1 (TIMEOUT)
+const RESPONSE_SEQ0_G41V2_TIMEOUT: &[u8] = &[
+    0xC0, 0x81, 0x80, 0x00, 41, 2, 0x17, 0x1, 0x07, 0x01, 0x02, 0x01,
+];
+// response, seq == 0, restart IIN + echo of request headers but with STATUS == 16 (CANCELED)
+const RESPONSE_SEQ0_G41V2_CANCELED: &[u8] = &[
+    0xC0, 0x81, 0x80, 0x00, 41, 2, 0x17, 0x1, 0x07, 0x01, 0x02, 0x10,
+];
+
+#[test]
+fn responds_once_deferred_operate_completes() {
+    let mut harness = new_harness(get_default_config());
+    harness.control_data.lock().unwrap().defer_operations = true;
+
+    harness.send(DIRECT_OPERATE_SEQ0_G41V2);
+
+    harness.check_events(&[
+        Event::BeginControls,
+        Event::Operate(G41V2_INDEX_7, OperateType::DirectOperate),
+        Event::EndControls,
+    ]);
+
+    let completion = harness
+        .control_data
+        .lock()
+        .unwrap()
+        .completions
+        .pop_front()
+        .unwrap();
+    completion.complete(CommandStatus::Success);
+
+    harness.expect_response(RESPONSE_SEQ0_G41V2_SUCCESS);
+    harness.check_all_io_consumed();
+}
+
+#[test]
+fn deferred_operate_times_out_if_not_completed() {
+    let mut harness = new_harness(get_default_config());
+    harness.control_data.lock().unwrap().defer_operations = true;
+
+    harness.send(DIRECT_OPERATE_SEQ0_G41V2);
+
+    crate::tokio::time::advance(
+        get_default_config().control_completion_timeout + Duration::from_millis(1),
+    );
+
+    harness.expect_response(RESPONSE_SEQ0_G41V2_TIMEOUT);
+    harness.check_all_io_consumed();
+}
+
+#[test]
+fn deferred_operate_is_canceled_if_completion_is_dropped() {
+    let mut harness = new_harness(get_default_config());
+    harness.control_data.lock().unwrap().defer_operations = true;
+
+    harness.send(DIRECT_OPERATE_SEQ0_G41V2);
+
+    harness.control_data.lock().unwrap().completions.clear();
+
+    harness.expect_response(RESPONSE_SEQ0_G41V2_CANCELED);
+    harness.check_all_io_consumed();
+}
+
+#[test]
+fn answers_deferred_operate_before_the_next_request() {
+    let mut harness = new_harness(get_default_config());
+    harness.control_data.lock().unwrap().defer_operations = true;
+
+    harness.send(DIRECT_OPERATE_SEQ0_G41V2);
+
+    harness.check_events(&[
+        Event::BeginControls,
+        Event::Operate(G41V2_INDEX_7, OperateType::DirectOperate),
+        Event::EndControls,
+    ]);
+
+    // the session keeps reading while the operation is pending, so a retry of the request is
+    // received before the completion timeout
+    harness.send(DIRECT_OPERATE_SEQ0_G41V2);
+    harness.expect_response(RESPONSE_SEQ0_G41V2_TIMEOUT);
+
+    // the retry is answered with the same response without operating again
+    harness.expect_response(RESPONSE_SEQ0_G41V2_TIMEOUT);
+    harness.check_no_events();
+    harness.check_all_io_consumed();
+}
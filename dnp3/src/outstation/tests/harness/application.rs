@@ -1,10 +1,13 @@
 use std::sync::{Arc, Mutex};
 
-use crate::app::Timestamp;
+use crate::app::{IndexedTime, Timestamp};
 use crate::outstation::database::Database;
 use crate::outstation::tests::harness::{Event, EventHandle};
-use crate::outstation::traits::{OutstationApplication, RestartDelay};
-use crate::outstation::{FreezeIndices, FreezeResult, FreezeType, WriteTimeResult};
+use crate::outstation::traits::{
+    ApplicationIdentifier, ApplicationIin, ApplicationOperationResult, OutstationApplication,
+    RestartDelay,
+};
+use crate::outstation::{FreezeIndices, FreezeResult, FreezeType, WriteIinResult, WriteTimeResult};
 
 pub(crate) struct MockOutstationApplication {
     events: EventHandle,
@@ -14,6 +17,7 @@ pub(crate) struct MockOutstationApplication {
 pub(crate) struct ApplicationData {
     pub(crate) processing_delay: u16,
     pub(crate) restart_delay: Option<RestartDelay>,
+    pub(crate) device_trouble: bool,
 }
 
 impl ApplicationData {
@@ -21,6 +25,7 @@ impl ApplicationData {
         Self {
             processing_delay: 0,
             restart_delay: None,
+            device_trouble: false,
         }
     }
 }
@@ -40,6 +45,29 @@ impl OutstationApplication for MockOutstationApplication {
         WriteTimeResult::Ok
     }
 
+    fn write_indexed_time(&mut self, index: u16, value: IndexedTime) -> WriteTimeResult {
+        self.events.push(Event::WriteIndexedTime(index, value));
+        WriteTimeResult::Ok
+    }
+
+    fn get_application_iin(&self) -> ApplicationIin {
+        ApplicationIin {
+            device_trouble: self.data.lock().unwrap().device_trouble,
+            ..ApplicationIin::default()
+        }
+    }
+
+    fn write_iin_bit(&mut self, index: u16, value: bool) -> WriteIinResult {
+        self.events.push(Event::WriteIinBit(index, value));
+        // only clearing DEVICE_TROUBLE is supported
+        if index == 6 && !value {
+            self.data.lock().unwrap().device_trouble = false;
+            WriteIinResult::Ok
+        } else {
+            WriteIinResult::NotSupported
+        }
+    }
+
     fn get_processing_delay_ms(&self) -> u16 {
         self.data.lock().unwrap().processing_delay
     }
@@ -56,6 +84,27 @@ impl OutstationApplication for MockOutstationApplication {
         delay
     }
 
+    fn start_application(
+        &mut self,
+        application: ApplicationIdentifier,
+    ) -> ApplicationOperationResult {
+        match application {
+            ApplicationIdentifier::All => {
+                self.events.push(Event::StartApplication(false));
+                ApplicationOperationResult::Ok
+            }
+            ApplicationIdentifier::Name(name) => {
+                self.events.push(Event::StartApplication(true));
+                // only a single application named "app" is known
+                if name == b"app" {
+                    ApplicationOperationResult::Ok
+                } else {
+                    ApplicationOperationResult::UnknownApplication
+                }
+            }
+        }
+    }
+
     fn freeze_counter(
         &mut self,
         indices: FreezeIndices,
@@ -0,0 +1,43 @@
+use std::sync::{Arc, Mutex};
+
+use crate::app::FunctionCode;
+use crate::outstation::{
+    AuditLogger, RequestOutcome, RequestRecord, ResponseOutcome, ResponseRecord,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum AuditRecord {
+    Request(u16, FunctionCode, RequestOutcome, String),
+    Response(u16, FunctionCode, ResponseOutcome, String),
+}
+
+pub(crate) struct MockAuditLogger {
+    records: Arc<Mutex<Vec<AuditRecord>>>,
+}
+
+impl MockAuditLogger {
+    pub(crate) fn new() -> (Arc<Mutex<Vec<AuditRecord>>>, Arc<dyn AuditLogger>) {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        (records.clone(), Arc::new(Self { records }))
+    }
+}
+
+impl AuditLogger for MockAuditLogger {
+    fn request_received(&self, record: RequestRecord) {
+        self.records.lock().unwrap().push(AuditRecord::Request(
+            record.source.raw_value(),
+            record.header.function,
+            record.outcome,
+            record.objects.to_string(),
+        ));
+    }
+
+    fn response_transmitted(&self, record: ResponseRecord) {
+        self.records.lock().unwrap().push(AuditRecord::Response(
+            record.destination.raw_value(),
+            record.header.function.into(),
+            record.outcome,
+            record.objects.to_string(),
+        ));
+    }
+}
@@ -1,16 +1,38 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
 use crate::app::control::CommandStatus;
 use crate::app::variations::{Group12Var1, Group41Var1, Group41Var2, Group41Var3, Group41Var4};
 use crate::outstation::database::Database;
 use crate::outstation::tests::harness::{Control, Event, EventHandle};
-use crate::outstation::traits::{ControlHandler, ControlSupport, OperateType};
+use crate::outstation::traits::{
+    ControlHandler, ControlSupport, OperateCompletion, OperateResult, OperateType,
+};
 
 pub(crate) struct MockControlHandler {
     events: EventHandle,
+    data: Arc<Mutex<ControlData>>,
+}
+
+pub(crate) struct ControlData {
+    /// if true, g41v2 operations complete asynchronously
+    pub(crate) defer_operations: bool,
+    pub(crate) completions: VecDeque<OperateCompletion>,
+}
+
+impl ControlData {
+    fn new() -> Self {
+        Self {
+            defer_operations: false,
+            completions: VecDeque::new(),
+        }
+    }
 }
 
 impl MockControlHandler {
-    pub(crate) fn new(events: EventHandle) -> Box<dyn ControlHandler> {
-        Box::new(Self { events })
+    pub(crate) fn new(events: EventHandle) -> (Arc<Mutex<ControlData>>, Box<dyn ControlHandler>) {
+        let data = Arc::new(Mutex::new(ControlData::new()));
+        (data.clone(), Box::new(Self { events, data }))
     }
 }
 
@@ -72,6 +94,23 @@ impl ControlSupport<Group41Var2> for MockControlHandler {
             .push(Event::Operate(Control::G41V2(control, index), op_type));
         CommandStatus::Success
     }
+
+    fn begin_operate(
+        &mut self,
+        control: Group41Var2,
+        index: u16,
+        op_type: OperateType,
+        database: &mut Database,
+    ) -> OperateResult {
+        if !self.data.lock().unwrap().defer_operations {
+            return OperateResult::Complete(self.operate(control, index, op_type, database));
+        }
+        self.events
+            .push(Event::Operate(Control::G41V2(control, index), op_type));
+        let (result, completion) = OperateResult::pending();
+        self.data.lock().unwrap().completions.push_back(completion);
+        result
+    }
 }
 
 impl ControlSupport<Group41Var3> for MockControlHandler {
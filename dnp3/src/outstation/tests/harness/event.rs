@@ -1,10 +1,11 @@
 use crate::app::variations::{Group12Var1, Group41Var1, Group41Var2, Group41Var3, Group41Var4};
+use crate::link::BroadcastConfirmMode;
 use crate::outstation::traits::{BroadcastAction, OperateType, RestartDelay};
 use crate::outstation::{FreezeIndices, FreezeType};
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 
-use crate::app::{FunctionCode, Timestamp};
+use crate::app::{FunctionCode, IndexedTime, Timestamp};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub(crate) enum Control {
@@ -22,7 +23,7 @@ pub(crate) enum Event {
     Operate(Control, OperateType),
     Freeze(FreezeIndices, FreezeType),
     EndControls,
-    BroadcastReceived(FunctionCode, BroadcastAction),
+    BroadcastReceived(FunctionCode, BroadcastAction, BroadcastConfirmMode),
     EnterSolicitedConfirmWait(u8),
     EnterUnsolicitedConfirmWait(u8),
     SolicitedConfirmTimeout(u8),
@@ -36,6 +37,10 @@ pub(crate) enum Event {
     WarmRestart(Option<RestartDelay>),
     ClearRestartIIN,
     WriteAbsoluteTime(Timestamp),
+    WriteIndexedTime(u16, IndexedTime),
+    WriteIinBit(u16, bool),
+    /// the bool indicates if the request targeted a specific application
+    StartApplication(bool),
 }
 
 #[derive(Clone)]
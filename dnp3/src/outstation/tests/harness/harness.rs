@@ -1,4 +1,5 @@
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use crate::decode::AppDecodeLevel;
 use crate::link::header::{BroadcastConfirmMode, FrameInfo, FrameType};
@@ -8,8 +9,8 @@ use crate::outstation::database::EventBufferConfig;
 use crate::outstation::session::RunError;
 use crate::outstation::task::OutstationTask;
 use crate::outstation::tests::harness::{
-    ApplicationData, Event, EventHandle, MockControlHandler, MockOutstationApplication,
-    MockOutstationInformation,
+    ApplicationData, AuditRecord, ControlData, Event, EventHandle, MockAuditLogger,
+    MockControlHandler, MockOutstationApplication, MockOutstationInformation,
 };
 use crate::outstation::OutstationHandle;
 use crate::tokio::test::*;
@@ -41,6 +42,8 @@ where
     task: Spawn<T>,
     events: EventHandle,
     pub(crate) application_data: Arc<Mutex<ApplicationData>>,
+    pub(crate) control_data: Arc<Mutex<ControlData>>,
+    pub(crate) audit_records: Arc<Mutex<Vec<AuditRecord>>>,
 }
 
 impl<T> OutstationTestHarness<T>
@@ -67,6 +70,17 @@ where
         assert!(self.io.all_written());
     }
 
+    pub(crate) fn expect_response_then_exit(&mut self, response: &[u8]) -> RunError {
+        self.io.write(response);
+        let err = assert_ready!(self.task.poll());
+        assert!(self.io.all_written());
+        err
+    }
+
+    pub(crate) fn poll_exit(&mut self) -> RunError {
+        assert_ready!(self.task.poll())
+    }
+
     pub(crate) fn send(&mut self, request: &[u8]) {
         self.io.read(request);
         self.poll_pending();
@@ -110,31 +124,46 @@ where
 pub(crate) fn new_harness(
     config: OutstationConfig,
 ) -> OutstationTestHarness<impl std::future::Future<Output = RunError>> {
-    new_harness_impl(config, None, None)
+    new_harness_impl(config, None, None, None)
 }
 
 pub(crate) fn new_harness_with_custom_event_buffers(
     config: OutstationConfig,
     event_config: EventBufferConfig,
 ) -> OutstationTestHarness<impl std::future::Future<Output = RunError>> {
-    new_harness_impl(config, Some(event_config), None)
+    new_harness_impl(config, Some(event_config), None, None)
 }
 
 pub(crate) fn new_harness_for_broadcast(
     config: OutstationConfig,
     broadcast: BroadcastConfirmMode,
 ) -> OutstationTestHarness<impl std::future::Future<Output = RunError>> {
-    new_harness_impl(config, None, Some(broadcast))
+    new_harness_impl(config, None, Some(broadcast), None)
+}
+
+pub(crate) fn new_harness_with_idle_timeout(
+    config: OutstationConfig,
+    idle_timeout: Duration,
+) -> OutstationTestHarness<impl std::future::Future<Output = RunError>> {
+    new_harness_impl(config, None, None, Some(idle_timeout))
 }
 
 fn new_harness_impl(
     config: OutstationConfig,
     event_config: Option<EventBufferConfig>,
     broadcast: Option<BroadcastConfirmMode>,
+    idle_timeout: Option<Duration>,
 ) -> OutstationTestHarness<impl std::future::Future<Output = RunError>> {
     let events = EventHandle::new();
 
     let (data, application) = MockOutstationApplication::new(events.clone());
+    let (audit_records, audit_logger) = MockAuditLogger::new();
+    let (control_data, control_handler) = MockControlHandler::new(events.clone());
+    let config = OutstationConfig {
+        audit_logger: Some(audit_logger),
+        ..config
+    };
+    let master_address = config.master_address;
 
     let (task, handle) = OutstationTask::create(
         LinkErrorMode::Close,
@@ -142,18 +171,15 @@ fn new_harness_impl(
         event_config.unwrap_or(EventBufferConfig::all_types(5)),
         application,
         MockOutstationInformation::new(events.clone()),
-        MockControlHandler::new(events.clone()),
+        control_handler,
     );
 
     let mut task = Box::new(task);
+    task.set_idle_timeout(idle_timeout);
 
     task.get_reader()
         .get_inner()
-        .set_rx_frame_info(FrameInfo::new(
-            config.master_address,
-            broadcast,
-            FrameType::Data,
-        ));
+        .set_rx_frame_info(FrameInfo::new(master_address, broadcast, FrameType::Data));
 
     let (io, io_handle) = io::mock();
 
@@ -165,5 +191,7 @@ fn new_harness_impl(
         task: spawn(async move { task.run(&mut io).await }),
         events,
         application_data: data,
+        control_data,
+        audit_records,
     }
 }
@@ -1,6 +1,7 @@
 use crate::app::FunctionCode;
 use crate::app::RequestHeader;
 use crate::app::Sequence;
+use crate::link::BroadcastConfirmMode;
 use crate::outstation::tests::harness::{Event, EventHandle};
 use crate::outstation::traits::{BroadcastAction, OutstationInformation};
 
@@ -19,8 +20,14 @@ impl OutstationInformation for MockOutstationInformation {
         // we ignore this one in tests b/c it's just too noisy
     }
 
-    fn broadcast_received(&mut self, function: FunctionCode, action: BroadcastAction) {
-        self.events.push(Event::BroadcastReceived(function, action))
+    fn broadcast_received(
+        &mut self,
+        function: FunctionCode,
+        action: BroadcastAction,
+        mode: BroadcastConfirmMode,
+    ) {
+        self.events
+            .push(Event::BroadcastReceived(function, action, mode))
     }
 
     fn enter_solicited_confirm_wait(&mut self, ecsn: Sequence) {
@@ -1,10 +1,12 @@
 pub(crate) use application::*;
+pub(crate) use audit::*;
 pub(crate) use control::*;
 pub(crate) use event::*;
 pub(crate) use harness::*;
 pub(crate) use info::*;
 
 mod application;
+mod audit;
 mod control;
 mod event;
 mod harness;
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use crate::outstation::session::RunError;
+use crate::tokio::test::*;
+use crate::tokio::time;
+
+use super::harness::*;
+
+const READ_CLASS_1: &[u8] = &[0xC0, 0x01, 60, 02, 0x06];
+const EMPTY_RESPONSE: &[u8] = &[0xC0, 0x81, 0x80, 0x00];
+
+#[test]
+fn session_ends_once_no_response_is_written_for_the_idle_timeout() {
+    let mut harness = new_harness_with_idle_timeout(get_default_config(), Duration::from_secs(10));
+    harness.poll_pending();
+
+    // a response restarts the idle timeout
+    time::advance(Duration::from_secs(5));
+    harness.test_request_response(READ_CLASS_1, EMPTY_RESPONSE);
+
+    time::advance(Duration::from_secs(9));
+    harness.poll_pending();
+    time::advance(Duration::from_secs(1));
+    assert_eq!(harness.poll_exit(), RunError::IdleTimeout);
+}
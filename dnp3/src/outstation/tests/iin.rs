@@ -81,3 +81,27 @@ fn buffer_overflow() {
     harness.test_request_no_response(CONFIRM_SEQ_0);
     harness.test_request_response(READ_CLASS_123, EMPTY_RESPONSE);
 }
+
+#[test]
+fn application_can_accept_write_of_device_trouble() {
+    let mut harness = new_harness(get_default_config());
+    harness.application_data.lock().unwrap().device_trouble = true;
+
+    harness.test_request_response(
+        &[0xC0, 0x02, 80, 1, 0x00, 0x06, 0x06, 0x00], // Write IIN1.6 DEVICE_TROUBLE to false
+        &[0xC0, 0x81, 0x80, 0x00],                    // IIN1.6 DEVICE_TROUBLE cleared
+    );
+    harness.check_events(&[Event::WriteIinBit(6, false)]);
+}
+
+#[test]
+fn application_can_reject_write_of_iin_bit() {
+    let mut harness = new_harness(get_default_config());
+    harness.application_data.lock().unwrap().device_trouble = true;
+
+    harness.test_request_response(
+        &[0xC0, 0x02, 80, 1, 0x00, 0x06, 0x06, 0x01], // Write IIN1.6 DEVICE_TROUBLE to true
+        &[0xC0, 0x81, 0xC0, 0x04],                    // IIN2.2 PARAMETER_ERROR set
+    );
+    harness.check_events(&[Event::WriteIinBit(6, true)]);
+}
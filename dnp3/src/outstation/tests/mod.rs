@@ -1,15 +1,25 @@
 pub(crate) mod harness;
 
+/// initialize/start/stop application
+mod application;
+/// audit trail of requests and responses
+mod audit;
 /// control functionality
 mod controls;
 /// freeze counters tests
 mod freeze;
+/// closing of the idle connections of dial-up clients
+mod idle;
 /// various IIN bit tests
 mod iin;
 /// state machine for READ requests
 mod read_states;
 /// clear restart IIN + cold/warm restart
 mod restart;
+/// graceful shutdown
+mod shutdown;
+/// forced IIN bits and delayed responses used by simulations
+mod simulation;
 /// time synchronization
 mod time;
 /// unsolicited responses
@@ -1,3 +1,5 @@
+use std::num::NonZeroU16;
+
 use tokio::time::Duration;
 
 use crate::app::measurement::*;
@@ -106,3 +108,92 @@ fn sol_confirm_wait_goes_back_to_idle_with_new_invalid_request() {
     );
     harness.check_events(&[Event::SolicitedConfirmWaitNewRequest]);
 }
+
+#[test]
+fn encodes_events_using_the_variation_requested_by_the_master() {
+    const READ_G2V2: &[u8] = &[0xC0, 0x01, 0x02, 0x02, 0x06];
+    const BINARY_EVENT_G2V2_RESPONSE: &[u8] = &[
+        0xE0, 0x81, 0x80, 0x00, 0x02, 0x02, 0x28, 0x01, 0x00, 0x00, 0x00, 0x81, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00,
+    ];
+
+    let mut harness = new_harness(get_default_config());
+
+    harness.handle.database.transaction(create_binary_and_event);
+
+    harness.test_request_response(READ_G2V2, BINARY_EVENT_G2V2_RESPONSE);
+    harness.check_events(&[Event::EnterSolicitedConfirmWait(0)]);
+    crate::tokio::time::advance(get_default_config().confirm_timeout + Duration::from_millis(1));
+    harness.poll_pending();
+    harness.check_events(&[Event::SolicitedConfirmTimeout(0)]);
+
+    // a subsequent class read reverts to the configured default variation
+    harness.test_request_response(READ_CLASS_123, BINARY_EVENT_RESPONSE);
+}
+
+#[test]
+fn event_limit_of_one_writes_each_event_in_its_own_fragment() {
+    let mut config = get_default_config();
+    config.max_events_per_solicited_response = NonZeroU16::new(1);
+    let mut harness = new_harness(config);
+
+    harness.handle.database.transaction(|database| {
+        create_binary_and_event(database);
+        database.update(
+            0,
+            &Binary::new(false, Flags::ONLINE, Time::Synchronized(Timestamp::new(0))),
+            UpdateOptions::default(),
+        );
+    });
+
+    // FIR without FIN, the remaining class 1 event is reported in IIN1
+    harness.test_request_response(
+        READ_CLASS_123,
+        &[
+            0xA0, 0x81, 0x82, 0x00, 0x02, 0x01, 0x28, 0x01, 0x00, 0x00, 0x00, 0x81,
+        ],
+    );
+    harness.check_events(&[Event::EnterSolicitedConfirmWait(0)]);
+
+    // the confirm releases the last fragment
+    harness.test_request_response(
+        CONFIRM_SEQ_0,
+        &[
+            0x61, 0x81, 0x80, 0x00, 0x02, 0x01, 0x28, 0x01, 0x00, 0x00, 0x00, 0x01,
+        ],
+    );
+    harness.check_events(&[Event::SolicitedConfirmReceived(0)]);
+    harness.send(CONFIRM_SEQ_1);
+    harness.check_events(&[Event::SolicitedConfirmReceived(1)]);
+}
+
+#[test]
+fn reads_point_metadata_as_device_attributes() {
+    let mut harness = new_harness(get_default_config());
+
+    harness.handle.database.transaction(|database| {
+        database.add(3, None, BinaryConfig::default());
+        let metadata = PointMetadata {
+            attribute_set: Some(1),
+            ..PointMetadata::new("breaker")
+        };
+        assert!(Metadata::<Binary>::set_metadata(database, 3, metadata));
+    });
+
+    // read all attributes of set 1: the group (1), index (3), and name of the point
+    harness.test_request_response(
+        &[0xC0, 0x01, 0x00, 0xFE, 0x00, 0x01, 0x01],
+        &[
+            0xC0, 0x81, 0x80, 0x00, 0x00, 0x01, 0x01, 0x01, 0x00, 0x01, 0x00, 0x02, 0x04, 0x01,
+            0x00, 0x00, 0x00, 0x00, 0x02, 0x01, 0x01, 0x00, 0x01, 0x00, 0x02, 0x04, 0x03, 0x00,
+            0x00, 0x00, 0x00, 0x03, 0x01, 0x01, 0x00, 0x01, 0x00, 0x01, 0x07, b'b', b'r', b'e',
+            b'a', b'k', b'e', b'r',
+        ],
+    );
+
+    // the standard attribute set isn't supported
+    harness.test_request_response(
+        &[0xC1, 0x01, 0x00, 0xFE, 0x00, 0x00, 0x00],
+        &[0xC1, 0x81, 0x80, 0x02],
+    );
+}
@@ -1,3 +1,5 @@
+use crate::app::FunctionCode;
+use crate::outstation::config::FunctionCodes;
 use crate::outstation::tests::harness::*;
 use crate::outstation::traits::RestartDelay;
 
@@ -25,6 +27,18 @@ fn handles_cold_restart_when_not_supported() {
     harness.check_events(&[Event::ColdRestart(None)])
 }
 
+#[test]
+fn rejects_cold_restart_when_disabled() {
+    let mut config = get_default_config();
+    config.functions = FunctionCodes::all().disable(FunctionCode::ColdRestart);
+    let mut harness = new_harness(config);
+    harness.application_data.lock().unwrap().restart_delay =
+        Some(RestartDelay::Milliseconds(0xCAFE));
+    harness.test_request_response(COLD_RESTART, RESPONSE_NO_FUNCTION_SUPPORT);
+    // the application is never asked to restart
+    harness.check_no_events();
+}
+
 #[test]
 fn handles_cold_restart_when_supported_via_time_delay_fine() {
     let mut harness = new_harness(get_default_config());
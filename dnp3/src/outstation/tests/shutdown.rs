@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use crate::outstation::session::RunError;
+use crate::tokio::test::*;
+
+use super::harness::*;
+
+const READ_CLASS_1: &[u8] = &[0xC0, 0x01, 60, 02, 0x06];
+const EMPTY_RESPONSE: &[u8] = &[0xC0, 0x81, 0x80, 0x00];
+
+#[test]
+fn shutdown_of_idle_outstation_completes_immediately() {
+    let mut harness = new_harness(get_default_config());
+    let mut handle = harness.handle.clone();
+
+    let mut shutdown = spawn(handle.shutdown());
+    assert_pending!(shutdown.poll());
+    assert_eq!(harness.poll_exit(), RunError::Shutdown);
+    assert_ready!(shutdown.poll());
+}
+
+#[test]
+fn shutdown_waits_for_response_in_progress() {
+    let mut harness = new_harness(get_default_config());
+    let mut handle = harness.handle.clone();
+
+    let delay = Duration::from_secs(2);
+    assert_ready!(spawn(handle.set_response_delay(Some(delay))).poll()).unwrap();
+    harness.poll_pending();
+
+    harness.send(READ_CLASS_1);
+    let mut shutdown = spawn(handle.shutdown());
+    assert_pending!(shutdown.poll());
+    harness.poll_pending();
+    assert_pending!(shutdown.poll());
+
+    crate::tokio::time::advance(delay);
+    assert_eq!(
+        harness.expect_response_then_exit(EMPTY_RESPONSE),
+        RunError::Shutdown
+    );
+    assert_ready!(shutdown.poll());
+}
+
+#[test]
+fn shutdown_of_exited_outstation_completes_immediately() {
+    let mut harness = new_harness(get_default_config());
+    let mut handle = harness.handle.clone();
+
+    assert_ready!(spawn(handle.stop()).poll()).unwrap();
+    assert_eq!(harness.poll_exit(), RunError::Shutdown);
+    assert_ready!(spawn(handle.shutdown()).poll());
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use crate::app::{Iin, Iin1, Iin2};
+use crate::tokio::test::*;
+
+use super::harness::*;
+
+const READ_CLASS_1: &[u8] = &[0xC0, 0x01, 60, 02, 0x06];
+const EMPTY_RESPONSE: &[u8] = &[0xC0, 0x81, 0x80, 0x00];
+
+#[test]
+fn forced_iin_bits_are_set_in_responses() {
+    let mut harness = new_harness(get_default_config());
+    let mut handle = harness.handle.clone();
+
+    let iin = Iin::new(Iin1::new(0), Iin2::new(0xC0));
+    assert_ready!(spawn(handle.set_forced_iin(iin)).poll()).unwrap();
+    harness.poll_pending();
+    harness.test_request_response(READ_CLASS_1, &[0xC0, 0x81, 0x80, 0xC0]);
+
+    assert_ready!(spawn(handle.set_forced_iin(Iin::default())).poll()).unwrap();
+    harness.poll_pending();
+    harness.test_request_response(READ_CLASS_1, EMPTY_RESPONSE);
+}
+
+#[test]
+fn solicited_responses_are_delayed() {
+    let mut harness = new_harness(get_default_config());
+    let mut handle = harness.handle.clone();
+
+    let delay = Duration::from_secs(2);
+    assert_ready!(spawn(handle.set_response_delay(Some(delay))).poll()).unwrap();
+    harness.poll_pending();
+
+    harness.send(READ_CLASS_1);
+    crate::tokio::time::advance(delay - Duration::from_millis(1));
+    harness.poll_pending();
+    crate::tokio::time::advance(Duration::from_millis(1));
+    harness.expect_response(EMPTY_RESPONSE);
+
+    assert_ready!(spawn(handle.set_response_delay(None)).poll()).unwrap();
+    harness.poll_pending();
+    harness.test_request_response(READ_CLASS_1, EMPTY_RESPONSE);
+}
@@ -1,4 +1,4 @@
-use crate::app::Timestamp;
+use crate::app::{IndexedTime, Timestamp};
 
 use super::harness::*;
 
@@ -12,6 +12,10 @@ const RECORD_CURRENT_TIME: &[u8] = &[0xC0, 0x18];
 const WRITE_LAST_RECORDED_TIME: &[u8] = &[
     0xC1, 0x02, 50, 3, 0x07, 1, 0xFE, 0xCA, 0x00, 0x00, 0x00, 0x00,
 ];
+const WRITE_INDEXED_TIME: &[u8] = &[
+    0xC1, 0x02, 50, 4, 0x17, 2, 0x03, 0xFE, 0xCA, 0x00, 0x00, 0x00, 0x00, 0x0F, 0x00, 0x00, 0x00,
+    0x03, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
 
 const EMPTY_RESPONSE_SEQ0: &[u8] = &[0xC0, 0x81, 0x80, 0x00];
 const EMPTY_RESPONSE_SEQ1: &[u8] = &[0xC1, 0x81, 0x80, 0x00];
@@ -48,3 +52,15 @@ fn lan_procedure() {
 
     harness.check_events(&[Event::WriteAbsoluteTime(Timestamp::new(0 + 0xCAFE))]);
 }
+
+#[test]
+fn writes_indexed_time_to_application() {
+    let mut harness = new_harness(get_default_config());
+
+    harness.test_request_response(WRITE_INDEXED_TIME, EMPTY_RESPONSE_SEQ1);
+
+    harness.check_events(&[
+        Event::WriteIndexedTime(3, IndexedTime::new(Timestamp::new(0xCAFE), 15, 3)),
+        Event::WriteIndexedTime(4, IndexedTime::new(Timestamp::new(0), 0, 0)),
+    ]);
+}
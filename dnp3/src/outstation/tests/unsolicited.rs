@@ -2,6 +2,7 @@ use crate::app::{measurement::*, Timestamp};
 use crate::outstation::config::OutstationConfig;
 use crate::outstation::database::*;
 use crate::outstation::session::RunError;
+use crate::outstation::UnsolicitedStatistics;
 
 use super::harness::*;
 
@@ -104,6 +105,57 @@ fn unsolicited_can_time_out_and_retry() {
     }
 }
 
+#[test]
+fn unsolicited_statistics_count_responses_and_retries() {
+    let mut harness = new_harness(get_default_unsolicited_config());
+    confirm_null_unsolicited(&mut harness);
+    enable_unsolicited(&mut harness);
+    generate_binary_event(&mut harness.handle.database);
+
+    harness.expect_response(UNSOL_G2V1_SEQ1);
+    harness.check_events(&[Event::EnterUnsolicitedConfirmWait(1)]);
+    crate::tokio::time::advance(OutstationConfig::DEFAULT_CONFIRM_TIMEOUT);
+    harness.expect_response(UNSOL_G2V1_SEQ1);
+    harness.check_events(&[Event::UnsolicitedConfirmTimeout(1, true)]);
+
+    assert_eq!(
+        harness.handle.get_unsolicited_statistics(),
+        UnsolicitedStatistics {
+            buffered_events: 1,
+            deferred: 0,
+            responses: 2,
+            retries: 1,
+        }
+    );
+
+    harness.send(UNS_CONFIRM_SEQ_1);
+    harness.check_events(&[Event::UnsolicitedConfirmReceived(1)]);
+    assert_eq!(
+        harness.handle.get_unsolicited_statistics().buffered_events,
+        0
+    );
+}
+
+#[test]
+fn unsolicited_waits_for_changes_to_coalesce() {
+    let mut config = get_default_unsolicited_config();
+    config.change_coalescing_delay = Some(std::time::Duration::from_millis(100));
+    let mut harness = new_harness(config);
+    confirm_null_unsolicited(&mut harness);
+    enable_unsolicited(&mut harness);
+    generate_binary_event(&mut harness.handle.database);
+
+    // held back until the delay elapses
+    harness.poll_pending();
+    crate::tokio::time::advance(std::time::Duration::from_millis(99));
+    harness.poll_pending();
+    harness.check_all_io_consumed();
+
+    crate::tokio::time::advance(std::time::Duration::from_millis(1));
+    harness.expect_response(UNSOL_G2V1_SEQ1);
+    harness.check_events(&[Event::EnterUnsolicitedConfirmWait(1)]);
+}
+
 #[test]
 fn unsolicited_can_timeout_and_not_retry() {
     let mut harness = new_harness(config_with_limited_retries(2));
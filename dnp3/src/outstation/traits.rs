@@ -4,7 +4,8 @@ use crate::app::parse::traits::{FixedSizeVariation, Index};
 use crate::app::FunctionCode;
 use crate::app::RequestHeader;
 use crate::app::Sequence;
-use crate::app::{control::*, Timestamp};
+use crate::app::{control::*, IndexedTime, Timestamp};
+use crate::link::BroadcastConfirmMode;
 use crate::outstation::database::Database;
 
 /// Application-controlled IIN bits
@@ -51,13 +52,43 @@ pub enum WriteTimeResult {
     Ok,
 }
 
+/// Enum describing the result of a WRITE to an IIN bit other than IIN1.7 (DEVICE_RESTART)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WriteIinResult {
+    /// outstation does not support writing the specified IIN bit to the specified value
+    NotSupported,
+    /// the write was accepted by the application
+    Ok,
+}
+
+/// Application targeted by an INITIALIZE_APPLICATION, START_APPLICATION, or STOP_APPLICATION request
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ApplicationIdentifier<'a> {
+    /// g90v1 with qualifier 0x06 - all applications
+    All,
+    /// g90v1 with qualifier 0x5B - the application with the specified free-format identifier
+    Name(&'a [u8]),
+}
+
+/// Enum describing the result of an INITIALIZE_APPLICATION, START_APPLICATION, or STOP_APPLICATION request
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ApplicationOperationResult {
+    /// outstation does not support the operation (translated to NO_FUNC_CODE_SUPPORT)
+    NotSupported,
+    /// the specified application does not exist (translated to PARAMETER_ERROR)
+    UnknownApplication,
+    /// success
+    Ok,
+}
+
 /// Outstation connection state for connection-oriented transports, e.g. TCP
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum ConnectionState {
-    /// Connected to the master
-    Connected,
-    /// Disconnected from the master
-    Disconnected,
+    /// Connected to the remote address
+    Connected(std::net::SocketAddr),
+    /// Disconnected for the specified reason
+    Disconnected(crate::app::DisconnectReason),
 }
 
 /// dynamic information required by the outstation from the user application
@@ -83,11 +114,37 @@ pub trait OutstationApplication: Sync + Send + 'static {
         WriteTimeResult::NotSupported
     }
 
+    /// Handle a write of an indexed absolute time and interval (g50v4)
+    ///
+    /// This is typically used to configure the schedule of a freeze. It is called once for each
+    /// object in the request.
+    fn write_indexed_time(&mut self, _index: u16, _value: IndexedTime) -> WriteTimeResult {
+        WriteTimeResult::NotSupported
+    }
+
     /// Returns the application-controlled IIN bits
+    ///
+    /// This method is called every time the outstation formats a response. It is always
+    /// called after any of the other callbacks in the same request have completed, so
+    /// changes made to the application state in [`OutstationApplication::write_iin_bit`]
+    /// are reflected in the response to the WRITE itself.
     fn get_application_iin(&self) -> ApplicationIin {
         ApplicationIin::default()
     }
 
+    /// Handle a WRITE of a g80v1 IIN bit other than IIN1.7 (DEVICE_RESTART) which is
+    /// always handled by the outstation itself.
+    ///
+    /// The index is the bit position where 0..7 are IIN1.0 to IIN1.7 and 8..15 are
+    /// IIN2.0 to IIN2.7, e.g. index 4 is IIN1.4 (NEED_TIME) and index 6 is IIN1.6 (DEVICE_TROUBLE).
+    ///
+    /// Returning `WriteIinResult::NotSupported` causes the outstation to respond with
+    /// IIN2.2 PARAMETER_ERROR. If the write is accepted, the application is responsible for
+    /// updating the value it subsequently returns from [`OutstationApplication::get_application_iin`].
+    fn write_iin_bit(&mut self, _index: u16, _value: bool) -> WriteIinResult {
+        WriteIinResult::NotSupported
+    }
+
     /// Request that the outstation perform a cold restart (IEEE-1815 2012, pg. 58)
     ///
     /// If supported, return Some(RestartDelay) indicating how long the restart
@@ -114,6 +171,36 @@ pub trait OutstationApplication: Sync + Send + 'static {
         None
     }
 
+    /// Request that the outstation initialize an application (IEEE-1815 2012, pg. 59)
+    ///
+    /// This method is called once for each g90v1 object header in the request
+    fn initialize_application(
+        &mut self,
+        _application: ApplicationIdentifier,
+    ) -> ApplicationOperationResult {
+        ApplicationOperationResult::NotSupported
+    }
+
+    /// Request that the outstation start an application (IEEE-1815 2012, pg. 59)
+    ///
+    /// This method is called once for each g90v1 object header in the request
+    fn start_application(
+        &mut self,
+        _application: ApplicationIdentifier,
+    ) -> ApplicationOperationResult {
+        ApplicationOperationResult::NotSupported
+    }
+
+    /// Request that the outstation stop an application (IEEE-1815 2012, pg. 59)
+    ///
+    /// This method is called once for each g90v1 object header in the request
+    fn stop_application(
+        &mut self,
+        _application: ApplicationIdentifier,
+    ) -> ApplicationOperationResult {
+        ApplicationOperationResult::NotSupported
+    }
+
     /// Perform a counter freeze operation
     fn freeze_counter(
         &mut self,
@@ -145,7 +232,15 @@ pub trait OutstationInformation: Sync + Send + 'static {
     /// called when a request is processed from the IDLE state
     fn process_request_from_idle(&mut self, _header: RequestHeader) {}
     /// called when a broadcast request is received by the outstation
-    fn broadcast_received(&mut self, _function: FunctionCode, _action: BroadcastAction) {}
+    ///
+    /// `mode` identifies the broadcast address on which the request was received
+    fn broadcast_received(
+        &mut self,
+        _function: FunctionCode,
+        _action: BroadcastAction,
+        _mode: BroadcastConfirmMode,
+    ) {
+    }
     /// outstation has begun waiting for a solicited confirm
     fn enter_solicited_confirm_wait(&mut self, _ecsn: Sequence) {}
     /// failed to receive a solicited confirm before the timeout occurred
@@ -220,6 +315,70 @@ pub trait ControlSupport<T> {
         op_type: OperateType,
         database: &mut Database,
     ) -> CommandStatus;
+
+    /// Begin operating a control point, possibly completing the operation asynchronously
+    ///
+    /// The default implementation calls [operate](ControlSupport::operate) and completes immediately.
+    /// Override this method to return [OperateResult::Pending] when the operation takes time to
+    /// complete, e.g. it is forwarded to a downstream device. The response is held back while the
+    /// session keeps running, and is sent once the pending operations of the request complete or
+    /// `OutstationConfig::control_completion_timeout` elapses. Operations that have not completed by
+    /// then, or by the time the master sends another request, are reported as `CommandStatus::Timeout`.
+    ///
+    /// If this method is overridden, the outstation never calls `operate` directly. The completion of a
+    /// pending DirectOperateNoAck is ignored since no response is sent.
+    fn begin_operate(
+        &mut self,
+        control: T,
+        index: u16,
+        op_type: OperateType,
+        database: &mut Database,
+    ) -> OperateResult {
+        OperateResult::Complete(self.operate(control, index, op_type, database))
+    }
+}
+
+/// Result of beginning an operation on a control point
+#[derive(Debug)]
+pub enum OperateResult {
+    /// operation completed with the specified status
+    Complete(CommandStatus),
+    /// operation is in progress and will be completed using the [OperateCompletion] returned by [OperateResult::pending]
+    Pending(PendingOperate),
+}
+
+impl OperateResult {
+    /// create a pending result along with the handle used to complete it
+    pub fn pending() -> (Self, OperateCompletion) {
+        let (tx, rx) = crate::tokio::sync::oneshot::channel();
+        (
+            OperateResult::Pending(PendingOperate { rx }),
+            OperateCompletion { tx },
+        )
+    }
+}
+
+/// Outstation side of an operation that completes asynchronously
+#[derive(Debug)]
+pub struct PendingOperate {
+    pub(crate) rx: crate::tokio::sync::oneshot::Receiver<CommandStatus>,
+}
+
+/// Handle used to complete an operation that returned [OperateResult::Pending]
+///
+/// If the handle is dropped without calling [complete](OperateCompletion::complete), the operation
+/// is reported to the master as `CommandStatus::Canceled`.
+#[derive(Debug)]
+pub struct OperateCompletion {
+    tx: crate::tokio::sync::oneshot::Sender<CommandStatus>,
+}
+
+impl OperateCompletion {
+    /// complete the operation with the specified status
+    pub fn complete(self, status: CommandStatus) {
+        // the outstation may have stopped waiting, e.g. the completion timeout elapsed
+        let _ = self.tx.send(status);
+    }
 }
 
 /// Indices used by freeze operations
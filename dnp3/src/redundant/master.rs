@@ -0,0 +1,238 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use tracing::Instrument;
+
+use crate::app::{DisconnectReason, Listener, Shutdown, Spawner};
+use crate::link::statistics::LinkCounters;
+use crate::link::LinkErrorMode;
+use crate::master::session::{MasterSession, RunError, StateChange};
+use crate::master::{MasterChannel, MasterChannelConfig};
+use crate::redundant::{Next, Path, PathConfig, PathSelector, RedundancyConfig, RedundantState};
+#[cfg(feature = "tcp")]
+use crate::tokio::net::TcpStream;
+use crate::transport::TransportReader;
+use crate::transport::TransportWriter;
+use crate::util::phys::PhysLayer;
+
+/// Spawn a master task onto the `Tokio` runtime which communicates over a primary path and
+/// fails over to a standby path. The task runs until the returned handle, and any
+/// `AssociationHandle` created from it, are dropped.
+///
+/// Both paths share the associations of the channel, so polls, user requests, and handlers are
+/// unaffected by a fail-over. Link-layer parsing errors are always discarded because either
+/// path may be a lossy medium.
+///
+/// **Note**: This function may only be called from within the runtime itself, and panics otherwise.
+/// It is preferable to use this method instead of `create(..)` when using `[tokio::main]`.
+pub fn spawn_master_redundant(
+    config: MasterChannelConfig,
+    primary: PathConfig,
+    standby: PathConfig,
+    redundancy: RedundancyConfig,
+    listener: Box<dyn Listener<RedundantState>>,
+) -> MasterChannel {
+    let spawner = config.spawner.clone();
+    let (future, handle) = create_master_redundant(config, primary, standby, redundancy, listener);
+    Spawner::spawn(spawner.as_deref(), future);
+    handle
+}
+
+/// Create a master future which communicates over a primary path and fails over to a standby
+/// path, which can be spawned onto a runtime, along with a controlling handle.
+///
+/// Once spawned or otherwise executed using the `run` method, the task runs until the handle
+/// and any `AssociationHandle` created from it are dropped.
+///
+/// **Note**: This function is required instead of `spawn` when using a runtime to directly spawn
+/// tasks instead of within the context of a runtime, e.g. in applications that cannot use
+/// `[tokio::main]` such as C language bindings.
+pub fn create_master_redundant(
+    config: MasterChannelConfig,
+    primary: PathConfig,
+    standby: PathConfig,
+    redundancy: RedundancyConfig,
+    listener: Box<dyn Listener<RedundantState>>,
+) -> (impl Future<Output = ()> + 'static, MasterChannel) {
+    let tracing_config = config.tracing;
+    let (mut task, handle) = MasterTask::new(config, primary, standby, redundancy, listener);
+    let future = async move {
+        let span = tracing_config.channel_span(tracing::info_span!("DNP3-Master-Redundant"));
+        task.run().instrument(span).await;
+    };
+    (future, handle)
+}
+
+struct MasterTask {
+    primary: PathConfig,
+    standby: PathConfig,
+    redundancy: RedundancyConfig,
+    session: MasterSession,
+    reader: TransportReader,
+    writer: TransportWriter,
+    listener: Box<dyn Listener<RedundantState>>,
+}
+
+impl MasterTask {
+    fn new(
+        config: MasterChannelConfig,
+        primary: PathConfig,
+        standby: PathConfig,
+        redundancy: RedundancyConfig,
+        listener: Box<dyn Listener<RedundantState>>,
+    ) -> (Self, MasterChannel) {
+        let (tx, rx) = crate::util::channel::request_channel();
+        let link_counters = Arc::new(LinkCounters::new(
+            config.clock,
+            config.tracing,
+            config.buffer_pool,
+        ));
+        let session = MasterSession::new(
+            false,
+            config.decode_level,
+            config.response_timeout,
+            config.scheduling,
+            config.link,
+            link_counters.clone(),
+            config.tracing,
+            config.tx_buffer_size,
+            config.rx_buffer_size,
+            rx,
+        );
+        let (reader, writer) = crate::transport::create_master_transport_layer(
+            // either path may be a lossy medium, so link parsing errors are always discarded
+            LinkErrorMode::Discard,
+            config.master_address,
+            config.rx_buffer_size,
+            config.link,
+            config.response_timeout.into(),
+            config.transport,
+            config.unknown_destination,
+            link_counters.clone(),
+        );
+        let task = Self {
+            primary,
+            standby,
+            redundancy,
+            session,
+            reader,
+            writer,
+            listener,
+        };
+        (task, MasterChannel::new(tx, link_counters))
+    }
+
+    async fn run(&mut self) {
+        let _ = self.run_impl().await;
+        self.session.shutdown().await;
+        self.listener.update(RedundantState::Shutdown);
+    }
+
+    async fn run_impl(&mut self) -> Result<(), Shutdown> {
+        loop {
+            self.listener.update(RedundantState::Disabled);
+            self.session.wait_for_enabled().await?;
+            if let Err(StateChange::Shutdown) = self.run_enabled().await {
+                return Err(Shutdown);
+            }
+        }
+    }
+
+    async fn run_enabled(&mut self) -> Result<(), StateChange> {
+        let mut selector = PathSelector::new();
+        loop {
+            let path = selector.current();
+            self.listener.update(RedundantState::Opening(path));
+            let next = match self.open(path).await {
+                Err(reason) => {
+                    tracing::warn!("unable to open {:?} path: {:?}", path, reason);
+                    self.listener.update(RedundantState::Closed(path, reason));
+                    selector.on_open_failure()
+                }
+                Ok(mut io) => {
+                    tracing::info!("{:?} path open", path);
+                    selector.on_opened();
+                    self.listener.update(RedundantState::Active(path));
+                    match self.run_path(path, &mut io).await {
+                        RunError::State(StateChange::Disconnect) => {
+                            tracing::info!("closing standby path to try the primary path");
+                            self.listener.update(RedundantState::Closed(
+                                path,
+                                DisconnectReason::ReturnToMain,
+                            ));
+                            selector.on_return_to_primary()
+                        }
+                        RunError::State(x) => {
+                            if x == StateChange::Disable {
+                                self.listener.update(RedundantState::Closed(
+                                    path,
+                                    DisconnectReason::Disabled,
+                                ));
+                            }
+                            return Err(x);
+                        }
+                        RunError::Link(err) => {
+                            tracing::warn!("{:?} path lost: {}", path, err);
+                            self.listener
+                                .update(RedundantState::Closed(path, err.into()));
+                            selector.on_path_lost()
+                        }
+                    }
+                }
+            };
+
+            if let Next::WaitThenOpen(_) = next {
+                let delay = self.redundancy.retry_delay;
+                tracing::warn!(
+                    "unable to open either path - waiting {} ms to retry",
+                    delay.as_millis()
+                );
+                self.listener.update(RedundantState::Wait(delay));
+                self.session.wait_for_retry(delay).await?;
+            }
+        }
+    }
+
+    async fn run_path(&mut self, path: Path, io: &mut PhysLayer) -> RunError {
+        match (path, self.redundancy.return_to_primary) {
+            (Path::Standby, Some(hold_time)) => {
+                let deadline = self.session.clock().now() + hold_time;
+                self.session
+                    .run_until(io, &mut self.writer, &mut self.reader, deadline)
+                    .await
+            }
+            _ => {
+                self.session
+                    .run(io, &mut self.writer, &mut self.reader)
+                    .await
+            }
+        }
+    }
+
+    async fn open(&mut self, path: Path) -> Result<PhysLayer, DisconnectReason> {
+        let config = match path {
+            Path::Primary => &mut self.primary,
+            Path::Standby => &mut self.standby,
+        };
+        match config {
+            #[cfg(feature = "tcp")]
+            PathConfig::Tcp(endpoints) => {
+                let address = endpoints
+                    .next_address()
+                    .await
+                    .ok_or(DisconnectReason::Io(std::io::ErrorKind::NotFound))?;
+                let socket = TcpStream::connect(address)
+                    .await
+                    .map_err(|err| DisconnectReason::Io(err.kind()))?;
+                endpoints.on_connected();
+                Ok(PhysLayer::Tcp(socket))
+            }
+            #[cfg(feature = "serial")]
+            PathConfig::Serial(port, settings) => {
+                let serial = crate::serial::open(port.as_str(), *settings, self.session.clock())
+                    .map_err(|err| DisconnectReason::Io(std::io::Error::from(err).kind()))?;
+                Ok(PhysLayer::Serial(serial))
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use crate::app::DisconnectReason;
+#[cfg(feature = "serial")]
+use crate::serial::SerialSettings;
+#[cfg(feature = "tcp")]
+use crate::tcp::EndpointList;
+
+pub use master::*;
+
+mod master;
+
+/// Physical channel used by one path of a redundant master
+#[derive(Clone, Debug)]
+pub enum PathConfig {
+    /// TCP client connection to the endpoints in the list
+    #[cfg(feature = "tcp")]
+    Tcp(EndpointList),
+    /// Serial port at the specified path
+    #[cfg(feature = "serial")]
+    Serial(String, SerialSettings),
+}
+
+/// Identifies one of the two paths of a redundant master
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Path {
+    /// path that is preferred whenever it is available
+    Primary,
+    /// path that is used when the primary path fails
+    Standby,
+}
+
+impl Path {
+    fn other(self) -> Self {
+        match self {
+            Self::Primary => Self::Standby,
+            Self::Standby => Self::Primary,
+        }
+    }
+}
+
+/// Controls when a redundant master switches between its paths
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RedundancyConfig {
+    /// delay before trying the primary path again after both paths failed to open
+    pub retry_delay: Duration,
+    /// amount of time the standby path is used before the primary path is tried again
+    ///
+    /// The standby path is closed between tasks, never while a request is outstanding. A value
+    /// of `None` keeps using the standby path until it fails.
+    pub return_to_primary: Option<Duration>,
+}
+
+impl RedundancyConfig {
+    /// Create a configuration that returns to the primary path after the specified time
+    pub fn new(retry_delay: Duration, return_to_primary: Option<Duration>) -> Self {
+        Self {
+            retry_delay,
+            return_to_primary,
+        }
+    }
+}
+
+/// State of a redundant master channel
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RedundantState {
+    /// Disabled and idle until enabled
+    Disabled,
+    /// Attempting to open the path
+    Opening(Path),
+    /// Communicating with the outstations over the path
+    Active(Path),
+    /// The path failed to open or was closed for the specified reason
+    Closed(Path, DisconnectReason),
+    /// Both paths failed to open, waiting to retry
+    Wait(Duration),
+    /// Task has been shut down
+    Shutdown,
+}
+
+/// What to do after a path failed to open or was closed
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Next {
+    /// open the path immediately
+    Open(Path),
+    /// wait before opening the path
+    WaitThenOpen(Path),
+}
+
+/// Selects the path to use based on the outcome of the previous one
+struct PathSelector {
+    current: Path,
+    failed_opens: usize,
+}
+
+impl PathSelector {
+    fn new() -> Self {
+        Self {
+            current: Path::Primary,
+            failed_opens: 0,
+        }
+    }
+
+    fn current(&self) -> Path {
+        self.current
+    }
+
+    fn on_opened(&mut self) {
+        self.failed_opens = 0;
+    }
+
+    /// the current path couldn't be opened
+    fn on_open_failure(&mut self) -> Next {
+        self.failed_opens += 1;
+        if self.failed_opens < 2 {
+            self.current = self.current.other();
+            return Next::Open(self.current);
+        }
+
+        // both paths failed in a row
+        self.failed_opens = 0;
+        self.current = Path::Primary;
+        Next::WaitThenOpen(self.current)
+    }
+
+    /// the current path was open and then failed
+    fn on_path_lost(&mut self) -> Next {
+        self.current = self.current.other();
+        Next::Open(self.current)
+    }
+
+    /// the standby path was closed to try the primary path again
+    fn on_return_to_primary(&mut self) -> Next {
+        self.current = Path::Primary;
+        Next::Open(self.current)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fails_over_to_standby_when_primary_is_lost() {
+        let mut selector = PathSelector::new();
+        selector.on_opened();
+        assert_eq!(selector.on_path_lost(), Next::Open(Path::Standby));
+        selector.on_opened();
+        assert_eq!(selector.on_return_to_primary(), Next::Open(Path::Primary));
+    }
+
+    #[test]
+    fn waits_after_both_paths_fail_to_open() {
+        let mut selector = PathSelector::new();
+        assert_eq!(selector.on_open_failure(), Next::Open(Path::Standby));
+        assert_eq!(
+            selector.on_open_failure(),
+            Next::WaitThenOpen(Path::Primary)
+        );
+        assert_eq!(selector.on_open_failure(), Next::Open(Path::Standby));
+        selector.on_opened();
+        assert_eq!(selector.on_path_lost(), Next::Open(Path::Primary));
+        assert_eq!(selector.on_open_failure(), Next::Open(Path::Standby));
+    }
+}
@@ -0,0 +1,444 @@
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::decode::DecodeDirection;
+use crate::link::constant;
+use crate::link::header::constants::MASK_DIR;
+
+/// Block or record read from a pcapng or pcap capture
+///
+/// Captures written by the library contain exactly one link-layer frame per entry. Captures made
+/// by other tools may split or combine frames, so the entries are best replayed as a byte stream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapturedFrame {
+    /// time at which the data was captured
+    pub timestamp: SystemTime,
+    /// direction relative to the capturing device, if recorded in the capture
+    pub direction: Option<DecodeDirection>,
+    /// captured bytes
+    pub data: Vec<u8>,
+}
+
+/// Errors that occur when reading a capture
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CaptureReadError {
+    /// the capture could not be read
+    Io(std::io::ErrorKind),
+    /// the capture doesn't start with a pcapng section header or a pcap magic number
+    UnknownFormat(u32),
+    /// the capture ends in the middle of a block or record
+    Truncated,
+    /// a block has a length that is too short or not a multiple of 4
+    BadBlockLength(u32),
+}
+
+impl CapturedFrame {
+    /// Construct a frame from its fields
+    pub fn new(timestamp: SystemTime, direction: Option<DecodeDirection>, data: Vec<u8>) -> Self {
+        Self {
+            timestamp,
+            direction,
+            data,
+        }
+    }
+
+    /// DIR bit of the link-layer header if the data starts with one
+    ///
+    /// `Some(true)` means that the frame was sent by a master
+    pub fn is_from_master(&self) -> Option<bool> {
+        match self.data.get(0..4) {
+            Some([constant::START1, constant::START2, _, control]) => Some(control & MASK_DIR != 0),
+            _ => None,
+        }
+    }
+}
+
+/// Read every frame of a pcapng or pcap capture
+///
+/// The format is detected from the first four bytes. Only the blocks that contain packets are
+/// returned, in the order they appear in the capture.
+pub fn read_capture<R: Read>(mut reader: R) -> Result<Vec<CapturedFrame>, CaptureReadError> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let magic = Input::new(&data, false).u32_at(0)?;
+    match magic {
+        PcapNg::SECTION_HEADER => PcapNg::read(&data),
+        _ => Pcap::read(&data),
+    }
+}
+
+/// Read every frame of a pcapng or pcap capture file
+pub fn open_capture<P: AsRef<Path>>(path: P) -> Result<Vec<CapturedFrame>, CaptureReadError> {
+    read_capture(std::io::BufReader::new(std::fs::File::open(path)?))
+}
+
+#[derive(Copy, Clone)]
+struct Input<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Input<'a> {
+    fn new(data: &'a [u8], big_endian: bool) -> Self {
+        Self { data, big_endian }
+    }
+
+    fn bytes_at(&self, pos: usize, count: usize) -> Result<&'a [u8], CaptureReadError> {
+        self.data
+            .get(pos..pos.saturating_add(count))
+            .ok_or(CaptureReadError::Truncated)
+    }
+
+    fn u16_at(&self, pos: usize) -> Result<u16, CaptureReadError> {
+        let bytes = self.bytes_at(pos, 2)?;
+        let bytes = [bytes[0], bytes[1]];
+        Ok(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn u32_at(&self, pos: usize) -> Result<u32, CaptureReadError> {
+        let bytes = self.bytes_at(pos, 4)?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+}
+
+/// resolution of the timestamps of an interface
+#[derive(Copy, Clone)]
+enum Resolution {
+    /// units of 10^-n seconds
+    Decimal(u8),
+    /// units of 2^-n seconds
+    Binary(u8),
+}
+
+impl Resolution {
+    fn to_time(self, units: u64) -> SystemTime {
+        let nanos: u128 = match self {
+            Resolution::Decimal(exp) if exp <= 9 => units as u128 * 10u128.pow(9 - exp as u32),
+            Resolution::Decimal(exp) => units as u128 / 10u128.pow((exp as u32 - 9).min(38)),
+            Resolution::Binary(exp) => (units as u128 * 1_000_000_000) >> (exp as u32).min(127),
+        };
+        let secs = (nanos / 1_000_000_000) as u64;
+        let nanos = (nanos % 1_000_000_000) as u32;
+        UNIX_EPOCH + Duration::new(secs, nanos)
+    }
+}
+
+struct PcapNg;
+
+impl PcapNg {
+    const SECTION_HEADER: u32 = 0x0A0D_0D0A;
+    const INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+    const ENHANCED_PACKET: u32 = 0x0000_0006;
+    const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+    const OPT_END: u16 = 0;
+    const IF_TSRESOL: u16 = 9;
+    const EPB_FLAGS: u16 = 2;
+
+    fn read(data: &[u8]) -> Result<Vec<CapturedFrame>, CaptureReadError> {
+        let mut frames = Vec::new();
+        let mut input = Input::new(data, false);
+        // resolution of each interface in the current section
+        let mut interfaces: Vec<Resolution> = Vec::new();
+        let mut pos = 0;
+
+        while pos < data.len() {
+            let block_type = input.u32_at(pos)?;
+            if block_type == Self::SECTION_HEADER {
+                // the byte order of the section, including its length, is given by the magic
+                let magic = input.bytes_at(pos + 8, 4)?;
+                input.big_endian = magic == Self::BYTE_ORDER_MAGIC.to_be_bytes();
+                interfaces.clear();
+            }
+
+            let length = input.u32_at(pos + 4)?;
+            if length < 12 || length % 4 != 0 {
+                return Err(CaptureReadError::BadBlockLength(length));
+            }
+            let body = Input::new(
+                input.bytes_at(pos + 8, length as usize - 12)?,
+                input.big_endian,
+            );
+
+            match block_type {
+                Self::INTERFACE_DESCRIPTION => {
+                    interfaces.push(Self::read_resolution(body));
+                }
+                Self::ENHANCED_PACKET => {
+                    frames.push(Self::read_packet(body, &interfaces)?);
+                }
+                // simple packets, statistics, name resolution, etc
+                _ => {}
+            }
+
+            pos += length as usize;
+        }
+
+        Ok(frames)
+    }
+
+    fn read_resolution(body: Input) -> Resolution {
+        let mut resolution = Resolution::Decimal(6);
+        for (code, value) in Self::options(body, 8) {
+            if code == Self::IF_TSRESOL {
+                if let Some(&x) = value.first() {
+                    resolution = if x & 0x80 == 0 {
+                        Resolution::Decimal(x)
+                    } else {
+                        Resolution::Binary(x & 0x7F)
+                    };
+                }
+            }
+        }
+        resolution
+    }
+
+    fn read_packet(
+        body: Input,
+        interfaces: &[Resolution],
+    ) -> Result<CapturedFrame, CaptureReadError> {
+        let interface = body.u32_at(0)? as usize;
+        let units = ((body.u32_at(4)? as u64) << 32) | body.u32_at(8)? as u64;
+        let length = body.u32_at(12)? as usize;
+        let data = body.bytes_at(20, length)?;
+        let resolution = interfaces
+            .get(interface)
+            .copied()
+            .unwrap_or(Resolution::Decimal(6));
+
+        let padded = (length + 3) & !3;
+        let mut direction = None;
+        for (code, value) in Self::options(body, 20 + padded) {
+            if code == Self::EPB_FLAGS && value.len() == 4 {
+                let flags = Input::new(value, body.big_endian).u32_at(0)?;
+                direction = match flags & 0b11 {
+                    0b01 => Some(DecodeDirection::Rx),
+                    0b10 => Some(DecodeDirection::Tx),
+                    _ => None,
+                };
+            }
+        }
+
+        Ok(CapturedFrame::new(
+            resolution.to_time(units),
+            direction,
+            data.to_vec(),
+        ))
+    }
+
+    /// options starting at `pos`, stopping at the end option or the first malformed option
+    fn options(body: Input, mut pos: usize) -> Vec<(u16, &[u8])> {
+        let mut options = Vec::new();
+        while let (Ok(code), Ok(length)) = (body.u16_at(pos), body.u16_at(pos + 2)) {
+            if code == Self::OPT_END {
+                break;
+            }
+            let value = match body.bytes_at(pos + 4, length as usize) {
+                Ok(x) => x,
+                Err(_) => break,
+            };
+            options.push((code, value));
+            pos += 4 + ((length as usize + 3) & !3);
+        }
+        options
+    }
+}
+
+struct Pcap;
+
+impl Pcap {
+    const MAGIC_MICROS: u32 = 0xA1B2_C3D4;
+    const MAGIC_NANOS: u32 = 0xA1B2_3C4D;
+    const HEADER_LENGTH: usize = 24;
+    const RECORD_HEADER_LENGTH: usize = 16;
+
+    fn read(data: &[u8]) -> Result<Vec<CapturedFrame>, CaptureReadError> {
+        let little = Input::new(data, false).u32_at(0)?;
+        let big = Input::new(data, true).u32_at(0)?;
+        let (input, nanos) = match (little, big) {
+            (Self::MAGIC_MICROS, _) => (Input::new(data, false), false),
+            (Self::MAGIC_NANOS, _) => (Input::new(data, false), true),
+            (_, Self::MAGIC_MICROS) => (Input::new(data, true), false),
+            (_, Self::MAGIC_NANOS) => (Input::new(data, true), true),
+            _ => return Err(CaptureReadError::UnknownFormat(little)),
+        };
+
+        let mut frames = Vec::new();
+        let mut pos = Self::HEADER_LENGTH;
+        while pos < data.len() {
+            let secs = input.u32_at(pos)? as u64;
+            let fraction = input.u32_at(pos + 4)?;
+            let length = input.u32_at(pos + 8)? as usize;
+            let frame = input.bytes_at(pos + Self::RECORD_HEADER_LENGTH, length)?;
+            let fraction = if nanos {
+                Duration::from_nanos(fraction as u64)
+            } else {
+                Duration::from_micros(fraction as u64)
+            };
+            frames.push(CapturedFrame::new(
+                UNIX_EPOCH + Duration::from_secs(secs) + fraction,
+                None,
+                frame.to_vec(),
+            ));
+            pos += Self::RECORD_HEADER_LENGTH + length;
+        }
+
+        Ok(frames)
+    }
+}
+
+impl From<std::io::Error> for CaptureReadError {
+    fn from(err: std::io::Error) -> Self {
+        CaptureReadError::Io(err.kind())
+    }
+}
+
+impl std::fmt::Display for CaptureReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CaptureReadError::Io(kind) => write!(f, "{}", std::io::Error::from(*kind)),
+            CaptureReadError::UnknownFormat(magic) => {
+                write!(f, "unknown capture format (magic: 0x{:08X})", magic)
+            }
+            CaptureReadError::Truncated => f.write_str("capture ends in the middle of a block"),
+            CaptureReadError::BadBlockLength(x) => write!(f, "bad block length: {}", x),
+        }
+    }
+}
+
+impl std::error::Error for CaptureReadError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn block(block_type: u32, body: &[u8]) -> Vec<u8> {
+        let length = (body.len() + 12) as u32;
+        let mut block = Vec::new();
+        block.extend_from_slice(&block_type.to_le_bytes());
+        block.extend_from_slice(&length.to_le_bytes());
+        block.extend_from_slice(body);
+        block.extend_from_slice(&length.to_le_bytes());
+        block
+    }
+
+    fn section_header() -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&0x1A2B_3C4Du32.to_le_bytes());
+        body.extend_from_slice(&[0x01, 0x00, 0x00, 0x00]);
+        body.extend_from_slice(&(-1i64).to_le_bytes());
+        block(0x0A0D_0D0A, &body)
+    }
+
+    fn interface(resolution: Option<u8>) -> Vec<u8> {
+        let mut body = vec![0x93, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        if let Some(x) = resolution {
+            body.extend_from_slice(&[0x09, 0x00, 0x01, 0x00, x, 0x00, 0x00, 0x00]);
+            body.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+        }
+        block(1, &body)
+    }
+
+    fn packet(units: u64, data: &[u8], flags: Option<u32>) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&((units >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(units as u32).to_le_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(data);
+        body.resize((body.len() + 3) & !3, 0);
+        if let Some(flags) = flags {
+            body.extend_from_slice(&[0x02, 0x00, 0x04, 0x00]);
+            body.extend_from_slice(&flags.to_le_bytes());
+            body.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+        }
+        block(6, &body)
+    }
+
+    const FRAME: &[u8] = &[0x05, 0x64, 0x05, 0xC9, 0x01, 0x00, 0x00, 0x04, 0x7D, 0xE2];
+
+    #[test]
+    fn reads_enhanced_packets_with_direction() {
+        let mut data = section_header();
+        data.extend(interface(None));
+        data.extend(packet(1_500_000, FRAME, Some(0b10)));
+        data.extend(packet(2_000_000, &FRAME[0..3], None));
+
+        let frames = read_capture(data.as_slice()).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(
+            frames[0],
+            CapturedFrame::new(
+                UNIX_EPOCH + Duration::from_millis(1500),
+                Some(DecodeDirection::Tx),
+                FRAME.to_vec()
+            )
+        );
+        assert_eq!(frames[0].is_from_master(), Some(true));
+        assert_eq!(frames[1].direction, None);
+        assert_eq!(frames[1].data, &FRAME[0..3]);
+        assert_eq!(frames[1].is_from_master(), None);
+    }
+
+    #[test]
+    fn applies_interface_timestamp_resolution() {
+        let mut data = section_header();
+        data.extend(interface(Some(9)));
+        data.extend(packet(1_000_000_007, FRAME, Some(0b01)));
+
+        let frames = read_capture(data.as_slice()).unwrap();
+        assert_eq!(frames[0].timestamp, UNIX_EPOCH + Duration::new(1, 7));
+        assert_eq!(frames[0].direction, Some(DecodeDirection::Rx));
+    }
+
+    #[test]
+    fn reads_classic_pcap_records() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0xA1B2_C3D4u32.to_le_bytes());
+        data.extend_from_slice(&[0x02, 0x00, 0x04, 0x00]);
+        data.extend_from_slice(&[0x00; 12]);
+        data.extend_from_slice(&147u32.to_le_bytes());
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(&250u32.to_le_bytes());
+        data.extend_from_slice(&(FRAME.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(FRAME.len() as u32).to_le_bytes());
+        data.extend_from_slice(FRAME);
+
+        let frames = read_capture(data.as_slice()).unwrap();
+        assert_eq!(
+            frames,
+            vec![CapturedFrame::new(
+                UNIX_EPOCH + Duration::from_secs(3) + Duration::from_micros(250),
+                None,
+                FRAME.to_vec()
+            )]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_and_truncated_captures() {
+        assert_eq!(
+            read_capture(&[0x01, 0x02, 0x03, 0x04][..]),
+            Err(CaptureReadError::UnknownFormat(0x0403_0201))
+        );
+
+        let mut data = section_header();
+        data.extend(interface(None));
+        let packet = packet(0, FRAME, None);
+        data.extend_from_slice(&packet[0..packet.len() - 8]);
+        assert_eq!(
+            read_capture(data.as_slice()),
+            Err(CaptureReadError::Truncated)
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use crate::app::parse::parser::ParsedFragment;
+use crate::decode::{
+    DecodeDirection, DecodeEvent, DecodeEventHandler, DecodeOutput, TransportSegmentEvent,
+};
+use crate::link::header::Header;
+use crate::link::parser::{FramePayload, Parser};
+use crate::link::statistics::LinkCounters;
+use crate::link::{LinkErrorMode, LinkStatistics};
+use crate::util::cursor::ReadCursor;
+
+/// Decodes a captured byte stream, e.g. the data sent in one direction, through the same link,
+/// transport, and application layer parsers used by masters and outstations
+///
+/// The data may be split or combined in any way, so it can be fed with whatever chunks the
+/// capture contains. Frames with bad CRCs or lengths are discarded and counted as they would be
+/// on a serial channel.
+pub struct Decoder {
+    direction: DecodeDirection,
+    parser: Parser,
+    counters: Arc<LinkCounters>,
+    output: DecodeOutput,
+    // unparsed data at the end of the last chunk
+    pending: Vec<u8>,
+    // partially assembled fragment and the expected sequence number of the next segment
+    fragment: Vec<u8>,
+    next_seq: Option<u8>,
+}
+
+struct Segment {
+    fir: bool,
+    fin: bool,
+    seq: u8,
+}
+
+impl Segment {
+    const FIN_MASK: u8 = 0x80;
+    const FIR_MASK: u8 = 0x40;
+    const SEQ_MASK: u8 = 0x3F;
+
+    fn new(header: u8) -> Self {
+        Self {
+            fir: header & Self::FIR_MASK != 0,
+            fin: header & Self::FIN_MASK != 0,
+            seq: header & Self::SEQ_MASK,
+        }
+    }
+}
+
+impl Decoder {
+    /// Maximum size of a reassembled fragment
+    pub const MAX_FRAGMENT_SIZE: usize = 65535;
+
+    /// Create a decoder for data travelling in the specified direction
+    pub fn new(direction: DecodeDirection) -> Self {
+        let counters = Arc::new(LinkCounters::default());
+        Self {
+            direction,
+            parser: Parser::new(LinkErrorMode::Discard, counters.clone()),
+            counters,
+            output: DecodeOutput::default(),
+            pending: Vec::new(),
+            fragment: Vec::new(),
+            next_seq: None,
+        }
+    }
+
+    /// Receive a [DecodeEvent] for every frame, segment, fragment, and object header
+    pub fn set_event_handler(&mut self, handler: Option<Box<dyn DecodeEventHandler>>) {
+        self.output.set_event_handler(handler);
+    }
+
+    /// Counters of the frames decoded so far
+    pub fn link_statistics(&self) -> LinkStatistics {
+        self.counters.snapshot()
+    }
+
+    /// Decode the next chunk of the stream, returning the fragments that it completes
+    ///
+    /// The fragments may be parsed with [crate::app::parse::Fragment].
+    pub fn decode(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        let mut fragments = Vec::new();
+        self.pending.extend_from_slice(data);
+
+        let pending = std::mem::take(&mut self.pending);
+        let mut cursor = ReadCursor::new(&pending);
+        loop {
+            let mut payload = FramePayload::empty();
+            match self.parser.parse(&mut cursor, &mut payload) {
+                Ok(Some(header)) => {
+                    if let Some(fragment) = self.on_frame(header, payload) {
+                        fragments.push(fragment);
+                    }
+                }
+                // more data is required
+                Ok(None) => break,
+                // only possible in LinkErrorMode::Close
+                Err(_) => {
+                    self.parser.reset();
+                    break;
+                }
+            }
+        }
+
+        self.pending = cursor.read_all().to_vec();
+        fragments
+    }
+
+    fn on_frame(&mut self, header: Header, payload: FramePayload) -> Option<Vec<u8>> {
+        self.counters.on_frame_rx();
+        self.output
+            .emit_event(header.to_event(self.direction, payload.len()));
+
+        let (transport, data) = payload.split_first()?;
+        let segment = Segment::new(transport);
+        self.output
+            .emit_event(DecodeEvent::TransportSegment(TransportSegmentEvent {
+                direction: self.direction,
+                fir: segment.fir,
+                fin: segment.fin,
+                seq: segment.seq,
+                length: data.len(),
+            }));
+
+        if segment.fir {
+            self.fragment.clear();
+        } else if self.next_seq != Some(segment.seq) {
+            // not the continuation of a fragment
+            self.fragment.clear();
+            self.next_seq = None;
+            return None;
+        }
+
+        if self.fragment.len() + data.len() > Self::MAX_FRAGMENT_SIZE {
+            self.fragment.clear();
+            self.next_seq = None;
+            return None;
+        }
+
+        self.fragment.extend(data.bytes());
+        self.next_seq = Some((segment.seq + 1) & Segment::SEQ_MASK);
+
+        if !segment.fin {
+            return None;
+        }
+
+        self.next_seq = None;
+        let fragment = std::mem::take(&mut self.fragment);
+        if let Ok(parsed) = ParsedFragment::parse(&fragment) {
+            self.output.emit_fragment(self.direction, &parsed);
+        }
+        Some(fragment)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::app::FunctionCode;
+    use crate::link::format::{format_data_frame, Payload};
+    use crate::link::header::AnyAddress;
+    use crate::util::cursor::WriteCursor;
+
+    fn frame(transport: u8, app: &[u8]) -> Vec<u8> {
+        let mut buffer = [0; crate::link::constant::MAX_LINK_FRAME_LENGTH];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let header =
+            Header::unconfirmed_user_data(true, AnyAddress::from(1024), AnyAddress::from(1));
+        format_data_frame(header, Payload::new(transport, app), &mut cursor)
+            .unwrap()
+            .frame
+            .to_vec()
+    }
+
+    // read class 1 events
+    const READ: &[u8] = &[0xC0, 0x01, 0x3C, 0x02, 0x06];
+
+    #[test]
+    fn decodes_fragment_split_across_chunks() {
+        let data = frame(0xC0, READ);
+        let mut decoder = Decoder::new(DecodeDirection::Tx);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        decoder.set_event_handler(Some(Box::new(move |event: DecodeEvent| {
+            sink.lock().unwrap().push(event)
+        })));
+
+        assert!(decoder.decode(&data[0..7]).is_empty());
+        assert_eq!(decoder.decode(&data[7..]), vec![READ.to_vec()]);
+        assert_eq!(decoder.link_statistics().frames_rx, 1);
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 4);
+        assert!(matches!(&events[0], DecodeEvent::LinkFrame(x) if x.length == 6));
+        assert!(matches!(&events[1], DecodeEvent::TransportSegment(x) if x.fir && x.fin));
+        assert!(matches!(&events[2], DecodeEvent::Fragment(x) if x.function == FunctionCode::Read));
+        assert!(matches!(&events[3], DecodeEvent::ObjectHeader(x) if x.group == 60));
+    }
+
+    #[test]
+    fn reassembles_multiple_segments() {
+        let mut data = b"garbage".to_vec();
+        data.extend(frame(0x45, &READ[0..2]));
+        data.extend(frame(0x86, &READ[2..]));
+
+        let mut decoder = Decoder::new(DecodeDirection::Rx);
+        assert_eq!(decoder.decode(&data), vec![READ.to_vec()]);
+        assert_eq!(decoder.link_statistics().garbage_bytes, 7);
+    }
+
+    #[test]
+    fn discards_out_of_sequence_segment() {
+        let mut data = frame(0x45, &READ[0..2]);
+        data.extend(frame(0x87, &READ[2..]));
+
+        let mut decoder = Decoder::new(DecodeDirection::Rx);
+        assert!(decoder.decode(&data).is_empty());
+    }
+}
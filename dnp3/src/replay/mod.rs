@@ -0,0 +1,7 @@
+pub use capture::*;
+pub use decoder::*;
+pub use session::*;
+
+mod capture;
+mod decoder;
+mod session;
@@ -0,0 +1,264 @@
+use std::time::Duration;
+
+use crate::app::Clock;
+use crate::custom::PhysicalLayer;
+use crate::replay::CapturedFrame;
+use crate::tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Replays captured data to a master or outstation session running on a user-provided physical
+/// layer
+///
+/// Each entry is delivered to the session at the same time, relative to the first entry, as it
+/// was captured. The data written by the session is collected so that it can be decoded with a
+/// [Decoder](crate::replay::Decoder) and compared with the capture.
+///
+/// Replays are deterministic when the Tokio clock is paused, e.g. with `tokio::time::pause` or
+/// `#[tokio::test(start_paused = true)]`: time advances automatically whenever the session is
+/// idle, so timeouts in the session expire exactly as they would with the captured timing, and
+/// the replay completes without waiting in real time. They are also deterministic when the
+/// replay and the session share a [ManualClock](crate::app::ManualClock).
+pub struct SessionReplay {
+    entries: Vec<(Duration, Vec<u8>)>,
+    linger: Duration,
+    clock: Clock,
+}
+
+impl SessionReplay {
+    /// Default amount of time the replay waits after the last entry before closing the session
+    pub const DEFAULT_LINGER: Duration = Duration::from_secs(5);
+
+    const BUFFER_SIZE: usize = 64 * 1024;
+
+    /// Replay every entry of a capture in order
+    pub fn new(frames: &[CapturedFrame]) -> Self {
+        Self::filter(frames, |_| true)
+    }
+
+    /// Replay the frames of a capture that were sent by the outstation to a master session
+    ///
+    /// The direction of each frame is determined from the DIR bit of its link-layer header,
+    /// so the capture must contain one frame per entry. Timing is relative to the first frame
+    /// of the capture in either direction.
+    pub fn for_master(frames: &[CapturedFrame]) -> Self {
+        Self::filter(frames, |frame| frame.is_from_master() == Some(false))
+    }
+
+    /// Replay the frames of a capture that were sent by the master to an outstation session
+    ///
+    /// The direction of each frame is determined from the DIR bit of its link-layer header,
+    /// so the capture must contain one frame per entry. Timing is relative to the first frame
+    /// of the capture in either direction.
+    pub fn for_outstation(frames: &[CapturedFrame]) -> Self {
+        Self::filter(frames, |frame| frame.is_from_master() == Some(true))
+    }
+
+    /// Set the amount of time the replay waits after the last entry before closing the session
+    pub fn linger(mut self, linger: Duration) -> Self {
+        self.linger = linger;
+        self
+    }
+
+    /// Set the clock that determines when each entry is delivered
+    ///
+    /// This should be the clock in the configuration of the session.
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Create the physical layer on which the session runs and a future that performs the
+    /// replay
+    ///
+    /// The future completes with all of the data written by the session once the linger time
+    /// following the last entry has elapsed or the session closes the physical layer. The
+    /// physical layer is closed when the future completes, which ends the session.
+    pub fn start(
+        self,
+    ) -> (
+        Box<dyn PhysicalLayer>,
+        impl std::future::Future<Output = Vec<u8>> + Send + 'static,
+    ) {
+        let (session, replay) = crate::tokio::io::duplex(Self::BUFFER_SIZE);
+        (Box::new(session), self.run(replay))
+    }
+
+    fn filter<F>(frames: &[CapturedFrame], selected: F) -> Self
+    where
+        F: Fn(&CapturedFrame) -> bool,
+    {
+        let start = frames.first().map(|x| x.timestamp);
+        let entries = frames
+            .iter()
+            .filter(|frame| selected(frame))
+            .map(|frame| {
+                let offset = start
+                    .and_then(|start| frame.timestamp.duration_since(start).ok())
+                    .unwrap_or_default();
+                (offset, frame.data.clone())
+            })
+            .collect();
+
+        Self {
+            entries,
+            linger: Self::DEFAULT_LINGER,
+            clock: Clock::default(),
+        }
+    }
+
+    async fn run<S>(self, mut stream: S) -> Vec<u8>
+    where
+        S: PhysicalLayer,
+    {
+        let clock = self.clock.clone();
+        let start = clock.now();
+        let end = start
+            + self
+                .entries
+                .last()
+                .map(|(offset, _)| *offset)
+                .unwrap_or_default()
+            + self.linger;
+
+        let mut output = Vec::new();
+        let mut buffer = [0; 1024];
+        let mut entries = self.entries.into_iter().peekable();
+
+        loop {
+            let deadline = match entries.peek() {
+                Some((offset, _)) => start + *offset,
+                None => end,
+            };
+
+            crate::tokio::select! {
+                res = stream.read(&mut buffer) => {
+                    match res {
+                        Ok(count) if count > 0 => {
+                            output.extend_from_slice(&buffer[0..count]);
+                        }
+                        // the session closed the physical layer
+                        _ => break,
+                    }
+                }
+                _ = clock.sleep_until(deadline) => {
+                    match entries.next() {
+                        Some((_, data)) => {
+                            if stream.write_all(&data).await.is_err() {
+                                break;
+                            }
+                        }
+                        None => break,
+                    }
+                }
+            }
+        }
+
+        let _ = stream.shutdown().await;
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+    use crate::tokio::test::*;
+    use crate::util::phys::PhysLayer;
+
+    fn frame(secs: u64, data: &[u8]) -> CapturedFrame {
+        CapturedFrame::new(UNIX_EPOCH + Duration::from_secs(secs), None, data.to_vec())
+    }
+
+    // link status requests from the master and the outstation
+    const FROM_MASTER: &[u8] = &[0x05, 0x64, 0x05, 0xC9, 0x01, 0x00, 0x00, 0x04, 0x7D, 0xE2];
+    const FROM_OUTSTATION: &[u8] = &[0x05, 0x64, 0x05, 0x49, 0x00, 0x04, 0x01, 0x00, 0x9C, 0x53];
+
+    #[test]
+    fn selects_frames_by_dir_bit() {
+        let frames = [
+            frame(10, FROM_MASTER),
+            frame(12, FROM_OUTSTATION),
+            frame(13, &[0x01, 0x02]),
+        ];
+
+        let replay = SessionReplay::for_master(&frames);
+        assert_eq!(
+            replay.entries,
+            vec![(Duration::from_secs(2), FROM_OUTSTATION.to_vec())]
+        );
+
+        let replay = SessionReplay::for_outstation(&frames);
+        assert_eq!(
+            replay.entries,
+            vec![(Duration::from_secs(0), FROM_MASTER.to_vec())]
+        );
+
+        assert_eq!(SessionReplay::new(&frames).entries.len(), 3);
+    }
+
+    #[test]
+    fn delivers_entries_at_captured_times_and_collects_output() {
+        let frames = [frame(0, &[0x01]), frame(2, &[0x02, 0x03])];
+        let (phys, replay) = SessionReplay::new(&frames)
+            .linger(Duration::from_secs(1))
+            .start();
+        let mut phys = PhysLayer::Custom(phys);
+        let mut replay = spawn(replay);
+        let mut buffer = [0; 8];
+
+        assert_pending!(replay.poll());
+        let mut read = spawn(phys.read(&mut buffer));
+        assert_eq!(assert_ready!(read.poll()).unwrap(), 1);
+        drop(read);
+        assert_eq!(buffer[0], 0x01);
+
+        crate::tokio::time::advance(Duration::from_secs(2));
+        assert_pending!(replay.poll());
+        let mut read = spawn(phys.read(&mut buffer));
+        assert_eq!(assert_ready!(read.poll()).unwrap(), 2);
+        drop(read);
+        assert_eq!(&buffer[0..2], &[0x02, 0x03]);
+
+        let mut write = spawn(phys.write(&[0xAA, 0xBB]));
+        assert_ready!(write.poll()).unwrap();
+        drop(write);
+        assert_pending!(replay.poll());
+
+        crate::tokio::time::advance(Duration::from_secs(1));
+        assert_eq!(assert_ready!(replay.poll()), vec![0xAA, 0xBB]);
+    }
+
+    #[test]
+    fn follows_manual_clock() {
+        let manual = Arc::new(crate::app::ManualClock::new());
+        let frames = [frame(0, &[0x01]), frame(2, &[0x02])];
+        let (phys, replay) = SessionReplay::new(&frames)
+            .linger(Duration::from_secs(1))
+            .clock(Clock::manual(manual.clone()))
+            .start();
+        let mut phys = PhysLayer::Custom(phys);
+        let mut replay = spawn(replay);
+        let mut buffer = [0; 8];
+
+        assert_pending!(replay.poll());
+        let mut read = spawn(phys.read(&mut buffer));
+        assert_eq!(assert_ready!(read.poll()).unwrap(), 1);
+        drop(read);
+
+        // the Tokio clock has no effect on the replay
+        crate::tokio::time::advance(Duration::from_secs(10));
+        assert_pending!(replay.poll());
+        assert_eq!(manual.next_wake(), Some(Duration::from_secs(2)));
+
+        manual.advance(Duration::from_secs(2));
+        assert_pending!(replay.poll());
+        let mut read = spawn(phys.read(&mut buffer));
+        assert_eq!(assert_ready!(read.poll()).unwrap(), 1);
+        drop(read);
+        assert_eq!(buffer[0], 0x02);
+
+        manual.advance(Duration::from_secs(1));
+        assert_eq!(assert_ready!(replay.poll()), Vec::<u8>::new());
+    }
+}
@@ -1,9 +1,11 @@
 use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
 
 use tracing::Instrument;
 
-use crate::app::{Listener, Shutdown};
+use crate::app::{DisconnectReason, Listener, Shutdown, Spawner};
+use crate::link::statistics::LinkCounters;
 use crate::link::LinkErrorMode;
 use crate::master::session::{MasterSession, RunError, StateChange};
 use crate::master::*;
@@ -24,9 +26,10 @@ pub fn spawn_master_serial(
     retry_delay: Duration,
     listener: Box<dyn Listener<PortState>>,
 ) -> MasterChannel {
+    let spawner = config.spawner.clone();
     let (future, handle) =
         create_master_serial(config, path, serial_settings, retry_delay, listener);
-    crate::tokio::spawn(future);
+    Spawner::spawn(spawner.as_deref(), future);
     handle
 }
 
@@ -45,20 +48,29 @@ pub fn create_master_serial(
     retry_delay: Duration,
     listener: Box<dyn Listener<PortState>>,
 ) -> (impl Future<Output = ()> + 'static, MasterChannel) {
+    let port = path.to_owned();
+    let clock = config.clock.clone();
+    let open = Box::new(move || -> std::io::Result<PhysLayer> {
+        Ok(PhysLayer::Serial(crate::serial::open(
+            &port,
+            settings,
+            clock.clone(),
+        )?))
+    });
     let log_path = path.to_owned();
-    let (mut task, handle) = MasterTask::new(path, settings, config, retry_delay, listener);
+    let tracing_config = config.tracing;
+    let (mut task, handle) = MasterTask::new(open, settings, config, retry_delay, listener);
     let future = async move {
-        let _ = task
-            .run()
-            .instrument(tracing::info_span!("DNP3-Master-Serial", "port" = ?log_path))
-            .await;
+        let span = tracing_config
+            .channel_span(tracing::info_span!("DNP3-Master-Serial", "port" = ?log_path));
+        let _ = task.run().instrument(span).await;
     };
     (future, handle)
 }
 
 struct MasterTask {
-    path: String,
-    serial_settings: SerialSettings,
+    // opens the port, replaced in tests to simulate a port that disappears and appears again
+    open: Box<dyn FnMut() -> std::io::Result<PhysLayer> + Send>,
     retry_delay: Duration,
     session: MasterSession,
     reader: TransportReader,
@@ -68,18 +80,29 @@ struct MasterTask {
 
 impl MasterTask {
     fn new(
-        path: &str,
+        open: Box<dyn FnMut() -> std::io::Result<PhysLayer> + Send>,
         serial_settings: SerialSettings,
         config: MasterChannelConfig,
         retry_delay: Duration,
         listener: Box<dyn Listener<PortState>>,
     ) -> (Self, MasterChannel) {
+        let link = serial_settings.link_config(config.link);
         let (tx, rx) = crate::util::channel::request_channel();
+        let link_counters = Arc::new(LinkCounters::new(
+            config.clock,
+            config.tracing,
+            config.buffer_pool,
+        ));
         let session = MasterSession::new(
             false,
             config.decode_level,
             config.response_timeout,
+            config.scheduling,
+            link,
+            link_counters.clone(),
+            config.tracing,
             config.tx_buffer_size,
+            config.rx_buffer_size,
             rx,
         );
         let (reader, writer) = crate::transport::create_master_transport_layer(
@@ -87,17 +110,21 @@ impl MasterTask {
             LinkErrorMode::Discard,
             config.master_address,
             config.rx_buffer_size,
+            link,
+            config.response_timeout.into(),
+            config.transport,
+            config.unknown_destination,
+            link_counters.clone(),
         );
         let task = Self {
-            path: path.to_string(),
-            serial_settings,
+            open,
             retry_delay,
             session,
             reader,
             writer,
             listener,
         };
-        (task, MasterChannel::new(tx))
+        (task, MasterChannel::new(tx, link_counters))
     }
 
     async fn run(&mut self) {
@@ -118,7 +145,7 @@ impl MasterTask {
 
     async fn run_enabled(&mut self) -> Result<(), StateChange> {
         loop {
-            match crate::serial::open(self.path.as_str(), self.serial_settings) {
+            match (self.open)() {
                 Err(err) => {
                     tracing::warn!(
                         "{} - waiting {} ms to re-open port",
@@ -128,8 +155,7 @@ impl MasterTask {
                     self.listener.update(PortState::Wait(self.retry_delay));
                     self.session.wait_for_retry(self.retry_delay).await?;
                 }
-                Ok(serial) => {
-                    let mut io = PhysLayer::Serial(serial);
+                Ok(mut io) => {
                     tracing::info!("serial port open");
                     self.listener.update(PortState::Open);
                     match self
@@ -138,10 +164,15 @@ impl MasterTask {
                         .await
                     {
                         RunError::State(x) => {
+                            if x == StateChange::Disable {
+                                self.listener
+                                    .update(PortState::Closed(DisconnectReason::Disabled));
+                            }
                             return Err(x);
                         }
                         RunError::Link(err) => {
                             tracing::warn!("serial port error: {}", err);
+                            self.listener.update(PortState::Closed(err.into()));
                             tracing::info!(
                                 "waiting {} ms to re-open",
                                 self.retry_delay.as_millis()
@@ -155,3 +186,137 @@ impl MasterTask {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+    use std::io::ErrorKind;
+
+    use super::*;
+    use crate::app::listener_channel;
+    use crate::link::EndpointAddress;
+    use crate::tokio::test::*;
+    use crate::tokio::time;
+
+    const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn reports_the_reason_the_port_was_closed() {
+        let (first, remote) = crate::tokio::io::duplex(1024);
+        let (second, _remote) = crate::tokio::io::duplex(1024);
+        let mut ports: VecDeque<std::io::Result<PhysLayer>> = VecDeque::new();
+        ports.push_back(Ok(PhysLayer::Custom(Box::new(first))));
+        ports.push_back(Ok(PhysLayer::Custom(Box::new(second))));
+
+        let (listener, mut states) = listener_channel(16);
+        let (mut task, mut channel) = MasterTask::new(
+            Box::new(move || {
+                ports
+                    .pop_front()
+                    .unwrap_or_else(|| Err(ErrorKind::NotFound.into()))
+            }),
+            SerialSettings::default(),
+            MasterChannelConfig::new(EndpointAddress::from(1).unwrap()),
+            RETRY_DELAY,
+            listener,
+        );
+        let mut run = spawn(async move { task.run().await });
+
+        assert_pending!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Disabled));
+        assert_eq!(states.try_next(), None);
+
+        assert_ready!(spawn(channel.enable()).poll()).unwrap();
+        assert_pending!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Open));
+        assert_eq!(states.try_next(), None);
+
+        // the remote end closes the port
+        drop(remote);
+        assert_pending!(run.poll());
+        assert_eq!(
+            states.try_next(),
+            Some(PortState::Closed(DisconnectReason::Io(
+                ErrorKind::UnexpectedEof
+            )))
+        );
+        assert_eq!(states.try_next(), Some(PortState::Wait(RETRY_DELAY)));
+        assert_eq!(states.try_next(), None);
+
+        time::advance(RETRY_DELAY);
+        assert_pending!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Open));
+        assert_eq!(states.try_next(), None);
+
+        // communication is disabled locally
+        assert_ready!(spawn(channel.disable()).poll()).unwrap();
+        assert_pending!(run.poll());
+        assert_eq!(
+            states.try_next(),
+            Some(PortState::Closed(DisconnectReason::Disabled))
+        );
+        assert_eq!(states.try_next(), Some(PortState::Disabled));
+        assert_eq!(states.try_next(), None);
+
+        drop(channel);
+        assert_ready!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Shutdown));
+    }
+
+    #[test]
+    fn reopens_port_that_disappears_and_appears_again() {
+        let (port, remote) = crate::tokio::io::duplex(1024);
+        let mut attempts: VecDeque<std::io::Result<PhysLayer>> = VecDeque::new();
+        attempts.push_back(Err(ErrorKind::NotFound.into()));
+        attempts.push_back(Ok(PhysLayer::Custom(Box::new(port))));
+
+        let (listener, mut states) = listener_channel(16);
+        let (mut task, mut channel) = MasterTask::new(
+            Box::new(move || {
+                attempts
+                    .pop_front()
+                    .unwrap_or_else(|| Err(ErrorKind::NotFound.into()))
+            }),
+            SerialSettings::default(),
+            MasterChannelConfig::new(EndpointAddress::from(1).unwrap()),
+            RETRY_DELAY,
+            listener,
+        );
+        let mut run = spawn(async move { task.run().await });
+
+        assert_pending!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Disabled));
+        assert_ready!(spawn(channel.enable()).poll()).unwrap();
+
+        // the port doesn't exist yet
+        assert_pending!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Wait(RETRY_DELAY)));
+        assert_eq!(states.try_next(), None);
+
+        time::advance(RETRY_DELAY);
+        assert_pending!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Open));
+        assert_eq!(states.try_next(), None);
+
+        // the USB adapter is unplugged
+        drop(remote);
+        assert_pending!(run.poll());
+        assert_eq!(
+            states.try_next(),
+            Some(PortState::Closed(DisconnectReason::Io(
+                ErrorKind::UnexpectedEof
+            )))
+        );
+        assert_eq!(states.try_next(), Some(PortState::Wait(RETRY_DELAY)));
+
+        // the port is gone, so the task keeps waiting until the handle is dropped
+        time::advance(RETRY_DELAY);
+        assert_pending!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Wait(RETRY_DELAY)));
+        assert_eq!(states.try_next(), None);
+
+        drop(channel);
+        assert_ready!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Shutdown));
+    }
+}
@@ -1,5 +1,32 @@
+use std::time::Duration;
+
+use crate::app::Clock;
+use crate::app::Instant;
+use crate::link::LinkConfig;
+use crate::tokio::io::{AsyncReadExt, AsyncWriteExt};
+
 pub use tokio_serial::{DataBits, FlowControl, Parity, StopBits};
 
+/// timing applied around each transmitted frame, required for half-duplex RS-485 networks
+///
+/// The inter-frame gap and the post-transmit quiet time are measured from the moment the last
+/// byte of a frame is estimated to have left the port, based on the baud rate and character format.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SerialTiming {
+    /// delay between enabling the transmitter and writing the first byte of a frame
+    pub turnaround_delay: Duration,
+    /// minimum idle time on the line between the end of one frame and the start of the next
+    pub inter_frame_gap: Duration,
+    /// time to remain quiet after transmitting a frame before anything else is done on the channel
+    pub post_transmit_quiet: Duration,
+}
+
+impl SerialTiming {
+    fn is_enabled(&self) -> bool {
+        *self != Self::default()
+    }
+}
+
 /// serial port settings
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct SerialSettings {
@@ -13,6 +40,19 @@ pub struct SerialSettings {
     pub stop_bits: StopBits,
     /// parity setting
     pub parity: Parity,
+    /// timing around transmitted frames
+    pub timing: SerialTiming,
+    /// maximum gap between the received characters of a partial frame
+    ///
+    /// When it elapses, the partial frame is discarded and the following bytes are searched for
+    /// the start of a new frame, so that a frame truncated by noise doesn't consume the start of
+    /// the next one. When set, it replaces the
+    /// [inter_frame_timeout](crate::link::LinkConfig::inter_frame_timeout) of the channel.
+    ///
+    /// Serial drivers and USB adapters deliver received bytes in bursts, so the value should cover
+    /// their latency, usually a few milliseconds, on top of a few character times. It isn't applied
+    /// to the serial path of a redundant master, whose link settings are shared with its other path.
+    pub inter_character_timeout: Option<Duration>,
 }
 
 impl SerialSettings {
@@ -27,6 +67,35 @@ impl SerialSettings {
             .stop_bits(self.stop_bits)
             .parity(self.parity)
     }
+
+    /// link settings of a channel using the port
+    pub(crate) fn link_config(&self, link: LinkConfig) -> LinkConfig {
+        LinkConfig {
+            inter_frame_timeout: self.inter_character_timeout.or(link.inter_frame_timeout),
+            ..link
+        }
+    }
+
+    /// time it takes to transmit the specified number of characters on the line
+    fn transmission_time(&self, count: usize) -> Duration {
+        let data_bits = match self.data_bits {
+            DataBits::Five => 5,
+            DataBits::Six => 6,
+            DataBits::Seven => 7,
+            DataBits::Eight => 8,
+        };
+        let parity_bits = match self.parity {
+            Parity::None => 0,
+            Parity::Odd | Parity::Even => 1,
+        };
+        let stop_bits = match self.stop_bits {
+            StopBits::One => 1,
+            StopBits::Two => 2,
+        };
+        // one start bit per character
+        let bits = (1 + data_bits + parity_bits + stop_bits) * count as u64;
+        Duration::from_micros(bits * 1_000_000 / u64::from(self.baud_rate.max(1)))
+    }
 }
 
 impl Default for SerialSettings {
@@ -37,13 +106,100 @@ impl Default for SerialSettings {
             flow_control: FlowControl::None,
             stop_bits: StopBits::One,
             parity: Parity::None,
+            timing: SerialTiming::default(),
+            inter_character_timeout: None,
+        }
+    }
+}
+
+/// waits around the frames written to a port according to the configured timing
+struct FrameTiming {
+    settings: SerialSettings,
+    clock: Clock,
+    // estimated time at which the last transmitted byte left the port
+    last_tx: Option<Instant>,
+}
+
+impl FrameTiming {
+    fn new(settings: SerialSettings, clock: Clock) -> Self {
+        Self {
+            settings,
+            clock,
+            last_tx: None,
+        }
+    }
+
+    /// true if each frame must be written separately so that the timing applies between them
+    fn separates_frames(&self) -> bool {
+        self.settings.timing.is_enabled()
+    }
+
+    /// wait for the inter-frame gap and the turnaround delay before writing a frame
+    async fn before_frame(&self) {
+        let timing = self.settings.timing;
+        if let Some(last_tx) = self.last_tx {
+            self.clock
+                .sleep_until(last_tx + timing.inter_frame_gap)
+                .await;
+        }
+        if timing.turnaround_delay > Duration::from_secs(0) {
+            self.clock
+                .sleep_until(self.clock.now() + timing.turnaround_delay)
+                .await;
+        }
+    }
+
+    /// record the end of the transmission of a frame and wait for the post-transmit quiet time
+    async fn after_frame(&mut self, length: usize) {
+        let timing = self.settings.timing;
+        let tx_complete = self.clock.now() + self.settings.transmission_time(length);
+        self.last_tx = Some(tx_complete);
+        if timing.post_transmit_quiet > Duration::from_secs(0) {
+            self.clock
+                .sleep_until(tx_complete + timing.post_transmit_quiet)
+                .await;
         }
     }
 }
 
-pub(crate) fn open(path: &str, settings: SerialSettings) -> tokio_serial::Result<TTYPort> {
+/// an open serial port that applies the configured frame timing to each write
+pub(crate) struct SerialPort {
+    port: TTYPort,
+    timing: FrameTiming,
+}
+
+impl SerialPort {
+    pub(crate) async fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        self.port.read(buffer).await
+    }
+
+    /// true if consecutive frames may be passed to a single write
+    pub(crate) fn coalesces_writes(&self) -> bool {
+        !self.timing.separates_frames()
+    }
+
+    pub(crate) async fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        if !self.timing.separates_frames() {
+            return self.port.write_all(data).await;
+        }
+
+        self.timing.before_frame().await;
+        self.port.write_all(data).await?;
+        self.timing.after_frame(data.len()).await;
+        Ok(())
+    }
+}
+
+pub(crate) fn open(
+    path: &str,
+    settings: SerialSettings,
+    clock: Clock,
+) -> tokio_serial::Result<SerialPort> {
     let builder = settings.apply(tokio_serial::new(path, settings.baud_rate));
-    TTYPort::open(&builder)
+    Ok(SerialPort {
+        port: TTYPort::open(&builder)?,
+        timing: FrameTiming::new(settings, clock),
+    })
 }
 
 pub use master::*;
@@ -62,6 +218,106 @@ pub enum PortState {
     Wait(std::time::Duration),
     /// Port is open
     Open,
+    /// Port was closed for the specified reason
+    Closed(crate::app::DisconnectReason),
     /// Task has been shut down
     Shutdown,
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::app::ManualClock;
+    use crate::tokio::test::*;
+
+    #[test]
+    fn transmission_time_accounts_for_character_format() {
+        let settings = SerialSettings::default();
+        // 8N1 is 10 bits per character
+        assert_eq!(settings.transmission_time(96), Duration::from_millis(100));
+
+        let settings = SerialSettings {
+            parity: Parity::Even,
+            stop_bits: StopBits::Two,
+            ..SerialSettings::default()
+        };
+        // 8E2 is 12 bits per character
+        assert_eq!(settings.transmission_time(80), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn inter_character_timeout_replaces_inter_frame_timeout() {
+        let link = LinkConfig {
+            inter_frame_timeout: Some(Duration::from_secs(1)),
+            ..LinkConfig::default()
+        };
+        assert_eq!(SerialSettings::default().link_config(link), link);
+
+        let settings = SerialSettings {
+            inter_character_timeout: Some(Duration::from_millis(20)),
+            ..SerialSettings::default()
+        };
+        assert_eq!(
+            settings.link_config(link).inter_frame_timeout,
+            Some(Duration::from_millis(20))
+        );
+    }
+
+    #[test]
+    fn waits_for_the_gap_between_frames() {
+        let manual = Arc::new(ManualClock::new());
+        let settings = SerialSettings {
+            timing: SerialTiming {
+                inter_frame_gap: Duration::from_millis(50),
+                ..SerialTiming::default()
+            },
+            ..SerialSettings::default()
+        };
+        let mut timing = FrameTiming::new(settings, Clock::manual(manual.clone()));
+
+        // nothing was transmitted yet
+        assert_ready!(spawn(timing.before_frame()).poll());
+        assert_ready!(spawn(timing.after_frame(96)).poll());
+
+        // the 96 characters take 100 ms to leave the port at 9600 baud, then the gap follows
+        let mut before = spawn(timing.before_frame());
+        assert_pending!(before.poll());
+        manual.advance(Duration::from_millis(149));
+        assert_pending!(before.poll());
+        manual.advance(Duration::from_millis(1));
+        assert_ready!(before.poll());
+    }
+
+    #[test]
+    fn frames_are_written_separately_when_timing_is_configured() {
+        let clock = Clock::manual(Arc::new(ManualClock::new()));
+        let timing = |timing: SerialTiming| {
+            FrameTiming::new(
+                SerialSettings {
+                    timing,
+                    ..SerialSettings::default()
+                },
+                clock.clone(),
+            )
+        };
+
+        assert!(!timing(SerialTiming::default()).separates_frames());
+        assert!(timing(SerialTiming {
+            inter_frame_gap: Duration::from_millis(5),
+            ..SerialTiming::default()
+        })
+        .separates_frames());
+        assert!(timing(SerialTiming {
+            turnaround_delay: Duration::from_millis(1),
+            ..SerialTiming::default()
+        })
+        .separates_frames());
+        assert!(timing(SerialTiming {
+            post_transmit_quiet: Duration::from_millis(1),
+            ..SerialTiming::default()
+        })
+        .separates_frames());
+    }
+}
@@ -1,15 +1,18 @@
 use std::future::Future;
+use std::time::Duration;
 
 use tracing::Instrument;
 
+use crate::app::{Clock, DisconnectReason, Listener, Shutdown, Spawner};
 use crate::link::LinkErrorMode;
 use crate::outstation::database::EventBufferConfig;
+use crate::outstation::session::RunError;
 use crate::outstation::task::OutstationTask;
 use crate::outstation::{
     ControlHandler, OutstationApplication, OutstationConfig, OutstationHandle,
     OutstationInformation,
 };
-use crate::serial::SerialSettings;
+use crate::serial::{PortState, SerialSettings};
 use crate::util::phys::PhysLayer;
 
 /// Spawn an outstation task onto the `Tokio` runtime. The task runs until the returned handle is dropped or
@@ -26,6 +29,7 @@ pub fn spawn_outstation_serial(
     information: Box<dyn OutstationInformation>,
     control_handler: Box<dyn ControlHandler>,
 ) -> std::io::Result<OutstationHandle> {
+    let spawner = config.spawner.clone();
     let (future, handle) = create_outstation_serial(
         path,
         settings,
@@ -35,7 +39,7 @@ pub fn spawn_outstation_serial(
         information,
         control_handler,
     )?;
-    crate::tokio::spawn(future);
+    Spawner::spawn(spawner.as_deref(), future);
     Ok(handle)
 }
 
@@ -56,10 +60,14 @@ pub fn create_outstation_serial(
     information: Box<dyn OutstationInformation>,
     control_handler: Box<dyn ControlHandler>,
 ) -> std::io::Result<(impl Future<Output = ()> + 'static, OutstationHandle)> {
-    let serial = crate::serial::open(path, settings)?;
+    let serial = crate::serial::open(path, settings, config.clock.clone())?;
+    let tracing_config = config.tracing;
     let (mut task, handle) = OutstationTask::create(
         LinkErrorMode::Discard,
-        config,
+        OutstationConfig {
+            link: settings.link_config(config.link),
+            ..config
+        },
         event_config,
         application,
         information,
@@ -68,11 +76,260 @@ pub fn create_outstation_serial(
 
     let log_path = path.to_owned();
     let future = async move {
+        let span = tracing_config
+            .channel_span(tracing::info_span!("DNP3-Master-Serial", "port" = ?log_path));
         let mut io = PhysLayer::Serial(serial);
-        let _ = task
-            .run(&mut io)
-            .instrument(tracing::info_span!("DNP3-Master-Serial", "port" = ?log_path))
-            .await;
+        let _ = task.run(&mut io).instrument(span).await;
     };
     Ok((future, handle))
 }
+
+/// Spawn an outstation task onto the `Tokio` runtime. The task runs until the returned handle is dropped.
+///
+/// Unlike `spawn_outstation_serial(..)`, the port is opened by the task and is re-opened after
+/// `retry_delay` whenever it cannot be opened or fails, e.g. when a USB adapter is unplugged.
+/// The outstation state is reset in between each use of the port.
+///
+/// **Note**: This function may only be called from within the runtime itself, and panics otherwise.
+/// It is preferable to use this method instead of `create_outstation_serial_fault_tolerant(..)` when using `[tokio::main]`.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_outstation_serial_fault_tolerant(
+    path: &str,
+    settings: SerialSettings,
+    retry_delay: Duration,
+    listener: Box<dyn Listener<PortState>>,
+    config: OutstationConfig,
+    event_config: EventBufferConfig,
+    application: Box<dyn OutstationApplication>,
+    information: Box<dyn OutstationInformation>,
+    control_handler: Box<dyn ControlHandler>,
+) -> OutstationHandle {
+    let spawner = config.spawner.clone();
+    let (future, handle) = create_outstation_serial_fault_tolerant(
+        path,
+        settings,
+        retry_delay,
+        listener,
+        config,
+        event_config,
+        application,
+        information,
+        control_handler,
+    );
+    Spawner::spawn(spawner.as_deref(), future);
+    handle
+}
+
+/// Create an outstation future, which can be spawned onto a runtime, along with a controlling handle.
+///
+/// Once spawned or otherwise executed using the `run` method, the task runs until the handle
+/// is dropped. The port is re-opened after `retry_delay` whenever it cannot be opened or fails.
+///
+/// **Note**: This function is required instead of `spawn` when using a runtime to directly spawn
+/// tasks instead of within the context of a runtime, e.g. in applications that cannot use
+/// `[tokio::main]` such as C language bindings.
+#[allow(clippy::too_many_arguments)]
+pub fn create_outstation_serial_fault_tolerant(
+    path: &str,
+    settings: SerialSettings,
+    retry_delay: Duration,
+    listener: Box<dyn Listener<PortState>>,
+    config: OutstationConfig,
+    event_config: EventBufferConfig,
+    application: Box<dyn OutstationApplication>,
+    information: Box<dyn OutstationInformation>,
+    control_handler: Box<dyn ControlHandler>,
+) -> (impl Future<Output = ()> + 'static, OutstationHandle) {
+    let clock = config.clock.clone();
+    let tracing_config = config.tracing;
+    let (task, handle) = OutstationTask::create(
+        LinkErrorMode::Discard,
+        OutstationConfig {
+            link: settings.link_config(config.link),
+            ..config
+        },
+        event_config,
+        application,
+        information,
+        control_handler,
+    );
+
+    let port = path.to_owned();
+    let open_clock = clock.clone();
+    let mut task = SerialOutstationTask {
+        open: Box::new(move || -> std::io::Result<PhysLayer> {
+            Ok(PhysLayer::Serial(crate::serial::open(
+                &port,
+                settings,
+                open_clock.clone(),
+            )?))
+        }),
+        retry_delay,
+        clock,
+        listener,
+        task,
+    };
+
+    let log_path = path.to_owned();
+    let future = async move {
+        let span = tracing_config
+            .channel_span(tracing::info_span!("DNP3-Outstation-Serial", "port" = ?log_path));
+        task.run().instrument(span).await;
+    };
+    (future, handle)
+}
+
+struct SerialOutstationTask {
+    // opens the port, replaced in tests to simulate a port that disappears and appears again
+    open: Box<dyn FnMut() -> std::io::Result<PhysLayer> + Send>,
+    retry_delay: Duration,
+    clock: Clock,
+    listener: Box<dyn Listener<PortState>>,
+    task: OutstationTask,
+}
+
+impl SerialOutstationTask {
+    async fn run(&mut self) {
+        let _ = self.run_impl().await;
+        self.listener.update(PortState::Shutdown);
+    }
+
+    async fn run_impl(&mut self) -> Result<(), Shutdown> {
+        loop {
+            match (self.open)() {
+                Err(err) => {
+                    tracing::warn!(
+                        "{} - waiting {} ms to re-open port",
+                        err,
+                        self.retry_delay.as_millis()
+                    );
+                    self.listener.update(PortState::Wait(self.retry_delay));
+                    self.wait_for_retry().await?;
+                }
+                Ok(mut io) => {
+                    tracing::info!("serial port open");
+                    self.listener.update(PortState::Open);
+                    let result = self.task.run(&mut io).await;
+
+                    // reset outstation state in between uses of the port
+                    self.task.reset();
+
+                    match result {
+                        RunError::Shutdown => return Err(Shutdown),
+                        // idle timeouts are only configured for TCP clients
+                        RunError::IdleTimeout => {
+                            self.listener
+                                .update(PortState::Closed(DisconnectReason::IdleTimeout));
+                        }
+                        RunError::Link(err) => {
+                            tracing::warn!("serial port error: {}", err);
+                            self.listener.update(PortState::Closed(err.into()));
+                            tracing::info!(
+                                "waiting {} ms to re-open",
+                                self.retry_delay.as_millis()
+                            );
+                            self.listener.update(PortState::Wait(self.retry_delay));
+                            self.wait_for_retry().await?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// process messages from the handle until the retry delay elapses
+    async fn wait_for_retry(&mut self) -> Result<(), Shutdown> {
+        let clock = self.clock.clone();
+        let deadline = clock.now() + self.retry_delay;
+        crate::tokio::select! {
+            ret = self.task.process_messages() => {
+                ret
+            }
+            _ = clock.sleep_until(deadline) => {
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+    use std::io::ErrorKind;
+
+    use super::*;
+    use crate::app::listener_channel;
+    use crate::link::EndpointAddress;
+    use crate::outstation::{
+        DefaultControlHandler, DefaultOutstationApplication, DefaultOutstationInformation,
+    };
+    use crate::tokio::test::*;
+    use crate::tokio::time;
+
+    const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn reopens_port_that_disappears_and_appears_again() {
+        let (port, remote) = crate::tokio::io::duplex(1024);
+        let mut attempts: VecDeque<std::io::Result<PhysLayer>> = VecDeque::new();
+        attempts.push_back(Err(ErrorKind::NotFound.into()));
+        attempts.push_back(Ok(PhysLayer::Custom(Box::new(port))));
+
+        let (listener, mut states) = listener_channel(16);
+        let config = OutstationConfig::new(
+            EndpointAddress::from(10).unwrap(),
+            EndpointAddress::from(1).unwrap(),
+        );
+        let clock = config.clock.clone();
+        let (task, handle) = OutstationTask::create(
+            LinkErrorMode::Discard,
+            config,
+            EventBufferConfig::all_types(5),
+            DefaultOutstationApplication::create(),
+            DefaultOutstationInformation::create(),
+            DefaultControlHandler::create(),
+        );
+        let mut task = SerialOutstationTask {
+            open: Box::new(move || {
+                attempts
+                    .pop_front()
+                    .unwrap_or_else(|| Err(ErrorKind::NotFound.into()))
+            }),
+            retry_delay: RETRY_DELAY,
+            clock,
+            listener,
+            task,
+        };
+        let mut run = spawn(async move { task.run().await });
+
+        // the port doesn't exist yet
+        assert_pending!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Wait(RETRY_DELAY)));
+        assert_eq!(states.try_next(), None);
+
+        time::advance(RETRY_DELAY);
+        assert_pending!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Open));
+        assert_eq!(states.try_next(), None);
+
+        // the USB adapter is unplugged
+        drop(remote);
+        assert_pending!(run.poll());
+        assert_eq!(
+            states.try_next(),
+            Some(PortState::Closed(DisconnectReason::Io(
+                ErrorKind::UnexpectedEof
+            )))
+        );
+        assert_eq!(states.try_next(), Some(PortState::Wait(RETRY_DELAY)));
+
+        // the port is gone, so the task keeps waiting until the handle is dropped
+        time::advance(RETRY_DELAY);
+        assert_pending!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Wait(RETRY_DELAY)));
+
+        drop(handle);
+        assert_ready!(run.poll());
+        assert_eq!(states.try_next(), Some(PortState::Shutdown));
+    }
+}
@@ -0,0 +1,5 @@
+pub use scenario::*;
+pub use waveform::*;
+
+mod scenario;
+mod waveform;
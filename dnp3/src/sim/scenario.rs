@@ -0,0 +1,410 @@
+use std::time::Duration;
+
+use crate::app::measurement::{Analog, Binary, Counter, Flags, Time};
+use crate::app::{Clock, Iin, Shutdown, Timestamp};
+use crate::outstation::database::{
+    Add, AnalogConfig, BinaryConfig, CounterConfig, Database, EventClass, EventMode, Get, Update,
+    UpdateOptions,
+};
+use crate::outstation::OutstationHandle;
+use crate::sim::Waveform;
+
+/// Point of the outstation database driven by a [Scenario]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SimPoint {
+    /// binary input that is true whenever the simulated value is greater than or equal to 0.5
+    Binary(u16),
+    /// counter whose value is the simulated value, saturated to the range of a `u32`
+    Counter(u16),
+    /// analog input whose value is the simulated value
+    Analog(u16),
+}
+
+/// Change in the data or behavior of the simulated outstation at a point in a [Scenario]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Action {
+    /// set a point to a value, producing an event if the value changed
+    Set(SimPoint, f64),
+    /// produce the specified number of events for a point with its current value
+    EventBurst(SimPoint, u16),
+    /// delay every subsequent solicited response, or remove the delay with `None`
+    ResponseDelay(Option<Duration>),
+    /// set IIN bits in every subsequent response, e.g. reserved bits or DEVICE_TROUBLE, or clear
+    /// them with `Iin::default()`
+    ForceIin(Iin),
+}
+
+/// Declarative description of the behavior of a simulated outstation over time
+///
+/// A scenario drives the points of the database of a running outstation with [Waveform]s and
+/// applies timed [Action]s. It works with an outstation on any physical layer, so a single
+/// scenario can load test or regression test any master.
+///
+/// Waveforms are sampled at their scheduled times rather than at the time the runtime wakes up,
+/// so the values reported by a scenario are the same on every run. Runs are also independent of
+/// real time when the scenario and the outstation share a [ManualClock](crate::app::ManualClock).
+#[derive(Clone, Debug)]
+pub struct Scenario {
+    signals: Vec<Signal>,
+    actions: Vec<(Duration, Action)>,
+    duration: Option<Duration>,
+    clock: Clock,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Signal {
+    point: SimPoint,
+    waveform: Waveform,
+    interval: Duration,
+}
+
+impl Scenario {
+    /// Minimum interval between the updates of a point driven by a waveform
+    pub const MIN_INTERVAL: Duration = Duration::from_millis(1);
+
+    /// Create a scenario without any signals or actions that runs until it is dropped
+    pub fn new() -> Self {
+        Self {
+            signals: Vec::new(),
+            actions: Vec::new(),
+            duration: None,
+            clock: Clock::default(),
+        }
+    }
+
+    /// Update a point with the value of a waveform every `interval`, starting when the scenario
+    /// starts
+    ///
+    /// The interval is raised to [Scenario::MIN_INTERVAL] if it is shorter.
+    pub fn signal(mut self, point: SimPoint, waveform: Waveform, interval: Duration) -> Self {
+        self.signals.push(Signal {
+            point,
+            waveform,
+            interval: interval.max(Self::MIN_INTERVAL),
+        });
+        self
+    }
+
+    /// Apply an action at the specified time after the start of the scenario
+    ///
+    /// Actions scheduled at the same time are applied in the order they were added.
+    pub fn at(mut self, offset: Duration, action: Action) -> Self {
+        let position = self
+            .actions
+            .iter()
+            .position(|(x, _)| *x > offset)
+            .unwrap_or_else(|| self.actions.len());
+        self.actions.insert(position, (offset, action));
+        self
+    }
+
+    /// Stop the scenario once the specified amount of time has elapsed since it started
+    ///
+    /// Without a duration, a scenario with signals runs until it is dropped, and a scenario
+    /// without signals completes after its last action.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Set the clock that determines when points are updated and actions are applied
+    ///
+    /// This should be the clock in the configuration of the outstation.
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Add every point driven by the scenario to a database, assigned to class 1 with the
+    /// default configuration of its type and initialized with the value of its waveform at the
+    /// start of the scenario
+    ///
+    /// Points that already exist are left unchanged.
+    pub fn add_points(&self, database: &mut Database) {
+        for signal in self.signals.iter() {
+            let added = match signal.point {
+                SimPoint::Binary(index) => {
+                    database.add(index, Some(EventClass::Class1), BinaryConfig::default())
+                }
+                SimPoint::Counter(index) => {
+                    database.add(index, Some(EventClass::Class1), CounterConfig::default())
+                }
+                SimPoint::Analog(index) => {
+                    database.add(index, Some(EventClass::Class1), AnalogConfig::default())
+                }
+            };
+            if added {
+                update(
+                    database,
+                    signal.point,
+                    signal.waveform.value_at(Duration::from_secs(0)),
+                    now(&self.clock),
+                    UpdateOptions::initialize(),
+                );
+            }
+        }
+    }
+
+    /// Run the scenario against a running outstation
+    ///
+    /// Completes once the duration of the scenario elapses, or with an error if the outstation
+    /// shuts down. The response delay and forced IIN bits set by the scenario are left in place.
+    pub async fn run(self, mut handle: OutstationHandle) -> Result<(), Shutdown> {
+        let clock = self.clock.clone();
+        let start = clock.now();
+        let mut timeline = Timeline::new(&self);
+
+        while let Some((offset, steps)) = timeline.next() {
+            clock.sleep_until(start + offset).await;
+            let time = now(&clock);
+            for step in steps {
+                match step {
+                    Step::Update(point, value) => handle.transaction(|database| {
+                        update(database, point, value, time, UpdateOptions::default());
+                    }),
+                    Step::Action(Action::Set(point, value)) => handle.transaction(|database| {
+                        update(database, point, value, time, UpdateOptions::default());
+                    }),
+                    Step::Action(Action::EventBurst(point, count)) => {
+                        handle.transaction(|database| {
+                            burst(database, point, count, time);
+                        })
+                    }
+                    Step::Action(Action::ResponseDelay(delay)) => {
+                        handle.set_response_delay(delay).await?
+                    }
+                    Step::Action(Action::ForceIin(iin)) => handle.set_forced_iin(iin).await?,
+                }
+            }
+        }
+
+        if let Some(duration) = self.duration {
+            clock.sleep_until(start + duration).await;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Step {
+    Update(SimPoint, f64),
+    Action(Action),
+}
+
+/// steps of a scenario grouped by the time at which they occur
+struct Timeline<'a> {
+    signals: Vec<(&'a Signal, Duration)>,
+    actions: std::iter::Peekable<std::slice::Iter<'a, (Duration, Action)>>,
+    end: Option<Duration>,
+}
+
+impl<'a> Timeline<'a> {
+    fn new(scenario: &'a Scenario) -> Self {
+        Self {
+            signals: scenario
+                .signals
+                .iter()
+                .map(|signal| (signal, Duration::from_secs(0)))
+                .collect(),
+            actions: scenario.actions.iter().peekable(),
+            end: scenario.duration,
+        }
+    }
+
+    fn next(&mut self) -> Option<(Duration, Vec<Step>)> {
+        let next_signal = self.signals.iter().map(|(_, next)| *next).min();
+        let next_action = self.actions.peek().map(|(offset, _)| *offset);
+        let offset = match (next_signal, next_action) {
+            (Some(x), Some(y)) => x.min(y),
+            (Some(x), None) | (None, Some(x)) => x,
+            (None, None) => return None,
+        };
+
+        if matches!(self.end, Some(end) if offset >= end) {
+            return None;
+        }
+
+        let mut steps = Vec::new();
+        for (signal, next) in self.signals.iter_mut() {
+            if *next == offset {
+                steps.push(Step::Update(signal.point, signal.waveform.value_at(offset)));
+                *next += signal.interval;
+            }
+        }
+        while let Some(&&(next, action)) = self.actions.peek() {
+            if next != offset {
+                break;
+            }
+            steps.push(Step::Action(action));
+            self.actions.next();
+        }
+
+        Some((offset, steps))
+    }
+}
+
+fn now(clock: &Clock) -> Time {
+    Time::Synchronized(
+        Timestamp::try_from_system_time(clock.system_time()).unwrap_or_else(Timestamp::min),
+    )
+}
+
+fn update(
+    database: &mut Database,
+    point: SimPoint,
+    value: f64,
+    time: Time,
+    options: UpdateOptions,
+) {
+    let updated = match point {
+        SimPoint::Binary(index) => database.update(
+            index,
+            &Binary::new(value >= 0.5, Flags::ONLINE, time),
+            options,
+        ),
+        SimPoint::Counter(index) => database.update(
+            index,
+            &Counter::new(value as u32, Flags::ONLINE, time),
+            options,
+        ),
+        SimPoint::Analog(index) => {
+            database.update(index, &Analog::new(value, Flags::ONLINE, time), options)
+        }
+    };
+
+    if !updated {
+        tracing::warn!("simulated point {:?} is not in the database", point);
+    }
+}
+
+fn burst(database: &mut Database, point: SimPoint, count: u16, time: Time) {
+    let options = UpdateOptions::new(false, EventMode::Force);
+    let time = Some(time);
+    for _ in 0..count {
+        let found = match point {
+            SimPoint::Binary(index) => database.get(index).map(|mut x: Binary| {
+                x.time = time;
+                database.update(index, &x, options)
+            }),
+            SimPoint::Counter(index) => database.get(index).map(|mut x: Counter| {
+                x.time = time;
+                database.update(index, &x, options)
+            }),
+            SimPoint::Analog(index) => database.get(index).map(|mut x: Analog| {
+                x.time = time;
+                database.update(index, &x, options)
+            }),
+        };
+
+        if found.is_none() {
+            tracing::warn!("simulated point {:?} is not in the database", point);
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn collect(scenario: &Scenario) -> Vec<(Duration, Vec<Step>)> {
+        let mut timeline = Timeline::new(scenario);
+        let mut steps = Vec::new();
+        while let Some(x) = timeline.next() {
+            steps.push(x);
+        }
+        steps
+    }
+
+    #[test]
+    fn interleaves_signals_and_actions_until_the_end() {
+        let ramp = Waveform::Ramp {
+            start: 0.0,
+            end: 4.0,
+            period: secs(4),
+        };
+        let scenario = Scenario::new()
+            .signal(SimPoint::Analog(3), ramp, secs(1))
+            .at(millis(1500), Action::EventBurst(SimPoint::Binary(0), 5))
+            .at(secs(1), Action::ResponseDelay(Some(secs(2))))
+            .duration(millis(2500));
+
+        assert_eq!(
+            collect(&scenario),
+            vec![
+                (secs(0), vec![Step::Update(SimPoint::Analog(3), 0.0)]),
+                (
+                    secs(1),
+                    vec![
+                        Step::Update(SimPoint::Analog(3), 1.0),
+                        Step::Action(Action::ResponseDelay(Some(secs(2)))),
+                    ]
+                ),
+                (
+                    millis(1500),
+                    vec![Step::Action(Action::EventBurst(SimPoint::Binary(0), 5))]
+                ),
+                (secs(2), vec![Step::Update(SimPoint::Analog(3), 2.0)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn actions_at_the_same_time_keep_their_order() {
+        let scenario = Scenario::new()
+            .at(secs(2), Action::Set(SimPoint::Counter(0), 1.0))
+            .at(secs(1), Action::Set(SimPoint::Counter(0), 2.0))
+            .at(secs(2), Action::Set(SimPoint::Counter(0), 3.0));
+
+        assert_eq!(
+            collect(&scenario),
+            vec![
+                (
+                    secs(1),
+                    vec![Step::Action(Action::Set(SimPoint::Counter(0), 2.0))]
+                ),
+                (
+                    secs(2),
+                    vec![
+                        Step::Action(Action::Set(SimPoint::Counter(0), 1.0)),
+                        Step::Action(Action::Set(SimPoint::Counter(0), 3.0)),
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn short_intervals_are_raised_to_the_minimum() {
+        let scenario = Scenario::new()
+            .signal(
+                SimPoint::Binary(1),
+                Waveform::Constant(1.0),
+                Duration::from_secs(0),
+            )
+            .duration(millis(2));
+
+        assert_eq!(
+            collect(&scenario),
+            vec![
+                (secs(0), vec![Step::Update(SimPoint::Binary(1), 1.0)]),
+                (millis(1), vec![Step::Update(SimPoint::Binary(1), 1.0)]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::time::Duration;
+
+/// Value of a simulated point as a function of the time elapsed since the start of a
+/// [Scenario](crate::sim::Scenario)
+///
+/// Periodic waveforms with a zero period keep their initial value.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Waveform {
+    /// value that never changes
+    Constant(f64),
+    /// linear ramp from `start` towards `end` that restarts at `start` every `period`
+    Ramp {
+        /// value at the beginning of each period
+        start: f64,
+        /// value approached at the end of each period
+        end: f64,
+        /// time between the restarts of the ramp
+        period: Duration,
+    },
+    /// sine wave starting at `offset` and rising
+    Sine {
+        /// value around which the wave oscillates
+        offset: f64,
+        /// maximum deviation from the offset
+        amplitude: f64,
+        /// time of one full oscillation
+        period: Duration,
+    },
+    /// square wave that spends the first half of each `period` at `low` and the second half at
+    /// `high`
+    Square {
+        /// value during the first half of each period
+        low: f64,
+        /// value during the second half of each period
+        high: f64,
+        /// time of one full cycle
+        period: Duration,
+    },
+}
+
+impl Waveform {
+    /// Value of the waveform at the specified time since the start of the scenario
+    pub fn value_at(&self, elapsed: Duration) -> f64 {
+        match *self {
+            Waveform::Constant(value) => value,
+            Waveform::Ramp { start, end, period } => {
+                start + (end - start) * Self::phase(elapsed, period)
+            }
+            Waveform::Sine {
+                offset,
+                amplitude,
+                period,
+            } => {
+                offset
+                    + amplitude * (2.0 * std::f64::consts::PI * Self::phase(elapsed, period)).sin()
+            }
+            Waveform::Square { low, high, period } => {
+                if Self::phase(elapsed, period) < 0.5 {
+                    low
+                } else {
+                    high
+                }
+            }
+        }
+    }
+
+    /// fraction of the current period that has elapsed, in the range [0, 1)
+    fn phase(elapsed: Duration, period: Duration) -> f64 {
+        let period = period.as_nanos();
+        if period == 0 {
+            return 0.0;
+        }
+        (elapsed.as_nanos() % period) as f64 / period as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at_millis(waveform: Waveform, millis: u64) -> f64 {
+        waveform.value_at(Duration::from_millis(millis))
+    }
+
+    #[test]
+    fn ramp_restarts_every_period() {
+        let ramp = Waveform::Ramp {
+            start: 10.0,
+            end: 20.0,
+            period: Duration::from_secs(1),
+        };
+        assert_eq!(at_millis(ramp, 0), 10.0);
+        assert_eq!(at_millis(ramp, 500), 15.0);
+        assert_eq!(at_millis(ramp, 1000), 10.0);
+        assert_eq!(at_millis(ramp, 1250), 12.5);
+    }
+
+    #[test]
+    fn sine_oscillates_around_offset() {
+        let sine = Waveform::Sine {
+            offset: 5.0,
+            amplitude: 2.0,
+            period: Duration::from_secs(4),
+        };
+        assert!((at_millis(sine, 0) - 5.0).abs() < 1e-9);
+        assert!((at_millis(sine, 1000) - 7.0).abs() < 1e-9);
+        assert!((at_millis(sine, 3000) - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn square_alternates_between_levels() {
+        let square = Waveform::Square {
+            low: 0.0,
+            high: 1.0,
+            period: Duration::from_secs(2),
+        };
+        assert_eq!(at_millis(square, 0), 0.0);
+        assert_eq!(at_millis(square, 999), 0.0);
+        assert_eq!(at_millis(square, 1000), 1.0);
+        assert_eq!(at_millis(square, 2000), 0.0);
+    }
+
+    #[test]
+    fn zero_period_keeps_initial_value() {
+        let ramp = Waveform::Ramp {
+            start: 3.0,
+            end: 4.0,
+            period: Duration::from_secs(0),
+        };
+        assert_eq!(at_millis(ramp, 1234), 3.0);
+    }
+}
@@ -1,18 +1,38 @@
 use std::collections::VecDeque;
 use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Controls which endpoint of an `EndpointList` is tried first after a connection is lost
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FailoverPolicy {
+    /// Start again with the main endpoint, then the other endpoints in the order they were defined
+    RestartFromMain,
+    /// Start again with the endpoint that was connected, then the endpoints that follow it
+    Sticky,
+}
+
+impl Default for FailoverPolicy {
+    fn default() -> Self {
+        Self::RestartFromMain
+    }
+}
 
 /// List of IP endpoints
 ///
 /// You can write IP addresses or DNS names and the port to connect to. e.g. `"127.0.0.1:20000"` or `"dnp3.myorg.com:20000"`.
 ///
-/// The main endpoint is always favoured. When a successful connection is established, it resets the next endpoint
+/// By default, the main endpoint is always favoured. When a successful connection is established, it resets the next endpoint
 /// to try, meaning if the connection is lost, the main endpoint will be retried first, then the other endpoints
-/// in the order they were defined.
+/// in the order they were defined. This is controlled by the `FailoverPolicy`.
 #[derive(Clone, Debug)]
 pub struct EndpointList {
     endpoints: Vec<String>,
     pending_endpoints: VecDeque<SocketAddr>,
     current_endpoint: usize,
+    // endpoint from which the last returned address was taken
+    resolved_endpoint: usize,
+    policy: FailoverPolicy,
+    return_to_main: Option<Duration>,
 }
 
 impl EndpointList {
@@ -29,6 +49,25 @@ impl EndpointList {
             endpoints,
             pending_endpoints: VecDeque::new(),
             current_endpoint: 0,
+            resolved_endpoint: 0,
+            policy: FailoverPolicy::default(),
+            return_to_main: None,
+        }
+    }
+
+    /// Select the endpoint that is tried first after a connection is lost
+    pub fn with_failover_policy(self, policy: FailoverPolicy) -> Self {
+        Self { policy, ..self }
+    }
+
+    /// Close connections to a fail-over endpoint after `hold_time` to try the main endpoint again
+    ///
+    /// The connection is closed between tasks, never while a request is outstanding. If the
+    /// main endpoint can't be reached, the fail-overs are tried again in order.
+    pub fn with_return_to_main(self, hold_time: Duration) -> Self {
+        Self {
+            return_to_main: Some(hold_time),
+            ..self
         }
     }
 
@@ -42,11 +81,39 @@ impl EndpointList {
         self.endpoints.first().unwrap()
     }
 
-    pub(crate) fn reset(&mut self) {
+    /// record a successful connection to the last returned endpoint, returning its index
+    pub(crate) fn on_connected(&mut self) -> usize {
+        self.pending_endpoints.clear();
+        self.current_endpoint = match self.policy {
+            FailoverPolicy::RestartFromMain => 0,
+            FailoverPolicy::Sticky => self.resolved_endpoint,
+        };
+        self.resolved_endpoint
+    }
+
+    /// amount of time to stay connected to an endpoint before returning to the main endpoint
+    pub(crate) fn hold_time(&self, index: usize) -> Option<Duration> {
+        if index == 0 {
+            None
+        } else {
+            self.return_to_main
+        }
+    }
+
+    /// try the main endpoint on the next connection attempt
+    pub(crate) fn return_to_main(&mut self) {
         self.pending_endpoints.clear();
         self.current_endpoint = 0;
     }
 
+    /// next endpoint to try, without resolving it
+    pub(crate) fn next_name(&mut self) -> String {
+        let endpoint = self.endpoints[self.current_endpoint].clone();
+        self.resolved_endpoint = self.current_endpoint;
+        self.current_endpoint = (self.current_endpoint + 1) % self.endpoints.len();
+        endpoint
+    }
+
     pub(crate) async fn next_address(&mut self) -> Option<SocketAddr> {
         if let Some(endpoint) = self.pending_endpoints.pop_front() {
             return Some(endpoint);
@@ -56,6 +123,7 @@ impl EndpointList {
 
         loop {
             let endpoint_idx = self.current_endpoint;
+            self.resolved_endpoint = endpoint_idx;
 
             // Increment the current endpoint
             self.current_endpoint = (self.current_endpoint + 1) % self.endpoints.len();
@@ -80,3 +148,43 @@ impl EndpointList {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn list() -> EndpointList {
+        EndpointList::new(
+            "main:20000".to_string(),
+            &["backup1:20000".to_string(), "backup2:20000".to_string()],
+        )
+    }
+
+    #[test]
+    fn restarts_from_main_after_connection_by_default() {
+        let mut list = list();
+        assert_eq!(list.next_name(), "main:20000");
+        assert_eq!(list.next_name(), "backup1:20000");
+        assert_eq!(list.on_connected(), 1);
+        assert_eq!(list.next_name(), "main:20000");
+    }
+
+    #[test]
+    fn sticky_policy_restarts_from_connected_endpoint() {
+        let mut list = list().with_failover_policy(FailoverPolicy::Sticky);
+        assert_eq!(list.next_name(), "main:20000");
+        assert_eq!(list.next_name(), "backup1:20000");
+        assert_eq!(list.on_connected(), 1);
+        assert_eq!(list.next_name(), "backup1:20000");
+        assert_eq!(list.next_name(), "backup2:20000");
+        list.return_to_main();
+        assert_eq!(list.next_name(), "main:20000");
+    }
+
+    #[test]
+    fn only_fail_overs_have_a_hold_time() {
+        let list = list().with_return_to_main(Duration::from_secs(60));
+        assert_eq!(list.hold_time(0), None);
+        assert_eq!(list.hold_time(2), Some(Duration::from_secs(60)));
+    }
+}
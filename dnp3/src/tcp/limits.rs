@@ -0,0 +1,156 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use crate::app::{Clock, Instant};
+
+/// Maximum number of connections accepted from a single IP address within a period of time
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RateLimit {
+    /// maximum number of connections accepted within the period
+    pub max_connections: usize,
+    /// length of the sliding window over which connections are counted
+    pub period: Duration,
+}
+
+impl RateLimit {
+    /// Create a rate limit of `max_connections` per `period`
+    pub fn new(max_connections: usize, period: Duration) -> Self {
+        Self {
+            max_connections,
+            period,
+        }
+    }
+}
+
+/// What a `TcpServer` does when a connection arrives for an outstation that is already connected
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExistingConnectionPolicy {
+    /// Close the existing connection and use the new one
+    Replace,
+    /// Close the new connection and keep the existing one
+    Reject,
+}
+
+impl Default for ExistingConnectionPolicy {
+    fn default() -> Self {
+        Self::Replace
+    }
+}
+
+/// Limits applied by a `TcpServer` to incoming connections
+///
+/// The defaults don't limit connections and replace existing connections, which matches the
+/// behavior of a server without limits.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ServerLimits {
+    /// maximum number of outstations connected at the same time
+    ///
+    /// A connection that replaces an existing connection doesn't count against the limit.
+    pub max_connections: Option<usize>,
+    /// maximum rate at which connections are accepted from each IP address
+    pub rate_limit: Option<RateLimit>,
+    /// what to do when a connection arrives for an outstation that is already connected
+    pub existing_connection: ExistingConnectionPolicy,
+}
+
+/// Reason a `TcpServer` closed an incoming connection
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RejectReason {
+    /// no outstation has an address filter matching the remote address
+    NoMatchingOutstation,
+    /// the maximum number of concurrent connections was reached
+    TooManyConnections,
+    /// the remote IP address exceeded its rate limit
+    RateLimited,
+    /// the outstation is already connected and the policy is to keep the existing connection
+    AlreadyConnected,
+}
+
+/// Decision made by a `TcpServer` about an incoming connection
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ServerEvent {
+    /// connection was handed to an outstation
+    Accepted(SocketAddr),
+    /// connection was handed to an outstation, replacing its existing connection
+    Replaced(SocketAddr),
+    /// connection was closed for the specified reason
+    Rejected(SocketAddr, RejectReason),
+}
+
+/// tracks recent connections from each IP address
+pub(crate) struct RateLimiter {
+    limit: Option<RateLimit>,
+    history: HashMap<IpAddr, VecDeque<Instant>>,
+    clock: Clock,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: Option<RateLimit>) -> Self {
+        Self {
+            limit,
+            history: HashMap::new(),
+            clock: Clock::default(),
+        }
+    }
+
+    /// record a connection attempt, returning false if it exceeds the rate limit
+    pub(crate) fn check(&mut self, ip: IpAddr) -> bool {
+        let limit = match self.limit {
+            Some(x) => x,
+            None => return true,
+        };
+
+        let now = self.clock.now();
+        // forget the addresses that haven't connected within the period
+        self.history.retain(|_, times| {
+            while let Some(first) = times.front() {
+                if now.saturating_duration_since(*first) < limit.period {
+                    break;
+                }
+                times.pop_front();
+            }
+            !times.is_empty()
+        });
+
+        let times = self.history.entry(ip).or_default();
+        if times.len() >= limit.max_connections {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tokio::time;
+
+    #[test]
+    fn rejects_connections_over_the_rate_until_window_slides() {
+        let mut limiter = RateLimiter::new(Some(RateLimit::new(2, Duration::from_secs(10))));
+        let ip1: IpAddr = "192.168.0.1".parse().unwrap();
+        let ip2: IpAddr = "192.168.0.2".parse().unwrap();
+
+        assert!(limiter.check(ip1));
+        time::advance(Duration::from_secs(5));
+        assert!(limiter.check(ip1));
+        assert!(!limiter.check(ip1));
+        assert!(limiter.check(ip2));
+
+        // only the first connection has left the window
+        time::advance(Duration::from_secs(5));
+        assert!(limiter.check(ip1));
+        assert!(!limiter.check(ip1));
+    }
+
+    #[test]
+    fn accepts_everything_without_limit() {
+        let mut limiter = RateLimiter::new(None);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        for _ in 0..100 {
+            assert!(limiter.check(ip));
+        }
+    }
+}
@@ -1,15 +1,18 @@
 use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
 
 use tracing::Instrument;
 
-use crate::app::{ConnectStrategy, ExponentialBackOff, Listener};
-use crate::app::{RetryStrategy, Shutdown};
+use crate::app::Shutdown;
+use crate::app::{ConnectStrategy, DisconnectReason, ExponentialBackOff, Listener, Spawner};
+use crate::link::statistics::LinkCounters;
 use crate::link::LinkErrorMode;
 use crate::master::session::{MasterSession, RunError, StateChange};
 use crate::master::{MasterChannel, MasterChannelConfig};
-use crate::tcp::ClientState;
 use crate::tcp::EndpointList;
+use crate::tcp::Socks5Proxy;
+use crate::tcp::{ActiveEndpoint, ClientState};
 use crate::tokio::net::TcpStream;
 use crate::transport::TransportReader;
 use crate::transport::TransportWriter;
@@ -27,6 +30,7 @@ pub fn spawn_master_tcp_client(
     connect_strategy: ConnectStrategy,
     listener: Box<dyn Listener<ClientState>>,
 ) -> MasterChannel {
+    let spawner = config.spawner.clone();
     let (future, handle) = create_master_tcp_client(
         link_error_mode,
         config,
@@ -34,7 +38,7 @@ pub fn spawn_master_tcp_client(
         connect_strategy,
         listener,
     );
-    crate::tokio::spawn(future);
+    Spawner::spawn(spawner.as_deref(), future);
     handle
 }
 
@@ -54,25 +58,92 @@ pub fn create_master_tcp_client(
     listener: Box<dyn Listener<ClientState>>,
 ) -> (impl Future<Output = ()> + 'static, MasterChannel) {
     let main_addr = endpoints.main_addr().to_string();
+    let tracing_config = config.tracing;
     let (mut task, handle) = MasterTask::new(
         link_error_mode,
         endpoints,
+        None,
         config,
         connect_strategy,
         listener,
     );
     let future = async move {
-        task.run()
-            .instrument(tracing::info_span!("DNP3-Master-TCP", "endpoint" = ?main_addr))
-            .await;
+        let span = tracing_config
+            .channel_span(tracing::info_span!("DNP3-Master-TCP", "endpoint" = ?main_addr));
+        task.run().instrument(span).await;
+    };
+    (future, handle)
+}
+
+/// Spawn a task onto the `Tokio` runtime which connects to the endpoints through a SOCKS5 proxy.
+/// The task runs until the returned handle, and any `AssociationHandle` created from it, are dropped.
+///
+/// **Note**: This function may only be called from within the runtime itself, and panics otherwise.
+/// It is preferable to use this method instead of `create(..)` when using `[tokio::main]`.
+pub fn spawn_master_tcp_client_via_proxy(
+    link_error_mode: LinkErrorMode,
+    config: MasterChannelConfig,
+    endpoints: EndpointList,
+    proxy: Socks5Proxy,
+    connect_strategy: ConnectStrategy,
+    listener: Box<dyn Listener<ClientState>>,
+) -> MasterChannel {
+    let spawner = config.spawner.clone();
+    let (future, handle) = create_master_tcp_client_via_proxy(
+        link_error_mode,
+        config,
+        endpoints,
+        proxy,
+        connect_strategy,
+        listener,
+    );
+    Spawner::spawn(spawner.as_deref(), future);
+    handle
+}
+
+/// Create a Future which connects to the endpoints through a SOCKS5 proxy, which can be spawned
+/// onto a runtime, along with a controlling handle.
+///
+/// Once spawned or otherwise executed using the `run` method, the task runs until the handle
+/// and any `AssociationHandle` created from it are dropped.
+///
+/// **Note**: This function is required instead of `spawn` when using a runtime to directly spawn
+/// tasks instead of within the context of a runtime, e.g. in applications that cannot use
+/// `[tokio::main]` such as C language bindings.
+pub fn create_master_tcp_client_via_proxy(
+    link_error_mode: LinkErrorMode,
+    config: MasterChannelConfig,
+    endpoints: EndpointList,
+    proxy: Socks5Proxy,
+    connect_strategy: ConnectStrategy,
+    listener: Box<dyn Listener<ClientState>>,
+) -> (impl Future<Output = ()> + 'static, MasterChannel) {
+    let main_addr = endpoints.main_addr().to_string();
+    let proxy_addr = proxy.address().to_string();
+    let tracing_config = config.tracing;
+    let (mut task, handle) = MasterTask::new(
+        link_error_mode,
+        endpoints,
+        Some(proxy),
+        config,
+        connect_strategy,
+        listener,
+    );
+    let future = async move {
+        let span = tracing_config.channel_span(
+            tracing::info_span!("DNP3-Master-TCP", "endpoint" = ?main_addr, "proxy" = ?proxy_addr),
+        );
+        task.run().instrument(span).await;
     };
     (future, handle)
 }
 
 struct MasterTask {
     endpoints: EndpointList,
+    proxy: Option<Socks5Proxy>,
     back_off: ExponentialBackOff,
     reconnect_delay: Duration,
+    dial_up: bool,
     session: MasterSession,
     reader: TransportReader,
     writer: TransportWriter,
@@ -83,36 +154,54 @@ impl MasterTask {
     fn new(
         link_error_mode: LinkErrorMode,
         endpoints: EndpointList,
+        proxy: Option<Socks5Proxy>,
         config: MasterChannelConfig,
         connect_strategy: ConnectStrategy,
         listener: Box<dyn Listener<ClientState>>,
     ) -> (Self, MasterChannel) {
         let (tx, rx) = crate::util::channel::request_channel();
-        let session = MasterSession::new(
+        let link_counters = Arc::new(LinkCounters::new(
+            config.clock,
+            config.tracing,
+            config.buffer_pool,
+        ));
+        let mut session = MasterSession::new(
             false,
             config.decode_level,
             config.response_timeout,
+            config.scheduling,
+            config.link,
+            link_counters.clone(),
+            config.tracing,
             config.tx_buffer_size,
+            config.rx_buffer_size,
             rx,
         );
+        if let Some(idle_timeout) = connect_strategy.dial_up_idle_timeout {
+            session.set_dial_up(idle_timeout);
+        }
         let (reader, writer) = crate::transport::create_master_transport_layer(
             link_error_mode,
             config.master_address,
             config.rx_buffer_size,
+            config.link,
+            config.response_timeout.into(),
+            config.transport,
+            config.unknown_destination,
+            link_counters.clone(),
         );
         let task = Self {
             endpoints,
-            back_off: ExponentialBackOff::new(RetryStrategy::new(
-                connect_strategy.min_connect_delay,
-                connect_strategy.max_connect_delay,
-            )),
+            proxy,
+            back_off: ExponentialBackOff::new(connect_strategy.retry),
             reconnect_delay: connect_strategy.reconnect_delay,
+            dial_up: connect_strategy.dial_up_idle_timeout.is_some(),
             session,
             reader,
             writer,
             listener,
         };
-        (task, MasterChannel::new(tx))
+        (task, MasterChannel::new(tx, link_counters))
     }
 
     async fn run(&mut self) {
@@ -133,11 +222,58 @@ impl MasterTask {
 
     async fn run_connection(&mut self) -> Result<(), StateChange> {
         loop {
+            if self.dial_up {
+                self.session.wait_for_work().await?;
+            }
             self.run_one_connection().await?;
         }
     }
 
     async fn run_one_connection(&mut self) -> Result<(), StateChange> {
+        match self.proxy.clone() {
+            Some(proxy) => self.run_one_proxy_connection(&proxy).await,
+            None => self.run_one_direct_connection().await,
+        }
+    }
+
+    async fn run_one_proxy_connection(&mut self, proxy: &Socks5Proxy) -> Result<(), StateChange> {
+        let endpoint = self.endpoints.next_name();
+        self.listener.update(ClientState::Connecting);
+        let clock = self.session.clock();
+        match self
+            .session
+            .wait_for(proxy.connect(&endpoint, &clock))
+            .await?
+        {
+            Err(err) => {
+                let delay = self.back_off.on_failure();
+                tracing::warn!(
+                    "failed to connect to {} via proxy {}: {} - waiting {} ms to retry",
+                    endpoint,
+                    proxy.address(),
+                    err,
+                    delay.as_millis()
+                );
+                self.listener
+                    .update(ClientState::WaitAfterFailedConnect(delay));
+                self.session.wait_for_retry(delay).await
+            }
+            Ok((socket, proxy_addr)) => {
+                tracing::info!("connected to {} via proxy {}", endpoint, proxy_addr);
+                let index = self.endpoints.on_connected();
+                self.run_socket(
+                    socket,
+                    ActiveEndpoint {
+                        index,
+                        addr: proxy_addr,
+                    },
+                )
+                .await
+            }
+        }
+    }
+
+    async fn run_one_direct_connection(&mut self) -> Result<(), StateChange> {
         if let Some(endpoint) = self.endpoints.next_address().await {
             self.listener.update(ClientState::Connecting);
             match TcpStream::connect(endpoint).await {
@@ -155,10 +291,15 @@ impl MasterTask {
                 }
                 Ok(socket) => {
                     tracing::info!("connected to {}", endpoint);
-                    self.endpoints.reset();
-                    self.back_off.on_success();
-                    self.listener.update(ClientState::Connected);
-                    self.run_socket(socket).await
+                    let index = self.endpoints.on_connected();
+                    self.run_socket(
+                        socket,
+                        ActiveEndpoint {
+                            index,
+                            addr: endpoint,
+                        },
+                    )
+                    .await
                 }
             }
         } else {
@@ -173,24 +314,66 @@ impl MasterTask {
         }
     }
 
-    async fn run_socket(&mut self, socket: TcpStream) -> Result<(), StateChange> {
+    async fn run_socket(
+        &mut self,
+        socket: TcpStream,
+        endpoint: ActiveEndpoint,
+    ) -> Result<(), StateChange> {
+        self.listener.update(ClientState::Connected(endpoint));
+        let clock = self.session.clock();
+        let connected = clock.now();
         let mut io = PhysLayer::Tcp(socket);
-        match self
-            .session
-            .run(&mut io, &mut self.writer, &mut self.reader)
-            .await
-        {
-            RunError::State(s) => Err(s),
+        let result = match self.endpoints.hold_time(endpoint.index) {
+            Some(hold_time) => {
+                self.session
+                    .run_until(
+                        &mut io,
+                        &mut self.writer,
+                        &mut self.reader,
+                        connected + hold_time,
+                    )
+                    .await
+            }
+            None => {
+                self.session
+                    .run(&mut io, &mut self.writer, &mut self.reader)
+                    .await
+            }
+        };
+        match result {
+            RunError::State(StateChange::Disconnect) => {
+                tracing::info!("closing connection to fail-over endpoint to try the main endpoint");
+                self.listener
+                    .update(ClientState::Disconnected(DisconnectReason::ReturnToMain));
+                self.endpoints.return_to_main();
+                Ok(())
+            }
+            RunError::State(StateChange::IdleTimeout) => {
+                tracing::info!("closing idle dial-up connection");
+                self.listener
+                    .update(ClientState::Disconnected(DisconnectReason::IdleTimeout));
+                self.back_off.on_success();
+                Ok(())
+            }
+            RunError::State(s) => {
+                if s == StateChange::Disable {
+                    self.listener
+                        .update(ClientState::Disconnected(DisconnectReason::Disabled));
+                }
+                Err(s)
+            }
             RunError::Link(err) => {
                 tracing::warn!("connection lost - {}", err);
-                if self.reconnect_delay > Duration::from_secs(0) {
-                    tracing::warn!(
-                        "waiting {} ms to reconnect",
-                        self.reconnect_delay.as_millis()
-                    );
+                self.listener.update(ClientState::Disconnected(err.into()));
+                let delay = self.back_off.on_disconnect(
+                    clock.now().saturating_duration_since(connected),
+                    self.reconnect_delay,
+                );
+                if delay > Duration::from_secs(0) {
+                    tracing::warn!("waiting {} ms to reconnect", delay.as_millis());
                     self.listener
-                        .update(ClientState::WaitAfterDisconnect(self.reconnect_delay));
-                    self.session.wait_for_retry(self.reconnect_delay).await?;
+                        .update(ClientState::WaitAfterDisconnect(delay));
+                    self.session.wait_for_retry(delay).await?;
                 }
                 Ok(())
             }
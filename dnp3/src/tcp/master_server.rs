@@ -0,0 +1,458 @@
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::Instrument;
+
+use crate::app::{Clock, DisconnectReason, Listener, Shutdown, Spawner};
+use crate::link::header::AnyAddress;
+use crate::link::statistics::LinkCounters;
+use crate::link::{EndpointAddress, LinkErrorMode};
+use crate::master::session::{MasterSession, RunError, StateChange};
+use crate::master::{MasterChannel, MasterChannelConfig};
+use crate::outstation::ConnectionState;
+use crate::tcp::ServerHandle;
+use crate::tokio::io::AsyncReadExt;
+use crate::tokio::net::{TcpListener, TcpStream};
+use crate::transport::{TransportReader, TransportWriter};
+use crate::util::channel::{request_channel, Receiver, Sender};
+use crate::util::phys::PhysLayer;
+
+/// length of a link-layer header, including its CRC
+const HEADER_LENGTH: usize = 10;
+
+/// error type returned when a channel cannot be added to a `MasterTcpServer`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MasterServerError {
+    /// a channel for this outstation address has already been added
+    DuplicateAddress(EndpointAddress),
+}
+
+impl std::error::Error for MasterServerError {}
+
+impl std::fmt::Display for MasterServerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MasterServerError::DuplicateAddress(address) => write!(
+                f,
+                "a channel for outstation address {} already exists",
+                address
+            ),
+        }
+    }
+}
+
+/// socket accepted by the server and routed to a channel
+struct NewConnection {
+    id: u64,
+    addr: SocketAddr,
+    // the link header read to identify the outstation, which the channel must still parse
+    header: [u8; HEADER_LENGTH],
+    phys: PhysLayer,
+}
+
+#[derive(Clone)]
+struct Route {
+    outstation: EndpointAddress,
+    sender: Sender<NewConnection>,
+    /// id of the most recent connection routed to the channel
+    latest: Arc<AtomicU64>,
+    channel: MasterChannel,
+}
+
+/// A builder for a TCP server that accepts connections initiated by outstations, e.g. dial-home
+/// RTUs on cellular networks.
+///
+/// Each accepted socket is routed to the channel of the outstation whose link address is the
+/// source of the first frame received on it. If the outstation is already connected, the existing
+/// connection is closed in favor of the new one once its current task is interrupted.
+pub struct MasterTcpServer {
+    address: std::net::SocketAddr,
+    identification_timeout: Duration,
+    clock: Clock,
+    connection_id: u64,
+    routes: Vec<Route>,
+}
+
+impl MasterTcpServer {
+    /// create a server builder object that will eventually be bound to the specified address
+    ///
+    /// Connections on which a valid link header is not received within `identification_timeout`
+    /// are closed.
+    pub fn new(address: std::net::SocketAddr, identification_timeout: Duration) -> Self {
+        Self {
+            address,
+            identification_timeout,
+            clock: Clock::default(),
+            connection_id: 0,
+            routes: Vec::new(),
+        }
+    }
+
+    /// wait for the identification timeout using this clock instead of Tokio's clock
+    ///
+    /// The channels use the clocks of their own configurations.
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock;
+    }
+
+    /// add a channel for connections from the specified outstation, but do not spawn it
+    pub fn add_channel_no_spawn(
+        &mut self,
+        config: MasterChannelConfig,
+        outstation: EndpointAddress,
+        listener: Box<dyn Listener<ConnectionState>>,
+    ) -> Result<(MasterChannel, impl Future<Output = ()> + 'static), MasterServerError> {
+        if self.routes.iter().any(|x| x.outstation == outstation) {
+            return Err(MasterServerError::DuplicateAddress(outstation));
+        }
+
+        let (tx, rx) = request_channel();
+        let latest = Arc::new(AtomicU64::new(0));
+        let tracing_config = config.tracing;
+        let (mut task, handle) = MasterTask::new(config, rx, latest.clone(), listener);
+
+        self.routes.push(Route {
+            outstation,
+            sender: tx,
+            latest,
+            channel: handle.clone(),
+        });
+
+        let endpoint = self.address;
+        let address = outstation.raw_value();
+        let future = async move {
+            let span = tracing_config.channel_span(
+                tracing::info_span!("DNP3-Master-TCP-Server", "listen" = ?endpoint, "addr" = address),
+            );
+            task.run().instrument(span).await;
+        };
+        Ok((handle, future))
+    }
+
+    /// add a channel for connections from the specified outstation and spawn it
+    ///
+    /// Must be called from within the Tokio runtime
+    pub fn add_channel(
+        &mut self,
+        config: MasterChannelConfig,
+        outstation: EndpointAddress,
+        listener: Box<dyn Listener<ConnectionState>>,
+    ) -> Result<MasterChannel, MasterServerError> {
+        let spawner = config.spawner.clone();
+        let (handle, future) = self.add_channel_no_spawn(config, outstation, listener)?;
+        Spawner::spawn(spawner.as_deref(), future);
+        Ok(handle)
+    }
+
+    /// Consume the builder object, bind it to pre-specified port, and return a (ServerHandle, Future)
+    /// tuple.
+    ///
+    /// This may be called outside the Tokio runtime and allows for manual spawning
+    pub async fn bind_no_spawn(
+        mut self,
+    ) -> Result<(ServerHandle, impl Future<Output = Shutdown>), crate::tokio::io::Error> {
+        let listener = TcpListener::bind(self.address).await?;
+
+        let (tx, rx) = crate::tokio::sync::oneshot::channel();
+
+        let task = async move {
+            let local = self.address;
+            self.run(listener, rx)
+                .instrument(tracing::info_span!("MasterTCPServer", "listen" = ?local))
+                .await
+        };
+
+        Ok((ServerHandle::new(tx), task))
+    }
+
+    /// Consume the builder object, bind it to pre-specified port, and spawn the server
+    /// task onto the Tokio runtime. Returns a ServerHandle that will shut down the server
+    /// when dropped. Channels run until their own handles are dropped.
+    ///
+    /// This must be called from within the Tokio runtime
+    pub async fn bind(self) -> Result<ServerHandle, crate::tokio::io::Error> {
+        let (handle, future) = self.bind_no_spawn().await?;
+        crate::tokio::spawn(future);
+        Ok(handle)
+    }
+
+    async fn run(
+        &mut self,
+        listener: TcpListener,
+        rx: crate::tokio::sync::oneshot::Receiver<()>,
+    ) -> Shutdown {
+        tracing::info!("accepting connections");
+
+        crate::tokio::select! {
+             _ = self.accept_loop(listener) => {
+                // if the accept loop shuts down we exit
+             }
+             _ = rx => {
+                // if we get the message or shutdown we exit
+             }
+        }
+
+        tracing::info!("shutdown");
+
+        Shutdown
+    }
+
+    async fn accept_loop(&mut self, listener: TcpListener) -> Result<(), Shutdown> {
+        loop {
+            match listener.accept().await {
+                Ok((stream, addr)) => {
+                    self.connection_id = self.connection_id.wrapping_add(1);
+                    let id = self.connection_id;
+                    tracing::info!("accepted connection {} from: {}", id, addr);
+                    let routes = self.routes.clone();
+                    let timeout = self.identification_timeout;
+                    crate::tokio::spawn(
+                        route(id, addr, stream, timeout, self.clock.clone(), routes)
+                            .instrument(tracing::info_span!("Connection", "id" = id)),
+                    );
+                }
+                Err(err) => {
+                    tracing::error!("{}", err);
+                    return Err(Shutdown);
+                }
+            }
+        }
+    }
+}
+
+/// identify the outstation on the other end of an accepted socket and hand it to its channel
+async fn route(
+    id: u64,
+    addr: SocketAddr,
+    mut stream: TcpStream,
+    timeout: Duration,
+    clock: Clock,
+    routes: Vec<Route>,
+) {
+    let mut header = [0; HEADER_LENGTH];
+    let deadline = clock.now() + timeout;
+    crate::tokio::select! {
+        res = stream.read_exact(&mut header) => {
+            if let Err(err) = res {
+                tracing::warn!("error identifying the outstation: {}", err);
+                return;
+            }
+        }
+        _ = clock.sleep_until(deadline) => {
+            tracing::warn!("no link header received before the identification timeout");
+            return;
+        }
+    }
+
+    let source = match source_address(&header) {
+        Some(source) => source,
+        None => {
+            tracing::warn!("first frame does not have a valid link header");
+            return;
+        }
+    };
+
+    let mut route = match routes.into_iter().find(|x| x.outstation == source) {
+        Some(route) => route,
+        None => {
+            tracing::warn!("no channel for outstation address: {}", source);
+            return;
+        }
+    };
+
+    tracing::info!("routing connection to outstation: {}", source);
+    let previous = route.latest.swap(id, Ordering::SeqCst);
+    let connection = NewConnection {
+        id,
+        addr,
+        header,
+        phys: PhysLayer::Tcp(stream),
+    };
+    if route.sender.send(connection).await.is_err() {
+        return;
+    }
+    if previous != 0 {
+        // close any previous connection to the same outstation
+        let _ = route.channel.replace_io().await;
+    }
+}
+
+/// source address of a link header, if the header is valid and the source is an endpoint
+fn source_address(header: &[u8; HEADER_LENGTH]) -> Option<EndpointAddress> {
+    if header[0..2] != [0x05, 0x64] {
+        return None;
+    }
+    let crc = u16::from_le_bytes([header[8], header[9]]);
+    if crc != crate::link::crc::calc_crc_with_0564(&header[2..8]) {
+        return None;
+    }
+    match AnyAddress::from(u16::from_le_bytes([header[6], header[7]])) {
+        AnyAddress::Endpoint(address) => Some(address),
+        _ => None,
+    }
+}
+
+struct MasterTask {
+    session: MasterSession,
+    reader: TransportReader,
+    writer: TransportWriter,
+    connections: Receiver<NewConnection>,
+    latest: Arc<AtomicU64>,
+    listener: Box<dyn Listener<ConnectionState>>,
+    counters: Arc<LinkCounters>,
+}
+
+impl MasterTask {
+    fn new(
+        config: MasterChannelConfig,
+        connections: Receiver<NewConnection>,
+        latest: Arc<AtomicU64>,
+        listener: Box<dyn Listener<ConnectionState>>,
+    ) -> (Self, MasterChannel) {
+        let (tx, rx) = request_channel();
+        let link_counters = Arc::new(LinkCounters::new(
+            config.clock,
+            config.tracing,
+            config.buffer_pool,
+        ));
+        let session = MasterSession::new(
+            false,
+            config.decode_level,
+            config.response_timeout,
+            config.scheduling,
+            config.link,
+            link_counters.clone(),
+            config.tracing,
+            config.tx_buffer_size,
+            config.rx_buffer_size,
+            rx,
+        );
+        let (reader, writer) = crate::transport::create_master_transport_layer(
+            LinkErrorMode::Close,
+            config.master_address,
+            config.rx_buffer_size,
+            config.link,
+            config.response_timeout.into(),
+            config.transport,
+            config.unknown_destination,
+            link_counters.clone(),
+        );
+        let task = Self {
+            session,
+            reader,
+            writer,
+            connections,
+            latest,
+            listener,
+            counters: link_counters.clone(),
+        };
+        (task, MasterChannel::new(tx, link_counters))
+    }
+
+    async fn run(&mut self) {
+        let _ = self.run_impl().await;
+        self.session.shutdown().await;
+    }
+
+    async fn run_impl(&mut self) -> Result<(), Shutdown> {
+        loop {
+            self.session.wait_for_enabled().await?;
+            if let Err(StateChange::Shutdown) = self.run_enabled().await {
+                return Err(Shutdown);
+            }
+        }
+    }
+
+    async fn run_enabled(&mut self) -> Result<(), StateChange> {
+        loop {
+            let connection = self.wait_for_connection().await?;
+            self.run_connection(connection).await?;
+        }
+    }
+
+    async fn wait_for_connection(&mut self) -> Result<NewConnection, StateChange> {
+        loop {
+            crate::tokio::select! {
+                connection = self.connections.receive() => {
+                    let connection = connection?;
+                    if connection.id == self.latest.load(Ordering::SeqCst) {
+                        return Ok(connection);
+                    }
+                    // a newer connection is already queued
+                    tracing::warn!("discarding superseded connection {}", connection.id);
+                }
+                err = self.session.process_messages() => {
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    async fn run_connection(&mut self, mut connection: NewConnection) -> Result<(), StateChange> {
+        let id = connection.id;
+        self.listener
+            .update(ConnectionState::Connected(connection.addr));
+        // the link reader parses the header consumed while identifying the outstation first
+        self.counters.replace_unread(&connection.header);
+        let result = loop {
+            let result = self
+                .session
+                .run(&mut connection.phys, &mut self.writer, &mut self.reader)
+                .instrument(tracing::info_span!("Session", "id" = id))
+                .await;
+
+            match result {
+                RunError::State(StateChange::ReplaceIo) => {
+                    if self.latest.load(Ordering::SeqCst) == id {
+                        // the request was meant for the connection this one replaced
+                        continue;
+                    }
+                    tracing::warn!("closing connection {} for a newer connection", id);
+                    break (Ok(()), DisconnectReason::Replaced);
+                }
+                RunError::State(StateChange::Disable) => {
+                    break (Err(StateChange::Disable), DisconnectReason::Disabled)
+                }
+                RunError::State(x) => break (Err(x), DisconnectReason::Shutdown),
+                RunError::Link(err) => {
+                    tracing::warn!("connection {} lost - {}", id, err);
+                    break (Ok(()), err.into());
+                }
+            }
+        };
+        let (result, reason) = result;
+        self.listener.update(ConnectionState::Disconnected(reason));
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::link::test_data::*;
+
+    fn header(bytes: &[u8]) -> [u8; HEADER_LENGTH] {
+        let mut header = [0; HEADER_LENGTH];
+        header.copy_from_slice(&bytes[0..HEADER_LENGTH]);
+        header
+    }
+
+    #[test]
+    fn identifies_source_of_valid_header() {
+        let expected = match RESET_LINK.header.source {
+            AnyAddress::Endpoint(x) => x,
+            _ => unreachable!(),
+        };
+        assert_eq!(source_address(&header(RESET_LINK.bytes)), Some(expected));
+    }
+
+    #[test]
+    fn rejects_header_with_bad_crc() {
+        let mut bytes = header(RESET_LINK.bytes);
+        bytes[6] ^= 0x01;
+        assert_eq!(source_address(&bytes), None);
+    }
+}
@@ -1,12 +1,36 @@
 pub use address_filter::*;
 pub use endpoint_list::*;
+pub use limits::*;
 pub use master::*;
+pub use master_server::*;
 pub use outstation::*;
+pub use outstation_client::*;
+pub use proxy::*;
 
 mod address_filter;
 mod endpoint_list;
+mod limits;
 mod master;
+mod master_server;
 mod outstation;
+mod outstation_client;
+mod proxy;
+
+/// Endpoint of an `EndpointList` that a client is connected to
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ActiveEndpoint {
+    /// position of the endpoint in the list, the main endpoint being 0
+    pub index: usize,
+    /// socket address of the remote device, or of the proxy when connected through one
+    pub addr: std::net::SocketAddr,
+}
+
+impl ActiveEndpoint {
+    /// true if the client is connected to the main endpoint
+    pub fn is_main(&self) -> bool {
+        self.index == 0
+    }
+}
 
 /// state of TCP client connection
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -15,8 +39,10 @@ pub enum ClientState {
     Disabled,
     /// client attempting to establish a connection
     Connecting,
-    /// client is connected
-    Connected,
+    /// client is connected to the endpoint
+    Connected(ActiveEndpoint),
+    /// client was disconnected for the specified reason
+    Disconnected(crate::app::DisconnectReason),
     /// client is waiting to retry after a failed attempt to connect
     WaitAfterFailedConnect(std::time::Duration),
     /// client is waiting to retry after a disconnection
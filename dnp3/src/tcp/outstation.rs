@@ -1,12 +1,18 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
 use tracing::Instrument;
 
-use crate::app::{Listener, Shutdown};
+use crate::app::{Listener, NullListener, Shutdown, Spawner};
 use crate::link::LinkErrorMode;
 use crate::outstation::database::EventBufferConfig;
 use crate::outstation::task::OutstationTask;
 use crate::outstation::OutstationHandle;
 use crate::outstation::*;
-use crate::tcp::{AddressFilter, FilterError};
+use crate::tcp::{
+    AddressFilter, ExistingConnectionPolicy, FilterError, RateLimiter, RejectReason, ServerEvent,
+    ServerLimits,
+};
 use crate::util::channel::Sender;
 
 use crate::outstation::adapter::{NewSession, OutstationTaskAdapter};
@@ -16,6 +22,8 @@ struct OutstationInfo {
     handle: OutstationHandle,
     /// how we notify the outstation adapter task to switch to new socket
     sender: Sender<NewSession>,
+    /// set by the adapter task while a session is running
+    connected: Arc<AtomicBool>,
 }
 
 /// A builder for creating a TCP server with one or more outstation instances
@@ -25,6 +33,9 @@ pub struct TcpServer {
     connection_id: u64,
     address: std::net::SocketAddr,
     outstations: Vec<OutstationInfo>,
+    limits: ServerLimits,
+    rate_limiter: RateLimiter,
+    listener: Box<dyn Listener<ServerEvent>>,
 }
 
 /// Handle to a running server. Dropping the handle, shuts down the server.
@@ -32,6 +43,12 @@ pub struct ServerHandle {
     _tx: crate::tokio::sync::oneshot::Sender<()>,
 }
 
+impl ServerHandle {
+    pub(crate) fn new(tx: crate::tokio::sync::oneshot::Sender<()>) -> Self {
+        Self { _tx: tx }
+    }
+}
+
 impl TcpServer {
     /// create a TCP server builder object that will eventually be bound
     /// to the specified address
@@ -41,9 +58,19 @@ impl TcpServer {
             connection_id: 0,
             address,
             outstations: Vec::new(),
+            limits: ServerLimits::default(),
+            rate_limiter: RateLimiter::new(None),
+            listener: NullListener::create(),
         }
     }
 
+    /// limit the connections accepted by the server and report each decision to the listener
+    pub fn set_limits(&mut self, limits: ServerLimits, listener: Box<dyn Listener<ServerEvent>>) {
+        self.limits = limits;
+        self.rate_limiter = RateLimiter::new(limits.rate_limit);
+        self.listener = listener;
+    }
+
     /// associate an outstation with the TcpServer, but do not spawn it
     #[allow(clippy::too_many_arguments)]
     pub fn add_outstation_no_spawn(
@@ -62,6 +89,8 @@ impl TcpServer {
             }
         }
 
+        let address = config.outstation_address.raw_value();
+        let tracing_config = config.tracing;
         let (task, handle) = OutstationTask::create(
             self.link_error_mode,
             config,
@@ -71,23 +100,23 @@ impl TcpServer {
             control_handler,
         );
 
-        let (mut adapter, tx) = OutstationTaskAdapter::create(task, listener);
+        let connected = Arc::new(AtomicBool::new(false));
+        let (mut adapter, tx) = OutstationTaskAdapter::create(task, listener, connected.clone());
 
         let outstation = OutstationInfo {
             filter,
             handle: handle.clone(),
             sender: tx,
+            connected,
         };
         self.outstations.push(outstation);
 
         let endpoint = self.address;
-        let address = config.outstation_address.raw_value();
         let future = async move {
-            let _ = adapter.run()
-                .instrument(
-                    tracing::info_span!("DNP3-Outstation-TCP", "listen" = ?endpoint, "addr" = address),
-                )
-                .await;
+            let span = tracing_config.channel_span(
+                tracing::info_span!("DNP3-Outstation-TCP", "listen" = ?endpoint, "addr" = address),
+            );
+            let _ = adapter.run().instrument(span).await;
         };
         Ok((handle, future))
     }
@@ -106,6 +135,7 @@ impl TcpServer {
         listener: Box<dyn Listener<ConnectionState>>,
         filter: AddressFilter,
     ) -> Result<OutstationHandle, FilterError> {
+        let spawner = config.spawner.clone();
         let (handle, future) = self.add_outstation_no_spawn(
             config,
             event_config,
@@ -115,7 +145,7 @@ impl TcpServer {
             listener,
             filter,
         )?;
-        crate::tokio::spawn(future);
+        Spawner::spawn(spawner.as_deref(), future);
         Ok(handle)
     }
 
@@ -138,7 +168,7 @@ impl TcpServer {
                 .await
         };
 
-        let handle = ServerHandle { _tx: tx };
+        let handle = ServerHandle::new(tx);
 
         Ok((handle, task))
     }
@@ -174,7 +204,7 @@ impl TcpServer {
 
         for x in self.outstations.iter_mut() {
             // best effort to shutdown outstations before exiting
-            let _ = x.handle.shutdown().await;
+            let _ = x.handle.stop().await;
         }
 
         tracing::info!("shutdown");
@@ -217,24 +247,63 @@ impl TcpServer {
 
         tracing::info!("accepted connection {} from: {}", id, addr);
 
-        let first_match = self
-            .outstations
-            .iter_mut()
-            .find(|x| x.filter.matches(addr.ip()));
-
-        match first_match {
-            None => {
-                tracing::warn!("no matching outstation for: {}", addr)
+        let event = match self.select_outstation(addr) {
+            Err(reason) => {
+                tracing::warn!("rejected connection {} from {}: {:?}", id, addr, reason);
+                ServerEvent::Rejected(addr, reason)
             }
-            Some(x) => {
+            Ok(index) => {
+                let x = &mut self.outstations[index];
+                let event = if x.connected.swap(true, Ordering::Relaxed) {
+                    ServerEvent::Replaced(addr)
+                } else {
+                    ServerEvent::Accepted(addr)
+                };
                 let _ = x
                     .sender
                     .send(NewSession::new(
                         id,
+                        addr,
                         crate::util::phys::PhysLayer::Tcp(stream),
                     ))
                     .await;
+                event
             }
+        };
+
+        self.listener.update(event);
+    }
+
+    /// apply the limits to a connection, returning the index of the outstation that receives it
+    fn select_outstation(&mut self, addr: std::net::SocketAddr) -> Result<usize, RejectReason> {
+        if !self.rate_limiter.check(addr.ip()) {
+            return Err(RejectReason::RateLimited);
         }
+
+        let index = self
+            .outstations
+            .iter()
+            .position(|x| x.filter.matches(addr.ip()))
+            .ok_or(RejectReason::NoMatchingOutstation)?;
+
+        if self.outstations[index].connected.load(Ordering::Relaxed) {
+            return match self.limits.existing_connection {
+                ExistingConnectionPolicy::Replace => Ok(index),
+                ExistingConnectionPolicy::Reject => Err(RejectReason::AlreadyConnected),
+            };
+        }
+
+        if let Some(max) = self.limits.max_connections {
+            let count = self
+                .outstations
+                .iter()
+                .filter(|x| x.connected.load(Ordering::Relaxed))
+                .count();
+            if count >= max {
+                return Err(RejectReason::TooManyConnections);
+            }
+        }
+
+        Ok(index)
     }
 }
@@ -0,0 +1,277 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tracing::Instrument;
+
+use crate::app::{
+    Clock, ConnectStrategy, DisconnectReason, ExponentialBackOff, Instant, Listener, Shutdown,
+    Spawner,
+};
+use crate::link::LinkErrorMode;
+use crate::master::EventClasses;
+use crate::outstation::database::EventBufferConfig;
+use crate::outstation::session::RunError;
+use crate::outstation::task::OutstationTask;
+use crate::outstation::{
+    ControlHandler, OutstationApplication, OutstationConfig, OutstationHandle,
+    OutstationInformation,
+};
+use crate::tcp::{ActiveEndpoint, ClientState, EndpointList};
+use crate::tokio::net::TcpStream;
+use crate::util::phys::PhysLayer;
+
+/// Determines when an outstation TCP client connects to the master, instead of maintaining a
+/// connection at all times
+///
+/// This is the usual pattern of battery-powered RTUs on cellular links: the outstation connects
+/// when it has events to report, sends them as unsolicited responses, and disconnects once the
+/// master stops sending requests. The first connection is established at startup so that the
+/// master learns of the restart of the outstation.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConnectTrigger {
+    /// classes of the events that establish a connection
+    pub(crate) classes: EventClasses,
+    /// period after the start of the last connection at which a connection is established
+    /// even without events
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) heartbeat: Option<Duration>,
+    /// time without solicited or unsolicited responses after which the connection is closed
+    pub(crate) idle_timeout: Duration,
+}
+
+impl ConnectTrigger {
+    /// construct a `ConnectTrigger`
+    ///
+    /// `classes` - Classes of the events that establish a connection when they are waiting to be
+    /// reported
+    /// `idle_timeout` - Time without solicited or unsolicited responses after which the
+    /// connection is closed
+    pub fn new(classes: EventClasses, idle_timeout: Duration) -> Self {
+        Self {
+            classes,
+            heartbeat: None,
+            idle_timeout,
+        }
+    }
+
+    /// also establish a connection once `period` has elapsed since the start of the last one,
+    /// whether events are waiting or not, e.g. so that the master can poll the outstation
+    pub fn with_heartbeat(self, period: Duration) -> Self {
+        Self {
+            heartbeat: Some(period),
+            ..self
+        }
+    }
+}
+
+/// Spawn an outstation task onto the `Tokio` runtime which connects to a master listening on one
+/// of the endpoints. The task runs until the returned handle is dropped.
+///
+/// Without a `trigger`, the connection is maintained at all times. The outstation state is reset
+/// in between each connection, but events stay buffered until a master confirms them. The hold
+/// time of the fail-over endpoints is not used.
+///
+/// **Note**: This function may only be called from within the runtime itself, and panics otherwise.
+/// It is preferable to use this method instead of `create_outstation_tcp_client(..)` when using `[tokio::main]`.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_outstation_tcp_client(
+    link_error_mode: LinkErrorMode,
+    endpoints: EndpointList,
+    connect_strategy: ConnectStrategy,
+    trigger: Option<ConnectTrigger>,
+    listener: Box<dyn Listener<ClientState>>,
+    config: OutstationConfig,
+    event_config: EventBufferConfig,
+    application: Box<dyn OutstationApplication>,
+    information: Box<dyn OutstationInformation>,
+    control_handler: Box<dyn ControlHandler>,
+) -> OutstationHandle {
+    let spawner = config.spawner.clone();
+    let (future, handle) = create_outstation_tcp_client(
+        link_error_mode,
+        endpoints,
+        connect_strategy,
+        trigger,
+        listener,
+        config,
+        event_config,
+        application,
+        information,
+        control_handler,
+    );
+    Spawner::spawn(spawner.as_deref(), future);
+    handle
+}
+
+/// Create an outstation future which connects to a master listening on one of the endpoints, which
+/// can be spawned onto a runtime, along with a controlling handle.
+///
+/// Once spawned or otherwise executed using the `run` method, the task runs until the handle
+/// is dropped.
+///
+/// **Note**: This function is required instead of `spawn` when using a runtime to directly spawn
+/// tasks instead of within the context of a runtime, e.g. in applications that cannot use
+/// `[tokio::main]` such as C language bindings.
+#[allow(clippy::too_many_arguments)]
+pub fn create_outstation_tcp_client(
+    link_error_mode: LinkErrorMode,
+    endpoints: EndpointList,
+    connect_strategy: ConnectStrategy,
+    trigger: Option<ConnectTrigger>,
+    listener: Box<dyn Listener<ClientState>>,
+    config: OutstationConfig,
+    event_config: EventBufferConfig,
+    application: Box<dyn OutstationApplication>,
+    information: Box<dyn OutstationInformation>,
+    control_handler: Box<dyn ControlHandler>,
+) -> (impl Future<Output = ()> + 'static, OutstationHandle) {
+    let clock = config.clock.clone();
+    let tracing_config = config.tracing;
+    let (mut task, handle) = OutstationTask::create(
+        link_error_mode,
+        config,
+        event_config,
+        application,
+        information,
+        control_handler,
+    );
+    task.set_idle_timeout(trigger.map(|x| x.idle_timeout));
+
+    let main_addr = endpoints.main_addr().to_string();
+    let mut task = OutstationClientTask {
+        endpoints,
+        back_off: ExponentialBackOff::new(connect_strategy.retry),
+        reconnect_delay: connect_strategy.reconnect_delay,
+        trigger,
+        last_connected: None,
+        clock,
+        listener,
+        task,
+    };
+
+    let future = async move {
+        let span = tracing_config
+            .channel_span(tracing::info_span!("DNP3-Outstation-TCP", "endpoint" = ?main_addr));
+        task.run().instrument(span).await;
+    };
+    (future, handle)
+}
+
+struct OutstationClientTask {
+    endpoints: EndpointList,
+    back_off: ExponentialBackOff,
+    reconnect_delay: Duration,
+    trigger: Option<ConnectTrigger>,
+    last_connected: Option<Instant>,
+    clock: Clock,
+    listener: Box<dyn Listener<ClientState>>,
+    task: OutstationTask,
+}
+
+impl OutstationClientTask {
+    async fn run(&mut self) {
+        let _ = self.run_impl().await;
+        self.listener.update(ClientState::Shutdown);
+    }
+
+    async fn run_impl(&mut self) -> Result<(), Shutdown> {
+        loop {
+            if let (Some(trigger), Some(connected)) = (self.trigger, self.last_connected) {
+                let heartbeat = trigger.heartbeat.map(|x| connected + x);
+                self.task
+                    .wait_for_events(trigger.classes, heartbeat)
+                    .await?;
+            }
+            self.run_one_connection().await?;
+        }
+    }
+
+    async fn run_one_connection(&mut self) -> Result<(), Shutdown> {
+        let endpoint = match self.endpoints.next_address().await {
+            Some(x) => x,
+            None => {
+                let delay = self.back_off.on_failure();
+                tracing::warn!(
+                    "Name resolution failure - waiting {} ms to retry",
+                    delay.as_millis()
+                );
+                self.listener
+                    .update(ClientState::WaitAfterFailedConnect(delay));
+                return self.wait_for_retry(delay).await;
+            }
+        };
+
+        self.listener.update(ClientState::Connecting);
+        let socket = match TcpStream::connect(endpoint).await {
+            Ok(x) => x,
+            Err(err) => {
+                let delay = self.back_off.on_failure();
+                tracing::warn!(
+                    "failed to connect to {}: {} - waiting {} ms to retry",
+                    endpoint,
+                    err,
+                    delay.as_millis()
+                );
+                self.listener
+                    .update(ClientState::WaitAfterFailedConnect(delay));
+                return self.wait_for_retry(delay).await;
+            }
+        };
+
+        tracing::info!("connected to {}", endpoint);
+        let index = self.endpoints.on_connected();
+        self.listener.update(ClientState::Connected(ActiveEndpoint {
+            index,
+            addr: endpoint,
+        }));
+        let connected = self.clock.now();
+        self.last_connected = Some(connected);
+
+        let mut io = PhysLayer::Tcp(socket);
+        let result = self.task.run(&mut io).await;
+
+        // reset outstation state in between connections
+        self.task.reset();
+
+        match result {
+            RunError::Shutdown => Err(Shutdown),
+            RunError::IdleTimeout => {
+                tracing::info!("closing idle connection");
+                self.listener
+                    .update(ClientState::Disconnected(DisconnectReason::IdleTimeout));
+                self.back_off.on_success();
+                Ok(())
+            }
+            RunError::Link(err) => {
+                tracing::warn!("connection lost - {}", err);
+                self.listener.update(ClientState::Disconnected(err.into()));
+                let delay = self.back_off.on_disconnect(
+                    self.clock.now().saturating_duration_since(connected),
+                    self.reconnect_delay,
+                );
+                if delay > Duration::from_secs(0) {
+                    tracing::warn!("waiting {} ms to reconnect", delay.as_millis());
+                    self.listener
+                        .update(ClientState::WaitAfterDisconnect(delay));
+                    self.wait_for_retry(delay).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// process messages from the handle until the retry delay elapses
+    async fn wait_for_retry(&mut self, delay: Duration) -> Result<(), Shutdown> {
+        let clock = self.clock.clone();
+        let deadline = clock.now() + delay;
+        crate::tokio::select! {
+            ret = self.task.process_messages() => {
+                ret
+            }
+            _ = clock.sleep_until(deadline) => {
+                Ok(())
+            }
+        }
+    }
+}
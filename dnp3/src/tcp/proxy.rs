@@ -0,0 +1,336 @@
+use std::io::{Error, ErrorKind};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crate::app::Clock;
+use crate::tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use crate::tokio::net::TcpStream;
+
+const VERSION: u8 = 0x05;
+const AUTH_VERSION: u8 = 0x01;
+const METHOD_NONE: u8 = 0x00;
+const METHOD_USERNAME_PASSWORD: u8 = 0x02;
+const METHOD_NOT_ACCEPTABLE: u8 = 0xFF;
+const COMMAND_CONNECT: u8 = 0x01;
+const ADDRESS_IPV4: u8 = 0x01;
+const ADDRESS_DOMAIN: u8 = 0x03;
+const ADDRESS_IPV6: u8 = 0x04;
+
+/// SOCKS5 proxy through which TCP client connections are established
+///
+/// When a proxy is used, the names in the `EndpointList` are not resolved locally. They are
+/// passed to the proxy, which resolves them and connects on behalf of the client.
+#[derive(Clone, Debug)]
+pub struct Socks5Proxy {
+    address: String,
+    credentials: Option<(String, String)>,
+    timeout: Duration,
+}
+
+impl Socks5Proxy {
+    /// Default time allowed to connect to each address of the proxy and complete the handshake
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Create a proxy configuration without authentication
+    ///
+    /// The address is a name or IP address and port, e.g. `"proxy.myorg.com:1080"`
+    pub fn new(address: String) -> Self {
+        Self {
+            address,
+            credentials: None,
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Authenticate to the proxy with a username and password (RFC 1929)
+    ///
+    /// Each value must be between 1 and 255 bytes long, otherwise every connection attempt fails.
+    pub fn with_credentials(self, username: String, password: String) -> Self {
+        Self {
+            credentials: Some((username, password)),
+            ..self
+        }
+    }
+
+    /// Time allowed to connect to each address of the proxy and complete the handshake, after
+    /// which the next address is tried
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Returns the address of the proxy
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// connect to the proxy and ask it to connect to the target
+    ///
+    /// returns the stream along with the socket address of the proxy
+    pub(crate) async fn connect(
+        &self,
+        target: &str,
+        clock: &Clock,
+    ) -> std::io::Result<(TcpStream, SocketAddr)> {
+        let mut last_error = Error::new(
+            ErrorKind::NotFound,
+            format!("unable to resolve \"{}\"", self.address),
+        );
+        for address in crate::tokio::net::lookup_host(&self.address).await? {
+            let deadline = clock.now() + self.timeout;
+            let result = crate::tokio::select! {
+                result = self.connect_to(address, target) => result,
+                _ = clock.sleep_until(deadline) => Err(Error::new(
+                    ErrorKind::TimedOut,
+                    "timeout connecting through the proxy",
+                )),
+            };
+            match result {
+                Ok(stream) => return Ok((stream, address)),
+                Err(err) => {
+                    tracing::warn!("unable to connect through proxy {}: {}", address, err);
+                    last_error = err;
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    async fn connect_to(&self, address: SocketAddr, target: &str) -> std::io::Result<TcpStream> {
+        let mut stream = TcpStream::connect(address).await?;
+        handshake(&mut stream, target, self.credentials.as_ref()).await?;
+        Ok(stream)
+    }
+}
+
+fn invalid_input(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, message.to_string())
+}
+
+fn protocol_error(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// encode the destination of a CONNECT request as an address type, address, and port
+fn encode_target(target: &str) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    match target.parse::<SocketAddr>() {
+        Ok(SocketAddr::V4(address)) => {
+            bytes.push(ADDRESS_IPV4);
+            bytes.extend_from_slice(&address.ip().octets());
+            bytes.extend_from_slice(&address.port().to_be_bytes());
+        }
+        Ok(SocketAddr::V6(address)) => {
+            bytes.push(ADDRESS_IPV6);
+            bytes.extend_from_slice(&address.ip().octets());
+            bytes.extend_from_slice(&address.port().to_be_bytes());
+        }
+        Err(_) => {
+            let (host, port) = target
+                .rsplit_once(':')
+                .ok_or_else(|| invalid_input("endpoint is missing a port"))?;
+            let port: u16 = port
+                .parse()
+                .map_err(|_| invalid_input("endpoint has an invalid port"))?;
+            if host.is_empty() || host.len() > 255 {
+                return Err(invalid_input("endpoint name must be 1 to 255 bytes long"));
+            }
+            bytes.push(ADDRESS_DOMAIN);
+            bytes.push(host.len() as u8);
+            bytes.extend_from_slice(host.as_bytes());
+            bytes.extend_from_slice(&port.to_be_bytes());
+        }
+    }
+    Ok(bytes)
+}
+
+fn reply_message(code: u8) -> &'static str {
+    match code {
+        0x01 => "general SOCKS server failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+async fn authenticate<S>(stream: &mut S, username: &str, password: &str) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    if username.is_empty() || username.len() > 255 || password.is_empty() || password.len() > 255 {
+        return Err(invalid_input(
+            "proxy username and password must be 1 to 255 bytes long",
+        ));
+    }
+
+    let mut request = vec![AUTH_VERSION, username.len() as u8];
+    request.extend_from_slice(username.as_bytes());
+    request.push(password.len() as u8);
+    request.extend_from_slice(password.as_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply).await?;
+    if reply[1] != 0 {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            "proxy rejected the username and password",
+        ));
+    }
+    Ok(())
+}
+
+/// perform the SOCKS5 handshake asking the proxy to connect to the target
+async fn handshake<S>(
+    stream: &mut S,
+    target: &str,
+    credentials: Option<&(String, String)>,
+) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let target = encode_target(target)?;
+
+    // method negotiation
+    match credentials {
+        Some(_) => {
+            stream
+                .write_all(&[VERSION, 2, METHOD_NONE, METHOD_USERNAME_PASSWORD])
+                .await?
+        }
+        None => stream.write_all(&[VERSION, 1, METHOD_NONE]).await?,
+    }
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != VERSION {
+        return Err(protocol_error(format!(
+            "unsupported proxy version: {}",
+            reply[0]
+        )));
+    }
+    match (reply[1], credentials) {
+        (METHOD_NONE, _) => {}
+        (METHOD_USERNAME_PASSWORD, Some((username, password))) => {
+            authenticate(stream, username, password).await?
+        }
+        (METHOD_NOT_ACCEPTABLE, _) => {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "proxy requires an unsupported authentication method",
+            ))
+        }
+        (method, _) => {
+            return Err(protocol_error(format!(
+                "proxy selected an unexpected authentication method: {}",
+                method
+            )))
+        }
+    }
+
+    // connect request
+    let mut request = vec![VERSION, COMMAND_CONNECT, 0x00];
+    request.extend_from_slice(&target);
+    stream.write_all(&request).await?;
+
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[1] != 0 {
+        return Err(Error::new(
+            ErrorKind::ConnectionRefused,
+            format!("proxy failed to connect: {}", reply_message(reply[1])),
+        ));
+    }
+
+    // discard the bound address and port
+    let length = match reply[3] {
+        ADDRESS_IPV4 => 4,
+        ADDRESS_IPV6 => 16,
+        ADDRESS_DOMAIN => stream.read_u8().await? as usize,
+        x => {
+            return Err(protocol_error(format!(
+                "proxy replied with an unknown address type: {}",
+                x
+            )))
+        }
+    };
+    let mut bound = [0; 257];
+    stream.read_exact(&mut bound[..length + 2]).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tokio::test::*;
+
+    #[test]
+    fn encodes_ip_and_domain_targets() {
+        assert_eq!(
+            encode_target("127.0.0.1:20000").unwrap(),
+            vec![ADDRESS_IPV4, 127, 0, 0, 1, 0x4E, 0x20]
+        );
+        assert_eq!(
+            encode_target("rtu:20000").unwrap(),
+            vec![ADDRESS_DOMAIN, 3, b'r', b't', b'u', 0x4E, 0x20]
+        );
+        assert!(encode_target("rtu").is_err());
+        assert!(encode_target("rtu:dnp3").is_err());
+    }
+
+    #[test]
+    fn connects_through_proxy_with_credentials() {
+        let (mut io, mut handle) = io::mock();
+        let credentials = ("user".to_string(), "pw".to_string());
+
+        handle.write(&[VERSION, 2, METHOD_NONE, METHOD_USERNAME_PASSWORD]);
+        handle.read(&[VERSION, METHOD_USERNAME_PASSWORD]);
+        handle.write(&[AUTH_VERSION, 4, b'u', b's', b'e', b'r', 2, b'p', b'w']);
+        handle.read(&[AUTH_VERSION, 0]);
+        handle.write(&[
+            VERSION,
+            COMMAND_CONNECT,
+            0,
+            ADDRESS_DOMAIN,
+            3,
+            b'r',
+            b't',
+            b'u',
+            0x4E,
+            0x20,
+        ]);
+        handle.read(&[VERSION, 0, 0, ADDRESS_IPV4, 10, 0, 0, 1, 0x04, 0x38]);
+
+        let mut task = spawn(handshake(&mut io, "rtu:20000", Some(&credentials)));
+        assert_ready!(task.poll()).unwrap();
+    }
+
+    #[test]
+    fn fails_when_proxy_refuses_connection() {
+        let (mut io, mut handle) = io::mock();
+
+        handle.write(&[VERSION, 1, METHOD_NONE]);
+        handle.read(&[VERSION, METHOD_NONE]);
+        handle.write(&[
+            VERSION,
+            COMMAND_CONNECT,
+            0,
+            ADDRESS_IPV4,
+            127,
+            0,
+            0,
+            1,
+            0x4E,
+            0x20,
+        ]);
+        handle.read(&[VERSION, 0x05, 0, ADDRESS_IPV4, 0, 0, 0, 0, 0, 0]);
+
+        let mut task = spawn(handshake(&mut io, "127.0.0.1:20000", None));
+        let err = assert_ready!(task.poll()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+    }
+}
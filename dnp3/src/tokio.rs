@@ -1,5 +1,5 @@
 // When testing, we replace all the tokio components with mocks
+#[cfg(not(test))]
+pub(crate) use ::tokio::*;
 #[cfg(test)]
 pub(crate) use tokio_mock::mock::*;
-#[cfg(not(test))]
-pub(crate) use tokio_mock::real::*;
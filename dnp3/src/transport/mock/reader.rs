@@ -1,7 +1,14 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::app::Clock;
 use crate::decode::DecodeLevel;
 use crate::link::error::LinkError;
 use crate::link::header::FrameInfo;
-use crate::link::{EndpointAddress, LinkErrorMode};
+use crate::link::statistics::LinkCounters;
+use crate::link::{
+    EndpointAddress, LinkErrorMode, RxTimestamp, TransportConfig, UnknownDestinationPolicy,
+};
 use crate::outstation::Feature;
 use crate::transport::{Fragment, FragmentInfo, TransportData};
 use crate::util::buffer::Buffer;
@@ -13,29 +20,44 @@ pub(crate) struct MockReader {
     frame_id: u32,
     info: Option<FrameInfo>,
     buffer: Buffer,
+    clock: Clock,
 }
 
 impl MockReader {
-    pub(crate) fn master(_: LinkErrorMode, _: EndpointAddress, rx_buffer_size: usize) -> Self {
-        Self::new(rx_buffer_size)
+    pub(crate) fn master(
+        _: LinkErrorMode,
+        _: EndpointAddress,
+        rx_buffer_size: usize,
+        _: TransportConfig,
+        _: Option<Duration>,
+        _: UnknownDestinationPolicy,
+        counters: Arc<LinkCounters>,
+    ) -> Self {
+        Self::new(rx_buffer_size, counters.clock())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn outstation(
         _: LinkErrorMode,
         _: EndpointAddress,
         _self_address: Feature,
         rx_buffer_size: usize,
+        _: TransportConfig,
+        _: Option<Duration>,
+        _: UnknownDestinationPolicy,
+        counters: Arc<LinkCounters>,
     ) -> Self {
-        Self::new(rx_buffer_size)
+        Self::new(rx_buffer_size, counters.clock())
     }
 
-    fn new(buffer_size: usize) -> Self {
+    fn new(buffer_size: usize, clock: Clock) -> Self {
         Self {
             num_reads: 0,
             count: 0,
             frame_id: 0,
             info: None,
             buffer: Buffer::new(buffer_size),
+            clock,
         }
     }
 
@@ -67,7 +89,12 @@ impl MockReader {
             .info
             .expect("call set_rx_frame_info(..) before running test");
         let fragment = Fragment {
-            info: FragmentInfo::new(self.frame_id, info.source, info.broadcast),
+            info: FragmentInfo::new(
+                self.frame_id,
+                info.source,
+                info.broadcast,
+                RxTimestamp::now(&self.clock),
+            ),
             data: &self.buffer.get(count).unwrap(),
         };
         Some(fragment)
@@ -76,7 +103,7 @@ impl MockReader {
     pub(crate) async fn read(
         &mut self,
         io: &mut PhysLayer,
-        level: DecodeLevel,
+        _: DecodeLevel,
     ) -> Result<(), LinkError> {
         if self.count > 0 {
             return Ok(());
@@ -84,11 +111,11 @@ impl MockReader {
 
         self.num_reads += 1;
         self.count = io
-            .read(
-                self.buffer.get_mut(self.buffer.len()).unwrap(),
-                level.physical,
-            )
+            .read(self.buffer.get_mut(self.buffer.len()).unwrap())
             .await?;
+        if self.count == 0 {
+            return Err(LinkError::Stdio(std::io::ErrorKind::UnexpectedEof));
+        }
         self.frame_id = self.frame_id.wrapping_add(1);
         Ok(())
     }
@@ -1,8 +1,13 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use crate::app::EndpointType;
+use crate::app::Instant;
 use crate::decode::DecodeLevel;
 use crate::link::error::LinkError;
 use crate::link::header::AnyAddress;
-use crate::link::EndpointAddress;
+use crate::link::statistics::LinkCounters;
+use crate::link::{EndpointAddress, LinkConfig, SegmentSize};
 use crate::util::phys::PhysLayer;
 
 pub(crate) struct MockWriter {
@@ -11,12 +16,23 @@ pub(crate) struct MockWriter {
 
 // same signature as the real transport writer
 impl MockWriter {
-    pub(crate) fn new(_: EndpointType, _: EndpointAddress) -> Self {
+    pub(crate) fn new(
+        _: EndpointType,
+        _: EndpointAddress,
+        _: SegmentSize,
+        _: LinkConfig,
+        _: Duration,
+        _: Arc<LinkCounters>,
+    ) -> Self {
         Self { num_writes: 0 }
     }
 
     pub(crate) fn reset(&mut self) {}
 
+    pub(crate) fn busy_until(&self) -> Option<Instant> {
+        None
+    }
+
     pub(crate) fn num_writes(&self) -> usize {
         self.num_writes
     }
@@ -25,11 +41,11 @@ impl MockWriter {
     pub(crate) async fn write(
         &mut self,
         io: &mut PhysLayer,
-        level: DecodeLevel,
+        _: DecodeLevel,
         _: AnyAddress,
         fragment: &[u8],
     ) -> Result<(), LinkError> {
-        io.write(fragment, level.physical).await?;
+        io.write(fragment).await?;
         self.num_writes += 1;
         Ok(())
     }
@@ -2,44 +2,75 @@ pub(crate) use reader::*;
 pub(crate) use types::*;
 pub(crate) use writer::*;
 
+use std::sync::Arc;
+use std::time::Duration;
+
 use crate::app::EndpointType;
+use crate::link::statistics::LinkCounters;
 use crate::link::EndpointAddress;
-use crate::link::LinkErrorMode;
+use crate::link::{LinkConfig, LinkErrorMode, TransportConfig, UnknownDestinationPolicy};
 use crate::master::session::MasterSession;
 use crate::outstation::Feature;
 
 #[cfg(test)]
 pub(crate) mod mock;
-#[cfg(not(test))]
+// the sessions are tested with the mocks, but the real reader and writer are tested directly
+#[cfg_attr(test, allow(dead_code))]
 pub(crate) mod real;
 
 mod reader;
 mod types;
 mod writer;
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn create_master_transport_layer(
     link_error_mode: LinkErrorMode,
     address: EndpointAddress,
     rx_buffer_size: usize,
+    link_config: LinkConfig,
+    default_ack_timeout: Duration,
+    transport_config: TransportConfig,
+    unknown_destination: UnknownDestinationPolicy,
+    counters: Arc<LinkCounters>,
 ) -> (TransportReader, TransportWriter) {
-    let rx_buffer_size = if rx_buffer_size < MasterSession::MIN_RX_BUFFER_SIZE {
-        tracing::warn!("Minimum RX buffer size is {}. Defaulting to this value because the provided value ({}) is too low.", MasterSession::MIN_RX_BUFFER_SIZE, rx_buffer_size);
-        MasterSession::MIN_RX_BUFFER_SIZE
-    } else {
-        rx_buffer_size
-    };
+    let effective_size = MasterSession::effective_rx_buffer_size(rx_buffer_size);
+    if effective_size != rx_buffer_size {
+        tracing::warn!("RX buffer size must be between {} and {}. Defaulting to {} because the provided value ({}) is out of range.", MasterSession::MIN_RX_BUFFER_SIZE, MasterSession::MAX_RX_BUFFER_SIZE, effective_size, rx_buffer_size);
+    }
+    let rx_buffer_size = effective_size;
 
     (
-        TransportReader::master(link_error_mode, address, rx_buffer_size),
-        TransportWriter::new(EndpointType::Master, address),
+        TransportReader::master(
+            link_error_mode,
+            address,
+            rx_buffer_size,
+            transport_config,
+            link_config.inter_frame_timeout,
+            unknown_destination,
+            counters.clone(),
+        ),
+        TransportWriter::new(
+            EndpointType::Master,
+            address,
+            transport_config.segment_size,
+            link_config,
+            link_config.ack_timeout_or(default_ack_timeout),
+            counters,
+        ),
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn create_outstation_transport_layer(
     link_error_mode: LinkErrorMode,
     address: EndpointAddress,
     self_address: Feature,
     rx_buffer_size: crate::outstation::BufferSize,
+    link_config: LinkConfig,
+    default_ack_timeout: Duration,
+    transport_config: TransportConfig,
+    unknown_destination: UnknownDestinationPolicy,
+    counters: Arc<LinkCounters>,
 ) -> (TransportReader, TransportWriter) {
     (
         TransportReader::outstation(
@@ -47,7 +78,18 @@ pub(crate) fn create_outstation_transport_layer(
             address,
             self_address,
             rx_buffer_size.value(),
+            transport_config,
+            link_config.inter_frame_timeout,
+            unknown_destination,
+            counters.clone(),
+        ),
+        TransportWriter::new(
+            EndpointType::Outstation,
+            address,
+            transport_config.segment_size,
+            link_config,
+            link_config.ack_timeout_or(default_ack_timeout),
+            counters,
         ),
-        TransportWriter::new(EndpointType::Outstation, address),
     )
 }
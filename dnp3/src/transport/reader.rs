@@ -1,8 +1,12 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use crate::app::parse::parser::ParsedFragment;
-use crate::app::HeaderParseError;
-use crate::decode::{AppDecodeLevel, DecodeLevel};
+use crate::app::{AnomalyKind, HeaderParseError};
+use crate::decode::{AppDecodeLevel, DecodeDirection, DecodeLevel, WireLayer};
 use crate::link::error::LinkError;
-use crate::link::{EndpointAddress, LinkErrorMode};
+use crate::link::statistics::LinkCounters;
+use crate::link::{EndpointAddress, LinkErrorMode, TransportConfig, UnknownDestinationPolicy};
 use crate::outstation::Feature;
 use crate::transport::{
     FragmentInfo, LinkLayerMessage, TransportData, TransportRequest, TransportResponse,
@@ -18,6 +22,7 @@ pub(crate) type InnerReaderType = crate::transport::mock::reader::MockReader;
 
 pub(crate) struct TransportReader {
     inner: InnerReaderType,
+    counters: Arc<LinkCounters>,
 }
 
 pub(crate) struct RequestGuard<'a> {
@@ -55,17 +60,35 @@ impl TransportReader {
         link_error_mode: LinkErrorMode,
         address: EndpointAddress,
         rx_buffer_size: usize,
+        transport_config: TransportConfig,
+        inter_frame_timeout: Option<Duration>,
+        unknown_destination: UnknownDestinationPolicy,
+        counters: Arc<LinkCounters>,
     ) -> Self {
         Self {
-            inner: InnerReaderType::master(link_error_mode, address, rx_buffer_size),
+            inner: InnerReaderType::master(
+                link_error_mode,
+                address,
+                rx_buffer_size,
+                transport_config,
+                inter_frame_timeout,
+                unknown_destination,
+                counters.clone(),
+            ),
+            counters,
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn outstation(
         link_error_mode: LinkErrorMode,
         address: EndpointAddress,
         self_address: Feature,
         rx_buffer_size: usize,
+        transport_config: TransportConfig,
+        inter_frame_timeout: Option<Duration>,
+        unknown_destination: UnknownDestinationPolicy,
+        counters: Arc<LinkCounters>,
     ) -> Self {
         Self {
             inner: InnerReaderType::outstation(
@@ -73,7 +96,12 @@ impl TransportReader {
                 address,
                 self_address,
                 rx_buffer_size,
+                transport_config,
+                inter_frame_timeout,
+                unknown_destination,
+                counters.clone(),
             ),
+            counters,
         }
     }
 
@@ -88,17 +116,39 @@ impl TransportReader {
         decode_level: DecodeLevel,
     ) -> Result<(), LinkError> {
         self.inner.read(io, decode_level).await?;
-        if decode_level.application.enabled() {
+        self.tap();
+        if decode_level.application.enabled() || self.counters.decode().fragments_enabled() {
             self.decode(decode_level.application);
         }
         Ok(())
     }
 
+    fn tap(&self) {
+        if !self.counters.decode().tap_enabled() {
+            return;
+        }
+
+        if let Some(TransportData::Fragment(fragment)) = self.inner.peek() {
+            self.counters.decode().emit_wire(
+                WireLayer::Application,
+                DecodeDirection::Rx,
+                fragment.data,
+            );
+        }
+    }
+
     fn decode(&self, level: AppDecodeLevel) {
         if let Some(TransportData::Fragment(fragment)) = self.inner.peek() {
             match ParsedFragment::parse(fragment.data) {
                 Ok(fragment) => {
-                    tracing::info!("APP RX - {}", fragment.display(level));
+                    if level.enabled() {
+                        self.counters
+                            .decode()
+                            .emit(format_args!("APP RX - {}", fragment.display(level)));
+                    }
+                    self.counters
+                        .decode()
+                        .emit_fragment(DecodeDirection::Rx, &fragment);
                 }
                 Err(err) => {
                     tracing::warn!("error parsing fragment header: {}", err);
@@ -116,11 +166,24 @@ impl TransportReader {
     }
 
     pub(crate) fn pop_response(&mut self) -> Option<TransportResponse> {
-        let data = self.parse(false)?;
+        let data = Self::parse(&mut self.inner, false)?;
 
         match data {
             Ok(ParsedTransportData::Fragment(info, fragment)) => match fragment.to_response() {
-                Ok(response) => Some(TransportResponse::Response(info.source, response)),
+                Ok(response) => {
+                    let iin2 = response.header.iin.iin2;
+                    if iin2.get_reserved_1() || iin2.get_reserved_2() {
+                        self.counters.anomalies().record(
+                            AnomalyKind::ReservedIinBits,
+                            format_args!("response with reserved IIN2 bits set: {}", iin2),
+                        );
+                    }
+                    Some(TransportResponse::Response(
+                        info.source,
+                        info.received,
+                        response,
+                    ))
+                }
                 Err(err) => Some(TransportResponse::Error(err.into())),
             },
             Ok(ParsedTransportData::LinkLayerMessage(msg)) => {
@@ -135,7 +198,7 @@ impl TransportReader {
     }
 
     fn peek_request(&mut self) -> Option<TransportRequest> {
-        let data = self.parse(true)?;
+        let data = Self::parse(&mut self.inner, true)?;
         match data {
             Ok(ParsedTransportData::Fragment(info, fragment)) => match fragment.to_request() {
                 Ok(request) => Some(TransportRequest::Request(info, request)),
@@ -148,12 +211,11 @@ impl TransportReader {
         }
     }
 
-    fn parse(&mut self, peek: bool) -> Option<Result<ParsedTransportData, HeaderParseError>> {
-        let transport_data = if peek {
-            self.inner.peek()?
-        } else {
-            self.inner.pop()?
-        };
+    fn parse(
+        inner: &mut InnerReaderType,
+        peek: bool,
+    ) -> Option<Result<ParsedTransportData, HeaderParseError>> {
+        let transport_data = if peek { inner.peek()? } else { inner.pop()? };
 
         match transport_data {
             TransportData::Fragment(fragment) => Some(
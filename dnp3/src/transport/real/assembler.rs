@@ -1,4 +1,11 @@
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use crate::app::Instant;
 use crate::link::header::FrameInfo;
+use crate::link::parser::FramePayload;
+use crate::link::statistics::LinkCounters;
+use crate::link::RxTimestamp;
 use crate::transport::real::header::Header;
 use crate::transport::{Fragment, FragmentInfo};
 use crate::util::buffer::Buffer;
@@ -6,12 +13,28 @@ use crate::util::buffer::Buffer;
 #[derive(Copy, Clone)]
 enum InternalState {
     Empty,
-    // last info, header, and accumulated length
-    Running(FrameInfo, Header, usize),
+    Running(Running),
     // buffer contains an assembled ADU
     Complete(FragmentInfo, usize),
 }
 
+// a partially assembled fragment
+#[derive(Copy, Clone)]
+struct Running {
+    // info of the last segment
+    info: FrameInfo,
+    // reception time of the first segment
+    received: RxTimestamp,
+    // header of the last segment
+    header: Header,
+    // accumulated length
+    length: usize,
+    // number of segments received
+    segments: usize,
+    // time at which the last segment was received
+    last_segment: Instant,
+}
+
 impl InternalState {
     fn to_assembly_state(self) -> AssemblyState {
         match self {
@@ -26,19 +49,49 @@ pub(crate) enum AssemblyState {
     ReadMore,
 }
 
+/// reasons why a partially assembled fragment is discarded
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum AssemblyError {
+    /// the fragment doesn't fit in the buffer
+    BufferOverflow(usize),
+    /// the fragment requires more segments than the configured maximum
+    TooManySegments(usize),
+}
+
+impl std::fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AssemblyError::BufferOverflow(size) => {
+                write!(f, "transport buffer overflow with {} bytes to write", size)
+            }
+            AssemblyError::TooManySegments(max) => {
+                write!(f, "fragment exceeds the maximum of {} segments", max)
+            }
+        }
+    }
+}
+
 pub(crate) struct Assembler {
     state: InternalState,
     // assembled count
     frame_id: u32,
+    max_segments: Option<NonZeroUsize>,
+    counters: Arc<LinkCounters>,
     buffer: Buffer,
 }
 
 impl Assembler {
-    pub(crate) fn new(max_buffer_size: usize) -> Self {
+    pub(crate) fn new(
+        max_buffer_size: usize,
+        max_segments: Option<NonZeroUsize>,
+        counters: Arc<LinkCounters>,
+    ) -> Self {
         Self {
             state: InternalState::Empty,
             frame_id: 0,
-            buffer: Buffer::new(max_buffer_size),
+            max_segments,
+            buffer: Buffer::pooled(max_buffer_size, counters.buffer_pool()),
+            counters,
         }
     }
 
@@ -46,6 +99,13 @@ impl Assembler {
         self.state = InternalState::Empty;
     }
 
+    /// return the buffer to the pool if no fragment is being assembled or waiting to be read
+    pub(crate) fn release_if_empty(&mut self) {
+        if let InternalState::Empty = self.state {
+            self.buffer.release(0);
+        }
+    }
+
     pub(crate) fn peek(&self) -> Option<Fragment> {
         match self.state {
             InternalState::Complete(info, size) => {
@@ -73,19 +133,44 @@ impl Assembler {
         }
     }
 
+    /// time at which the last segment of a partially assembled fragment was received
+    pub(crate) fn last_segment(&self) -> Option<Instant> {
+        match self.state {
+            InternalState::Running(running) => Some(running.last_segment),
+            _ => None,
+        }
+    }
+
+    /// discard a partially assembled fragment whose next segment didn't arrive in time
+    pub(crate) fn on_reassembly_timeout(&mut self) {
+        if let InternalState::Running(running) = self.state {
+            tracing::warn!(
+                "transport: reassembly timeout - dropping {} assembled bytes from {}",
+                running.length,
+                running.info.source
+            );
+            self.state = InternalState::Empty;
+        }
+    }
+
     pub(crate) fn assemble(
         &mut self,
         info: FrameInfo,
+        received: RxTimestamp,
         header: Header,
-        payload: &[u8],
+        payload: FramePayload,
     ) -> AssemblyState {
+        self.counters.on_segment_rx();
+        let now = self.counters.clock().now();
+
         // FIR always clears the state
         if header.fir {
-            if let InternalState::Running(info, _, size) = self.state {
+            if let InternalState::Running(running) = self.state {
+                self.counters.on_fir_reset();
                 tracing::warn!(
                     "transport: received FIR - dropping {} assembled bytes from {}",
-                    size,
-                    info.source
+                    running.length,
+                    running.info.source
                 );
             }
             self.state = InternalState::Empty;
@@ -93,7 +178,7 @@ impl Assembler {
 
         if info.broadcast.is_some() {
             if header.fir && header.fin {
-                self.append(info, header, 0, payload);
+                self.append(Running::first(info, received, header, now), payload);
             } else {
                 tracing::warn!(
                     "ignoring broadcast frame with transport header fir: {} and fin: {}",
@@ -107,7 +192,7 @@ impl Assembler {
         match self.state {
             InternalState::Complete(_, _) => {
                 self.state = InternalState::Empty;
-                self.append(info, header, 0, payload);
+                self.append(Running::first(info, received, header, now), payload);
             }
             InternalState::Empty => {
                 // ignore non-FIR segments if there was no previous frame
@@ -118,51 +203,189 @@ impl Assembler {
                     );
                     return AssemblyState::ReadMore;
                 }
-                self.append(info, header, 0, payload);
+                self.append(Running::first(info, received, header, now), payload);
             }
-            InternalState::Running(previous_info, previous_header, length) => {
-                if header.seq.value() != previous_header.seq.next() {
-                    tracing::warn!("transport: conflicting addresses, previous segment with {:?}, but received {:?}", previous_info, info);
+            InternalState::Running(running) => {
+                if header.seq.value() != running.header.seq.next() {
+                    self.counters.on_out_of_sequence();
+                    tracing::warn!("transport: conflicting addresses, previous segment with {:?}, but received {:?}", running.info, info);
                     self.state = InternalState::Empty;
                     return AssemblyState::ReadMore;
                 }
-                if info != previous_info {
-                    tracing::warn!("transport: conflicting addresses, previous segment with {:?}, but received {:?}", previous_info, info);
+                if info != running.info {
+                    tracing::warn!("transport: conflicting addresses, previous segment with {:?}, but received {:?}", running.info, info);
                     self.state = InternalState::Empty;
                     return AssemblyState::ReadMore;
                 }
-                self.append(info, header, length, payload);
+                self.append(running.next(header, now), payload);
             }
         }
 
         self.state.to_assembly_state()
     }
 
-    fn append(&mut self, info: FrameInfo, header: Header, acc_length: usize, data: &[u8]) {
-        let new_length = acc_length + data.len();
+    fn append(&mut self, running: Running, data: FramePayload) {
+        match self.try_append(running, data) {
+            Err(err) => {
+                tracing::warn!("transport: {}", err);
+                self.state = InternalState::Empty;
+            }
+            Ok(state) => {
+                self.state = state;
+            }
+        }
+    }
+
+    fn try_append(
+        &mut self,
+        running: Running,
+        data: FramePayload,
+    ) -> Result<InternalState, AssemblyError> {
+        if let Some(max) = self.max_segments {
+            if running.segments > max.get() {
+                return Err(AssemblyError::TooManySegments(max.get()));
+            }
+        }
+
+        let offset = running.length;
+        let running = Running {
+            length: running.length + data.len(),
+            ..running
+        };
 
         let mut cursor = self.buffer.write_cursor();
         cursor
-            .skip(acc_length)
+            .skip(offset)
             .expect("accumulated length is greater than the buffer size");
-        match cursor.write_slice(data) {
-            Err(_) => {
-                tracing::warn!(
-                    "transport buffer overflow with {} bytes to write",
-                    data.len()
-                );
-                self.state = InternalState::Empty;
-            }
-            Ok(_) => {
-                if header.fin {
-                    let frame_id = self.frame_id;
-                    let info = FragmentInfo::new(frame_id, info.source, info.broadcast);
-                    self.frame_id = self.frame_id.wrapping_add(1);
-                    self.state = InternalState::Complete(info, new_length)
-                } else {
-                    self.state = InternalState::Running(info, header, new_length)
-                }
-            }
+        // copy the blocks directly from the link layer's read buffer
+        data.blocks()
+            .try_for_each(|block| cursor.write_slice(block))
+            .map_err(|_| AssemblyError::BufferOverflow(data.len()))?;
+
+        if running.header.fin {
+            let frame_id = self.frame_id;
+            let info = FragmentInfo::new(
+                frame_id,
+                running.info.source,
+                running.info.broadcast,
+                running.received,
+            );
+            self.frame_id = self.frame_id.wrapping_add(1);
+            self.counters.on_fragment_rx();
+            Ok(InternalState::Complete(info, running.length))
+        } else {
+            Ok(InternalState::Running(running))
+        }
+    }
+}
+
+impl Running {
+    // state for the FIR segment, before its data is appended
+    fn first(info: FrameInfo, received: RxTimestamp, header: Header, now: Instant) -> Self {
+        Self {
+            info,
+            received,
+            header,
+            length: 0,
+            segments: 1,
+            last_segment: now,
         }
     }
+
+    // state for a subsequent segment, before its data is appended
+    fn next(self, header: Header, now: Instant) -> Self {
+        Self {
+            header,
+            segments: self.segments + 1,
+            last_segment: now,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app::Clock;
+    use crate::link::header::FrameType;
+    use crate::link::EndpointAddress;
+    use crate::transport::real::sequence::Sequence;
+
+    fn info() -> FrameInfo {
+        FrameInfo::new(EndpointAddress::from(1).unwrap(), None, FrameType::Data)
+    }
+
+    fn segment(fir: bool, fin: bool, seq: u8) -> Header {
+        Header::new(fin, fir, Sequence::new(seq))
+    }
+
+    fn assemble(assembler: &mut Assembler, header: Header, data: &[u8]) -> bool {
+        let state = assembler.assemble(
+            info(),
+            RxTimestamp::now(&Clock::default()),
+            header,
+            FramePayload::contiguous(data),
+        );
+        matches!(state, AssemblyState::Complete)
+    }
+
+    #[test]
+    fn discards_fragment_with_too_many_segments() {
+        let counters = Arc::new(LinkCounters::default());
+        let mut assembler = Assembler::new(2048, NonZeroUsize::new(2), counters.clone());
+
+        assert!(!assemble(&mut assembler, segment(true, false, 0), &[0x01]));
+        assert!(!assemble(&mut assembler, segment(false, false, 1), &[0x02]));
+        // the third segment exceeds the maximum, so the partial fragment is discarded
+        assert!(!assemble(&mut assembler, segment(false, true, 2), &[0x03]));
+        assert!(assembler.peek().is_none());
+        assert!(assembler.last_segment().is_none());
+
+        // a fragment within the limit is still accepted
+        assert!(!assemble(&mut assembler, segment(true, false, 3), &[0x04]));
+        assert!(assemble(&mut assembler, segment(false, true, 4), &[0x05]));
+        assert_eq!(assembler.pop().unwrap().data, &[0x04, 0x05]);
+        assert_eq!(counters.transport_snapshot().fragments_rx, 1);
+    }
+
+    #[test]
+    fn discards_fragment_that_overflows_the_buffer() {
+        let counters = Arc::new(LinkCounters::default());
+        let mut assembler = Assembler::new(4, None, counters.clone());
+
+        assert!(!assemble(
+            &mut assembler,
+            segment(true, false, 0),
+            &[0x01, 0x02, 0x03]
+        ));
+        // two more bytes don't fit
+        assert!(!assemble(
+            &mut assembler,
+            segment(false, true, 1),
+            &[0x04, 0x05]
+        ));
+        assert!(assembler.peek().is_none());
+        assert!(assembler.last_segment().is_none());
+
+        // a single segment that doesn't fit is discarded too
+        assert!(!assemble(
+            &mut assembler,
+            segment(true, true, 2),
+            &[0x00; 5]
+        ));
+        assert!(assembler.peek().is_none());
+        assert_eq!(counters.transport_snapshot().fragments_rx, 0);
+    }
+
+    #[test]
+    fn errors_describe_the_limit() {
+        assert_eq!(
+            AssemblyError::TooManySegments(2).to_string(),
+            "fragment exceeds the maximum of 2 segments"
+        );
+        assert_eq!(
+            AssemblyError::BufferOverflow(5).to_string(),
+            "transport buffer overflow with 5 bytes to write"
+        );
+    }
 }
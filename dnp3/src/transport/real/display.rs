@@ -1,14 +1,19 @@
-use crate::decode::TransportDecodeLevel;
+use crate::decode::{PayloadKind, TransportDecodeLevel};
+use crate::link::parser::FramePayload;
 use crate::transport::real::header::Header;
 
 pub(crate) struct SegmentDisplay<'a> {
     pub(crate) header: Header,
-    pub(crate) payload: &'a [u8],
+    pub(crate) payload: FramePayload<'a>,
     level: TransportDecodeLevel,
 }
 
 impl<'a> SegmentDisplay<'a> {
-    pub(crate) fn new(header: Header, payload: &'a [u8], level: TransportDecodeLevel) -> Self {
+    pub(crate) fn new(
+        header: Header,
+        payload: FramePayload<'a>,
+        level: TransportDecodeLevel,
+    ) -> Self {
         Self {
             header,
             payload,
@@ -30,7 +35,7 @@ impl<'a> std::fmt::Display for SegmentDisplay<'a> {
             )?;
         }
         if self.level.payload_enabled() {
-            crate::util::decode::format_bytes(f, self.payload)?;
+            crate::util::decode::format_byte_iter(f, PayloadKind::Transport, self.payload.bytes())?;
         }
         Ok(())
     }
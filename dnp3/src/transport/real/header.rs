@@ -1,3 +1,4 @@
+use crate::decode::{DecodeDirection, DecodeEvent, TransportSegmentEvent};
 use crate::transport::real::constants::{FIN_MASK, FIR_MASK};
 use crate::transport::real::sequence::Sequence;
 
@@ -32,4 +33,14 @@ impl Header {
 
         acc | self.seq.value()
     }
+
+    pub(crate) fn to_event(self, direction: DecodeDirection, length: usize) -> DecodeEvent {
+        DecodeEvent::TransportSegment(TransportSegmentEvent {
+            direction,
+            fir: self.fir,
+            fin: self.fin,
+            seq: self.seq.value(),
+            length,
+        })
+    }
 }
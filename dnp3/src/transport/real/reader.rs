@@ -1,9 +1,15 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use crate::app::EndpointType;
-use crate::decode::DecodeLevel;
+use crate::decode::{DecodeDirection, DecodeLevel, WireLayer};
 use crate::link::error::LinkError;
+use crate::link::header::FrameInfo;
 use crate::link::header::FrameType;
-use crate::link::parser::FramePayload;
-use crate::link::{EndpointAddress, LinkErrorMode};
+use crate::link::statistics::LinkCounters;
+use crate::link::{
+    EndpointAddress, LinkErrorMode, RxTimestamp, TransportConfig, UnknownDestinationPolicy,
+};
 use crate::outstation::Feature;
 use crate::transport::real::assembler::{Assembler, AssemblyState};
 use crate::transport::real::display::SegmentDisplay;
@@ -14,7 +20,9 @@ use crate::util::phys::PhysLayer;
 pub(crate) struct Reader {
     link: crate::link::layer::Layer,
     assembler: Assembler,
+    reassembly_timeout: Option<Duration>,
     pending_link_layer_message: Option<LinkLayerMessage>,
+    counters: Arc<LinkCounters>,
 }
 
 impl Reader {
@@ -22,6 +30,10 @@ impl Reader {
         link_error_mode: LinkErrorMode,
         source: EndpointAddress,
         max_tx_buffer: usize,
+        transport_config: TransportConfig,
+        inter_frame_timeout: Option<Duration>,
+        unknown_destination: UnknownDestinationPolicy,
+        counters: Arc<LinkCounters>,
     ) -> Self {
         Self {
             link: crate::link::layer::Layer::new(
@@ -29,17 +41,31 @@ impl Reader {
                 EndpointType::Master,
                 Feature::Disabled,
                 source,
+                inter_frame_timeout,
+                unknown_destination,
+                counters.clone(),
+            ),
+            assembler: Assembler::new(
+                max_tx_buffer,
+                transport_config.max_rx_segments,
+                counters.clone(),
             ),
-            assembler: Assembler::new(max_tx_buffer),
+            reassembly_timeout: transport_config.reassembly_timeout,
             pending_link_layer_message: None,
+            counters,
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn outstation(
         link_error_mode: LinkErrorMode,
         source: EndpointAddress,
         self_address: Feature,
         max_rx_buffer: usize,
+        transport_config: TransportConfig,
+        inter_frame_timeout: Option<Duration>,
+        unknown_destination: UnknownDestinationPolicy,
+        counters: Arc<LinkCounters>,
     ) -> Self {
         Self {
             link: crate::link::layer::Layer::new(
@@ -47,9 +73,18 @@ impl Reader {
                 EndpointType::Outstation,
                 self_address,
                 source,
+                inter_frame_timeout,
+                unknown_destination,
+                counters.clone(),
+            ),
+            assembler: Assembler::new(
+                max_rx_buffer,
+                transport_config.max_rx_segments,
+                counters.clone(),
             ),
-            assembler: Assembler::new(max_rx_buffer),
+            reassembly_timeout: transport_config.reassembly_timeout,
             pending_link_layer_message: None,
+            counters,
         }
     }
 
@@ -84,28 +119,43 @@ impl Reader {
             return Ok(());
         }
 
-        let mut payload = FramePayload::new();
+        self.assembler.release_if_empty();
 
         loop {
-            let info = self.link.read(io, level, &mut payload).await?;
+            let (info, received) = match self.read_frame(io, level).await? {
+                Some(frame) => frame,
+                None => continue,
+            };
 
             match info.frame_type {
-                FrameType::Data => match payload.get() {
-                    [transport, data @ ..] => {
-                        let header = Header::from_u8(*transport);
+                FrameType::Data => match self.link.payload().split_first() {
+                    Some((transport, data)) => {
+                        let header = Header::from_u8(transport);
                         if level.transport.enabled() {
-                            tracing::info!(
+                            self.counters.decode().emit(format_args!(
                                 "TRANSPORT RX - {}",
                                 SegmentDisplay::new(header, data, level.transport)
+                            ));
+                        }
+                        self.counters
+                            .decode()
+                            .emit_event(header.to_event(DecodeDirection::Rx, data.len()));
+                        if self.counters.decode().tap_enabled() {
+                            let mut segment = [0; crate::link::constant::MAX_FRAME_PAYLOAD_LENGTH];
+                            self.counters.decode().emit_wire(
+                                WireLayer::Transport,
+                                DecodeDirection::Rx,
+                                self.link.payload().copy_to(&mut segment),
                             );
                         }
 
-                        if let AssemblyState::Complete = self.assembler.assemble(info, header, data)
+                        if let AssemblyState::Complete =
+                            self.assembler.assemble(info, received, header, data)
                         {
                             return Ok(());
                         }
                     }
-                    [] => tracing::warn!("received link data frame with no payload"),
+                    None => tracing::warn!("received link data frame with no payload"),
                 },
                 FrameType::LinkStatusRequest => {
                     self.pending_link_layer_message = Some(LinkLayerMessage {
@@ -124,4 +174,157 @@ impl Reader {
             }
         }
     }
+
+    /// read the next frame, returning `None` if a partially reassembled fragment times out first
+    async fn read_frame(
+        &mut self,
+        io: &mut PhysLayer,
+        level: DecodeLevel,
+    ) -> Result<Option<(FrameInfo, RxTimestamp)>, LinkError> {
+        let deadline = match (self.reassembly_timeout, self.assembler.last_segment()) {
+            (Some(timeout), Some(last)) => last + timeout,
+            _ => return Ok(Some(self.link.read(io, level).await?)),
+        };
+
+        let clock = self.counters.clock();
+        crate::tokio::select! {
+            frame = self.link.read(io, level) => {
+                Ok(Some(frame?))
+            }
+            _ = clock.sleep_until(deadline) => {
+                self.assembler.on_reassembly_timeout();
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app::{Clock, ManualClock};
+    use crate::decode::TracingConfig;
+    use crate::link::constant::MAX_LINK_FRAME_LENGTH;
+    use crate::link::format::{format_data_frame, Payload};
+    use crate::link::header::AnyAddress;
+    use crate::link::statistics::TransportStatistics;
+    use crate::tokio::test::*;
+    use crate::transport::real::sequence::Sequence;
+    use crate::util::cursor::WriteCursor;
+
+    fn segment(fir: bool, fin: bool, seq: u8, data: &[u8]) -> Vec<u8> {
+        let header = crate::link::header::Header::unconfirmed_user_data(
+            true,
+            AnyAddress::from(1),
+            AnyAddress::from(1024),
+        );
+        let transport = Header::new(fin, fir, Sequence::new(seq)).to_u8();
+        let mut buffer = [0; MAX_LINK_FRAME_LENGTH];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        format_data_frame(header, Payload::new(transport, data), &mut cursor)
+            .unwrap()
+            .frame
+            .to_vec()
+    }
+
+    fn outstation(clock: Arc<ManualClock>) -> Reader {
+        let counters = Arc::new(LinkCounters::new(
+            Clock::manual(clock),
+            TracingConfig::default(),
+            None,
+        ));
+        Reader::outstation(
+            LinkErrorMode::Close,
+            EndpointAddress::from(1).unwrap(),
+            Feature::Disabled,
+            2048,
+            TransportConfig {
+                reassembly_timeout: Some(Duration::from_secs(1)),
+                ..TransportConfig::default()
+            },
+            None,
+            UnknownDestinationPolicy::default(),
+            counters,
+        )
+    }
+
+    fn fragment_data(reader: &mut Reader) -> Vec<u8> {
+        match reader.pop() {
+            Some(TransportData::Fragment(fragment)) => fragment.data.to_vec(),
+            x => panic!("expected fragment, but got: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn drops_stalled_partial_fragment_after_reassembly_timeout() {
+        let clock = Arc::new(ManualClock::new());
+        let mut reader = outstation(clock.clone());
+        let (io, mut io_handle) = io::mock();
+        let mut io = PhysLayer::Mock(io);
+
+        io_handle.read(&segment(true, false, 0, &[0xC0, 0x01]));
+        let mut task = spawn(reader.read(&mut io, DecodeLevel::nothing()));
+        assert_pending!(task.poll());
+        assert_eq!(clock.next_wake(), Some(Duration::from_secs(1)));
+
+        // the partial fragment is discarded, so the final segment is ignored
+        clock.advance(Duration::from_secs(1));
+        assert_pending!(task.poll());
+        assert_eq!(clock.next_wake(), None);
+        io_handle.read(&segment(false, true, 1, &[0x02]));
+        assert_pending!(task.poll());
+
+        // a new fragment is reassembled normally
+        io_handle.read(&segment(true, true, 2, &[0xC1, 0x03]));
+        assert_ready!(task.poll()).unwrap();
+        drop(task);
+        assert_eq!(fragment_data(&mut reader), [0xC1, 0x03]);
+    }
+
+    #[test]
+    fn keeps_partial_fragment_when_segments_arrive_in_time() {
+        let clock = Arc::new(ManualClock::new());
+        let mut reader = outstation(clock.clone());
+        let (io, mut io_handle) = io::mock();
+        let mut io = PhysLayer::Mock(io);
+
+        io_handle.read(&segment(true, false, 0, &[0xC0, 0x01]));
+        let mut task = spawn(reader.read(&mut io, DecodeLevel::nothing()));
+        assert_pending!(task.poll());
+
+        clock.advance(Duration::from_millis(999));
+        io_handle.read(&segment(false, true, 1, &[0x02]));
+        assert_ready!(task.poll()).unwrap();
+        drop(task);
+        assert_eq!(fragment_data(&mut reader), [0xC0, 0x01, 0x02]);
+    }
+
+    #[test]
+    fn counts_segments_discards_and_reassembled_fragments() {
+        let clock = Arc::new(ManualClock::new());
+        let mut reader = outstation(clock.clone());
+        let (io, mut io_handle) = io::mock();
+        let mut io = PhysLayer::Mock(io);
+
+        // a FIR segment restarts the partial fragment, then a skipped sequence number discards it
+        io_handle.read(&segment(true, false, 0, &[0xC0, 0x01]));
+        io_handle.read(&segment(true, false, 1, &[0xC1, 0x02]));
+        io_handle.read(&segment(false, false, 3, &[0x03]));
+        io_handle.read(&segment(true, false, 4, &[0xC2, 0x04]));
+        io_handle.read(&segment(false, true, 5, &[0x05]));
+        let mut task = spawn(reader.read(&mut io, DecodeLevel::nothing()));
+        assert_ready!(task.poll()).unwrap();
+        drop(task);
+        assert_eq!(fragment_data(&mut reader), [0xC2, 0x04, 0x05]);
+
+        assert_eq!(
+            reader.counters.transport_snapshot(),
+            TransportStatistics {
+                segments_rx: 5,
+                out_of_sequence: 1,
+                fir_resets: 1,
+                fragments_rx: 1,
+            }
+        );
+    }
 }
@@ -1,34 +1,86 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use crate::app::EndpointType;
-use crate::decode::DecodeLevel;
+use crate::app::Instant;
+use crate::decode::{DecodeDirection, DecodeLevel, WireLayer};
+use crate::link::confirmed::ConfirmedService;
+use crate::link::constant::MAX_LINK_FRAME_LENGTH;
 use crate::link::error::LinkError;
 use crate::link::format::{format_data_frame, format_header_only, Payload};
 use crate::link::header::AnyAddress;
-use crate::link::EndpointAddress;
+use crate::link::pacing::Pacer;
+use crate::link::parser::FramePayload;
+use crate::link::statistics::LinkCounters;
+use crate::link::{EndpointAddress, LinkConfig, SegmentSize};
 use crate::transport::real::display::SegmentDisplay;
 use crate::transport::real::header::Header;
 use crate::transport::real::sequence::Sequence;
-use crate::util::cursor::WriteCursor;
+use crate::util::buffer::Buffer;
 use crate::util::phys::PhysLayer;
 
+/// maximum number of frames of a fragment passed to a single write of the physical layer
+const MAX_FRAMES_PER_WRITE: usize = 8;
+const BUFFER_SIZE: usize = MAX_FRAMES_PER_WRITE * MAX_LINK_FRAME_LENGTH;
+
 pub(crate) struct Writer {
     endpoint_type: EndpointType,
     local_address: EndpointAddress,
     seq: Sequence,
-    buffer: [u8; crate::link::constant::MAX_LINK_FRAME_LENGTH],
+    segment_size: SegmentSize,
+    pacer: Pacer,
+    // present if user data is sent with the confirmed service
+    confirmed: Option<ConfirmedService>,
+    counters: Arc<LinkCounters>,
+    // frames formatted but not yet written are at the front of the buffer, which is returned to
+    // the pool, if any, after each write
+    buffer: Buffer,
+    pending: usize,
 }
 
 impl Writer {
-    pub(crate) fn new(endpoint_type: EndpointType, local_address: EndpointAddress) -> Self {
+    pub(crate) fn new(
+        endpoint_type: EndpointType,
+        local_address: EndpointAddress,
+        segment_size: SegmentSize,
+        link: LinkConfig,
+        ack_timeout: Duration,
+        counters: Arc<LinkCounters>,
+    ) -> Self {
+        let confirmed = if link.confirmed_user_data {
+            Some(ConfirmedService::new(
+                link.retries,
+                ack_timeout,
+                counters.clone(),
+            ))
+        } else {
+            None
+        };
         Self {
             endpoint_type,
             local_address,
             seq: Sequence::default(),
-            buffer: [0; crate::link::constant::MAX_LINK_FRAME_LENGTH],
+            segment_size,
+            pacer: Pacer::new(link.pacing, counters.clock()),
+            confirmed,
+            buffer: Buffer::pooled(BUFFER_SIZE, counters.buffer_pool()),
+            counters,
+            pending: 0,
         }
     }
 
     pub(crate) fn reset(&mut self) {
         self.seq.reset();
+        self.pacer.reset();
+        if let Some(confirmed) = self.confirmed.as_mut() {
+            confirmed.reset();
+        }
+        self.pending = 0;
+    }
+
+    /// time until which the link is occupied by frames that were already written
+    pub(crate) fn busy_until(&self) -> Option<Instant> {
+        self.pacer.busy_until()
     }
 
     pub(crate) async fn write(
@@ -38,7 +90,19 @@ impl Writer {
         destination: AnyAddress,
         fragment: &[u8],
     ) -> Result<(), LinkError> {
-        let chunks = fragment.chunks(crate::link::constant::MAX_APP_BYTES_PER_FRAME);
+        let result = self.write_frames(io, level, destination, fragment).await;
+        self.buffer.release(0);
+        result
+    }
+
+    async fn write_frames(
+        &mut self,
+        io: &mut PhysLayer,
+        level: DecodeLevel,
+        destination: AnyAddress,
+        fragment: &[u8],
+    ) -> Result<(), LinkError> {
+        let chunks = fragment.chunks(self.segment_size.value());
 
         let last = if chunks.len() == 0 {
             0
@@ -46,41 +110,156 @@ impl Writer {
             chunks.len() - 1
         };
 
+        // discard the frames of a previous write that was cancelled
+        self.pending = 0;
+        // broadcasts can't be acknowledged
+        let confirmed =
+            self.confirmed.is_some() && !matches!(destination, AnyAddress::Broadcast(_));
+        // frames of the fragment are written together unless they must be paced or confirmed
+        // individually
+        let coalesce = io.coalesces_writes() && !self.pacer.is_enabled() && !confirmed;
+
         for (count, chunk) in chunks.enumerate() {
-            let mut cursor = WriteCursor::new(&mut self.buffer);
+            if self.pending + MAX_LINK_FRAME_LENGTH > BUFFER_SIZE {
+                self.flush(io, level).await?;
+            }
+
+            let mut cursor = self.buffer.write_cursor();
+            cursor.skip(self.pending)?;
             let header = Header::new(count == last, count == 0, self.seq.increment());
             if level.transport.enabled() {
-                tracing::info!(
+                self.counters.decode().emit(format_args!(
                     "TRANSPORT TX - {}",
-                    SegmentDisplay::new(header, chunk, level.transport)
-                );
+                    SegmentDisplay::new(header, FramePayload::contiguous(chunk), level.transport)
+                ));
             }
-            let link_header = crate::link::header::Header::unconfirmed_user_data(
-                self.endpoint_type.dir_bit(),
-                destination,
-                self.local_address.wrap(),
-            );
+            self.counters
+                .decode()
+                .emit_event(header.to_event(DecodeDirection::Tx, chunk.len()));
+            let link_header = if confirmed {
+                crate::link::header::Header::confirmed_user_data(
+                    self.endpoint_type.dir_bit(),
+                    destination,
+                    self.local_address.wrap(),
+                )
+            } else {
+                crate::link::header::Header::unconfirmed_user_data(
+                    self.endpoint_type.dir_bit(),
+                    destination,
+                    self.local_address.wrap(),
+                )
+            };
             let data = format_data_frame(
                 link_header,
                 Payload::new(header.to_u8(), chunk),
                 &mut cursor,
             )?;
+            if self.counters.decode().tap_enabled() {
+                let mut segment = Vec::with_capacity(chunk.len() + 1);
+                segment.push(header.to_u8());
+                segment.extend_from_slice(chunk);
+                self.counters.decode().emit_wire(
+                    WireLayer::Transport,
+                    DecodeDirection::Tx,
+                    &segment,
+                );
+                self.counters
+                    .decode()
+                    .emit_wire(WireLayer::Link, DecodeDirection::Tx, data.frame);
+            }
             if level.link.header_enabled() {
-                tracing::info!("LINK TX - {}", data.to_link_display(level.link));
+                self.counters.decode().emit(format_args!(
+                    "LINK TX - {}",
+                    data.to_link_display(level.link)
+                ));
+            }
+            self.counters
+                .decode()
+                .emit_event(link_header.to_event(DecodeDirection::Tx, chunk.len() + 1));
+            self.counters.on_frame_tx();
+            #[cfg(feature = "pcap")]
+            self.counters.capture().on_tx(data.frame);
+            self.pending += data.frame.len();
+
+            if confirmed {
+                self.pacer.wait().await;
+                match self.flush_confirmed(io, level, link_header).await? {
+                    Some(length) => self.pacer.on_transmit(length),
+                    None => {
+                        tracing::warn!(
+                            "discarding the remaining {} segment(s) of the fragment",
+                            last - count
+                        );
+                        return Ok(());
+                    }
+                }
+            } else if !coalesce || count == last {
+                self.pacer.wait().await;
+                let length = self.flush(io, level).await?;
+                self.pacer.on_transmit(length);
             }
-            io.write(data.frame, level.physical).await?;
         }
 
         Ok(())
     }
 
+    /// write the pending frames to the physical layer, returning the number of bytes written
+    async fn flush(&mut self, io: &mut PhysLayer, level: DecodeLevel) -> Result<usize, LinkError> {
+        let length = self.pending;
+        if length == 0 {
+            return Ok(0);
+        }
+
+        // discard the frames even if the write fails
+        self.pending = 0;
+        let data = self.buffer.get(length).unwrap_or_default();
+        self.counters
+            .decode()
+            .emit_phys(DecodeDirection::Tx, level.physical, data);
+        io.write(data).await?;
+        Ok(length)
+    }
+
+    /// write the pending frame with the confirmed service, returning the number of bytes written
+    /// or `None` if the frame wasn't acknowledged
+    async fn flush_confirmed(
+        &mut self,
+        io: &mut PhysLayer,
+        level: DecodeLevel,
+        header: crate::link::header::Header,
+    ) -> Result<Option<usize>, LinkError> {
+        let length = self.pending;
+        self.pending = 0;
+        let confirmed = match self.confirmed.as_mut() {
+            Some(confirmed) => confirmed,
+            None => return Ok(Some(0)),
+        };
+        let data = self.buffer.get(length).unwrap_or_default();
+        if confirmed.transmit(io, level, header, data).await? {
+            Ok(Some(length))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub(crate) async fn write_link_status_request(
         &mut self,
         io: &mut PhysLayer,
         level: DecodeLevel,
         destination: AnyAddress,
     ) -> Result<(), LinkError> {
-        let mut cursor = WriteCursor::new(&mut self.buffer);
+        let result = self.write_header_only(io, level, destination).await;
+        self.buffer.release(0);
+        result
+    }
+
+    async fn write_header_only(
+        &mut self,
+        io: &mut PhysLayer,
+        level: DecodeLevel,
+        destination: AnyAddress,
+    ) -> Result<(), LinkError> {
+        let mut cursor = self.buffer.write_cursor();
         let header = crate::link::header::Header::request_link_status(
             self.endpoint_type.dir_bit(),
             destination,
@@ -89,9 +268,26 @@ impl Writer {
 
         let data = format_header_only(header, &mut cursor)?;
         if level.link.enabled() {
-            tracing::info!("LINK TX - {}", data.to_link_display(level.link));
+            self.counters.decode().emit(format_args!(
+                "LINK TX - {}",
+                data.to_link_display(level.link)
+            ));
         }
-        io.write(data.frame, level.physical).await?;
+        self.counters
+            .decode()
+            .emit_event(header.to_event(DecodeDirection::Tx, 0));
+        self.counters
+            .decode()
+            .emit_wire(WireLayer::Link, DecodeDirection::Tx, data.frame);
+        self.pacer.wait().await;
+        self.counters
+            .decode()
+            .emit_phys(DecodeDirection::Tx, level.physical, data.frame);
+        io.write(data.frame).await?;
+        self.pacer.on_transmit(data.frame.len());
+        self.counters.on_frame_tx();
+        #[cfg(feature = "pcap")]
+        self.counters.capture().on_tx(data.frame);
 
         Ok(())
     }
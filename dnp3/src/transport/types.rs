@@ -1,13 +1,15 @@
 use crate::app::parse::parser::{Request, Response};
-use crate::app::{HeaderParseError, RequestValidationError, ResponseValidationError, Sequence};
+use crate::app::{HeaderParseError, RequestValidationError, ResponseHeaderError, Sequence};
 use crate::link::header::BroadcastConfirmMode;
-use crate::link::EndpointAddress;
+use crate::link::{EndpointAddress, RxTimestamp};
 
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct FragmentInfo {
     pub(crate) id: u32,
     pub(crate) source: EndpointAddress,
     pub(crate) broadcast: Option<BroadcastConfirmMode>,
+    /// reception time of the first frame of the fragment
+    pub(crate) received: RxTimestamp,
 }
 
 impl FragmentInfo {
@@ -15,11 +17,13 @@ impl FragmentInfo {
         id: u32,
         source: EndpointAddress,
         broadcast: Option<BroadcastConfirmMode>,
+        received: RxTimestamp,
     ) -> Self {
         FragmentInfo {
             id,
             source,
             broadcast,
+            received,
         }
     }
 }
@@ -49,27 +53,9 @@ pub(crate) enum LinkLayerMessageType {
 }
 
 pub(crate) enum TransportResponse<'a> {
-    Response(EndpointAddress, Response<'a>),
+    Response(EndpointAddress, RxTimestamp, Response<'a>),
     LinkLayerMessage(LinkLayerMessage),
-    Error(TransportResponseError),
-}
-
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub(crate) enum TransportResponseError {
-    HeaderParseError(HeaderParseError),
-    ResponseValidationError(ResponseValidationError),
-}
-
-impl From<HeaderParseError> for TransportResponseError {
-    fn from(from: HeaderParseError) -> Self {
-        Self::HeaderParseError(from)
-    }
-}
-
-impl From<ResponseValidationError> for TransportResponseError {
-    fn from(from: ResponseValidationError) -> Self {
-        Self::ResponseValidationError(from)
-    }
+    Error(ResponseHeaderError),
 }
 
 pub(crate) enum TransportRequest<'a> {
@@ -1,9 +1,14 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use crate::app::parse::parser::{FragmentDisplay, ParsedFragment};
 use crate::app::EndpointType;
-use crate::decode::DecodeLevel;
+use crate::app::Instant;
+use crate::decode::{DecodeDirection, DecodeLevel, WireLayer};
 use crate::link::error::LinkError;
 use crate::link::header::AnyAddress;
-use crate::link::EndpointAddress;
+use crate::link::statistics::LinkCounters;
+use crate::link::{EndpointAddress, LinkConfig, SegmentSize};
 use crate::util::phys::PhysLayer;
 
 /// This type definition is used so that we can mock the transport writer during testing.
@@ -15,12 +20,28 @@ pub(crate) type InnerTransportWriter = crate::transport::mock::writer::MockWrite
 
 pub(crate) struct TransportWriter {
     inner: InnerTransportWriter,
+    counters: Arc<LinkCounters>,
 }
 
 impl TransportWriter {
-    pub(crate) fn new(endpoint_type: EndpointType, local_address: EndpointAddress) -> Self {
+    pub(crate) fn new(
+        endpoint_type: EndpointType,
+        local_address: EndpointAddress,
+        segment_size: SegmentSize,
+        link: LinkConfig,
+        ack_timeout: Duration,
+        counters: Arc<LinkCounters>,
+    ) -> Self {
         Self {
-            inner: InnerTransportWriter::new(endpoint_type, local_address),
+            inner: InnerTransportWriter::new(
+                endpoint_type,
+                local_address,
+                segment_size,
+                link,
+                ack_timeout,
+                counters.clone(),
+            ),
+            counters,
         }
     }
 
@@ -28,6 +49,12 @@ impl TransportWriter {
         self.inner.reset()
     }
 
+    /// time until which the link is occupied by frames that were already written, e.g. because
+    /// pacing delays their transmission
+    pub(crate) fn busy_until(&self) -> Option<Instant> {
+        self.inner.busy_until()
+    }
+
     pub(crate) async fn write(
         &mut self,
         io: &mut PhysLayer,
@@ -35,10 +62,18 @@ impl TransportWriter {
         destination: AnyAddress,
         fragment: &[u8],
     ) -> Result<(), LinkError> {
-        if level.application.enabled() {
+        self.counters
+            .decode()
+            .emit_wire(WireLayer::Application, DecodeDirection::Tx, fragment);
+        if level.application.enabled() || self.counters.decode().fragments_enabled() {
             if let Ok(fragment) = ParsedFragment::parse(fragment) {
-                let x: FragmentDisplay = fragment.display(level.application);
-                tracing::info!("APP TX - {}", x);
+                if level.application.enabled() {
+                    let x: FragmentDisplay = fragment.display(level.application);
+                    self.counters.decode().emit(format_args!("APP TX - {}", x));
+                }
+                self.counters
+                    .decode()
+                    .emit_fragment(DecodeDirection::Tx, &fragment);
             }
         }
         self.inner.write(io, level, destination, fragment).await
@@ -0,0 +1,185 @@
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::Instrument;
+
+use crate::app::{DisconnectReason, Listener, Shutdown, Spawner};
+use crate::link::statistics::LinkCounters;
+use crate::link::LinkErrorMode;
+use crate::master::session::{MasterSession, RunError, StateChange};
+use crate::master::{MasterChannel, MasterChannelConfig};
+use crate::tokio::net::UdpSocket;
+use crate::transport::TransportReader;
+use crate::transport::TransportWriter;
+use crate::udp::{UdpEndpointMap, UdpLayer, UdpState};
+use crate::util::phys::PhysLayer;
+
+/// Spawn a master task onto the `Tokio` runtime. The task runs until the returned handle, and any
+/// `AssociationHandle` created from it, are dropped.
+///
+/// The socket is bound to `local`, and each outstation is reached at the socket address it is
+/// mapped to in `endpoints`.
+///
+/// **Note**: This function may only be called from within the runtime itself, and panics otherwise.
+/// It is preferable to use this method instead of `create(..)` when using `[tokio::main]`.
+pub fn spawn_master_udp(
+    config: MasterChannelConfig,
+    local: SocketAddr,
+    endpoints: UdpEndpointMap,
+    retry_delay: Duration,
+    listener: Box<dyn Listener<UdpState>>,
+) -> MasterChannel {
+    let spawner = config.spawner.clone();
+    let (future, handle) = create_master_udp(config, local, endpoints, retry_delay, listener);
+    Spawner::spawn(spawner.as_deref(), future);
+    handle
+}
+
+/// Create a master future, which can be spawned onto a runtime, along with a controlling handle.
+///
+/// Once spawned or otherwise executed using the `run` method, the task runs until the handle
+/// and any `AssociationHandle` created from it are dropped.
+///
+/// **Note**: This function is required instead of `spawn` when using a runtime to directly spawn
+/// tasks instead of within the context of a runtime, e.g. in applications that cannot use
+/// `[tokio::main]` such as C language bindings.
+pub fn create_master_udp(
+    config: MasterChannelConfig,
+    local: SocketAddr,
+    endpoints: UdpEndpointMap,
+    retry_delay: Duration,
+    listener: Box<dyn Listener<UdpState>>,
+) -> (impl Future<Output = ()> + 'static, MasterChannel) {
+    let tracing_config = config.tracing;
+    let (mut task, handle) = MasterTask::new(local, endpoints, config, retry_delay, listener);
+    let future = async move {
+        let span =
+            tracing_config.channel_span(tracing::info_span!("DNP3-Master-UDP", "local" = ?local));
+        task.run().instrument(span).await;
+    };
+    (future, handle)
+}
+
+struct MasterTask {
+    local: SocketAddr,
+    endpoints: UdpEndpointMap,
+    retry_delay: Duration,
+    session: MasterSession,
+    reader: TransportReader,
+    writer: TransportWriter,
+    listener: Box<dyn Listener<UdpState>>,
+}
+
+impl MasterTask {
+    fn new(
+        local: SocketAddr,
+        endpoints: UdpEndpointMap,
+        config: MasterChannelConfig,
+        retry_delay: Duration,
+        listener: Box<dyn Listener<UdpState>>,
+    ) -> (Self, MasterChannel) {
+        let (tx, rx) = crate::util::channel::request_channel();
+        let link_counters = Arc::new(LinkCounters::new(
+            config.clock,
+            config.tracing,
+            config.buffer_pool,
+        ));
+        let session = MasterSession::new(
+            false,
+            config.decode_level,
+            config.response_timeout,
+            config.scheduling,
+            config.link,
+            link_counters.clone(),
+            config.tracing,
+            config.tx_buffer_size,
+            config.rx_buffer_size,
+            rx,
+        );
+        let (reader, writer) = crate::transport::create_master_transport_layer(
+            // datagrams may be lost or reordered, so link parsing errors are always discarded
+            LinkErrorMode::Discard,
+            config.master_address,
+            config.rx_buffer_size,
+            config.link,
+            config.response_timeout.into(),
+            config.transport,
+            config.unknown_destination,
+            link_counters.clone(),
+        );
+        let task = Self {
+            local,
+            endpoints,
+            retry_delay,
+            session,
+            reader,
+            writer,
+            listener,
+        };
+        (task, MasterChannel::new(tx, link_counters))
+    }
+
+    async fn run(&mut self) {
+        let _ = self.run_impl().await;
+        self.session.shutdown().await;
+        self.listener.update(UdpState::Shutdown);
+    }
+
+    async fn run_impl(&mut self) -> Result<(), Shutdown> {
+        loop {
+            self.listener.update(UdpState::Disabled);
+            self.session.wait_for_enabled().await?;
+            if let Err(StateChange::Shutdown) = self.run_enabled().await {
+                return Err(Shutdown);
+            }
+        }
+    }
+
+    async fn run_enabled(&mut self) -> Result<(), StateChange> {
+        loop {
+            match UdpSocket::bind(self.local).await {
+                Err(err) => {
+                    tracing::warn!(
+                        "unable to bind {}: {} - waiting {} ms to retry",
+                        self.local,
+                        err,
+                        self.retry_delay.as_millis()
+                    );
+                    self.listener.update(UdpState::Wait(self.retry_delay));
+                    self.session.wait_for_retry(self.retry_delay).await?;
+                }
+                Ok(socket) => {
+                    tracing::info!("socket bound");
+                    self.listener.update(UdpState::Bound);
+                    let mut io =
+                        PhysLayer::Udp(Box::new(UdpLayer::new(socket, self.endpoints.clone())));
+                    match self
+                        .session
+                        .run(&mut io, &mut self.writer, &mut self.reader)
+                        .await
+                    {
+                        RunError::State(x) => {
+                            if x == StateChange::Disable {
+                                self.listener
+                                    .update(UdpState::Closed(DisconnectReason::Disabled));
+                            }
+                            return Err(x);
+                        }
+                        RunError::Link(err) => {
+                            tracing::warn!("socket error: {}", err);
+                            self.listener.update(UdpState::Closed(err.into()));
+                            tracing::info!(
+                                "waiting {} ms to re-bind",
+                                self.retry_delay.as_millis()
+                            );
+                            self.listener.update(UdpState::Wait(self.retry_delay));
+                            self.session.wait_for_retry(self.retry_delay).await?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
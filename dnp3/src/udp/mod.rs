@@ -0,0 +1,144 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+
+use crate::link::header::AnyAddress;
+use crate::link::EndpointAddress;
+use crate::tokio::net::UdpSocket;
+
+pub use master::*;
+
+mod master;
+
+/// Maps the link address of each remote device to the socket address it is reached at
+///
+/// Datagrams are only accepted from socket addresses in the map. Frames sent to a broadcast
+/// address are sent to every socket address in the map.
+#[derive(Clone, Debug, Default)]
+pub struct UdpEndpointMap {
+    endpoints: BTreeMap<EndpointAddress, SocketAddr>,
+}
+
+impl UdpEndpointMap {
+    /// Create an empty map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map a link address to a socket address, replacing any previous mapping
+    pub fn add(&mut self, address: EndpointAddress, remote: SocketAddr) {
+        self.endpoints.insert(address, remote);
+    }
+
+    /// Remove the mapping for a link address
+    pub fn remove(&mut self, address: EndpointAddress) {
+        self.endpoints.remove(&address);
+    }
+
+    fn contains(&self, remote: SocketAddr) -> bool {
+        self.endpoints.values().any(|x| *x == remote)
+    }
+}
+
+/// State of a UDP channel
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UdpState {
+    /// Disabled and idle until enabled
+    Disabled,
+    /// Waiting to bind the socket again
+    Wait(std::time::Duration),
+    /// Socket is bound
+    Bound,
+    /// Socket was closed for the specified reason
+    Closed(crate::app::DisconnectReason),
+    /// Task has been shut down
+    Shutdown,
+}
+
+/// A bound UDP socket carrying one link frame per datagram
+pub(crate) struct UdpLayer {
+    socket: UdpSocket,
+    endpoints: UdpEndpointMap,
+    // datagram that has not yet been fully consumed by the reader
+    begin: usize,
+    end: usize,
+    buffer: [u8; crate::link::constant::MAX_LINK_FRAME_LENGTH],
+}
+
+impl UdpLayer {
+    pub(crate) fn new(socket: UdpSocket, endpoints: UdpEndpointMap) -> Self {
+        Self {
+            socket,
+            endpoints,
+            begin: 0,
+            end: 0,
+            buffer: [0; crate::link::constant::MAX_LINK_FRAME_LENGTH],
+        }
+    }
+
+    pub(crate) async fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        while self.begin == self.end {
+            let (count, remote) = self.socket.recv_from(&mut self.buffer).await?;
+            if self.endpoints.contains(remote) {
+                self.begin = 0;
+                self.end = count;
+            } else {
+                tracing::warn!("ignoring datagram from unknown remote: {}", remote);
+            }
+        }
+
+        let count = buffer.len().min(self.end - self.begin);
+        buffer[..count].copy_from_slice(&self.buffer[self.begin..self.begin + count]);
+        self.begin += count;
+        Ok(count)
+    }
+
+    pub(crate) async fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        match destination(data) {
+            Some(AnyAddress::Endpoint(address)) => match self.endpoints.endpoints.get(&address) {
+                Some(remote) => {
+                    self.socket.send_to(data, remote).await?;
+                }
+                None => {
+                    tracing::warn!("no socket address for destination: {}", address);
+                }
+            },
+            Some(AnyAddress::Broadcast(_)) => {
+                for remote in self.endpoints.endpoints.values() {
+                    self.socket.send_to(data, remote).await?;
+                }
+            }
+            _ => {
+                tracing::warn!("no socket address for frame destination");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// destination address of an encoded link frame
+fn destination(frame: &[u8]) -> Option<AnyAddress> {
+    match frame.get(4..6) {
+        Some(&[low, high]) => Some(AnyAddress::from(u16::from_le_bytes([low, high]))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::link::test_data::*;
+    use crate::link::BroadcastConfirmMode;
+
+    #[test]
+    fn reads_destination_from_encoded_frame() {
+        assert_eq!(
+            destination(RESET_LINK.bytes),
+            Some(RESET_LINK.header.destination)
+        );
+        assert_eq!(
+            destination(&[0x05, 0x64, 0x05, 0xC0, 0xFF, 0xFF]),
+            Some(AnyAddress::Broadcast(BroadcastConfirmMode::Optional))
+        );
+        assert_eq!(destination(&[0x05, 0x64, 0x05]), None);
+    }
+}
@@ -1,31 +1,107 @@
+use std::sync::Arc;
+
+use crate::app::BufferPool;
 use crate::util::cursor::WriteCursor;
 
 pub(crate) struct Buffer {
-    inner: Box<[u8]>,
+    size: usize,
+    inner: Option<Box<[u8]>>,
+    // bytes kept while the storage is released to the pool
+    retained: Box<[u8]>,
+    pool: Option<Arc<BufferPool>>,
 }
 
 impl Buffer {
     pub(crate) fn new(size: usize) -> Self {
+        Self::pooled(size, None)
+    }
+
+    /// create a buffer whose storage is obtained from the pool when it is first written
+    pub(crate) fn pooled(size: usize, pool: Option<Arc<BufferPool>>) -> Self {
         Self {
-            inner: vec![0; size].into_boxed_slice(),
+            size,
+            inner: match pool {
+                Some(_) => None,
+                None => Some(vec![0; size].into_boxed_slice()),
+            },
+            retained: Box::new([]),
+            pool,
         }
     }
 
     pub(crate) fn write_cursor(&mut self) -> WriteCursor {
-        WriteCursor::new(self.inner.as_mut())
+        WriteCursor::new(self.storage())
     }
 
     pub(crate) fn get(&self, length: usize) -> Option<&[u8]> {
-        self.inner.get(0..length)
+        match &self.inner {
+            Some(inner) => inner.get(0..length),
+            None => self.retained.get(0..length),
+        }
+    }
+
+    /// return the storage to the pool, keeping the first `retain` bytes so that they can still be
+    /// read or written. Does nothing if the buffer isn't pooled.
+    pub(crate) fn release(&mut self, retain: usize) {
+        if let (Some(pool), Some(inner)) = (&self.pool, self.inner.take()) {
+            self.retained = inner.get(0..retain).unwrap_or(&inner[..]).into();
+            pool.release(inner);
+        }
     }
 
     #[cfg(test)]
     pub(crate) fn get_mut(&mut self, length: usize) -> Option<&mut [u8]> {
-        self.inner.get_mut(0..length)
+        self.storage().get_mut(0..length)
     }
 
     #[cfg(test)]
     pub(crate) fn len(&self) -> usize {
-        self.inner.len()
+        self.size
+    }
+
+    fn storage(&mut self) -> &mut [u8] {
+        if self.inner.is_none() {
+            if let Some(pool) = &self.pool {
+                let mut inner = pool.acquire(self.size);
+                inner[..self.retained.len()].copy_from_slice(&self.retained);
+                self.retained = Box::new([]);
+                self.inner = Some(inner);
+            }
+        }
+        self.inner.get_or_insert_with(Box::default)
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        if let (Some(pool), Some(inner)) = (&self.pool, self.inner.take()) {
+            pool.release(inner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keeps_retained_bytes_across_release() {
+        let pool = Arc::new(BufferPool::new(1024));
+        let mut buffer = Buffer::pooled(256, Some(pool.clone()));
+        assert_eq!(pool.statistics().in_use, 0);
+
+        buffer.write_cursor().write(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(pool.statistics().in_use, 1);
+
+        buffer.release(3);
+        assert_eq!(pool.statistics().in_use, 0);
+        assert_eq!(buffer.get(3), Some([1, 2, 3].as_ref()));
+
+        buffer.write_cursor().skip(3).unwrap();
+        assert_eq!(buffer.get(3), Some([1, 2, 3].as_ref()));
+        assert_eq!(pool.statistics().allocations, 1);
+
+        drop(buffer);
+        assert_eq!(pool.statistics().idle, 1);
     }
 }
@@ -19,7 +19,7 @@ impl<'a> ReadCursor<'a> {
 
     pub(crate) fn transaction<T, R, E>(&mut self, mut read: T) -> Result<R, E>
     where
-        T: FnMut(&mut ReadCursor) -> Result<R, E>,
+        T: FnMut(&mut ReadCursor<'a>) -> Result<R, E>,
     {
         let start = self.pos;
         let result = read(self);
@@ -34,6 +34,10 @@ impl<'a> ReadCursor<'a> {
         self.remaining() == 0
     }
 
+    pub(crate) fn peek_all(&self) -> &'a [u8] {
+        &self.input[self.pos..]
+    }
+
     pub(crate) fn read_all(&mut self) -> &'a [u8] {
         let ret = &self.input[self.pos..];
         self.pos += self.remaining();
@@ -1,18 +1,45 @@
 use std::fmt::Write;
 
+use crate::decode::PayloadKind;
+
 const BYTES_PER_DECODE_LINE: usize = 18;
 
-pub(crate) fn format_bytes(f: &mut std::fmt::Formatter, bytes: &[u8]) -> std::fmt::Result {
-    for chunk in bytes.chunks(BYTES_PER_DECODE_LINE) {
-        writeln!(f)?;
-        let mut first = true;
-        for byte in chunk {
-            if !first {
-                f.write_char(' ')?;
-            }
-            first = false;
-            write!(f, "{:02X?}", byte)?;
+pub(crate) fn format_bytes(
+    f: &mut std::fmt::Formatter,
+    kind: PayloadKind,
+    bytes: &[u8],
+) -> std::fmt::Result {
+    format_byte_iter(f, kind, bytes.iter().copied())
+}
+
+pub(crate) fn format_byte_iter(
+    f: &mut std::fmt::Formatter,
+    kind: PayloadKind,
+    bytes: impl Iterator<Item = u8>,
+) -> std::fmt::Result {
+    if !crate::decode::redaction_enabled() {
+        return write_byte_iter(f, bytes);
+    }
+
+    // the filter inspects contiguous bytes
+    let bytes: Vec<u8> = bytes.collect();
+    if crate::decode::is_redacted(kind, &bytes) {
+        return write!(f, "\n<{} bytes redacted>", bytes.len());
+    }
+    write_byte_iter(f, bytes.into_iter())
+}
+
+fn write_byte_iter(
+    f: &mut std::fmt::Formatter,
+    bytes: impl Iterator<Item = u8>,
+) -> std::fmt::Result {
+    for (count, byte) in bytes.enumerate() {
+        if count % BYTES_PER_DECODE_LINE == 0 {
+            writeln!(f)?;
+        } else {
+            f.write_char(' ')?;
         }
+        write!(f, "{:02X?}", byte)?;
     }
     Ok(())
 }
@@ -5,6 +5,7 @@ pub(crate) mod cursor;
 pub(crate) mod decode;
 pub(crate) mod future;
 pub(crate) mod phys;
+pub(crate) mod random;
 pub(crate) mod slice_ext;
 
 pub(crate) struct Smallest<T>
@@ -1,10 +1,15 @@
-use crate::decode::PhysDecodeLevel;
+use crate::decode::{PayloadKind, PhysDecodeLevel};
 use crate::tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 // encapsulates all possible physical layers as an enum
 pub(crate) enum PhysLayer {
+    #[cfg(feature = "tcp")]
     Tcp(crate::tokio::net::TcpStream),
-    Serial(tokio_serial::TTYPort),
+    #[cfg(feature = "serial")]
+    Serial(crate::serial::SerialPort),
+    #[cfg(feature = "udp")]
+    Udp(Box<crate::udp::UdpLayer>),
+    Custom(Box<dyn crate::custom::PhysicalLayer>),
     #[cfg(test)]
     Mock(tokio_mock::mock::test::io::MockIO),
 }
@@ -12,8 +17,13 @@ pub(crate) enum PhysLayer {
 impl std::fmt::Debug for PhysLayer {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
+            #[cfg(feature = "tcp")]
             PhysLayer::Tcp(_) => f.write_str("Tcp"),
+            #[cfg(feature = "serial")]
             PhysLayer::Serial(_) => f.write_str("Serial"),
+            #[cfg(feature = "udp")]
+            PhysLayer::Udp(_) => f.write_str("Udp"),
+            PhysLayer::Custom(_) => f.write_str("Custom"),
             #[cfg(test)]
             PhysLayer::Mock(_) => f.write_str("Mock"),
         }
@@ -21,43 +31,59 @@ impl std::fmt::Debug for PhysLayer {
 }
 
 impl PhysLayer {
-    pub(crate) async fn read(
-        &mut self,
-        buffer: &mut [u8],
-        level: PhysDecodeLevel,
-    ) -> Result<usize, std::io::Error> {
+    pub(crate) async fn read(&mut self, buffer: &mut [u8]) -> Result<usize, std::io::Error> {
         let length = match self {
+            #[cfg(feature = "tcp")]
             Self::Tcp(x) => x.read(buffer).await?,
+            #[cfg(feature = "serial")]
             Self::Serial(x) => x.read(buffer).await?,
+            #[cfg(feature = "udp")]
+            Self::Udp(x) => x.read(buffer).await?,
+            Self::Custom(x) => x.read(buffer).await?,
             #[cfg(test)]
             Self::Mock(x) => x.read(buffer).await?,
         };
 
-        if level.enabled() {
-            if let Some(x) = buffer.get(0..length) {
-                tracing::info!("PHYS RX - {}", PhysDisplay::new(level, x))
-            }
-        }
-
         Ok(length)
     }
 
-    pub(crate) async fn write(
-        &mut self,
-        data: &[u8],
-        level: PhysDecodeLevel,
-    ) -> Result<(), std::io::Error> {
-        if level.enabled() {
-            tracing::info!("PHYS TX - {}", PhysDisplay::new(level, data));
-        }
-
+    pub(crate) async fn write(&mut self, data: &[u8]) -> Result<(), std::io::Error> {
         match self {
+            #[cfg(feature = "tcp")]
             Self::Tcp(x) => x.write_all(data).await,
-            Self::Serial(x) => x.write_all(data).await,
+            #[cfg(feature = "serial")]
+            Self::Serial(x) => x.write(data).await,
+            #[cfg(feature = "udp")]
+            Self::Udp(x) => x.write(data).await,
+            Self::Custom(x) => x.write_all(data).await,
             #[cfg(test)]
             Self::Mock(x) => x.write_all(data).await,
         }
     }
+
+    /// true if consecutive frames may be passed to a single write
+    ///
+    /// Datagram layers preserve the boundaries between writes and the simulated layers apply
+    /// their impairments to each write, so only the byte streams coalesce frames. Serial ports
+    /// configured with a frame timing wait in between the frames.
+    pub(crate) fn coalesces_writes(&self) -> bool {
+        match self {
+            #[cfg(feature = "tcp")]
+            Self::Tcp(_) => true,
+            #[cfg(feature = "serial")]
+            Self::Serial(x) => x.coalesces_writes(),
+            _ => false,
+        }
+    }
+
+    /// gracefully close user-provided layers, the others are closed when dropped
+    pub(crate) async fn close(&mut self) {
+        if let Self::Custom(x) = self {
+            if let Err(err) = x.shutdown().await {
+                tracing::warn!("error closing physical layer: {}", err);
+            }
+        }
+    }
 }
 
 pub(crate) struct PhysDisplay<'a> {
@@ -75,7 +101,7 @@ impl<'a> std::fmt::Display for PhysDisplay<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{} bytes", self.data.len())?;
         if self.level.data_enabled() {
-            crate::util::decode::format_bytes(f, self.data)?;
+            crate::util::decode::format_bytes(f, PayloadKind::Physical, self.data)?;
         }
         Ok(())
     }
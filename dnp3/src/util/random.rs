@@ -0,0 +1,61 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// small xorshift pseudo-random generator for jitter and simulated faults, not for cryptography
+#[derive(Clone, Debug)]
+pub(crate) struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    /// generator seeded differently for every instance
+    pub(crate) fn new() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        // the keys of the standard hasher are random without reading the system time, which
+        // isn't available on wasm32-unknown-unknown
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        Self::seeded(hasher.finish())
+    }
+
+    /// generator whose sequence is entirely determined by the seed
+    pub(crate) fn seeded(seed: u64) -> Self {
+        // must never be seeded with zero
+        Self { state: seed | 1 }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// uniformly distributed in the range [0.0, 1.0)
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_seed_produces_same_sequence() {
+        let mut first = XorShift::seeded(42);
+        let mut second = XorShift::seeded(42);
+        for _ in 0..100 {
+            assert_eq!(first.next_u64(), second.next_u64());
+        }
+    }
+
+    #[test]
+    fn zero_seed_does_not_get_stuck() {
+        let mut rng = XorShift::seeded(0);
+        assert_ne!(rng.next_u64(), 0);
+        let value = rng.next_f64();
+        assert!((0.0..1.0).contains(&value));
+    }
+}
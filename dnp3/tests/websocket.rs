@@ -0,0 +1,163 @@
+//! Runs a master over the WebSocket physical layer on `wasm32-unknown-unknown`, with the time
+//! and the delays of the browser, against a fake WebSocket that answers like an outstation.
+//!
+//! Run it with `wasm-bindgen-test-runner` as the runner of the target:
+//!
+//! ```text
+//! CARGO_TARGET_WASM32_UNKNOWN_UNKNOWN_RUNNER=wasm-bindgen-test-runner \
+//!     cargo test --target wasm32-unknown-unknown --no-default-features --features websocket --test websocket
+//! ```
+#![cfg(all(target_arch = "wasm32", feature = "websocket"))]
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, SystemTime};
+
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use dnp3::app::{Clock, Spawner, Timer, TimerFuture};
+use dnp3::custom::{spawn_master_custom, WebSocketLayer};
+use dnp3::link::{EndpointAddress, LinkErrorMode};
+use dnp3::master::*;
+
+#[wasm_bindgen(inline_js = r#"
+function crc(bytes) {
+    let crc = 0;
+    for (const b of bytes) {
+        crc ^= b;
+        for (let i = 0; i < 8; i++) {
+            crc = (crc & 1) ? ((crc >>> 1) ^ 0xA6BC) : (crc >>> 1);
+        }
+    }
+    crc = ~crc & 0xFFFF;
+    return [crc & 0xFF, crc >>> 8];
+}
+
+function withCrc(bytes) {
+    return bytes.concat(crc(bytes));
+}
+
+// WebSocket that answers every request with an empty response from outstation 1024 to master 1
+export function install_outstation() {
+    globalThis.WebSocket = class {
+        constructor(url) {
+            this.url = url;
+            this.binaryType = "blob";
+            setTimeout(() => this.onopen && this.onopen({}), 0);
+        }
+
+        send(request) {
+            // the application control field follows the link header and the transport header
+            const seq = request[11] & 0x0F;
+            const frame = withCrc([0x05, 0x64, 0x0A, 0x44, 0x01, 0x00, 0x00, 0x04])
+                .concat(withCrc([0xC0, 0xC0 | seq, 0x81, 0x00, 0x00]));
+            const data = new Uint8Array(frame).buffer;
+            setTimeout(() => this.onmessage && this.onmessage({ data }), 0);
+        }
+
+        close() {}
+    };
+}
+
+export function set_timeout(callback, ms) {
+    setTimeout(callback, ms);
+}
+
+export function performance_now() {
+    return performance.now();
+}
+
+export function date_now() {
+    return Date.now();
+}
+"#)]
+extern "C" {
+    fn install_outstation();
+    fn set_timeout(callback: &JsValue, ms: f64);
+    fn performance_now() -> f64;
+    fn date_now() -> f64;
+}
+
+#[derive(Default)]
+struct DelayState {
+    expired: bool,
+    waker: Option<Waker>,
+}
+
+// completed by a callback of setTimeout, which holds the only non-Send part
+struct Delay {
+    state: Arc<Mutex<DelayState>>,
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.expired {
+            return Poll::Ready(());
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+fn delay(duration: Duration) -> TimerFuture {
+    let state = Arc::new(Mutex::new(DelayState::default()));
+    let expired = state.clone();
+    let callback = Closure::once_into_js(move || {
+        let mut state = expired.lock().unwrap();
+        state.expired = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    });
+    set_timeout(&callback, duration.as_secs_f64() * 1000.0);
+    Box::pin(Delay { state })
+}
+
+fn browser_clock() -> Clock {
+    Clock::custom(Arc::new(Timer::new(
+        delay,
+        || Duration::from_secs_f64(performance_now() / 1000.0),
+        || SystemTime::UNIX_EPOCH + Duration::from_secs_f64(date_now() / 1000.0),
+    )))
+}
+
+#[wasm_bindgen_test]
+async fn master_reads_over_websocket() {
+    install_outstation();
+    let layer = WebSocketLayer::connect("ws://localhost/dnp3")
+        .await
+        .unwrap();
+
+    let mut config = MasterChannelConfig::new(EndpointAddress::from(1).unwrap());
+    config.clock = browser_clock();
+    config.spawner = Some(Arc::new(Spawner::new(|task| {
+        wasm_bindgen_futures::spawn_local(task)
+    })));
+
+    let mut channel = spawn_master_custom(LinkErrorMode::Close, config, Box::new(layer));
+    let mut association = channel
+        .add_association(
+            EndpointAddress::from(1024).unwrap(),
+            AssociationConfig::quiet(),
+            NullReadHandler::boxed(),
+            DefaultAssociationHandler::boxed(),
+        )
+        .await
+        .unwrap();
+    channel.enable().await.unwrap();
+
+    association
+        .read(ReadRequest::class_scan(Classes::all()))
+        .await
+        .unwrap();
+
+    let statistics = channel.get_link_statistics();
+    assert_eq!(statistics.frames_rx, 1);
+}
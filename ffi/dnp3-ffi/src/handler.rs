@@ -100,6 +100,7 @@ impl From<ReadType> for ffi::ReadType {
             ReadType::StartupIntegrity => ffi::ReadType::StartupIntegrity,
             ReadType::PeriodicPoll => ffi::ReadType::PeriodicPoll,
             ReadType::SinglePoll => ffi::ReadType::SinglePoll,
+            ReadType::CommLost => ffi::ReadType::CommLost,
         }
     }
 }
@@ -1,8 +1,14 @@
 use std::ffi::CStr;
 use std::time::Duration;
 
-use dnp3::app::{ConnectStrategy, Listener, RetryStrategy, Timeout, Timestamp};
-use dnp3::link::{EndpointAddress, LinkStatusResult, SpecialAddressError};
+use dnp3::app::{
+    Clock, ConnectStrategy, Listener, ParseMode, RetryStrategy, Timeout, Timestamp, VendorObjects,
+};
+use dnp3::decode::TracingConfig;
+use dnp3::link::{
+    EndpointAddress, LinkConfig, LinkStatusResult, SpecialAddressError, TransportConfig,
+    UnknownDestinationPolicy,
+};
 use dnp3::master::*;
 use dnp3::serial::*;
 use dnp3::tcp::ClientState;
@@ -114,6 +120,7 @@ pub unsafe fn master_channel_add_association(
         disable_unsol_classes: convert_event_classes(&config.disable_unsol_classes()),
         enable_unsol_classes: convert_event_classes(&config.enable_unsol_classes()),
         startup_integrity_classes: convert_classes(&config.startup_integrity_classes()),
+        class_zero_request: ClassZeroRequest::Class0,
         auto_time_sync: convert_auto_time_sync(&config.auto_time_sync()),
         auto_tasks_retry_strategy: RetryStrategy::new(
             config.auto_tasks_retry_strategy.min_delay(),
@@ -129,6 +136,14 @@ pub unsafe fn master_channel_add_association(
             &config.event_scan_on_events_available(),
         ),
         max_queued_user_requests: config.max_queued_user_requests as usize,
+        queue_overflow_policy: QueueOverflowPolicy::RejectNew,
+        priority: 0,
+        response_timeout: None,
+        capture_unknown_objects: false,
+        vendor_objects: VendorObjects::new(),
+        parse_mode: ParseMode::Strict,
+        variation_fallback: VariationFallback::Disabled,
+        comm_lost_timeouts: None,
     };
 
     channel.runtime.block_on(channel.handle.add_association(
@@ -505,7 +520,9 @@ impl Listener<ClientState> for ffi::ClientStateListener {
         let value = match value {
             ClientState::Disabled => ffi::ClientState::Disabled,
             ClientState::Connecting => ffi::ClientState::Connecting,
-            ClientState::Connected => ffi::ClientState::Connected,
+            ClientState::Connected(_) => ffi::ClientState::Connected,
+            // the disconnection is followed by the state the client waits in
+            ClientState::Disconnected(_) => return,
             ClientState::WaitAfterFailedConnect(_) => ffi::ClientState::WaitAfterFailedConnect,
             ClientState::WaitAfterDisconnect(_) => ffi::ClientState::WaitAfterDisconnect,
             ClientState::Shutdown => ffi::ClientState::Shutdown,
@@ -520,6 +537,8 @@ impl Listener<PortState> for ffi::PortStateListener {
             PortState::Disabled => ffi::PortState::Disabled,
             PortState::Wait(_) => ffi::PortState::Wait,
             PortState::Open => ffi::PortState::Open,
+            // the closure is followed by the state the port waits in
+            PortState::Closed(_) => return,
             PortState::Shutdown => ffi::PortState::Shutdown,
         };
         self.on_change(value);
@@ -553,8 +572,16 @@ fn convert_config(
         master_address: address,
         decode_level: config.decode_level().clone().into(),
         response_timeout: Timeout::from_duration(config.response_timeout()).unwrap(),
+        scheduling: SchedulingConfig::default(),
+        link: LinkConfig::default(),
+        transport: TransportConfig::default(),
+        unknown_destination: UnknownDestinationPolicy::default(),
         tx_buffer_size: config.tx_buffer_size() as usize,
         rx_buffer_size: config.rx_buffer_size() as usize,
+        clock: Clock::default(),
+        tracing: TracingConfig::default(),
+        buffer_pool: None,
+        spawner: None,
     })
 }
 
@@ -588,6 +615,8 @@ impl From<ffi::SerialPortSettings> for dnp3::serial::SerialSettings {
                 ffi::StopBits::One => StopBits::One,
                 ffi::StopBits::Two => StopBits::Two,
             },
+            timing: SerialTiming::default(),
+            inter_character_timeout: None,
         }
     }
 }
@@ -640,20 +669,22 @@ macro_rules! define_task_from_impl {
             fn from(err: TaskError) -> Self {
                 match err {
                     TaskError::TooManyRequests => ffi::$name::TooManyRequests,
+                    TaskError::Dropped => ffi::$name::TooManyRequests,
                     TaskError::Link(_) => ffi::$name::NoConnection,
-                    TaskError::Transport => ffi::$name::NoConnection,
+                    TaskError::Transport(_) => ffi::$name::NoConnection,
                     TaskError::MalformedResponse(_) => ffi::$name::BadResponse,
                     TaskError::UnexpectedResponseHeaders => ffi::$name::BadResponse,
-                    TaskError::NonFinWithoutCon => ffi::$name::BadResponse,
-                    TaskError::NeverReceivedFir => ffi::$name::BadResponse,
-                    TaskError::UnexpectedFir => ffi::$name::BadResponse,
-                    TaskError::MultiFragmentResponse => ffi::$name::BadResponse,
-                    TaskError::ResponseTimeout => ffi::$name::ResponseTimeout,
+                    TaskError::NonFinWithoutCon(_) => ffi::$name::BadResponse,
+                    TaskError::NeverReceivedFir(_) => ffi::$name::BadResponse,
+                    TaskError::UnexpectedFir(_) => ffi::$name::BadResponse,
+                    TaskError::MultiFragmentResponse(_) => ffi::$name::BadResponse,
+                    TaskError::ResponseTimeout(_) => ffi::$name::ResponseTimeout,
                     TaskError::WriteError => ffi::$name::WriteError,
                     TaskError::NoSuchAssociation(_) => ffi::$name::AssociationRemoved,
                     TaskError::NoConnection => ffi::$name::NoConnection,
                     TaskError::Shutdown => ffi::$name::Shutdown,
                     TaskError::Disabled => ffi::$name::NoConnection,
+                    TaskError::Preempted => ffi::$name::NoConnection,
                 }
             }
         }
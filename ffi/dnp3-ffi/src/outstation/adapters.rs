@@ -1,5 +1,6 @@
 use dnp3::app::control::*;
 use dnp3::app::*;
+use dnp3::link::BroadcastConfirmMode;
 use dnp3::outstation::database::Database;
 use dnp3::outstation::*;
 
@@ -97,7 +98,12 @@ impl OutstationInformation for ffi::OutstationInformation {
         ffi::OutstationInformation::process_request_from_idle(self, header.into());
     }
 
-    fn broadcast_received(&mut self, function: FunctionCode, action: BroadcastAction) {
+    fn broadcast_received(
+        &mut self,
+        function: FunctionCode,
+        action: BroadcastAction,
+        _mode: BroadcastConfirmMode,
+    ) {
         ffi::OutstationInformation::broadcast_received(self, function.into(), action.into());
     }
 
@@ -394,8 +400,11 @@ impl From<FunctionCode> for ffi::FunctionCode {
             FunctionCode::GetFileInfo => ffi::FunctionCode::GetFileInfo,
             FunctionCode::AuthenticateFile => ffi::FunctionCode::AuthenticateFile,
             FunctionCode::AbortFile => ffi::FunctionCode::AbortFile,
+            FunctionCode::AuthRequest => ffi::FunctionCode::AuthRequest,
+            FunctionCode::AuthRequestNoAck => ffi::FunctionCode::AuthRequestNoAck,
             FunctionCode::Response => ffi::FunctionCode::Response,
             FunctionCode::UnsolicitedResponse => ffi::FunctionCode::UnsolicitedResponse,
+            FunctionCode::AuthResponse => ffi::FunctionCode::AuthResponse,
         }
     }
 }
@@ -3,10 +3,16 @@ use std::net::AddrParseError;
 use std::time::Duration;
 
 pub use database::*;
-use dnp3::app::Listener;
-use dnp3::link::{EndpointAddress, LinkErrorMode};
-use dnp3::outstation::database::{ClassZeroConfig, EventBufferConfig};
-use dnp3::outstation::{BufferSize, ConnectionState, Feature, Features, OutstationConfig};
+use dnp3::app::{Clock, Listener};
+use dnp3::decode::TracingConfig;
+use dnp3::link::{
+    EndpointAddress, LinkConfig, LinkErrorMode, TransportConfig, UnknownDestinationPolicy,
+};
+use dnp3::outstation::database::{ClassZeroConfig, EventBufferConfig, StorageConfig};
+use dnp3::outstation::{
+    BroadcastAddresses, BufferSize, ConnectionState, DuplicateDetection, Feature, Features,
+    FunctionCodes, OutstationConfig,
+};
 use dnp3::outstation::{BufferSizeError, OutstationHandle};
 use dnp3::tcp::{FilterError, ServerHandle};
 pub use struct_constructors::*;
@@ -205,13 +211,30 @@ fn convert_outstation_config(
         decode_level: config.decode_level().clone().into(),
         confirm_timeout: config.confirm_timeout(),
         select_timeout: config.select_timeout(),
+        max_concurrent_selects: OutstationConfig::DEFAULT_MAX_CONCURRENT_SELECTS,
         features: config.features().into(),
+        functions: FunctionCodes::default(),
+        broadcast_addresses: BroadcastAddresses::default(),
         max_unsolicited_retries: Some(config.max_unsolicited_retries() as usize),
         unsolicited_retry_delay: config.unsolicited_retry_delay(),
         keep_alive_timeout,
+        link: LinkConfig::default(),
+        transport: TransportConfig::default(),
+        unknown_destination: UnknownDestinationPolicy::default(),
         class_zero: config.class_zero.into(),
+        storage: StorageConfig::default(),
         max_read_request_headers: Some(config.max_read_request_headers),
         max_controls_per_request: Some(config.max_controls_per_request),
+        control_completion_timeout: OutstationConfig::DEFAULT_CONTROL_COMPLETION_TIMEOUT,
+        max_events_per_solicited_response: None,
+        max_events_per_unsolicited_response: None,
+        change_coalescing_delay: None,
+        duplicate_detection: DuplicateDetection::default(),
+        clock: Clock::default(),
+        tracing: TracingConfig::default(),
+        buffer_pool: None,
+        spawner: None,
+        audit_logger: None,
     })
 }
 
@@ -224,8 +247,8 @@ impl Listener<ConnectionState> for ffi::ConnectionStateListener {
 impl From<ConnectionState> for ffi::ConnectionState {
     fn from(x: ConnectionState) -> Self {
         match x {
-            ConnectionState::Connected => ffi::ConnectionState::Connected,
-            ConnectionState::Disconnected => ffi::ConnectionState::Disconnected,
+            ConnectionState::Connected(_) => ffi::ConnectionState::Connected,
+            _ => ffi::ConnectionState::Disconnected,
         }
     }
 }
@@ -258,6 +281,7 @@ impl From<ffi::ClassZeroConfig> for ClassZeroConfig {
             analog: from.analog(),
             analog_output_status: from.analog_output_status(),
             octet_strings: from.octet_strings(),
+            unsigned_integers: from.unsigned_integers(),
         }
     }
 }
@@ -273,6 +297,7 @@ impl From<ffi::EventBufferConfig> for EventBufferConfig {
             max_analog: from.max_analog(),
             max_analog_output_status: from.max_analog_output_status(),
             max_octet_string: from.max_octet_string(),
+            max_unsigned_integer: from.max_unsigned_integer(),
         }
     }
 }
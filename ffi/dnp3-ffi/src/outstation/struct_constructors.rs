@@ -102,6 +102,7 @@ impl From<EventBufferConfig> for ffi::EventBufferConfig {
             max_analog: from.max_analog,
             max_analog_output_status: from.max_analog_output_status,
             max_octet_string: from.max_octet_string,
+            max_unsigned_integer: from.max_unsigned_integer,
         }
         .into()
     }
@@ -89,6 +89,10 @@ pub unsafe fn request_add_all_objects_header(request: *mut Request, variation: f
 impl From<ffi::Variation> for Variation {
     fn from(from: ffi::Variation) -> Variation {
         match from {
+            // the variation of a specific attribute can't be expressed, so all of them are read
+            ffi::Variation::Group0 => Variation::Group0Var254,
+            ffi::Variation::Group0Var254 => Variation::Group0Var254,
+            ffi::Variation::Group0Var255 => Variation::Group0Var255,
             ffi::Variation::Group1Var0 => Variation::Group1Var0,
             ffi::Variation::Group1Var1 => Variation::Group1Var1,
             ffi::Variation::Group1Var2 => Variation::Group1Var2,
@@ -192,8 +196,26 @@ impl From<ffi::Variation> for Variation {
             ffi::Variation::Group60Var3 => Variation::Group60Var3,
             ffi::Variation::Group60Var4 => Variation::Group60Var4,
             ffi::Variation::Group80Var1 => Variation::Group80Var1,
+            ffi::Variation::Group90Var1 => Variation::Group90Var1,
+            ffi::Variation::Group102Var0 => Variation::Group102Var0,
+            ffi::Variation::Group102Var1 => Variation::Group102Var1,
             ffi::Variation::Group110 => Variation::Group110(0),
             ffi::Variation::Group111 => Variation::Group111(0),
+            ffi::Variation::Group120Var1 => Variation::Group120Var1,
+            ffi::Variation::Group120Var2 => Variation::Group120Var2,
+            ffi::Variation::Group120Var3 => Variation::Group120Var3,
+            ffi::Variation::Group120Var4 => Variation::Group120Var4,
+            ffi::Variation::Group120Var5 => Variation::Group120Var5,
+            ffi::Variation::Group120Var6 => Variation::Group120Var6,
+            ffi::Variation::Group120Var7 => Variation::Group120Var7,
+            ffi::Variation::Group120Var8 => Variation::Group120Var8,
+            ffi::Variation::Group120Var9 => Variation::Group120Var9,
+            ffi::Variation::Group120Var10 => Variation::Group120Var10,
+            ffi::Variation::Group120Var11 => Variation::Group120Var11,
+            ffi::Variation::Group120Var12 => Variation::Group120Var12,
+            ffi::Variation::Group120Var13 => Variation::Group120Var13,
+            ffi::Variation::Group120Var14 => Variation::Group120Var14,
+            ffi::Variation::Group120Var15 => Variation::Group120Var15,
             /*
             ffi::Variation::Group112 => Variation::Group112(0),
             ffi::Variation::Group113 => Variation::Group113(0),
@@ -205,6 +227,9 @@ impl From<ffi::Variation> for Variation {
 impl From<Variation> for ffi::Variation {
     fn from(from: Variation) -> ffi::Variation {
         match from {
+            Variation::Group0(_) => ffi::Variation::Group0,
+            Variation::Group0Var254 => ffi::Variation::Group0Var254,
+            Variation::Group0Var255 => ffi::Variation::Group0Var255,
             Variation::Group1Var0 => ffi::Variation::Group1Var0,
             Variation::Group1Var1 => ffi::Variation::Group1Var1,
             Variation::Group1Var2 => ffi::Variation::Group1Var2,
@@ -310,8 +335,26 @@ impl From<Variation> for ffi::Variation {
             Variation::Group60Var3 => ffi::Variation::Group60Var3,
             Variation::Group60Var4 => ffi::Variation::Group60Var4,
             Variation::Group80Var1 => ffi::Variation::Group80Var1,
+            Variation::Group90Var1 => ffi::Variation::Group90Var1,
+            Variation::Group102Var0 => ffi::Variation::Group102Var0,
+            Variation::Group102Var1 => ffi::Variation::Group102Var1,
             Variation::Group110(_) => ffi::Variation::Group110,
             Variation::Group111(_) => ffi::Variation::Group111,
+            Variation::Group120Var1 => ffi::Variation::Group120Var1,
+            Variation::Group120Var2 => ffi::Variation::Group120Var2,
+            Variation::Group120Var3 => ffi::Variation::Group120Var3,
+            Variation::Group120Var4 => ffi::Variation::Group120Var4,
+            Variation::Group120Var5 => ffi::Variation::Group120Var5,
+            Variation::Group120Var6 => ffi::Variation::Group120Var6,
+            Variation::Group120Var7 => ffi::Variation::Group120Var7,
+            Variation::Group120Var8 => ffi::Variation::Group120Var8,
+            Variation::Group120Var9 => ffi::Variation::Group120Var9,
+            Variation::Group120Var10 => ffi::Variation::Group120Var10,
+            Variation::Group120Var11 => ffi::Variation::Group120Var11,
+            Variation::Group120Var12 => ffi::Variation::Group120Var12,
+            Variation::Group120Var13 => ffi::Variation::Group120Var13,
+            Variation::Group120Var14 => ffi::Variation::Group120Var14,
+            Variation::Group120Var15 => ffi::Variation::Group120Var15,
             /*
             Variation::Group112(_) => ffi::Variation::Group112,
             Variation::Group113(_) => ffi::Variation::Group113,
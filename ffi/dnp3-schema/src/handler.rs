@@ -329,6 +329,10 @@ fn define_read_type_enum(lib: &mut LibraryBuilder) -> Result<NativeEnumHandle, B
         .push("Unsolicited", "Unsolicited message")?
         .push("SinglePoll", "Single poll requested by the user")?
         .push("PeriodicPoll", "Periodic poll configured by the user")?
+        .push(
+            "CommLost",
+            "Last values of the points reported by the master when communication with the outstation is lost or restored",
+        )?
         .doc("Describes the source of a read event")?
         .build()
 }
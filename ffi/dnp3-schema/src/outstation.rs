@@ -287,6 +287,11 @@ fn define_outstation_config(
             doc("Include Binary Inputs in Class 0 reads")
                 .warning("For conformance, this should be false."),
         )?
+        .add(
+            "unsigned_integers",
+            StructElementType::Bool(Some(true)),
+            "Include Unsigned Integers in Class 0 reads",
+        )?
         .doc("Controls which types are reported during a Class 0 read.")?
         .build()?;
 
@@ -422,6 +427,11 @@ fn define_event_buffer_config(
             Type::Uint16,
             doc("Maximum number of Octet String events (g111)"),
         )?
+        .add(
+            "max_unsigned_integer",
+            Type::Uint16,
+            "Maximum number of Unsigned Integer events (g102)",
+        )?
         .doc(
             doc("Maximum number of events for each type")
                 .details("A value of zero means that events will not be buffered for that type."),
@@ -991,8 +1001,11 @@ fn define_function_code(lib: &mut LibraryBuilder) -> Result<NativeEnumHandle, Bi
         .push("GetFileInfo", "Outstation shall retrieve information about a file (value == 28)")?
         .push("AuthenticateFile", "Outstation shall return a file authentication key (value == 29)")?
         .push("AbortFile", "Outstation shall abort a file transfer operation (value == 30)")?
+        .push("AuthRequest", "Master or outstation shall process the secure authentication objects in the request (value == 32)")?
+        .push("AuthRequestNoAck", "Same as AuthRequest but the receiver shall not send a response (value == 33)")?
         .push("Response", "Master shall interpret this fragment as an Application Layer response to an ApplicationLayer request (value == 129)")?
         .push("UnsolicitedResponse", "Master shall interpret this fragment as an unsolicited response that was not prompted by an explicit request (value == 130)")?
+        .push("AuthResponse", "Master shall interpret this fragment as a secure authentication response (value == 131)")?
         .doc("Application layer function code")?
         .build()
 }
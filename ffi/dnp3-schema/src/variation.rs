@@ -5,6 +5,18 @@ pub(crate) fn define(
     lib: &mut LibraryBuilder,
 ) -> std::result::Result<NativeEnumHandle, BindingError> {
     lib.define_native_enum("Variation")?
+        .push(
+            "Group0",
+            "Device Attributes - Specific attribute, requested as all attributes",
+        )?
+        .push(
+            "Group0Var254",
+            "Device Attributes - Non-specific all attributes request",
+        )?
+        .push(
+            "Group0Var255",
+            "Device Attributes - List of attribute variations",
+        )?
         .push("Group1Var0", "Binary Input - Default variation")?
         .push("Group1Var1", "Binary Input - Packed format")?
         .push("Group1Var2", "Binary Input - With flags")?
@@ -209,8 +221,41 @@ pub(crate) fn define(
         .push("Group60Var3", "Class objects - Class 2 data")?
         .push("Group60Var4", "Class objects - Class 3 data")?
         .push("Group80Var1", "Internal Indications - Packed format")?
+        .push("Group90Var1", "Application - Identifier")?
+        .push("Group102Var0", "Unsigned Integer - Any Variation")?
+        .push("Group102Var1", "Unsigned Integer - 8-bit")?
         .push("Group110", "Octet String")?
         .push("Group111", "Octet String Event")?
+        .push("Group120Var1", "Authentication - Challenge")?
+        .push("Group120Var2", "Authentication - Reply")?
+        .push("Group120Var3", "Authentication - Aggressive Mode Request")?
+        .push(
+            "Group120Var4",
+            "Authentication - Session Key Status Request",
+        )?
+        .push("Group120Var5", "Authentication - Session Key Status")?
+        .push("Group120Var6", "Authentication - Session Key Change")?
+        .push("Group120Var7", "Authentication - Error")?
+        .push("Group120Var8", "Authentication - User Certificate")?
+        .push(
+            "Group120Var9",
+            "Authentication - Message Authentication Code",
+        )?
+        .push("Group120Var10", "Authentication - User Status Change")?
+        .push(
+            "Group120Var11",
+            "Authentication - Update Key Change Request",
+        )?
+        .push("Group120Var12", "Authentication - Update Key Change Reply")?
+        .push("Group120Var13", "Authentication - Update Key Change")?
+        .push(
+            "Group120Var14",
+            "Authentication - Update Key Change Signature",
+        )?
+        .push(
+            "Group120Var15",
+            "Authentication - Update Key Change Confirmation",
+        )?
         /*
         .push("Group112", "Virtual Terminal Output Block")?
         .push("Group113", "Virtual Terminal Event Data")?